@@ -84,6 +84,7 @@ impl TargetIsa for X64Backend {
             buffer,
             frame_size,
             vcode: emit_result.disasm,
+            vcode_offsets: emit_result.disasm_offsets,
             value_labels_ranges,
             sized_stackslot_offsets,
             dynamic_stackslot_offsets,
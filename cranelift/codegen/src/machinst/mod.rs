@@ -333,6 +333,9 @@ pub struct CompiledCodeBase<T: CompilePhase> {
     pub frame_size: u32,
     /// Disassembly, if requested.
     pub vcode: Option<String>,
+    /// The code offset of the instruction on each line of `vcode`, or `None`
+    /// for lines without an instruction such as block labels.
+    pub vcode_offsets: Vec<Option<CodeOffset>>,
     /// Debug info: value labels to registers/stackslots at code offsets.
     pub value_labels_ranges: ValueLabelsRanges,
     /// Debug info: stackslots to stack pointer offsets.
@@ -360,6 +363,7 @@ impl CompiledCodeStencil {
             buffer: self.buffer.apply_base_srcloc(params.base_srcloc()),
             frame_size: self.frame_size,
             vcode: self.vcode,
+            vcode_offsets: self.vcode_offsets,
             value_labels_ranges: self.value_labels_ranges,
             sized_stackslot_offsets: self.sized_stackslot_offsets,
             dynamic_stackslot_offsets: self.dynamic_stackslot_offsets,
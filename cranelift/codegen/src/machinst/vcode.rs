@@ -202,6 +202,11 @@ pub struct EmitResult {
     /// epilogue(s), and makes use of the regalloc results.
     pub disasm: Option<String>,
 
+    /// The code offset of the instruction printed on each line of `disasm`,
+    /// or `None` for lines that don't print an instruction such as block
+    /// labels. Empty if no disassembly was requested.
+    pub disasm_offsets: Vec<Option<CodeOffset>>,
+
     /// Offsets of sized stackslots.
     pub sized_stackslot_offsets: PrimaryMap<StackSlot, u32>,
 
@@ -785,7 +790,8 @@ impl<I: VCodeInst> VCode<I> {
         let mut inst_offsets = vec![];
         let mut state = I::State::new(&self.abi, std::mem::take(ctrl_plane));
 
-        let mut disasm = String::new();
+        // The disassembly text along with the code offset of each of its lines.
+        let mut disasm = (String::new(), Vec::new());
 
         if !self.debug_value_labels.is_empty() {
             inst_offsets.resize(self.insts.len(), NO_INST_OFFSET);
@@ -831,12 +837,17 @@ impl<I: VCodeInst> VCode<I> {
 
             let do_emit = |inst: &I,
                            allocs: &[Allocation],
-                           disasm: &mut String,
+                           disasm: &mut (String, Vec<Option<CodeOffset>>),
                            buffer: &mut MachBuffer<I>,
                            state: &mut I::State| {
                 if want_disasm && !inst.is_args() {
                     let mut s = state.clone();
-                    writeln!(disasm, "  {}", inst.pretty_print_inst(allocs, &mut s)).unwrap();
+                    let text = inst.pretty_print_inst(allocs, &mut s);
+                    writeln!(disasm.0, "  {}", text).unwrap();
+                    let offset = Some(buffer.cur_offset());
+                    disasm
+                        .1
+                        .extend((0..text.lines().count().max(1)).map(|_| offset));
                 }
                 inst.emit(allocs, buffer, &self.emit_info, state);
             };
@@ -857,7 +868,8 @@ impl<I: VCodeInst> VCode<I> {
             buffer.bind_label(MachLabel::from_block(block), state.ctrl_plane_mut());
 
             if want_disasm {
-                writeln!(&mut disasm, "block{}:", block.index()).unwrap();
+                writeln!(&mut disasm.0, "block{}:", block.index()).unwrap();
+                disasm.1.push(None);
             }
 
             if flags.machine_code_cfg_info() {
@@ -1092,7 +1104,8 @@ impl<I: VCodeInst> VCode<I> {
             bb_offsets,
             bb_edges,
             func_body_len,
-            disasm: if want_disasm { Some(disasm) } else { None },
+            disasm: if want_disasm { Some(disasm.0) } else { None },
+            disasm_offsets: disasm.1,
             sized_stackslot_offsets: self.abi.sized_stackslot_offsets().clone(),
             dynamic_stackslot_offsets: self.abi.dynamic_stackslot_offsets().clone(),
             value_labels_ranges,
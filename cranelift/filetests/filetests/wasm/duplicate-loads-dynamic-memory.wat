@@ -48,10 +48,9 @@
 ;;     gv2 = load.i64 notrap aligned gv0
 ;;
 ;;                                 block0(v0: i32, v1: i64):
+;;                                     v18 -> v1
+;;                                     v19 -> v1
 ;;                                     v20 -> v1
-;;                                     v21 -> v1
-;;                                     v22 -> v1
-;;                                     v23 -> v1
 ;; @0057                               v5 = load.i64 notrap aligned v1+8
 ;; @0057                               v7 = load.i64 notrap aligned v1
 ;; @0057                               v4 = uextend.i64 v0
@@ -73,10 +72,9 @@
 ;;     gv2 = load.i64 notrap aligned gv0
 ;;
 ;;                                 block0(v0: i32, v1: i64):
+;;                                     v22 -> v1
+;;                                     v23 -> v1
 ;;                                     v24 -> v1
-;;                                     v25 -> v1
-;;                                     v26 -> v1
-;;                                     v27 -> v1
 ;; @0064                               v5 = load.i64 notrap aligned v1+8
 ;; @0064                               v7 = load.i64 notrap aligned v1
 ;; @0064                               v4 = uextend.i64 v0
@@ -61,12 +61,11 @@
 ;;     gv2 = load.i64 notrap aligned gv0
 ;;
 ;;                                 block0(v0: i32, v1: i64):
+;;                                     v33 -> v1
+;;                                     v34 -> v1
 ;;                                     v35 -> v1
 ;;                                     v36 -> v1
 ;;                                     v37 -> v1
-;;                                     v38 -> v1
-;;                                     v39 -> v1
-;;                                     v40 -> v1
 ;; @0047                               v6 = load.i64 notrap aligned v1+8
 ;; @0047                               v8 = load.i64 notrap aligned v1
 ;; @0047                               v5 = uextend.i64 v0
@@ -76,23 +75,23 @@
 ;; @0047                               v11 = select_spectre_guard v7, v10, v9  ; v10 = 0
 ;; @0047                               v12 = load.i32 little heap v11
 ;;                                     v2 -> v12
-;; @004c                               v18 = iconst.i64 4
-;; @004c                               v19 = iadd v9, v18  ; v18 = 4
-;; @004c                               v21 = select_spectre_guard v7, v10, v19  ; v10 = 0
-;; @004c                               v22 = load.i32 little heap v21
-;;                                     v3 -> v22
-;; @0051                               v24 = iconst.i64 0x0010_0003
-;; @0051                               v25 = uadd_overflow_trap v5, v24, heap_oob  ; v24 = 0x0010_0003
-;; @0051                               v27 = icmp ugt v25, v6
-;; @0051                               v30 = iconst.i64 0x000f_ffff
-;; @0051                               v31 = iadd v9, v30  ; v30 = 0x000f_ffff
-;; @0051                               v33 = select_spectre_guard v27, v10, v31  ; v10 = 0
-;; @0051                               v34 = load.i32 little heap v33
-;;                                     v4 -> v34
+;; @004c                               v16 = iconst.i64 4
+;; @004c                               v17 = iadd v9, v16  ; v16 = 4
+;; @004c                               v19 = select_spectre_guard v7, v10, v17  ; v10 = 0
+;; @004c                               v20 = load.i32 little heap v19
+;;                                     v3 -> v20
+;; @0051                               v22 = iconst.i64 0x0010_0003
+;; @0051                               v23 = uadd_overflow_trap v5, v22, heap_oob  ; v22 = 0x0010_0003
+;; @0051                               v25 = icmp ugt v23, v6
+;; @0051                               v28 = iconst.i64 0x000f_ffff
+;; @0051                               v29 = iadd v9, v28  ; v28 = 0x000f_ffff
+;; @0051                               v31 = select_spectre_guard v25, v10, v29  ; v10 = 0
+;; @0051                               v32 = load.i32 little heap v31
+;;                                     v4 -> v32
 ;; @0056                               jump block1
 ;;
 ;;                                 block1:
-;; @0056                               return v12, v22, v34
+;; @0056                               return v12, v20, v32
 ;; }
 ;;
 ;; function u0:1(i32, i32, i32, i32, i64 vmctx) fast {
@@ -101,12 +100,11 @@
 ;;     gv2 = load.i64 notrap aligned gv0
 ;;
 ;;                                 block0(v0: i32, v1: i32, v2: i32, v3: i32, v4: i64):
+;;                                     v30 -> v4
+;;                                     v31 -> v4
 ;;                                     v32 -> v4
 ;;                                     v33 -> v4
 ;;                                     v34 -> v4
-;;                                     v35 -> v4
-;;                                     v36 -> v4
-;;                                     v37 -> v4
 ;; @005d                               v6 = load.i64 notrap aligned v4+8
 ;; @005d                               v8 = load.i64 notrap aligned v4
 ;; @005d                               v5 = uextend.i64 v0
@@ -115,17 +113,17 @@
 ;; @005d                               v9 = iadd v8, v5
 ;; @005d                               v11 = select_spectre_guard v7, v10, v9  ; v10 = 0
 ;; @005d                               store little heap v1, v11
-;; @0064                               v17 = iconst.i64 4
-;; @0064                               v18 = iadd v9, v17  ; v17 = 4
-;; @0064                               v20 = select_spectre_guard v7, v10, v18  ; v10 = 0
-;; @0064                               store little heap v2, v20
-;; @006b                               v22 = iconst.i64 0x0010_0003
-;; @006b                               v23 = uadd_overflow_trap v5, v22, heap_oob  ; v22 = 0x0010_0003
-;; @006b                               v25 = icmp ugt v23, v6
-;; @006b                               v28 = iconst.i64 0x000f_ffff
-;; @006b                               v29 = iadd v9, v28  ; v28 = 0x000f_ffff
-;; @006b                               v31 = select_spectre_guard v25, v10, v29  ; v10 = 0
-;; @006b                               store little heap v3, v31
+;; @0064                               v15 = iconst.i64 4
+;; @0064                               v16 = iadd v9, v15  ; v15 = 4
+;; @0064                               v18 = select_spectre_guard v7, v10, v16  ; v10 = 0
+;; @0064                               store little heap v2, v18
+;; @006b                               v20 = iconst.i64 0x0010_0003
+;; @006b                               v21 = uadd_overflow_trap v5, v20, heap_oob  ; v20 = 0x0010_0003
+;; @006b                               v23 = icmp ugt v21, v6
+;; @006b                               v26 = iconst.i64 0x000f_ffff
+;; @006b                               v27 = iadd v9, v26  ; v26 = 0x000f_ffff
+;; @006b                               v29 = select_spectre_guard v23, v10, v27  ; v10 = 0
+;; @006b                               store little heap v3, v29
 ;; @0070                               jump block1
 ;;
 ;;                                 block1:
@@ -72,6 +72,7 @@
 //!     ("Relax verification to allow I8X16 to act as a default vector type")
 
 mod bounds_checks;
+mod induction;
 
 use super::{hash_map, HashMap};
 use crate::environ::{FuncEnvironment, GlobalVariable};
@@ -96,6 +97,8 @@ use std::convert::TryFrom;
 use std::vec::Vec;
 use wasmparser::{FuncValidator, MemArg, Operator, WasmModuleResources};
 
+pub(crate) use induction::{loop_induction_variable, InductionVariable};
+
 /// Given a `Reachability<T>`, unwrap the inner `T` or, when unreachable, set
 /// `state.reachable = false` and return.
 ///
@@ -268,6 +271,21 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.push_block(next, params.len(), results.len());
         }
         Operator::Loop { blockty } => {
+            // The induction variable's value in the loop header is at most the
+            // larger of its value on entry and the largest value below the
+            // bound that its back edges are guarded by.
+            let induction_variable = state.loop_induction_variable.take().and_then(|iv| {
+                let var = Variable::from_u32(iv.local_index);
+                let entry = builder.try_use_var(var).ok()?;
+                let entry_max =
+                    bounds_checks::index_max_value(&builder.func.dfg, Some(&*state), entry, 0)?;
+                let max = match iv.bound.checked_sub(1) {
+                    Some(max) => max.max(entry_max),
+                    None => entry_max,
+                };
+                Some((var, max))
+            });
+
             let (params, results) = blocktype_params_results(validator, *blockty)?;
             let loop_body = block_with_params(builder, params.clone(), environ)?;
             let next = block_with_params(builder, results.clone(), environ)?;
@@ -282,12 +300,20 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                 .extend_from_slice(builder.block_params(loop_body));
 
             builder.switch_to_block(loop_body);
+            if let Some((var, max)) = induction_variable {
+                let val = builder.use_var(var);
+                state.record_value_max(val, max);
+            }
             environ.translate_loop_header(builder)?;
         }
         Operator::If { blockty } => {
             let val = state.pop1();
+            let likely = environ.before_conditional_branch(builder, val)?;
 
             let next_block = builder.create_block();
+            if likely == Some(false) {
+                builder.set_cold_block(next_block);
+            }
             let (params, results) = blocktype_params_results(validator, *blockty)?;
             let (destination, else_data) = if params.clone().eq(results.clone()) {
                 // It is possible there is no `else` block, so we will only
@@ -326,6 +352,9 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                     state.peekn(params.len()),
                 );
                 builder.seal_block(else_block);
+                if likely == Some(true) {
+                    builder.set_cold_block(else_block);
+                }
                 (destination, ElseData::WithElse { else_block })
             };
 
@@ -348,6 +377,8 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
         }
         Operator::Else => {
             let i = state.control_stack.len() - 1;
+            // Checks in the consequent don't dominate the alternative.
+            state.forget_checked_heap_accesses(i);
             match state.control_stack[i] {
                 ControlStackFrame::If {
                     ref else_data,
@@ -450,6 +481,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state
                 .stack
                 .extend_from_slice(builder.block_params(next_block));
+            state.forget_checked_heap_accesses(state.control_stack.len());
         }
         /**************************** Branch instructions *********************************
          * The branch instructions all have as arguments a target nesting level, which
@@ -490,7 +522,9 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.popn(return_count);
             state.reachable = false;
         }
-        Operator::BrIf { relative_depth } => translate_br_if(*relative_depth, builder, state),
+        Operator::BrIf { relative_depth } => {
+            translate_br_if(*relative_depth, builder, state, environ)?
+        }
         Operator::BrTable { targets } => {
             let default = targets.default();
             let mut min_depth = default;
@@ -2541,6 +2575,8 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
         }
         Operator::Else => {
             let i = state.control_stack.len() - 1;
+            // Checks in the consequent don't dominate the alternative.
+            state.forget_checked_heap_accesses(i);
             match state.control_stack[i] {
                 ControlStackFrame::If {
                     ref else_data,
@@ -2640,6 +2676,7 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
                 stack.extend_from_slice(builder.block_params(frame.following_code()));
                 state.reachable = true;
             }
+            state.forget_checked_heap_accesses(state.control_stack.len());
         }
         _ => {
             // We don't translate because this is unreachable code
@@ -2751,6 +2788,7 @@ where
         Ok(offset) => bounds_checks::bounds_check_and_compute_addr(
             builder,
             environ,
+            state,
             &heap,
             index,
             offset,
@@ -2792,6 +2830,7 @@ where
             bounds_checks::bounds_check_and_compute_addr(
                 builder,
                 environ,
+                state,
                 &heap,
                 adjusted_index,
                 0,
@@ -3185,18 +3224,40 @@ fn translate_vector_fcmp(
     state.push1(builder.ins().fcmp(cc, bitcast_a, bitcast_b))
 }
 
-fn translate_br_if(
+fn translate_br_if<FE: FuncEnvironment + ?Sized>(
     relative_depth: u32,
     builder: &mut FunctionBuilder,
     state: &mut FuncTranslationState,
-) {
+    environ: &mut FE,
+) -> WasmResult<()> {
     let val = state.pop1();
+    let likely = environ.before_conditional_branch(builder, val)?;
     let (br_destination, inputs) = translate_br_if_args(relative_depth, state);
     let next_block = builder.create_block();
-    canonicalise_brif(builder, val, br_destination, inputs, next_block, &[]);
+    match likely {
+        // The branch destination may have other predecessors, so rather than
+        // marking it cold route the unlikely edge through a cold block of its
+        // own.
+        Some(false) => {
+            let taken_block = builder.create_block();
+            builder.set_cold_block(taken_block);
+            builder.ins().brif(val, taken_block, &[], next_block, &[]);
+            builder.seal_block(taken_block);
+            builder.switch_to_block(taken_block);
+            canonicalise_then_jump(builder, br_destination, inputs);
+        }
+        Some(true) => {
+            builder.set_cold_block(next_block);
+            canonicalise_brif(builder, val, br_destination, inputs, next_block, &[]);
+        }
+        None => {
+            canonicalise_brif(builder, val, br_destination, inputs, next_block, &[]);
+        }
+    }
 
     builder.seal_block(next_block); // The only predecessor is the current block.
     builder.switch_to_block(next_block);
+    Ok(())
 }
 
 fn translate_br_if_args(
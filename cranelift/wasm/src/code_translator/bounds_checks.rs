@@ -20,6 +20,7 @@
 //! !!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!
 
 use super::Reachability;
+use crate::state::FuncTranslationState;
 use crate::{FuncEnvironment, HeapData, HeapStyle};
 use cranelift_codegen::{
     cursor::{Cursor, FuncCursor},
@@ -38,6 +39,7 @@ use Reachability::*;
 pub fn bounds_check_and_compute_addr<Env>(
     builder: &mut FunctionBuilder,
     env: &mut Env,
+    state: &mut FuncTranslationState,
     heap: &HeapData,
     // Dynamic operand indexing into the heap.
     index: ir::Value,
@@ -114,6 +116,84 @@ where
         result
     };
 
+    // ====== Redundant Dynamic Bounds Checks ======
+    //
+    // Before emitting a new check for a dynamic memory, see whether this
+    // access is already known to be in bounds:
+    //
+    // 1. When the index has a known maximum value (e.g. it is a constant, is
+    //    masked with a constant, or is a loop induction variable with a
+    //    constant bound) and
+    //
+    //        max_index + offset + access_size <= min_size
+    //
+    //    then the access is always in bounds, since the bound never drops
+    //    below `min_size`. No check is needed, not even for Spectre. Maxima
+    //    of induction variables are only used without Spectre mitigations,
+    //    since a mispredicted back edge runs the loop body once more with the
+    //    induction variable past its bound.
+    //
+    // 2. When a check of the same index against the same bound dominates this
+    //    access and proved `index + end <= bound`, then heap bounds only ever
+    //    grow, so this access is in bounds if `offset + access_size <= end`, or
+    //    will hit the guard region if `offset + access_size <= end +
+    //    guard_size`. The dominating check's condition is still precise enough
+    //    to act as the Spectre guard, so we reuse it rather than reloading the
+    //    bound and comparing again.
+    //
+    // These are skipped when emitting proof-carrying code, since the facts on
+    // the new address could not be checked without the facts of the
+    // dominating check, which the checker doesn't track across blocks.
+    if let HeapStyle::Dynamic { bound_gv } = heap.style {
+        if !pcc {
+            let maxima = if spectre_mitigations_enabled {
+                None
+            } else {
+                Some(&*state)
+            };
+            if index_max_value(&builder.func.dfg, maxima, index, 0)
+                .and_then(|max| max.checked_add(offset_and_size))
+                .is_some_and(|end| end <= heap.min_size)
+            {
+                return Ok(Reachable(compute_addr(
+                    &mut builder.cursor(),
+                    heap,
+                    env.pointer_type(),
+                    index,
+                    offset,
+                    None,
+                )));
+            }
+
+            if let Some(check) = state.checked_heap_access(bound_gv, orig_index) {
+                if offset_and_size <= check.end.saturating_add(heap.offset_guard_size) {
+                    return Ok(Reachable(if spectre_mitigations_enabled {
+                        explicit_check_oob_condition_and_compute_addr(
+                            &mut builder.cursor(),
+                            heap,
+                            env.pointer_type(),
+                            index,
+                            offset,
+                            access_size,
+                            spectre_mitigations_enabled,
+                            None,
+                            check.oob_condition,
+                        )
+                    } else {
+                        compute_addr(
+                            &mut builder.cursor(),
+                            heap,
+                            env.pointer_type(),
+                            index,
+                            offset,
+                            None,
+                        )
+                    }));
+                }
+            }
+        }
+    }
+
     // We need to emit code that will trap (or compute an address that will trap
     // when accessed) if
     //
@@ -150,6 +230,7 @@ where
                 bound,
                 Some(0),
             );
+            state.record_checked_heap_access(bound_gv, orig_index, 1, oob);
             Reachable(explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
@@ -198,6 +279,7 @@ where
                 bound,
                 Some(0),
             );
+            state.record_checked_heap_access(bound_gv, orig_index, 0, oob);
             Reachable(explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
@@ -242,6 +324,7 @@ where
                 adjusted_bound,
                 Some(adjustment),
             );
+            state.record_checked_heap_access(bound_gv, orig_index, offset_and_size, oob);
             Reachable(explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
@@ -291,6 +374,7 @@ where
                 bound,
                 Some(0),
             );
+            state.record_checked_heap_access(bound_gv, orig_index, offset_and_size, oob);
             Reachable(explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
@@ -469,6 +553,61 @@ where
     value
 }
 
+/// Get the maximum value that the given heap index can have, if it is known
+/// from the instruction defining it or, when `state` is given, from the
+/// maxima recorded in the translation state.
+///
+/// The recorded maxima are derived from branch conditions, and so only hold
+/// when those branches aren't mispredicted.
+pub(super) fn index_max_value(
+    dfg: &ir::DataFlowGraph,
+    state: Option<&FuncTranslationState>,
+    value: ir::Value,
+    depth: u32,
+) -> Option<u64> {
+    // Don't chase arbitrarily long chains of instructions.
+    const MAX_DEPTH: u32 = 4;
+
+    if let Some(max) = state.and_then(|state| state.value_max(value)) {
+        return Some(max);
+    }
+    if depth == MAX_DEPTH {
+        return None;
+    }
+
+    let inst = dfg.value_def(value).inst()?;
+    let width_mask = match dfg.value_type(value).bits() {
+        64 => u64::MAX,
+        bits => (1u64 << bits) - 1,
+    };
+    match dfg.insts[inst] {
+        ir::InstructionData::UnaryImm {
+            opcode: ir::Opcode::Iconst,
+            imm,
+        } => Some(imm.bits() as u64 & width_mask),
+        ir::InstructionData::Unary {
+            opcode: ir::Opcode::Uextend,
+            arg,
+        } => index_max_value(dfg, state, arg, depth + 1),
+        ir::InstructionData::BinaryImm64 {
+            opcode: ir::Opcode::BandImm,
+            imm,
+            ..
+        } => Some(imm.bits() as u64 & width_mask),
+        ir::InstructionData::Binary {
+            opcode: ir::Opcode::Band,
+            args: [x, y],
+        } => match (
+            index_max_value(dfg, state, x, depth + 1),
+            index_max_value(dfg, state, y, depth + 1),
+        ) {
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (max, None) | (None, max) => max,
+        },
+        _ => None,
+    }
+}
+
 fn cast_index_to_pointer_ty(
     index: ir::Value,
     index_ty: ir::Type,
@@ -0,0 +1,220 @@
+//! Recognition of loop induction variables with a constant upper bound.
+//!
+//! A Wasm local is the induction variable of a loop when every branch back to
+//! the loop's header is a `br_if` guarded by comparing the local against a
+//! constant `bound`, as in the loops LLVM emits for `for i in init..bound`:
+//!
+//! ```wat
+//! (loop $l
+//!   ...
+//!   (br_if $l (i32.lt_u (local.tee $i (i32.add (local.get $i) (i32.const 1)))
+//!                       (i32.const 100))))
+//! ```
+//!
+//! Whenever control reaches the loop header, the local then either still
+//! holds the value it had on entry to the loop, or a value below `bound`.
+//! Together with a known maximum of the value on entry, this gives a maximum
+//! for the local's value in the loop header, which lets the bounds checks of
+//! heap accesses indexed by it be elided.
+
+use wasmparser::{BinaryReader, Operator};
+
+/// An induction variable of a loop, as found by [`loop_induction_variable`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct InductionVariable {
+    /// The index of the Wasm local holding the induction variable.
+    pub local_index: u32,
+    /// The exclusive upper bound, as an unsigned integer, that the local is
+    /// checked against before every branch back to the loop header.
+    pub bound: u64,
+}
+
+/// Find the induction variable of the loop whose body `reader` is positioned
+/// at, i.e. just after its `loop` operator.
+///
+/// This only looks ahead in the operators and doesn't validate them, so any
+/// error reading them is left for the actual translation to report.
+pub(crate) fn loop_induction_variable(mut reader: BinaryReader) -> Option<InductionVariable> {
+    // The number of control frames nested in the loop body that are currently
+    // open, which is also the relative depth of the loop's label.
+    let mut depth = 0;
+    // The three operators preceding the current one, most recent last.
+    let mut window: [Option<Operator>; 3] = [None, None, None];
+    let mut induction_variable = None;
+
+    loop {
+        let op = reader.read_operator().ok()?;
+        match &op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => depth += 1,
+            Operator::End | Operator::Delegate { .. } if depth == 0 => break,
+            Operator::End | Operator::Delegate { .. } => depth -= 1,
+            Operator::BrIf { relative_depth } if *relative_depth == depth => {
+                let guard = back_edge_guard(&window)?;
+                induction_variable = match induction_variable {
+                    None => Some(guard),
+                    Some(InductionVariable { local_index, bound })
+                        if local_index == guard.local_index =>
+                    {
+                        Some(InductionVariable {
+                            local_index,
+                            bound: bound.max(guard.bound),
+                        })
+                    }
+                    Some(_) => return None,
+                };
+            }
+            // Any other branch back to the loop header isn't guarded.
+            Operator::Br { relative_depth }
+            | Operator::BrOnNull { relative_depth }
+            | Operator::BrOnNonNull { relative_depth }
+                if *relative_depth == depth =>
+            {
+                return None
+            }
+            Operator::BrTable { targets } => {
+                for target in targets.targets().chain(Some(Ok(targets.default()))) {
+                    if target.ok()? == depth {
+                        return None;
+                    }
+                }
+            }
+            _ => {}
+        }
+        window.rotate_left(1);
+        window[2] = Some(op);
+    }
+
+    induction_variable
+}
+
+/// Match the operators preceding a `br_if` against `local < bound`, where
+/// `local` is either read or assigned by its operator.
+fn back_edge_guard(window: &[Option<Operator>; 3]) -> Option<InductionVariable> {
+    let local_index = |op: &Operator| match *op {
+        Operator::LocalGet { local_index } | Operator::LocalTee { local_index } => {
+            Some(local_index)
+        }
+        _ => None,
+    };
+    let constant = |op: &Operator| match *op {
+        Operator::I32Const { value } => Some(u64::from(value as u32)),
+        Operator::I64Const { value } => Some(value as u64),
+        _ => None,
+    };
+
+    let [Some(a), Some(b), Some(cmp)] = window else {
+        return None;
+    };
+    let (local_index, bound) = match cmp {
+        Operator::I32LtU | Operator::I64LtU => (local_index(a)?, constant(b)?),
+        Operator::I32GtU | Operator::I64GtU => (local_index(b)?, constant(a)?),
+        _ => return None,
+    };
+    Some(InductionVariable { local_index, bound })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{loop_induction_variable, InductionVariable};
+    use wasmparser::{Operator, Parser, Payload};
+
+    /// Find the induction variable of the first loop in the function `func`.
+    fn first_loop(func: &str) -> Option<InductionVariable> {
+        let wasm = wat::parse_str(format!("(module {func})")).unwrap();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            if let Payload::CodeSectionEntry(body) = payload.unwrap() {
+                let mut reader = body.get_operators_reader().unwrap().get_binary_reader();
+                while !matches!(reader.read_operator().unwrap(), Operator::Loop { .. }) {}
+                return loop_induction_variable(reader);
+            }
+        }
+        panic!("no function body");
+    }
+
+    #[test]
+    fn guarded_back_edges() {
+        assert_eq!(
+            first_loop(
+                "(func (local i32)
+                    (loop
+                        (br_if 0 (i32.lt_u (local.tee 0 (i32.add (local.get 0) (i32.const 1)))
+                                           (i32.const 100)))))"
+            ),
+            Some(InductionVariable {
+                local_index: 0,
+                bound: 100
+            })
+        );
+
+        // Every back edge guards the same local, in either operand order, and
+        // branches out of the loop don't matter.
+        assert_eq!(
+            first_loop(
+                "(func (param i64 i32)
+                    (block
+                        (loop
+                            (br_if 1 (local.get 1))
+                            (if (local.get 1)
+                                (then (br_if 1 (i64.lt_u (local.get 0) (i64.const 10)))))
+                            (br_if 0 (i64.gt_u (i64.const 20) (local.get 0))))))"
+            ),
+            Some(InductionVariable {
+                local_index: 0,
+                bound: 20
+            })
+        );
+    }
+
+    #[test]
+    fn unguarded_back_edges() {
+        // An unconditional back edge.
+        assert_eq!(
+            first_loop(
+                "(func (local i32)
+                    (loop
+                        (local.set 0 (i32.add (local.get 0) (i32.const 1)))
+                        (br 0)))"
+            ),
+            None
+        );
+
+        // A back edge through a `br_table`.
+        assert_eq!(
+            first_loop(
+                "(func (local i32)
+                    (block
+                        (loop
+                            (br_if 0 (i32.lt_u (local.get 0) (i32.const 10)))
+                            (br_table 1 0 (local.get 0)))))"
+            ),
+            None
+        );
+
+        // Back edges guarding different locals.
+        assert_eq!(
+            first_loop(
+                "(func (local i32 i32)
+                    (loop
+                        (br_if 0 (i32.lt_u (local.get 0) (i32.const 10)))
+                        (br_if 0 (i32.lt_u (local.get 1) (i32.const 10)))))"
+            ),
+            None
+        );
+
+        // A signed comparison.
+        assert_eq!(
+            first_loop(
+                "(func (local i32)
+                    (loop
+                        (br_if 0 (i32.lt_s (local.get 0) (i32.const 10)))))"
+            ),
+            None
+        );
+
+        // A loop without back edges has no induction variable.
+        assert_eq!(first_loop("(func (loop (nop)))"), None);
+    }
+}
@@ -601,6 +601,21 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(())
     }
 
+    /// Optional callback for the `FuncEnvironment` performing this translation
+    /// to instrument or predict a conditional branch (`if` or `br_if`) on
+    /// `condition`. Called just before the branch is emitted.
+    ///
+    /// Returns whether the branch is expected to be taken, if known, in which
+    /// case the destination that isn't expected to be reached is laid out as
+    /// cold code.
+    fn before_conditional_branch(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _condition: ir::Value,
+    ) -> WasmResult<Option<bool>> {
+        Ok(None)
+    }
+
     /// Whether or not to force relaxed simd instructions to have deterministic
     /// lowerings meaning they will produce the same results across all hosts,
     /// regardless of the cost to performance.
@@ -4,7 +4,7 @@
 //! function to Cranelift IR guided by a `FuncEnvironment` which provides information about the
 //! WebAssembly module and the runtime environment.
 
-use crate::code_translator::{bitcast_wasm_returns, translate_operator};
+use crate::code_translator::{bitcast_wasm_returns, loop_induction_variable, translate_operator};
 use crate::environ::FuncEnvironment;
 use crate::state::FuncTranslationState;
 use crate::translation_utils::get_vmctx_value_label;
@@ -260,6 +260,11 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
         builder.set_srcloc(cur_srcloc(&reader));
         let op = reader.read_operator()?;
         validator.op(pos, &op)?;
+        if let wasmparser::Operator::Loop { .. } = op {
+            if state.reachable {
+                state.loop_induction_variable = loop_induction_variable(reader.clone());
+            }
+        }
         environ.before_translate_operator(&op, builder, state)?;
         translate_operator(validator, &op, builder, state, environ)?;
         environ.after_translate_operator(&op, builder, state)?;
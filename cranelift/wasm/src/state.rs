@@ -3,6 +3,7 @@
 //! The `FuncTranslationState` struct defined in this module is used to keep track of the WebAssembly
 //! value and control stacks during the translation of a single function.
 
+use crate::code_translator::InductionVariable;
 use crate::environ::{FuncEnvironment, GlobalVariable};
 use crate::{FuncIndex, GlobalIndex, Heap, MemoryIndex, TableIndex, TypeIndex, WasmResult};
 use crate::{HashMap, Occupied, Vacant};
@@ -208,6 +209,25 @@ impl ControlStackFrame {
     }
 }
 
+/// A bounds check of a dynamic heap that has already been emitted, and which
+/// proves `index + end <= bound` in all code it dominates.
+///
+/// Heap bounds never shrink, so such a check remains valid for the rest of the
+/// control frame it was emitted in, including any nested frames.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CheckedHeapAccess {
+    /// The global value holding the bound of the checked heap.
+    pub bound_gv: ir::GlobalValue,
+    /// The Wasm index operand that was checked.
+    pub index: Value,
+    /// How many bytes past `index` the check proved to be in bounds.
+    pub end: u64,
+    /// The `i8` condition that is non-zero when the check failed.
+    pub oob_condition: Value,
+    /// The depth of the control stack when the check was emitted.
+    depth: usize,
+}
+
 /// Contains information passed along during a function's translation and that records:
 ///
 /// - The current value and control stacks.
@@ -241,6 +261,18 @@ pub struct FuncTranslationState {
     // `FuncEnvironment::make_direct_func()`.
     // Stores both the function reference and the number of WebAssembly arguments
     functions: HashMap<FuncIndex, (ir::FuncRef, usize)>,
+
+    // Dynamic heap bounds checks that dominate the code currently being
+    // translated, ordered by the depth of the control stack they were emitted
+    // at.
+    checked_heap_accesses: Vec<CheckedHeapAccess>,
+
+    // The induction variable of the loop that is about to be translated, if
+    // it has one.
+    pub(crate) loop_induction_variable: Option<InductionVariable>,
+
+    // Known maximum values of integer values, as unsigned integers.
+    value_maxima: HashMap<Value, u64>,
 }
 
 // Public methods that are exposed to non-`cranelift_wasm` API consumers.
@@ -250,6 +282,13 @@ impl FuncTranslationState {
     pub fn reachable(&self) -> bool {
         self.reachable
     }
+
+    /// The values on the operand stack at this point, from the bottom of the
+    /// stack to its top.
+    #[inline]
+    pub fn operand_stack(&self) -> &[Value] {
+        &self.stack
+    }
 }
 
 impl FuncTranslationState {
@@ -264,6 +303,9 @@ impl FuncTranslationState {
             tables: HashMap::new(),
             signatures: HashMap::new(),
             functions: HashMap::new(),
+            checked_heap_accesses: Vec::new(),
+            loop_induction_variable: None,
+            value_maxima: HashMap::new(),
         }
     }
 
@@ -276,6 +318,9 @@ impl FuncTranslationState {
         self.tables.clear();
         self.signatures.clear();
         self.functions.clear();
+        self.checked_heap_accesses.clear();
+        self.loop_induction_variable = None;
+        self.value_maxima.clear();
     }
 
     /// Initialize the state for compiling a function with the given signature.
@@ -437,6 +482,62 @@ impl FuncTranslationState {
             blocktype,
         });
     }
+
+    /// Find the dominating bounds check of `index` against the dynamic heap
+    /// bound `bound_gv` which proves the largest range in bounds, if any.
+    pub(crate) fn checked_heap_access(
+        &self,
+        bound_gv: ir::GlobalValue,
+        index: Value,
+    ) -> Option<CheckedHeapAccess> {
+        self.checked_heap_accesses
+            .iter()
+            .filter(|check| check.bound_gv == bound_gv && check.index == index)
+            .max_by_key(|check| check.end)
+            .copied()
+    }
+
+    /// Record a bounds check of `index + end` against the dynamic heap bound
+    /// `bound_gv` emitted at the current position.
+    pub(crate) fn record_checked_heap_access(
+        &mut self,
+        bound_gv: ir::GlobalValue,
+        index: Value,
+        end: u64,
+        oob_condition: Value,
+    ) {
+        self.checked_heap_accesses.push(CheckedHeapAccess {
+            bound_gv,
+            index,
+            end,
+            oob_condition,
+            depth: self.control_stack.len(),
+        });
+    }
+
+    /// Forget all bounds checks emitted inside control frames deeper than
+    /// `depth`, as they no longer dominate the code that follows.
+    pub(crate) fn forget_checked_heap_accesses(&mut self, depth: usize) {
+        while self
+            .checked_heap_accesses
+            .last()
+            .is_some_and(|check| check.depth > depth)
+        {
+            self.checked_heap_accesses.pop();
+        }
+    }
+
+    /// Get the known maximum of the given integer value, as an unsigned
+    /// integer.
+    pub(crate) fn value_max(&self, value: Value) -> Option<u64> {
+        self.value_maxima.get(&value).copied()
+    }
+
+    /// Record that the given integer value is at most `max`, as an unsigned
+    /// integer, wherever it is used.
+    pub(crate) fn record_value_max(&mut self, value: Value, max: u64) {
+        self.value_maxima.insert(value, max);
+    }
 }
 
 /// Methods for handling entity references.
@@ -1,6 +1,7 @@
 //! Module for configuring the cache system.
 
-use super::Worker;
+use super::remote::DEFAULT_MAX_OBJECT_SIZE;
+use super::{HttpCacheBackend, ModuleCacheBackend, Worker};
 use anyhow::{anyhow, bail, Context, Result};
 use directories_next::ProjectDirs;
 use log::{trace, warn};
@@ -87,13 +88,46 @@ pub struct CacheConfig {
         deserialize_with = "deserialize_percent"
     )]
     files_total_size_limit_percent_if_deleting: Option<u8>,
+    #[serde(default, rename = "remote-url")]
+    remote_url: Option<String>,
+    #[serde(
+        default,
+        rename = "remote-timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    remote_timeout: Option<Duration>,
+    #[serde(default, rename = "remote-secret-file")]
+    remote_secret_file: Option<PathBuf>,
+    #[serde(
+        default,
+        rename = "remote-max-object-size",
+        deserialize_with = "deserialize_disk_space"
+    )]
+    remote_max_object_size: Option<u64>,
 
+    #[serde(skip)]
+    remote: Option<Remote>,
     #[serde(skip)]
     worker: Option<Worker>,
     #[serde(skip)]
     state: Arc<CacheState>,
 }
 
+/// A remote store along with the secret authenticating its objects.
+#[derive(Clone)]
+struct Remote {
+    backend: Arc<dyn ModuleCacheBackend>,
+    secret: Arc<[u8]>,
+}
+
+impl Debug for Remote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Remote")
+            .field("backend", &self.backend)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Default, Debug)]
 struct CacheState {
     hits: AtomicUsize,
@@ -184,6 +218,11 @@ const DEFAULT_FILES_TOTAL_SIZE_SOFT_LIMIT: u64 = 1024 * 1024 * 512;
 const DEFAULT_FILE_COUNT_LIMIT_PERCENT_IF_DELETING: u8 = 70;
 // if changed, update cli-cache.md
 const DEFAULT_FILES_TOTAL_SIZE_LIMIT_PERCENT_IF_DELETING: u8 = 70;
+// compiling is usually cheaper than waiting long on an unresponsive server
+// if changed, update cli-cache.md
+const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_secs(5);
+// if changed, update cli-cache.md
+const DEFAULT_REMOTE_MAX_OBJECT_SIZE: u64 = DEFAULT_MAX_OBJECT_SIZE;
 
 fn project_dirs() -> Option<ProjectDirs> {
     ProjectDirs::from("", "BytecodeAlliance", "wasmtime")
@@ -300,6 +339,8 @@ impl CacheConfig {
     generate_setting_getter!(files_total_size_soft_limit: u64);
     generate_setting_getter!(file_count_limit_percent_if_deleting: u8);
     generate_setting_getter!(files_total_size_limit_percent_if_deleting: u8);
+    generate_setting_getter!(remote_timeout: Duration);
+    generate_setting_getter!(remote_max_object_size: u64);
 
     /// Returns true if and only if the cache is enabled.
     pub fn enabled(&self) -> bool {
@@ -315,6 +356,32 @@ impl CacheConfig {
             .expect(CACHE_IMPROPER_CONFIG_ERROR_MSG)
     }
 
+    /// Returns the remote store that modules are shared through, if any.
+    pub fn remote(&self) -> Option<&Arc<dyn ModuleCacheBackend>> {
+        self.remote.as_ref().map(|remote| &remote.backend)
+    }
+
+    /// Returns the secret authenticating the objects of the remote store.
+    pub(crate) fn remote_secret(&self) -> Option<&[u8]> {
+        self.remote.as_ref().map(|remote| &*remote.secret)
+    }
+
+    /// Shares modules through the `remote` store, in addition to the local
+    /// cache directory.
+    ///
+    /// Objects are authenticated with an HMAC keyed by `secret`, which must be
+    /// the same on every host sharing the store. Objects that weren't written
+    /// by a host knowing the secret are ignored, and their modules recompiled.
+    ///
+    /// This replaces any store configured with `remote-url`. The remote store
+    /// is only used while the cache is enabled.
+    pub fn set_remote(&mut self, remote: Arc<dyn ModuleCacheBackend>, secret: &[u8]) {
+        self.remote = Some(Remote {
+            backend: remote,
+            secret: secret.into(),
+        });
+    }
+
     /// Creates a new set of configuration which represents a disabled cache
     pub fn new_cache_disabled() -> Self {
         Self {
@@ -331,6 +398,11 @@ impl CacheConfig {
             files_total_size_soft_limit: None,
             file_count_limit_percent_if_deleting: None,
             files_total_size_limit_percent_if_deleting: None,
+            remote_url: None,
+            remote_timeout: None,
+            remote_secret_file: None,
+            remote_max_object_size: None,
+            remote: None,
             worker: None,
             state: Arc::new(CacheState::default()),
         }
@@ -359,6 +431,9 @@ impl CacheConfig {
         config.validate_files_total_size_soft_limit_or_default();
         config.validate_file_count_limit_percent_if_deleting_or_default()?;
         config.validate_files_total_size_limit_percent_if_deleting_or_default()?;
+        config.validate_remote_timeout_or_default();
+        config.validate_remote_max_object_size_or_default();
+        config.validate_remote_url()?;
         config.spawn_worker();
 
         Ok(config)
@@ -577,6 +652,51 @@ impl CacheConfig {
         }
         Ok(())
     }
+
+    fn validate_remote_timeout_or_default(&mut self) {
+        if self.remote_timeout.is_none() {
+            self.remote_timeout = Some(DEFAULT_REMOTE_TIMEOUT);
+        }
+    }
+
+    fn validate_remote_max_object_size_or_default(&mut self) {
+        if self.remote_max_object_size.is_none() {
+            self.remote_max_object_size = Some(DEFAULT_REMOTE_MAX_OBJECT_SIZE);
+        }
+    }
+
+    fn validate_remote_url(&mut self) -> Result<()> {
+        let url = match &self.remote_url {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+        let backend = HttpCacheBackend::new(url, self.remote_timeout())?
+            .max_object_size(self.remote_max_object_size());
+        let secret_file = self
+            .remote_secret_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing remote-secret-file, required with remote-url"))?;
+        let secret = fs::read(secret_file).with_context(|| {
+            format!(
+                "Failed to read remote cache secret, path: {}",
+                secret_file.display()
+            )
+        })?;
+        // ignore the trailing newline of secrets written by shell commands
+        let len = secret
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+        let secret = &secret[..len];
+        if secret.is_empty() {
+            bail!(
+                "Remote cache secret is empty, path: {}",
+                secret_file.display()
+            );
+        }
+        self.set_remote(Arc::new(backend), secret);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -523,3 +523,67 @@ fn test_percent_settings() {
         cd
     );
 }
+
+#[test]
+fn test_remote_settings() {
+    let (td, cd, cp) = test_prolog();
+    let conf = load_config!(
+        cp,
+        "[cache]\n\
+         enabled = true\n\
+         directory = {cache_dir}",
+        cd
+    );
+    assert!(conf.remote().is_none());
+    assert_eq!(conf.remote_timeout(), Duration::from_secs(5));
+    assert_eq!(conf.remote_max_object_size(), 256 * (1u64 << 20));
+
+    let secret_file = td.path().join("secret");
+    fs::write(&secret_file, "hunter2\n").expect("Failed to write test secret file");
+    let remote_config = |settings: &str| {
+        let content = format!(
+            "[cache]\n\
+             enabled = true\n\
+             directory = {}\n\
+             remote-secret-file = {}\n\
+             {}",
+            toml::to_string_pretty(&format!("{}", cd.display())).unwrap(),
+            toml::to_string_pretty(&format!("{}", secret_file.display())).unwrap(),
+            settings,
+        );
+        fs::write(&cp, content).expect("Failed to write test config file");
+        CacheConfig::from_file(Some(&cp))
+    };
+
+    let conf = remote_config(
+        "remote-url = 'http://cache.example.com:8080/modules/'\n\
+         remote-timeout = '1m'\n\
+         remote-max-object-size = '1Mi'",
+    )
+    .unwrap();
+    assert!(conf.remote().is_some());
+    assert_eq!(conf.remote_secret(), Some(&b"hunter2"[..]));
+    assert_eq!(conf.remote_timeout(), Duration::from_secs(60));
+    assert_eq!(conf.remote_max_object_size(), 1 << 20);
+
+    assert!(remote_config("remote-url = 'http://[::1]:8080/modules'").is_ok());
+    assert!(remote_config("remote-url = 'http://[::1]/modules'").is_ok());
+    assert!(remote_config("remote-url = 'http://::1:8080/modules'").is_err());
+    assert!(remote_config("remote-url = 'http://[::1:8080/modules'").is_err());
+    assert!(remote_config("remote-url = 'http://[::1]8080/modules'").is_err());
+    assert!(remote_config("remote-url = 'https://cache.example.com'").is_err());
+    assert!(remote_config("remote-url = 'http://cache.example.com:http'").is_err());
+    assert!(remote_config("remote-url = 'http://:8080'").is_err());
+
+    // the secret is required, and must not be empty
+    bad_config!(
+        cp,
+        "[cache]\n\
+         enabled = true\n\
+         directory = {cache_dir}\n\
+         remote-url = 'http://cache.example.com'",
+        cd
+    );
+    fs::write(&secret_file, "\n").expect("Failed to write test secret file");
+    assert!(remote_config("remote-url = 'http://cache.example.com'").is_err());
+}
@@ -10,15 +10,18 @@ use std::{fs, io};
 
 #[macro_use] // for tests
 mod config;
+mod remote;
 mod worker;
 
 pub use config::{create_new_config, CacheConfig};
+pub use remote::{HttpCacheBackend, ModuleCacheBackend};
 use worker::Worker;
 
 /// Module level cache entry.
 pub struct ModuleCacheEntry<'config>(Option<ModuleCacheEntryInner<'config>>);
 
 struct ModuleCacheEntryInner<'config> {
+    compiler_dir: String,
     root_path: PathBuf,
     cache_config: &'config CacheConfig,
 }
@@ -96,12 +99,24 @@ impl<'config> ModuleCacheEntry<'config> {
                 return Ok(val);
             }
         }
+        if let Some(cached_val) = inner.get_remote_data(&hash) {
+            if let Some(val) = deserialize(state, cached_val.clone()) {
+                // keep a local copy so the remote is only consulted once per
+                // host, which is new to the local cache as if it was compiled
+                if inner.update_data(&hash, &cached_val).is_some() {
+                    let mod_cache_path = inner.root_path.join(&hash);
+                    inner.cache_config.on_cache_update_async(&mod_cache_path); // call on success
+                }
+                return Ok(val);
+            }
+        }
         let val_to_cache = compute(state)?;
         if let Some(bytes) = serialize(state, &val_to_cache) {
             if inner.update_data(&hash, &bytes).is_some() {
                 let mod_cache_path = inner.root_path.join(&hash);
                 inner.cache_config.on_cache_update_async(&mod_cache_path); // call on success
             }
+            let _ = inner.update_remote_data(&hash, &bytes);
         }
         Ok(val_to_cache)
     }
@@ -142,9 +157,10 @@ impl<'config> ModuleCacheEntryInner<'config> {
                 comp_ver = env!("GIT_REV"),
             )
         };
-        let root_path = cache_config.directory().join("modules").join(compiler_dir);
+        let root_path = cache_config.directory().join("modules").join(&compiler_dir);
 
         Self {
+            compiler_dir,
             root_path,
             cache_config,
         }
@@ -205,6 +221,81 @@ impl<'config> ModuleCacheEntryInner<'config> {
             }
         }
     }
+
+    // Objects in the remote store are an HMAC-SHA256 of their key and
+    // compressed contents, keyed by the configured secret, followed by the
+    // contents themselves. The MAC is checked on every read, so an object
+    // that's corrupted, or that was written or moved to another key by anyone
+    // without the secret, is never used.
+    fn get_remote_data(&self, hash: &str) -> Option<Vec<u8>> {
+        let remote = self.cache_config.remote()?;
+        let secret = self.cache_config.remote_secret()?;
+        let key = format!("{}/{}", self.compiler_dir, hash);
+        let object = remote.get(&key)?;
+        if object.len() < MAC_LEN {
+            warn!(
+                "Discarding truncated module from remote cache, key: {}",
+                key
+            );
+            return None;
+        }
+        let (mac, compressed_cache_bytes) = object.split_at(MAC_LEN);
+        let expected = hmac_sha256(secret, &[key.as_bytes(), &[0], compressed_cache_bytes]);
+        if !constant_time_eq(&expected, mac) {
+            warn!(
+                "Discarding unauthenticated module from remote cache, key: {}",
+                key
+            );
+            return None;
+        }
+        let cache_bytes = zstd::decode_all(compressed_cache_bytes)
+            .map_err(|err| warn!("Failed to decompress remotely cached code: {}", err))
+            .ok()?;
+        Some(cache_bytes)
+    }
+
+    fn update_remote_data(&self, hash: &str, serialized_data: &[u8]) -> Option<()> {
+        let remote = self.cache_config.remote()?;
+        let secret = self.cache_config.remote_secret()?;
+        let key = format!("{}/{}", self.compiler_dir, hash);
+        let compressed_data = zstd::encode_all(
+            &serialized_data[..],
+            self.cache_config.baseline_compression_level(),
+        )
+        .map_err(|err| warn!("Failed to compress cached code: {}", err))
+        .ok()?;
+        let mut object = hmac_sha256(secret, &[key.as_bytes(), &[0], &compressed_data]).to_vec();
+        object.extend_from_slice(&compressed_data);
+        remote.insert(&key, &object).then_some(())
+    }
+}
+
+const MAC_LEN: usize = 32;
+
+/// Computes the HMAC-SHA256 (RFC 2104) of the concatenation of `parts`.
+fn hmac_sha256(secret: &[u8], parts: &[&[u8]]) -> [u8; MAC_LEN] {
+    const BLOCK_LEN: usize = 64;
+    let mut block = [0; BLOCK_LEN];
+    if secret.len() > BLOCK_LEN {
+        block[..32].copy_from_slice(&Sha256::digest(secret));
+    } else {
+        block[..secret.len()].copy_from_slice(secret);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    for part in parts {
+        inner.update(part);
+    }
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Compares two MACs without leaking the position of the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
 }
 
 impl Hasher for Sha256Hasher {
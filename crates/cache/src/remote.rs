@@ -0,0 +1,274 @@
+//! Remote storage of cached modules, shared between hosts.
+
+use anyhow::{bail, Context, Result};
+use log::{trace, warn};
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// A remote store of compiled modules, which lets a fleet of hosts share the
+/// artifacts compiled by any one of them.
+///
+/// The cache consults its remote store whenever a module isn't found in the
+/// local cache directory, and uploads every module it compiles to it.
+///
+/// Values are opaque bytes which the cache authenticates itself with an HMAC
+/// keyed by a secret shared between the hosts, so a store that's corrupted or
+/// written to by anyone without the secret can only cause modules to be
+/// recompiled, never to run code that no host compiled. The store can however
+/// read the compiled code of every module, and hosts knowing the secret must
+/// be trusted as much as the code they compile.
+pub trait ModuleCacheBackend: Send + Sync + Debug {
+    /// Retrieves the value stored under `key`, if any.
+    ///
+    /// Keys only contain URL-safe characters and `/`.
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Stores `value` under `key`.
+    ///
+    /// Returns false when the value couldn't be stored.
+    fn insert(&self, key: &str, value: &[u8]) -> bool;
+}
+
+/// A [`ModuleCacheBackend`] which stores modules on an HTTP server in the
+/// style of an object store.
+///
+/// Each value is an object at `<url>/<key>` that's fetched with `GET` and
+/// uploaded with `PUT`, so any server or object store with such an interface
+/// can be used. Only plain `http://` URLs are supported; use a local proxy to
+/// reach a server over TLS.
+///
+/// Requests are made synchronously, so every module that isn't cached locally
+/// can take up to three times the timeout (to connect, send and receive)
+/// longer to compile while the server is unresponsive.
+#[derive(Debug)]
+pub struct HttpCacheBackend {
+    host: String,
+    port: u16,
+    authority: String,
+    path: String,
+    timeout: Duration,
+    max_object_size: u64,
+}
+
+/// The default limit on the size of objects read from the server.
+// if changed, update cli-cache.md
+pub(crate) const DEFAULT_MAX_OBJECT_SIZE: u64 = 256 << 20;
+
+impl HttpCacheBackend {
+    /// Creates a backend storing modules under `url`, giving up on requests
+    /// that take longer than `timeout`.
+    ///
+    /// The host of `url` may be a name, an IPv4 address, or an IPv6 address
+    /// enclosed in brackets.
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        let rest = url.strip_prefix("http://").with_context(|| {
+            format!("unsupported remote cache URL, only `http://` is supported: {url}")
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(rest) => {
+                let (host, port) = rest.split_once(']').with_context(|| {
+                    format!("unterminated IPv6 address in remote cache URL: {url}")
+                })?;
+                let port = match port {
+                    "" => None,
+                    port => Some(
+                        port.strip_prefix(':')
+                            .with_context(|| format!("invalid port in remote cache URL: {url}"))?,
+                    ),
+                };
+                (host, port)
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        if host.is_empty() {
+            bail!("missing host in remote cache URL: {url}");
+        }
+        if !authority.starts_with('[') && host.contains(':') {
+            bail!("IPv6 addresses must be enclosed in brackets in remote cache URL: {url}");
+        }
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .with_context(|| format!("invalid port in remote cache URL: {url}"))?,
+            None => 80,
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            authority: authority.to_string(),
+            path: path.trim_end_matches('/').to_string(),
+            timeout,
+            max_object_size: DEFAULT_MAX_OBJECT_SIZE,
+        })
+    }
+
+    /// Limits the size of objects read from the server to `size` bytes.
+    ///
+    /// Larger objects are treated as missing, so that a misbehaving server
+    /// can't exhaust the memory of the compiling process.
+    pub fn max_object_size(mut self, size: u64) -> Self {
+        self.max_object_size = size;
+        self
+    }
+
+    fn request(&self, method: &str, key: &str, body: &[u8]) -> io::Result<(u16, Vec<u8>)> {
+        let addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address for host"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        write!(
+            stream,
+            "{method} {path}/{key} HTTP/1.1\r\n\
+             Host: {authority}\r\n\
+             Content-Length: {len}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            path = self.path,
+            authority = self.authority,
+            len = body.len(),
+        )?;
+        stream.write_all(body)?;
+        stream.flush()?;
+        read_response(BufReader::new(stream), self.max_object_size)
+    }
+}
+
+impl ModuleCacheBackend for HttpCacheBackend {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        trace!("get() for remote key: {}", key);
+        match self.request("GET", key, &[]) {
+            Ok((200, body)) => Some(body),
+            Ok((404, _)) => None,
+            Ok((status, _)) => {
+                warn!(
+                    "Failed to fetch module from remote cache, key: {}, status: {}",
+                    key, status
+                );
+                None
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to fetch module from remote cache, key: {}, err: {}",
+                    key, err
+                );
+                None
+            }
+        }
+    }
+
+    fn insert(&self, key: &str, value: &[u8]) -> bool {
+        trace!("insert() for remote key: {}", key);
+        match self.request("PUT", key, value) {
+            Ok((status, _)) if (200..300).contains(&status) => true,
+            Ok((status, _)) => {
+                warn!(
+                    "Failed to upload module to remote cache, key: {}, status: {}",
+                    key, status
+                );
+                false
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to upload module to remote cache, key: {}, err: {}",
+                    key, err
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Reads an HTTP/1.1 response, returning its status code and body.
+///
+/// Fails if the body is larger than `max_size` bytes.
+fn read_response(mut reader: impl BufRead, max_size: u64) -> io::Result<(u16, Vec<u8>)> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let too_large = || invalid("response body exceeds the maximum object size");
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid("malformed status line"))?;
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                let len: u64 = value
+                    .parse()
+                    .map_err(|_| invalid("malformed content length"))?;
+                content_length = Some(len);
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let size = line
+                .split(';')
+                .next()
+                .and_then(|s| u64::from_str_radix(s.trim(), 16).ok())
+                .ok_or_else(|| invalid("malformed chunk size"))?;
+            if size == 0 {
+                break;
+            }
+            if size > max_size - body.len() as u64 {
+                return Err(too_large());
+            }
+            read_body(&mut reader, size, &mut body)?;
+            // the CRLF terminating the chunk
+            line.clear();
+            reader.read_line(&mut line)?;
+        }
+    } else if let Some(len) = content_length {
+        if len > max_size {
+            return Err(too_large());
+        }
+        read_body(&mut reader, len, &mut body)?;
+    } else {
+        reader.take(max_size + 1).read_to_end(&mut body)?;
+        if body.len() as u64 > max_size {
+            return Err(too_large());
+        }
+    }
+    Ok((status, body))
+}
+
+/// Appends exactly `len` bytes read from `reader` to `body`.
+fn read_body(reader: &mut impl Read, len: u64, body: &mut Vec<u8>) -> io::Result<()> {
+    // Memory is only allocated as the data arrives, so a wrong length can't
+    // allocate more than what's actually sent.
+    let read = reader.take(len).read_to_end(body)?;
+    if read as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
@@ -1,6 +1,11 @@
 use super::config::tests::test_prolog;
 use super::*;
+use std::collections::HashMap;
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 // Since cache system is a global thing, each test needs to be run in seperate process.
 // So, init() tests are run as integration tests.
@@ -90,3 +95,202 @@ fn test_write_read_cache() {
     entry1.get_data::<_, i32, i32>(4, |_| panic!()).unwrap();
     entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap();
 }
+
+#[test]
+fn test_remote_cache() {
+    let (url, objects) = spawn_object_store();
+
+    let (_tempdir1, cache_config1) = remote_cache_config(&url, "secret", "");
+    let (_tempdir2, cache_config2) = remote_cache_config(&url, "secret", "");
+    let entry1 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config1));
+    let entry2 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config2));
+
+    // a module compiled on one host is found by another
+    assert_eq!(entry1.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(objects.lock().unwrap().len(), 1);
+    assert_eq!(
+        entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(),
+        100
+    );
+    assert_eq!(cache_config2.cache_misses(), 1);
+    assert_eq!(cache_config2.cache_hits(), 0);
+
+    // a corrupted module is recompiled and replaced
+    for object in objects.lock().unwrap().values_mut() {
+        *object.last_mut().unwrap() ^= 1;
+    }
+    let (_tempdir3, cache_config3) = remote_cache_config(&url, "secret", "");
+    let (_tempdir4, cache_config4) = remote_cache_config(&url, "secret", "");
+    let entry3 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config3));
+    let entry4 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config4));
+    assert_eq!(entry3.get_data::<_, i32, i32>(1, |_| Ok(200)).unwrap(), 200);
+    assert_eq!(
+        entry4.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(),
+        200
+    );
+
+    // the local copy is used once the module has been fetched
+    objects.lock().unwrap().clear();
+    assert_eq!(
+        entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(),
+        100
+    );
+    assert_eq!(cache_config2.cache_hits(), 1);
+}
+
+#[test]
+fn test_remote_cache_authentication() {
+    let (url, objects) = spawn_object_store();
+
+    let (_tempdir1, cache_config1) = remote_cache_config(&url, "secret", "");
+    let entry1 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config1));
+    assert_eq!(entry1.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+
+    // a host with another secret doesn't trust the module
+    let (_tempdir2, cache_config2) = remote_cache_config(&url, "other secret", "");
+    let entry2 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config2));
+    assert_eq!(entry2.get_data::<_, i32, i32>(1, |_| Ok(200)).unwrap(), 200);
+
+    // nor a module whose content hash was recomputed by someone without the
+    // secret
+    for object in objects.lock().unwrap().values_mut() {
+        let compressed = object.split_off(MAC_LEN);
+        *object = Sha256::digest(&compressed).to_vec();
+        object.extend_from_slice(&compressed);
+    }
+    let (_tempdir3, cache_config3) = remote_cache_config(&url, "secret", "");
+    let entry3 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config3));
+    assert_eq!(entry3.get_data::<_, i32, i32>(1, |_| Ok(300)).unwrap(), 300);
+
+    // nor a module copied to the key of another one
+    let original = objects.lock().unwrap().clone();
+    assert_eq!(entry3.get_data::<_, i32, i32>(2, |_| Ok(400)).unwrap(), 400);
+    {
+        let mut objects = objects.lock().unwrap();
+        let (_, object) = original.iter().next().unwrap();
+        for (key, value) in objects.iter_mut() {
+            if !original.contains_key(key) {
+                *value = object.clone();
+            }
+        }
+    }
+    let (_tempdir4, cache_config4) = remote_cache_config(&url, "secret", "");
+    let entry4 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config4));
+    assert_eq!(entry4.get_data::<_, i32, i32>(2, |_| Ok(500)).unwrap(), 500);
+}
+
+#[test]
+fn test_remote_cache_max_object_size() {
+    let (url, _objects) = spawn_object_store();
+
+    let (_tempdir1, cache_config1) = remote_cache_config(&url, "secret", "");
+    let entry1 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config1));
+    assert_eq!(entry1.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+
+    // objects larger than the limit are ignored
+    let (_tempdir2, cache_config2) =
+        remote_cache_config(&url, "secret", "remote-max-object-size = '8'\n");
+    let entry2 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config2));
+    assert_eq!(entry2.get_data::<_, i32, i32>(1, |_| Ok(200)).unwrap(), 200);
+}
+
+#[test]
+fn test_hmac_sha256() {
+    // test case 2 of RFC 4231
+    let mac = hmac_sha256(b"Jefe", &[b"what do ya ", b"want for nothing?"]);
+    assert_eq!(
+        mac.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    // test case 6, with a secret longer than a block
+    let mac = hmac_sha256(
+        &[0xaa; 131],
+        &[b"Test Using Larger Than Block-Size Key - Hash Key First"],
+    );
+    assert_eq!(
+        mac.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+}
+
+fn remote_cache_config(
+    url: &str,
+    secret: &str,
+    settings: &str,
+) -> (tempfile::TempDir, CacheConfig) {
+    let (tempdir, cache_dir, config_path) = test_prolog();
+    let secret_path = tempdir.path().join("secret");
+    fs::write(&secret_path, secret).expect("Failed to write test secret file");
+    let config_content = format!(
+        "[cache]\n\
+         enabled = true\n\
+         directory = {}\n\
+         remote-url = {}\n\
+         remote-secret-file = {}\n\
+         {}",
+        toml::to_string_pretty(&format!("{}", cache_dir.display())).unwrap(),
+        toml::to_string_pretty(url).unwrap(),
+        toml::to_string_pretty(&format!("{}", secret_path.display())).unwrap(),
+        settings,
+    );
+    fs::write(&config_path, config_content).expect("Failed to write test config file");
+    let cache_config = CacheConfig::from_file(Some(&config_path)).unwrap();
+    (tempdir, cache_config)
+}
+
+// A minimal object store which keeps the objects `PUT` to it in memory.
+fn spawn_object_store() -> (String, Arc<Mutex<HashMap<String, Vec<u8>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/modules", listener.local_addr().unwrap());
+    let objects = Arc::new(Mutex::new(HashMap::<String, Vec<u8>>::new()));
+
+    let store = objects.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = BufReader::new(stream.unwrap());
+            let mut line = String::new();
+            stream.read_line(&mut line).unwrap();
+            let mut request = line.split_whitespace();
+            let method = request.next().unwrap().to_string();
+            let path = request.next().unwrap().to_string();
+
+            let mut len = 0;
+            loop {
+                line.clear();
+                stream.read_line(&mut line).unwrap();
+                let header = line.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some(value) = header.strip_prefix("Content-Length:") {
+                    len = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; len];
+            stream.read_exact(&mut body).unwrap();
+
+            let mut objects = store.lock().unwrap();
+            let (status, body) = match method.as_str() {
+                "GET" => match objects.get(&path) {
+                    Some(object) => ("200 OK", object.clone()),
+                    None => ("404 Not Found", Vec::new()),
+                },
+                "PUT" => {
+                    objects.insert(path, body);
+                    ("201 Created", Vec::new())
+                }
+                _ => ("405 Method Not Allowed", Vec::new()),
+            };
+            let stream = stream.get_mut();
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+
+    (url, objects)
+}
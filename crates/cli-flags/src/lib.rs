@@ -36,6 +36,35 @@ fn init_file_per_thread_logger(prefix: &'static str) {
         .unwrap();
 }
 
+/// Returns whether any of the comma-separated `tracing` directives in
+/// `filter` apply to `target` specifically, rather than only setting the
+/// default level of every target.
+///
+/// Directives match every target which starts with their own, and invalid
+/// directives are ignored just like `EnvFilter` does.
+#[cfg(feature = "logging")]
+fn filter_configures_target(filter: &str, target: &str) -> bool {
+    use tracing_subscriber::filter::{Directive, LevelFilter};
+
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| directive.parse::<Directive>().is_ok())
+        .filter(|directive| directive.parse::<LevelFilter>().is_err())
+        .any(|directive| {
+            let directive_target = directive.split(['[', '=']).next().unwrap_or("");
+            !directive_target.is_empty() && target.starts_with(directive_target)
+        })
+}
+
+#[cfg(feature = "cranelift")]
+fn load_pgo_profile(path: &str) -> Result<wasmtime::PgoProfile> {
+    use anyhow::Context;
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read profile `{path}`"))?;
+    wasmtime::PgoProfile::deserialize(&bytes)
+        .with_context(|| format!("failed to load profile `{path}`"))
+}
+
 wasmtime_option_group! {
     #[derive(PartialEq, Clone)]
     pub struct OptimizeOptions {
@@ -73,6 +102,14 @@ wasmtime_option_group! {
         /// Configure attempting to initialize linear memory via a
         /// copy-on-write mapping (default: yes)
         pub memory_init_cow: Option<bool>,
+
+        /// Back linear memories with transparent huge pages where supported
+        /// (default: no)
+        pub memory_huge_pages: Option<bool>,
+
+        /// Inline small functions into their callers within a module
+        /// (default: no)
+        pub inlining: Option<bool>,
     }
 
     enum Optimize {
@@ -83,10 +120,10 @@ wasmtime_option_group! {
 wasmtime_option_group! {
     #[derive(PartialEq, Clone)]
     pub struct CodegenOptions {
-        /// Either `cranelift` or `winch`.
+        /// Either `cranelift`, `winch` or `interpreter`.
         ///
-        /// Currently only `cranelift` and `winch` are supported, but not all
-        /// builds of Wasmtime have both built in.
+        /// Currently only `cranelift`, `winch` and `interpreter` are
+        /// supported, but not all builds of Wasmtime have all of them built in.
         pub compiler: Option<wasmtime::Strategy>,
         /// Enable Cranelift's internal debug verifier (expensive)
         pub cranelift_debug_verifier: Option<bool>,
@@ -98,6 +135,13 @@ wasmtime_option_group! {
         pub parallel_compilation: Option<bool>,
         /// Whether to enable proof-carrying code (PCC)-based validation.
         pub pcc: Option<bool>,
+        /// Instrument compiled code to collect an execution profile for
+        /// profile-guided optimization, written to `-C pgo-profile` by
+        /// `wasmtime run`.
+        pub pgo_instrument: Option<bool>,
+        /// Path of an execution profile to optimize compiled code with, or to
+        /// write the collected profile to with `-C pgo-instrument`.
+        pub pgo_profile: Option<String>,
 
         #[prefixed = "cranelift"]
         /// Set a cranelift-specific option. Use `wasmtime settings` to see
@@ -157,6 +201,10 @@ wasmtime_option_group! {
         /// Allow the main module to import unknown functions, using an
         /// implementation that returns default values, when running commands.
         pub unknown_imports_default: Option<bool>,
+        /// Provide the main module with the import `wasmtime::memory_discard`,
+        /// taking an address and a length, which zeroes that region of the
+        /// module's exported `memory` and returns it to the operating system.
+        pub memory_discard: Option<bool>,
         /// Enables memory error checking. (see wmemcheck.md for more info)
         pub wmemcheck: Option<bool>,
         /// Maximum size, in bytes, that a linear memory is allowed to reach.
@@ -232,6 +280,13 @@ wasmtime_option_group! {
         pub threads: Option<bool>,
         /// Enable suport for WASI HTTP API (experimental)
         pub http: Option<bool>,
+        /// Enable support for WASI key-value API (experimental)
+        pub keyvalue: Option<bool>,
+        /// Directory in which to persist wasi-keyvalue buckets.
+        ///
+        /// If this is not specified then buckets are kept in memory and are
+        /// discarded when the process exits.
+        pub keyvalue_dir: Option<String>,
         /// Inherit environment variables and file descriptors following the
         /// systemd listen fd specification (UNIX only)
         pub listenfd: Option<bool>,
@@ -265,6 +320,34 @@ wasmtime_option_group! {
         pub udp: Option<bool>,
         /// Allows imports from the `wasi_unstable` core wasm module.
         pub preview0: Option<bool>,
+        /// Enable support for WASI runtime config API (experimental)
+        pub config: Option<bool>,
+        /// Provide a runtime config value to the guest, e.g.
+        /// `-S config-var=greeting=hello`.
+        ///
+        /// These take precedence over values from `config-inherit-env` and
+        /// `config-file`.
+        pub config_var: Vec<KeyValuePair>,
+        /// Provide runtime config values to the guest from a TOML file.
+        ///
+        /// Nested tables are flattened into dotted keys.
+        pub config_file: Option<String>,
+        /// Provide the host's environment variables to the guest as runtime
+        /// config values.
+        ///
+        /// These take precedence over values from `config-file`.
+        pub config_inherit_env: Option<bool>,
+        /// Enable support for WASI logging API (experimental)
+        ///
+        /// Guest log records are emitted under the `wasi_logging` target and
+        /// are shown at `info` level and above unless `WASMTIME_LOG`
+        /// configures the `wasi_logging` target itself.
+        pub logging: Option<bool>,
+        /// Maximum number of wasi-logging records guests may emit per second.
+        ///
+        /// Records over the limit are dropped. For `wasmtime serve` the limit
+        /// is shared between all requests.
+        pub logging_rate_limit: Option<u32>,
     }
 
     enum Wasi {
@@ -278,6 +361,12 @@ pub struct WasiNnGraph {
     pub dir: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyValuePair {
+    pub key: String,
+    pub value: String,
+}
+
 /// Common options for commands that translate WebAssembly modules
 #[derive(Parser, Clone)]
 pub struct CommonOptions {
@@ -372,9 +461,16 @@ impl CommonOptions {
         } else {
             use std::io::IsTerminal;
             use tracing_subscriber::{EnvFilter, FmtSubscriber};
+            let mut filter = EnvFilter::from_env("WASMTIME_LOG");
+            let configures_guest_logs = std::env::var("WASMTIME_LOG")
+                .map(|s| filter_configures_target(&s, "wasi_logging"))
+                .unwrap_or(false);
+            if self.wasi.logging == Some(true) && !configures_guest_logs {
+                filter = filter.add_directive("wasi_logging=info".parse()?);
+            }
             let b = FmtSubscriber::builder()
                 .with_writer(std::io::stderr)
-                .with_env_filter(EnvFilter::from_env("WASMTIME_LOG"))
+                .with_env_filter(filter)
                 .with_ansi(std::io::stderr().is_terminal());
             b.init();
         }
@@ -418,6 +514,11 @@ impl CommonOptions {
             level => config.cranelift_opt_level(level),
             _ => err,
         }
+        match_feature! {
+            ["cranelift" : self.opts.inlining]
+            enable => config.cranelift_inlining(enable),
+            true => err,
+        }
         match_feature! {
             ["cranelift" : self.wasm.nan_canonicalization]
             enable => config.cranelift_nan_canonicalization(enable),
@@ -428,6 +529,20 @@ impl CommonOptions {
             enable => config.cranelift_pcc(enable),
             true => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.pgo_instrument]
+            enable => config.pgo_instrumentation(enable),
+            true => err,
+        }
+        // When instrumenting the profile path is where the collected profile
+        // is written to rather than one to optimize with.
+        if self.codegen.pgo_instrument != Some(true) {
+            match_feature! {
+                ["cranelift" : &self.codegen.pgo_profile]
+                path => config.pgo_profile(load_pgo_profile(path)?),
+                _ => err,
+            }
+        }
 
         self.enable_wasm_features(&mut config)?;
 
@@ -505,6 +620,9 @@ impl CommonOptions {
         if let Some(enable) = self.opts.memory_init_cow {
             config.memory_init_cow(enable);
         }
+        if let Some(enable) = self.opts.memory_huge_pages {
+            config.memory_huge_pages(enable);
+        }
 
         match_feature! {
             ["pooling-allocator" : self.opts.pooling_allocator]
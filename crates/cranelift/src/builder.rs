@@ -20,7 +20,9 @@ struct Builder {
     linkopts: LinkOptions,
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<path::PathBuf>,
+    emit_compiled_clif: bool,
     wmemcheck: bool,
+    inlining: bool,
 }
 
 #[derive(Clone, Default)]
@@ -43,7 +45,9 @@ pub fn builder() -> Box<dyn CompilerBuilder> {
         linkopts: LinkOptions::default(),
         cache_store: None,
         clif_dir: None,
+        emit_compiled_clif: false,
         wmemcheck: false,
+        inlining: false,
     })
 }
 
@@ -72,6 +76,10 @@ impl CompilerBuilder for Builder {
             self.linkopts.force_jump_veneers = value.parse()?;
             return Ok(());
         }
+        if name == "wasmtime_inlining" {
+            self.inlining = value.parse()?;
+            return Ok(());
+        }
 
         self.inner.set(name, value)
     }
@@ -93,7 +101,9 @@ impl CompilerBuilder for Builder {
             self.cache_store.clone(),
             self.linkopts.clone(),
             self.clif_dir.clone(),
+            self.emit_compiled_clif,
             self.wmemcheck,
+            self.inlining,
         )))
     }
 
@@ -112,6 +122,10 @@ impl CompilerBuilder for Builder {
     fn wmemcheck(&mut self, enable: bool) {
         self.wmemcheck = enable;
     }
+
+    fn emit_compiled_clif(&mut self, enable: bool) {
+        self.emit_compiled_clif = enable;
+    }
 }
 
 impl fmt::Debug for Builder {
@@ -1,5 +1,5 @@
 use crate::debug::{DwarfSectionRelocTarget, ModuleMemoryOffset};
-use crate::func_environ::FuncEnvironment;
+use crate::func_environ::{FuncEnvironment, OperandSource};
 use crate::{array_call_signature, native_call_signature, DEBUG_ASSERT_TRAP_CODE};
 use crate::{builder::LinkOptions, value_type, wasm_call_signature};
 use anyhow::{Context as _, Result};
@@ -11,13 +11,13 @@ use cranelift_codegen::isa::{
     OwnedTargetIsa, TargetIsa,
 };
 use cranelift_codegen::print_errors::pretty_error;
-use cranelift_codegen::Context;
-use cranelift_codegen::{CompiledCode, MachStackMap};
+use cranelift_codegen::{CompiledCode, Final, MachSrcLoc, MachStackMap};
+use cranelift_codegen::{Context, LabelValueLoc, ValueLabelsRanges};
 use cranelift_entity::{EntityRef, PrimaryMap};
 use cranelift_frontend::FunctionBuilder;
 use cranelift_wasm::{
     DefinedFuncIndex, FuncIndex, FuncTranslator, MemoryIndex, OwnedMemoryIndex, WasmFuncType,
-    WasmType,
+    WasmResult, WasmType,
 };
 use object::write::{Object, StandardSegment, SymbolId};
 use object::{RelocationEncoding, RelocationKind, SectionKind};
@@ -25,6 +25,7 @@ use std::any::Any;
 use std::cmp;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt;
 use std::mem;
 use std::path;
 use std::sync::{Arc, Mutex};
@@ -32,12 +33,14 @@ use wasmparser::{FuncValidatorAllocations, FunctionBody};
 use wasmtime_cranelift_shared::{CompiledFunction, ModuleTextBuilder};
 use wasmtime_environ::{
     AddressMapSection, CacheStore, CompileError, FlagValue, FunctionBodyData, FunctionLoc,
-    ModuleTranslation, ModuleTypesBuilder, PtrSize, StackMapInformation, TrapEncodingBuilder,
-    Tunables, VMOffsets, WasmFunctionInfo,
+    LocalSlotInformation, ModuleTranslation, ModuleTypesBuilder, OperandSlotInformation,
+    OperandStackHeight, PtrSize, StackMapInformation, TrapEncodingBuilder, Tunables, TypeConvert,
+    VMOffsets, WasmFunctionInfo,
 };
 
 #[cfg(feature = "component-model")]
 mod component;
+mod inline;
 
 struct IncrementalCacheContext {
     #[cfg(feature = "incremental-cache")]
@@ -46,6 +49,30 @@ struct IncrementalCacheContext {
     num_cached: usize,
 }
 
+/// A wasm function which has been translated to CLIF but not yet compiled,
+/// produced when inlining is enabled.
+struct TranslatedFunction {
+    func: ir::Function,
+    body: FunctionBodyInfo,
+    /// The size of this function if it may be inlined into its callers.
+    inlinable_size: Option<usize>,
+}
+
+/// Information about a wasm function's body gathered while translating it,
+/// which is recorded once the function is compiled.
+#[derive(Clone)]
+struct FunctionBodyInfo {
+    /// The offset and length of the function's body in the original wasm.
+    range: (u32, u32),
+    /// The types of the function's locals, including its parameters. Only
+    /// gathered when native debug info is generated.
+    locals: Box<[WasmType]>,
+    /// The wasm offset of each operator along with where the values on the
+    /// operand stack before it can be found. Only gathered when native debug
+    /// info is generated.
+    operand_stacks: Box<[(u32, Box<[Option<OperandSource>]>)]>,
+}
+
 struct CompilerContext {
     func_translator: FuncTranslator,
     codegen_context: Context,
@@ -73,7 +100,9 @@ pub(crate) struct Compiler {
     linkopts: LinkOptions,
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<path::PathBuf>,
+    emit_compiled_clif: bool,
     wmemcheck: bool,
+    inlining: bool,
 }
 
 impl Drop for Compiler {
@@ -111,8 +140,14 @@ impl Compiler {
         cache_store: Option<Arc<dyn CacheStore>>,
         linkopts: LinkOptions,
         clif_dir: Option<path::PathBuf>,
+        emit_compiled_clif: bool,
         wmemcheck: bool,
+        inlining: bool,
     ) -> Compiler {
+        // Inlined code carries neither DWARF for the callee nor
+        // proof-carrying-code facts, so inlining is disabled when either is
+        // requested.
+        let inlining = inlining && !tunables.generate_native_debuginfo && !isa.flags().enable_pcc();
         Compiler {
             contexts: Default::default(),
             tunables,
@@ -120,7 +155,9 @@ impl Compiler {
             linkopts,
             cache_store,
             clif_dir,
+            emit_compiled_clif,
             wmemcheck,
+            inlining,
         }
     }
 }
@@ -133,108 +170,72 @@ impl wasmtime_environ::Compiler for Compiler {
         input: FunctionBodyData<'_>,
         types: &ModuleTypesBuilder,
     ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
-        let isa = &*self.isa;
-        let module = &translation.module;
-        let func_index = module.func_index(func_index);
-        let sig = translation.module.functions[func_index].signature;
-        let wasm_func_ty = &types[sig];
-
         let mut compiler = self.function_compiler();
+        let body =
+            self.translate_function_body(&mut compiler, translation, func_index, input, types)?;
+        let func_index = translation.module.func_index(func_index);
+        self.emit_clif(func_index, &compiler.cx.codegen_context.func);
+        self.finish_wasm_function(compiler, func_index, &body)
+    }
 
-        let context = &mut compiler.cx.codegen_context;
-        context.func.signature = wasm_call_signature(isa, wasm_func_ty, &self.tunables);
-        context.func.name = UserFuncName::User(UserExternalName {
-            namespace: 0,
-            index: func_index.as_u32(),
-        });
-
-        if self.tunables.generate_native_debuginfo {
-            context.func.collect_debug_info();
-        }
-
-        let mut func_env =
-            FuncEnvironment::new(isa, translation, types, &self.tunables, self.wmemcheck);
-
-        // The `stack_limit` global value below is the implementation of stack
-        // overflow checks in Wasmtime.
-        //
-        // The Wasm spec defines that stack overflows will raise a trap, and
-        // there's also an added constraint where as an embedder you frequently
-        // are running host-provided code called from wasm. WebAssembly and
-        // native code currently share the same call stack, so Wasmtime needs to
-        // make sure that host-provided code will have enough call-stack
-        // available to it.
-        //
-        // The way that stack overflow is handled here is by adding a prologue
-        // check to all functions for how much native stack is remaining. The
-        // `VMContext` pointer is the first argument to all functions, and the
-        // first field of this structure is `*const VMRuntimeLimits` and the
-        // first field of that is the stack limit. Note that the stack limit in
-        // this case means "if the stack pointer goes below this, trap". Each
-        // function which consumes stack space or isn't a leaf function starts
-        // off by loading the stack limit, checking it against the stack
-        // pointer, and optionally traps.
-        //
-        // This manual check allows the embedder to give wasm a relatively
-        // precise amount of stack allocation. Using this scheme we reserve a
-        // chunk of stack for wasm code relative from where wasm code was
-        // called. This ensures that native code called by wasm should have
-        // native stack space to run, and the numbers of stack spaces here
-        // should all be configurable for various embeddings.
-        //
-        // Note that this check is independent of each thread's stack guard page
-        // here. If the stack guard page is reached that's still considered an
-        // abort for the whole program since the runtime limits configured by
-        // the embedder should cause wasm to trap before it reaches that
-        // (ensuring the host has enough space as well for its functionality).
-        let vmctx = context
-            .func
-            .create_global_value(ir::GlobalValueData::VMContext);
-        let interrupts_ptr = context.func.create_global_value(ir::GlobalValueData::Load {
-            base: vmctx,
-            offset: i32::try_from(func_env.offsets.vmctx_runtime_limits())
-                .unwrap()
-                .into(),
-            global_type: isa.pointer_type(),
-            flags: MemFlags::trusted().with_readonly(),
-        });
-        let stack_limit = context.func.create_global_value(ir::GlobalValueData::Load {
-            base: interrupts_ptr,
-            offset: i32::try_from(func_env.offsets.ptr.vmruntime_limits_stack_limit())
-                .unwrap()
-                .into(),
-            global_type: isa.pointer_type(),
-            flags: MemFlags::trusted(),
-        });
-        context.func.stack_limit = Some(stack_limit);
-        let FunctionBodyData { validator, body } = input;
-        let mut validator =
-            validator.into_validator(mem::take(&mut compiler.cx.validator_allocations));
-        compiler.cx.func_translator.translate_body(
-            &mut validator,
-            body.clone(),
-            &mut context.func,
-            &mut func_env,
-        )?;
+    fn inlining(&self) -> bool {
+        self.inlining
+    }
 
-        if let Some(path) = &self.clif_dir {
-            use std::io::Write;
+    fn translate_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        func_index: DefinedFuncIndex,
+        input: FunctionBodyData<'_>,
+        types: &ModuleTypesBuilder,
+    ) -> Result<Box<dyn Any + Send + Sync>, CompileError> {
+        let mut compiler = self.function_compiler();
+        let body =
+            self.translate_function_body(&mut compiler, translation, func_index, input, types)?;
+        let func = compiler.take_func();
+        let max_size = inline::max_inlined_callee_size(translation.profile.as_deref(), func_index);
+        let inlinable_size = inline::inlinable_size(&func, max_size);
+        Ok(Box::new(TranslatedFunction {
+            func,
+            body,
+            inlinable_size,
+        }))
+    }
 
-            let mut path = path.to_path_buf();
-            path.push(format!("wasm_func_{}", func_index.as_u32()));
-            path.set_extension("clif");
+    fn compile_translated_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        def_func_index: DefinedFuncIndex,
+        functions: &PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>,
+        _types: &ModuleTypesBuilder,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let module = &translation.module;
+        let translated = functions[def_func_index]
+            .downcast_ref::<TranslatedFunction>()
+            .unwrap();
+        let func_index = module.func_index(def_func_index);
 
-            let mut output = std::fs::File::create(path).unwrap();
-            write!(output, "{}", context.func.display()).unwrap();
+        let mut compiler = self.function_compiler();
+        let context = &mut compiler.cx.codegen_context;
+        context.func = translated.func.clone();
+
+        // Code which never ran while profiling isn't worth growing.
+        let caller_is_cold = translation
+            .profile
+            .as_ref()
+            .is_some_and(|profile| profile.is_cold(def_func_index));
+        if !caller_is_cold {
+            let inlined = inline::inline_calls(&mut context.func, |callee| {
+                let callee = functions[module.defined_func_index(callee)?]
+                    .downcast_ref::<TranslatedFunction>()
+                    .unwrap();
+                Some((&callee.func, callee.inlinable_size?))
+            });
+            log::trace!("{:?} had {} call sites inlined", func_index, inlined);
         }
 
-        let (info, func) = compiler.finish_with_info(Some((&body, &self.tunables)))?;
-
-        let timing = cranelift_codegen::timing::take_current();
-        log::debug!("{:?} translated in {:?}", func_index, timing.total());
-        log::trace!("{:?} timing info\n{}", func_index, timing);
-
-        Ok((info, Box::new(func)))
+        self.emit_clif(func_index, &context.func);
+        self.finish_wasm_function(compiler, func_index, &translated.body)
     }
 
     fn compile_array_to_wasm_trampoline(
@@ -696,6 +697,180 @@ fn compile_uncached<'a>(
 }
 
 impl Compiler {
+    /// Translates the wasm function `func_index` into CLIF within the codegen
+    /// context of `compiler`.
+    ///
+    /// Returns the information about the function's body that's needed once
+    /// it's compiled.
+    fn translate_function_body(
+        &self,
+        compiler: &mut FunctionCompiler<'_>,
+        translation: &ModuleTranslation<'_>,
+        func_index: DefinedFuncIndex,
+        input: FunctionBodyData<'_>,
+        types: &ModuleTypesBuilder,
+    ) -> Result<FunctionBodyInfo, CompileError> {
+        let isa = &*self.isa;
+        let module = &translation.module;
+        let func_index = module.func_index(func_index);
+        let sig = translation.module.functions[func_index].signature;
+        let wasm_func_ty = &types[sig];
+
+        let context = &mut compiler.cx.codegen_context;
+        context.func.signature = wasm_call_signature(isa, wasm_func_ty, &self.tunables);
+        context.func.name = UserFuncName::User(UserExternalName {
+            namespace: 0,
+            index: func_index.as_u32(),
+        });
+
+        if self.tunables.generate_native_debuginfo {
+            context.func.collect_debug_info();
+        }
+
+        let mut func_env =
+            FuncEnvironment::new(isa, translation, types, &self.tunables, self.wmemcheck);
+
+        // The `stack_limit` global value below is the implementation of stack
+        // overflow checks in Wasmtime.
+        //
+        // The Wasm spec defines that stack overflows will raise a trap, and
+        // there's also an added constraint where as an embedder you frequently
+        // are running host-provided code called from wasm. WebAssembly and
+        // native code currently share the same call stack, so Wasmtime needs to
+        // make sure that host-provided code will have enough call-stack
+        // available to it.
+        //
+        // The way that stack overflow is handled here is by adding a prologue
+        // check to all functions for how much native stack is remaining. The
+        // `VMContext` pointer is the first argument to all functions, and the
+        // first field of this structure is `*const VMRuntimeLimits` and the
+        // first field of that is the stack limit. Note that the stack limit in
+        // this case means "if the stack pointer goes below this, trap". Each
+        // function which consumes stack space or isn't a leaf function starts
+        // off by loading the stack limit, checking it against the stack
+        // pointer, and optionally traps.
+        //
+        // This manual check allows the embedder to give wasm a relatively
+        // precise amount of stack allocation. Using this scheme we reserve a
+        // chunk of stack for wasm code relative from where wasm code was
+        // called. This ensures that native code called by wasm should have
+        // native stack space to run, and the numbers of stack spaces here
+        // should all be configurable for various embeddings.
+        //
+        // Note that this check is independent of each thread's stack guard page
+        // here. If the stack guard page is reached that's still considered an
+        // abort for the whole program since the runtime limits configured by
+        // the embedder should cause wasm to trap before it reaches that
+        // (ensuring the host has enough space as well for its functionality).
+        let vmctx = context
+            .func
+            .create_global_value(ir::GlobalValueData::VMContext);
+        let interrupts_ptr = context.func.create_global_value(ir::GlobalValueData::Load {
+            base: vmctx,
+            offset: i32::try_from(func_env.offsets.vmctx_runtime_limits())
+                .unwrap()
+                .into(),
+            global_type: isa.pointer_type(),
+            flags: MemFlags::trusted().with_readonly(),
+        });
+        let stack_limit = context.func.create_global_value(ir::GlobalValueData::Load {
+            base: interrupts_ptr,
+            offset: i32::try_from(func_env.offsets.ptr.vmruntime_limits_stack_limit())
+                .unwrap()
+                .into(),
+            global_type: isa.pointer_type(),
+            flags: MemFlags::trusted(),
+        });
+        context.func.stack_limit = Some(stack_limit);
+        let FunctionBodyData { validator, body } = input;
+        let locals = if self.tunables.generate_native_debuginfo {
+            local_types(&func_env, wasm_func_ty, &body)?
+        } else {
+            Box::default()
+        };
+        let mut validator =
+            validator.into_validator(mem::take(&mut compiler.cx.validator_allocations));
+        compiler.cx.func_translator.translate_body(
+            &mut validator,
+            body.clone(),
+            &mut context.func,
+            &mut func_env,
+        )?;
+
+        let data = body.get_binary_reader();
+        Ok(FunctionBodyInfo {
+            range: (
+                data.original_position() as u32,
+                data.bytes_remaining() as u32,
+            ),
+            locals,
+            operand_stacks: func_env.take_operand_stacks().into(),
+        })
+    }
+
+    /// Writes `func` to the configured CLIF output directory, if any.
+    fn emit_clif(&self, func_index: FuncIndex, func: &ir::Function) {
+        self.emit_clif_file(func_index, "clif", func.display());
+    }
+
+    /// Writes the optimized CLIF and lowered VCode of a compiled function to
+    /// the configured CLIF output directory, if any.
+    ///
+    /// Each line of VCode is prefixed with the wasm offset of its
+    /// instruction in the same `@xxxx` form as CLIF.
+    fn emit_compiled_clif(&self, func_index: FuncIndex, func: &ir::Function, code: &CompiledCode) {
+        self.emit_clif_file(func_index, "opt.clif", func.display());
+        if let Some(vcode) = &code.vcode {
+            use std::fmt::Write;
+
+            let srclocs = code.buffer.get_srclocs_sorted();
+            let mut annotated = String::new();
+            for (line, offset) in vcode.lines().zip(&code.vcode_offsets) {
+                let loc = offset.and_then(|offset| {
+                    let i = srclocs.partition_point(|s| s.end <= offset);
+                    srclocs
+                        .get(i)
+                        .filter(|s| s.start <= offset && !s.loc.is_default())
+                });
+                match loc {
+                    Some(s) => writeln!(annotated, "{} {line}", s.loc).unwrap(),
+                    None => writeln!(annotated, "{line}").unwrap(),
+                }
+            }
+            self.emit_clif_file(func_index, "vcode", annotated);
+        }
+    }
+
+    fn emit_clif_file(&self, func_index: FuncIndex, extension: &str, contents: impl fmt::Display) {
+        if let Some(path) = &self.clif_dir {
+            use std::io::Write;
+
+            let mut path = path.to_path_buf();
+            path.push(format!("wasm_func_{}", func_index.as_u32()));
+            path.set_extension(extension);
+
+            let mut output = std::fs::File::create(path).unwrap();
+            write!(output, "{}", contents).unwrap();
+        }
+    }
+
+    /// Compiles the wasm function whose CLIF is held by `compiler`.
+    fn finish_wasm_function(
+        &self,
+        compiler: FunctionCompiler<'_>,
+        func_index: FuncIndex,
+        body: &FunctionBodyInfo,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let (info, func) =
+            compiler.finish_with_info(Some((body, &self.tunables)), Some(func_index))?;
+
+        let timing = cranelift_codegen::timing::take_current();
+        log::debug!("{:?} translated in {:?}", func_index, timing.total());
+        log::trace!("{:?} timing info\n{}", func_index, timing);
+
+        Ok((info, Box::new(func)))
+    }
+
     /// Creates a trampoline for calling a host function callee defined with the
     /// "array" calling convention from a native calling convention caller.
     ///
@@ -1011,21 +1186,46 @@ impl FunctionCompiler<'_> {
         (builder, block0)
     }
 
+    /// Takes the function held by this compiler's codegen context, returning
+    /// the context itself to the pool for reuse.
+    fn take_func(mut self) -> ir::Function {
+        let func = mem::replace(&mut self.cx.codegen_context.func, ir::Function::new());
+        self.compiler.contexts.lock().unwrap().push(self.cx);
+        func
+    }
+
     fn finish(self) -> Result<CompiledFunction<CompiledFuncEnv>, CompileError> {
-        let (info, func) = self.finish_with_info(None)?;
+        let (info, func) = self.finish_with_info(None, None)?;
         assert!(info.stack_maps.is_empty());
         Ok(func)
     }
 
+    /// Compiles the CLIF held by this compiler. If `func_index` is given and
+    /// CLIF output is enabled the optimized CLIF and VCode are emitted too.
     fn finish_with_info(
         mut self,
-        body_and_tunables: Option<(&FunctionBody<'_>, &Tunables)>,
+        body_and_tunables: Option<(&FunctionBodyInfo, &Tunables)>,
+        func_index: Option<FuncIndex>,
     ) -> Result<(WasmFunctionInfo, CompiledFunction<CompiledFuncEnv>), CompileError> {
         let context = &mut self.cx.codegen_context;
         let isa = &*self.compiler.isa;
-        let (_, _code_buf) =
-            compile_maybe_cached(context, isa, self.cx.incremental_cache_ctx.as_mut())?;
+        let emit_clif = func_index
+            .filter(|_| self.compiler.clif_dir.is_some() && self.compiler.emit_compiled_clif);
+
+        // Functions loaded from the incremental cache are never optimized or
+        // lowered, so the cache is bypassed while their compiled CLIF is being
+        // emitted.
+        context.set_disasm(emit_clif.is_some());
+        let cache_ctx = match emit_clif {
+            Some(_) => None,
+            None => self.cx.incremental_cache_ctx.as_mut(),
+        };
+        let (_, _code_buf) = compile_maybe_cached(context, isa, cache_ctx)?;
         let compiled_code = context.compiled_code().unwrap();
+        if let Some(func_index) = emit_clif {
+            self.compiler
+                .emit_compiled_clif(func_index, &context.func, compiled_code);
+        }
 
         // Give wasm functions, user defined code, a "preferred" alignment
         // instead of the minimum alignment as this can help perf in niche
@@ -1044,14 +1244,8 @@ impl FunctionCompiler<'_> {
             CompiledFunction::new(compiled_code.buffer.clone(), env, alignment);
 
         if let Some((body, tunables)) = body_and_tunables {
-            let data = body.get_binary_reader();
-            let offset = data.original_position();
-            let len = data.bytes_remaining();
-            compiled_function.set_address_map(
-                offset as u32,
-                len as u32,
-                tunables.generate_address_map,
-            );
+            let (offset, len) = body.range;
+            compiled_function.set_address_map(offset, len, tunables.generate_address_map);
         }
 
         if isa.flags().unwind_info() {
@@ -1064,10 +1258,19 @@ impl FunctionCompiler<'_> {
             }
         }
 
-        if body_and_tunables
-            .map(|(_, t)| t.generate_native_debuginfo)
-            .unwrap_or(false)
-        {
+        let mut locals = Box::default();
+        let mut local_slots = Vec::new();
+        let mut operand_slots = Vec::new();
+        let mut operand_stack_heights = Vec::new();
+        if let Some((body, _)) = body_and_tunables.filter(|(_, t)| t.generate_native_debuginfo) {
+            locals = body.locals.clone();
+            local_slots =
+                value_labels_to_local_slots(&compiled_code.value_labels_ranges, body.locals.len());
+            (operand_stack_heights, operand_slots) = native_operand_stacks(
+                compiled_code.buffer.get_srclocs_sorted(),
+                &compiled_code.value_labels_ranges,
+                &body.operand_stacks,
+            );
             compiled_function.set_value_labels_ranges(compiled_code.value_labels_ranges.clone());
 
             // DWARF debugging needs the CFA-based unwind information even on Windows.
@@ -1094,6 +1297,10 @@ impl FunctionCompiler<'_> {
             WasmFunctionInfo {
                 start_srcloc: compiled_function.metadata().address_map.start_srcloc,
                 stack_maps: stack_maps.into(),
+                locals,
+                local_slots: local_slots.into(),
+                operand_slots: operand_slots.into(),
+                operand_stack_heights: operand_stack_heights.into(),
             },
             compiled_function,
         ))
@@ -1124,6 +1331,124 @@ fn mach_stack_maps_to_stack_maps(mach_stack_maps: &[MachStackMap]) -> Vec<StackM
     stack_maps
 }
 
+/// Returns the types of the locals of a function, including its parameters.
+fn local_types(
+    env: &FuncEnvironment<'_>,
+    ty: &WasmFuncType,
+    body: &FunctionBody<'_>,
+) -> WasmResult<Box<[WasmType]>> {
+    let mut locals = ty.params().to_vec();
+    let mut reader = body.get_locals_reader()?;
+    for _ in 0..reader.get_count() {
+        let (count, ty) = reader.read()?;
+        let ty = env.convert_valtype(ty);
+        locals.extend(std::iter::repeat(ty).take(count as usize));
+    }
+    Ok(locals.into())
+}
+
+fn value_labels_to_local_slots(
+    ranges: &ValueLabelsRanges,
+    num_locals: usize,
+) -> Vec<LocalSlotInformation> {
+    // Only locals which were spilled to the stack are recorded. Registers
+    // aren't preserved once a frame makes a call, so they can't be recovered
+    // for anything but the youngest frame of a trap.
+    let mut slots = Vec::new();
+    for (label, ranges) in ranges {
+        let local = label.as_u32();
+        if local as usize >= num_locals {
+            continue;
+        }
+        for range in ranges {
+            if let LabelValueLoc::CFAOffset(offset) = range.loc {
+                if let Ok(cfa_offset) = i32::try_from(offset) {
+                    slots.push(LocalSlotInformation {
+                        local,
+                        code_start: range.start,
+                        code_end: range.end,
+                        cfa_offset,
+                    });
+                }
+            }
+        }
+    }
+    slots.sort_unstable_by_key(|slot| (slot.code_start, slot.local));
+    slots
+}
+
+/// Maps the operand stack before each wasm operator, keyed by the operator's
+/// wasm offset, to the native code generated for it, giving the height of the
+/// stack over that code along with the stack slots its values are held in.
+fn native_operand_stacks(
+    srclocs: &[MachSrcLoc<Final>],
+    ranges: &ValueLabelsRanges,
+    stacks: &[(u32, Box<[Option<OperandSource>]>)],
+) -> (Vec<OperandStackHeight>, Vec<OperandSlotInformation>) {
+    let mut native_heights = Vec::<OperandStackHeight>::new();
+    let mut slots = Vec::new();
+    for srcloc in srclocs {
+        if srcloc.loc.is_default() {
+            continue;
+        }
+        let offset = srcloc.loc.bits();
+        let Ok(i) = stacks.binary_search_by_key(&offset, |(offset, _)| *offset) else {
+            continue;
+        };
+        let stack = &stacks[i].1;
+        let height = u32::try_from(stack.len()).unwrap();
+        if native_heights.last().map(|h| h.height) != Some(height) {
+            native_heights.push(OperandStackHeight {
+                code_start: srcloc.start,
+                height,
+            });
+        }
+
+        // Like locals, only operands which were spilled to the stack are
+        // recorded. The ranges of their labels may extend past the code where
+        // they're on the stack, so they're limited to the operator's code.
+        for (depth, source) in stack.iter().enumerate() {
+            let Some(source) = source else {
+                continue;
+            };
+            for range in ranges.get(&source.label).into_iter().flatten() {
+                let (code_start, code_end) =
+                    (range.start.max(srcloc.start), range.end.min(srcloc.end));
+                if code_start >= code_end {
+                    continue;
+                }
+                if let LabelValueLoc::CFAOffset(offset) = range.loc {
+                    if let Ok(cfa_offset) = i32::try_from(offset) {
+                        slots.push(OperandSlotInformation {
+                            depth: depth as u32,
+                            ty: source.ty,
+                            code_start,
+                            code_end,
+                            cfa_offset,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Merge the slots of a value which is held in the same place over the code
+    // of consecutive operators.
+    slots.sort_unstable_by_key(|slot: &OperandSlotInformation| (slot.depth, slot.code_start));
+    slots.dedup_by(|next, prev| {
+        let merge = next.depth == prev.depth
+            && next.ty == prev.ty
+            && next.cfa_offset == prev.cfa_offset
+            && next.code_start == prev.code_end;
+        if merge {
+            prev.code_end = next.code_end;
+        }
+        merge
+    });
+    slots.sort_unstable_by_key(|slot| (slot.code_start, slot.depth));
+    (native_heights, slots)
+}
+
 fn declare_and_call(
     builder: &mut FunctionBuilder,
     signature: ir::Signature,
@@ -0,0 +1,435 @@
+//! Inlining of direct calls between functions defined in the same module.
+//!
+//! When inlining is enabled all functions of a module are first translated to
+//! CLIF. Each function is then compiled with its direct calls to small leaf
+//! functions of the same module replaced by a copy of the callee's body.
+//!
+//! Only direct `call` instructions to functions defined in the module are
+//! candidates. Calls to imported functions, `call_indirect`, and `call_ref`
+//! have no statically-known callee body and are left alone, as are tail calls
+//! since an inlined `return_call` would return from the caller instead.
+
+use cranelift_codegen::ir::{
+    self, Block, BlockCall, ExternalName, FuncRef, GlobalValue, GlobalValueData, Inst, InstBuilder,
+    InstructionData, Opcode, SigRef, StackSlot, UserExternalName, Value,
+};
+use cranelift_entity::packed_option::ReservedValue;
+use cranelift_entity::SecondaryMap;
+use wasmtime_environ::{DefinedFuncIndex, FuncIndex, ModuleProfile};
+
+/// The maximum number of instructions a callee may contain to be inlined.
+const MAX_INLINED_CALLEE_SIZE: usize = 64;
+
+/// The maximum number of instructions a callee may contain to be inlined when
+/// an execution profile shows that it is hot.
+const MAX_INLINED_HOT_CALLEE_SIZE: usize = 256;
+
+/// The maximum number of instructions inlining may add to a single caller.
+const MAX_INLINED_SIZE_PER_CALLER: usize = 2048;
+
+/// Returns the maximum size of the defined function `func` for it to be
+/// inlined, as passed to `inlinable_size`.
+///
+/// Without a profile this is a fixed limit. With one, functions which never
+/// ran aren't worth growing their callers for and hot functions are allowed to
+/// be larger.
+pub(super) fn max_inlined_callee_size(
+    profile: Option<&ModuleProfile>,
+    func: DefinedFuncIndex,
+) -> usize {
+    match profile {
+        None => MAX_INLINED_CALLEE_SIZE,
+        Some(profile) if profile.is_cold(func) => 0,
+        Some(profile) if profile.is_hot(func) => MAX_INLINED_HOT_CALLEE_SIZE,
+        Some(_) => MAX_INLINED_CALLEE_SIZE,
+    }
+}
+
+/// Returns the number of instructions in `func` if it is a candidate for
+/// being inlined into its callers, or `None` otherwise.
+///
+/// Candidates are functions of at most `max_size` instructions which don't
+/// make any calls themselves. Being a leaf function means inlining never
+/// recurses and a caller only ever needs a single pass over its original call
+/// sites.
+pub(super) fn inlinable_size(func: &ir::Function, max_size: usize) -> Option<usize> {
+    // Entities which only show up with features that aren't used in wasm
+    // function bodies, or which carry proof-carrying-code facts that we don't
+    // attempt to transfer, disqualify the function.
+    if !func.dynamic_stack_slots.is_empty()
+        || !func.dfg.dynamic_types.is_empty()
+        || !func.tables.is_empty()
+        || !func.memory_types.is_empty()
+    {
+        return None;
+    }
+
+    let mut size = 0;
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            size += 1;
+            if size > max_size || func.dfg.insts[inst].opcode().is_call() {
+                return None;
+            }
+        }
+    }
+    Some(size)
+}
+
+/// Inlines direct calls within `func` to the callees for which `lookup`
+/// returns a body along with that body's size, as computed by
+/// `inlinable_size`.
+///
+/// Returns the number of call sites that were inlined.
+pub(super) fn inline_calls<'a>(
+    func: &mut ir::Function,
+    lookup: impl Fn(FuncIndex) -> Option<(&'a ir::Function, usize)>,
+) -> usize {
+    // Collect all call sites up front as inlining modifies the layout. Note
+    // that inlined callees are leaf functions so there are never any new call
+    // sites to consider afterwards.
+    let mut sites = Vec::new();
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            if let InstructionData::Call {
+                opcode: Opcode::Call,
+                func_ref,
+                ..
+            } = func.dfg.insts[inst]
+            {
+                if let Some(callee) = callee_index(func, func_ref) {
+                    sites.push((inst, callee));
+                }
+            }
+        }
+    }
+
+    let mut budget = MAX_INLINED_SIZE_PER_CALLER;
+    let mut inlined = 0;
+    for (call, callee) in sites {
+        let (callee, size) = match lookup(callee) {
+            Some(pair) => pair,
+            None => continue,
+        };
+        if size > budget {
+            continue;
+        }
+        budget -= size;
+        Inliner::new(func, callee).inline(call);
+        inlined += 1;
+    }
+    inlined
+}
+
+/// Returns the index of the wasm function that `func_ref` refers to, if any.
+fn callee_index(func: &ir::Function, func_ref: FuncRef) -> Option<FuncIndex> {
+    match func.dfg.ext_funcs[func_ref].name {
+        ExternalName::User(name) => {
+            let UserExternalName { namespace, index } = func.params.user_named_funcs()[name];
+            if namespace == 0 {
+                Some(FuncIndex::from_u32(index))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// State for copying a single callee's body into a caller.
+///
+/// The maps here translate entities of the callee to their copies within the
+/// caller.
+struct Inliner<'a> {
+    func: &'a mut ir::Function,
+    callee: &'a ir::Function,
+    return_block: Block,
+    blocks: SecondaryMap<Block, Block>,
+    values: SecondaryMap<Value, Value>,
+    sig_refs: SecondaryMap<SigRef, SigRef>,
+    func_refs: SecondaryMap<FuncRef, FuncRef>,
+    global_values: SecondaryMap<GlobalValue, GlobalValue>,
+    stack_slots: SecondaryMap<StackSlot, StackSlot>,
+}
+
+impl<'a> Inliner<'a> {
+    fn new(func: &'a mut ir::Function, callee: &'a ir::Function) -> Self {
+        Inliner {
+            func,
+            callee,
+            return_block: Block::reserved_value(),
+            blocks: SecondaryMap::with_default(Block::reserved_value()),
+            values: SecondaryMap::with_default(Value::reserved_value()),
+            sig_refs: SecondaryMap::with_default(SigRef::reserved_value()),
+            func_refs: SecondaryMap::with_default(FuncRef::reserved_value()),
+            global_values: SecondaryMap::with_default(GlobalValue::reserved_value()),
+            stack_slots: SecondaryMap::with_default(StackSlot::reserved_value()),
+        }
+    }
+
+    /// Replaces the `call` instruction with a copy of the callee's body.
+    fn inline(mut self, call: Inst) {
+        let callee = self.callee;
+
+        // Everything after the call moves into a new block which receives the
+        // callee's return values as block parameters. The call's results are
+        // reattached as those parameters so existing uses remain valid.
+        self.return_block = self.func.dfg.make_block();
+        let next = self.func.layout.next_inst(call).unwrap();
+        self.func.layout.split_block(self.return_block, next);
+        let results = self.func.dfg.detach_results(call);
+        for i in 0..results.len(&self.func.dfg.value_lists) {
+            let result = results.get(i, &self.func.dfg.value_lists).unwrap();
+            self.func.dfg.attach_block_param(self.return_block, result);
+        }
+        let args = self.func.dfg.inst_args(call).to_vec();
+
+        self.copy_entities();
+
+        // Create all blocks and instructions first, and only afterwards rewrite
+        // the operands of the copied instructions, since uses of a value may
+        // come before its definition in layout order.
+        let mut copied = Vec::new();
+        for block in callee.layout.blocks() {
+            let new_block = self.blocks[block];
+            self.func.layout.insert_block(new_block, self.return_block);
+            if callee.layout.is_cold(block) {
+                self.func.layout.set_cold(new_block);
+            }
+            for inst in callee.layout.block_insts(block) {
+                let data = self.copy_inst_data(inst);
+                let new_inst = self.func.dfg.make_inst(data);
+                self.func.layout.append_inst(new_inst, new_block);
+                let srcloc = callee.srcloc(inst);
+                if !srcloc.is_default() {
+                    self.func.set_srcloc(new_inst, srcloc);
+                }
+                let ctrl_typevar = callee.dfg.ctrl_typevar(inst);
+                self.func.dfg.make_inst_results(new_inst, ctrl_typevar);
+                let old_results = callee.dfg.inst_results(inst);
+                let new_results = self.func.dfg.inst_results(new_inst);
+                for (old, new) in old_results.iter().zip(new_results) {
+                    self.values[*old] = *new;
+                }
+                copied.push(new_inst);
+            }
+        }
+        let values = &self.values;
+        for inst in copied {
+            self.func
+                .dfg
+                .map_inst_values(inst, |_, v| values[callee.dfg.resolve_aliases(v)]);
+        }
+
+        // Finally the call itself becomes a jump to the callee's entry block.
+        let entry = self.blocks[callee.layout.entry_block().unwrap()];
+        self.func.dfg.replace(call).jump(entry, &args);
+    }
+
+    /// Copies the non-instruction entities of the callee into the caller:
+    /// signatures, function references, global values, stack slots, and blocks
+    /// along with their parameters.
+    fn copy_entities(&mut self) {
+        let callee = self.callee;
+        for (sig_ref, sig) in callee.dfg.signatures.iter() {
+            self.sig_refs[sig_ref] = self.func.import_signature(sig.clone());
+        }
+
+        for (func_ref, data) in callee.dfg.ext_funcs.iter() {
+            let data = ir::ExtFuncData {
+                name: self.external_name(&data.name),
+                signature: self.sig_refs[data.signature],
+                colocated: data.colocated,
+            };
+            self.func_refs[func_ref] = self.func.import_function(data);
+        }
+
+        // Global values may only refer to global values created before them,
+        // so a single pass in order suffices.
+        for (gv, data) in callee.global_values.iter() {
+            let data = match data {
+                GlobalValueData::Load {
+                    base,
+                    offset,
+                    global_type,
+                    flags,
+                } => GlobalValueData::Load {
+                    base: self.global_values[*base],
+                    offset: *offset,
+                    global_type: *global_type,
+                    flags: *flags,
+                },
+                GlobalValueData::IAddImm {
+                    base,
+                    offset,
+                    global_type,
+                } => GlobalValueData::IAddImm {
+                    base: self.global_values[*base],
+                    offset: *offset,
+                    global_type: *global_type,
+                },
+                GlobalValueData::Symbol {
+                    name,
+                    offset,
+                    colocated,
+                    tls,
+                } => GlobalValueData::Symbol {
+                    name: self.external_name(name),
+                    offset: *offset,
+                    colocated: *colocated,
+                    tls: *tls,
+                },
+                other @ (GlobalValueData::VMContext
+                | GlobalValueData::DynScaleTargetConst { .. }) => other.clone(),
+            };
+            self.global_values[gv] = self.func.create_global_value(data);
+        }
+
+        for (slot, data) in callee.sized_stack_slots.iter() {
+            self.stack_slots[slot] = self.func.create_sized_stack_slot(data.clone());
+        }
+
+        for block in callee.layout.blocks() {
+            let new_block = self.func.dfg.make_block();
+            self.blocks[block] = new_block;
+            for param in callee.dfg.block_params(block) {
+                let ty = callee.dfg.value_type(*param);
+                self.values[*param] = self.func.dfg.append_block_param(new_block, ty);
+            }
+        }
+    }
+
+    fn external_name(&mut self, name: &ExternalName) -> ExternalName {
+        match name {
+            ExternalName::User(name) => {
+                let name = self.callee.params.user_named_funcs()[*name].clone();
+                ExternalName::User(self.func.declare_imported_user_function(name))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// Returns a copy of the callee's instruction `inst` with all entities
+    /// other than values translated into the caller.
+    ///
+    /// Value operands still refer to the callee's values and are rewritten
+    /// once all instructions have been copied.
+    fn copy_inst_data(&mut self, inst: Inst) -> InstructionData {
+        let callee = self.callee;
+        let pool = &callee.dfg.value_lists;
+        match callee.dfg.insts[inst] {
+            InstructionData::MultiAry {
+                opcode: Opcode::Return,
+                args,
+            } => InstructionData::Jump {
+                opcode: Opcode::Jump,
+                destination: BlockCall::new(
+                    self.return_block,
+                    args.as_slice(pool),
+                    &mut self.func.dfg.value_lists,
+                ),
+            },
+            InstructionData::MultiAry { opcode, args } => InstructionData::MultiAry {
+                opcode,
+                args: ir::ValueList::from_slice(
+                    args.as_slice(pool),
+                    &mut self.func.dfg.value_lists,
+                ),
+            },
+            InstructionData::Jump {
+                opcode,
+                destination,
+            } => InstructionData::Jump {
+                opcode,
+                destination: self.block_call(destination),
+            },
+            InstructionData::Brif {
+                opcode,
+                arg,
+                blocks,
+            } => InstructionData::Brif {
+                opcode,
+                arg,
+                blocks: [self.block_call(blocks[0]), self.block_call(blocks[1])],
+            },
+            InstructionData::BranchTable { opcode, arg, table } => {
+                let data = &callee.dfg.jump_tables[table];
+                let default = self.block_call(data.default_block());
+                let entries = data
+                    .as_slice()
+                    .iter()
+                    .map(|call| self.block_call(*call))
+                    .collect::<Vec<_>>();
+                let table = self
+                    .func
+                    .create_jump_table(ir::JumpTableData::new(default, &entries));
+                InstructionData::BranchTable { opcode, arg, table }
+            }
+            InstructionData::FuncAddr { opcode, func_ref } => InstructionData::FuncAddr {
+                opcode,
+                func_ref: self.func_refs[func_ref],
+            },
+            InstructionData::UnaryGlobalValue {
+                opcode,
+                global_value,
+            } => InstructionData::UnaryGlobalValue {
+                opcode,
+                global_value: self.global_values[global_value],
+            },
+            InstructionData::UnaryConst {
+                opcode,
+                constant_handle,
+            } => {
+                let data = callee.dfg.constants.get(constant_handle).clone();
+                InstructionData::UnaryConst {
+                    opcode,
+                    constant_handle: self.func.dfg.constants.insert(data),
+                }
+            }
+            InstructionData::Shuffle { opcode, args, imm } => {
+                let data = callee.dfg.immediates[imm].clone();
+                InstructionData::Shuffle {
+                    opcode,
+                    args,
+                    imm: self.func.dfg.immediates.push(data),
+                }
+            }
+            InstructionData::StackLoad {
+                opcode,
+                stack_slot,
+                offset,
+            } => InstructionData::StackLoad {
+                opcode,
+                stack_slot: self.stack_slots[stack_slot],
+                offset,
+            },
+            InstructionData::StackStore {
+                opcode,
+                arg,
+                stack_slot,
+                offset,
+            } => InstructionData::StackStore {
+                opcode,
+                arg,
+                stack_slot: self.stack_slots[stack_slot],
+                offset,
+            },
+            // Calls are never present in inlined callees, and the remaining
+            // entity-carrying formats are excluded by `inlinable_size`, so
+            // everything else only holds inline values and immediates.
+            data => {
+                debug_assert!(!data.opcode().is_call());
+                data
+            }
+        }
+    }
+
+    fn block_call(&mut self, call: BlockCall) -> BlockCall {
+        let pool = &self.callee.dfg.value_lists;
+        BlockCall::new(
+            self.blocks[call.block(pool)],
+            call.args_slice(pool),
+            &mut self.func.dfg.value_lists,
+        )
+    }
+}
@@ -21,8 +21,9 @@ use std::convert::TryFrom;
 use std::mem;
 use wasmparser::Operator;
 use wasmtime_environ::{
-    BuiltinFunctionIndex, MemoryPlan, MemoryStyle, Module, ModuleTranslation, ModuleTypesBuilder,
-    PtrSize, TableStyle, Tunables, TypeConvert, VMOffsets, WASM_PAGE_SIZE,
+    BuiltinFunctionIndex, DefinedFuncIndex, FunctionProfile, MemoryPlan, MemoryStyle, Module,
+    ModuleTranslation, ModuleTypesBuilder, PtrSize, TableStyle, Tunables, TypeConvert, VMOffsets,
+    WASM_PAGE_SIZE,
 };
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 
@@ -161,6 +162,26 @@ pub struct FuncEnvironment<'module_environment> {
 
     fuel_consumed: i64,
 
+    /// The number of conditional branches translated so far, which is the
+    /// index of the next branch site in this function's execution profile.
+    pgo_branch_sites: u32,
+
+    /// The execution profile previously collected for this function, if any.
+    pgo_profile: Option<&'module_environment FunctionProfile>,
+
+    /// The values on the operand stack after the last operator, along with
+    /// where each of them can be found, when generating native debug info.
+    operands: Vec<(Value, Option<OperandSource>)>,
+
+    /// The number of instructions and the current block before the operator
+    /// being translated, when generating native debug info.
+    operator_start: (usize, Option<ir::Block>),
+
+    /// The wasm offset of each operator translated so far along with where
+    /// the values on the operand stack before it can be found, when
+    /// generating native debug info.
+    operand_stacks: Vec<(u32, Box<[Option<OperandSource>]>)>,
+
     #[cfg(feature = "wmemcheck")]
     wmemcheck: bool,
 }
@@ -206,11 +227,100 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             // Start with at least one fuel being consumed because even empty
             // functions should consume at least some fuel.
             fuel_consumed: 1,
+            pgo_branch_sites: 0,
+            pgo_profile: None,
+            operands: Vec::new(),
+            operator_start: (0, None),
+            operand_stacks: Vec::new(),
             #[cfg(feature = "wmemcheck")]
             wmemcheck,
         }
     }
 
+    /// Takes the wasm offset of each translated operator along with where the
+    /// values on the operand stack before it can be found, sorted by offset.
+    pub(crate) fn take_operand_stacks(&mut self) -> Vec<(u32, Box<[Option<OperandSource>]>)> {
+        mem::take(&mut self.operand_stacks)
+    }
+
+    /// Records where the values on the operand stack before the operator
+    /// being translated can be found.
+    fn record_operand_stack(&mut self, builder: &FunctionBuilder, state: &FuncTranslationState) {
+        self.operator_start = (builder.func.dfg.num_insts(), builder.current_block());
+        if !state.reachable() {
+            return;
+        }
+        let stack = state.operand_stack();
+        let sources = stack
+            .iter()
+            .enumerate()
+            .map(|(depth, val)| match self.operands.get(depth) {
+                Some((operand, source)) if operand == val => *source,
+                _ => None,
+            })
+            .collect();
+        self.operand_stacks.push((builder.srcloc().bits(), sources));
+    }
+
+    /// Labels the values pushed onto the operand stack by the last operator,
+    /// so that their locations show up in the value labels ranges of the
+    /// compiled code.
+    ///
+    /// A value label is found in the last value defined with it, in the order
+    /// of the compiled code, so only values which the operator defined can be
+    /// labeled with their depth. Values which were read from a local are
+    /// found wherever the local is instead, until the local is set, and the
+    /// remaining ones can't be found.
+    fn label_operands(
+        &mut self,
+        op: &Operator,
+        builder: &mut FunctionBuilder,
+        state: &FuncTranslationState,
+    ) {
+        let stack = state.operand_stack();
+        let unchanged = self
+            .operands
+            .iter()
+            .zip(stack)
+            .take_while(|((a, _), b)| a == *b)
+            .count();
+        self.operands.truncate(unchanged);
+        if let Operator::LocalSet { local_index } | Operator::LocalTee { local_index } = *op {
+            let label = ir::ValueLabel::from_u32(local_index);
+            for (_, source) in &mut self.operands {
+                if source.map(|s| s.label) == Some(label) {
+                    *source = None;
+                }
+            }
+        }
+
+        let (first_inst, first_block) = self.operator_start;
+        for (depth, &val) in stack.iter().enumerate().skip(unchanged) {
+            let ty = operand_type(builder.func.dfg.value_type(val));
+            let defined = match builder.func.dfg.value_def(val) {
+                ir::ValueDef::Result(inst, _) => inst.index() >= first_inst,
+                ir::ValueDef::Param(block, _) => {
+                    Some(block) == builder.current_block() && Some(block) != first_block
+                }
+                _ => false,
+            };
+            let label = match *op {
+                _ if defined => operand_label(depth),
+                Operator::LocalGet { local_index } | Operator::LocalTee { local_index }
+                    if depth + 1 == stack.len() =>
+                {
+                    Some(ir::ValueLabel::from_u32(local_index))
+                }
+                _ => None,
+            };
+            let source = ty.zip(label).map(|(ty, label)| OperandSource { label, ty });
+            if let (true, Some(source)) = (defined, source) {
+                builder.set_val_label(val, source.label);
+            }
+            self.operands.push((val, source));
+        }
+    }
+
     fn pointer_type(&self) -> ir::Type {
         self.isa.pointer_type()
     }
@@ -916,6 +1026,34 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             .call_indirect(malloc_start_sig, malloc_start, &[vmctx]);
     }
 
+    fn pgo_enter_function(&mut self, builder: &mut FunctionBuilder, func: DefinedFuncIndex) {
+        let pgo_enter_function_sig = self
+            .builtin_function_signatures
+            .pgo_enter_function(builder.func);
+        let (vmctx, pgo_enter_function) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::pgo_enter_function(),
+        );
+        let func = builder.ins().iconst(I32, i64::from(func.as_u32()));
+        builder
+            .ins()
+            .call_indirect(pgo_enter_function_sig, pgo_enter_function, &[vmctx, func]);
+    }
+
+    fn pgo_branch(&mut self, builder: &mut FunctionBuilder, site: u32, condition: ir::Value) {
+        let func = self.current_defined_func_index(builder);
+        let pgo_branch_sig = self.builtin_function_signatures.pgo_branch(builder.func);
+        let (vmctx, pgo_branch) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::pgo_branch(),
+        );
+        let func = builder.ins().iconst(I32, i64::from(func.as_u32()));
+        let site = builder.ins().iconst(I32, i64::from(site));
+        builder
+            .ins()
+            .call_indirect(pgo_branch_sig, pgo_branch, &[vmctx, func, site, condition]);
+    }
+
     fn check_free_start(&mut self, builder: &mut FunctionBuilder) {
         let free_start_sig = self.builtin_function_signatures.free_start(builder.func);
         let (vmctx, free_start) = self.translate_load_builtin_function_address(
@@ -927,13 +1065,22 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             .call_indirect(free_start_sig, free_start, &[vmctx]);
     }
 
-    fn current_func_name(&self, builder: &mut FunctionBuilder) -> Option<&str> {
-        let func_index = match &builder.func.name {
+    fn current_func_index(&self, builder: &FunctionBuilder) -> FuncIndex {
+        match &builder.func.name {
             UserFuncName::User(user) => FuncIndex::from_u32(user.index),
             _ => {
                 panic!("function name not a UserFuncName::User as expected")
             }
-        };
+        }
+    }
+
+    fn current_defined_func_index(&self, builder: &FunctionBuilder) -> DefinedFuncIndex {
+        let func_index = self.current_func_index(builder);
+        self.module.defined_func_index(func_index).unwrap()
+    }
+
+    fn current_func_name(&self, builder: &mut FunctionBuilder) -> Option<&str> {
+        let func_index = self.current_func_index(builder);
         self.translation
             .debuginfo
             .name_section
@@ -2545,6 +2692,9 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         if self.tunables.consume_fuel {
             self.fuel_before_op(op, builder, state.reachable());
         }
+        if self.tunables.generate_native_debuginfo {
+            self.record_operand_stack(builder, state);
+        }
         Ok(())
     }
 
@@ -2557,6 +2707,9 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_after_op(op, builder);
         }
+        if self.tunables.generate_native_debuginfo && state.reachable() {
+            self.label_operands(op, builder, state);
+        }
         Ok(())
     }
 
@@ -2590,6 +2743,16 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
             self.epoch_function_entry(builder);
         }
 
+        let func = self.current_defined_func_index(builder);
+        let translation = self.translation;
+        self.pgo_profile = translation
+            .profile
+            .as_ref()
+            .and_then(|profile| profile.function(func));
+        if self.tunables.pgo_instrumentation {
+            self.pgo_enter_function(builder, func);
+        }
+
         let func_name = self.current_func_name(builder);
         if func_name == Some("malloc") {
             self.check_malloc_start(builder);
@@ -2621,6 +2784,22 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         Ok(())
     }
 
+    fn before_conditional_branch(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        condition: ir::Value,
+    ) -> WasmResult<Option<bool>> {
+        let site = self.pgo_branch_sites;
+        self.pgo_branch_sites += 1;
+        if self.tunables.pgo_instrumentation {
+            self.pgo_branch(builder, site, condition);
+        }
+        Ok(self
+            .pgo_profile
+            .and_then(|profile| profile.branch(site as usize))
+            .and_then(|branch| branch.likely()))
+    }
+
     fn relaxed_simd_deterministic(&self) -> bool {
         self.tunables.relaxed_simd_deterministic
     }
@@ -2748,3 +2927,38 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         }
     }
 }
+
+/// Where a value on the operand stack can be found in the compiled code.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct OperandSource {
+    /// The label of the values whose locations hold the value.
+    pub label: ir::ValueLabel,
+    /// The type of the value.
+    pub ty: WasmType,
+}
+
+/// Value labels with this bit set label values on the operand stack, rather
+/// than locals, with the depth of the value on the stack in the remaining
+/// bits.
+const OPERAND_LABEL_BIT: u32 = 1 << 31;
+
+/// Returns the value label for values at `depth` on the operand stack.
+fn operand_label(depth: usize) -> Option<ir::ValueLabel> {
+    let depth = u32::try_from(depth)
+        .ok()
+        .filter(|d| d & OPERAND_LABEL_BIT == 0)?;
+    Some(ir::ValueLabel::from_u32(OPERAND_LABEL_BIT | depth))
+}
+
+/// Returns the type of an operand stack value of type `ty`, if it can be
+/// recovered for core dumps.
+fn operand_type(ty: ir::Type) -> Option<WasmType> {
+    match ty {
+        I32 => Some(WasmType::I32),
+        I64 => Some(WasmType::I64),
+        F32 => Some(WasmType::F32),
+        F64 => Some(WasmType::F64),
+        // Core dumps have no encoding for vectors or references.
+        _ => None,
+    }
+}
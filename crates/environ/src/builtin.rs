@@ -67,6 +67,11 @@ macro_rules! foreach_builtin_function {
             update_stack_pointer(vmctx: vmctx, value: i32);
             /// Invoked before memory.grow is called.
             update_mem_size(vmctx: vmctx, num_bytes: i32);
+            /// Invoked on function entry when collecting an execution profile.
+            pgo_enter_function(vmctx: vmctx, func: i32);
+            /// Invoked before a conditional branch when collecting an execution
+            /// profile.
+            pgo_branch(vmctx: vmctx, func: i32, site: i32, taken: i32);
         }
     };
 }
@@ -0,0 +1,522 @@
+//! Bytecode executed by Wasmtime's portable interpreter.
+//!
+//! When modules are compiled for the interpreter each defined function is
+//! translated to a compact register-based bytecode instead of native code. The
+//! bytecode of a function is placed in the text section of the compiled image
+//! just like native code would be, so the `wasm_call` pointer of an
+//! interpreted function points at the start of its bytecode.
+//!
+//! Every function starts with a [`FunctionHeader`] followed by its
+//! instructions. An instruction is an [`Op`] byte followed by its operands,
+//! all of which are little-endian and unaligned:
+//!
+//! * registers are `u16` indices into the function's frame,
+//! * indices (functions, globals, ...) and immediates are `u32`, except for
+//!   64-bit constants which are `u64`,
+//! * branch targets are `u32` byte offsets from the start of the function,
+//!   including its header.
+//!
+//! A frame holds one 64-bit register per wasm local, parameters first,
+//! followed by one register per slot of the operand stack. Values of 32-bit
+//! types are always zero-extended to 64 bits in registers. Calls pass their
+//! arguments in consecutive registers of the caller, which become the first
+//! registers of the callee's frame, and results are returned in the same
+//! registers.
+
+/// Register index within a function's frame.
+pub type Reg = u16;
+
+/// Layout information at the start of every interpreted function.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FunctionHeader {
+    /// Number of parameters of the function, stored in the first registers of
+    /// the frame.
+    pub num_params: u32,
+    /// Number of results of the function, returned in the first registers of
+    /// the frame.
+    pub num_results: u32,
+    /// Number of locals of the function, including its parameters. Locals
+    /// which aren't parameters are zeroed on entry.
+    pub num_locals: u32,
+    /// Total number of registers of the function's frame.
+    pub frame_size: u32,
+}
+
+impl FunctionHeader {
+    /// Size in bytes of an encoded header.
+    pub const SIZE: usize = 16;
+
+    /// Encodes this header.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.num_params.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.num_results.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.num_locals.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.frame_size.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a header previously encoded with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> FunctionHeader {
+        let word = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        FunctionHeader {
+            num_params: word(0),
+            num_results: word(4),
+            num_locals: word(8),
+            frame_size: word(12),
+        }
+    }
+}
+
+macro_rules! define_ops {
+    ($( $(#[$attr:meta])* $name:ident, )*) => {
+        /// An instruction of the interpreter's bytecode.
+        ///
+        /// The documentation of each variant lists its operands in the order
+        /// they're encoded, where `rN` operands are registers.
+        #[repr(u8)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Op {
+            $( $(#[$attr])* $name, )*
+        }
+
+        impl Op {
+            const ALL: &'static [Op] = &[$(Op::$name,)*];
+
+            /// Decodes an opcode byte, returning `None` for bytes which
+            /// aren't a valid opcode.
+            #[inline]
+            pub fn from_u8(byte: u8) -> Option<Op> {
+                Op::ALL.get(usize::from(byte)).copied()
+            }
+        }
+    };
+}
+
+define_ops! {
+    // Control flow.
+
+    /// `target`: continue at `target`.
+    Jump,
+    /// `cond, target`: continue at `target` if the 32-bit `cond` is non-zero.
+    BrIf,
+    /// `cond, target`: continue at `target` if the 32-bit `cond` is zero.
+    BrIfNot,
+    /// `index, count, target * (count + 1)`: continue at the `index`th target,
+    /// or at the last one if `index` is at least `count`.
+    BrTable,
+    /// Returns from the function, with its results in its first registers.
+    Return,
+    /// `func, base, params, results`: calls the function `func` with the
+    /// arguments in the `params` registers starting at `base`, which receive
+    /// the `results` results of the call. `params` and `results` are `u16`s.
+    Call,
+    /// `table, signature, index, base, params, results`: like `Call` but
+    /// calls the function at the 32-bit `index` of `table`, trapping if it is
+    /// null or doesn't have the module's `signature`.
+    CallIndirect,
+    /// Traps with `Trap::UnreachableCodeReached`.
+    Unreachable,
+    /// `cost`: adds the `u32` `cost` to the fuel consumed by the store.
+    ConsumeFuel,
+    /// Calls into the store, which refuels or traps, if all of its fuel has
+    /// been consumed. Emitted at the entry of functions and loops.
+    CheckFuel,
+    /// Calls into the store, which updates the deadline or traps, if the
+    /// engine's epoch has reached the store's deadline. Emitted at the entry
+    /// of functions and loops.
+    CheckEpoch,
+
+    // Moves and constants.
+
+    /// `dst, src`
+    Copy,
+    /// `dst, u32`
+    Const32,
+    /// `dst, u64`
+    Const64,
+    /// `dst, a, b, cond`: `dst = if cond != 0 { a } else { b }`.
+    Select,
+
+    // Globals.
+
+    /// `dst, global`: reads a 32-bit global.
+    GlobalGet32,
+    /// `dst, global`: reads a 64-bit global.
+    GlobalGet64,
+    /// `global, src`: writes a 32-bit global.
+    GlobalSet32,
+    /// `global, src`: writes a 64-bit global.
+    GlobalSet64,
+    /// `dst, global`: reads a `funcref` global.
+    GlobalGetRef,
+    /// `global, src`: writes a `funcref` global.
+    GlobalSetRef,
+
+    // Accesses of the default linear memory, all of which take an `offset`
+    // immediate which is added to the 32-bit address.
+
+    /// `dst, addr, offset`
+    Load8U,
+    /// `dst, addr, offset`
+    Load16U,
+    /// `dst, addr, offset`
+    Load32U,
+    /// `dst, addr, offset`
+    Load64,
+    /// `dst, addr, offset`
+    I32Load8S,
+    /// `dst, addr, offset`
+    I32Load16S,
+    /// `dst, addr, offset`
+    I64Load8S,
+    /// `dst, addr, offset`
+    I64Load16S,
+    /// `dst, addr, offset`
+    I64Load32S,
+    /// `addr, src, offset`
+    Store8,
+    /// `addr, src, offset`
+    Store16,
+    /// `addr, src, offset`
+    Store32,
+    /// `addr, src, offset`
+    Store64,
+    /// `dst`: the size of the default memory in pages.
+    MemorySize,
+    /// `dst, delta`: grows the default memory, returning the old size in
+    /// pages or -1.
+    MemoryGrow,
+    /// `dst, val, len`
+    MemoryFill,
+    /// `dst, src, len`
+    MemoryCopy,
+    /// `data, dst, src, len`
+    MemoryInit,
+    /// `data`
+    DataDrop,
+
+    // Tables and references. Only tables of `funcref`s are supported, whose
+    // values are pointers to `VMFuncRef`s or zero for null.
+
+    /// `dst, table, index`
+    TableGet,
+    /// `table, index, val`
+    TableSet,
+    /// `dst, table`
+    TableSize,
+    /// `dst, table, init, delta`: grows `table`, returning its old size or -1.
+    TableGrow,
+    /// `table, dst, val, len`
+    TableFill,
+    /// `dst_table, src_table, dst, src, len`
+    TableCopy,
+    /// `table, elem, dst, src, len`
+    TableInit,
+    /// `elem`
+    ElemDrop,
+    /// `dst, func`
+    RefFunc,
+    /// `dst, a`
+    RefIsNull,
+
+    // Integer operations, all `dst, a, b` for binary operators and `dst, a`
+    // for unary operators.
+
+    /// `dst, a, b`
+    I32Add,
+    /// `dst, a, b`
+    I32Sub,
+    /// `dst, a, b`
+    I32Mul,
+    /// `dst, a, b`
+    I32DivS,
+    /// `dst, a, b`
+    I32DivU,
+    /// `dst, a, b`
+    I32RemS,
+    /// `dst, a, b`
+    I32RemU,
+    /// `dst, a, b`
+    I32And,
+    /// `dst, a, b`
+    I32Or,
+    /// `dst, a, b`
+    I32Xor,
+    /// `dst, a, b`
+    I32Shl,
+    /// `dst, a, b`
+    I32ShrS,
+    /// `dst, a, b`
+    I32ShrU,
+    /// `dst, a, b`
+    I32Rotl,
+    /// `dst, a, b`
+    I32Rotr,
+    /// `dst, a, b`
+    I32Eq,
+    /// `dst, a, b`
+    I32Ne,
+    /// `dst, a, b`
+    I32LtS,
+    /// `dst, a, b`
+    I32LtU,
+    /// `dst, a, b`
+    I32GtS,
+    /// `dst, a, b`
+    I32GtU,
+    /// `dst, a, b`
+    I32LeS,
+    /// `dst, a, b`
+    I32LeU,
+    /// `dst, a, b`
+    I32GeS,
+    /// `dst, a, b`
+    I32GeU,
+    /// `dst, a`
+    I32Eqz,
+    /// `dst, a`
+    I32Clz,
+    /// `dst, a`
+    I32Ctz,
+    /// `dst, a`
+    I32Popcnt,
+    /// `dst, a`
+    I32Extend8S,
+    /// `dst, a`
+    I32Extend16S,
+    /// `dst, a`
+    I32WrapI64,
+
+    /// `dst, a, b`
+    I64Add,
+    /// `dst, a, b`
+    I64Sub,
+    /// `dst, a, b`
+    I64Mul,
+    /// `dst, a, b`
+    I64DivS,
+    /// `dst, a, b`
+    I64DivU,
+    /// `dst, a, b`
+    I64RemS,
+    /// `dst, a, b`
+    I64RemU,
+    /// `dst, a, b`
+    I64And,
+    /// `dst, a, b`
+    I64Or,
+    /// `dst, a, b`
+    I64Xor,
+    /// `dst, a, b`
+    I64Shl,
+    /// `dst, a, b`
+    I64ShrS,
+    /// `dst, a, b`
+    I64ShrU,
+    /// `dst, a, b`
+    I64Rotl,
+    /// `dst, a, b`
+    I64Rotr,
+    /// `dst, a, b`
+    I64Eq,
+    /// `dst, a, b`
+    I64Ne,
+    /// `dst, a, b`
+    I64LtS,
+    /// `dst, a, b`
+    I64LtU,
+    /// `dst, a, b`
+    I64GtS,
+    /// `dst, a, b`
+    I64GtU,
+    /// `dst, a, b`
+    I64LeS,
+    /// `dst, a, b`
+    I64LeU,
+    /// `dst, a, b`
+    I64GeS,
+    /// `dst, a, b`
+    I64GeU,
+    /// `dst, a`
+    I64Eqz,
+    /// `dst, a`
+    I64Clz,
+    /// `dst, a`
+    I64Ctz,
+    /// `dst, a`
+    I64Popcnt,
+    /// `dst, a`
+    I64Extend8S,
+    /// `dst, a`
+    I64Extend16S,
+    /// `dst, a`
+    I64Extend32S,
+    /// `dst, a`
+    I64ExtendI32U,
+
+    // Floating-point operations, operating on the bits of the values.
+
+    /// `dst, a, b`
+    F32Add,
+    /// `dst, a, b`
+    F32Sub,
+    /// `dst, a, b`
+    F32Mul,
+    /// `dst, a, b`
+    F32Div,
+    /// `dst, a, b`
+    F32Min,
+    /// `dst, a, b`
+    F32Max,
+    /// `dst, a, b`
+    F32Copysign,
+    /// `dst, a, b`
+    F32Eq,
+    /// `dst, a, b`
+    F32Ne,
+    /// `dst, a, b`
+    F32Lt,
+    /// `dst, a, b`
+    F32Gt,
+    /// `dst, a, b`
+    F32Le,
+    /// `dst, a, b`
+    F32Ge,
+    /// `dst, a`
+    F32Abs,
+    /// `dst, a`
+    F32Neg,
+    /// `dst, a`
+    F32Sqrt,
+    /// `dst, a`
+    F32Ceil,
+    /// `dst, a`
+    F32Floor,
+    /// `dst, a`
+    F32Trunc,
+    /// `dst, a`
+    F32Nearest,
+
+    /// `dst, a, b`
+    F64Add,
+    /// `dst, a, b`
+    F64Sub,
+    /// `dst, a, b`
+    F64Mul,
+    /// `dst, a, b`
+    F64Div,
+    /// `dst, a, b`
+    F64Min,
+    /// `dst, a, b`
+    F64Max,
+    /// `dst, a, b`
+    F64Copysign,
+    /// `dst, a, b`
+    F64Eq,
+    /// `dst, a, b`
+    F64Ne,
+    /// `dst, a, b`
+    F64Lt,
+    /// `dst, a, b`
+    F64Gt,
+    /// `dst, a, b`
+    F64Le,
+    /// `dst, a, b`
+    F64Ge,
+    /// `dst, a`
+    F64Abs,
+    /// `dst, a`
+    F64Neg,
+    /// `dst, a`
+    F64Sqrt,
+    /// `dst, a`
+    F64Ceil,
+    /// `dst, a`
+    F64Floor,
+    /// `dst, a`
+    F64Trunc,
+    /// `dst, a`
+    F64Nearest,
+
+    // Conversions, all `dst, a`.
+
+    /// `dst, a`
+    I32TruncF32S,
+    /// `dst, a`
+    I32TruncF32U,
+    /// `dst, a`
+    I32TruncF64S,
+    /// `dst, a`
+    I32TruncF64U,
+    /// `dst, a`
+    I64TruncF32S,
+    /// `dst, a`
+    I64TruncF32U,
+    /// `dst, a`
+    I64TruncF64S,
+    /// `dst, a`
+    I64TruncF64U,
+    /// `dst, a`
+    I32TruncSatF32S,
+    /// `dst, a`
+    I32TruncSatF32U,
+    /// `dst, a`
+    I32TruncSatF64S,
+    /// `dst, a`
+    I32TruncSatF64U,
+    /// `dst, a`
+    I64TruncSatF32S,
+    /// `dst, a`
+    I64TruncSatF32U,
+    /// `dst, a`
+    I64TruncSatF64S,
+    /// `dst, a`
+    I64TruncSatF64U,
+    /// `dst, a`
+    F32ConvertI32S,
+    /// `dst, a`
+    F32ConvertI32U,
+    /// `dst, a`
+    F32ConvertI64S,
+    /// `dst, a`
+    F32ConvertI64U,
+    /// `dst, a`
+    F64ConvertI32S,
+    /// `dst, a`
+    F64ConvertI32U,
+    /// `dst, a`
+    F64ConvertI64S,
+    /// `dst, a`
+    F64ConvertI64U,
+    /// `dst, a`
+    F32DemoteF64,
+    /// `dst, a`
+    F64PromoteF32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcodes_roundtrip() {
+        assert!(Op::ALL.len() <= 256);
+        for (i, op) in Op::ALL.iter().enumerate() {
+            assert_eq!(*op as usize, i);
+            assert_eq!(Op::from_u8(*op as u8), Some(*op));
+        }
+        assert_eq!(Op::from_u8(u8::MAX), None);
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let header = FunctionHeader {
+            num_params: 1,
+            num_results: 2,
+            num_locals: 3,
+            frame_size: 0x1234_5678,
+        };
+        assert_eq!(FunctionHeader::from_bytes(&header.to_bytes()), header);
+    }
+}
@@ -4,7 +4,7 @@
 use crate::{obj, Tunables};
 use crate::{
     DefinedFuncIndex, FilePos, FuncIndex, FunctionBodyData, ModuleTranslation, ModuleTypesBuilder,
-    PrimaryMap, StackMap, WasmError, WasmFuncType,
+    PrimaryMap, StackMap, WasmError, WasmFuncType, WasmType,
 };
 use anyhow::Result;
 use object::write::{Object, SymbolId};
@@ -24,6 +24,17 @@ use thiserror::Error;
 pub struct WasmFunctionInfo {
     pub start_srcloc: FilePos,
     pub stack_maps: Box<[StackMapInformation]>,
+    /// The types of the function's locals, including its parameters. Only
+    /// recorded when native debug info is generated.
+    pub locals: Box<[WasmType]>,
+    /// Stack slots holding the function's locals, sorted by `code_start`.
+    pub local_slots: Box<[LocalSlotInformation]>,
+    /// Stack slots holding values on the function's operand stack, sorted by
+    /// `code_start`.
+    pub operand_slots: Box<[OperandSlotInformation]>,
+    /// The height of the operand stack throughout the function's native code,
+    /// sorted by `code_start`.
+    pub operand_stack_heights: Box<[OperandStackHeight]>,
 }
 
 /// Description of where a function is located in the text section of a
@@ -49,6 +60,64 @@ pub struct StackMapInformation {
     pub stack_map: StackMap,
 }
 
+/// A stack slot holding a wasm local over a range of a function's native
+/// code, used to recover the values of locals for core dumps.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LocalSlotInformation {
+    /// The index of the local within the function.
+    pub local: u32,
+
+    /// The start of the range of native code, relative to the beginning of the
+    /// function, where the local is held in this slot.
+    pub code_start: u32,
+
+    /// The exclusive end of the range of native code where the local is held
+    /// in this slot.
+    pub code_end: u32,
+
+    /// The offset of the slot from the function's canonical frame address,
+    /// that is the stack pointer of its caller.
+    pub cfa_offset: i32,
+}
+
+/// A stack slot holding a value on the wasm operand stack over a range of a
+/// function's native code, used to recover the operand stack for core dumps.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OperandSlotInformation {
+    /// The depth of the value on the operand stack, counted from its bottom.
+    pub depth: u32,
+
+    /// The type of the value.
+    pub ty: WasmType,
+
+    /// The start of the range of native code, relative to the beginning of the
+    /// function, where the value is held in this slot.
+    pub code_start: u32,
+
+    /// The exclusive end of the range of native code where the value is held
+    /// in this slot.
+    pub code_end: u32,
+
+    /// The offset of the slot from the function's canonical frame address.
+    pub cfa_offset: i32,
+}
+
+/// The height of the wasm operand stack from an offset in a function's native
+/// code up to the next such offset.
+///
+/// The height is the one before the wasm instruction at that point executes,
+/// so it includes the operands of that instruction, such as the arguments of
+/// a call.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OperandStackHeight {
+    /// The start of the range of native code, relative to the beginning of the
+    /// function.
+    pub code_start: u32,
+
+    /// The number of values on the operand stack.
+    pub height: u32,
+}
+
 /// An error while compiling WebAssembly to machine code.
 #[derive(Error, Debug)]
 pub enum CompileError {
@@ -94,6 +163,10 @@ pub trait CompilerBuilder: Send + Sync + fmt::Debug {
         anyhow::bail!("clif output not supported");
     }
 
+    /// Also writes the optimized CLIF and VCode of each function to the
+    /// directory given to [`CompilerBuilder::clif_dir`].
+    fn emit_compiled_clif(&mut self, _enable: bool) {}
+
     /// Returns the currently configured target triple that compilation will
     /// produce artifacts for.
     fn triple(&self) -> &target_lexicon::Triple;
@@ -182,6 +255,50 @@ pub trait Compiler: Send + Sync {
         types: &ModuleTypesBuilder,
     ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError>;
 
+    /// Returns whether this compiler inlines calls between functions of the
+    /// same module.
+    ///
+    /// When this returns `true` all functions of a module are first
+    /// translated with `translate_function`, and only afterwards compiled with
+    /// `compile_translated_function`, instead of using `compile_function`.
+    fn inlining(&self) -> bool {
+        false
+    }
+
+    /// Translates the function `index` within `translation` into this
+    /// compiler's intermediate representation without compiling it.
+    ///
+    /// This is only used when `inlining` returns `true`.
+    fn translate_function(
+        &self,
+        _translation: &ModuleTranslation<'_>,
+        _index: DefinedFuncIndex,
+        _data: FunctionBodyData<'_>,
+        _types: &ModuleTypesBuilder,
+    ) -> Result<Box<dyn Any + Send + Sync>, CompileError> {
+        Err(CompileError::Codegen(
+            "inlining is not supported by this compiler".to_string(),
+        ))
+    }
+
+    /// Compiles the function `index` within `translation`, which was
+    /// previously translated with `translate_function`.
+    ///
+    /// The `functions` map contains the results of `translate_function` for
+    /// every defined function in the module, making their bodies available
+    /// for inlining into the function being compiled.
+    fn compile_translated_function(
+        &self,
+        _translation: &ModuleTranslation<'_>,
+        _index: DefinedFuncIndex,
+        _functions: &PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>,
+        _types: &ModuleTypesBuilder,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        Err(CompileError::Codegen(
+            "inlining is not supported by this compiler".to_string(),
+        ))
+    }
+
     /// Compile a trampoline for an array-call host function caller calling the
     /// `index`th Wasm function.
     ///
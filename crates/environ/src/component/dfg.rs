@@ -37,6 +37,10 @@ use std::ops::Index;
 #[derive(Default)]
 #[allow(missing_docs)]
 pub struct ComponentDfg {
+    /// Same as `Component::ty`, filled in once the root component has
+    /// finished its instantiation.
+    pub ty: Option<TypeComponentIndex>,
+
     /// Same as `Component::import_types`
     pub import_types: PrimaryMap<ImportIndex, (String, TypeDef)>,
 
@@ -79,6 +83,15 @@ pub struct ComponentDfg {
     /// phase (this is not edited after creation).
     pub num_runtime_component_instances: u32,
 
+    /// Metadata about each component instance that was created during the
+    /// inlining phase, indexed the same way as
+    /// `num_runtime_component_instances`.
+    pub runtime_component_instances:
+        PrimaryMap<RuntimeComponentInstanceIndex, RuntimeComponentInstanceInfo>,
+
+    /// The component instance that created each of the `instances` above.
+    pub instance_owners: PrimaryMap<InstanceId, RuntimeComponentInstanceIndex>,
+
     /// Known adapter modules and how they are instantiated.
     ///
     /// This map is not filled in on the initial creation of a `ComponentDfg`.
@@ -366,6 +379,18 @@ impl ComponentDfg {
             .map(|(name, export)| (name.clone(), linearize.export(export)))
             .collect();
 
+        // Adapter modules are interleaved with the other core instances, so
+        // they're the ones without an owner here.
+        let mut runtime_instance_owners: PrimaryMap<RuntimeInstanceIndex, _> =
+            (0..linearize.runtime_instances.len())
+                .map(|_| None)
+                .collect();
+        for (instance, index) in linearize.runtime_instances.iter() {
+            if let RuntimeInstance::Normal(id) = instance {
+                runtime_instance_owners[*index] = Some(self.instance_owners[*id]);
+            }
+        }
+
         // With all those pieces done the results of the dataflow-based
         // linearization are recorded into the `Component`. The number of
         // runtime values used for each index space is used from the `linearize`
@@ -373,6 +398,7 @@ impl ComponentDfg {
         ComponentTranslation {
             trampolines: linearize.trampoline_defs,
             component: Component {
+                ty: self.ty.unwrap(),
                 exports,
                 initializers: linearize.initializers,
                 trampolines: linearize.trampolines,
@@ -393,6 +419,8 @@ impl ComponentDfg {
                     .iter()
                     .map(|(_, r)| r.instance)
                     .collect(),
+                runtime_component_instances: self.runtime_component_instances,
+                runtime_instance_owners,
             },
         }
     }
@@ -69,8 +69,12 @@ pub struct ComponentTranslation {
 ///
 /// NB: Lots of the component model is not yet implemented in the runtime so
 /// this is going to undergo a lot of churn.
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Component {
+    /// The type of this component, describing all of its imports and exports
+    /// with their types.
+    pub ty: TypeComponentIndex,
+
     /// A list of typed values that this component imports.
     ///
     /// Note that each name is given an `ImportIndex` here for the next map to
@@ -172,6 +176,34 @@ pub struct Component {
     /// This is used to determine which set of instance flags are inspected when
     /// testing reentrance.
     pub defined_resource_instances: PrimaryMap<DefinedResourceIndex, RuntimeComponentInstanceIndex>,
+
+    /// Metadata about each component instance, including nested ones, that's
+    /// created when instantiating this component.
+    ///
+    /// The root component is always the first entry. This is only used to
+    /// describe the structure of an instance, for example in core dumps.
+    pub runtime_component_instances:
+        PrimaryMap<RuntimeComponentInstanceIndex, RuntimeComponentInstanceInfo>,
+
+    /// The component instance which instantiated each core wasm instance.
+    ///
+    /// This is `None` for the adapter modules that are synthesized by Wasmtime
+    /// rather than instantiated by any component.
+    pub runtime_instance_owners:
+        PrimaryMap<RuntimeInstanceIndex, Option<RuntimeComponentInstanceIndex>>,
+}
+
+/// Metadata about a component instance that's created when instantiating a
+/// component, stored in `Component::runtime_component_instances`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeComponentInstanceInfo {
+    /// The name of the component that was instantiated, from its
+    /// `component-name` custom section.
+    pub name: Option<String>,
+
+    /// The component instance which instantiated this one, or `None` for the
+    /// root component.
+    pub parent: Option<RuntimeComponentInstanceIndex>,
 }
 
 impl Component {
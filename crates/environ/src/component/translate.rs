@@ -11,7 +11,10 @@ use std::mem;
 use wasmparser::types::{
     AliasableResourceId, ComponentEntityType, ComponentFuncTypeId, ComponentInstanceTypeId, Types,
 };
-use wasmparser::{Chunk, ComponentImportName, Encoding, Parser, Payload, Validator};
+use wasmparser::{
+    Chunk, ComponentImportName, ComponentName, ComponentNameSectionReader, Encoding, Parser,
+    Payload, Validator,
+};
 
 mod adapt;
 pub use self::adapt::*;
@@ -157,6 +160,9 @@ struct Translation<'data> {
     /// component has finished, e.g. for the `inline` pass, but beforehand this
     /// is set to `None`.
     types: Option<Types>,
+
+    /// The name of this component, from its `component-name` custom section.
+    name: Option<&'data str>,
 }
 
 // NB: the type information contained in `LocalInitializer` should always point
@@ -655,10 +661,15 @@ impl<'a, 'data> Translator<'a, 'data> {
                 }
             }
 
-            // All custom sections are ignored by Wasmtime at this time.
-            //
-            // FIXME(WebAssembly/component-model#14): probably want to specify
-            // and parse a `name` section here.
+            // Only the name of the component itself is read from the name
+            // section, for diagnostics. All other custom sections are ignored
+            // by Wasmtime at this time.
+            Payload::CustomSection(s) if s.name() == "component-name" => {
+                let reader = ComponentNameSectionReader::new(s.data(), s.data_offset());
+                if let Err(e) = self.component_name_section(reader) {
+                    log::warn!("failed to parse component name section {:?}", e);
+                }
+            }
             Payload::CustomSection { .. } => {}
 
             // Anything else is either not reachable since we never enable the
@@ -896,6 +907,18 @@ impl<'a, 'data> Translator<'a, 'data> {
         let ty = self.types.convert_func_type(ty);
         self.types.module_types_builder().wasm_func_type(id, ty)
     }
+
+    fn component_name_section(
+        &mut self,
+        names: ComponentNameSectionReader<'data>,
+    ) -> wasmparser::Result<()> {
+        for subsection in names {
+            if let ComponentName::Component { name, .. } = subsection? {
+                self.result.name = Some(name);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Translation<'_> {
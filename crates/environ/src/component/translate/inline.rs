@@ -125,6 +125,13 @@ pub(super) fn run(
     // the root frame which are then used for recording the exports of the
     // component.
     inliner.result.num_runtime_component_instances += 1;
+    inliner
+        .result
+        .runtime_component_instances
+        .push(RuntimeComponentInstanceInfo {
+            name: result.name.map(|s| s.to_string()),
+            parent: None,
+        });
     let frame = InlinerFrame::new(index, result, ComponentClosure::default(), args, None);
     let resources_snapshot = types.resources_mut().clone();
     let mut frames = vec![(frame, resources_snapshot)];
@@ -141,6 +148,30 @@ pub(super) fn run(
     Ok(inliner.result)
 }
 
+/// Converts the type of the root component `translation`, with all of its
+/// imports and exports, into Wasmtime's type representation.
+fn component_type(
+    translation: &Translation<'_>,
+    types: &mut ComponentTypesBuilder,
+) -> Result<TypeComponentIndex> {
+    let types_ref = translation.types_ref();
+    let mut ty = TypeComponent::default();
+    for init in translation.initializers.iter() {
+        if let LocalInitializer::Import(name, import) = init {
+            let import = types.convert_component_entity_type(types_ref, *import)?;
+            ty.imports.insert(name.0.to_string(), import);
+        }
+    }
+    for name in translation.exports.keys() {
+        let export = match types_ref.component_entity_type_of_export(name).unwrap() {
+            ComponentEntityType::Type { created, .. } => types.convert_type(types_ref, created)?,
+            export => types.convert_component_entity_type(types_ref, export)?,
+        };
+        ty.exports.insert(name.to_string(), export);
+    }
+    Ok(types.add_component_type(ty))
+}
+
 struct Inliner<'a> {
     /// The list of static modules that were found during initial translation of
     /// the component.
@@ -392,6 +423,14 @@ impl<'a> Inliner<'a> {
                         .map(|(name, item)| Ok((*name, frame.item(*item, types)?)))
                         .collect::<Result<_>>()?;
                     let instance_ty = frame.instance_ty;
+
+                    // The type of the root component is recorded here, before
+                    // resources are reset below, as its exports may refer to
+                    // resources defined within the component.
+                    if instance_ty.is_none() {
+                        self.result.ty = Some(component_type(frame.translation, types)?);
+                    }
+
                     let (_, snapshot) = frames.pop().unwrap();
                     *types.resources_mut() = snapshot;
                     match frames.last_mut() {
@@ -717,6 +756,8 @@ impl<'a> Inliner<'a> {
                 };
 
                 let idx = self.result.instances.push(init);
+                let idx3 = self.result.instance_owners.push(frame.instance);
+                assert_eq!(idx, idx3);
                 self.result
                     .side_effects
                     .push(dfg::SideEffect::Instance(idx));
@@ -771,9 +812,18 @@ impl<'a> Inliner<'a> {
                     self.result.num_runtime_component_instances,
                 );
                 self.result.num_runtime_component_instances += 1;
+                let translation = &self.nested_components[component.index];
+                let index2 =
+                    self.result
+                        .runtime_component_instances
+                        .push(RuntimeComponentInstanceInfo {
+                            name: translation.name.map(|s| s.to_string()),
+                            parent: Some(frame.instance),
+                        });
+                assert_eq!(index, index2);
                 let frame = InlinerFrame::new(
                     index,
-                    &self.nested_components[component.index],
+                    translation,
                     component.closure.clone(),
                     args.iter()
                         .map(|(name, item)| Ok((*name, frame.item(*item, types)?)))
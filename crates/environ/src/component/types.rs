@@ -798,6 +798,14 @@ impl ComponentTypesBuilder {
         self.resources.convert(id, &mut self.component_types)
     }
 
+    /// Adds a new component type to this type information.
+    ///
+    /// Component types are not interned as they're not compared by the
+    /// runtime.
+    pub fn add_component_type(&mut self, ty: TypeComponent) -> TypeComponentIndex {
+        self.component_types.components.push(ty)
+    }
+
     /// Interns a new function type within this type information.
     pub fn add_func_type(&mut self, ty: TypeFunc) -> TypeFuncIndex {
         intern(&mut self.functions, &mut self.component_types.functions, ty)
@@ -8,11 +8,13 @@
 
 mod address_map;
 mod builtin;
+pub mod bytecode;
 mod compilation;
 mod module;
 mod module_environ;
 mod module_types;
 pub mod obj;
+mod pgo;
 mod ref_bits;
 mod scopevec;
 mod stack_map;
@@ -26,6 +28,7 @@ pub use crate::compilation::*;
 pub use crate::module::*;
 pub use crate::module_environ::*;
 pub use crate::module_types::*;
+pub use crate::pgo::*;
 pub use crate::ref_bits::*;
 pub use crate::scopevec::ScopeVec;
 pub use crate::stack_map::StackMap;
@@ -860,6 +860,11 @@ pub struct Module {
 
     /// WebAssembly global initializers for locally-defined globals.
     pub global_initializers: PrimaryMap<DefinedGlobalIndex, GlobalInit>,
+
+    /// Hash of the wasm bytes this module was translated from, recorded when
+    /// it is compiled with profiling instrumentation so the collected
+    /// `ModuleProfile` can be matched up with the module later on.
+    pub pgo_key: Option<[u8; 32]>,
 }
 
 /// Initialization routines for creating an instance, encompassing imports,
@@ -4,9 +4,9 @@ use crate::module::{
 };
 use crate::{
     DataIndex, DefinedFuncIndex, ElemIndex, EntityIndex, EntityType, FuncIndex, GlobalIndex,
-    GlobalInit, MemoryIndex, ModuleTypesBuilder, PrimaryMap, SignatureIndex, TableIndex,
-    TableInitialValue, Tunables, TypeConvert, TypeIndex, Unsigned, WasmError, WasmHeapType,
-    WasmResult, WasmType, WasmparserTypeConverter,
+    GlobalInit, MemoryIndex, ModuleProfile, ModuleTypesBuilder, PrimaryMap, SignatureIndex,
+    TableIndex, TableInitialValue, Tunables, TypeConvert, TypeIndex, Unsigned, WasmError,
+    WasmHeapType, WasmResult, WasmType, WasmparserTypeConverter,
 };
 use cranelift_entity::packed_option::ReservedValue;
 use std::borrow::Cow;
@@ -89,6 +89,10 @@ pub struct ModuleTranslation<'data> {
     /// Total size of all passive data pushed into `passive_data` so far.
     total_passive_data: u32,
 
+    /// An execution profile previously collected for this module, used to
+    /// guide optimization of its functions.
+    pub profile: Option<Arc<ModuleProfile>>,
+
     /// When we're parsing the code section this will be incremented so we know
     /// which function is currently being defined.
     code_index: u32,
@@ -129,6 +129,13 @@ pub const ELF_NAME_DATA: &'static str = ".name.wasm";
 /// metadata.
 pub const ELF_WASMTIME_DWARF: &str = ".wasmtime.dwarf";
 
+/// An empty marker section which is present when the ".text" section contains
+/// bytecode for Wasmtime's portable interpreter instead of native code.
+///
+/// The text of such images is only ever read by the interpreter, so it is not
+/// made executable when the image is loaded and has no unwind information.
+pub const ELF_WASMTIME_BYTECODE: &str = ".wasmtime.bytecode";
+
 macro_rules! libcalls {
     ($($rust:ident = $sym:tt)*) => (
         #[allow(missing_docs)]
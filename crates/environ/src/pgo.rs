@@ -0,0 +1,209 @@
+//! Execution profiles for profile-guided optimization.
+//!
+//! A module compiled with `Tunables::pgo_instrumentation` counts how often
+//! each of its functions is entered and how often each of its conditional
+//! branches (`if` and `br_if`) is executed and taken. Those counts are
+//! collected into a `ModuleProfile` which can be handed back to the compiler
+//! the next time the same module is compiled to lay out unlikely code out of
+//! line and to make inlining decisions based on how hot functions are.
+//!
+//! Branches are identified by their position within a function: the `N`th
+//! conditional branch translated in a function body is branch site `N`. This
+//! is stable as long as the same wasm module is translated, which is why
+//! profiles are keyed by a hash of the module's bytes.
+
+use crate::DefinedFuncIndex;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Minimum number of times a branch has to have been executed before its
+/// profile is trusted to predict its direction.
+const MIN_BRANCH_EXECUTIONS: u64 = 16;
+
+/// A branch direction must be taken at most once every this many executions
+/// to be considered unlikely.
+const UNLIKELY_BRANCH_RATIO: u64 = 64;
+
+/// A function is considered hot when it was entered at least once for every
+/// this many entries of the most frequently entered function in the module.
+const HOT_FUNCTION_RATIO: u64 = 16;
+
+/// Execution counts for all functions defined in a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModuleProfile {
+    /// Profiles of the functions that were entered at least once.
+    pub functions: BTreeMap<DefinedFuncIndex, FunctionProfile>,
+}
+
+/// Execution counts for a single function.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionProfile {
+    /// Number of times this function was entered.
+    pub entries: u64,
+    /// Counts for each conditional branch site of this function, in the order
+    /// that they're translated.
+    pub branches: Vec<BranchProfile>,
+}
+
+/// Execution counts for a single conditional branch.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BranchProfile {
+    /// Number of times the branch was executed.
+    pub executed: u64,
+    /// Number of times the branch condition was non-zero, meaning that the
+    /// `br_if` was taken or the consequent of the `if` was executed.
+    pub taken: u64,
+}
+
+impl ModuleProfile {
+    /// Returns the profile for `func`, if it was ever entered.
+    pub fn function(&self, func: DefinedFuncIndex) -> Option<&FunctionProfile> {
+        self.functions.get(&func)
+    }
+
+    /// Returns whether `func` was never entered while profiling.
+    pub fn is_cold(&self, func: DefinedFuncIndex) -> bool {
+        self.function(func).map_or(true, |f| f.entries == 0)
+    }
+
+    /// Returns whether `func` was entered often relative to the other
+    /// functions of the module.
+    pub fn is_hot(&self, func: DefinedFuncIndex) -> bool {
+        let max = self
+            .functions
+            .values()
+            .map(|f| f.entries)
+            .max()
+            .unwrap_or(0);
+        let entries = self.function(func).map_or(0, |f| f.entries);
+        entries > 0 && entries.saturating_mul(HOT_FUNCTION_RATIO) >= max
+    }
+
+    /// Adds the counts of `other`, a profile of the same module, to this one.
+    pub fn merge(&mut self, other: &ModuleProfile) {
+        for (func, profile) in other.functions.iter() {
+            self.functions.entry(*func).or_default().merge(profile);
+        }
+    }
+}
+
+impl FunctionProfile {
+    /// Returns the profile for the `site`th conditional branch of this
+    /// function.
+    pub fn branch(&self, site: usize) -> Option<&BranchProfile> {
+        self.branches.get(site)
+    }
+
+    /// Adds the counts of `other` to this profile.
+    pub fn merge(&mut self, other: &FunctionProfile) {
+        self.entries = self.entries.saturating_add(other.entries);
+        if self.branches.len() < other.branches.len() {
+            self.branches
+                .resize(other.branches.len(), BranchProfile::default());
+        }
+        for (mine, theirs) in self.branches.iter_mut().zip(&other.branches) {
+            mine.executed = mine.executed.saturating_add(theirs.executed);
+            mine.taken = mine.taken.saturating_add(theirs.taken);
+        }
+    }
+}
+
+impl BranchProfile {
+    /// Returns `Some(true)` if this branch is almost always taken,
+    /// `Some(false)` if it is almost never taken, and `None` if it goes both
+    /// ways or didn't execute often enough to tell.
+    pub fn likely(&self) -> Option<bool> {
+        if self.executed < MIN_BRANCH_EXECUTIONS {
+            return None;
+        }
+        let not_taken = self.executed.saturating_sub(self.taken);
+        if self.taken.saturating_mul(UNLIKELY_BRANCH_RATIO) <= self.executed {
+            Some(false)
+        } else if not_taken.saturating_mul(UNLIKELY_BRANCH_RATIO) <= self.executed {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_likelihood() {
+        let branch = |executed, taken| BranchProfile { executed, taken };
+        assert_eq!(branch(0, 0).likely(), None);
+        assert_eq!(branch(10, 0).likely(), None);
+        assert_eq!(branch(1000, 0).likely(), Some(false));
+        assert_eq!(branch(1000, 10).likely(), Some(false));
+        assert_eq!(branch(1000, 500).likely(), None);
+        assert_eq!(branch(1000, 990).likely(), Some(true));
+        assert_eq!(branch(1000, 1000).likely(), Some(true));
+    }
+
+    #[test]
+    fn merge_and_hotness() {
+        let f0 = DefinedFuncIndex::from_u32(0);
+        let f1 = DefinedFuncIndex::from_u32(1);
+        let f2 = DefinedFuncIndex::from_u32(2);
+
+        let mut a = ModuleProfile::default();
+        a.functions.insert(
+            f0,
+            FunctionProfile {
+                entries: 1,
+                branches: vec![BranchProfile {
+                    executed: 1,
+                    taken: 1,
+                }],
+            },
+        );
+        let mut b = ModuleProfile::default();
+        b.functions.insert(
+            f0,
+            FunctionProfile {
+                entries: 2,
+                branches: vec![
+                    BranchProfile {
+                        executed: 2,
+                        taken: 0,
+                    },
+                    BranchProfile {
+                        executed: 2,
+                        taken: 2,
+                    },
+                ],
+            },
+        );
+        b.functions.insert(
+            f1,
+            FunctionProfile {
+                entries: 1000,
+                branches: Vec::new(),
+            },
+        );
+        a.merge(&b);
+
+        let p0 = a.function(f0).unwrap();
+        assert_eq!(p0.entries, 3);
+        assert_eq!(
+            p0.branches,
+            [
+                BranchProfile {
+                    executed: 3,
+                    taken: 1
+                },
+                BranchProfile {
+                    executed: 2,
+                    taken: 2
+                },
+            ]
+        );
+        assert!(a.is_hot(f1));
+        assert!(!a.is_hot(f0));
+        assert!(!a.is_cold(f0));
+        assert!(a.is_cold(f2));
+    }
+}
@@ -178,13 +178,9 @@ impl TrapEncodingBuilder {
     }
 }
 
-/// Decodes the provided trap information section and attempts to find the trap
-/// code corresponding to the `offset` specified.
-///
-/// The `section` provided is expected to have been built by
-/// `TrapEncodingBuilder` above. Additionally the `offset` should be a relative
-/// offset within the text section of the compilation image.
-pub fn lookup_trap_code(section: &[u8], offset: usize) -> Option<Trap> {
+/// Splits the trap information section into its sorted array of text offsets
+/// and the trap code byte for each offset.
+fn parse_traps(section: &[u8]) -> Option<(&[U32Bytes<LittleEndian>], &[u8])> {
     let mut section = Bytes(section);
     // NB: this matches the encoding written by `append_to` above.
     let count = section.read::<U32Bytes<LittleEndian>>().ok()?;
@@ -192,6 +188,17 @@ pub fn lookup_trap_code(section: &[u8], offset: usize) -> Option<Trap> {
     let (offsets, traps) =
         object::slice_from_bytes::<U32Bytes<LittleEndian>>(section.0, count).ok()?;
     debug_assert_eq!(traps.len(), count);
+    Some((offsets, traps))
+}
+
+/// Decodes the provided trap information section and attempts to find the trap
+/// code corresponding to the `offset` specified.
+///
+/// The `section` provided is expected to have been built by
+/// `TrapEncodingBuilder` above. Additionally the `offset` should be a relative
+/// offset within the text section of the compilation image.
+pub fn lookup_trap_code(section: &[u8], offset: usize) -> Option<Trap> {
+    let (offsets, traps) = parse_traps(section)?;
 
     // The `offsets` table is sorted in the trap section so perform a binary
     // search of the contents of this section to find whether `offset` is an
@@ -207,8 +214,26 @@ pub fn lookup_trap_code(section: &[u8], offset: usize) -> Option<Trap> {
         .binary_search_by_key(&offset, |val| val.get(LittleEndian))
         .ok()?;
     debug_assert!(index < traps.len());
-    let trap = *traps.get(index)?;
+    decode_trap(*traps.get(index)?)
+}
+
+/// Iterate over the traps contained in the given trap information section.
+///
+/// The `section` provided is expected to have been built by
+/// `TrapEncodingBuilder` above. The yielded offsets are relative to the start
+/// of the text section of the compilation image, and each trap is decoded the
+/// same way as with `lookup_trap_code`.
+pub fn iterate_traps(section: &[u8]) -> Option<impl Iterator<Item = (u32, Option<Trap>)> + '_> {
+    let (offsets, traps) = parse_traps(section)?;
+    Some(
+        offsets
+            .iter()
+            .map(|offset| offset.get(LittleEndian))
+            .zip(traps.iter().map(|trap| decode_trap(*trap))),
+    )
+}
 
+fn decode_trap(trap: u8) -> Option<Trap> {
     // FIXME: this could use some sort of derive-like thing to avoid having to
     // deduplicate the names here.
     //
@@ -54,6 +54,14 @@ pub struct Tunables {
 
     /// Whether or not Wasm functions can be tail-called or not.
     pub tail_callable: bool,
+
+    /// Whether or not generated code counts function entries and conditional
+    /// branch outcomes to collect a profile for profile-guided optimization.
+    pub pgo_instrumentation: bool,
+
+    /// Whether functions are compiled to bytecode for Wasmtime's portable
+    /// interpreter instead of to native code.
+    pub interpreter: bool,
 }
 
 impl Default for Tunables {
@@ -112,6 +120,8 @@ impl Default for Tunables {
             debug_adapter_modules: false,
             relaxed_simd_deterministic: false,
             tail_callable: false,
+            pgo_instrumentation: false,
+            interpreter: false,
         }
     }
 }
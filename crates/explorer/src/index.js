@@ -1,13 +1,13 @@
 /*** State *********************************************************************/
 
 class State {
-  constructor(wat, asm) {
-    this.wat = wat;
-    this.asm = asm;
+  constructor(modules) {
+    this.modules = modules;
+    this.module = modules[0];
   }
 }
 
-const state = window.STATE = new State(window.WAT, window.ASM);
+const state = window.STATE = new State(window.MODULES);
 
 /*** Hues for Offsets **********************************************************/
 
@@ -64,7 +64,7 @@ const existingHueForOffset = offset => {
 // Get WAT chunk elements by Wasm offset.
 const watByOffset = new Map();
 
-// Get asm instruction elements by Wasm offset.
+// Get asm instruction and CLIF/VCode line elements by Wasm offset.
 const asmByOffset = new Map();
 
 // Get all (WAT chunk or asm instruction) elements by offset.
@@ -189,50 +189,116 @@ const renderInst = (mnemonic, operands) => {
   }
 };
 
-// Render the ASM.
+const renderOffsetElem = (elem, offset) => {
+  elem.dataset.wasmOffset = offset;
+  const hue = hueForOffset(offset);
+  elem.style.backgroundColor = `hsl(${hue} 50% 90%)`;
+  elem.addEventListener("mouseenter", onMouseEnter);
+  elem.addEventListener("mouseleave", onMouseLeave);
+  addAsmElem(offset, elem);
+};
 
-let nthFunc = 0;
-for (const func of state.asm.functions) {
-  const funcElem = document.createElement("div");
+// Render a collapsible section of a function's code.
+const renderSection = (title, open, lines) => {
+  const sectionElem = document.createElement("details");
+  sectionElem.open = open;
 
-  const funcHeader = document.createElement("h3");
-  funcHeader.textContent = `Defined Function ${nthFunc}`;
-  funcElem.appendChild(funcHeader);
+  const summaryElem = document.createElement("summary");
+  summaryElem.textContent = title;
+  sectionElem.appendChild(summaryElem);
 
   const bodyElem = document.createElement("pre");
-  for (const inst of func.instructions) {
-    const instElem = document.createElement("span");
-    instElem.textContent = `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}\n`;
-    if (inst.wasm_offset != null) {
-      instElem.setAttribute("data-wasm-offset", inst.wasm_offset);
-      const hue = hueForOffset(inst.wasm_offset);
-      instElem.style.backgroundColor = `hsl(${hue} 50% 90%)`;
-      instElem.addEventListener("mouseenter", onMouseEnter);
-      instElem.addEventListener("mouseleave", onMouseLeave);
-      addAsmElem(inst.wasm_offset, instElem);
-    }
-    bodyElem.appendChild(instElem);
+  for (const line of lines) {
+    bodyElem.appendChild(line);
   }
-  funcElem.appendChild(bodyElem);
+  sectionElem.appendChild(bodyElem);
+  return sectionElem;
+};
 
-  asmElem.appendChild(funcElem);
-  nthFunc++;
-}
+// Render the ASM, preceded by the CLIF and VCode of each function.
+
+const renderAsm = () => {
+  let nthFunc = 0;
+  for (const func of state.module.asm.functions) {
+    const funcElem = document.createElement("div");
+
+    const funcHeader = document.createElement("h3");
+    funcHeader.textContent = `Defined Function ${nthFunc}`;
+    funcElem.appendChild(funcHeader);
+
+    for (const stage of func.stages) {
+      const lines = stage.lines.map(line => {
+        const lineElem = document.createElement("span");
+        lineElem.textContent = `${line.text}\n`;
+        if (line.wasm_offset != null) {
+          renderOffsetElem(lineElem, line.wasm_offset);
+        }
+        return lineElem;
+      });
+      funcElem.appendChild(renderSection(stage.name, false, lines));
+    }
+
+    const instructions = func.instructions.map(inst => {
+      const instElem = document.createElement("span");
+      instElem.textContent = `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}\n`;
+      if (inst.wasm_offset != null) {
+        renderOffsetElem(instElem, inst.wasm_offset);
+      }
+      return instElem;
+    });
+    funcElem.appendChild(renderSection("Machine Code", true, instructions));
+
+    asmElem.appendChild(funcElem);
+    nthFunc++;
+  }
+};
 
 // Render the WAT.
 
-for (const chunk of state.wat.chunks) {
-  const chunkElem = document.createElement("span");
-  if (chunk.wasm_offset != null) {
-    chunkElem.dataset.wasmOffset = chunk.wasm_offset;
-    const hue = existingHueForOffset(chunk.wasm_offset);
-    if (hue) {
-      chunkElem.style.backgroundColor = `hsl(${hue} 50% 95%)`;
-      chunkElem.addEventListener("mouseenter", onMouseEnter);
-      chunkElem.addEventListener("mouseleave", onMouseLeave);
-      addWatElem(chunk.wasm_offset, chunkElem);
+const renderWat = () => {
+  for (const chunk of state.module.wat.chunks) {
+    const chunkElem = document.createElement("span");
+    if (chunk.wasm_offset != null) {
+      chunkElem.dataset.wasmOffset = chunk.wasm_offset;
+      const hue = existingHueForOffset(chunk.wasm_offset);
+      if (hue) {
+        chunkElem.style.backgroundColor = `hsl(${hue} 50% 95%)`;
+        chunkElem.addEventListener("mouseenter", onMouseEnter);
+        chunkElem.addEventListener("mouseleave", onMouseLeave);
+        addWatElem(chunk.wasm_offset, chunkElem);
+      }
     }
+    chunkElem.textContent = chunk.wat;
+    watElem.appendChild(chunkElem);
   }
-  chunkElem.textContent = chunk.wat;
-  watElem.appendChild(chunkElem);
-}
+};
+
+// Render the currently selected module, replacing any previous one.
+
+const render = () => {
+  watByOffset.clear();
+  asmByOffset.clear();
+  anyByOffset.clear();
+  offsetToHue.clear();
+  watElem.replaceChildren();
+  asmElem.replaceChildren();
+
+  renderAsm();
+  renderWat();
+};
+
+const moduleElem = document.getElementById("module");
+state.modules.forEach((module, i) => {
+  const optionElem = document.createElement("option");
+  optionElem.value = i;
+  optionElem.textContent = module.name;
+  moduleElem.appendChild(optionElem);
+});
+// Only components have more than one module to choose from.
+moduleElem.hidden = state.modules.length < 2;
+moduleElem.addEventListener("change", () => {
+  state.module = state.modules[moduleElem.value];
+  render();
+});
+
+render();
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use capstone::arch::BuildsCapstone;
 use serde_derive::Serialize;
+use std::path::Path;
 use std::{io::Write, str::FromStr};
 
 pub fn generate(
@@ -14,10 +15,17 @@ pub fn generate(
         Some(target) => target_lexicon::Triple::from_str(target)?,
     };
 
-    let wat = annotate_wat(wasm)?;
-    let wat_json = serde_json::to_string(&wat)?;
-    let asm = annotate_asm(config, &target, wasm)?;
-    let asm_json = serde_json::to_string(&asm)?;
+    // Components are explored one core module at a time.
+    let modules = if wasmparser::Parser::is_component(wasm) {
+        core_modules(wasm)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, module)| explore_module(config, &target, format!("module {i}"), module))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        vec![explore_module(config, &target, "module".to_string(), wasm)?]
+    };
+    let modules_json = serde_json::to_string(&modules)?;
 
     let index_css = include_str!("./index.css");
     let index_js = include_str!("./index.js");
@@ -33,12 +41,16 @@ pub fn generate(
       {index_css}
     </style>
   </head>
-  <body class="hbox">
-    <pre id="wat"></pre>
-    <div id="asm"></div>
+  <body class="vbox">
+    <div id="toolbar">
+      <select id="module"></select>
+    </div>
+    <div class="hbox">
+      <pre id="wat"></pre>
+      <div id="asm"></div>
+    </div>
     <script>
-      window.WAT = {wat_json};
-      window.ASM = {asm_json};
+      window.MODULES = {modules_json};
     </script>
     <script>
       {index_js}
@@ -53,6 +65,38 @@ pub fn generate(
 #[derive(Serialize, Clone, Copy, Debug)]
 struct WasmOffset(u32);
 
+#[derive(Serialize, Debug)]
+struct ExploredModule {
+    name: String,
+    wat: AnnotatedWat,
+    asm: AnnotatedAsm,
+}
+
+fn explore_module(
+    config: &wasmtime::Config,
+    target: &target_lexicon::Triple,
+    name: String,
+    wasm: &[u8],
+) -> Result<ExploredModule> {
+    Ok(ExploredModule {
+        name,
+        wat: annotate_wat(wasm)?,
+        asm: annotate_asm(config, target, wasm)?,
+    })
+}
+
+/// Returns the bytes of every core module defined within `component`,
+/// including those within nested components.
+fn core_modules(component: &[u8]) -> Result<Vec<&[u8]>> {
+    let mut modules = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(component) {
+        if let wasmparser::Payload::ModuleSection { range, .. } = payload? {
+            modules.push(&component[range.start..range.end]);
+        }
+    }
+    Ok(modules)
+}
+
 #[derive(Serialize, Debug)]
 struct AnnotatedWat {
     chunks: Vec<AnnotatedWatChunk>,
@@ -83,9 +127,24 @@ struct AnnotatedAsm {
 
 #[derive(Serialize, Debug)]
 struct AnnotatedFunction {
+    /// The intermediate representations of this function, in the order they
+    /// are produced during compilation.
+    stages: Vec<AnnotatedStage>,
     instructions: Vec<AnnotatedInstruction>,
 }
 
+#[derive(Serialize, Debug)]
+struct AnnotatedStage {
+    name: &'static str,
+    lines: Vec<AnnotatedLine>,
+}
+
+#[derive(Serialize, Debug)]
+struct AnnotatedLine {
+    wasm_offset: Option<WasmOffset>,
+    text: String,
+}
+
 #[derive(Serialize, Debug)]
 struct AnnotatedInstruction {
     wasm_offset: Option<WasmOffset>,
@@ -100,8 +159,18 @@ fn annotate_asm(
     target: &target_lexicon::Triple,
     wasm: &[u8],
 ) -> Result<AnnotatedAsm> {
-    let engine = wasmtime::Engine::new(config)?;
+    // Have the compiler write out the CLIF, optimized CLIF and VCode of every
+    // function.
+    let clif_dir = tempfile::tempdir()?;
+    let mut config = config.clone();
+    config.emit_clif(clif_dir.path()).emit_compiled_clif(true);
+
+    let engine = wasmtime::Engine::new(&config)?;
     let module = wasmtime::Module::new(&engine, wasm)?;
+    let imported_funcs = module
+        .imports()
+        .filter(|import| matches!(import.ty(), wasmtime::ExternType::Func(_)))
+        .count();
 
     let text = module.text();
     let address_map: Vec<_> = module
@@ -131,7 +200,8 @@ fn annotate_asm(
     let functions = module
         .function_locations()
         .into_iter()
-        .map(|(start, len)| {
+        .enumerate()
+        .map(|(defined_index, (start, len))| {
             let body = &text[start..][..len];
 
             let mut cs = match target.architecture {
@@ -181,9 +251,48 @@ fn annotate_asm(
                     })
                 })
                 .collect::<Result<Vec<_>>>()?;
-            Ok(AnnotatedFunction { instructions })
+            let stages = read_stages(clif_dir.path(), imported_funcs + defined_index);
+            Ok(AnnotatedFunction {
+                stages,
+                instructions,
+            })
         })
         .collect::<Result<Vec<_>>>()?;
 
     Ok(AnnotatedAsm { functions })
 }
+
+/// Reads the files written by `Config::emit_clif` and the
+/// `Config::emit_compiled_clif` setting for the function
+/// `func_index`, skipping any stage which wasn't written.
+fn read_stages(dir: &Path, func_index: usize) -> Vec<AnnotatedStage> {
+    let stages = [
+        ("CLIF", "clif"),
+        ("Optimized CLIF", "opt.clif"),
+        ("VCode", "vcode"),
+    ];
+    stages
+        .into_iter()
+        .filter_map(|(name, extension)| {
+            let path = dir.join(format!("wasm_func_{func_index}.{extension}"));
+            let text = std::fs::read_to_string(path).ok()?;
+            let lines = text
+                .lines()
+                .map(|line| AnnotatedLine {
+                    wasm_offset: clif_srcloc(line),
+                    text: line.to_string(),
+                })
+                .collect();
+            Some(AnnotatedStage { name, lines })
+        })
+        .collect()
+}
+
+/// Parses the `@xxxx` source location which CLIF instructions translated from
+/// WebAssembly are prefixed with, and which is the offset of the WebAssembly
+/// instruction in the module.
+fn clif_srcloc(line: &str) -> Option<WasmOffset> {
+    let srcloc = line.trim_start().strip_prefix('@')?;
+    let srcloc = srcloc.split_whitespace().next()?;
+    u32::from_str_radix(srcloc, 16).ok().map(WasmOffset)
+}
@@ -0,0 +1,102 @@
+use crate::compiler::Compiler;
+use anyhow::{bail, Result};
+use std::fmt;
+use std::sync::Arc;
+use target_lexicon::Triple;
+use wasmtime_environ::{CacheStore, CompilerBuilder, Setting, Tunables};
+
+/// The settings which Wasmtime configures for the compilers generating native
+/// code. They only influence the generation of native code, so they're
+/// accepted but have no effect on the bytecode.
+const NATIVE_SETTINGS: &[&str] = &[
+    "enable_nan_canonicalization",
+    "enable_pcc",
+    "enable_probestack",
+    "enable_safepoints",
+    "enable_verifier",
+    "opt_level",
+    "preserve_frame_pointers",
+    "probestack_strategy",
+    "unwind_info",
+    "wasmtime_inlining",
+];
+
+/// Compiler builder.
+struct Builder {
+    triple: Triple,
+    tunables: Tunables,
+}
+
+pub fn builder() -> Box<dyn CompilerBuilder> {
+    Box::new(Builder {
+        triple: Triple::host(),
+        tunables: Tunables::default(),
+    })
+}
+
+impl CompilerBuilder for Builder {
+    fn triple(&self) -> &Triple {
+        &self.triple
+    }
+
+    fn target(&mut self, target: Triple) -> Result<()> {
+        self.triple = target;
+        Ok(())
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        if name == "enable_nan_canonicalization" && value == "true" {
+            bail!("NaN canonicalization is not supported by the interpreter");
+        }
+        check_setting(name)
+    }
+
+    fn enable(&mut self, name: &str) -> Result<()> {
+        self.set(name, "true")
+    }
+
+    fn settings(&self) -> Vec<Setting> {
+        Vec::new()
+    }
+
+    fn set_tunables(&mut self, tunables: Tunables) -> Result<()> {
+        if tunables.generate_native_debuginfo {
+            bail!("native debug information is not supported by the interpreter");
+        }
+        if tunables.pgo_instrumentation {
+            bail!(
+                "profile-guided optimization instrumentation is not supported by the interpreter"
+            );
+        }
+        self.tunables = tunables;
+        Ok(())
+    }
+
+    fn build(&self) -> Result<Box<dyn wasmtime_environ::Compiler>> {
+        Ok(Box::new(Compiler::new(
+            self.triple.clone(),
+            self.tunables.clone(),
+        )))
+    }
+
+    fn enable_incremental_compilation(&mut self, _cache_store: Arc<dyn CacheStore>) -> Result<()> {
+        bail!("incremental compilation is not supported by the interpreter");
+    }
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("triple", &self.triple)
+            .finish()
+    }
+}
+
+/// Rejects settings the interpreter doesn't know about, like the native
+/// compilers do, so that misspelled settings aren't silently ignored.
+fn check_setting(name: &str) -> Result<()> {
+    if !NATIVE_SETTINGS.contains(&name) {
+        bail!("no existing setting named '{name}'");
+    }
+    Ok(())
+}
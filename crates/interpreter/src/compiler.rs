@@ -0,0 +1,206 @@
+use crate::translate::FuncTranslator;
+use anyhow::{bail, Result};
+use object::write::{Object, SectionId, Symbol, SymbolId, SymbolSection};
+use object::{SectionKind, SymbolFlags, SymbolKind, SymbolScope};
+use std::any::Any;
+use std::sync::Mutex;
+use target_lexicon::Triple;
+use wasmparser::FuncValidatorAllocations;
+use wasmtime_environ::obj::ELF_WASMTIME_BYTECODE;
+use wasmtime_environ::{
+    CompileError, DefinedFuncIndex, FilePos, FlagValue, FuncIndex, FunctionBodyData, FunctionLoc,
+    ModuleTranslation, ModuleTypesBuilder, PrimaryMap, TrapEncodingBuilder, Tunables, WasmFuncType,
+    WasmFunctionInfo,
+};
+
+/// Alignment of functions within the text section, which keeps function
+/// headers aligned.
+const FUNCTION_ALIGNMENT: u64 = 16;
+
+/// The bytecode of a function, or of a trampoline.
+///
+/// Interpreted functions are entered directly by the interpreter, so
+/// trampolines are empty. They're still emitted to keep the layout of the
+/// compiled image the same as for native code.
+struct Bytecode(Vec<u8>);
+
+pub(crate) struct Compiler {
+    triple: Triple,
+    tunables: Tunables,
+    allocations: Mutex<Vec<FuncValidatorAllocations>>,
+}
+
+impl Compiler {
+    pub fn new(triple: Triple, tunables: Tunables) -> Self {
+        Self {
+            triple,
+            tunables,
+            allocations: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl wasmtime_environ::Compiler for Compiler {
+    fn compile_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        data: FunctionBodyData<'_>,
+        types: &ModuleTypesBuilder,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let FunctionBodyData { body, validator } = data;
+        let start_srcloc = FilePos::new(
+            body.get_binary_reader()
+                .original_position()
+                .try_into()
+                .unwrap(),
+        );
+        let allocations = self.allocations.lock().unwrap().pop();
+        let mut validator = validator.into_validator(allocations.unwrap_or_default());
+        let code = FuncTranslator::new(translation, types, &self.tunables).translate(
+            index,
+            &body,
+            &mut validator,
+        );
+        self.allocations
+            .lock()
+            .unwrap()
+            .push(validator.into_allocations());
+
+        Ok((
+            WasmFunctionInfo {
+                start_srcloc,
+                stack_maps: Box::new([]),
+                locals: Box::new([]),
+                local_slots: Box::new([]),
+                operand_slots: Box::new([]),
+                operand_stack_heights: Box::new([]),
+            },
+            Box::new(Bytecode(code?)),
+        ))
+    }
+
+    fn compile_array_to_wasm_trampoline(
+        &self,
+        _translation: &ModuleTranslation<'_>,
+        _types: &ModuleTypesBuilder,
+        _index: DefinedFuncIndex,
+    ) -> Result<Box<dyn Any + Send>, CompileError> {
+        Ok(Box::new(Bytecode(Vec::new())))
+    }
+
+    fn compile_native_to_wasm_trampoline(
+        &self,
+        _translation: &ModuleTranslation<'_>,
+        _types: &ModuleTypesBuilder,
+        _index: DefinedFuncIndex,
+    ) -> Result<Box<dyn Any + Send>, CompileError> {
+        Ok(Box::new(Bytecode(Vec::new())))
+    }
+
+    fn compile_wasm_to_native_trampoline(
+        &self,
+        _wasm_func_ty: &WasmFuncType,
+    ) -> Result<Box<dyn Any + Send>, CompileError> {
+        Ok(Box::new(Bytecode(Vec::new())))
+    }
+
+    fn append_code(
+        &self,
+        obj: &mut Object<'static>,
+        funcs: &[(String, Box<dyn Any + Send>)],
+        _resolve_reloc: &dyn Fn(usize, FuncIndex) -> usize,
+    ) -> Result<Vec<(SymbolId, FunctionLoc)>> {
+        let text = text_section(obj);
+        let mut ret = Vec::with_capacity(funcs.len());
+        for (name, func) in funcs {
+            let code = &func.downcast_ref::<Bytecode>().unwrap().0;
+            let start = obj.append_section_data(text, code, FUNCTION_ALIGNMENT);
+            let sym = obj.add_symbol(Symbol {
+                name: name.as_bytes().to_vec(),
+                value: start,
+                size: u64::try_from(code.len()).unwrap(),
+                kind: SymbolKind::Text,
+                scope: SymbolScope::Compilation,
+                weak: false,
+                section: SymbolSection::Section(text),
+                flags: SymbolFlags::None,
+            });
+            let info = FunctionLoc {
+                start: u32::try_from(start).unwrap(),
+                length: u32::try_from(code.len()).unwrap(),
+            };
+            ret.push((sym, info));
+        }
+
+        // Traps are raised by the interpreter itself rather than by faulting
+        // instructions, so there are no trapping offsets to record.
+        TrapEncodingBuilder::default().append_to(obj);
+        obj.add_section(
+            Vec::new(),
+            ELF_WASMTIME_BYTECODE.as_bytes().to_vec(),
+            SectionKind::ReadOnlyData,
+        );
+        Ok(ret)
+    }
+
+    fn emit_trampolines_for_array_call_host_func(
+        &self,
+        _ty: &WasmFuncType,
+        _host_fn: usize,
+        _obj: &mut Object<'static>,
+    ) -> Result<(FunctionLoc, FunctionLoc)> {
+        bail!("host functions are called directly by the interpreter and have no trampolines")
+    }
+
+    fn triple(&self) -> &Triple {
+        &self.triple
+    }
+
+    fn flags(&self) -> Vec<(&'static str, FlagValue<'static>)> {
+        Vec::new()
+    }
+
+    fn isa_flags(&self) -> Vec<(&'static str, FlagValue<'static>)> {
+        Vec::new()
+    }
+
+    fn is_branch_protection_enabled(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "component-model")]
+    fn component_compiler(&self) -> &dyn wasmtime_environ::component::ComponentCompiler {
+        self
+    }
+
+    fn append_dwarf(
+        &self,
+        _obj: &mut Object<'_>,
+        _translation: &ModuleTranslation<'_>,
+        _funcs: &PrimaryMap<DefinedFuncIndex, (SymbolId, &(dyn Any + Send))>,
+    ) -> Result<()> {
+        bail!("native debug information is not supported by the interpreter")
+    }
+}
+
+#[cfg(feature = "component-model")]
+impl wasmtime_environ::component::ComponentCompiler for Compiler {
+    fn compile_trampoline(
+        &self,
+        _component: &wasmtime_environ::component::ComponentTranslation,
+        _types: &wasmtime_environ::component::ComponentTypesBuilder,
+        _trampoline: wasmtime_environ::component::TrampolineIndex,
+    ) -> Result<wasmtime_environ::component::AllCallFunc<Box<dyn Any + Send>>> {
+        bail!("components are not supported by the interpreter")
+    }
+}
+
+/// Returns the text section of `obj`, where bytecode is placed just like
+/// native code.
+fn text_section(obj: &mut Object<'static>) -> SectionId {
+    let segment = obj
+        .segment_name(object::write::StandardSegment::Text)
+        .to_vec();
+    obj.add_section(segment, b".text".to_vec(), SectionKind::Text)
+}
@@ -0,0 +1,15 @@
+//! Compilation of WebAssembly to the bytecode of Wasmtime's portable
+//! interpreter.
+//!
+//! Instead of generating native code, the compiler in this crate translates
+//! each WebAssembly function to the register-based bytecode described in
+//! `wasmtime_environ::bytecode`, which is executed by the interpreter in
+//! `wasmtime-runtime`. This allows running WebAssembly on hosts where
+//! generating native code at runtime isn't possible or not supported by
+//! Wasmtime's other compilers.
+
+pub use builder::builder;
+
+mod builder;
+mod compiler;
+mod translate;
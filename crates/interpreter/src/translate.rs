@@ -0,0 +1,1186 @@
+//! Translation of WebAssembly function bodies to the interpreter's bytecode.
+//!
+//! The translation is a single pass over the operators of a function which
+//! assigns a register to every slot of the wasm operand stack. Reads of locals
+//! aren't copied eagerly: the operand stack instead remembers which local holds
+//! a value and instructions read the local's register directly. Such values
+//! are only "materialized" into their slot's register when the local is about
+//! to be overwritten or when the operand stack must be in a canonical state,
+//! such as at the entry of blocks and at calls.
+
+use wasmparser::{
+    BlockType, FuncValidator, FunctionBody, HeapType, Operator, ValType, WasmModuleResources,
+};
+use wasmtime_environ::bytecode::{FunctionHeader, Op, Reg};
+use wasmtime_environ::{
+    wasm_unsupported, DefinedFuncIndex, FuncIndex, GlobalIndex, ModuleTranslation,
+    ModuleTypesBuilder, SignatureIndex, TableIndex, Tunables, TypeIndex, WasmError, WasmFuncType,
+    WasmRefType, WasmResult, WasmType,
+};
+
+/// Where the value of an operand stack slot currently lives.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Operand {
+    /// The value is the current value of the local in this register.
+    Local(Reg),
+    /// The value is in the register of its operand stack slot.
+    Slot,
+}
+
+enum FrameKind {
+    Function,
+    Block,
+    Loop {
+        /// Offset of the start of the loop, which branches to the loop jump
+        /// to.
+        start: u32,
+    },
+    If {
+        /// Position of the target of the branch to the `else` arm, which is
+        /// `None` once it has been patched.
+        else_fixup: Option<usize>,
+    },
+    Else,
+}
+
+/// A wasm control frame.
+struct Frame {
+    kind: FrameKind,
+    /// Height of the operand stack when the frame was entered, excluding its
+    /// parameters.
+    height: usize,
+    params: usize,
+    results: usize,
+    /// Positions of branch targets which need to be patched with the offset
+    /// of the end of this frame.
+    end_fixups: Vec<usize>,
+}
+
+impl Frame {
+    /// Number of values passed by branches to this frame.
+    fn branch_arity(&self) -> usize {
+        match self.kind {
+            FrameKind::Loop { .. } => self.params,
+            _ => self.results,
+        }
+    }
+}
+
+/// Translator of a single function to bytecode.
+pub(crate) struct FuncTranslator<'a> {
+    translation: &'a ModuleTranslation<'a>,
+    types: &'a ModuleTypesBuilder,
+    tunables: &'a Tunables,
+    /// Fuel consumed by the operators translated since fuel was last
+    /// consumed, which is consumed before control flow leaves straight-line
+    /// code.
+    fuel: u32,
+    code: Vec<u8>,
+    num_locals: usize,
+    num_results: usize,
+    stack: Vec<Operand>,
+    max_height: usize,
+    frames: Vec<Frame>,
+    /// Whether the current position in the function is reachable.
+    reachable: bool,
+    /// Number of blocks entered within unreachable code, which are skipped.
+    unreachable_depth: u32,
+}
+
+impl<'a> FuncTranslator<'a> {
+    pub(crate) fn new(
+        translation: &'a ModuleTranslation<'a>,
+        types: &'a ModuleTypesBuilder,
+        tunables: &'a Tunables,
+    ) -> Self {
+        FuncTranslator {
+            translation,
+            types,
+            tunables,
+            fuel: 0,
+            code: Vec::new(),
+            num_locals: 0,
+            num_results: 0,
+            stack: Vec::new(),
+            max_height: 0,
+            frames: Vec::new(),
+            reachable: true,
+            unreachable_depth: 0,
+        }
+    }
+
+    /// Translates the body of the defined function `index`, returning its
+    /// bytecode.
+    pub(crate) fn translate(
+        mut self,
+        index: DefinedFuncIndex,
+        body: &FunctionBody<'_>,
+        validator: &mut FuncValidator<impl WasmModuleResources>,
+    ) -> WasmResult<Vec<u8>> {
+        let module = &self.translation.module;
+        if module
+            .memory_plans
+            .values()
+            .any(|plan| plan.memory.memory64)
+        {
+            return Err(wasm_unsupported!(
+                "64-bit memories are not supported by the interpreter"
+            ));
+        }
+        if module.memory_plans.values().any(|plan| plan.memory.shared) {
+            return Err(wasm_unsupported!(
+                "shared memories are not supported by the interpreter"
+            ));
+        }
+
+        let sig = module.functions[module.func_index(index)].signature;
+        let ty = &self.types[sig];
+        check_func_type(ty)?;
+        let num_params = ty.params().len();
+        self.num_results = ty.returns().len();
+
+        let mut reader = body.get_binary_reader();
+        self.num_locals = num_params;
+        for _ in 0..reader.read_var_u32()? {
+            let pos = reader.original_position();
+            let count = reader.read_var_u32()?;
+            let ty = reader.read()?;
+            validator.define_locals(pos, count, ty)?;
+            check_val_type(ty)?;
+            self.num_locals += count as usize;
+        }
+
+        self.code.resize(FunctionHeader::SIZE, 0);
+        self.frames.push(Frame {
+            kind: FrameKind::Function,
+            height: 0,
+            params: 0,
+            results: self.num_results,
+            end_fixups: Vec::new(),
+        });
+        self.check_interrupts();
+        while !reader.eof() {
+            let pos = reader.original_position();
+            let op = reader.read_operator()?;
+            validator.op(pos, &op)?;
+            self.translate_operator(&op)?;
+        }
+        validator.finish(reader.original_position())?;
+        debug_assert!(self.frames.is_empty());
+
+        // Registers are encoded as 16-bit indices, so larger frames can't be
+        // represented. Registers emitted beyond this limit were truncated, but
+        // the code is discarded here.
+        let frame_size = self.num_locals + self.max_height;
+        if frame_size > usize::from(Reg::MAX) + 1 {
+            return Err(WasmError::ImplLimitExceeded);
+        }
+        let header = FunctionHeader {
+            num_params: u32::try_from(num_params).unwrap(),
+            num_results: u32::try_from(self.num_results).unwrap(),
+            num_locals: u32::try_from(self.num_locals).unwrap(),
+            frame_size: u32::try_from(frame_size).unwrap(),
+        };
+        self.code[..FunctionHeader::SIZE].copy_from_slice(&header.to_bytes());
+        Ok(self.code)
+    }
+
+    fn translate_operator(&mut self, op: &Operator<'_>) -> WasmResult<()> {
+        if !self.reachable {
+            return self.translate_unreachable_operator(op);
+        }
+
+        if self.tunables.consume_fuel {
+            self.fuel += fuel_cost(op);
+        }
+
+        match *op {
+            Operator::Nop => {}
+            Operator::Unreachable => {
+                self.consume_fuel();
+                self.op(Op::Unreachable);
+                self.reachable = false;
+            }
+            Operator::Block { blockty } => {
+                let (params, results) = self.block_arity(blockty)?;
+                self.push_frame(FrameKind::Block, params, results);
+            }
+            Operator::Loop { blockty } => {
+                let (params, results) = self.block_arity(blockty)?;
+                self.materialize_all();
+                self.consume_fuel();
+                let start = self.offset();
+                self.check_interrupts();
+                self.push_frame(FrameKind::Loop { start }, params, results);
+            }
+            Operator::If { blockty } => {
+                let (params, results) = self.block_arity(blockty)?;
+                let cond = self.pop();
+                self.materialize_all();
+                self.consume_fuel();
+                self.op(Op::BrIfNot);
+                self.reg(cond);
+                let else_fixup = Some(self.fixup());
+                self.push_frame(FrameKind::If { else_fixup }, params, results);
+            }
+            Operator::Else => self.translate_else(),
+            Operator::End => self.translate_end(),
+            Operator::Br { relative_depth } => {
+                self.branch(relative_depth);
+                self.reachable = false;
+            }
+            Operator::BrIf { relative_depth } => self.translate_br_if(relative_depth),
+            Operator::BrTable { ref targets } => {
+                let default = targets.default();
+                let targets = targets.targets().collect::<Result<Vec<_>, _>>()?;
+                self.translate_br_table(&targets, default);
+                self.reachable = false;
+            }
+            Operator::Return => {
+                self.return_();
+                self.reachable = false;
+            }
+            Operator::Call { function_index } => {
+                let func = FuncIndex::from_u32(function_index);
+                let sig = self.translation.module.functions[func].signature;
+                let (base, params, results) = self.call_args(sig)?;
+                self.consume_fuel();
+                self.op(Op::Call);
+                self.u32(function_index);
+                self.call_operands(base, params, results);
+            }
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+                ..
+            } => {
+                let sig = self.signature(type_index);
+                let index = self.pop();
+                let (base, params, results) = self.call_args(sig)?;
+                self.consume_fuel();
+                self.op(Op::CallIndirect);
+                self.u32(table_index);
+                self.u32(sig.as_u32());
+                self.reg(index);
+                self.call_operands(base, params, results);
+            }
+
+            Operator::Drop => {
+                self.pop();
+            }
+            Operator::Select => self.select(),
+            Operator::TypedSelect { ty } => {
+                check_val_type(ty)?;
+                self.select();
+            }
+            Operator::LocalGet { local_index } => {
+                self.push(Operand::Local(local_index as Reg));
+            }
+            Operator::LocalSet { local_index } => {
+                let src = self.pop();
+                self.set_local(local_index as Reg, src);
+            }
+            Operator::LocalTee { local_index } => {
+                let src = self.pop();
+                self.set_local(local_index as Reg, src);
+                self.push(Operand::Local(local_index as Reg));
+            }
+            Operator::GlobalGet { global_index } => {
+                let (op, _) = self.global_ops(global_index)?;
+                let dst = self.push_slot();
+                self.op(op);
+                self.reg(dst);
+                self.u32(global_index);
+            }
+            Operator::GlobalSet { global_index } => {
+                let (_, op) = self.global_ops(global_index)?;
+                let src = self.pop();
+                self.op(op);
+                self.u32(global_index);
+                self.reg(src);
+            }
+
+            Operator::I32Const { value } => {
+                let dst = self.push_slot();
+                self.op(Op::Const32);
+                self.reg(dst);
+                self.u32(value as u32);
+            }
+            Operator::I64Const { value } => {
+                let dst = self.push_slot();
+                self.op(Op::Const64);
+                self.reg(dst);
+                self.u64(value as u64);
+            }
+            Operator::F32Const { value } => {
+                let dst = self.push_slot();
+                self.op(Op::Const32);
+                self.reg(dst);
+                self.u32(value.bits());
+            }
+            Operator::F64Const { value } => {
+                let dst = self.push_slot();
+                self.op(Op::Const64);
+                self.reg(dst);
+                self.u64(value.bits());
+            }
+
+            Operator::I32Load { memarg } | Operator::F32Load { memarg } => {
+                self.load(Op::Load32U, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Load { memarg } | Operator::F64Load { memarg } => {
+                self.load(Op::Load64, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Load8U { memarg } | Operator::I64Load8U { memarg } => {
+                self.load(Op::Load8U, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Load16U { memarg } | Operator::I64Load16U { memarg } => {
+                self.load(Op::Load16U, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Load32U { memarg } => {
+                self.load(Op::Load32U, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Load8S { memarg } => {
+                self.load(Op::I32Load8S, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Load16S { memarg } => {
+                self.load(Op::I32Load16S, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Load8S { memarg } => {
+                self.load(Op::I64Load8S, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Load16S { memarg } => {
+                self.load(Op::I64Load16S, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Load32S { memarg } => {
+                self.load(Op::I64Load32S, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Store8 { memarg } | Operator::I64Store8 { memarg } => {
+                self.store(Op::Store8, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Store16 { memarg } | Operator::I64Store16 { memarg } => {
+                self.store(Op::Store16, memarg.memory, memarg.offset)?
+            }
+            Operator::I32Store { memarg }
+            | Operator::I64Store32 { memarg }
+            | Operator::F32Store { memarg } => {
+                self.store(Op::Store32, memarg.memory, memarg.offset)?
+            }
+            Operator::I64Store { memarg } | Operator::F64Store { memarg } => {
+                self.store(Op::Store64, memarg.memory, memarg.offset)?
+            }
+            Operator::MemorySize { mem, .. } => {
+                check_memory(mem)?;
+                let dst = self.push_slot();
+                self.op(Op::MemorySize);
+                self.reg(dst);
+            }
+            Operator::MemoryGrow { mem, .. } => {
+                check_memory(mem)?;
+                self.unary(Op::MemoryGrow);
+            }
+            Operator::MemoryFill { mem } => {
+                check_memory(mem)?;
+                self.ternary(Op::MemoryFill);
+            }
+            Operator::MemoryCopy { dst_mem, src_mem } => {
+                check_memory(dst_mem)?;
+                check_memory(src_mem)?;
+                self.ternary(Op::MemoryCopy);
+            }
+            Operator::MemoryInit { data_index, mem } => {
+                check_memory(mem)?;
+                let len = self.pop();
+                let src = self.pop();
+                let dst = self.pop();
+                self.op(Op::MemoryInit);
+                self.u32(data_index);
+                self.reg(dst);
+                self.reg(src);
+                self.reg(len);
+            }
+            Operator::DataDrop { data_index } => {
+                self.op(Op::DataDrop);
+                self.u32(data_index);
+            }
+
+            Operator::TableGet { table } => {
+                self.check_table(table)?;
+                let index = self.pop();
+                let dst = self.push_slot();
+                self.op(Op::TableGet);
+                self.reg(dst);
+                self.u32(table);
+                self.reg(index);
+            }
+            Operator::TableSet { table } => {
+                self.check_table(table)?;
+                let val = self.pop();
+                let index = self.pop();
+                self.op(Op::TableSet);
+                self.u32(table);
+                self.reg(index);
+                self.reg(val);
+            }
+            Operator::TableSize { table } => {
+                self.check_table(table)?;
+                let dst = self.push_slot();
+                self.op(Op::TableSize);
+                self.reg(dst);
+                self.u32(table);
+            }
+            Operator::TableGrow { table } => {
+                self.check_table(table)?;
+                let delta = self.pop();
+                let init = self.pop();
+                let dst = self.push_slot();
+                self.op(Op::TableGrow);
+                self.reg(dst);
+                self.u32(table);
+                self.reg(init);
+                self.reg(delta);
+            }
+            Operator::TableFill { table } => {
+                self.check_table(table)?;
+                let len = self.pop();
+                let val = self.pop();
+                let dst = self.pop();
+                self.op(Op::TableFill);
+                self.u32(table);
+                self.reg(dst);
+                self.reg(val);
+                self.reg(len);
+            }
+            Operator::TableCopy {
+                dst_table,
+                src_table,
+            } => {
+                self.check_table(dst_table)?;
+                self.check_table(src_table)?;
+                let len = self.pop();
+                let src = self.pop();
+                let dst = self.pop();
+                self.op(Op::TableCopy);
+                self.u32(dst_table);
+                self.u32(src_table);
+                self.reg(dst);
+                self.reg(src);
+                self.reg(len);
+            }
+            Operator::TableInit { elem_index, table } => {
+                self.check_table(table)?;
+                let len = self.pop();
+                let src = self.pop();
+                let dst = self.pop();
+                self.op(Op::TableInit);
+                self.u32(table);
+                self.u32(elem_index);
+                self.reg(dst);
+                self.reg(src);
+                self.reg(len);
+            }
+            Operator::ElemDrop { elem_index } => {
+                self.op(Op::ElemDrop);
+                self.u32(elem_index);
+            }
+            Operator::RefNull {
+                hty: HeapType::Func,
+            } => {
+                let dst = self.push_slot();
+                self.op(Op::Const32);
+                self.reg(dst);
+                self.u32(0);
+            }
+            Operator::RefFunc { function_index } => {
+                let dst = self.push_slot();
+                self.op(Op::RefFunc);
+                self.reg(dst);
+                self.u32(function_index);
+            }
+            // Values of other reference types can't be produced, so this is
+            // always a `funcref`.
+            Operator::RefIsNull => self.unary(Op::RefIsNull),
+
+            // Values are stored as bits in registers, so reinterpreting them
+            // is a no-op.
+            Operator::I32ReinterpretF32
+            | Operator::I64ReinterpretF64
+            | Operator::F32ReinterpretI32
+            | Operator::F64ReinterpretI64 => {}
+
+            Operator::I32Add => self.binary(Op::I32Add),
+            Operator::I32Sub => self.binary(Op::I32Sub),
+            Operator::I32Mul => self.binary(Op::I32Mul),
+            Operator::I32DivS => self.binary(Op::I32DivS),
+            Operator::I32DivU => self.binary(Op::I32DivU),
+            Operator::I32RemS => self.binary(Op::I32RemS),
+            Operator::I32RemU => self.binary(Op::I32RemU),
+            Operator::I32And => self.binary(Op::I32And),
+            Operator::I32Or => self.binary(Op::I32Or),
+            Operator::I32Xor => self.binary(Op::I32Xor),
+            Operator::I32Shl => self.binary(Op::I32Shl),
+            Operator::I32ShrS => self.binary(Op::I32ShrS),
+            Operator::I32ShrU => self.binary(Op::I32ShrU),
+            Operator::I32Rotl => self.binary(Op::I32Rotl),
+            Operator::I32Rotr => self.binary(Op::I32Rotr),
+            Operator::I32Eq => self.binary(Op::I32Eq),
+            Operator::I32Ne => self.binary(Op::I32Ne),
+            Operator::I32LtS => self.binary(Op::I32LtS),
+            Operator::I32LtU => self.binary(Op::I32LtU),
+            Operator::I32GtS => self.binary(Op::I32GtS),
+            Operator::I32GtU => self.binary(Op::I32GtU),
+            Operator::I32LeS => self.binary(Op::I32LeS),
+            Operator::I32LeU => self.binary(Op::I32LeU),
+            Operator::I32GeS => self.binary(Op::I32GeS),
+            Operator::I32GeU => self.binary(Op::I32GeU),
+            Operator::I32Eqz => self.unary(Op::I32Eqz),
+            Operator::I32Clz => self.unary(Op::I32Clz),
+            Operator::I32Ctz => self.unary(Op::I32Ctz),
+            Operator::I32Popcnt => self.unary(Op::I32Popcnt),
+            Operator::I32Extend8S => self.unary(Op::I32Extend8S),
+            Operator::I32Extend16S => self.unary(Op::I32Extend16S),
+            Operator::I32WrapI64 => self.unary(Op::I32WrapI64),
+
+            Operator::I64Add => self.binary(Op::I64Add),
+            Operator::I64Sub => self.binary(Op::I64Sub),
+            Operator::I64Mul => self.binary(Op::I64Mul),
+            Operator::I64DivS => self.binary(Op::I64DivS),
+            Operator::I64DivU => self.binary(Op::I64DivU),
+            Operator::I64RemS => self.binary(Op::I64RemS),
+            Operator::I64RemU => self.binary(Op::I64RemU),
+            Operator::I64And => self.binary(Op::I64And),
+            Operator::I64Or => self.binary(Op::I64Or),
+            Operator::I64Xor => self.binary(Op::I64Xor),
+            Operator::I64Shl => self.binary(Op::I64Shl),
+            Operator::I64ShrS => self.binary(Op::I64ShrS),
+            Operator::I64ShrU => self.binary(Op::I64ShrU),
+            Operator::I64Rotl => self.binary(Op::I64Rotl),
+            Operator::I64Rotr => self.binary(Op::I64Rotr),
+            Operator::I64Eq => self.binary(Op::I64Eq),
+            Operator::I64Ne => self.binary(Op::I64Ne),
+            Operator::I64LtS => self.binary(Op::I64LtS),
+            Operator::I64LtU => self.binary(Op::I64LtU),
+            Operator::I64GtS => self.binary(Op::I64GtS),
+            Operator::I64GtU => self.binary(Op::I64GtU),
+            Operator::I64LeS => self.binary(Op::I64LeS),
+            Operator::I64LeU => self.binary(Op::I64LeU),
+            Operator::I64GeS => self.binary(Op::I64GeS),
+            Operator::I64GeU => self.binary(Op::I64GeU),
+            Operator::I64Eqz => self.unary(Op::I64Eqz),
+            Operator::I64Clz => self.unary(Op::I64Clz),
+            Operator::I64Ctz => self.unary(Op::I64Ctz),
+            Operator::I64Popcnt => self.unary(Op::I64Popcnt),
+            Operator::I64Extend8S => self.unary(Op::I64Extend8S),
+            Operator::I64Extend16S => self.unary(Op::I64Extend16S),
+            Operator::I64Extend32S | Operator::I64ExtendI32S => self.unary(Op::I64Extend32S),
+            Operator::I64ExtendI32U => self.unary(Op::I64ExtendI32U),
+
+            Operator::F32Add => self.binary(Op::F32Add),
+            Operator::F32Sub => self.binary(Op::F32Sub),
+            Operator::F32Mul => self.binary(Op::F32Mul),
+            Operator::F32Div => self.binary(Op::F32Div),
+            Operator::F32Min => self.binary(Op::F32Min),
+            Operator::F32Max => self.binary(Op::F32Max),
+            Operator::F32Copysign => self.binary(Op::F32Copysign),
+            Operator::F32Eq => self.binary(Op::F32Eq),
+            Operator::F32Ne => self.binary(Op::F32Ne),
+            Operator::F32Lt => self.binary(Op::F32Lt),
+            Operator::F32Gt => self.binary(Op::F32Gt),
+            Operator::F32Le => self.binary(Op::F32Le),
+            Operator::F32Ge => self.binary(Op::F32Ge),
+            Operator::F32Abs => self.unary(Op::F32Abs),
+            Operator::F32Neg => self.unary(Op::F32Neg),
+            Operator::F32Sqrt => self.unary(Op::F32Sqrt),
+            Operator::F32Ceil => self.unary(Op::F32Ceil),
+            Operator::F32Floor => self.unary(Op::F32Floor),
+            Operator::F32Trunc => self.unary(Op::F32Trunc),
+            Operator::F32Nearest => self.unary(Op::F32Nearest),
+
+            Operator::F64Add => self.binary(Op::F64Add),
+            Operator::F64Sub => self.binary(Op::F64Sub),
+            Operator::F64Mul => self.binary(Op::F64Mul),
+            Operator::F64Div => self.binary(Op::F64Div),
+            Operator::F64Min => self.binary(Op::F64Min),
+            Operator::F64Max => self.binary(Op::F64Max),
+            Operator::F64Copysign => self.binary(Op::F64Copysign),
+            Operator::F64Eq => self.binary(Op::F64Eq),
+            Operator::F64Ne => self.binary(Op::F64Ne),
+            Operator::F64Lt => self.binary(Op::F64Lt),
+            Operator::F64Gt => self.binary(Op::F64Gt),
+            Operator::F64Le => self.binary(Op::F64Le),
+            Operator::F64Ge => self.binary(Op::F64Ge),
+            Operator::F64Abs => self.unary(Op::F64Abs),
+            Operator::F64Neg => self.unary(Op::F64Neg),
+            Operator::F64Sqrt => self.unary(Op::F64Sqrt),
+            Operator::F64Ceil => self.unary(Op::F64Ceil),
+            Operator::F64Floor => self.unary(Op::F64Floor),
+            Operator::F64Trunc => self.unary(Op::F64Trunc),
+            Operator::F64Nearest => self.unary(Op::F64Nearest),
+
+            Operator::I32TruncF32S => self.unary(Op::I32TruncF32S),
+            Operator::I32TruncF32U => self.unary(Op::I32TruncF32U),
+            Operator::I32TruncF64S => self.unary(Op::I32TruncF64S),
+            Operator::I32TruncF64U => self.unary(Op::I32TruncF64U),
+            Operator::I64TruncF32S => self.unary(Op::I64TruncF32S),
+            Operator::I64TruncF32U => self.unary(Op::I64TruncF32U),
+            Operator::I64TruncF64S => self.unary(Op::I64TruncF64S),
+            Operator::I64TruncF64U => self.unary(Op::I64TruncF64U),
+            Operator::I32TruncSatF32S => self.unary(Op::I32TruncSatF32S),
+            Operator::I32TruncSatF32U => self.unary(Op::I32TruncSatF32U),
+            Operator::I32TruncSatF64S => self.unary(Op::I32TruncSatF64S),
+            Operator::I32TruncSatF64U => self.unary(Op::I32TruncSatF64U),
+            Operator::I64TruncSatF32S => self.unary(Op::I64TruncSatF32S),
+            Operator::I64TruncSatF32U => self.unary(Op::I64TruncSatF32U),
+            Operator::I64TruncSatF64S => self.unary(Op::I64TruncSatF64S),
+            Operator::I64TruncSatF64U => self.unary(Op::I64TruncSatF64U),
+            Operator::F32ConvertI32S => self.unary(Op::F32ConvertI32S),
+            Operator::F32ConvertI32U => self.unary(Op::F32ConvertI32U),
+            Operator::F32ConvertI64S => self.unary(Op::F32ConvertI64S),
+            Operator::F32ConvertI64U => self.unary(Op::F32ConvertI64U),
+            Operator::F64ConvertI32S => self.unary(Op::F64ConvertI32S),
+            Operator::F64ConvertI32U => self.unary(Op::F64ConvertI32U),
+            Operator::F64ConvertI64S => self.unary(Op::F64ConvertI64S),
+            Operator::F64ConvertI64U => self.unary(Op::F64ConvertI64U),
+            Operator::F32DemoteF64 => self.unary(Op::F32DemoteF64),
+            Operator::F64PromoteF32 => self.unary(Op::F64PromoteF32),
+
+            ref op => {
+                return Err(wasm_unsupported!(
+                    "operator {op:?} is not supported by the interpreter"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips operators in unreachable code, only keeping track of the nesting
+    /// of blocks to find where code becomes reachable again.
+    fn translate_unreachable_operator(&mut self, op: &Operator<'_>) -> WasmResult<()> {
+        match *op {
+            Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                self.unreachable_depth += 1;
+            }
+            Operator::Else if self.unreachable_depth == 0 => self.translate_else(),
+            Operator::End if self.unreachable_depth == 0 => self.translate_end(),
+            Operator::End => self.unreachable_depth -= 1,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn translate_else(&mut self) {
+        if self.reachable {
+            self.fallthrough();
+            self.consume_fuel();
+            self.op(Op::Jump);
+            let fixup = self.fixup();
+            self.frames.last_mut().unwrap().end_fixups.push(fixup);
+        }
+        let else_target = self.offset();
+        let frame = self.frames.last_mut().unwrap();
+        let else_fixup = match &mut frame.kind {
+            FrameKind::If { else_fixup } => else_fixup.take().unwrap(),
+            _ => unreachable!(),
+        };
+        frame.kind = FrameKind::Else;
+
+        // The `else` arm starts with the parameters of the `if`, which were
+        // materialized in their slots on entry.
+        let height = frame.height + frame.params;
+        self.stack.truncate(frame.height);
+        self.stack.resize(height, Operand::Slot);
+        self.patch(else_fixup, else_target);
+        self.reachable = true;
+    }
+
+    fn translate_end(&mut self) {
+        if self.reachable {
+            if let FrameKind::Function = self.frames.last().unwrap().kind {
+                self.return_();
+            } else {
+                self.fallthrough();
+                self.consume_fuel();
+            }
+        }
+        let frame = self.frames.pop().unwrap();
+        let end = self.offset();
+        for fixup in &frame.end_fixups {
+            self.patch(*fixup, end);
+        }
+
+        // Without an `else` arm a false condition falls through to the end of
+        // the `if`, where its parameters are its results.
+        let mut else_reachable = false;
+        if let FrameKind::If {
+            else_fixup: Some(fixup),
+        } = frame.kind
+        {
+            self.patch(fixup, end);
+            else_reachable = true;
+        }
+
+        self.reachable = self.reachable || else_reachable || !frame.end_fixups.is_empty();
+        self.stack.truncate(frame.height);
+        for _ in 0..frame.results {
+            self.push_slot();
+        }
+    }
+
+    fn translate_br_if(&mut self, relative_depth: u32) {
+        let cond = self.pop();
+        self.consume_fuel();
+        if self.needs_branch_copies(relative_depth) {
+            self.op(Op::BrIfNot);
+            self.reg(cond);
+            let skip = self.fixup();
+            self.branch(relative_depth);
+            let end = self.offset();
+            self.patch(skip, end);
+        } else {
+            self.op(Op::BrIf);
+            self.reg(cond);
+            self.target(relative_depth);
+        }
+    }
+
+    fn translate_br_table(&mut self, targets: &[u32], default: u32) {
+        let index = self.pop();
+        self.consume_fuel();
+        self.op(Op::BrTable);
+        self.reg(index);
+        self.u32(u32::try_from(targets.len()).unwrap());
+
+        // Targets which need to move values are routed through stubs emitted
+        // after the table, one per distinct depth.
+        let mut stubs: Vec<(u32, Vec<usize>)> = Vec::new();
+        for depth in targets.iter().copied().chain(Some(default)) {
+            if self.needs_branch_copies(depth) {
+                let fixup = self.fixup();
+                match stubs.iter_mut().find(|(d, _)| *d == depth) {
+                    Some((_, fixups)) => fixups.push(fixup),
+                    None => stubs.push((depth, vec![fixup])),
+                }
+            } else {
+                self.target(depth);
+            }
+        }
+        for (depth, fixups) in stubs {
+            let stub = self.offset();
+            for fixup in fixups {
+                self.patch(fixup, stub);
+            }
+            self.branch(depth);
+        }
+    }
+
+    /// Pushes a new control frame with `params` parameters on top of the
+    /// operand stack.
+    ///
+    /// All values on the operand stack are materialized beforehand so that
+    /// every path reaching a label of the frame agrees on where they live.
+    fn push_frame(&mut self, kind: FrameKind, params: usize, results: usize) {
+        self.materialize_all();
+        self.frames.push(Frame {
+            kind,
+            height: self.stack.len() - params,
+            params,
+            results,
+            end_fixups: Vec::new(),
+        });
+    }
+
+    fn block_arity(&self, ty: BlockType) -> WasmResult<(usize, usize)> {
+        Ok(match ty {
+            BlockType::Empty => (0, 0),
+            BlockType::Type(ty) => {
+                check_val_type(ty)?;
+                (0, 1)
+            }
+            BlockType::FuncType(index) => {
+                let ty = &self.types[self.signature(index)];
+                check_func_type(ty)?;
+                (ty.params().len(), ty.returns().len())
+            }
+        })
+    }
+
+    fn signature(&self, type_index: u32) -> SignatureIndex {
+        self.translation.module.types[TypeIndex::from_u32(type_index)].unwrap_function()
+    }
+
+    /// Returns the operations reading and writing the global `global_index`.
+    fn global_ops(&self, global_index: u32) -> WasmResult<(Op, Op)> {
+        let global = &self.translation.module.globals[GlobalIndex::from_u32(global_index)];
+        check_type(global.wasm_ty)?;
+        Ok(match global.wasm_ty {
+            WasmType::I32 | WasmType::F32 => (Op::GlobalGet32, Op::GlobalSet32),
+            WasmType::I64 | WasmType::F64 => (Op::GlobalGet64, Op::GlobalSet64),
+            _ => (Op::GlobalGetRef, Op::GlobalSetRef),
+        })
+    }
+
+    fn check_table(&self, table: u32) -> WasmResult<()> {
+        let plan = &self.translation.module.table_plans[TableIndex::from_u32(table)];
+        check_type(WasmType::Ref(plan.table.wasm_ty))
+    }
+
+    /// Moves the arguments of a call to `sig` into consecutive registers,
+    /// returning the first of them along with the number of parameters and
+    /// results of the call.
+    fn call_args(&mut self, sig: SignatureIndex) -> WasmResult<(Reg, u16, u16)> {
+        let types = self.types;
+        let ty = &types[sig];
+        check_func_type(ty)?;
+        let params = ty.params().len();
+        let results = ty.returns().len();
+        let base = self.stack.len() - params;
+        for i in base..self.stack.len() {
+            self.materialize(i);
+        }
+        self.stack.truncate(base);
+        for _ in 0..results {
+            self.push_slot();
+        }
+        // Make sure the frame has room for the arguments even if the callee
+        // has fewer results than parameters.
+        self.max_height = self.max_height.max(base + params);
+        let too_many = || WasmError::ImplLimitExceeded;
+        Ok((
+            self.slot(base),
+            u16::try_from(params).map_err(|_| too_many())?,
+            u16::try_from(results).map_err(|_| too_many())?,
+        ))
+    }
+
+    fn call_operands(&mut self, base: Reg, params: u16, results: u16) {
+        self.reg(base);
+        self.code.extend_from_slice(&params.to_le_bytes());
+        self.code.extend_from_slice(&results.to_le_bytes());
+    }
+
+    fn set_local(&mut self, local: Reg, src: Reg) {
+        for i in 0..self.stack.len() {
+            if self.stack[i] == Operand::Local(local) {
+                self.materialize(i);
+            }
+        }
+        if src != local {
+            self.op(Op::Copy);
+            self.reg(local);
+            self.reg(src);
+        }
+    }
+
+    fn select(&mut self) {
+        let cond = self.pop();
+        let b = self.pop();
+        let a = self.pop();
+        let dst = self.push_slot();
+        self.op(Op::Select);
+        self.reg(dst);
+        self.reg(a);
+        self.reg(b);
+        self.reg(cond);
+    }
+
+    fn load(&mut self, op: Op, memory: u32, offset: u64) -> WasmResult<()> {
+        check_memory(memory)?;
+        let addr = self.pop();
+        let dst = self.push_slot();
+        self.op(op);
+        self.reg(dst);
+        self.reg(addr);
+        self.u32(u32::try_from(offset).unwrap());
+        Ok(())
+    }
+
+    fn store(&mut self, op: Op, memory: u32, offset: u64) -> WasmResult<()> {
+        check_memory(memory)?;
+        let src = self.pop();
+        let addr = self.pop();
+        self.op(op);
+        self.reg(addr);
+        self.reg(src);
+        self.u32(u32::try_from(offset).unwrap());
+        Ok(())
+    }
+
+    fn unary(&mut self, op: Op) {
+        let a = self.pop();
+        let dst = self.push_slot();
+        self.op(op);
+        self.reg(dst);
+        self.reg(a);
+    }
+
+    fn binary(&mut self, op: Op) {
+        let b = self.pop();
+        let a = self.pop();
+        let dst = self.push_slot();
+        self.op(op);
+        self.reg(dst);
+        self.reg(a);
+        self.reg(b);
+    }
+
+    fn ternary(&mut self, op: Op) {
+        let c = self.pop();
+        let b = self.pop();
+        let a = self.pop();
+        self.op(op);
+        self.reg(a);
+        self.reg(b);
+        self.reg(c);
+    }
+
+    // Branches.
+
+    fn frame(&self, relative_depth: u32) -> &Frame {
+        &self.frames[self.frames.len() - 1 - relative_depth as usize]
+    }
+
+    /// Returns whether branching to `relative_depth` requires more than a
+    /// jump, either to move the branch's values or to return.
+    fn needs_branch_copies(&self, relative_depth: u32) -> bool {
+        let frame = self.frame(relative_depth);
+        if let FrameKind::Function = frame.kind {
+            return true;
+        }
+        let arity = frame.branch_arity();
+        let base = self.stack.len() - arity;
+        base != frame.height || self.stack[base..].iter().any(|o| *o != Operand::Slot)
+    }
+
+    /// Emits an unconditional branch to `relative_depth`, moving the values
+    /// it passes to the slots the target expects them in.
+    fn branch(&mut self, relative_depth: u32) {
+        let frame = self.frame(relative_depth);
+        if let FrameKind::Function = frame.kind {
+            return self.return_();
+        }
+        let (height, arity) = (frame.height, frame.branch_arity());
+        self.copy_to_frame(height, arity);
+        self.consume_fuel();
+        self.op(Op::Jump);
+        self.target(relative_depth);
+    }
+
+    /// Emits the branch target for `relative_depth`, to be patched later if
+    /// it's the end of a frame.
+    fn target(&mut self, relative_depth: u32) {
+        if let FrameKind::Loop { start } = self.frame(relative_depth).kind {
+            self.u32(start);
+        } else {
+            let fixup = self.fixup();
+            let index = self.frames.len() - 1 - relative_depth as usize;
+            self.frames[index].end_fixups.push(fixup);
+        }
+    }
+
+    /// Moves the results of the current frame to their slots when control
+    /// falls through to its end.
+    fn fallthrough(&mut self) {
+        let frame = self.frames.last().unwrap();
+        let (height, results) = (frame.height, frame.results);
+        self.copy_to_frame(height, results);
+    }
+
+    /// Copies the top `count` values of the operand stack to the slots
+    /// starting at `height`.
+    ///
+    /// Slots are copied in ascending order, which never overwrites a value
+    /// before it's copied as the destinations are below the sources.
+    fn copy_to_frame(&mut self, height: usize, count: usize) {
+        let base = self.stack.len() - count;
+        for i in 0..count {
+            let src = self.operand_reg(base + i);
+            let dst = self.slot(height + i);
+            if src != dst {
+                self.op(Op::Copy);
+                self.reg(dst);
+                self.reg(src);
+            }
+        }
+    }
+
+    /// Moves the function's results to the first registers of the frame and
+    /// returns.
+    fn return_(&mut self) {
+        let base = self.stack.len() - self.num_results;
+        // Results read from locals could be overwritten by the results before
+        // them, so they're materialized first. Other results are in slots,
+        // which are always above the registers they're copied to.
+        for i in base..self.stack.len() {
+            self.materialize(i);
+        }
+        for i in 0..self.num_results {
+            let src = self.slot(base + i);
+            let dst = i as Reg;
+            if src != dst {
+                self.op(Op::Copy);
+                self.reg(dst);
+                self.reg(src);
+            }
+        }
+        self.consume_fuel();
+        self.op(Op::Return);
+    }
+
+    // Fuel and epochs.
+
+    /// Consumes the fuel of the operators translated so far, which must be
+    /// done before control flow leaves straight-line code.
+    fn consume_fuel(&mut self) {
+        if self.fuel > 0 {
+            self.op(Op::ConsumeFuel);
+            self.u32(self.fuel);
+            self.fuel = 0;
+        }
+    }
+
+    /// Checks whether execution has run out of fuel or reached the epoch
+    /// deadline, at the entry of functions and loops like native code does.
+    fn check_interrupts(&mut self) {
+        if self.tunables.consume_fuel {
+            self.op(Op::CheckFuel);
+        }
+        if self.tunables.epoch_interruption {
+            self.op(Op::CheckEpoch);
+        }
+    }
+
+    // Operand stack.
+
+    fn slot(&self, index: usize) -> Reg {
+        (self.num_locals + index) as Reg
+    }
+
+    fn operand_reg(&self, index: usize) -> Reg {
+        match self.stack[index] {
+            Operand::Local(reg) => reg,
+            Operand::Slot => self.slot(index),
+        }
+    }
+
+    fn push(&mut self, operand: Operand) {
+        self.stack.push(operand);
+        self.max_height = self.max_height.max(self.stack.len());
+    }
+
+    /// Pushes a value which is computed into its slot, returning the slot's
+    /// register.
+    fn push_slot(&mut self) -> Reg {
+        self.push(Operand::Slot);
+        self.slot(self.stack.len() - 1)
+    }
+
+    fn pop(&mut self) -> Reg {
+        let reg = self.operand_reg(self.stack.len() - 1);
+        self.stack.pop();
+        reg
+    }
+
+    /// Copies the operand at `index` to its slot if it's read from a local.
+    fn materialize(&mut self, index: usize) {
+        if let Operand::Local(reg) = self.stack[index] {
+            let dst = self.slot(index);
+            self.op(Op::Copy);
+            self.reg(dst);
+            self.reg(reg);
+            self.stack[index] = Operand::Slot;
+        }
+    }
+
+    fn materialize_all(&mut self) {
+        for i in 0..self.stack.len() {
+            self.materialize(i);
+        }
+    }
+
+    // Encoding.
+
+    fn offset(&self) -> u32 {
+        u32::try_from(self.code.len()).unwrap()
+    }
+
+    fn op(&mut self, op: Op) {
+        self.code.push(op as u8);
+    }
+
+    fn reg(&mut self, reg: Reg) {
+        self.code.extend_from_slice(&reg.to_le_bytes());
+    }
+
+    fn u32(&mut self, val: u32) {
+        self.code.extend_from_slice(&val.to_le_bytes());
+    }
+
+    fn u64(&mut self, val: u64) {
+        self.code.extend_from_slice(&val.to_le_bytes());
+    }
+
+    /// Emits a placeholder branch target, returning its position to patch it
+    /// later.
+    fn fixup(&mut self) -> usize {
+        let pos = self.code.len();
+        self.u32(0);
+        pos
+    }
+
+    fn patch(&mut self, pos: usize, target: u32) {
+        self.code[pos..pos + 4].copy_from_slice(&target.to_le_bytes());
+    }
+}
+
+/// Returns the fuel consumed by `op`, which matches what native code consumes.
+fn fuel_cost(op: &Operator<'_>) -> u32 {
+    match op {
+        // Nop and drop generate no code, so don't consume fuel for them.
+        Operator::Nop | Operator::Drop => 0,
+
+        // Control flow is generally cheap, except for the condition of `if`.
+        Operator::Block { .. }
+        | Operator::Loop { .. }
+        | Operator::Unreachable
+        | Operator::Return
+        | Operator::Else
+        | Operator::End => 0,
+
+        _ => 1,
+    }
+}
+
+fn check_type(ty: WasmType) -> WasmResult<()> {
+    match ty {
+        WasmType::I32 | WasmType::I64 | WasmType::F32 | WasmType::F64 => Ok(()),
+        WasmType::Ref(WasmRefType::FUNCREF) => Ok(()),
+        WasmType::V128 | WasmType::Ref(_) => Err(wasm_unsupported!(
+            "values of type `{ty}` are not supported by the interpreter"
+        )),
+    }
+}
+
+fn check_val_type(ty: ValType) -> WasmResult<()> {
+    match ty {
+        ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 | ValType::FUNCREF => Ok(()),
+        _ => Err(wasm_unsupported!(
+            "values of type `{ty:?}` are not supported by the interpreter"
+        )),
+    }
+}
+
+fn check_func_type(ty: &WasmFuncType) -> WasmResult<()> {
+    for ty in ty.params().iter().chain(ty.returns()) {
+        check_type(*ty)?;
+    }
+    Ok(())
+}
+
+fn check_memory(memory: u32) -> WasmResult<()> {
+    if memory != 0 {
+        return Err(wasm_unsupported!(
+            "multiple memories are not supported by the interpreter"
+        ));
+    }
+    Ok(())
+}
@@ -21,6 +21,7 @@ pub struct CodeMemory {
     unwind_registration: ManuallyDrop<Option<UnwindRegistration>>,
     published: bool,
     enable_branch_protection: bool,
+    bytecode: bool,
 
     relocations: Vec<(usize, obj::LibCall)>,
 
@@ -64,6 +65,7 @@ impl CodeMemory {
         let mut text = 0..0;
         let mut unwind = 0..0;
         let mut enable_branch_protection = None;
+        let mut bytecode = false;
         let mut trap_data = 0..0;
         let mut wasm_data = 0..0;
         let mut address_map_data = 0..0;
@@ -124,6 +126,7 @@ impl CodeMemory {
                 obj::ELF_NAME_DATA => func_name_data = range,
                 obj::ELF_WASMTIME_INFO => info_data = range,
                 obj::ELF_WASMTIME_DWARF => dwarf = range,
+                obj::ELF_WASMTIME_BYTECODE => bytecode = true,
 
                 _ => log::debug!("ignoring section {name}"),
             }
@@ -134,6 +137,7 @@ impl CodeMemory {
             published: false,
             enable_branch_protection: enable_branch_protection
                 .ok_or_else(|| anyhow!("missing `{}` section", obj::ELF_WASM_BTI))?,
+            bytecode,
             text,
             unwind,
             trap_data,
@@ -159,6 +163,13 @@ impl CodeMemory {
         &self.mmap[self.text.clone()]
     }
 
+    /// Returns whether the text section contains bytecode for Wasmtime's
+    /// portable interpreter instead of native code.
+    #[inline]
+    pub fn is_bytecode(&self) -> bool {
+        self.bytecode
+    }
+
     /// Returns the contents of the `ELF_WASMTIME_DWARF` section.
     #[inline]
     pub fn dwarf(&self) -> &[u8] {
@@ -245,6 +256,12 @@ impl CodeMemory {
             // otherwise written to the image at any point either.
             self.mmap.make_readonly(0..self.mmap.len())?;
 
+            // Bytecode is only ever read by the interpreter, so there's
+            // nothing to execute or unwind.
+            if self.bytecode {
+                return Ok(());
+            }
+
             let text = self.text();
 
             // Clear the newly allocated code from cache if the processor requires it
@@ -21,6 +21,13 @@ pub unsafe fn get_next_older_pc_from_fp(fp: usize) -> usize {
 // by the current "FP".
 pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = 0;
 
+// The caller's stack pointer, which Cranelift uses as the canonical frame
+// address, depends on the size of the frame and so can't be found from the
+// backchain pointer alone.
+pub fn get_cfa_from_fp(_fp: usize) -> Option<usize> {
+    None
+}
+
 pub fn reached_entry_sp(fp: usize, entry_sp: usize) -> bool {
     fp > entry_sp
 }
@@ -278,6 +278,14 @@ impl ResourceTables<'_> {
 }
 
 impl ResourceTable {
+    /// Returns the number of handles, owned or borrowed, in this table.
+    pub fn num_handles(&self) -> usize {
+        self.slots
+            .iter()
+            .filter(|slot| !matches!(slot, Slot::Free { .. }))
+            .count()
+    }
+
     fn next(&self) -> usize {
         self.next as usize
     }
@@ -288,6 +288,16 @@ pub struct MemoryImageSlot {
     /// `self.accessible` and `self.static_size` is inaccessible.
     dirty: bool,
 
+    /// Whether part of `image` has been replaced with anonymous zero pages by
+    /// `discard()`. If so the mapping no longer matches `image` and it must be
+    /// removed when this slot is reset rather than reused as-is.
+    image_discarded: bool,
+
+    /// Whether the host should back this slot's anonymous memory with
+    /// transparent huge pages. Replacing a mapping discards this advice, so
+    /// it's reapplied whenever this slot maps fresh anonymous memory.
+    huge_pages: bool,
+
     /// Whether this MemoryImageSlot is responsible for mapping anonymous
     /// memory (to hold the reservation while overwriting mappings
     /// specific to this slot) in place when it is dropped. Default
@@ -311,6 +321,8 @@ impl MemoryImageSlot {
             accessible,
             image: None,
             dirty: false,
+            image_discarded: false,
+            huge_pages: false,
             clear_on_drop: true,
         }
     }
@@ -327,6 +339,8 @@ impl MemoryImageSlot {
             image: None,
             accessible: 0,
             dirty: false,
+            image_discarded: false,
+            huge_pages: false,
             clear_on_drop: false,
         }
     }
@@ -339,6 +353,15 @@ impl MemoryImageSlot {
         self.clear_on_drop = false;
     }
 
+    /// Configures whether the anonymous memory of this slot is backed by
+    /// transparent huge pages.
+    ///
+    /// The slot's existing mapping is assumed to already be configured
+    /// accordingly; this only affects mappings that the slot creates later.
+    pub(crate) fn set_huge_pages(&mut self, enable: bool) {
+        self.huge_pages = enable;
+    }
+
     pub(crate) fn set_heap_limit(&mut self, size_bytes: usize) -> Result<()> {
         assert!(size_bytes <= self.static_size);
 
@@ -449,9 +472,55 @@ impl MemoryImageSlot {
         if let Some(image) = &self.image {
             unsafe {
                 image.remap_as_zeros_at(self.base.as_ptr())?;
+                self.advise_huge_pages(image.linear_memory_offset, image.len);
             }
             self.image = None;
         }
+        self.image_discarded = false;
+        Ok(())
+    }
+
+    /// Zeroes the bytes of linear memory in `range`, releasing the physical
+    /// memory backing them.
+    ///
+    /// The `range` must be page-aligned and within the accessible region of
+    /// this slot. Pages outside of the image are released with
+    /// `vm::discard_pages`, but that can't be used on the CoW image itself
+    /// since on Linux `madvise` would bring back the image's contents rather
+    /// than zeros. The image's pages are instead replaced with a fresh
+    /// anonymous mapping, and the image is removed entirely on the next reset
+    /// so it's mapped in again for the next instantiation.
+    pub(crate) fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        assert!(range.start <= range.end);
+        assert!(range.end <= self.accessible);
+        if range.len() == 0 {
+            return Ok(());
+        }
+
+        let base = self.base.as_ptr();
+        let image = self.image.as_ref().and_then(|image| {
+            let start = range.start.max(image.linear_memory_offset);
+            let end = range.end.min(image.linear_memory_offset + image.len);
+            if start < end {
+                Some((image, start..end))
+            } else {
+                None
+            }
+        });
+        unsafe {
+            match image {
+                Some((image, overlap)) => {
+                    vm::discard_pages(base.add(range.start), overlap.start - range.start)?;
+                    image
+                        .source
+                        .remap_as_zeros_at(base.add(overlap.start), overlap.len())?;
+                    self.advise_huge_pages(overlap.start, overlap.len());
+                    vm::discard_pages(base.add(overlap.end), range.end - overlap.end)?;
+                    self.image_discarded = true;
+                }
+                None => vm::discard_pages(base.add(range.start), range.len())?,
+            }
+        }
         Ok(())
     }
 
@@ -476,6 +545,12 @@ impl MemoryImageSlot {
 
     #[allow(dead_code)] // ignore warnings as this is only used in some cfgs
     unsafe fn reset_all_memory_contents(&mut self, keep_resident: usize) -> Result<()> {
+        // If part of the image was discarded then resetting the image's pages
+        // wouldn't restore its contents, so remove the image altogether.
+        if self.image_discarded {
+            self.remove_image()?;
+        }
+
         if !vm::supports_madvise_dontneed() {
             // If we're not on Linux then there's no generic platform way to
             // reset memory back to its original state, so instead reset memory
@@ -590,6 +665,12 @@ impl MemoryImageSlot {
         Ok(())
     }
 
+    unsafe fn advise_huge_pages(&self, base: usize, len: usize) {
+        if self.huge_pages {
+            crate::enable_huge_pages(self.base.as_ptr().add(base), len);
+        }
+    }
+
     fn set_protection(&self, range: Range<usize>, readwrite: bool) -> Result<()> {
         assert!(range.start <= range.end);
         assert!(range.end <= self.static_size);
@@ -629,9 +710,11 @@ impl MemoryImageSlot {
 
         unsafe {
             vm::erase_existing_mapping(self.base.as_ptr(), self.static_size)?;
+            self.advise_huge_pages(0, self.static_size);
         }
 
         self.image = None;
+        self.image_discarded = false;
         self.accessible = 0;
 
         Ok(())
@@ -110,7 +110,7 @@ use std::mem;
 use std::ops::Deref;
 use std::ptr::{self, NonNull};
 use std::sync::atomic::{self, AtomicUsize, Ordering};
-use wasmtime_environ::StackMap;
+use wasmtime_environ::{StackMap, WasmFunctionInfo};
 
 /// An external reference to some opaque data.
 ///
@@ -803,6 +803,11 @@ pub trait ModuleInfoLookup {
 pub trait ModuleInfo {
     /// Lookup the stack map at a program counter value.
     fn lookup_stack_map(&self, pc: usize) -> Option<&StackMap>;
+
+    /// Lookup the information about the wasm function containing a program
+    /// counter value, along with the offset of the program counter within
+    /// that function's native code.
+    fn lookup_func_info(&self, pc: usize) -> Option<(&WasmFunctionInfo, u32)>;
 }
 
 #[derive(Debug, Default)]
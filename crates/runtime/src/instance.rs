@@ -13,7 +13,7 @@ use crate::vmcontext::{
 };
 use crate::{
     ExportFunction, ExportGlobal, ExportMemory, ExportTable, Imports, ModuleRuntimeInfo,
-    SendSyncPtr, Store, VMFunctionBody, VMSharedSignatureIndex, WasmFault,
+    ProfileCounters, SendSyncPtr, Store, VMFunctionBody, VMSharedSignatureIndex, WasmFault,
 };
 use anyhow::Error;
 use anyhow::Result;
@@ -29,8 +29,9 @@ use std::{mem, ptr};
 use wasmtime_environ::{
     packed_option::ReservedValue, DataIndex, DefinedGlobalIndex, DefinedMemoryIndex,
     DefinedTableIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex,
-    GlobalInit, HostPtr, MemoryIndex, MemoryPlan, Module, PrimaryMap, SignatureIndex, TableIndex,
-    TableInitialValue, Trap, VMOffsets, WasmHeapType, WasmRefType, WasmType, VMCONTEXT_MAGIC,
+    GlobalInit, HostPtr, Initializer, MemoryIndex, MemoryPlan, Module, PrimaryMap, SignatureIndex,
+    TableIndex, TableInitialValue, Trap, VMOffsets, WasmHeapType, WasmRefType, WasmType,
+    VMCONTEXT_MAGIC,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::Wmemcheck;
@@ -271,11 +272,33 @@ impl Instance {
         self.runtime_info.offsets()
     }
 
+    pub(crate) fn pgo_counters(&self) -> Option<&ProfileCounters> {
+        self.runtime_info.pgo_counters()
+    }
+
     /// Return the indexed `VMFunctionImport`.
-    fn imported_function(&self, index: FuncIndex) -> &VMFunctionImport {
+    pub(crate) fn imported_function(&self, index: FuncIndex) -> &VMFunctionImport {
         unsafe { &*self.vmctx_plus_offset(self.offsets().vmctx_vmfunction_import(index)) }
     }
 
+    /// Returns the module and field names of the import through which this
+    /// instance calls the function whose callee context is `vmctx`, if any.
+    pub fn imported_function_name(&self, vmctx: *mut VMOpaqueContext) -> Option<(&str, &str)> {
+        self.module()
+            .initializers
+            .iter()
+            .find_map(|init| match init {
+                Initializer::Import {
+                    name,
+                    field,
+                    index: EntityIndex::Function(index),
+                } if self.imported_function(*index).vmctx == vmctx => {
+                    Some((name.as_str(), field.as_str()))
+                }
+                _ => None,
+            })
+    }
+
     /// Return the index `VMTableImport`.
     fn imported_table(&self, index: TableIndex) -> &VMTableImport {
         unsafe { &*self.vmctx_plus_offset(self.offsets().vmctx_vmtable_import(index)) }
@@ -350,6 +373,20 @@ impl Instance {
         unsafe { *self.vmctx_plus_offset(self.offsets().vmctx_vmmemory_pointer(index)) }
     }
 
+    /// Get a raw pointer to the memory at the given index regardless whether it
+    /// is defined locally or imported from another module.
+    #[cfg(feature = "interpreter")]
+    pub(crate) fn defined_or_imported_memory_ptr(
+        &self,
+        index: MemoryIndex,
+    ) -> *mut VMMemoryDefinition {
+        if let Some(index) = self.module().defined_memory_index(index) {
+            self.memory_ptr(index)
+        } else {
+            self.imported_memory(index).from
+        }
+    }
+
     /// Return the memories defined within this instance (not imported).
     pub fn defined_memories<'a>(
         &'a self,
@@ -691,11 +728,7 @@ impl Instance {
     /// before, because resetting that state on (re)instantiation is
     /// very expensive if there are many funcrefs.
     fn construct_func_ref(&mut self, index: FuncIndex, sig: SignatureIndex, into: *mut VMFuncRef) {
-        let type_index = unsafe {
-            let base: *const VMSharedSignatureIndex =
-                *self.vmctx_plus_offset_mut(self.offsets().vmctx_signature_ids_array());
-            *base.add(sig.index())
-        };
+        let type_index = self.shared_signature_id(sig);
 
         let func_ref = if let Some(def_index) = self.module().defined_func_index(index) {
             VMFuncRef {
@@ -729,6 +762,26 @@ impl Instance {
         }
     }
 
+    /// Returns the engine-wide index of this module's signature `sig`, as
+    /// found in the `type_index` of `VMFuncRef`s.
+    pub(crate) fn shared_signature_id(&self, sig: SignatureIndex) -> VMSharedSignatureIndex {
+        unsafe {
+            let base: *const VMSharedSignatureIndex =
+                *self.vmctx_plus_offset(self.offsets().vmctx_signature_ids_array());
+            *base.add(sig.index())
+        }
+    }
+
+    /// Returns the pointer called by wasm to call the defined function
+    /// `index`.
+    #[cfg(feature = "interpreter")]
+    pub(crate) fn defined_function(
+        &self,
+        index: wasmtime_environ::DefinedFuncIndex,
+    ) -> NonNull<crate::VMWasmCallFunction> {
+        self.runtime_info.function(index)
+    }
+
     /// Get a `&VMFuncRef` for the given `FuncIndex`.
     ///
     /// Returns `None` if the index is the reserved index value.
@@ -1419,6 +1472,24 @@ impl InstanceHandle {
         self.instance_mut().defined_globals()
     }
 
+    /// Return the runtime representation of each memory defined in this
+    /// instance (not imported).
+    pub fn defined_memory_objects(&self) -> impl ExactSizeIterator<Item = &Memory> + '_ {
+        self.instance()
+            .memories
+            .values()
+            .map(|(_alloc_index, memory)| memory)
+    }
+
+    /// Return the runtime representation of each table defined in this
+    /// instance (not imported).
+    pub fn defined_table_objects(&self) -> impl ExactSizeIterator<Item = &Table> + '_ {
+        self.instance()
+            .tables
+            .values()
+            .map(|(_alloc_index, table)| table)
+    }
+
     /// Return a reference to the contained `Instance`.
     #[inline]
     pub(crate) fn instance(&self) -> &Instance {
@@ -111,6 +111,9 @@ pub struct InstanceLimits {
     pub max_memories_per_module: u32,
 
     /// Maximum number of Wasm pages for each linear memory.
+    ///
+    /// This may exceed the 65536 pages of a 32-bit memory to accommodate
+    /// 64-bit memories larger than 4GiB.
     pub memory_pages: u64,
 }
 
@@ -165,6 +168,11 @@ pub struct PoolingInstanceAllocatorConfig {
     ///
     /// Only applicable on Linux.
     pub linear_memory_keep_resident: usize,
+    /// Whether to ask the OS to back linear memory slots with transparent huge
+    /// pages.
+    ///
+    /// Only applicable on Linux.
+    pub linear_memory_huge_pages: bool,
     /// Same as `linear_memory_keep_resident` but for tables.
     pub table_keep_resident: usize,
     /// Whether to enable memory protection keys.
@@ -182,6 +190,7 @@ impl Default for PoolingInstanceAllocatorConfig {
             async_stack_zeroing: false,
             async_stack_keep_resident: 0,
             linear_memory_keep_resident: 0,
+            linear_memory_huge_pages: false,
             table_keep_resident: 0,
             memory_protection_keys: MpkEnabled::Disable,
             max_memory_protection_keys: 16,
@@ -564,12 +573,13 @@ unsafe impl InstanceAllocatorImpl for PoolingInstanceAllocator {
 mod test {
     use super::*;
 
+    #[cfg(target_pointer_width = "64")]
     #[test]
     fn test_pooling_allocator_with_memory_pages_exceeded() {
         let config = PoolingInstanceAllocatorConfig {
             limits: InstanceLimits {
                 total_memories: 1,
-                memory_pages: 0x10001,
+                memory_pages: (1 << 48) + 1,
                 ..Default::default()
             },
             ..PoolingInstanceAllocatorConfig::default()
@@ -584,10 +594,27 @@ mod test {
             )
             .map_err(|e| e.to_string())
             .expect_err("expected a failure constructing instance allocator"),
-            "module memory page limit of 65537 exceeds the maximum of 65536"
+            "module memory page limit of 281474976710657 exceeds the maximum of 281474976710656"
         );
     }
 
+    #[cfg(all(target_pointer_width = "64", not(miri)))]
+    #[test]
+    fn test_pooling_allocator_with_memory64_pages() -> Result<()> {
+        // Memories larger than 4GiB are only reserved, not committed, so this
+        // doesn't require any physical memory.
+        let config = PoolingInstanceAllocatorConfig {
+            limits: InstanceLimits {
+                total_memories: 1,
+                memory_pages: 0x10001,
+                ..Default::default()
+            },
+            ..PoolingInstanceAllocatorConfig::default()
+        };
+        PoolingInstanceAllocator::new(&config, &Tunables::default())?;
+        Ok(())
+    }
+
     #[cfg(all(unix, target_pointer_width = "64", feature = "async", not(miri)))]
     #[test]
     fn test_stack_zeroed() -> Result<()> {
@@ -64,7 +64,8 @@ use std::ffi::c_void;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use wasmtime_environ::{
-    DefinedMemoryIndex, MemoryPlan, MemoryStyle, Module, Tunables, WASM_PAGE_SIZE,
+    DefinedMemoryIndex, MemoryPlan, MemoryStyle, Module, Tunables, WASM32_MAX_PAGES,
+    WASM64_MAX_PAGES, WASM_PAGE_SIZE,
 };
 
 /// A set of allocator slots.
@@ -129,6 +130,9 @@ pub struct MemoryPool {
     //
     // Only applicable on Linux.
     keep_resident: usize,
+    // Whether slots are backed by transparent huge pages, which needs to be
+    // reapplied whenever a slot's mapping is replaced.
+    huge_pages: bool,
     // Keep track of protection keys handed out to initialized stores; this
     // allows us to round-robin the assignment of stores to stripes.
     next_available_pkey: AtomicUsize,
@@ -137,10 +141,17 @@ pub struct MemoryPool {
 impl MemoryPool {
     /// Create a new `MemoryPool`.
     pub fn new(config: &PoolingInstanceAllocatorConfig, tunables: &Tunables) -> Result<Self> {
-        // The maximum module memory page count cannot exceed 65536 pages
-        if config.limits.memory_pages > 0x10000 {
+        // 32-bit memories are limited to 65536 pages, but 64-bit memories may
+        // be larger so the only limit here is that the whole slot must fit in
+        // the host's address space.
+        let max_pages = if cfg!(target_pointer_width = "64") {
+            WASM64_MAX_PAGES
+        } else {
+            WASM32_MAX_PAGES
+        };
+        if config.limits.memory_pages > max_pages {
             bail!(
-                "module memory page limit of {} exceeds the maximum of 65536",
+                "module memory page limit of {} exceeds the maximum of {max_pages}",
                 config.limits.memory_pages
             );
         }
@@ -187,6 +198,22 @@ impl MemoryPool {
         );
         let mut mapping = Mmap::accessible_reserved(0, layout.total_slab_bytes()?)
             .context("failed to create memory pool mapping")?;
+        if config.linear_memory_huge_pages {
+            unsafe { crate::enable_huge_pages(mapping.as_mut_ptr(), mapping.len()) };
+        }
+
+        // Resetting a slot `memset`s the first `keep_resident` bytes and uses
+        // `madvise` for the rest. With huge pages the boundary between the two
+        // is rounded up to a whole huge page so that resetting a slot doesn't
+        // split a huge page in two.
+        let mut keep_resident = config.linear_memory_keep_resident;
+        if config.linear_memory_huge_pages && keep_resident > 0 {
+            if let Some(huge_page_size) = crate::sys::vm::huge_page_size() {
+                keep_resident = keep_resident
+                    .checked_next_multiple_of(huge_page_size)
+                    .unwrap_or(keep_resident);
+            }
+        }
 
         // Then, stripe the memory with the available protection keys. This is
         // unnecessary if there is only one stripe color.
@@ -234,7 +261,8 @@ impl MemoryPool {
             image_slots,
             layout,
             memories_per_instance: usize::try_from(config.limits.max_memories_per_module).unwrap(),
-            keep_resident: config.linear_memory_keep_resident,
+            keep_resident,
+            huge_pages: config.linear_memory_huge_pages,
             next_available_pkey: AtomicUsize::new(0),
         };
 
@@ -277,7 +305,23 @@ impl MemoryPool {
                         );
                     }
                 }
-                MemoryStyle::Dynamic { .. } => {}
+                // Dynamic memories, such as 64-bit memories whose maximum
+                // exceeds the static memory bound, are bounds-checked against
+                // their current size but rely on a guard region after the
+                // memory for accesses with small offsets. Pooled memories
+                // never move, so that guard must fit in the slot after the
+                // largest size the memory can grow to.
+                MemoryStyle::Dynamic { .. } => {
+                    let guard =
+                        self.layout.bytes_to_next_stripe_slot() - self.layout.max_memory_bytes;
+                    if u64::try_from(guard).unwrap() < plan.offset_guard_size {
+                        bail!(
+                            "memory guard region of {guard:#x} bytes per-memory is too \
+                             small to satisfy dynamic guard size of {:#x} bytes",
+                            plan.offset_guard_size
+                        );
+                    }
+                }
             }
             if plan.memory.minimum > u64::try_from(max_memory_pages).unwrap() {
                 bail!(
@@ -469,11 +513,13 @@ impl MemoryPool {
             .take();
 
         maybe_slot.unwrap_or_else(|| {
-            MemoryImageSlot::create(
+            let mut slot = MemoryImageSlot::create(
                 self.get_base(allocation_index) as *mut c_void,
                 0,
                 self.layout.max_memory_bytes,
-            )
+            );
+            slot.set_huge_pages(self.huge_pages);
+            slot
         })
     }
 
@@ -545,7 +591,10 @@ impl SlabConstraints {
         num_pkeys_available: usize,
     ) -> Result<Self> {
         // The maximum size a memory can grow to in this pool.
-        let max_memory_bytes = limits.memory_pages * u64::from(WASM_PAGE_SIZE);
+        let max_memory_bytes = limits
+            .memory_pages
+            .checked_mul(u64::from(WASM_PAGE_SIZE))
+            .context("max memory is too large")?;
 
         // `static_memory_bound` is the configured number of Wasm pages for a
         // static memory slot (see `Config::static_memory_maximum_size`); even
@@ -0,0 +1,920 @@
+//! Wasmtime's portable interpreter.
+//!
+//! Modules compiled for the interpreter contain the bytecode described in
+//! `wasmtime_environ::bytecode` instead of native code. Their functions still
+//! use the same `VMContext` and `VMFuncRef` layouts as native code: the
+//! `wasm_call` of an interpreted function points at its bytecode, while its
+//! `array_call` and `native_call` are markers which must never be called.
+//! Callers check whether a function is interpreted with [`is_interpreted`]
+//! and then enter it with [`call`] instead.
+//!
+//! The interpreter calls everything that isn't interpreted, such as host
+//! functions, through its `array_call` entry point. Traps are reported the same
+//! way as traps of native code, by unwinding to the `catch_traps` which the
+//! interpreter was entered from.
+
+use crate::libcalls::relocs;
+use crate::table::Table;
+use crate::{
+    catch_traps, raise_trap, raise_user_trap, resume_panic, Instance, TrapReason,
+    VMArrayCallFunction, VMContext, VMFuncRef, VMMemoryDefinition, VMNativeCallFunction,
+    VMOpaqueContext, VMRuntimeLimits, VMWasmCallFunction, ValRaw,
+};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::{self, NonNull};
+use std::sync::atomic::Ordering::Relaxed;
+use wasmtime_environ::bytecode::{FunctionHeader, Op};
+use wasmtime_environ::{
+    DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, Trap,
+    WASM_PAGE_SIZE,
+};
+
+/// The `array_call` of interpreted functions.
+///
+/// This is only used to identify interpreted functions, which are entered with
+/// [`call`] instead. Should it be called anyway, it traps with an error rather
+/// than taking down the process.
+unsafe extern "C" fn interpreted_array_call(
+    _callee: *mut VMOpaqueContext,
+    _caller: *mut VMOpaqueContext,
+    _values: *mut ValRaw,
+    _len: usize,
+) {
+    raise_user_trap(
+        anyhow::anyhow!("interpreted function called through its `array_call` entry point"),
+        false,
+    )
+}
+
+/// The `native_call` of interpreted functions, and of host functions without
+/// trampolines, which similarly traps if it's called.
+unsafe extern "C" fn interpreted_native_call() {
+    raise_user_trap(
+        anyhow::anyhow!(
+            "function without native code called through its `native_call` entry point"
+        ),
+        false,
+    )
+}
+
+/// Returns the `array_call` entry point to use for interpreted functions.
+pub fn array_call() -> VMArrayCallFunction {
+    interpreted_array_call
+}
+
+/// Returns the `native_call` entry point to use for interpreted functions.
+pub fn native_call() -> NonNull<VMNativeCallFunction> {
+    NonNull::new(interpreted_native_call as *const () as *mut VMNativeCallFunction).unwrap()
+}
+
+/// Returns the `wasm_call` entry point to use for host functions in engines
+/// using the interpreter, which traps if it's called since the interpreter
+/// calls host functions through their `array_call`.
+pub fn wasm_call() -> NonNull<VMWasmCallFunction> {
+    native_call().cast()
+}
+
+/// Returns whether `func_ref` is an interpreted function, which must be
+/// called with [`call`].
+pub fn is_interpreted(func_ref: &VMFuncRef) -> bool {
+    is_interpreted_array_call(func_ref.array_call)
+}
+
+/// Returns whether `func_ref` has a `native_call` entry point.
+///
+/// Interpreted functions don't, and neither do host functions defined in
+/// engines using the interpreter, as those have no trampolines. Such functions
+/// must be called with the array calling convention instead.
+pub fn has_native_call(func_ref: &VMFuncRef) -> bool {
+    func_ref.native_call != native_call()
+}
+
+fn is_interpreted_array_call(array_call: VMArrayCallFunction) -> bool {
+    array_call as usize == interpreted_array_call as usize
+}
+
+/// Calls the interpreted function `func_ref` with the array calling
+/// convention: its arguments are read from `values`, which receives its
+/// results.
+///
+/// # Safety
+///
+/// This must be called within `catch_traps` and `values` must have room for
+/// `len` values, which must be enough for both the parameters and results of
+/// the function.
+pub unsafe fn call(func_ref: &VMFuncRef, values: *mut ValRaw, len: usize) {
+    debug_assert!(is_interpreted(func_ref));
+    let vmctx = VMContext::from_opaque(func_ref.vmctx);
+    let func = func_ref
+        .wasm_call
+        .unwrap()
+        .as_ptr()
+        .cast::<u8>()
+        .cast_const();
+
+    // Traps and panics unwind to `catch_traps` without running destructors,
+    // so they're only raised once the interpreter's state has been dropped.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let header = header(func);
+        let values = std::slice::from_raw_parts_mut(values, len);
+        let mut interpreter = Interpreter {
+            regs: values[..header.num_params as usize]
+                .iter()
+                .map(|value| value.get_u64())
+                .collect(),
+            limits: Instance::from_vmctx(vmctx, |i| *i.runtime_limits()),
+        };
+        interpreter.execute(vmctx, func, 0)?;
+        let results = &interpreter.regs[..header.num_results as usize];
+        for (value, reg) in values.iter_mut().zip(results) {
+            *value = ValRaw::u64(*reg);
+        }
+        Ok(())
+    }));
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(reason)) => raise_trap(reason),
+        Err(panic) => resume_panic(panic),
+    }
+}
+
+unsafe fn header(func: *const u8) -> FunctionHeader {
+    FunctionHeader::from_bytes(&*func.cast::<[u8; FunctionHeader::SIZE]>())
+}
+
+struct Interpreter {
+    /// The registers of all active frames, where each frame starts at the
+    /// registers holding the arguments passed by its caller.
+    regs: Vec<u64>,
+    limits: *const VMRuntimeLimits,
+}
+
+/// Reader of the instructions of a function.
+struct Code {
+    func: *const u8,
+    pc: usize,
+}
+
+impl Code {
+    unsafe fn read<T: Copy>(&mut self) -> T {
+        let val = ptr::read_unaligned(self.func.add(self.pc).cast::<T>());
+        self.pc += std::mem::size_of::<T>();
+        val
+    }
+
+    unsafe fn op(&mut self) -> Op {
+        Op::from_u8(self.read::<u8>()).expect("invalid interpreter opcode")
+    }
+
+    unsafe fn u16(&mut self) -> u16 {
+        u16::from_le(self.read())
+    }
+
+    unsafe fn u32(&mut self) -> u32 {
+        u32::from_le(self.read())
+    }
+
+    unsafe fn u64(&mut self) -> u64 {
+        u64::from_le(self.read())
+    }
+
+    /// Reads a register, returning its index in the registers of all frames
+    /// given the frame's `base`.
+    unsafe fn reg(&mut self, base: usize) -> usize {
+        base + usize::from(self.u16())
+    }
+}
+
+impl Interpreter {
+    /// Executes the function `func` of the instance `vmctx`, whose frame
+    /// starts at the register `base`.
+    unsafe fn execute(
+        &mut self,
+        vmctx: *mut VMContext,
+        func: *const u8,
+        base: usize,
+    ) -> Result<(), TrapReason> {
+        // The interpreter recurses for every wasm call, so wasm's stack limit
+        // is enforced on its native stack.
+        let stack_pointer = &base as *const usize as usize;
+        if stack_pointer < *(*self.limits).stack_limit.get() {
+            return Err(Trap::StackOverflow.into());
+        }
+
+        let header = header(func);
+        let frame_end = base + header.frame_size as usize;
+        if self.regs.len() < frame_end {
+            self.regs.resize(frame_end, 0);
+        }
+        self.regs[base + header.num_params as usize..base + header.num_locals as usize].fill(0);
+
+        let memory = Instance::from_vmctx(vmctx, |i| {
+            if i.module().memory_plans.is_empty() {
+                ptr::null_mut()
+            } else {
+                i.defined_or_imported_memory_ptr(MemoryIndex::from_u32(0))
+            }
+        });
+
+        let mut code = Code {
+            func,
+            pc: FunctionHeader::SIZE,
+        };
+
+        macro_rules! unary {
+            (|$a:ident| $e:expr) => {{
+                let dst = code.reg(base);
+                let $a = self.regs[code.reg(base)];
+                self.regs[dst] = $e;
+            }};
+        }
+        macro_rules! binary {
+            (|$a:ident, $b:ident| $e:expr) => {{
+                let dst = code.reg(base);
+                let $a = self.regs[code.reg(base)];
+                let $b = self.regs[code.reg(base)];
+                self.regs[dst] = $e;
+            }};
+        }
+        macro_rules! load {
+            ($ty:ty, |$v:ident| $e:expr) => {{
+                let dst = code.reg(base);
+                let addr = self.regs[code.reg(base)];
+                let offset = code.u32();
+                let ptr = memory_addr(memory, addr, offset, std::mem::size_of::<$ty>())?;
+                let $v = <$ty>::from_le(ptr::read_unaligned(ptr.cast::<$ty>()));
+                self.regs[dst] = $e;
+            }};
+        }
+        macro_rules! store {
+            ($ty:ty) => {{
+                let addr = self.regs[code.reg(base)];
+                let src = self.regs[code.reg(base)];
+                let offset = code.u32();
+                let ptr = memory_addr(memory, addr, offset, std::mem::size_of::<$ty>())?;
+                ptr::write_unaligned(ptr.cast::<$ty>(), (src as $ty).to_le());
+            }};
+        }
+
+        loop {
+            match code.op() {
+                Op::Jump => code.pc = code.u32() as usize,
+                Op::BrIf => {
+                    let cond = self.regs[code.reg(base)] as u32;
+                    let target = code.u32();
+                    if cond != 0 {
+                        code.pc = target as usize;
+                    }
+                }
+                Op::BrIfNot => {
+                    let cond = self.regs[code.reg(base)] as u32;
+                    let target = code.u32();
+                    if cond == 0 {
+                        code.pc = target as usize;
+                    }
+                }
+                Op::BrTable => {
+                    let index = self.regs[code.reg(base)] as u32;
+                    let count = code.u32();
+                    code.pc += 4 * index.min(count) as usize;
+                    code.pc = code.u32() as usize;
+                }
+                Op::Return => return Ok(()),
+                Op::Call => {
+                    let func = FuncIndex::from_u32(code.u32());
+                    let (callee_base, params, results) = call_operands(&mut code, base);
+                    let callee = Instance::from_vmctx(vmctx, |i| {
+                        match i.module().defined_func_index(func) {
+                            Some(index) => Callee {
+                                vmctx: VMOpaqueContext::from_vmcontext(vmctx),
+                                array_call: interpreted_array_call,
+                                wasm_call: Some(i.defined_function(index)),
+                            },
+                            None => {
+                                let import = i.imported_function(func);
+                                Callee {
+                                    vmctx: import.vmctx,
+                                    array_call: import.array_call,
+                                    wasm_call: Some(import.wasm_call),
+                                }
+                            }
+                        }
+                    });
+                    self.call(vmctx, callee, callee_base, params, results)?;
+                }
+                Op::CallIndirect => {
+                    let table = TableIndex::from_u32(code.u32());
+                    let sig = SignatureIndex::from_u32(code.u32());
+                    let index = self.regs[code.reg(base)] as u32;
+                    let (callee_base, params, results) = call_operands(&mut code, base);
+                    let (func_ref, expected) = Instance::from_vmctx(vmctx, |i| {
+                        let table = i.get_table_with_lazy_init(table, std::iter::once(index));
+                        let func_ref = (*table)
+                            .get(index)
+                            .map(|elem| elem.into_ref_asserting_initialized());
+                        (func_ref, i.shared_signature_id(sig))
+                    });
+                    let func_ref = func_ref.ok_or(Trap::TableOutOfBounds)?;
+                    if func_ref.is_null() {
+                        return Err(Trap::IndirectCallToNull.into());
+                    }
+                    let func_ref = &*func_ref.cast::<VMFuncRef>();
+                    if func_ref.type_index != expected {
+                        return Err(Trap::BadSignature.into());
+                    }
+                    let callee = Callee {
+                        vmctx: func_ref.vmctx,
+                        array_call: func_ref.array_call,
+                        wasm_call: func_ref.wasm_call,
+                    };
+                    self.call(vmctx, callee, callee_base, params, results)?;
+                }
+                Op::Unreachable => return Err(Trap::UnreachableCodeReached.into()),
+                Op::ConsumeFuel => {
+                    let cost = code.u32();
+                    *(*self.limits).fuel_consumed.get() += i64::from(cost);
+                }
+                Op::CheckFuel => {
+                    if *(*self.limits).fuel_consumed.get() >= 0 {
+                        Instance::from_vmctx(vmctx, |i| (*i.store()).out_of_gas())?;
+                    }
+                }
+                Op::CheckEpoch => {
+                    let epoch = Instance::from_vmctx(vmctx, |i| (**i.epoch_ptr()).load(Relaxed));
+                    if epoch >= *(*self.limits).epoch_deadline.get() {
+                        Instance::from_vmctx(vmctx, |i| (*i.store()).new_epoch())?;
+                    }
+                }
+
+                Op::Copy => unary!(|a| a),
+                Op::Const32 => {
+                    let dst = code.reg(base);
+                    self.regs[dst] = u64::from(code.u32());
+                }
+                Op::Const64 => {
+                    let dst = code.reg(base);
+                    self.regs[dst] = code.u64();
+                }
+                Op::Select => {
+                    let dst = code.reg(base);
+                    let a = self.regs[code.reg(base)];
+                    let b = self.regs[code.reg(base)];
+                    let cond = self.regs[code.reg(base)] as u32;
+                    self.regs[dst] = if cond != 0 { a } else { b };
+                }
+
+                Op::GlobalGet32 => {
+                    let dst = code.reg(base);
+                    let global = global_ptr(vmctx, code.u32());
+                    self.regs[dst] = u64::from(*(*global).as_u32());
+                }
+                Op::GlobalGet64 => {
+                    let dst = code.reg(base);
+                    let global = global_ptr(vmctx, code.u32());
+                    self.regs[dst] = *(*global).as_u64();
+                }
+                Op::GlobalSet32 => {
+                    let global = global_ptr(vmctx, code.u32());
+                    *(*global).as_u32_mut() = self.regs[code.reg(base)] as u32;
+                }
+                Op::GlobalSet64 => {
+                    let global = global_ptr(vmctx, code.u32());
+                    *(*global).as_u64_mut() = self.regs[code.reg(base)];
+                }
+                Op::GlobalGetRef => {
+                    let dst = code.reg(base);
+                    let global = global_ptr(vmctx, code.u32());
+                    self.regs[dst] = (*global).as_func_ref() as usize as u64;
+                }
+                Op::GlobalSetRef => {
+                    let global = global_ptr(vmctx, code.u32());
+                    *(*global).as_func_ref_mut() = func_ref(self.regs[code.reg(base)]);
+                }
+
+                Op::Load8U => load!(u8, |v| u64::from(v)),
+                Op::Load16U => load!(u16, |v| u64::from(v)),
+                Op::Load32U => load!(u32, |v| u64::from(v)),
+                Op::Load64 => load!(u64, |v| v),
+                Op::I32Load8S => load!(i8, |v| u64::from(v as u32)),
+                Op::I32Load16S => load!(i16, |v| u64::from(v as u32)),
+                Op::I64Load8S => load!(i8, |v| v as u64),
+                Op::I64Load16S => load!(i16, |v| v as u64),
+                Op::I64Load32S => load!(i32, |v| v as u64),
+                Op::Store8 => store!(u8),
+                Op::Store16 => store!(u16),
+                Op::Store32 => store!(u32),
+                Op::Store64 => store!(u64),
+                Op::MemorySize => {
+                    let dst = code.reg(base);
+                    let pages = (*memory).current_length() / WASM_PAGE_SIZE as usize;
+                    self.regs[dst] = pages as u64;
+                }
+                Op::MemoryGrow => {
+                    let dst = code.reg(base);
+                    let delta = self.regs[code.reg(base)] as u32;
+                    let result = Instance::from_vmctx(vmctx, |i| {
+                        i.memory_grow(MemoryIndex::from_u32(0), u64::from(delta))
+                    })?;
+                    self.regs[dst] = match result {
+                        Some(size_in_bytes) => (size_in_bytes / WASM_PAGE_SIZE as usize) as u64,
+                        None => u64::from(u32::MAX),
+                    };
+                }
+                Op::MemoryFill => {
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let val = self.regs[code.reg(base)] as u8;
+                    let len = self.regs[code.reg(base)] as u32;
+                    Instance::from_vmctx(vmctx, |i| {
+                        i.memory_fill(MemoryIndex::from_u32(0), dst.into(), val, len.into())
+                    })?;
+                }
+                Op::MemoryCopy => {
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let src = self.regs[code.reg(base)] as u32;
+                    let len = self.regs[code.reg(base)] as u32;
+                    let memory = MemoryIndex::from_u32(0);
+                    Instance::from_vmctx(vmctx, |i| {
+                        i.memory_copy(memory, dst.into(), memory, src.into(), len.into())
+                    })?;
+                }
+                Op::MemoryInit => {
+                    let data = DataIndex::from_u32(code.u32());
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let src = self.regs[code.reg(base)] as u32;
+                    let len = self.regs[code.reg(base)] as u32;
+                    Instance::from_vmctx(vmctx, |i| {
+                        i.memory_init(MemoryIndex::from_u32(0), data, dst.into(), src, len)
+                    })?;
+                }
+                Op::DataDrop => {
+                    let data = DataIndex::from_u32(code.u32());
+                    Instance::from_vmctx(vmctx, |i| i.data_drop(data));
+                }
+
+                Op::TableGet => {
+                    let dst = code.reg(base);
+                    let table = TableIndex::from_u32(code.u32());
+                    let index = self.regs[code.reg(base)] as u32;
+                    let func_ref = Instance::from_vmctx(vmctx, |i| {
+                        let table = i.get_table_with_lazy_init(table, std::iter::once(index));
+                        (*table)
+                            .get(index)
+                            .map(|elem| elem.into_ref_asserting_initialized())
+                    });
+                    let func_ref = func_ref.ok_or(Trap::TableOutOfBounds)?;
+                    self.regs[dst] = func_ref as usize as u64;
+                }
+                Op::TableSet => {
+                    let table = TableIndex::from_u32(code.u32());
+                    let index = self.regs[code.reg(base)] as u32;
+                    let val = func_ref(self.regs[code.reg(base)]);
+                    Instance::from_vmctx(vmctx, |i| (*i.get_table(table)).set(index, val.into()))
+                        .map_err(|()| Trap::TableOutOfBounds)?;
+                }
+                Op::TableSize => {
+                    let dst = code.reg(base);
+                    let table = TableIndex::from_u32(code.u32());
+                    let size = Instance::from_vmctx(vmctx, |i| (*i.get_table(table)).size());
+                    self.regs[dst] = u64::from(size);
+                }
+                Op::TableGrow => {
+                    let dst = code.reg(base);
+                    let table = TableIndex::from_u32(code.u32());
+                    let init = func_ref(self.regs[code.reg(base)]);
+                    let delta = self.regs[code.reg(base)] as u32;
+                    let result =
+                        Instance::from_vmctx(vmctx, |i| i.table_grow(table, delta, init.into()))?;
+                    self.regs[dst] = u64::from(result.unwrap_or(u32::MAX));
+                }
+                Op::TableFill => {
+                    let table = TableIndex::from_u32(code.u32());
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let val = func_ref(self.regs[code.reg(base)]);
+                    let len = self.regs[code.reg(base)] as u32;
+                    Instance::from_vmctx(vmctx, |i| {
+                        (*i.get_table(table)).fill(dst, val.into(), len)
+                    })?;
+                }
+                Op::TableCopy => {
+                    let dst_table = TableIndex::from_u32(code.u32());
+                    let src_table = TableIndex::from_u32(code.u32());
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let src = self.regs[code.reg(base)] as u32;
+                    let len = self.regs[code.reg(base)] as u32;
+                    Instance::from_vmctx(vmctx, |i| {
+                        let dst_table = i.get_table(dst_table);
+                        // Lazily initialize the whole source range first.
+                        let src_range = src..src.checked_add(len).unwrap_or(u32::MAX);
+                        let src_table = i.get_table_with_lazy_init(src_table, src_range);
+                        Table::copy(dst_table, src_table, dst, src, len)
+                    })?;
+                }
+                Op::TableInit => {
+                    let table = TableIndex::from_u32(code.u32());
+                    let elem = ElemIndex::from_u32(code.u32());
+                    let dst = self.regs[code.reg(base)] as u32;
+                    let src = self.regs[code.reg(base)] as u32;
+                    let len = self.regs[code.reg(base)] as u32;
+                    Instance::from_vmctx(vmctx, |i| i.table_init(table, elem, dst, src, len))?;
+                }
+                Op::ElemDrop => {
+                    let elem = ElemIndex::from_u32(code.u32());
+                    Instance::from_vmctx(vmctx, |i| i.elem_drop(elem));
+                }
+                Op::RefFunc => {
+                    let dst = code.reg(base);
+                    let func = FuncIndex::from_u32(code.u32());
+                    let func_ref = Instance::from_vmctx(vmctx, |i| i.get_func_ref(func))
+                        .expect("`ref.func` of a function which isn't declared");
+                    self.regs[dst] = func_ref as usize as u64;
+                }
+                Op::RefIsNull => unary!(|a| bool(func_ref(a).is_null())),
+
+                Op::I32Add => binary!(|a, b| i32(a.wrapping_add(b))),
+                Op::I32Sub => binary!(|a, b| i32(a.wrapping_sub(b))),
+                Op::I32Mul => binary!(|a, b| i32(a.wrapping_mul(b))),
+                Op::I32DivS => binary!(|a, b| {
+                    let (a, b) = (a as i32, b as i32);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    if a == i32::MIN && b == -1 {
+                        return Err(Trap::IntegerOverflow.into());
+                    }
+                    i32((a / b) as u64)
+                }),
+                Op::I32DivU => binary!(|a, b| {
+                    let (a, b) = (a as u32, b as u32);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    u64::from(a / b)
+                }),
+                Op::I32RemS => binary!(|a, b| {
+                    let (a, b) = (a as i32, b as i32);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    i32(a.wrapping_rem(b) as u64)
+                }),
+                Op::I32RemU => binary!(|a, b| {
+                    let (a, b) = (a as u32, b as u32);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    u64::from(a % b)
+                }),
+                Op::I32And => binary!(|a, b| a & b),
+                Op::I32Or => binary!(|a, b| a | b),
+                Op::I32Xor => binary!(|a, b| a ^ b),
+                Op::I32Shl => binary!(|a, b| u64::from((a as u32).wrapping_shl(b as u32))),
+                Op::I32ShrS => binary!(|a, b| i32((a as i32).wrapping_shr(b as u32) as u64)),
+                Op::I32ShrU => binary!(|a, b| u64::from((a as u32).wrapping_shr(b as u32))),
+                Op::I32Rotl => binary!(|a, b| u64::from((a as u32).rotate_left(b as u32))),
+                Op::I32Rotr => binary!(|a, b| u64::from((a as u32).rotate_right(b as u32))),
+                Op::I32Eq => binary!(|a, b| bool(a as u32 == b as u32)),
+                Op::I32Ne => binary!(|a, b| bool(a as u32 != b as u32)),
+                Op::I32LtS => binary!(|a, b| bool((a as i32) < (b as i32))),
+                Op::I32LtU => binary!(|a, b| bool((a as u32) < (b as u32))),
+                Op::I32GtS => binary!(|a, b| bool(a as i32 > b as i32)),
+                Op::I32GtU => binary!(|a, b| bool(a as u32 > b as u32)),
+                Op::I32LeS => binary!(|a, b| bool(a as i32 <= b as i32)),
+                Op::I32LeU => binary!(|a, b| bool(a as u32 <= b as u32)),
+                Op::I32GeS => binary!(|a, b| bool(a as i32 >= b as i32)),
+                Op::I32GeU => binary!(|a, b| bool(a as u32 >= b as u32)),
+                Op::I32Eqz => unary!(|a| bool(a as u32 == 0)),
+                Op::I32Clz => unary!(|a| u64::from((a as u32).leading_zeros())),
+                Op::I32Ctz => unary!(|a| u64::from((a as u32).trailing_zeros())),
+                Op::I32Popcnt => unary!(|a| u64::from((a as u32).count_ones())),
+                Op::I32Extend8S => unary!(|a| i32(a as i8 as u64)),
+                Op::I32Extend16S => unary!(|a| i32(a as i16 as u64)),
+                Op::I32WrapI64 => unary!(|a| i32(a)),
+
+                Op::I64Add => binary!(|a, b| a.wrapping_add(b)),
+                Op::I64Sub => binary!(|a, b| a.wrapping_sub(b)),
+                Op::I64Mul => binary!(|a, b| a.wrapping_mul(b)),
+                Op::I64DivS => binary!(|a, b| {
+                    let (a, b) = (a as i64, b as i64);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    if a == i64::MIN && b == -1 {
+                        return Err(Trap::IntegerOverflow.into());
+                    }
+                    (a / b) as u64
+                }),
+                Op::I64DivU => binary!(|a, b| {
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    a / b
+                }),
+                Op::I64RemS => binary!(|a, b| {
+                    let (a, b) = (a as i64, b as i64);
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    a.wrapping_rem(b) as u64
+                }),
+                Op::I64RemU => binary!(|a, b| {
+                    if b == 0 {
+                        return Err(Trap::IntegerDivisionByZero.into());
+                    }
+                    a % b
+                }),
+                Op::I64And => binary!(|a, b| a & b),
+                Op::I64Or => binary!(|a, b| a | b),
+                Op::I64Xor => binary!(|a, b| a ^ b),
+                Op::I64Shl => binary!(|a, b| a.wrapping_shl(b as u32)),
+                Op::I64ShrS => binary!(|a, b| (a as i64).wrapping_shr(b as u32) as u64),
+                Op::I64ShrU => binary!(|a, b| a.wrapping_shr(b as u32)),
+                Op::I64Rotl => binary!(|a, b| a.rotate_left((b % 64) as u32)),
+                Op::I64Rotr => binary!(|a, b| a.rotate_right((b % 64) as u32)),
+                Op::I64Eq => binary!(|a, b| bool(a == b)),
+                Op::I64Ne => binary!(|a, b| bool(a != b)),
+                Op::I64LtS => binary!(|a, b| bool((a as i64) < (b as i64))),
+                Op::I64LtU => binary!(|a, b| bool(a < b)),
+                Op::I64GtS => binary!(|a, b| bool(a as i64 > b as i64)),
+                Op::I64GtU => binary!(|a, b| bool(a > b)),
+                Op::I64LeS => binary!(|a, b| bool(a as i64 <= b as i64)),
+                Op::I64LeU => binary!(|a, b| bool(a <= b)),
+                Op::I64GeS => binary!(|a, b| bool(a as i64 >= b as i64)),
+                Op::I64GeU => binary!(|a, b| bool(a >= b)),
+                Op::I64Eqz => unary!(|a| bool(a == 0)),
+                Op::I64Clz => unary!(|a| u64::from(a.leading_zeros())),
+                Op::I64Ctz => unary!(|a| u64::from(a.trailing_zeros())),
+                Op::I64Popcnt => unary!(|a| u64::from(a.count_ones())),
+                Op::I64Extend8S => unary!(|a| a as i8 as u64),
+                Op::I64Extend16S => unary!(|a| a as i16 as u64),
+                Op::I64Extend32S => unary!(|a| a as i32 as u64),
+                Op::I64ExtendI32U => unary!(|a| i32(a)),
+
+                Op::F32Add => binary!(|a, b| from_f32(f32(a) + f32(b))),
+                Op::F32Sub => binary!(|a, b| from_f32(f32(a) - f32(b))),
+                Op::F32Mul => binary!(|a, b| from_f32(f32(a) * f32(b))),
+                Op::F32Div => binary!(|a, b| from_f32(f32(a) / f32(b))),
+                Op::F32Min => binary!(|a, b| from_f32(f32_min(f32(a), f32(b)))),
+                Op::F32Max => binary!(|a, b| from_f32(f32_max(f32(a), f32(b)))),
+                Op::F32Copysign => binary!(|a, b| from_f32(f32(a).copysign(f32(b)))),
+                Op::F32Eq => binary!(|a, b| bool(f32(a) == f32(b))),
+                Op::F32Ne => binary!(|a, b| bool(f32(a) != f32(b))),
+                Op::F32Lt => binary!(|a, b| bool(f32(a) < f32(b))),
+                Op::F32Gt => binary!(|a, b| bool(f32(a) > f32(b))),
+                Op::F32Le => binary!(|a, b| bool(f32(a) <= f32(b))),
+                Op::F32Ge => binary!(|a, b| bool(f32(a) >= f32(b))),
+                Op::F32Abs => unary!(|a| a & 0x7fff_ffff),
+                Op::F32Neg => unary!(|a| a ^ 0x8000_0000),
+                Op::F32Sqrt => unary!(|a| from_f32(f32(a).sqrt())),
+                Op::F32Ceil => unary!(|a| from_f32(relocs::ceilf32(f32(a)))),
+                Op::F32Floor => unary!(|a| from_f32(relocs::floorf32(f32(a)))),
+                Op::F32Trunc => unary!(|a| from_f32(relocs::truncf32(f32(a)))),
+                Op::F32Nearest => unary!(|a| from_f32(relocs::nearestf32(f32(a)))),
+
+                Op::F64Add => binary!(|a, b| from_f64(f64(a) + f64(b))),
+                Op::F64Sub => binary!(|a, b| from_f64(f64(a) - f64(b))),
+                Op::F64Mul => binary!(|a, b| from_f64(f64(a) * f64(b))),
+                Op::F64Div => binary!(|a, b| from_f64(f64(a) / f64(b))),
+                Op::F64Min => binary!(|a, b| from_f64(f64_min(f64(a), f64(b)))),
+                Op::F64Max => binary!(|a, b| from_f64(f64_max(f64(a), f64(b)))),
+                Op::F64Copysign => binary!(|a, b| from_f64(f64(a).copysign(f64(b)))),
+                Op::F64Eq => binary!(|a, b| bool(f64(a) == f64(b))),
+                Op::F64Ne => binary!(|a, b| bool(f64(a) != f64(b))),
+                Op::F64Lt => binary!(|a, b| bool(f64(a) < f64(b))),
+                Op::F64Gt => binary!(|a, b| bool(f64(a) > f64(b))),
+                Op::F64Le => binary!(|a, b| bool(f64(a) <= f64(b))),
+                Op::F64Ge => binary!(|a, b| bool(f64(a) >= f64(b))),
+                Op::F64Abs => unary!(|a| a & 0x7fff_ffff_ffff_ffff),
+                Op::F64Neg => unary!(|a| a ^ 0x8000_0000_0000_0000),
+                Op::F64Sqrt => unary!(|a| from_f64(f64(a).sqrt())),
+                Op::F64Ceil => unary!(|a| from_f64(relocs::ceilf64(f64(a)))),
+                Op::F64Floor => unary!(|a| from_f64(relocs::floorf64(f64(a)))),
+                Op::F64Trunc => unary!(|a| from_f64(relocs::truncf64(f64(a)))),
+                Op::F64Nearest => unary!(|a| from_f64(relocs::nearestf64(f64(a)))),
+
+                // Wasm traps on out-of-range truncations, which are checked on
+                // the exact value as an `f64`.
+                Op::I32TruncF32S => {
+                    unary!(|a| i32(
+                        trunc(f64::from(f32(a)), -2147483649.0, 2147483648.0)? as i32 as u64
+                    ))
+                }
+                Op::I32TruncF32U => {
+                    unary!(|a| i32(trunc(f64::from(f32(a)), -1.0, 4294967296.0)? as u32 as u64))
+                }
+                Op::I32TruncF64S => {
+                    unary!(|a| i32(trunc(f64(a), -2147483649.0, 2147483648.0)? as i32 as u64))
+                }
+                Op::I32TruncF64U => {
+                    unary!(|a| i32(trunc(f64(a), -1.0, 4294967296.0)? as u32 as u64))
+                }
+                Op::I64TruncF32S => {
+                    unary!(|a| trunc_i64(f64::from(f32(a)))? as u64)
+                }
+                Op::I64TruncF32U => {
+                    unary!(|a| trunc(f64::from(f32(a)), -1.0, 18446744073709551616.0)? as u64)
+                }
+                Op::I64TruncF64S => unary!(|a| trunc_i64(f64(a))? as u64),
+                Op::I64TruncF64U => {
+                    unary!(|a| trunc(f64(a), -1.0, 18446744073709551616.0)? as u64)
+                }
+                // Rust's float-to-int casts saturate, just like wasm's.
+                Op::I32TruncSatF32S => unary!(|a| i32(f32(a) as i32 as u64)),
+                Op::I32TruncSatF32U => unary!(|a| u64::from(f32(a) as u32)),
+                Op::I32TruncSatF64S => unary!(|a| i32(f64(a) as i32 as u64)),
+                Op::I32TruncSatF64U => unary!(|a| u64::from(f64(a) as u32)),
+                Op::I64TruncSatF32S => unary!(|a| f32(a) as i64 as u64),
+                Op::I64TruncSatF32U => unary!(|a| f32(a) as u64),
+                Op::I64TruncSatF64S => unary!(|a| f64(a) as i64 as u64),
+                Op::I64TruncSatF64U => unary!(|a| f64(a) as u64),
+                Op::F32ConvertI32S => unary!(|a| from_f32(a as i32 as f32)),
+                Op::F32ConvertI32U => unary!(|a| from_f32(a as u32 as f32)),
+                Op::F32ConvertI64S => unary!(|a| from_f32(a as i64 as f32)),
+                Op::F32ConvertI64U => unary!(|a| from_f32(a as f32)),
+                Op::F64ConvertI32S => unary!(|a| from_f64(f64::from(a as i32))),
+                Op::F64ConvertI32U => unary!(|a| from_f64(f64::from(a as u32))),
+                Op::F64ConvertI64S => unary!(|a| from_f64(a as i64 as f64)),
+                Op::F64ConvertI64U => unary!(|a| from_f64(a as f64)),
+                Op::F32DemoteF64 => unary!(|a| from_f32(f64(a) as f32)),
+                Op::F64PromoteF32 => unary!(|a| from_f64(f64::from(f32(a)))),
+            }
+        }
+    }
+
+    /// Calls `callee` with the `params` arguments in the registers starting at
+    /// `base`, which receive its `results` results.
+    unsafe fn call(
+        &mut self,
+        caller: *mut VMContext,
+        callee: Callee,
+        base: usize,
+        params: usize,
+        results: usize,
+    ) -> Result<(), TrapReason> {
+        if is_interpreted_array_call(callee.array_call) {
+            let func = callee.wasm_call.unwrap().as_ptr().cast::<u8>().cast_const();
+            return self.execute(VMContext::from_opaque(callee.vmctx), func, base);
+        }
+
+        // Other functions are called through their array calling convention.
+        // They're called within `catch_traps` as traps unwinding through the
+        // interpreter would skip its destructors.
+        let mut values = self.regs[base..base + params]
+            .iter()
+            .map(|reg| ValRaw::u64(*reg))
+            .collect::<Vec<_>>();
+        values.resize(params.max(results), ValRaw::u64(0));
+        let len = values.len();
+        let values_ptr = values.as_mut_ptr();
+        catch_traps(None, false, false, caller, |caller| {
+            (callee.array_call)(
+                callee.vmctx,
+                VMOpaqueContext::from_vmcontext(caller),
+                values_ptr,
+                len,
+            )
+        })
+        .map_err(|trap| trap.reason)?;
+        for (reg, value) in self.regs[base..base + results].iter_mut().zip(&values) {
+            *reg = value.get_u64();
+        }
+        Ok(())
+    }
+}
+
+/// A function called by the interpreter.
+struct Callee {
+    vmctx: *mut VMOpaqueContext,
+    array_call: VMArrayCallFunction,
+    wasm_call: Option<NonNull<VMWasmCallFunction>>,
+}
+
+/// Reads the `base, params, results` operands of calls.
+unsafe fn call_operands(code: &mut Code, base: usize) -> (usize, usize, usize) {
+    let callee_base = code.reg(base);
+    let params = usize::from(code.u16());
+    let results = usize::from(code.u16());
+    (callee_base, params, results)
+}
+
+unsafe fn global_ptr(vmctx: *mut VMContext, index: u32) -> *mut crate::VMGlobalDefinition {
+    Instance::from_vmctx(vmctx, |i| {
+        i.defined_or_imported_global_ptr(GlobalIndex::from_u32(index))
+    })
+}
+
+/// Returns the address of an access of `size` bytes at the 32-bit `addr` plus
+/// `offset` in `memory`, trapping if it's out of bounds.
+unsafe fn memory_addr(
+    memory: *mut VMMemoryDefinition,
+    addr: u64,
+    offset: u32,
+    size: usize,
+) -> Result<*mut u8, Trap> {
+    let memory = &*memory;
+    let start = u64::from(addr as u32) + u64::from(offset);
+    if start + size as u64 > memory.current_length() as u64 {
+        return Err(Trap::MemoryOutOfBounds);
+    }
+    Ok(memory.base.add(start as usize))
+}
+
+/// Truncates `x` towards zero, trapping unless the result is within the
+/// exclusive range `(min, max)`.
+fn trunc(x: f64, min: f64, max: f64) -> Result<f64, Trap> {
+    if x.is_nan() {
+        return Err(Trap::BadConversionToInteger);
+    }
+    if x <= min || x >= max {
+        return Err(Trap::IntegerOverflow);
+    }
+    Ok(x.trunc())
+}
+
+/// Like `trunc` to a signed 64-bit integer, whose minimum is exactly
+/// representable as an `f64` unlike the integer below it.
+fn trunc_i64(x: f64) -> Result<i64, Trap> {
+    if x.is_nan() {
+        return Err(Trap::BadConversionToInteger);
+    }
+    if x < -9223372036854775808.0 || x >= 9223372036854775808.0 {
+        return Err(Trap::IntegerOverflow);
+    }
+    Ok(x as i64)
+}
+
+/// Returns the `funcref` held in a register.
+///
+/// Only the low bits of the register are used on 32-bit hosts, where host
+/// functions returning a `funcref` don't write the upper half of its value.
+fn func_ref(x: u64) -> *mut VMFuncRef {
+    x as usize as *mut VMFuncRef
+}
+
+/// Zero-extends the low 32 bits of `x`, as 32-bit values are kept in
+/// registers.
+fn i32(x: u64) -> u64 {
+    u64::from(x as u32)
+}
+
+fn bool(b: bool) -> u64 {
+    u64::from(b)
+}
+
+fn f32(x: u64) -> f32 {
+    f32::from_bits(x as u32)
+}
+
+fn from_f32(x: f32) -> u64 {
+    u64::from(x.to_bits())
+}
+
+fn f64(x: u64) -> f64 {
+    f64::from_bits(x)
+}
+
+fn from_f64(x: f64) -> u64 {
+    x.to_bits()
+}
+
+// Unlike Rust's `min` and `max`, wasm's propagate NaNs and order `-0.0` before
+// `0.0`.
+
+fn f32_min(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f32::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f32_max(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f32::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+fn f64_min(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f64_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
@@ -17,10 +17,13 @@ mod export;
 mod externref;
 mod imports;
 mod instance;
+#[cfg(feature = "interpreter")]
+pub mod interpreter;
 mod memory;
 mod mmap;
 mod mmap_vec;
 mod parking_spot;
+mod pgo;
 mod send_sync_ptr;
 mod store_box;
 mod sys;
@@ -54,6 +57,7 @@ pub use crate::memory::{
 pub use crate::mmap::Mmap;
 pub use crate::mmap_vec::MmapVec;
 pub use crate::mpk::MpkEnabled;
+pub use crate::pgo::ProfileCounters;
 pub use crate::store_box::*;
 pub use crate::sys::unwind::UnwindRegistration;
 pub use crate::table::{Table, TableElement};
@@ -110,6 +114,10 @@ pub unsafe trait Store {
         &mut self,
     ) -> (&mut VMExternRefActivationsTable, &dyn ModuleInfoLookup);
 
+    /// Returns how to look up module information within this store, used to
+    /// recover the values of wasm locals when capturing a core dump.
+    fn module_info_lookup(&self) -> &dyn ModuleInfoLookup;
+
     /// Callback invoked to allow the store's resource limiter to reject a
     /// memory grow operation.
     fn memory_growing(
@@ -213,6 +221,11 @@ pub trait ModuleRuntimeInfo: Send + Sync + 'static {
 
     /// Offset information for the current host.
     fn offsets(&self) -> &VMOffsets<HostPtr>;
+
+    /// Returns the counters that instrumented code of this module records its
+    /// execution profile into, if it was compiled with profile-guided
+    /// optimization instrumentation.
+    fn pgo_counters(&self) -> Option<&ProfileCounters>;
 }
 
 /// Returns the host OS page size, in bytes.
@@ -230,6 +243,16 @@ pub fn page_size() -> usize {
     };
 }
 
+/// Asks the host OS to back `len` bytes at `ptr` with transparent huge pages.
+///
+/// Huge pages are only a hint, so failures, for example from a kernel built
+/// without support for them, are logged and otherwise ignored.
+pub(crate) unsafe fn enable_huge_pages(ptr: *mut u8, len: usize) {
+    if let Err(e) = sys::vm::madvise_huge_pages(ptr, len) {
+        log::debug!("failed to enable huge pages for linear memory: {e}");
+    }
+}
+
 /// Result of [`Memory::atomic_wait32`] and [`Memory::atomic_wait64`]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum WaitResult {
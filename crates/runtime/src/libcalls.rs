@@ -66,7 +66,8 @@ use std::mem;
 use std::ptr::{self, NonNull};
 use std::time::{Duration, Instant};
 use wasmtime_environ::{
-    DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Trap, Unsigned,
+    DataIndex, DefinedFuncIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Trap,
+    Unsigned,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::AccessError::{
@@ -637,6 +638,21 @@ cfg_if! {
     }
 }
 
+// Hook recording a function entry into the module's execution profile.
+fn pgo_enter_function(instance: &mut Instance, func: u32) {
+    if let Some(counters) = instance.pgo_counters() {
+        counters.enter_function(DefinedFuncIndex::from_u32(func));
+    }
+}
+
+// Hook recording the outcome of a conditional branch into the module's
+// execution profile.
+fn pgo_branch(instance: &mut Instance, func: u32, site: u32, taken: u32) {
+    if let Some(counters) = instance.pgo_counters() {
+        counters.branch(DefinedFuncIndex::from_u32(func), site as usize, taken != 0);
+    }
+}
+
 /// This module contains functions which are used for resolving relocations at
 /// runtime if necessary.
 ///
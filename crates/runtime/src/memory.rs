@@ -34,7 +34,11 @@ pub trait RuntimeMemoryCreator: Send + Sync {
 }
 
 /// A default memory allocator used by Wasmtime
-pub struct DefaultMemoryCreator;
+#[derive(Default)]
+pub struct DefaultMemoryCreator {
+    /// Whether to ask the OS to back memories with transparent huge pages.
+    pub huge_pages: bool,
+}
 
 impl RuntimeMemoryCreator for DefaultMemoryCreator {
     /// Create new MmapMemory
@@ -50,6 +54,7 @@ impl RuntimeMemoryCreator for DefaultMemoryCreator {
             minimum,
             maximum,
             memory_image,
+            self.huge_pages,
         )?))
     }
 }
@@ -161,6 +166,32 @@ pub trait RuntimeLinearMemory: Send + Sync {
     /// This starts at the base of linear memory and ends at the end of the
     /// guard pages, if any.
     fn wasm_accessible(&self) -> Range<usize>;
+
+    /// Returns the number of bytes of virtual address space reserved for this
+    /// memory, including any guard regions.
+    fn reserved_byte_size(&self) -> usize {
+        self.wasm_accessible().len()
+    }
+
+    /// Returns the number of accessible bytes of this memory which are
+    /// currently resident in physical memory, or `None` if that can't be
+    /// determined.
+    fn resident_byte_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Zeroes the bytes of this memory in `range`, releasing the physical
+    /// memory backing them where possible.
+    ///
+    /// The `range` must be aligned to the host page size and within
+    /// `byte_size()`. By default the bytes are simply overwritten with zeros.
+    fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        assert!(range.start <= range.end && range.end <= self.byte_size());
+        unsafe {
+            std::ptr::write_bytes(self.vmmemory().base.add(range.start), 0, range.len());
+        }
+        Ok(())
+    }
 }
 
 /// A linear memory instance.
@@ -193,6 +224,10 @@ pub struct MmapMemory {
     // An optional CoW mapping that provides the initial content of this
     // MmapMemory, if mapped.
     memory_image: Option<MemoryImageSlot>,
+
+    // Whether allocations for this memory are backed by transparent huge
+    // pages, if supported by the host.
+    huge_pages: bool,
 }
 
 impl MmapMemory {
@@ -203,6 +238,7 @@ impl MmapMemory {
         minimum: usize,
         mut maximum: Option<usize>,
         memory_image: Option<&Arc<MemoryImage>>,
+        huge_pages: bool,
     ) -> Result<Self> {
         // It's a programmer error for these two configuration values to exceed
         // the host available address space, so panic if such a configuration is
@@ -235,6 +271,9 @@ impl MmapMemory {
             .and_then(|i| i.checked_add(offset_guard_bytes))
             .ok_or_else(|| format_err!("cannot allocate {} with guard regions", minimum))?;
         let mut mmap = Mmap::accessible_reserved(0, request_bytes)?;
+        if huge_pages {
+            unsafe { crate::enable_huge_pages(mmap.as_mut_ptr(), mmap.len()) };
+        }
 
         if minimum > 0 {
             mmap.make_accessible(pre_guard_bytes, minimum)?;
@@ -250,6 +289,7 @@ impl MmapMemory {
                     minimum,
                     alloc_bytes + extra_to_reserve_on_growth,
                 );
+                slot.set_huge_pages(huge_pages);
                 slot.instantiate(minimum, Some(image), &plan)?;
                 // On drop, we will unmap our mmap'd range that this slot was
                 // mapped on top of, so there is no need for the slot to wipe
@@ -268,6 +308,7 @@ impl MmapMemory {
             offset_guard_size: offset_guard_bytes,
             extra_to_reserve_on_growth,
             memory_image,
+            huge_pages,
         })
     }
 }
@@ -295,6 +336,9 @@ impl RuntimeLinearMemory for MmapMemory {
                 .ok_or_else(|| format_err!("overflow calculating size of memory allocation"))?;
 
             let mut new_mmap = Mmap::accessible_reserved(0, request_bytes)?;
+            if self.huge_pages {
+                unsafe { crate::enable_huge_pages(new_mmap.as_mut_ptr(), new_mmap.len()) };
+            }
             new_mmap.make_accessible(self.pre_guard_size, new_size)?;
 
             // This method has an exclusive reference to `self.mmap` and just
@@ -360,6 +404,29 @@ impl RuntimeLinearMemory for MmapMemory {
         let end = base + (self.mmap.len() - self.pre_guard_size);
         base..end
     }
+
+    fn reserved_byte_size(&self) -> usize {
+        self.mmap.len()
+    }
+
+    fn resident_byte_size(&self) -> Option<usize> {
+        unsafe {
+            let base = self.mmap.as_ptr().add(self.pre_guard_size);
+            crate::sys::vm::resident_bytes(base.cast_mut(), self.accessible)
+        }
+    }
+
+    fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        assert!(range.start <= range.end && range.end <= self.accessible);
+        match self.memory_image.as_mut() {
+            Some(image) => image.discard(range),
+            None => unsafe {
+                let base = self.mmap.as_mut_ptr().add(self.pre_guard_size);
+                crate::sys::vm::discard_pages(base.add(range.start), range.len())?;
+                Ok(())
+            },
+        }
+    }
 }
 
 /// A "static" memory where the lifetime of the backing memory is managed
@@ -459,6 +526,19 @@ impl RuntimeLinearMemory for StaticMemory {
         let end = base + self.memory_and_guard_size;
         base..end
     }
+
+    fn reserved_byte_size(&self) -> usize {
+        self.memory_and_guard_size
+    }
+
+    fn resident_byte_size(&self) -> Option<usize> {
+        unsafe { crate::sys::vm::resident_bytes(self.base.as_ptr(), self.size) }
+    }
+
+    fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        assert!(range.start <= range.end && range.end <= self.size);
+        self.memory_image.discard(range)
+    }
 }
 
 /// For shared memory (and only for shared memory), this lock-version restricts
@@ -481,9 +561,9 @@ struct SharedMemoryInner {
 
 impl SharedMemory {
     /// Construct a new [`SharedMemory`].
-    pub fn new(plan: MemoryPlan) -> Result<Self> {
+    pub fn new(plan: MemoryPlan, huge_pages: bool) -> Result<Self> {
         let (minimum_bytes, maximum_bytes) = Memory::limit_new(&plan, None)?;
-        let mmap_memory = MmapMemory::new(&plan, minimum_bytes, maximum_bytes, None)?;
+        let mmap_memory = MmapMemory::new(&plan, minimum_bytes, maximum_bytes, None, huge_pages)?;
         Self::wrap(&plan, Box::new(mmap_memory), plan.memory)
     }
 
@@ -677,6 +757,18 @@ impl RuntimeLinearMemory for SharedMemory {
     fn wasm_accessible(&self) -> Range<usize> {
         self.0.memory.read().unwrap().wasm_accessible()
     }
+
+    fn reserved_byte_size(&self) -> usize {
+        self.0.memory.read().unwrap().reserved_byte_size()
+    }
+
+    fn resident_byte_size(&self) -> Option<usize> {
+        self.0.memory.read().unwrap().resident_byte_size()
+    }
+
+    fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        self.0.memory.write().unwrap().discard(range)
+    }
 }
 
 /// Representation of a runtime wasm linear memory.
@@ -839,6 +931,32 @@ impl Memory {
         self.0.maximum_byte_size()
     }
 
+    /// Returns the number of bytes of virtual address space reserved for this
+    /// memory, including guard regions.
+    pub fn reserved_byte_size(&self) -> usize {
+        self.0.reserved_byte_size()
+    }
+
+    /// Returns the number of accessible bytes of this memory resident in
+    /// physical memory, if that can be determined on this platform.
+    pub fn resident_byte_size(&self) -> Option<usize> {
+        self.0.resident_byte_size()
+    }
+
+    /// Zeroes the bytes of this memory in `range`, which must be aligned to
+    /// the host page size, releasing the physical memory backing them where
+    /// possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn discard(&mut self, range: Range<usize>) -> Result<()> {
+        let page_size = crate::page_size();
+        assert_eq!(range.start % page_size, 0);
+        assert_eq!(range.end % page_size, 0);
+        self.0.discard(range)
+    }
+
     /// Returns whether or not this memory needs initialization. It
     /// may not if it already has initial content thanks to a CoW
     /// mechanism.
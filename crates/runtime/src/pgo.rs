@@ -0,0 +1,146 @@
+//! Counters backing the execution profiles of modules compiled with
+//! profile-guided optimization instrumentation.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use wasmtime_environ::{
+    BranchProfile, DefinedFuncIndex, FunctionProfile, ModuleProfile, PrimaryMap,
+};
+
+/// Execution counts of all functions of a module, shared by all of its
+/// instances.
+///
+/// Instrumented code reports function entries and conditional branch outcomes
+/// through libcalls which update these counters, and a `ModuleProfile` can be
+/// extracted at any time with `ProfileCounters::profile`.
+pub struct ProfileCounters {
+    functions: PrimaryMap<DefinedFuncIndex, FunctionCounters>,
+}
+
+#[derive(Default)]
+struct FunctionCounters {
+    entries: AtomicU64,
+    // The number of branch sites in a function isn't recorded anywhere, so
+    // this grows on demand as sites are first executed.
+    branches: RwLock<Vec<[AtomicU64; 2]>>,
+}
+
+impl ProfileCounters {
+    /// Creates zeroed counters for a module with `num_defined_funcs` defined
+    /// functions.
+    pub fn new(num_defined_funcs: usize) -> ProfileCounters {
+        let mut functions = PrimaryMap::with_capacity(num_defined_funcs);
+        for _ in 0..num_defined_funcs {
+            functions.push(FunctionCounters::default());
+        }
+        ProfileCounters { functions }
+    }
+
+    /// Records an entry into `func`.
+    pub fn enter_function(&self, func: DefinedFuncIndex) {
+        if let Some(counters) = self.functions.get(func) {
+            counters.entries.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records an execution of the `site`th conditional branch of `func`.
+    pub fn branch(&self, func: DefinedFuncIndex, site: usize, taken: bool) {
+        let counters = match self.functions.get(func) {
+            Some(counters) => counters,
+            None => return,
+        };
+        let record = |site: &[AtomicU64; 2]| {
+            site[0].fetch_add(1, Ordering::Relaxed);
+            if taken {
+                site[1].fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        if let Some(counts) = counters.branches.read().unwrap().get(site) {
+            record(counts);
+            return;
+        }
+        let mut branches = counters.branches.write().unwrap();
+        if branches.len() <= site {
+            branches.resize_with(site + 1, Default::default);
+        }
+        record(&branches[site]);
+    }
+
+    /// Returns a snapshot of the counts recorded so far.
+    pub fn profile(&self) -> ModuleProfile {
+        let mut profile = ModuleProfile::default();
+        for (func, counters) in self.functions.iter() {
+            let entries = counters.entries.load(Ordering::Relaxed);
+            if entries == 0 {
+                continue;
+            }
+            let branches = counters
+                .branches
+                .read()
+                .unwrap()
+                .iter()
+                .map(|[executed, taken]| BranchProfile {
+                    executed: executed.load(Ordering::Relaxed),
+                    taken: taken.load(Ordering::Relaxed),
+                })
+                .collect();
+            profile
+                .functions
+                .insert(func, FunctionProfile { entries, branches });
+        }
+        profile
+    }
+
+    /// Resets all counts to zero.
+    pub fn reset(&self) {
+        for (_, counters) in self.functions.iter() {
+            counters.entries.store(0, Ordering::Relaxed);
+            counters.branches.write().unwrap().clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime_environ::EntityRef;
+
+    #[test]
+    fn counts() {
+        let counters = ProfileCounters::new(2);
+        let f0 = DefinedFuncIndex::new(0);
+        let f1 = DefinedFuncIndex::new(1);
+        counters.enter_function(f1);
+        counters.enter_function(f1);
+        counters.branch(f1, 2, true);
+        counters.branch(f1, 2, false);
+        counters.branch(f1, 0, false);
+        // Out-of-bounds functions are ignored.
+        counters.enter_function(DefinedFuncIndex::new(2));
+
+        let profile = counters.profile();
+        assert!(profile.function(f0).is_none());
+        let p1 = profile.function(f1).unwrap();
+        assert_eq!(p1.entries, 2);
+        assert_eq!(
+            p1.branches,
+            [
+                BranchProfile {
+                    executed: 1,
+                    taken: 0
+                },
+                BranchProfile {
+                    executed: 0,
+                    taken: 0
+                },
+                BranchProfile {
+                    executed: 2,
+                    taken: 1
+                },
+            ]
+        );
+
+        counters.reset();
+        assert!(counters.profile().functions.is_empty());
+    }
+}
@@ -29,10 +29,24 @@ pub unsafe fn decommit_table_pages(ptr: *mut u8, len: usize) -> io::Result<()> {
     Ok(())
 }
 
+pub unsafe fn discard_pages(ptr: *mut u8, len: usize) -> io::Result<()> {
+    std::ptr::write_bytes(ptr, 0, len);
+    Ok(())
+}
+
 pub fn get_page_size() -> usize {
     4096
 }
 
+#[cfg(feature = "pooling-allocator")]
+pub fn huge_page_size() -> Option<usize> {
+    None
+}
+
+pub unsafe fn madvise_huge_pages(_ptr: *mut u8, _len: usize) -> io::Result<()> {
+    Ok(())
+}
+
 pub fn supports_madvise_dontneed() -> bool {
     false
 }
@@ -41,6 +55,10 @@ pub unsafe fn madvise_dontneed(_ptr: *mut u8, _len: usize) -> io::Result<()> {
     unreachable!()
 }
 
+pub unsafe fn resident_bytes(_ptr: *mut u8, _len: usize) -> Option<usize> {
+    None
+}
+
 #[derive(PartialEq, Debug)]
 pub enum MemoryImageSource {}
 
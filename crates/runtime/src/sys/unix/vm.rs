@@ -25,7 +25,6 @@ pub unsafe fn erase_existing_mapping(ptr: *mut u8, len: usize) -> io::Result<()>
     Ok(())
 }
 
-#[cfg(feature = "pooling-allocator")]
 unsafe fn decommit(addr: *mut u8, len: usize) -> io::Result<()> {
     if len == 0 {
         return Ok(());
@@ -81,6 +80,42 @@ pub unsafe fn reset_stack_pages_to_zero(addr: *mut u8, len: usize) -> io::Result
     decommit(addr, len)
 }
 
+pub unsafe fn discard_pages(addr: *mut u8, len: usize) -> io::Result<()> {
+    decommit(addr, len)
+}
+
+#[cfg(feature = "pooling-allocator")]
+pub fn huge_page_size() -> Option<usize> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            static SIZE: std::sync::OnceLock<Option<usize>> = std::sync::OnceLock::new();
+            *SIZE.get_or_init(|| {
+                std::fs::read_to_string("/sys/kernel/mm/transparent_hugepage/hpage_pmd_size")
+                    .ok()?
+                    .trim()
+                    .parse()
+                    .ok()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+pub unsafe fn madvise_huge_pages(ptr: *mut u8, len: usize) -> io::Result<()> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            if len > 0 {
+                rustix::mm::madvise(ptr.cast(), len, rustix::mm::Advice::LinuxHugepage)?;
+            }
+            Ok(())
+        } else {
+            let _ = (ptr, len);
+            Ok(())
+        }
+    }
+}
+
 pub fn get_page_size() -> usize {
     unsafe { libc::sysconf(libc::_SC_PAGESIZE).try_into().unwrap() }
 }
@@ -101,6 +136,19 @@ pub unsafe fn madvise_dontneed(ptr: *mut u8, len: usize) -> io::Result<()> {
     }
 }
 
+pub unsafe fn resident_bytes(ptr: *mut u8, len: usize) -> Option<usize> {
+    if len == 0 {
+        return Some(0);
+    }
+    let page_size = get_page_size();
+    let mut pages = vec![0; (len + page_size - 1) / page_size];
+    if libc::mincore(ptr.cast(), len, pages.as_mut_ptr()) != 0 {
+        return None;
+    }
+    let resident = pages.iter().filter(|p| **p & 1 != 0).count();
+    Some((resident * page_size).min(len))
+}
+
 #[derive(Debug)]
 pub enum MemoryImageSource {
     Mmap(Arc<File>),
@@ -41,6 +41,13 @@ pub unsafe fn decommit_table_pages(addr: *mut u8, len: usize) -> io::Result<()>
     erase_existing_mapping(addr, len)
 }
 
+pub unsafe fn discard_pages(addr: *mut u8, len: usize) -> io::Result<()> {
+    // Decommitting and then recommitting the pages releases their physical
+    // memory and leaves them zeroed on next access.
+    erase_existing_mapping(addr, len)?;
+    expose_existing_mapping(addr, len)
+}
+
 pub fn get_page_size() -> usize {
     unsafe {
         let mut info = MaybeUninit::uninit();
@@ -49,6 +56,15 @@ pub fn get_page_size() -> usize {
     }
 }
 
+#[cfg(feature = "pooling-allocator")]
+pub fn huge_page_size() -> Option<usize> {
+    None
+}
+
+pub unsafe fn madvise_huge_pages(_ptr: *mut u8, _len: usize) -> io::Result<()> {
+    Ok(())
+}
+
 pub fn supports_madvise_dontneed() -> bool {
     false
 }
@@ -57,6 +73,10 @@ pub unsafe fn madvise_dontneed(_ptr: *mut u8, _len: usize) -> io::Result<()> {
     unreachable!()
 }
 
+pub unsafe fn resident_bytes(_ptr: *mut u8, _len: usize) -> Option<usize> {
+    None
+}
+
 #[derive(PartialEq, Debug)]
 pub enum MemoryImageSource {}
 
@@ -5,7 +5,7 @@ mod backtrace;
 mod coredump;
 
 use crate::sys::traphandlers;
-use crate::{Instance, VMContext, VMRuntimeLimits};
+use crate::{Instance, Store, VMContext, VMRuntimeLimits};
 use anyhow::Error;
 use std::any::Any;
 use std::cell::{Cell, UnsafeCell};
@@ -213,8 +213,13 @@ where
     F: FnMut(*mut VMContext),
 {
     let limits = Instance::from_vmctx(caller, |i| i.runtime_limits());
+    let coredump_store = if capture_coredump {
+        Some(Instance::from_vmctx(caller, |i| i.store()))
+    } else {
+        None
+    };
 
-    let result = CallThreadState::new(signal_handler, capture_backtrace, capture_coredump, *limits)
+    let result = CallThreadState::new(signal_handler, capture_backtrace, coredump_store, *limits)
         .with(|cx| {
             traphandlers::wasmtime_setjmp(
                 cx.jmp_buf.as_ptr(),
@@ -255,7 +260,9 @@ mod call_thread_state {
         pub(super) jmp_buf: Cell<*const u8>,
         pub(super) signal_handler: Option<*const SignalHandler<'static>>,
         pub(super) capture_backtrace: bool,
-        pub(super) capture_coredump: bool,
+        /// The store to look up module information in while capturing a core
+        /// dump, if core dumps are captured.
+        pub(super) coredump_store: Option<*mut dyn Store>,
 
         pub(crate) limits: *const VMRuntimeLimits,
 
@@ -289,7 +296,7 @@ mod call_thread_state {
         pub(super) fn new(
             signal_handler: Option<*const SignalHandler<'static>>,
             capture_backtrace: bool,
-            capture_coredump: bool,
+            coredump_store: Option<*mut dyn Store>,
             limits: *const VMRuntimeLimits,
         ) -> CallThreadState {
             CallThreadState {
@@ -297,7 +304,7 @@ mod call_thread_state {
                 jmp_buf: Cell::new(ptr::null()),
                 signal_handler,
                 capture_backtrace,
-                capture_coredump,
+                coredump_store,
                 limits,
                 prev: Cell::new(ptr::null()),
                 old_last_wasm_exit_fp: Cell::new(unsafe { *(*limits).last_wasm_exit_fp.get() }),
@@ -469,10 +476,8 @@ impl CallThreadState {
         limits: *const VMRuntimeLimits,
         trap_pc_and_fp: Option<(usize, usize)>,
     ) -> Option<CoreDumpStack> {
-        if !self.capture_coredump {
-            return None;
-        }
-        Some(CoreDumpStack::new(&self, limits, trap_pc_and_fp))
+        let store = self.coredump_store?;
+        Some(unsafe { CoreDumpStack::new(&self, limits, trap_pc_and_fp, store) })
     }
 
     pub(crate) fn iter<'a>(&'a self) -> impl Iterator<Item = &Self> + 'a {
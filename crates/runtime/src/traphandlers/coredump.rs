@@ -1,6 +1,7 @@
 use wasm_encoder::CoreDumpValue;
+use wasmtime_environ::WasmType;
 
-use crate::{Backtrace, VMRuntimeLimits};
+use crate::{arch, Backtrace, ModuleInfoLookup, Store, VMRuntimeLimits};
 
 use super::CallThreadState;
 
@@ -10,29 +11,122 @@ pub struct CoreDumpStack {
     /// The backtrace containing the stack frames for the CoreDump
     pub bt: Backtrace,
 
-    /// Unimplemented
+    /// The values of the locals, including parameters, of each frame.
+    ///
     /// The indices of the locals and operand_stack all map to each other (ie.
-    /// index 0 is the locals for the first frame in the backtrace, etc)
+    /// index 0 is the locals for the first frame in the backtrace, etc).
+    /// Locals are only recovered for functions compiled with native debug info,
+    /// and only from stack slots, so locals held in registers are missing.
     pub locals: Vec<Vec<CoreDumpValue>>,
 
-    /// Unimplemented
-    /// The operands for each stack frame
+    /// The values on the operand stack of each frame, from the bottom of the
+    /// stack to its top.
+    ///
+    /// Like locals, operands are only recovered for functions compiled with
+    /// native debug info and only from stack slots.
     pub operand_stack: Vec<Vec<CoreDumpValue>>,
 }
 
 impl CoreDumpStack {
     /// Capture a core dump of the current wasm state
-    pub fn new(
+    ///
+    /// # Unsafety
+    ///
+    /// This must be called while the stack of the wasm frames being captured
+    /// is still intact, and `store` must be the store they're running in.
+    pub unsafe fn new(
         cts: &CallThreadState,
         limits: *const VMRuntimeLimits,
         trap_pc_and_fp: Option<(usize, usize)>,
+        store: *mut dyn Store,
     ) -> Self {
-        let bt = unsafe { Backtrace::new_with_trap_state(limits, cts, trap_pc_and_fp) };
+        let bt = Backtrace::new_with_trap_state(limits, cts, trap_pc_and_fp);
+        let modules = (*store).module_info_lookup();
+
+        let trap_pc = trap_pc_and_fp.map(|(pc, _)| pc);
+        let (locals, operand_stack) = bt
+            .frames()
+            .map(|frame| {
+                // Like for backtraces, look up the call instruction rather
+                // than the return address for all but the trapping frame.
+                let pc = if Some(frame.pc()) == trap_pc {
+                    frame.pc()
+                } else {
+                    frame.pc() - 1
+                };
+                frame_values(modules, pc, frame.fp())
+            })
+            .unzip();
 
         Self {
             bt,
-            locals: vec![],
-            operand_stack: vec![],
+            locals,
+            operand_stack,
+        }
+    }
+}
+
+/// Reads the locals and operand stack of the frame at `pc` with frame pointer
+/// `fp` out of the stack slots they're held in.
+unsafe fn frame_values(
+    modules: &dyn ModuleInfoLookup,
+    pc: usize,
+    fp: usize,
+) -> (Vec<CoreDumpValue>, Vec<CoreDumpValue>) {
+    let Some((info, func_offset)) = modules.lookup(pc).and_then(|m| m.lookup_func_info(pc)) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut locals: Vec<_> = info.locals.iter().map(|_| CoreDumpValue::Missing).collect();
+    let height = match info
+        .operand_stack_heights
+        .partition_point(|h| h.code_start <= func_offset)
+    {
+        0 => 0,
+        i => info.operand_stack_heights[i - 1].height,
+    };
+    let mut stack: Vec<_> = (0..height).map(|_| CoreDumpValue::Missing).collect();
+    let Some(cfa) = arch::get_cfa_from_fp(fp) else {
+        return (locals, stack);
+    };
+
+    let live_locals = info
+        .local_slots
+        .iter()
+        .take_while(|slot| slot.code_start <= func_offset)
+        .filter(|slot| func_offset < slot.code_end);
+    for slot in live_locals {
+        let addr = cfa.wrapping_add_signed(slot.cfa_offset as isize) as *const u8;
+        if let Some(value) = read_value(info.locals[slot.local as usize], addr) {
+            locals[slot.local as usize] = value;
         }
     }
+
+    // Values that were popped off the stack may still be live, for example
+    // when they're also held in a local, so only the slots of the values that
+    // are within the stack's current height are used.
+    let live_operands = info
+        .operand_slots
+        .iter()
+        .take_while(|slot| slot.code_start <= func_offset)
+        .filter(|slot| func_offset < slot.code_end && slot.depth < height);
+    for slot in live_operands {
+        let addr = cfa.wrapping_add_signed(slot.cfa_offset as isize) as *const u8;
+        if let Some(value) = read_value(slot.ty, addr) {
+            stack[slot.depth as usize] = value;
+        }
+    }
+    (locals, stack)
+}
+
+/// Reads a value of type `ty` from `addr`, if it can be represented in a core
+/// dump.
+unsafe fn read_value(ty: WasmType, addr: *const u8) -> Option<CoreDumpValue> {
+    Some(match ty {
+        WasmType::I32 => CoreDumpValue::I32(addr.cast::<i32>().read_unaligned()),
+        WasmType::I64 => CoreDumpValue::I64(addr.cast::<i64>().read_unaligned()),
+        WasmType::F32 => CoreDumpValue::F32(addr.cast::<f32>().read_unaligned()),
+        WasmType::F64 => CoreDumpValue::F64(addr.cast::<f64>().read_unaligned()),
+        // Core dumps have no encoding for vectors or references.
+        WasmType::V128 | WasmType::Ref(_) => return None,
+    })
 }
@@ -0,0 +1,46 @@
+//! Run twice, as `server` and as `client`, in separate stores sharing a
+//! virtual network.
+
+use std::time::Duration;
+use test_programs::wasi::sockets::network::{
+    ErrorCode, IpAddressFamily, IpSocketAddress, Ipv4SocketAddress, Network,
+};
+use test_programs::wasi::sockets::tcp::TcpSocket;
+
+const ADDRESS: IpSocketAddress = IpSocketAddress::Ipv4(Ipv4SocketAddress {
+    port: 4000,
+    address: (127, 0, 0, 1),
+});
+
+fn server(net: &Network) {
+    let listener = TcpSocket::new(IpAddressFamily::Ipv4).unwrap();
+    listener.blocking_bind(net, ADDRESS).unwrap();
+    listener.blocking_listen().unwrap();
+
+    let (_accepted, input, output) = listener.blocking_accept().unwrap();
+    assert_eq!(input.blocking_read(4).unwrap(), b"ping");
+    output.blocking_write_util(b"pong").unwrap();
+}
+
+fn client(net: &Network) {
+    // The server runs concurrently and may not be listening yet.
+    let (_client, input, output) = loop {
+        let client = TcpSocket::new(IpAddressFamily::Ipv4).unwrap();
+        match client.blocking_connect(net, ADDRESS) {
+            Ok((input, output)) => break (client, input, output),
+            Err(ErrorCode::ConnectionRefused) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => panic!("failed to connect: {e:?}"),
+        }
+    };
+    output.blocking_write_util(b"ping").unwrap();
+    assert_eq!(input.blocking_read(4).unwrap(), b"pong");
+}
+
+fn main() {
+    let net = Network::default();
+    match std::env::args().nth(1).as_deref() {
+        Some("server") => server(&net),
+        Some("client") => client(&net),
+        other => panic!("unknown role {other:?}"),
+    }
+}
@@ -0,0 +1,52 @@
+use test_programs::wasi::io::error::Error;
+use test_programs::wasi::io::streams::{InputStream, OutputStream};
+use test_programs::wasi::sockets::network::{
+    IpAddressFamily, IpSocketAddress, Ipv4SocketAddress, Network,
+};
+use test_programs::wasi::sockets::tcp::TcpSocket;
+use test_programs::wasi::tls::types::{ClientConnection, ClientHandshake};
+
+/// Connects to the TLS echo server run by the host on `TLS_SERVER_PORT`,
+/// expecting it to be `server_name`.
+fn connect(
+    server_name: &str,
+) -> Result<(TcpSocket, ClientConnection, InputStream, OutputStream), Error> {
+    let port = std::env::var("TLS_SERVER_PORT").unwrap().parse().unwrap();
+    let address = IpSocketAddress::Ipv4(Ipv4SocketAddress {
+        port,
+        address: (127, 0, 0, 1),
+    });
+
+    let net = Network::default();
+    let socket = TcpSocket::new(IpAddressFamily::Ipv4).unwrap();
+    let (input, output) = socket.blocking_connect(&net, address).unwrap();
+
+    let handshake = ClientHandshake::new(server_name, input, output);
+    handshake.set_alpn_protocols(&["echo".to_string()]);
+    let future = ClientHandshake::finish(handshake);
+    future.subscribe().block();
+    let (connection, input, output) = future.get().unwrap().unwrap()?;
+
+    // The socket has to outlive the TLS connection.
+    Ok((socket, connection, input, output))
+}
+
+fn main() {
+    {
+        let (_socket, connection, input, output) = connect("localhost").unwrap();
+        assert_eq!(connection.alpn_protocol().as_deref(), Some("echo"));
+
+        let message = b"Hello, TLS!";
+        output.blocking_write_and_flush(message).unwrap();
+        let mut reply = Vec::new();
+        while reply.len() < message.len() {
+            let remaining = (message.len() - reply.len()) as u64;
+            reply.extend(input.blocking_read(remaining).unwrap());
+        }
+        assert_eq!(reply, message);
+    }
+
+    // The certificate of the server isn't valid for any other name.
+    let err = connect("example.com").err().unwrap();
+    eprintln!("handshake failed as expected: {}", err.to_debug_string());
+}
@@ -0,0 +1,279 @@
+//! # Wasmtime's [wasi-config] Implementation
+//!
+//! This crate provides a Wasmtime host implementation of the
+//! `wasi:config/store` interface, which gives components read-only access to
+//! runtime configuration values by key, along with Wasmtime's
+//! `wasmtime-ext:config/typed` extension to it.
+//!
+//! Values are looked up in an ordered list of [`ConfigProvider`]s held by a
+//! [`WasiConfigCtx`]; the first provider with a value for a key wins. This
+//! crate provides [`StaticConfig`], [`TomlConfig`] and [`EnvConfig`], and
+//! embedders may implement [`ConfigProvider`] for other sources.
+//!
+//! Values are typed, see [`Value`]. Guests may fetch them either as strings
+//! through the standard `wasi:config/store` interface or with their type
+//! through `wasmtime-ext:config/typed`.
+//!
+//! ```
+//! use wasmtime_wasi_config::{StaticConfig, WasiConfigCtx};
+//!
+//! let ctx = WasiConfigCtx::new()
+//!     .provider(StaticConfig::new([("greeting", "hello")]))
+//!     .provider(StaticConfig::new([("retries", 3i64)]));
+//! ```
+//!
+//! [wasi-config]: https://github.com/WebAssembly/wasi-runtime-config
+
+#![deny(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+mod provider;
+
+pub use crate::provider::{ConfigProvider, EnvConfig, Error, StaticConfig, TomlConfig, Value};
+
+/// Bindings generated from the `wasi:config` WIT package and Wasmtime's
+/// extension to it.
+pub mod bindings {
+    #![allow(missing_docs)]
+
+    // The extension isn't in the `wasmtime` namespace as the module generated
+    // for it would shadow the `wasmtime` crate in the generated code.
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "wasmtime-ext:config/imports",
+        tracing: true,
+    });
+
+    pub use self::wasi::config;
+    pub use self::wasmtime_ext::config::typed;
+}
+
+use crate::bindings::config::store;
+use crate::bindings::typed;
+
+/// Per-store state for the wasi-config host implementation.
+///
+/// Cloning a context is cheap, and clones share the same providers.
+#[derive(Clone, Default)]
+pub struct WasiConfigCtx {
+    providers: Vec<Arc<dyn ConfigProvider>>,
+}
+
+impl WasiConfigCtx {
+    /// Creates a context without any configuration values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `provider` to the list of providers consulted for values.
+    ///
+    /// Providers added earlier take precedence over those added later.
+    pub fn provider(mut self, provider: impl ConfigProvider) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Returns the value of `key` from the first provider which has one.
+    pub fn get(&self, key: &str) -> Result<Option<Value>, Error> {
+        for provider in self.providers.iter() {
+            if let Some(value) = provider.get(key)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns every key along with the value [`WasiConfigCtx::get`] would
+    /// return for it, sorted by key.
+    pub fn get_all(&self) -> Result<Vec<(String, Value)>, Error> {
+        let mut values = BTreeMap::new();
+        for provider in self.providers.iter().rev() {
+            values.extend(provider.get_all()?);
+        }
+        Ok(values.into_iter().collect())
+    }
+}
+
+/// Adds the `wasi:config/store` and `wasmtime-ext:config/typed` interfaces to
+/// `linker`, using `get` to project a [`WasiConfigCtx`] out of the store's
+/// data.
+pub fn add_to_linker<T>(
+    linker: &mut wasmtime::component::Linker<T>,
+    get: impl Fn(&mut T) -> &mut WasiConfigCtx + Send + Sync + Copy + 'static,
+) -> anyhow::Result<()> {
+    store::add_to_linker(linker, get)?;
+    typed::add_to_linker(linker, get)
+}
+
+impl From<Error> for store::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Upstream(msg) => store::Error::Upstream(msg),
+            Error::Io(msg) => store::Error::Io(msg),
+        }
+    }
+}
+
+impl From<Value> for typed::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(b) => typed::Value::Boolean(b),
+            Value::Integer(i) => typed::Value::Integer(i),
+            Value::Float(x) => typed::Value::Float(x),
+            Value::String(s) => typed::Value::Text(s),
+            Value::List(values) => typed::Value::List(values.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+/// Lists of lists can't be represented in WIT, so nested lists are provided
+/// to the guest as text.
+impl From<Value> for typed::Scalar {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(b) => typed::Scalar::Boolean(b),
+            Value::Integer(i) => typed::Scalar::Integer(i),
+            Value::Float(x) => typed::Scalar::Float(x),
+            Value::String(s) => typed::Scalar::Text(s),
+            list @ Value::List(_) => typed::Scalar::Text(list.to_string()),
+        }
+    }
+}
+
+impl store::Host for WasiConfigCtx {
+    fn get(&mut self, key: String) -> wasmtime::Result<Result<Option<String>, store::Error>> {
+        Ok(WasiConfigCtx::get(self, &key)
+            .map(|value| value.map(|value| value.to_string()))
+            .map_err(Into::into))
+    }
+
+    fn get_all(&mut self) -> wasmtime::Result<Result<Vec<(String, String)>, store::Error>> {
+        Ok(WasiConfigCtx::get_all(self)
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|(key, value)| (key, value.to_string()))
+                    .collect()
+            })
+            .map_err(Into::into))
+    }
+}
+
+impl typed::Host for WasiConfigCtx {
+    fn get(&mut self, key: String) -> wasmtime::Result<Result<Option<typed::Value>, store::Error>> {
+        Ok(WasiConfigCtx::get(self, &key)
+            .map(|value| value.map(Into::into))
+            .map_err(Into::into))
+    }
+
+    fn get_all(&mut self) -> wasmtime::Result<Result<Vec<(String, typed::Value)>, store::Error>> {
+        Ok(WasiConfigCtx::get_all(self)
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect()
+            })
+            .map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Failing;
+
+    impl ConfigProvider for Failing {
+        fn get(&self, _key: &str) -> Result<Option<Value>, Error> {
+            Err(Error::Upstream("unavailable".to_string()))
+        }
+
+        fn get_all(&self) -> Result<Vec<(String, Value)>, Error> {
+            Err(Error::Io("unavailable".to_string()))
+        }
+    }
+
+    #[test]
+    fn earlier_providers_take_precedence() {
+        let ctx = WasiConfigCtx::new()
+            .provider(StaticConfig::new([("a", "flag")]))
+            .provider(TomlConfig::parse("a = \"file\"\nb = \"file\"").unwrap());
+
+        assert_eq!(ctx.get("a").unwrap(), Some("flag".into()));
+        assert_eq!(ctx.get("b").unwrap(), Some("file".into()));
+        assert_eq!(ctx.get("c").unwrap(), None);
+        assert_eq!(
+            ctx.get_all().unwrap(),
+            [
+                ("a".to_string(), "flag".into()),
+                ("b".to_string(), "file".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn values_are_typed_for_the_guest() {
+        let mut ctx = WasiConfigCtx::new().provider(
+            TomlConfig::parse("port = 80\nratio = 0.5\nhosts = [\"a\", 1, [true]]").unwrap(),
+        );
+
+        // Untyped lookups render values as strings.
+        assert!(matches!(
+            store::Host::get(&mut ctx, "port".to_string()).unwrap(),
+            Ok(Some(v)) if v == "80"
+        ));
+        assert!(matches!(
+            store::Host::get(&mut ctx, "hosts".to_string()).unwrap(),
+            Ok(Some(v)) if v == r#"["a", 1, [true]]"#
+        ));
+
+        assert!(matches!(
+            typed::Host::get(&mut ctx, "port".to_string()).unwrap(),
+            Ok(Some(typed::Value::Integer(80)))
+        ));
+        assert!(matches!(
+            typed::Host::get(&mut ctx, "ratio".to_string()).unwrap(),
+            Ok(Some(typed::Value::Float(x))) if x == 0.5
+        ));
+        assert!(matches!(
+            typed::Host::get(&mut ctx, "missing".to_string()).unwrap(),
+            Ok(None)
+        ));
+        match typed::Host::get(&mut ctx, "hosts".to_string()).unwrap() {
+            Ok(Some(typed::Value::List(list))) => {
+                assert!(matches!(&list[0], typed::Scalar::Text(s) if s == "a"));
+                assert!(matches!(list[1], typed::Scalar::Integer(1)));
+                assert!(matches!(&list[2], typed::Scalar::Text(s) if s == "[true]"));
+            }
+            other => panic!("unexpected value: {other:?}"),
+        }
+
+        let all = typed::Host::get_all(&mut ctx).unwrap().unwrap();
+        let keys: Vec<_> = all.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["hosts", "port", "ratio"]);
+    }
+
+    #[test]
+    fn errors_are_returned_to_the_guest() {
+        let mut ctx = WasiConfigCtx::new()
+            .provider(StaticConfig::new([("a", "1")]))
+            .provider(Failing);
+
+        // A value found before reaching the failing provider is still served.
+        assert!(matches!(
+            store::Host::get(&mut ctx, "a".to_string()).unwrap(),
+            Ok(Some(v)) if v == "1"
+        ));
+        assert!(matches!(
+            store::Host::get(&mut ctx, "b".to_string()).unwrap(),
+            Err(store::Error::Upstream(_))
+        ));
+        assert!(matches!(
+            store::Host::get_all(&mut ctx).unwrap(),
+            Err(store::Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+//! Sources of configuration values.
+
+use anyhow::Context;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Errors which may be returned by a [`ConfigProvider`].
+///
+/// These map one-to-one onto the `error` variant of the `wasi:config/store`
+/// interface and are returned to the guest as-is.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// An error reported by the system the configuration is sourced from.
+    #[error("upstream error: {0}")]
+    Upstream(String),
+    /// An I/O error occurred while reading configuration.
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// A typed configuration value.
+///
+/// Guests which ask for a value as a string see it in its [`Display`]
+/// representation: strings are provided verbatim while all other values are
+/// rendered like TOML (e.g. `4`, `true` or `["a", "b"]`).
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Integer(i64),
+    /// A floating-point number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A list of values.
+    List(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => b.fmt(f),
+            Value::Integer(i) => i.fmt(f),
+            Value::Float(x) => toml::Value::Float(*x).fmt(f),
+            Value::String(s) => s.fmt(f),
+            Value::List(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match value {
+                        Value::String(s) => toml::Value::String(s.clone()).fmt(f)?,
+                        other => other.fmt(f)?,
+                    }
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Value {
+        Value::Integer(i)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Value {
+        Value::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A source of configuration values for the wasi-config API.
+pub trait ConfigProvider: Send + Sync + 'static {
+    /// Returns the value of `key`, if this provider has one.
+    fn get(&self, key: &str) -> Result<Option<Value>, Error>;
+
+    /// Returns every key/value pair this provider has.
+    fn get_all(&self) -> Result<Vec<(String, Value)>, Error>;
+}
+
+/// A fixed set of configuration values, for example those given on the
+/// command line.
+#[derive(Debug, Clone, Default)]
+pub struct StaticConfig {
+    values: BTreeMap<String, Value>,
+}
+
+impl StaticConfig {
+    /// Creates a provider serving `values`. Later duplicates of a key replace
+    /// earlier ones.
+    pub fn new<K, V>(values: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        StaticConfig {
+            values: values
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl ConfigProvider for StaticConfig {
+    fn get(&self, key: &str) -> Result<Option<Value>, Error> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, Value)>, Error> {
+        Ok(self
+            .values
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
+/// Configuration values read from a TOML document.
+///
+/// Nested tables are flattened into dotted keys, so
+///
+/// ```toml
+/// [database]
+/// url = "postgres://localhost"
+/// pool-size = 4
+/// ```
+///
+/// provides `database.url` and `database.pool-size`. Values keep their TOML
+/// type, with arrays provided as [`Value::List`] and date-times as strings.
+/// Tables nested within arrays are provided in their TOML representation.
+#[derive(Debug, Clone)]
+pub struct TomlConfig(StaticConfig);
+
+impl TomlConfig {
+    /// Parses `contents` as a TOML document.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let table: toml::value::Table = toml::from_str(contents)?;
+        let mut values = BTreeMap::new();
+        flatten("", &table, &mut values);
+        Ok(TomlConfig(StaticConfig { values }))
+    }
+
+    /// Reads and parses the TOML file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file `{}`", path.display()))?;
+        Self::parse(&contents)
+            .with_context(|| format!("failed to parse config file `{}`", path.display()))
+    }
+}
+
+fn flatten(prefix: &str, table: &toml::value::Table, values: &mut BTreeMap<String, Value>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, values),
+            other => {
+                values.insert(key, toml_value(other));
+            }
+        }
+    }
+}
+
+fn toml_value(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::Boolean(b) => Value::Bool(*b),
+        toml::Value::Integer(i) => Value::Integer(*i),
+        toml::Value::Float(x) => Value::Float(*x),
+        toml::Value::String(s) => Value::String(s.clone()),
+        toml::Value::Array(values) => Value::List(values.iter().map(toml_value).collect()),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Table(_) => Value::String(value.to_string()),
+    }
+}
+
+impl ConfigProvider for TomlConfig {
+    fn get(&self, key: &str) -> Result<Option<Value>, Error> {
+        self.0.get(key)
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, Value)>, Error> {
+        self.0.get_all()
+    }
+}
+
+/// Passes the host's environment variables through as configuration values.
+///
+/// The environment is read on every lookup so changes made by the host
+/// process are visible to guests. Variables whose name or value is not valid
+/// unicode are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct EnvConfig {
+    prefix: String,
+}
+
+impl EnvConfig {
+    /// Provides every environment variable under its own name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provides only the environment variables whose name starts with
+    /// `prefix`, with the prefix stripped from the key.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        EnvConfig {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl ConfigProvider for EnvConfig {
+    fn get(&self, key: &str) -> Result<Option<Value>, Error> {
+        Ok(std::env::var(format!("{}{key}", self.prefix))
+            .ok()
+            .map(Value::String))
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, Value)>, Error> {
+        Ok(std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+            .filter_map(|(k, v)| {
+                Some((k.strip_prefix(&self.prefix)?.to_string(), Value::String(v)))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_is_flattened() {
+        let config = TomlConfig::parse(
+            r#"
+                name = "app"
+                debug = true
+
+                [database]
+                url = "postgres://localhost"
+                pool-size = 4
+                replicas = ["a", "b"]
+
+                [database.timeouts]
+                connect = 1.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.get("name").unwrap(), Some("app".into()));
+        assert_eq!(config.get("debug").unwrap(), Some(true.into()));
+        assert_eq!(
+            config.get("database.pool-size").unwrap(),
+            Some(Value::Integer(4))
+        );
+        assert_eq!(
+            config.get("database.replicas").unwrap(),
+            Some(vec!["a", "b"].into())
+        );
+        assert_eq!(
+            config.get("database.timeouts.connect").unwrap(),
+            Some(1.5.into())
+        );
+        assert_eq!(config.get("database").unwrap(), None);
+        assert_eq!(config.get_all().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn values_display_like_toml() {
+        assert_eq!(Value::from("app").to_string(), "app");
+        assert_eq!(Value::from(true).to_string(), "true");
+        assert_eq!(Value::Integer(4).to_string(), "4");
+        assert_eq!(Value::from(1.5).to_string(), "1.5");
+        assert_eq!(Value::from(vec!["a", "b"]).to_string(), r#"["a", "b"]"#);
+        assert_eq!(
+            Value::List(vec![Value::Integer(1), vec![2i64].into()]).to_string(),
+            "[1, [2]]"
+        );
+    }
+
+    #[test]
+    fn toml_errors() {
+        assert!(TomlConfig::parse("not toml").is_err());
+        assert!(TomlConfig::from_file("/definitely/not/a/file.toml").is_err());
+    }
+
+    #[test]
+    fn env_prefix() {
+        std::env::set_var("WASI_CONFIG_TEST_PREFIX_KEY", "value");
+        let config = EnvConfig::with_prefix("WASI_CONFIG_TEST_PREFIX_");
+        assert_eq!(config.get("KEY").unwrap(), Some("value".into()));
+        assert!(config
+            .get_all()
+            .unwrap()
+            .contains(&("KEY".to_string(), "value".into())));
+        assert!(!config.get_all().unwrap().iter().any(|(k, _)| k == "PATH"));
+    }
+}
@@ -0,0 +1,291 @@
+//! A [`KeyValueStore`] persisted to the local filesystem.
+
+use crate::store::{increment_counter, Error, KeyValueStore};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+type Bucket = BTreeMap<String, Vec<u8>>;
+
+/// Header written at the start of every bucket file.
+const MAGIC: &[u8; 4] = b"WKV1";
+
+/// A [`KeyValueStore`] which persists each bucket as a single file in a
+/// directory.
+///
+/// A bucket is loaded into memory the first time it is used and every
+/// modification rewrites its file in full, by writing a temporary file and
+/// renaming it over the previous one. A crash therefore never leaves a
+/// partially-written bucket behind, at the cost of write amplification for
+/// large buckets. This makes the store well suited to the small amounts of
+/// state typically kept by handlers, but not as a general-purpose database.
+///
+/// Bucket names must consist only of ASCII alphanumerics, `-`, `_` and `.`,
+/// and may not start with `.`. Names are case-sensitive, even when the
+/// directory is on a case-insensitive filesystem, as upper case letters are
+/// escaped in file names.
+///
+/// The directory must not be shared with another `FileStore`, whether in this
+/// process or another, as buckets are cached in memory and not reloaded.
+pub struct FileStore {
+    dir: PathBuf,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl FileStore {
+    /// Opens a store rooted at `dir`, creating the directory if it does not
+    /// already exist.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn path(&self, bucket: &str, extension: &str) -> Result<PathBuf, Error> {
+        let valid = !bucket.is_empty()
+            && !bucket.starts_with('.')
+            && bucket
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+        if !valid {
+            return Err(Error::AccessDenied);
+        }
+        let mut name = String::new();
+        for c in bucket.chars() {
+            if c.is_ascii_uppercase() {
+                name.push_str(&format!("%{:02x}", c as u8));
+            } else {
+                name.push(c);
+            }
+        }
+        Ok(self.dir.join(format!("{name}.{extension}")))
+    }
+
+    /// Returns the cached contents of `bucket`, loading it from disk if
+    /// this is its first use.
+    fn load<'a>(
+        &self,
+        buckets: &'a mut HashMap<String, Bucket>,
+        bucket: &str,
+    ) -> Result<&'a mut Bucket, Error> {
+        if !buckets.contains_key(bucket) {
+            let path = self.path(bucket, "kv")?;
+            let contents = match fs::read(&path) {
+                Ok(bytes) => decode(&bytes).ok_or_else(|| {
+                    Error::Other(format!("corrupt bucket file `{}`", path.display()))
+                })?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Bucket::new(),
+                Err(e) => return Err(e.into()),
+            };
+            buckets.insert(bucket.to_string(), contents);
+        }
+        Ok(buckets.get_mut(bucket).unwrap())
+    }
+
+    fn read<R>(&self, bucket: &str, f: impl FnOnce(&Bucket) -> R) -> Result<R, Error> {
+        let mut buckets = self.buckets.lock().unwrap();
+        Ok(f(self.load(&mut buckets, bucket)?))
+    }
+
+    /// Applies `f` to `bucket` and writes the result back to disk.
+    fn modify<R>(
+        &self,
+        bucket: &str,
+        f: impl FnOnce(&mut Bucket) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let contents = self.load(&mut buckets, bucket)?;
+
+        // Apply the modification to a copy so that a failed write leaves the
+        // cached contents matching what is on disk.
+        let mut updated = contents.clone();
+        let result = f(&mut updated)?;
+        let tmp = self.path(bucket, "tmp")?;
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&encode(&updated))?;
+        file.sync_all()?;
+        fs::rename(&tmp, self.path(bucket, "kv")?)?;
+        *contents = updated;
+        Ok(result)
+    }
+}
+
+fn encode(bucket: &Bucket) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    for (key, value) in bucket {
+        for bytes in [key.as_bytes(), &value[..]] {
+            out.extend_from_slice(&u32::try_from(bytes.len()).unwrap().to_le_bytes());
+            out.extend_from_slice(bytes);
+        }
+    }
+    out
+}
+
+fn decode(mut bytes: &[u8]) -> Option<Bucket> {
+    fn chunk<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let len = u32::from_le_bytes(bytes.get(..4)?.try_into().unwrap()) as usize;
+        let end = len.checked_add(4)?;
+        let data = bytes.get(4..end)?;
+        *bytes = &bytes[end..];
+        Some(data)
+    }
+
+    bytes = bytes.strip_prefix(&MAGIC[..])?;
+    let mut bucket = Bucket::new();
+    while !bytes.is_empty() {
+        let key = String::from_utf8(chunk(&mut bytes)?.to_vec()).ok()?;
+        let value = chunk(&mut bytes)?.to_vec();
+        bucket.insert(key, value);
+    }
+    Some(bucket)
+}
+
+impl KeyValueStore for FileStore {
+    fn open(&self, bucket: &str) -> Result<(), Error> {
+        self.path(bucket, "kv")?;
+        Ok(())
+    }
+
+    fn get(&self, bucket: &str, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        self.read(bucket, |b| b.get(key).cloned())
+    }
+
+    fn set(&self, bucket: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        self.modify(bucket, |b| {
+            b.insert(key.to_string(), value.to_vec());
+            Ok(())
+        })
+    }
+
+    fn delete(&self, bucket: &str, key: &str) -> Result<(), Error> {
+        if !self.exists(bucket, key)? {
+            return Ok(());
+        }
+        self.modify(bucket, |b| {
+            b.remove(key);
+            Ok(())
+        })
+    }
+
+    fn exists(&self, bucket: &str, key: &str) -> Result<bool, Error> {
+        self.read(bucket, |b| b.contains_key(key))
+    }
+
+    fn list_keys(&self, bucket: &str) -> Result<Vec<String>, Error> {
+        self.read(bucket, |b| b.keys().cloned().collect())
+    }
+
+    fn increment(&self, bucket: &str, key: &str, delta: u64) -> Result<u64, Error> {
+        self.modify(bucket, |b| {
+            let value = increment_counter(b.get(key).map(|v| &v[..]), delta)?;
+            b.insert(key.to_string(), value.to_le_bytes().to_vec());
+            Ok(value)
+        })
+    }
+
+    fn get_many(&self, bucket: &str, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        self.read(bucket, |b| {
+            keys.iter().map(|key| b.get(key).cloned()).collect()
+        })
+    }
+
+    fn set_many(&self, bucket: &str, entries: &[(String, Vec<u8>)]) -> Result<(), Error> {
+        self.modify(bucket, |b| {
+            for (key, value) in entries {
+                b.insert(key.clone(), value.clone());
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_many(&self, bucket: &str, keys: &[String]) -> Result<(), Error> {
+        self.modify(bucket, |b| {
+            for key in keys {
+                b.remove(key);
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FileStore::open(dir.path()).unwrap();
+            store.set("app", "greeting", b"hello").unwrap();
+            store.set("app", "empty", b"").unwrap();
+            assert_eq!(store.increment("app", "hits", 3).unwrap(), 3);
+            store.set("other", "k", b"v").unwrap();
+            store.delete("other", "k").unwrap();
+        }
+
+        let store = FileStore::open(dir.path()).unwrap();
+        assert_eq!(
+            store.list_keys("app").unwrap(),
+            ["empty", "greeting", "hits"]
+        );
+        assert_eq!(
+            store.get("app", "greeting").unwrap().as_deref(),
+            Some(&b"hello"[..])
+        );
+        assert_eq!(
+            store.get("app", "empty").unwrap().as_deref(),
+            Some(&b""[..])
+        );
+        assert_eq!(store.increment("app", "hits", 1).unwrap(), 4);
+        assert!(store.list_keys("other").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_bucket_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::open(dir.path()).unwrap();
+        for name in ["", ".", "..", "../escape", "a/b", ".hidden"] {
+            assert_eq!(store.open(name), Err(Error::AccessDenied), "{name:?}");
+            assert_eq!(store.get(name, "k"), Err(Error::AccessDenied), "{name:?}");
+        }
+        assert!(store.open("my-bucket_1.v2").is_ok());
+    }
+
+    #[test]
+    fn bucket_names_are_case_sensitive() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FileStore::open(dir.path()).unwrap();
+            store.set("app", "k", b"lower").unwrap();
+            store.set("App", "k", b"upper").unwrap();
+        }
+
+        let store = FileStore::open(dir.path()).unwrap();
+        assert_eq!(
+            store.get("app", "k").unwrap().as_deref(),
+            Some(&b"lower"[..])
+        );
+        assert_eq!(
+            store.get("App", "k").unwrap().as_deref(),
+            Some(&b"upper"[..])
+        );
+        assert!(dir.path().join("%41pp.kv").exists());
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.kv"), b"garbage").unwrap();
+        // A chunk claiming to be longer than the file.
+        fs::write(dir.path().join("big.kv"), b"WKV1\xff\xff\xff\xff").unwrap();
+        let store = FileStore::open(dir.path()).unwrap();
+        assert!(matches!(store.get("app", "k"), Err(Error::Other(_))));
+        assert!(matches!(store.get("big", "k"), Err(Error::Other(_))));
+    }
+}
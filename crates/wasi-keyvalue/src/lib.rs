@@ -0,0 +1,378 @@
+//! # Wasmtime's [wasi-keyvalue] Implementation
+//!
+//! This crate provides a Wasmtime host implementation of the `store`,
+//! `atomics` and `batch` interfaces of the `wasi:keyvalue/imports` world. The
+//! host implementation is generic over the storage it uses: all operations
+//! are forwarded to a [`KeyValueStore`], of which two are provided:
+//!
+//! * [`InMemoryStore`] keeps everything in process memory, and
+//! * [`FileStore`] persists each bucket as a file in a local directory.
+//!
+//! Embedders wire the interfaces into a [`Linker`] with [`add_to_linker`],
+//! implementing [`WasiKeyValueView`] for their store data. A single
+//! [`WasiKeyValueCtx`] may be cloned into many stores to share state between
+//! them, for example between the requests handled by a server.
+//!
+//! [wasi-keyvalue]: https://github.com/WebAssembly/wasi-keyvalue
+//! [`Linker`]: wasmtime::component::Linker
+
+#![deny(missing_docs)]
+
+use std::sync::Arc;
+use wasmtime::component::{Resource, ResourceTable};
+
+mod file;
+mod memory;
+mod store;
+
+pub use crate::file::FileStore;
+pub use crate::memory::InMemoryStore;
+pub use crate::store::{Error, KeyValueStore};
+
+/// Bindings generated from the `wasi:keyvalue` WIT package.
+pub mod bindings {
+    #![allow(missing_docs)]
+
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "wasi:keyvalue/imports",
+        tracing: true,
+        with: {
+            "wasi:keyvalue/store/bucket": super::Bucket,
+        },
+    });
+
+    pub use self::wasi::keyvalue;
+}
+
+use crate::bindings::keyvalue::{atomics, batch, store as wit};
+
+/// Maximum number of keys returned by a single call to `list-keys`.
+const LIST_KEYS_PAGE_SIZE: usize = 1000;
+
+/// Per-store state for the wasi-keyvalue host implementation.
+///
+/// Cloning a context is cheap, and clones share the same underlying
+/// [`KeyValueStore`].
+#[derive(Clone)]
+pub struct WasiKeyValueCtx {
+    store: Arc<dyn KeyValueStore>,
+}
+
+impl WasiKeyValueCtx {
+    /// Creates a context which serves all buckets from `store`.
+    pub fn new(store: Arc<dyn KeyValueStore>) -> Self {
+        Self { store }
+    }
+
+    /// Returns the store backing this context.
+    pub fn store(&self) -> &Arc<dyn KeyValueStore> {
+        &self.store
+    }
+}
+
+impl Default for WasiKeyValueCtx {
+    /// Creates a context backed by a fresh [`InMemoryStore`].
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryStore::new()))
+    }
+}
+
+/// Accessors for the state the wasi-keyvalue host implementation needs from
+/// a store's data.
+pub trait WasiKeyValueView: Send {
+    /// Returns the wasi-keyvalue context of this store.
+    fn ctx(&self) -> &WasiKeyValueCtx;
+
+    /// Returns the table in which `bucket` resources are stored.
+    fn table(&mut self) -> &mut ResourceTable;
+}
+
+/// Host representation of an opened `bucket` resource.
+pub struct Bucket {
+    name: String,
+}
+
+impl Bucket {
+    /// Returns the identifier the bucket was opened with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Adds all wasi-keyvalue interfaces to `linker`.
+pub fn add_to_linker<T: WasiKeyValueView>(
+    linker: &mut wasmtime::component::Linker<T>,
+) -> anyhow::Result<()> {
+    wit::add_to_linker(linker, |t| t)?;
+    atomics::add_to_linker(linker, |t| t)?;
+    batch::add_to_linker(linker, |t| t)?;
+    Ok(())
+}
+
+impl From<Error> for wit::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NoSuchStore => wit::Error::NoSuchStore,
+            Error::AccessDenied => wit::Error::AccessDenied,
+            Error::Other(msg) => wit::Error::Other(msg),
+        }
+    }
+}
+
+/// Looks up the bucket `bucket` and returns its name along with the store it
+/// belongs to.
+fn bucket<T: WasiKeyValueView>(
+    view: &mut T,
+    bucket: &Resource<Bucket>,
+) -> wasmtime::Result<(Arc<dyn KeyValueStore>, String)> {
+    let store = view.ctx().store.clone();
+    let name = view.table().get(bucket)?.name.clone();
+    Ok((store, name))
+}
+
+impl<T: WasiKeyValueView> wit::Host for T {
+    fn open(
+        &mut self,
+        identifier: String,
+    ) -> wasmtime::Result<Result<Resource<Bucket>, wit::Error>> {
+        if let Err(e) = self.ctx().store.open(&identifier) {
+            return Ok(Err(e.into()));
+        }
+        let bucket = self.table().push(Bucket { name: identifier })?;
+        Ok(Ok(bucket))
+    }
+}
+
+impl<T: WasiKeyValueView> wit::HostBucket for T {
+    fn get(
+        &mut self,
+        this: Resource<Bucket>,
+        key: String,
+    ) -> wasmtime::Result<Result<Option<Vec<u8>>, wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.get(&name, &key).map_err(Into::into))
+    }
+
+    fn set(
+        &mut self,
+        this: Resource<Bucket>,
+        key: String,
+        value: Vec<u8>,
+    ) -> wasmtime::Result<Result<(), wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.set(&name, &key, &value).map_err(Into::into))
+    }
+
+    fn delete(
+        &mut self,
+        this: Resource<Bucket>,
+        key: String,
+    ) -> wasmtime::Result<Result<(), wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.delete(&name, &key).map_err(Into::into))
+    }
+
+    fn exists(
+        &mut self,
+        this: Resource<Bucket>,
+        key: String,
+    ) -> wasmtime::Result<Result<bool, wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.exists(&name, &key).map_err(Into::into))
+    }
+
+    fn list_keys(
+        &mut self,
+        this: Resource<Bucket>,
+        cursor: Option<u64>,
+    ) -> wasmtime::Result<Result<wit::KeyResponse, wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        let keys = match store.list_keys(&name) {
+            Ok(keys) => keys,
+            Err(e) => return Ok(Err(e.into())),
+        };
+
+        // The cursor is simply the index of the next key to return, so keys
+        // added or removed between calls may shift the page boundaries.
+        let start = usize::try_from(cursor.unwrap_or(0))
+            .unwrap_or(usize::MAX)
+            .min(keys.len());
+        let end = start.saturating_add(LIST_KEYS_PAGE_SIZE).min(keys.len());
+        let cursor = if end < keys.len() {
+            Some(end as u64)
+        } else {
+            None
+        };
+        Ok(Ok(wit::KeyResponse {
+            keys: keys[start..end].to_vec(),
+            cursor,
+        }))
+    }
+
+    fn drop(&mut self, this: Resource<Bucket>) -> wasmtime::Result<()> {
+        self.table().delete(this)?;
+        Ok(())
+    }
+}
+
+impl<T: WasiKeyValueView> atomics::Host for T {
+    fn increment(
+        &mut self,
+        this: Resource<Bucket>,
+        key: String,
+        delta: u64,
+    ) -> wasmtime::Result<Result<u64, wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.increment(&name, &key, delta).map_err(Into::into))
+    }
+}
+
+impl<T: WasiKeyValueView> batch::Host for T {
+    fn get_many(
+        &mut self,
+        this: Resource<Bucket>,
+        keys: Vec<String>,
+    ) -> wasmtime::Result<Result<Vec<Option<(String, Vec<u8>)>>, wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        let values = match store.get_many(&name, &keys) {
+            Ok(values) => values,
+            Err(e) => return Ok(Err(e.into())),
+        };
+        Ok(Ok(keys
+            .into_iter()
+            .zip(values)
+            .map(|(key, value)| value.map(|value| (key, value)))
+            .collect()))
+    }
+
+    fn set_many(
+        &mut self,
+        this: Resource<Bucket>,
+        key_values: Vec<(String, Vec<u8>)>,
+    ) -> wasmtime::Result<Result<(), wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.set_many(&name, &key_values).map_err(Into::into))
+    }
+
+    fn delete_many(
+        &mut self,
+        this: Resource<Bucket>,
+        keys: Vec<String>,
+    ) -> wasmtime::Result<Result<(), wit::Error>> {
+        let (store, name) = bucket(self, &this)?;
+        Ok(store.delete_many(&name, &keys).map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::keyvalue::store::{Host as _, HostBucket as _};
+
+    struct Ctx {
+        kv: WasiKeyValueCtx,
+        table: ResourceTable,
+    }
+
+    impl WasiKeyValueView for Ctx {
+        fn ctx(&self) -> &WasiKeyValueCtx {
+            &self.kv
+        }
+
+        fn table(&mut self) -> &mut ResourceTable {
+            &mut self.table
+        }
+    }
+
+    fn ctx(kv: &WasiKeyValueCtx) -> Ctx {
+        Ctx {
+            kv: kv.clone(),
+            table: ResourceTable::new(),
+        }
+    }
+
+    fn open(ctx: &mut Ctx, name: &str) -> Resource<Bucket> {
+        ctx.open(name.to_string()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn state_is_shared_between_clones() -> wasmtime::Result<()> {
+        let kv = WasiKeyValueCtx::default();
+        let mut a = ctx(&kv);
+        let mut b = ctx(&kv);
+
+        let bucket = open(&mut a, "counter");
+        assert_eq!(
+            atomics::Host::increment(&mut a, Resource::new_borrow(bucket.rep()), "n".into(), 1)?
+                .unwrap(),
+            1
+        );
+        wit::HostBucket::drop(&mut a, bucket)?;
+
+        let bucket = open(&mut b, "counter");
+        assert_eq!(
+            atomics::Host::increment(&mut b, Resource::new_borrow(bucket.rep()), "n".into(), 1)?
+                .unwrap(),
+            2
+        );
+        assert!(b
+            .exists(Resource::new_borrow(bucket.rep()), "n".into())?
+            .unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn list_keys_paginates() -> wasmtime::Result<()> {
+        let mut ctx = ctx(&WasiKeyValueCtx::default());
+        let bucket = open(&mut ctx, "b");
+        let entries = (0..LIST_KEYS_PAGE_SIZE + 1)
+            .map(|i| (format!("{i:05}"), Vec::new()))
+            .collect();
+        batch::Host::set_many(&mut ctx, Resource::new_borrow(bucket.rep()), entries)?.unwrap();
+
+        let first = ctx
+            .list_keys(Resource::new_borrow(bucket.rep()), None)?
+            .unwrap();
+        assert_eq!(first.keys.len(), LIST_KEYS_PAGE_SIZE);
+        assert_eq!(first.keys[0], "00000");
+        let second = ctx
+            .list_keys(Resource::new_borrow(bucket.rep()), first.cursor)?
+            .unwrap();
+        assert_eq!(second.keys, [format!("{LIST_KEYS_PAGE_SIZE:05}")]);
+        assert_eq!(second.cursor, None);
+        Ok(())
+    }
+
+    #[test]
+    fn batch_get_pairs_keys_with_values() -> wasmtime::Result<()> {
+        let mut ctx = ctx(&WasiKeyValueCtx::default());
+        let bucket = open(&mut ctx, "b");
+        ctx.set(
+            Resource::new_borrow(bucket.rep()),
+            "a".into(),
+            b"1".to_vec(),
+        )?
+        .unwrap();
+        let values = batch::Host::get_many(
+            &mut ctx,
+            Resource::new_borrow(bucket.rep()),
+            vec!["a".into(), "missing".into()],
+        )?
+        .unwrap();
+        assert_eq!(values, [Some(("a".to_string(), b"1".to_vec())), None]);
+        Ok(())
+    }
+
+    #[test]
+    fn store_errors_are_returned_to_the_guest() -> wasmtime::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let kv = WasiKeyValueCtx::new(Arc::new(FileStore::open(dir.path())?));
+        let mut ctx = ctx(&kv);
+        assert!(matches!(
+            ctx.open("../escape".to_string())?,
+            Err(wit::Error::AccessDenied)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! An in-memory [`KeyValueStore`].
+
+use crate::store::{increment_counter, Error, KeyValueStore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+type Bucket = BTreeMap<String, Vec<u8>>;
+
+/// A [`KeyValueStore`] which keeps all of its buckets in process memory.
+///
+/// Buckets are created on first use and their contents are lost when the
+/// store is dropped. All operations, including the batch operations, take a
+/// single lock and are therefore atomic with respect to each other.
+#[derive(Default)]
+pub struct InMemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl InMemoryStore {
+    /// Creates a new, empty, store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_bucket<R>(&self, bucket: &str, f: impl FnOnce(&mut Bucket) -> R) -> R {
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.contains_key(bucket) {
+            buckets.insert(bucket.to_string(), Bucket::new());
+        }
+        f(buckets.get_mut(bucket).unwrap())
+    }
+}
+
+impl KeyValueStore for InMemoryStore {
+    fn get(&self, bucket: &str, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.with_bucket(bucket, |b| b.get(key).cloned()))
+    }
+
+    fn set(&self, bucket: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        self.with_bucket(bucket, |b| b.insert(key.to_string(), value.to_vec()));
+        Ok(())
+    }
+
+    fn delete(&self, bucket: &str, key: &str) -> Result<(), Error> {
+        self.with_bucket(bucket, |b| b.remove(key));
+        Ok(())
+    }
+
+    fn exists(&self, bucket: &str, key: &str) -> Result<bool, Error> {
+        Ok(self.with_bucket(bucket, |b| b.contains_key(key)))
+    }
+
+    fn list_keys(&self, bucket: &str) -> Result<Vec<String>, Error> {
+        Ok(self.with_bucket(bucket, |b| b.keys().cloned().collect()))
+    }
+
+    fn increment(&self, bucket: &str, key: &str, delta: u64) -> Result<u64, Error> {
+        self.with_bucket(bucket, |b| {
+            let value = increment_counter(b.get(key).map(|v| &v[..]), delta)?;
+            b.insert(key.to_string(), value.to_le_bytes().to_vec());
+            Ok(value)
+        })
+    }
+
+    fn get_many(&self, bucket: &str, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        Ok(self.with_bucket(bucket, |b| {
+            keys.iter().map(|key| b.get(key).cloned()).collect()
+        }))
+    }
+
+    fn set_many(&self, bucket: &str, entries: &[(String, Vec<u8>)]) -> Result<(), Error> {
+        self.with_bucket(bucket, |b| {
+            for (key, value) in entries {
+                b.insert(key.clone(), value.clone());
+            }
+        });
+        Ok(())
+    }
+
+    fn delete_many(&self, bucket: &str, keys: &[String]) -> Result<(), Error> {
+        self.with_bucket(bucket, |b| {
+            for key in keys {
+                b.remove(key);
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_independent() {
+        let store = InMemoryStore::new();
+        store.set("a", "k", b"1").unwrap();
+        store.set("b", "k", b"2").unwrap();
+        assert_eq!(store.get("a", "k").unwrap().as_deref(), Some(&b"1"[..]));
+        assert_eq!(store.get("b", "k").unwrap().as_deref(), Some(&b"2"[..]));
+        store.delete("a", "k").unwrap();
+        assert!(!store.exists("a", "k").unwrap());
+        assert!(store.exists("b", "k").unwrap());
+    }
+
+    #[test]
+    fn increment() {
+        let store = InMemoryStore::new();
+        assert_eq!(store.increment("a", "n", 2).unwrap(), 2);
+        assert_eq!(store.increment("a", "n", 3).unwrap(), 5);
+        assert_eq!(
+            store.get("a", "n").unwrap(),
+            Some(5u64.to_le_bytes().to_vec())
+        );
+
+        store.set("a", "s", b"abc").unwrap();
+        assert!(store.increment("a", "s", 1).is_err());
+        store.set("a", "max", &u64::MAX.to_le_bytes()).unwrap();
+        assert!(store.increment("a", "max", 1).is_err());
+    }
+
+    #[test]
+    fn batch_and_list() {
+        let store = InMemoryStore::new();
+        store
+            .set_many(
+                "a",
+                &[
+                    ("y".to_string(), b"2".to_vec()),
+                    ("x".to_string(), b"1".to_vec()),
+                ],
+            )
+            .unwrap();
+        assert_eq!(store.list_keys("a").unwrap(), ["x", "y"]);
+        assert_eq!(
+            store
+                .get_many("a", &["x".to_string(), "z".to_string()])
+                .unwrap(),
+            [Some(b"1".to_vec()), None]
+        );
+        store.delete_many("a", &["x".to_string()]).unwrap();
+        assert_eq!(store.list_keys("a").unwrap(), ["y"]);
+    }
+}
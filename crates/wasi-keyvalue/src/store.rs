@@ -0,0 +1,110 @@
+//! The [`KeyValueStore`] trait which backs the wasi-keyvalue host
+//! implementation.
+
+/// Errors which may be returned by a [`KeyValueStore`].
+///
+/// These map one-to-one onto the `error` variant of the `wasi:keyvalue/store`
+/// interface and are returned to the guest as-is.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The store does not recognize the requested bucket.
+    #[error("no such store")]
+    NoSuchStore,
+    /// The bucket exists but access to it is not permitted.
+    #[error("access denied")]
+    AccessDenied,
+    /// Some other, implementation-specific, error occurred.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Other(err.to_string())
+    }
+}
+
+/// A storage backend for the wasi-keyvalue API.
+///
+/// A store holds any number of named buckets, each of which maps string keys
+/// to byte values. Implementations are shared between all `Store`s which are
+/// configured with them (for example, every request handled by `wasmtime
+/// serve`) and so must be internally synchronized.
+///
+/// Only [`KeyValueStore::increment`] is required to be atomic; the batch
+/// methods have default implementations in terms of the single-key methods
+/// and are not atomic unless a backend overrides them to be.
+pub trait KeyValueStore: Send + Sync + 'static {
+    /// Checks that `bucket` may be opened by the guest.
+    ///
+    /// The default implementation accepts every bucket name.
+    fn open(&self, bucket: &str) -> Result<(), Error> {
+        let _ = bucket;
+        Ok(())
+    }
+
+    /// Returns the value of `key` in `bucket`, if any.
+    fn get(&self, bucket: &str, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Sets `key` in `bucket` to `value`, overwriting any previous value.
+    fn set(&self, bucket: &str, key: &str, value: &[u8]) -> Result<(), Error>;
+
+    /// Removes `key` from `bucket`. Removing a missing key is not an error.
+    fn delete(&self, bucket: &str, key: &str) -> Result<(), Error>;
+
+    /// Returns whether `key` is present in `bucket`.
+    fn exists(&self, bucket: &str, key: &str) -> Result<bool, Error> {
+        Ok(self.get(bucket, key)?.is_some())
+    }
+
+    /// Returns every key in `bucket`, sorted lexicographically.
+    fn list_keys(&self, bucket: &str) -> Result<Vec<String>, Error>;
+
+    /// Atomically adds `delta` to the value of `key` in `bucket` and returns
+    /// the new value.
+    ///
+    /// Values are stored as little-endian 8-byte integers. A missing key is
+    /// treated as zero, and an existing value of any other length is an
+    /// error.
+    fn increment(&self, bucket: &str, key: &str, delta: u64) -> Result<u64, Error>;
+
+    /// Returns the values of each of `keys` in `bucket`, in order.
+    fn get_many(&self, bucket: &str, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        keys.iter().map(|key| self.get(bucket, key)).collect()
+    }
+
+    /// Sets each of `entries` in `bucket`.
+    fn set_many(&self, bucket: &str, entries: &[(String, Vec<u8>)]) -> Result<(), Error> {
+        for (key, value) in entries {
+            self.set(bucket, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Removes each of `keys` from `bucket`.
+    fn delete_many(&self, bucket: &str, keys: &[String]) -> Result<(), Error> {
+        for key in keys {
+            self.delete(bucket, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the result of a [`KeyValueStore::increment`] of `value` by
+/// `delta`.
+pub(crate) fn increment_counter(value: Option<&[u8]>, delta: u64) -> Result<u64, Error> {
+    let current = match value {
+        None => 0,
+        Some(bytes) => match <[u8; 8]>::try_from(bytes) {
+            Ok(bytes) => u64::from_le_bytes(bytes),
+            Err(_) => {
+                return Err(Error::Other(
+                    "value is not an 8-byte little-endian integer".to_string(),
+                ))
+            }
+        },
+    };
+    current
+        .checked_add(delta)
+        .ok_or_else(|| Error::Other("increment overflowed".to_string()))
+}
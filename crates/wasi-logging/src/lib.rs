@@ -0,0 +1,252 @@
+//! # Wasmtime's [wasi-logging] Implementation
+//!
+//! This crate provides a Wasmtime host implementation of the
+//! `wasi:logging/logging` interface. Rather than writing to stderr, guests
+//! emit log records with a level and a context string, and this crate turns
+//! each record into a [`tracing`] event with the target [`TARGET`]. Every
+//! event carries the fields configured on the store's [`WasiLoggingCtx`]:
+//!
+//! * `component`: the name of the component that emitted the record,
+//! * `request_id`: an optional request identifier, as used by `wasmtime
+//!   serve`, and
+//! * `context`: the context string passed by the guest.
+//!
+//! `tracing` has no equivalent of the `critical` level, so critical records
+//! are emitted as errors with an additional `critical = true` field.
+//!
+//! A [`RateLimiter`], which may be shared between many stores, bounds the
+//! number of records guests may emit. Records over the limit are dropped and
+//! a warning with the number of dropped records is emitted once logging is
+//! allowed again.
+//!
+//! [wasi-logging]: https://github.com/WebAssembly/wasi-logging
+
+#![deny(missing_docs)]
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Bindings generated from the `wasi:logging` WIT package.
+pub mod bindings {
+    #![allow(missing_docs)]
+
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "wasi:logging/imports",
+        tracing: true,
+    });
+
+    pub use self::wasi::logging;
+}
+
+use crate::bindings::logging::logging::{self, Level};
+
+/// The `tracing` target that guest log records are emitted with.
+pub const TARGET: &str = "wasi_logging";
+
+/// Per-store state for the wasi-logging host implementation.
+#[derive(Clone)]
+pub struct WasiLoggingCtx {
+    component: String,
+    request_id: Option<u64>,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl WasiLoggingCtx {
+    /// Creates a context for the component named `component`.
+    pub fn new(component: impl Into<String>) -> Self {
+        WasiLoggingCtx {
+            component: component.into(),
+            request_id: None,
+            limiter: None,
+        }
+    }
+
+    /// Tags every record emitted through this context with `request_id`.
+    pub fn request_id(mut self, request_id: u64) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Limits records emitted through this context with `limiter`.
+    pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Emits a single log record as a `tracing` event.
+    ///
+    /// Records which the current subscriber isn't interested in are discarded
+    /// without counting against the rate limit.
+    pub fn log(&self, level: Level, context: &str, message: &str) {
+        let enabled = match level {
+            Level::Trace => tracing::enabled!(target: TARGET, tracing::Level::TRACE),
+            Level::Debug => tracing::enabled!(target: TARGET, tracing::Level::DEBUG),
+            Level::Info => tracing::enabled!(target: TARGET, tracing::Level::INFO),
+            Level::Warn => tracing::enabled!(target: TARGET, tracing::Level::WARN),
+            Level::Error | Level::Critical => {
+                tracing::enabled!(target: TARGET, tracing::Level::ERROR)
+            }
+        };
+        if !enabled {
+            return;
+        }
+
+        if let Some(limiter) = &self.limiter {
+            match limiter.acquire(Instant::now()) {
+                Acquire::Denied => return,
+                Acquire::Allowed { dropped: 0 } => {}
+                Acquire::Allowed { dropped } => tracing::warn!(
+                    target: TARGET,
+                    component = %self.component,
+                    request_id = self.request_id,
+                    "rate limit exceeded, dropped {dropped} log records"
+                ),
+            }
+        }
+
+        macro_rules! emit {
+            ($level:ident $(, $field:ident = $value:expr)?) => {
+                tracing::$level!(
+                    target: TARGET,
+                    component = %self.component,
+                    request_id = self.request_id,
+                    context = %context,
+                    $($field = $value,)?
+                    "{message}"
+                )
+            };
+        }
+        match level {
+            Level::Trace => emit!(trace),
+            Level::Debug => emit!(debug),
+            Level::Info => emit!(info),
+            Level::Warn => emit!(warn),
+            Level::Error => emit!(error),
+            Level::Critical => emit!(error, critical = true),
+        }
+    }
+}
+
+/// Adds the `wasi:logging/logging` interface to `linker`, using `get` to
+/// project a [`WasiLoggingCtx`] out of the store's data.
+pub fn add_to_linker<T>(
+    linker: &mut wasmtime::component::Linker<T>,
+    get: impl Fn(&mut T) -> &mut WasiLoggingCtx + Send + Sync + Copy + 'static,
+) -> anyhow::Result<()> {
+    logging::add_to_linker(linker, get)
+}
+
+impl logging::Host for WasiLoggingCtx {
+    fn log(&mut self, level: Level, context: String, message: String) -> wasmtime::Result<()> {
+        WasiLoggingCtx::log(self, level, &context, &message);
+        Ok(())
+    }
+}
+
+/// A token-bucket rate limiter for guest log records.
+///
+/// Up to `per_second` records may be emitted in a burst, after which records
+/// are allowed at a steady rate of `per_second`.
+pub struct RateLimiter {
+    per_second: u32,
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    tokens: f64,
+    last_refill: Instant,
+    dropped: u64,
+}
+
+/// The outcome of [`RateLimiter::acquire`].
+#[derive(Debug, PartialEq, Eq)]
+enum Acquire {
+    /// The record may be emitted; `dropped` records were denied since the
+    /// last one that was allowed.
+    Allowed { dropped: u64 },
+    /// The record must be dropped.
+    Denied,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `per_second` records per second.
+    pub fn new(per_second: u32) -> Self {
+        RateLimiter {
+            per_second,
+            state: Mutex::new(LimiterState {
+                tokens: f64::from(per_second),
+                last_refill: Instant::now(),
+                dropped: 0,
+            }),
+        }
+    }
+
+    fn acquire(&self, now: Instant) -> Acquire {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.last_refill);
+        let capacity = f64::from(self.per_second);
+        state.tokens = (state.tokens + elapsed.as_secs_f64() * capacity).min(capacity);
+        state.last_refill = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Acquire::Allowed {
+                dropped: std::mem::take(&mut state.dropped),
+            }
+        } else {
+            state.dropped += 1;
+            Acquire::Denied
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rate_limiter_bursts_then_refills() {
+        let limiter = RateLimiter::new(2);
+        let start = Instant::now();
+        assert_eq!(limiter.acquire(start), Acquire::Allowed { dropped: 0 });
+        assert_eq!(limiter.acquire(start), Acquire::Allowed { dropped: 0 });
+        assert_eq!(limiter.acquire(start), Acquire::Denied);
+        assert_eq!(limiter.acquire(start), Acquire::Denied);
+
+        // Half a second refills a single token at two records per second.
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.acquire(later), Acquire::Allowed { dropped: 2 });
+        assert_eq!(limiter.acquire(later), Acquire::Denied);
+
+        // Tokens never accumulate past the burst size.
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(limiter.acquire(much_later), Acquire::Allowed { dropped: 1 });
+        assert_eq!(limiter.acquire(much_later), Acquire::Allowed { dropped: 0 });
+        assert_eq!(limiter.acquire(much_later), Acquire::Denied);
+    }
+
+    #[test]
+    fn disabled_records_take_no_tokens() {
+        // No subscriber is installed, so every record is disabled.
+        let limiter = Arc::new(RateLimiter::new(1));
+        let ctx = WasiLoggingCtx::new("test").rate_limiter(limiter.clone());
+        ctx.log(Level::Error, "", "discarded");
+        ctx.log(Level::Critical, "", "discarded");
+        assert_eq!(
+            limiter.acquire(Instant::now()),
+            Acquire::Allowed { dropped: 0 }
+        );
+    }
+
+    #[test]
+    fn zero_rate_drops_everything() {
+        let limiter = RateLimiter::new(0);
+        let start = Instant::now();
+        assert_eq!(limiter.acquire(start), Acquire::Denied);
+        assert_eq!(
+            limiter.acquire(start + Duration::from_secs(10)),
+            Acquire::Denied
+        );
+    }
+}
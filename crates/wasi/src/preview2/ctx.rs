@@ -7,6 +7,8 @@ use crate::preview2::{
     network::{SocketAddrCheck, SocketAddrUse},
     pipe, random, stdio,
     stdio::{StdinStream, StdoutStream},
+    tls::rustls,
+    virtual_network::NetworkProvider,
     DirPerms, FilePerms,
 };
 use cap_rand::{Rng, RngCore, SeedableRng};
@@ -28,6 +30,8 @@ pub struct WasiCtxBuilder {
     wall_clock: Box<dyn HostWallClock + Send + Sync>,
     monotonic_clock: Box<dyn HostMonotonicClock + Send + Sync>,
     allowed_network_uses: AllowedNetworkUses,
+    network_provider: Option<Arc<dyn NetworkProvider>>,
+    tls_client_config: Option<Arc<rustls::ClientConfig>>,
     built: bool,
 }
 
@@ -78,6 +82,8 @@ impl WasiCtxBuilder {
             wall_clock: wall_clock(),
             monotonic_clock: monotonic_clock(),
             allowed_network_uses: AllowedNetworkUses::default(),
+            network_provider: None,
+            tls_client_config: None,
             built: false,
         }
     }
@@ -222,6 +228,37 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Routes all TCP and UDP sockets created by the guest through `provider`
+    /// instead of the host's network stack.
+    ///
+    /// Addresses are still subject to the
+    /// [`socket_addr_check`](WasiCtxBuilder::socket_addr_check), so a check
+    /// such as [`inherit_network`](WasiCtxBuilder::inherit_network) is
+    /// needed for the guest to use the network at all. Guests in stores
+    /// configured with clones of the same
+    /// [`LoopbackNetwork`](crate::preview2::virtual_network::LoopbackNetwork)
+    /// can reach each other, but never the host.
+    ///
+    /// Note that `wasi:sockets/ip-name-lookup` is not affected by this
+    /// setting and continues to use the host's resolver if allowed.
+    pub fn network_provider(&mut self, provider: impl NetworkProvider) -> &mut Self {
+        self.network_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Configures the TLS client used for `wasi:tls` connections.
+    ///
+    /// This decides which root certificates are trusted to verify servers
+    /// and which client certificate, if any, is presented to them. Protocols
+    /// requested by the guest through ALPN replace those of `config`.
+    ///
+    /// By default the Mozilla root certificates are trusted and no client
+    /// certificate is presented.
+    pub fn tls_client_config(&mut self, config: rustls::ClientConfig) -> &mut Self {
+        self.tls_client_config = Some(Arc::new(config));
+        self
+    }
+
     /// Uses the configured context so far to construct the final `WasiCtx`.
     ///
     /// Note that each `WasiCtxBuilder` can only be used to "build" once, and
@@ -247,6 +284,8 @@ impl WasiCtxBuilder {
             wall_clock,
             monotonic_clock,
             allowed_network_uses,
+            network_provider,
+            tls_client_config,
             built: _,
         } = mem::replace(self, Self::new());
         self.built = true;
@@ -265,6 +304,8 @@ impl WasiCtxBuilder {
             wall_clock,
             monotonic_clock,
             allowed_network_uses,
+            network_provider,
+            tls_client_config,
         }
     }
 }
@@ -290,6 +331,8 @@ pub struct WasiCtx {
     pub(crate) stderr: Box<dyn StdoutStream>,
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allowed_network_uses: AllowedNetworkUses,
+    pub(crate) network_provider: Option<Arc<dyn NetworkProvider>>,
+    pub(crate) tls_client_config: Option<Arc<rustls::ClientConfig>>,
 }
 
 pub struct AllowedNetworkUses {
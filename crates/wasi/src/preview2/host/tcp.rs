@@ -1,6 +1,6 @@
 use crate::preview2::host::network::util;
 use crate::preview2::network::SocketAddrUse;
-use crate::preview2::tcp::{TcpSocket, TcpState};
+use crate::preview2::tcp::{TcpBackend, TcpSocket, TcpState};
 use crate::preview2::{
     bindings::{
         io::streams::{InputStream, OutputStream},
@@ -17,7 +17,10 @@ use rustix::net::sockopt;
 use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::io::Interest;
-use wasmtime::component::Resource;
+use wasmtime::component::{Resource, ResourceTable};
+
+/// The backlog size used by virtual sockets when the guest doesn't set one.
+const DEFAULT_BACKLOG: i32 = 128;
 
 impl<T: WasiView> tcp::Host for T {}
 
@@ -43,10 +46,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         util::validate_unicast(&local_address)?;
         util::validate_address_family(&local_address, &socket.family)?;
 
-        {
-            // Ensure that we're allowed to connect to this address.
-            network.check_socket_addr(&local_address, SocketAddrUse::TcpBind)?;
+        // Ensure that we're allowed to connect to this address.
+        network.check_socket_addr(&local_address, SocketAddrUse::TcpBind)?;
 
+        if socket.as_virtual().is_none() {
             // Automatically bypass the TIME_WAIT state when the user is trying
             // to bind to a specific port:
             let reuse_addr = local_address.port() > 0;
@@ -71,6 +74,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         }
 
         let socket = table.get_mut(&this)?;
+        let family = socket.family;
+        if let Some(virt) = socket.as_virtual_mut() {
+            virt.bind(local_address, family)?;
+        }
         socket.tcp_state = TcpState::BindStarted;
 
         Ok(())
@@ -98,10 +105,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
     ) -> SocketResult<()> {
         self.ctx().allowed_network_uses.check_allowed_tcp()?;
         let table = self.table_mut();
+        let remote_address: SocketAddr = remote_address.into();
         let r = {
             let socket = table.get(&this)?;
             let network = table.get(&network)?;
-            let remote_address: SocketAddr = remote_address.into();
 
             match socket.tcp_state {
                 TcpState::Default => {}
@@ -122,8 +129,12 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             // Ensure that we're allowed to connect to this address.
             network.check_socket_addr(&remote_address, SocketAddrUse::TcpConnect)?;
 
-            // Do an OS `connect`. Our socket is non-blocking, so it'll either...
-            util::tcp_connect(socket.tcp_socket(), &remote_address)
+            match socket.as_virtual() {
+                // Virtual connections always complete asynchronously.
+                Some(_) => Err(Errno::INPROGRESS),
+                // Do an OS `connect`. Our socket is non-blocking, so it'll either...
+                None => util::tcp_connect(socket.tcp_socket(), &remote_address),
+            }
         };
 
         match r {
@@ -145,6 +156,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         }
 
         let socket = table.get_mut(&this)?;
+        let family = socket.family;
+        if let Some(virt) = socket.as_virtual_mut() {
+            virt.start_connect(remote_address, family)?;
+        }
         socket.tcp_state = TcpState::Connecting;
 
         Ok(())
@@ -159,6 +174,16 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
 
         match socket.tcp_state {
             TcpState::ConnectReady => {}
+            TcpState::Connecting if socket.as_virtual().is_some() => {
+                match socket.as_virtual_mut().unwrap().finish_connect() {
+                    None => return Err(ErrorCode::WouldBlock.into()),
+                    Some(Ok(())) => {}
+                    Some(Err(err)) => {
+                        socket.tcp_state = TcpState::ConnectFailed;
+                        return Err(err.into());
+                    }
+                }
+            }
             TcpState::Connecting => {
                 // Do a `poll` to test for completion, using a timeout of zero
                 // to avoid blocking.
@@ -211,7 +236,15 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             | TcpState::BindStarted => return Err(ErrorCode::ConcurrencyConflict.into()),
         }
 
-        util::tcp_listen(socket.tcp_socket(), socket.listen_backlog_size)?;
+        match socket.backend {
+            TcpBackend::Host(_) => {
+                util::tcp_listen(socket.tcp_socket(), socket.listen_backlog_size)?;
+            }
+            TcpBackend::Virtual(ref mut virt) => {
+                let backlog = socket.listen_backlog_size.unwrap_or(DEFAULT_BACKLOG);
+                virt.listen(backlog.try_into().unwrap())?;
+            }
+        }
 
         socket.tcp_state = TcpState::ListenStarted;
 
@@ -249,6 +282,14 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             _ => return Err(ErrorCode::InvalidState.into()),
         }
 
+        if let Some(virt) = socket.as_virtual() {
+            let client = virt.accept().ok_or(ErrorCode::WouldBlock)?;
+            let mut tcp_socket =
+                TcpSocket::from_backend(TcpBackend::Virtual(Box::new(client)), socket.family);
+            tcp_socket.tcp_state = TcpState::Connected;
+            return push_accepted(self.table_mut(), tcp_socket);
+        }
+
         // Do the OS accept call.
         let tcp_socket = socket.tcp_socket();
         let (client_fd, _addr) = tcp_socket.try_io(Interest::READABLE, || {
@@ -285,14 +326,7 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         // Mark the socket as connected so that we can exit early from methods like `start-bind`.
         tcp_socket.tcp_state = TcpState::Connected;
 
-        let (input, output) = tcp_socket.as_split();
-        let output: OutputStream = output;
-
-        let tcp_socket = self.table_mut().push(tcp_socket)?;
-        let input_stream = self.table_mut().push_child(input, &tcp_socket)?;
-        let output_stream = self.table_mut().push_child(output, &tcp_socket)?;
-
-        Ok((tcp_socket, input_stream, output_stream))
+        push_accepted(self.table_mut(), tcp_socket)
     }
 
     fn local_address(&mut self, this: Resource<tcp::TcpSocket>) -> SocketResult<IpSocketAddress> {
@@ -305,6 +339,11 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             _ => {}
         }
 
+        if let Some(virt) = socket.as_virtual() {
+            let addr = virt.local_address().ok_or(ErrorCode::InvalidState)?;
+            return Ok(addr.into());
+        }
+
         let addr = socket
             .tcp_socket()
             .as_socketlike_view::<std::net::TcpStream>()
@@ -324,6 +363,11 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             _ => return Err(ErrorCode::InvalidState.into()),
         }
 
+        if let Some(virt) = socket.as_virtual() {
+            let addr = virt.remote_address().ok_or(ErrorCode::InvalidState)?;
+            return Ok(addr.into());
+        }
+
         let addr = socket
             .tcp_socket()
             .as_socketlike_view::<std::net::TcpStream>()
@@ -375,7 +419,9 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             SocketAddressFamily::Ipv4 => Err(ErrorCode::NotSupported.into()),
             SocketAddressFamily::Ipv6 { .. } => match socket.tcp_state {
                 TcpState::Default => {
-                    sockopt::set_ipv6_v6only(socket.tcp_socket(), value)?;
+                    if socket.as_virtual().is_none() {
+                        sockopt::set_ipv6_v6only(socket.tcp_socket(), value)?;
+                    }
                     socket.family = SocketAddressFamily::Ipv6 { v6only: value };
                     Ok(())
                 }
@@ -417,8 +463,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
                 // Try to update the backlog by calling `listen` again.
                 // Not all platforms support this. We'll only update our own value if the OS supports changing the backlog size after the fact.
 
-                rustix::net::listen(socket.tcp_socket(), value)
-                    .map_err(|_| ErrorCode::NotSupported)?;
+                if socket.as_virtual().is_none() {
+                    rustix::net::listen(socket.tcp_socket(), value)
+                        .map_err(|_| ErrorCode::NotSupported)?;
+                }
 
                 socket.listen_backlog_size = Some(value);
 
@@ -434,6 +482,9 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
     fn keep_alive_enabled(&mut self, this: Resource<tcp::TcpSocket>) -> SocketResult<bool> {
         let table = self.table();
         let socket = table.get(&this)?;
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.keep_alive_enabled);
+        }
         Ok(sockopt::get_socket_keepalive(socket.tcp_socket())?)
     }
 
@@ -442,14 +493,21 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         this: Resource<tcp::TcpSocket>,
         value: bool,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
+        if let Some(virt) = socket.as_virtual_mut() {
+            virt.options.keep_alive_enabled = value;
+            return Ok(());
+        }
         Ok(sockopt::set_socket_keepalive(socket.tcp_socket(), value)?)
     }
 
     fn keep_alive_idle_time(&mut self, this: Resource<tcp::TcpSocket>) -> SocketResult<u64> {
         let table = self.table();
         let socket = table.get(&this)?;
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.keep_alive_idle_time.as_nanos() as u64);
+        }
         Ok(sockopt::get_tcp_keepidle(socket.tcp_socket())?.as_nanos() as u64)
     }
 
@@ -463,6 +521,14 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
 
         let duration = Duration::from_nanos(value);
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if duration.is_zero() {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.keep_alive_idle_time = duration;
+            return Ok(());
+        }
+
         util::set_tcp_keepidle(socket.tcp_socket(), duration)?;
 
         #[cfg(target_os = "macos")]
@@ -476,6 +542,9 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
     fn keep_alive_interval(&mut self, this: Resource<tcp::TcpSocket>) -> SocketResult<u64> {
         let table = self.table();
         let socket = table.get(&this)?;
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.keep_alive_interval.as_nanos() as u64);
+        }
         Ok(sockopt::get_tcp_keepintvl(socket.tcp_socket())?.as_nanos() as u64)
     }
 
@@ -484,8 +553,15 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         this: Resource<tcp::TcpSocket>,
         value: u64,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.keep_alive_interval = Duration::from_nanos(value);
+            return Ok(());
+        }
         Ok(util::set_tcp_keepintvl(
             socket.tcp_socket(),
             Duration::from_nanos(value),
@@ -495,6 +571,9 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
     fn keep_alive_count(&mut self, this: Resource<tcp::TcpSocket>) -> SocketResult<u32> {
         let table = self.table();
         let socket = table.get(&this)?;
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.keep_alive_count);
+        }
         Ok(sockopt::get_tcp_keepcnt(socket.tcp_socket())?)
     }
 
@@ -503,8 +582,15 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         this: Resource<tcp::TcpSocket>,
         value: u32,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.keep_alive_count = value;
+            return Ok(());
+        }
         Ok(util::set_tcp_keepcnt(socket.tcp_socket(), value)?)
     }
 
@@ -512,6 +598,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.hop_limit);
+        }
+
         let ttl = match socket.family {
             SocketAddressFamily::Ipv4 => util::get_ip_ttl(socket.tcp_socket())?,
             SocketAddressFamily::Ipv6 { .. } => util::get_ipv6_unicast_hops(socket.tcp_socket())?,
@@ -524,6 +614,14 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let table = self.table_mut();
         let socket = table.get_mut(&this)?;
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.hop_limit = value;
+            return Ok(());
+        }
+
         match socket.family {
             SocketAddressFamily::Ipv4 => util::set_ip_ttl(socket.tcp_socket(), value)?,
             SocketAddressFamily::Ipv6 { .. } => {
@@ -543,6 +641,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.receive_buffer_size as u64);
+        }
+
         let value = util::get_socket_recv_buffer_size(socket.tcp_socket())?;
         Ok(value as u64)
     }
@@ -556,6 +658,14 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let socket = table.get_mut(&this)?;
         let value = value.try_into().unwrap_or(usize::MAX);
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.receive_buffer_size = value.min(i32::MAX as usize);
+            return Ok(());
+        }
+
         util::set_socket_recv_buffer_size(socket.tcp_socket(), value)?;
 
         #[cfg(target_os = "macos")]
@@ -570,6 +680,10 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.send_buffer_size as u64);
+        }
+
         let value = util::get_socket_send_buffer_size(socket.tcp_socket())?;
         Ok(value as u64)
     }
@@ -583,6 +697,14 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         let socket = table.get_mut(&this)?;
         let value = value.try_into().unwrap_or(usize::MAX);
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.send_buffer_size = value.min(i32::MAX as usize);
+            return Ok(());
+        }
+
         util::set_socket_send_buffer_size(socket.tcp_socket(), value)?;
 
         #[cfg(target_os = "macos")]
@@ -619,10 +741,19 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
             ShutdownType::Both => std::net::Shutdown::Both,
         };
 
-        socket
-            .tcp_socket()
-            .as_socketlike_view::<std::net::TcpStream>()
-            .shutdown(how)?;
+        match socket.as_virtual() {
+            Some(virt) => {
+                if let Some(stream) = virt.stream() {
+                    stream.shutdown(how);
+                }
+            }
+            None => {
+                socket
+                    .tcp_socket()
+                    .as_socketlike_view::<std::net::TcpStream>()
+                    .shutdown(how)?;
+            }
+        }
         Ok(())
     }
 
@@ -637,3 +768,22 @@ impl<T: WasiView> crate::preview2::host::tcp::tcp::HostTcpSocket for T {
         Ok(())
     }
 }
+
+/// Pushes a freshly accepted socket and its streams into the table.
+fn push_accepted(
+    table: &mut ResourceTable,
+    tcp_socket: TcpSocket,
+) -> SocketResult<(
+    Resource<tcp::TcpSocket>,
+    Resource<InputStream>,
+    Resource<OutputStream>,
+)> {
+    let (input, output) = tcp_socket.as_split();
+    let output: OutputStream = output;
+
+    let tcp_socket = table.push(tcp_socket)?;
+    let input_stream = table.push_child(input, &tcp_socket)?;
+    let output_stream = table.push_child(output, &tcp_socket)?;
+
+    Ok((tcp_socket, input_stream, output_stream))
+}
@@ -8,7 +8,10 @@ impl<T: WasiView> tcp_create_socket::Host for T {
         &mut self,
         address_family: IpAddressFamily,
     ) -> SocketResult<Resource<TcpSocket>> {
-        let socket = TcpSocket::new(address_family.into())?;
+        let socket = match &self.ctx().network_provider {
+            Some(provider) => TcpSocket::new_virtual(provider.clone(), address_family.into()),
+            None => TcpSocket::new(address_family.into())?,
+        };
         let socket = self.table_mut().push(socket)?;
         Ok(socket)
     }
@@ -5,7 +5,7 @@ use crate::preview2::{
         sockets::network::{ErrorCode, IpAddressFamily, IpSocketAddress, Network},
         sockets::udp,
     },
-    udp::{IncomingDatagramStream, OutgoingDatagramStream, SendState, UdpState},
+    udp::{IncomingDatagramStream, OutgoingDatagramStream, SendState, UdpState, UdpTransport},
     Subscribe,
 };
 use crate::preview2::{Pollable, SocketError, SocketResult, WasiView};
@@ -53,9 +53,9 @@ impl<T: WasiView> udp::HostUdpSocket for T {
 
         util::validate_address_family(&local_address, &socket.family)?;
 
-        {
-            check.check(&local_address, SocketAddrUse::UdpBind)?;
+        check.check(&local_address, SocketAddrUse::UdpBind)?;
 
+        if socket.as_virtual().is_none() {
             // Perform the OS bind call.
             util::udp_bind(socket.udp_socket(), &local_address).map_err(|error| match error {
                 // From https://pubs.opengroup.org/onlinepubs/9699919799/functions/bind.html:
@@ -71,6 +71,10 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         }
 
         let socket = table.get_mut(&this)?;
+        let family = socket.family;
+        if let Some(virt) = socket.as_virtual_mut() {
+            virt.bind(local_address, family)?;
+        }
         socket.udp_state = UdpState::BindStarted;
 
         Ok(())
@@ -123,7 +127,10 @@ impl<T: WasiView> udp::HostUdpSocket for T {
 
         // Step #1: Disconnect
         if let UdpState::Connected = socket.udp_state {
-            util::udp_disconnect(socket.udp_socket())?;
+            match socket.as_virtual_mut() {
+                Some(virt) => virt.set_remote_address(None),
+                None => util::udp_disconnect(socket.udp_socket())?,
+            }
             socket.udp_state = UdpState::Bound;
         }
 
@@ -136,25 +143,33 @@ impl<T: WasiView> udp::HostUdpSocket for T {
             util::validate_address_family(&connect_addr, &socket.family)?;
             check.check(&connect_addr, SocketAddrUse::UdpConnect)?;
 
-            rustix::net::connect(socket.udp_socket(), &connect_addr).map_err(
-                |error| match error {
-                    Errno::AFNOSUPPORT => ErrorCode::InvalidArgument, // See `bind` implementation.
-                    Errno::INPROGRESS => {
-                        log::debug!("UDP connect returned EINPROGRESS, which should never happen");
-                        ErrorCode::Unknown
-                    }
-                    _ => ErrorCode::from(error),
-                },
-            )?;
+            match socket.as_virtual_mut() {
+                Some(virt) => virt.set_remote_address(Some(connect_addr)),
+                None => {
+                    rustix::net::connect(socket.udp_socket(), &connect_addr).map_err(|error| {
+                        match error {
+                            Errno::AFNOSUPPORT => ErrorCode::InvalidArgument, // See `bind` implementation.
+                            Errno::INPROGRESS => {
+                                log::debug!(
+                                    "UDP connect returned EINPROGRESS, which should never happen"
+                                );
+                                ErrorCode::Unknown
+                            }
+                            _ => ErrorCode::from(error),
+                        }
+                    })?
+                }
+            }
             socket.udp_state = UdpState::Connected;
         }
 
+        let transport = socket.transport().ok_or(ErrorCode::InvalidState)?;
         let incoming_stream = IncomingDatagramStream {
-            inner: socket.inner.clone(),
+            inner: transport.clone(),
             remote_address,
         };
         let outgoing_stream = OutgoingDatagramStream {
-            inner: socket.inner.clone(),
+            inner: transport,
             remote_address,
             family: socket.family,
             send_state: SendState::Idle,
@@ -177,6 +192,11 @@ impl<T: WasiView> udp::HostUdpSocket for T {
             _ => {}
         }
 
+        if let Some(virt) = socket.as_virtual() {
+            let addr = virt.local_address().ok_or(ErrorCode::InvalidState)?;
+            return Ok(addr.into());
+        }
+
         let addr = socket
             .udp_socket()
             .as_socketlike_view::<std::net::UdpSocket>()
@@ -193,6 +213,11 @@ impl<T: WasiView> udp::HostUdpSocket for T {
             _ => return Err(ErrorCode::InvalidState.into()),
         }
 
+        if let Some(virt) = socket.as_virtual() {
+            let addr = virt.remote_address().ok_or(ErrorCode::InvalidState)?;
+            return Ok(addr.into());
+        }
+
         let addr = socket
             .udp_socket()
             .as_socketlike_view::<std::net::UdpSocket>()
@@ -231,7 +256,9 @@ impl<T: WasiView> udp::HostUdpSocket for T {
             SocketAddressFamily::Ipv4 => Err(ErrorCode::NotSupported.into()),
             SocketAddressFamily::Ipv6 { .. } => match socket.udp_state {
                 UdpState::Default => {
-                    sockopt::set_ipv6_v6only(socket.udp_socket(), value)?;
+                    if socket.as_virtual().is_none() {
+                        sockopt::set_ipv6_v6only(socket.udp_socket(), value)?;
+                    }
                     socket.family = SocketAddressFamily::Ipv6 { v6only: value };
                     Ok(())
                 }
@@ -245,6 +272,10 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.hop_limit);
+        }
+
         let ttl = match socket.family {
             SocketAddressFamily::Ipv4 => util::get_ip_ttl(socket.udp_socket())?,
             SocketAddressFamily::Ipv6 { .. } => util::get_ipv6_unicast_hops(socket.udp_socket())?,
@@ -258,8 +289,16 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         this: Resource<udp::UdpSocket>,
         value: u8,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
+
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.hop_limit = value;
+            return Ok(());
+        }
 
         match socket.family {
             SocketAddressFamily::Ipv4 => util::set_ip_ttl(socket.udp_socket(), value)?,
@@ -275,6 +314,10 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.receive_buffer_size as u64);
+        }
+
         let value = util::get_socket_recv_buffer_size(socket.udp_socket())?;
         Ok(value as u64)
     }
@@ -284,10 +327,18 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         this: Resource<udp::UdpSocket>,
         value: u64,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
         let value = value.try_into().unwrap_or(usize::MAX);
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.receive_buffer_size = value.min(i32::MAX as usize);
+            return Ok(());
+        }
+
         util::set_socket_recv_buffer_size(socket.udp_socket(), value)?;
         Ok(())
     }
@@ -296,6 +347,10 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         let table = self.table();
         let socket = table.get(&this)?;
 
+        if let Some(virt) = socket.as_virtual() {
+            return Ok(virt.options.send_buffer_size as u64);
+        }
+
         let value = util::get_socket_send_buffer_size(socket.udp_socket())?;
         Ok(value as u64)
     }
@@ -305,10 +360,18 @@ impl<T: WasiView> udp::HostUdpSocket for T {
         this: Resource<udp::UdpSocket>,
         value: u64,
     ) -> SocketResult<()> {
-        let table = self.table();
-        let socket = table.get(&this)?;
+        let table = self.table_mut();
+        let socket = table.get_mut(&this)?;
         let value = value.try_into().unwrap_or(usize::MAX);
 
+        if let Some(virt) = socket.as_virtual_mut() {
+            if value == 0 {
+                return Err(ErrorCode::InvalidArgument.into());
+            }
+            virt.options.send_buffer_size = value.min(i32::MAX as usize);
+            return Ok(());
+        }
+
         util::set_socket_send_buffer_size(socket.udp_socket(), value)?;
         Ok(())
     }
@@ -339,9 +402,15 @@ impl<T: WasiView> udp::HostIncomingDatagramStream for T {
         fn recv_one(
             stream: &IncomingDatagramStream,
         ) -> SocketResult<Option<udp::IncomingDatagram>> {
-            let mut buf = [0; MAX_UDP_DATAGRAM_SIZE];
-            let (size, received_addr) = stream.inner.try_recv_from(&mut buf)?;
-            debug_assert!(size <= buf.len());
+            let (data, received_addr) = match &stream.inner {
+                UdpTransport::Host(socket) => {
+                    let mut buf = [0; MAX_UDP_DATAGRAM_SIZE];
+                    let (size, received_addr) = socket.try_recv_from(&mut buf)?;
+                    debug_assert!(size <= buf.len());
+                    (buf[..size].to_vec(), received_addr)
+                }
+                UdpTransport::Virtual(binding) => binding.receive().ok_or(ErrorCode::WouldBlock)?,
+            };
 
             match stream.remote_address {
                 Some(connected_addr) if connected_addr != received_addr => {
@@ -352,7 +421,7 @@ impl<T: WasiView> udp::HostIncomingDatagramStream for T {
             }
 
             Ok(Some(udp::IncomingDatagram {
-                data: buf[..size].into(),
+                data,
                 remote_address: received_addr.into(),
             }))
         }
@@ -412,11 +481,14 @@ impl<T: WasiView> udp::HostIncomingDatagramStream for T {
 #[async_trait]
 impl Subscribe for IncomingDatagramStream {
     async fn ready(&mut self) {
-        // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
-        self.inner
-            .ready(Interest::READABLE)
-            .await
-            .expect("failed to await UDP socket readiness");
+        match &self.inner {
+            // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
+            UdpTransport::Host(socket) => socket
+                .ready(Interest::READABLE)
+                .await
+                .expect("failed to await UDP socket readiness"),
+            UdpTransport::Virtual(binding) => binding.ready().await,
+        }
     }
 }
 
@@ -470,10 +542,15 @@ impl<T: WasiView> udp::HostOutgoingDatagramStream for T {
             util::validate_remote_address(&addr)?;
             util::validate_address_family(&addr, &stream.family)?;
 
-            if stream.remote_address == Some(addr) {
-                stream.inner.try_send(&datagram.data)?;
-            } else {
-                stream.inner.try_send_to(&datagram.data, addr)?;
+            match &stream.inner {
+                UdpTransport::Host(socket) => {
+                    if stream.remote_address == Some(addr) {
+                        socket.try_send(&datagram.data)?;
+                    } else {
+                        socket.try_send_to(&datagram.data, addr)?;
+                    }
+                }
+                UdpTransport::Virtual(binding) => binding.send(addr, datagram.data.clone())?,
             }
 
             Ok(())
@@ -549,11 +626,15 @@ impl Subscribe for OutgoingDatagramStream {
         match self.send_state {
             SendState::Idle | SendState::Permitted(_) => {}
             SendState::Waiting => {
-                // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
-                self.inner
-                    .ready(Interest::WRITABLE)
-                    .await
-                    .expect("failed to await UDP socket readiness");
+                // Virtual sends never block, so only host sockets can end
+                // up waiting here.
+                if let UdpTransport::Host(socket) = &self.inner {
+                    // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
+                    socket
+                        .ready(Interest::WRITABLE)
+                        .await
+                        .expect("failed to await UDP socket readiness");
+                }
                 self.send_state = SendState::Idle;
             }
         }
@@ -8,7 +8,10 @@ impl<T: WasiView> udp_create_socket::Host for T {
         &mut self,
         address_family: IpAddressFamily,
     ) -> SocketResult<Resource<UdpSocket>> {
-        let socket = UdpSocket::new(address_family.into())?;
+        let socket = match &self.ctx().network_provider {
+            Some(provider) => UdpSocket::new_virtual(provider.clone(), address_family.into()),
+            None => UdpSocket::new(address_family.into())?,
+        };
         let socket = self.table_mut().push(socket)?;
         Ok(socket)
     }
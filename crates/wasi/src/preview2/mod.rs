@@ -37,14 +37,16 @@ mod random;
 mod stdio;
 mod stream;
 mod tcp;
+pub mod tls;
 mod udp;
+pub mod virtual_network;
 mod write_stream;
 
 pub use self::clocks::{HostMonotonicClock, HostWallClock};
 pub use self::ctx::{WasiCtx, WasiCtxBuilder, WasiView};
 pub use self::error::{I32Exit, TrappableError};
 pub use self::filesystem::{DirPerms, FilePerms, FsError, FsResult};
-pub use self::network::{Network, SocketError, SocketResult};
+pub use self::network::{Network, SocketAddrUse, SocketError, SocketResult};
 pub use self::poll::{subscribe, ClosureFuture, MakeFuture, Pollable, PollableFuture, Subscribe};
 pub use self::random::{thread_rng, Deterministic};
 pub use self::stdio::{
@@ -178,6 +180,26 @@ pub mod bindings {
     });
 
     pub use wasi::*;
+
+    /// Bindings for `wasi:tls`, which isn't part of the `wasi:cli/imports`
+    /// world.
+    pub mod tls {
+        wasmtime::component::bindgen!({
+            path: "wit",
+            world: "wasi:tls/imports",
+            tracing: true,
+            with: {
+                "wasi:io/error": crate::preview2::bindings::io::error,
+                "wasi:io/poll": crate::preview2::bindings::io::poll,
+                "wasi:io/streams": crate::preview2::bindings::io::streams,
+                "wasi:tls/types/client-handshake": crate::preview2::tls::ClientHandshake,
+                "wasi:tls/types/client-connection": crate::preview2::tls::ClientConnection,
+                "wasi:tls/types/future-client-streams": crate::preview2::tls::FutureClientStreams,
+            },
+        });
+
+        pub use self::wasi::tls::types;
+    }
 }
 
 pub(crate) static RUNTIME: once_cell::sync::Lazy<tokio::runtime::Runtime> =
@@ -1,6 +1,7 @@
 use super::network::SocketAddressFamily;
 use super::{HostInputStream, HostOutputStream, StreamError};
 use crate::preview2::host::network::util;
+use crate::preview2::virtual_network::{NetworkProvider, VirtualTcpSocket};
 use crate::preview2::{
     with_ambient_tokio_runtime, AbortOnDropJoinHandle, InputStream, OutputStream, Subscribe,
 };
@@ -47,14 +48,22 @@ pub(crate) enum TcpState {
     Connected,
 }
 
-/// A host TCP socket, plus associated bookkeeping.
-///
-/// The inner state is wrapped in an Arc because the same underlying socket is
-/// used for implementing the stream types.
+/// The socket which a `TcpSocket` is backed by.
+pub(crate) enum TcpBackend {
+    /// A host socket.
+    ///
+    /// This is wrapped in an Arc because the same underlying socket is used
+    /// for implementing the stream types.
+    Host(Arc<tokio::net::TcpStream>),
+
+    /// A socket of a virtual network.
+    Virtual(Box<VirtualTcpSocket>),
+}
+
+/// A host or virtual TCP socket, plus associated bookkeeping.
 pub struct TcpSocket {
-    /// The part of a `TcpSocket` which is reference-counted so that we
-    /// can pass it to async tasks.
-    pub(crate) inner: Arc<tokio::net::TcpStream>,
+    /// The underlying host or virtual socket.
+    pub(crate) backend: TcpBackend,
 
     /// The current state in the bind/listen/accept/connect progression.
     pub(crate) tcp_state: TcpState,
@@ -281,9 +290,27 @@ impl TcpSocket {
         family: SocketAddressFamily,
     ) -> io::Result<Self> {
         let stream = Self::setup_tokio_tcp_stream(fd)?;
+        Ok(Self::from_backend(
+            TcpBackend::Host(Arc::new(stream)),
+            family,
+        ))
+    }
 
-        Ok(Self {
-            inner: Arc::new(stream),
+    /// Create a new socket in the given family on a virtual network.
+    pub(crate) fn new_virtual(provider: Arc<dyn NetworkProvider>, family: AddressFamily) -> Self {
+        let family = match family {
+            AddressFamily::Ipv4 => SocketAddressFamily::Ipv4,
+            AddressFamily::Ipv6 => SocketAddressFamily::Ipv6 { v6only: false },
+        };
+        Self::from_backend(
+            TcpBackend::Virtual(Box::new(VirtualTcpSocket::new(provider))),
+            family,
+        )
+    }
+
+    pub(crate) fn from_backend(backend: TcpBackend, family: SocketAddressFamily) -> Self {
+        Self {
+            backend,
             tcp_state: TcpState::Default,
             listen_backlog_size: None,
             family,
@@ -295,7 +322,7 @@ impl TcpSocket {
             hop_limit: None,
             #[cfg(target_os = "macos")]
             keep_alive_idle_time: None,
-        })
+        }
     }
 
     fn setup_tokio_tcp_stream(fd: rustix::fd::OwnedFd) -> io::Result<tokio::net::TcpStream> {
@@ -304,15 +331,47 @@ impl TcpSocket {
         with_ambient_tokio_runtime(|| tokio::net::TcpStream::try_from(std_stream))
     }
 
+    /// Returns the host socket backing this socket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this socket belongs to a virtual network.
     pub fn tcp_socket(&self) -> &tokio::net::TcpStream {
-        &self.inner
+        match &self.backend {
+            TcpBackend::Host(stream) => stream,
+            TcpBackend::Virtual(_) => panic!("virtual sockets are not backed by a host socket"),
+        }
+    }
+
+    pub(crate) fn as_virtual(&self) -> Option<&VirtualTcpSocket> {
+        match &self.backend {
+            TcpBackend::Host(_) => None,
+            TcpBackend::Virtual(socket) => Some(socket),
+        }
+    }
+
+    pub(crate) fn as_virtual_mut(&mut self) -> Option<&mut VirtualTcpSocket> {
+        match &mut self.backend {
+            TcpBackend::Host(_) => None,
+            TcpBackend::Virtual(socket) => Some(socket),
+        }
     }
 
-    /// Create the input/output stream pair for a tcp socket.
+    /// Create the input/output stream pair for a connected tcp socket.
     pub fn as_split(&self) -> (InputStream, OutputStream) {
-        let input = Box::new(TcpReadStream::new(self.inner.clone()));
-        let output = Box::new(TcpWriteStream::new(self.inner.clone()));
-        (InputStream::Host(input), output)
+        match &self.backend {
+            TcpBackend::Host(stream) => {
+                let input = Box::new(TcpReadStream::new(stream.clone()));
+                let output = Box::new(TcpWriteStream::new(stream.clone()));
+                (InputStream::Host(input), output)
+            }
+            TcpBackend::Virtual(socket) => {
+                let stream = socket.stream().expect("virtual socket is not connected");
+                let input = Box::new(stream.read_stream());
+                let output = Box::new(stream.write_stream());
+                (InputStream::Host(input), output)
+            }
+        }
     }
 }
 
@@ -325,10 +384,15 @@ impl Subscribe for TcpSocket {
             _ => {}
         }
 
-        // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
-        self.inner
-            .ready(Interest::READABLE | Interest::WRITABLE)
-            .await
-            .unwrap();
+        match &mut self.backend {
+            // FIXME: Add `Interest::ERROR` when we update to tokio 1.32.
+            TcpBackend::Host(stream) => {
+                stream
+                    .ready(Interest::READABLE | Interest::WRITABLE)
+                    .await
+                    .unwrap();
+            }
+            TcpBackend::Virtual(socket) => socket.ready().await,
+        }
     }
 }
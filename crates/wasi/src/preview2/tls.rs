@@ -0,0 +1,612 @@
+//! Host implementation of `wasi:tls`.
+//!
+//! Guests hand the input and output streams of a connection, usually those of
+//! a connected `tcp-socket`, to a `client-handshake` and get back plaintext
+//! streams once the handshake has completed. The TLS session itself is run on
+//! the host with [`rustls`], configured through
+//! [`WasiCtxBuilder::tls_client_config`].
+//!
+//! [`WasiCtxBuilder::tls_client_config`]: crate::preview2::WasiCtxBuilder::tls_client_config
+
+use crate::preview2::bindings::io::error::Error;
+use crate::preview2::bindings::tls::types::{
+    Host, HostClientConnection, HostClientHandshake, HostFutureClientStreams,
+};
+use crate::preview2::pipe::{AsyncReadStream, AsyncWriteStream};
+use crate::preview2::poll::{subscribe, Pollable, Subscribe};
+use crate::preview2::{
+    AbortOnDropJoinHandle, HostInputStream, HostOutputStream, InputStream, OutputStream,
+    StreamError, WasiView,
+};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use wasmtime::component::Resource;
+
+pub use rustls;
+
+/// The number of plaintext bytes buffered by the output stream of a
+/// connection.
+const WRITE_BUDGET: usize = 16 * 1024;
+
+/// The client configuration used when none was configured: the Mozilla root
+/// certificates and no client authentication.
+pub(crate) static DEFAULT_CLIENT_CONFIG: once_cell::sync::Lazy<Arc<rustls::ClientConfig>> =
+    once_cell::sync::Lazy::new(|| {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Arc::new(config)
+    });
+
+type TlsStream = tokio_rustls::client::TlsStream<HostStreamIo>;
+
+pub struct ClientHandshake {
+    server_name: String,
+    alpn_protocols: Vec<String>,
+    io: HostStreamIo,
+}
+
+pub struct ClientConnection {
+    alpn_protocol: Option<String>,
+}
+
+pub enum FutureClientStreams {
+    Pending(AbortOnDropJoinHandle<io::Result<TlsStream>>),
+    Ready(io::Result<TlsStream>),
+    Consumed,
+}
+
+#[async_trait::async_trait]
+impl Subscribe for FutureClientStreams {
+    async fn ready(&mut self) {
+        if let FutureClientStreams::Pending(handle) = self {
+            *self = FutureClientStreams::Ready(handle.await);
+        }
+    }
+}
+
+impl<T: WasiView> Host for T {}
+
+impl<T: WasiView> HostClientHandshake for T {
+    fn new(
+        &mut self,
+        server_name: String,
+        input: Resource<InputStream>,
+        output: Resource<OutputStream>,
+    ) -> Result<Resource<ClientHandshake>> {
+        let table = self.table_mut();
+        let input = match table.delete(input)? {
+            InputStream::Host(stream) => stream,
+            InputStream::File(_) => {
+                return Err(anyhow!("file streams can't be used for TLS connections"))
+            }
+        };
+        let output = table.delete(output)?;
+        let handshake = ClientHandshake {
+            server_name,
+            alpn_protocols: Vec::new(),
+            io: HostStreamIo::new(input, output),
+        };
+        Ok(table.push(handshake)?)
+    }
+
+    fn set_alpn_protocols(
+        &mut self,
+        this: Resource<ClientHandshake>,
+        protocols: Vec<String>,
+    ) -> Result<()> {
+        self.table_mut().get_mut(&this)?.alpn_protocols = protocols;
+        Ok(())
+    }
+
+    fn finish(&mut self, this: Resource<ClientHandshake>) -> Result<Resource<FutureClientStreams>> {
+        let handshake = self.table_mut().delete(this)?;
+
+        let config = self
+            .ctx()
+            .tls_client_config
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CLIENT_CONFIG.clone());
+        let config = if handshake.alpn_protocols.is_empty() {
+            config
+        } else {
+            let mut config = (*config).clone();
+            config.alpn_protocols = handshake
+                .alpn_protocols
+                .into_iter()
+                .map(String::into_bytes)
+                .collect();
+            Arc::new(config)
+        };
+
+        let server_name = handshake.server_name;
+        let io = handshake.io;
+        let task = crate::preview2::spawn(async move {
+            let server_name = rustls::ServerName::try_from(server_name.as_str())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            tokio_rustls::TlsConnector::from(config)
+                .connect(server_name, io)
+                .await
+        });
+        Ok(self.table_mut().push(FutureClientStreams::Pending(task))?)
+    }
+
+    fn drop(&mut self, this: Resource<ClientHandshake>) -> Result<()> {
+        self.table_mut().delete(this)?;
+        Ok(())
+    }
+}
+
+impl<T: WasiView> HostClientConnection for T {
+    fn alpn_protocol(&mut self, this: Resource<ClientConnection>) -> Result<Option<String>> {
+        Ok(self.table().get(&this)?.alpn_protocol.clone())
+    }
+
+    fn drop(&mut self, this: Resource<ClientConnection>) -> Result<()> {
+        self.table_mut().delete(this)?;
+        Ok(())
+    }
+}
+
+impl<T: WasiView> HostFutureClientStreams for T {
+    fn subscribe(&mut self, this: Resource<FutureClientStreams>) -> Result<Resource<Pollable>> {
+        subscribe(self.table_mut(), this)
+    }
+
+    fn get(
+        &mut self,
+        this: Resource<FutureClientStreams>,
+    ) -> Result<
+        Option<
+            Result<
+                Result<
+                    (
+                        Resource<ClientConnection>,
+                        Resource<InputStream>,
+                        Resource<OutputStream>,
+                    ),
+                    Resource<Error>,
+                >,
+                (),
+            >,
+        >,
+    > {
+        let future = self.table_mut().get_mut(&this)?;
+        if let FutureClientStreams::Pending(handle) = future {
+            match crate::preview2::poll_noop(Pin::new(handle)) {
+                Some(result) => *future = FutureClientStreams::Ready(result),
+                None => return Ok(None),
+            }
+        }
+
+        let stream = match std::mem::replace(future, FutureClientStreams::Consumed) {
+            FutureClientStreams::Ready(Ok(stream)) => stream,
+            FutureClientStreams::Ready(Err(e)) => {
+                let error = self.table_mut().push(Error::from(e))?;
+                return Ok(Some(Ok(Err(error))));
+            }
+            FutureClientStreams::Consumed => return Ok(Some(Err(()))),
+            FutureClientStreams::Pending(_) => unreachable!(),
+        };
+
+        let alpn_protocol = stream
+            .get_ref()
+            .1
+            .alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).into_owned());
+        let (reader, writer) = tokio::io::split(stream);
+        let input = InputStream::Host(Box::new(AsyncReadStream::new(reader)));
+        let output: OutputStream = Box::new(AsyncWriteStream::new(WRITE_BUDGET, writer));
+
+        let table = self.table_mut();
+        let connection = table.push(ClientConnection { alpn_protocol })?;
+        let input = table.push_child(input, &connection)?;
+        let output = table.push_child(output, &connection)?;
+        Ok(Some(Ok(Ok((connection, input, output)))))
+    }
+
+    fn drop(&mut self, this: Resource<FutureClientStreams>) -> Result<()> {
+        self.table_mut().delete(this)?;
+        Ok(())
+    }
+}
+
+type ReadyFuture<S> = Pin<Box<dyn Future<Output = S> + Send>>;
+
+/// A stream which is either available or being waited on for readiness.
+///
+/// The future is only ever accessed through `&mut`, the mutex just makes this
+/// type `Sync` so that it can be stored in the table.
+enum Readiness<S> {
+    Idle(S),
+    Waiting(Mutex<ReadyFuture<S>>),
+}
+
+impl<S: Subscribe> Readiness<S> {
+    /// Polls until the stream is available again, returning it.
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<&mut S> {
+        if let Readiness::Waiting(future) = self {
+            let stream = ready!(future.get_mut().unwrap().as_mut().poll(cx));
+            *self = Readiness::Idle(stream);
+        }
+        match self {
+            Readiness::Idle(stream) => Poll::Ready(stream),
+            Readiness::Waiting(_) => unreachable!(),
+        }
+    }
+
+    /// Starts waiting for the stream to become ready.
+    fn wait(&mut self) {
+        // Temporarily park a dummy future to move the stream out of `self`.
+        let dummy: Readiness<S> = Readiness::Waiting(Mutex::new(Box::pin(std::future::pending())));
+        let stream = match std::mem::replace(self, dummy) {
+            Readiness::Idle(stream) => stream,
+            Readiness::Waiting(_) => unreachable!(),
+        };
+        *self = Readiness::Waiting(Mutex::new(Box::pin(async move {
+            let mut stream = stream;
+            stream.ready().await;
+            stream
+        })));
+    }
+}
+
+/// Adapts a pair of host streams to Tokio's I/O traits so the TLS session
+/// can run on top of them.
+struct HostStreamIo {
+    input: Readiness<Box<dyn HostInputStream>>,
+    output: Readiness<Box<dyn HostOutputStream>>,
+    flushing: bool,
+}
+
+impl HostStreamIo {
+    fn new(input: Box<dyn HostInputStream>, output: Box<dyn HostOutputStream>) -> HostStreamIo {
+        HostStreamIo {
+            input: Readiness::Idle(input),
+            output: Readiness::Idle(output),
+            flushing: false,
+        }
+    }
+}
+
+fn to_io_error(e: StreamError) -> io::Error {
+    match e {
+        StreamError::Closed => io::ErrorKind::BrokenPipe.into(),
+        StreamError::LastOperationFailed(e) | StreamError::Trap(e) => {
+            io::Error::new(io::ErrorKind::Other, e)
+        }
+    }
+}
+
+impl AsyncRead for HostStreamIo {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let stream = ready!(self.input.poll_idle(cx));
+            match stream.read(buf.remaining()) {
+                Ok(bytes) if bytes.is_empty() => self.input.wait(),
+                Ok(bytes) => {
+                    buf.put_slice(&bytes);
+                    return Poll::Ready(Ok(()));
+                }
+                // Reading nothing signals the end of the stream.
+                Err(StreamError::Closed) => return Poll::Ready(Ok(())),
+                Err(e) => return Poll::Ready(Err(to_io_error(e))),
+            }
+        }
+    }
+}
+
+impl AsyncWrite for HostStreamIo {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        loop {
+            let stream = ready!(self.output.poll_idle(cx));
+            match stream.check_write() {
+                Ok(0) => self.output.wait(),
+                Ok(permit) => {
+                    let n = permit.min(buf.len());
+                    stream
+                        .write(Bytes::copy_from_slice(&buf[..n]))
+                        .map_err(to_io_error)?;
+                    return Poll::Ready(Ok(n));
+                }
+                Err(e) => return Poll::Ready(Err(to_io_error(e))),
+            }
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if !this.flushing {
+            let stream = ready!(this.output.poll_idle(cx));
+            stream.flush().map_err(to_io_error)?;
+            this.flushing = true;
+            this.output.wait();
+        }
+        // The flush has completed once the stream is ready again.
+        let stream = ready!(this.output.poll_idle(cx));
+        this.flushing = false;
+        match stream.check_write() {
+            Ok(_) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(to_io_error(e))),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::preview2::{WasiCtx, WasiCtxBuilder};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use wasmtime::component::ResourceTable;
+
+    fn host_io(stream: tokio::io::DuplexStream) -> HostStreamIo {
+        let (reader, writer) = tokio::io::split(stream);
+        HostStreamIo::new(
+            Box::new(AsyncReadStream::new(reader)),
+            Box::new(AsyncWriteStream::new(1024, writer)),
+        )
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn host_stream_io_round_trip() {
+        let (a, mut b) = tokio::io::duplex(64);
+        let mut io = host_io(a);
+
+        // Larger than both the write budget and the duplex buffer.
+        let data = vec![42u8; 4096];
+        let writer = tokio::spawn(async move {
+            io.write_all(&data).await.unwrap();
+            io.flush().await.unwrap();
+            io
+        });
+        let mut received = vec![0; 4096];
+        b.read_exact(&mut received).await.unwrap();
+        assert!(received.iter().all(|b| *b == 42));
+        let mut io = writer.await.unwrap();
+
+        b.write_all(b"reply").await.unwrap();
+        drop(b);
+        let mut reply = Vec::new();
+        io.read_to_end(&mut reply).await.unwrap();
+        assert_eq!(reply, b"reply");
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn handshake_with_non_tls_peer_fails() {
+        let (a, mut b) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            // Wait for the ClientHello and reply with something which isn't
+            // TLS at all.
+            let mut buf = [0; 5];
+            b.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf[0], 0x16, "expected a TLS handshake record");
+            b.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n")
+                .await
+                .unwrap();
+            b
+        });
+
+        let server_name = rustls::ServerName::try_from("example.com").unwrap();
+        let result = tokio_rustls::TlsConnector::from(DEFAULT_CLIENT_CONFIG.clone())
+            .connect(server_name, host_io(a))
+            .await;
+        assert!(result.is_err());
+        drop(server.await.unwrap());
+    }
+
+    const CA_CERT: &[u8] = include_bytes!("../../tests/all/tls/ca.crt.der");
+    const SERVER_CERT: &[u8] = include_bytes!("../../tests/all/tls/server.crt.der");
+    const SERVER_KEY: &[u8] = include_bytes!("../../tests/all/tls/server.key.der");
+    const CLIENT_CERT: &[u8] = include_bytes!("../../tests/all/tls/client.crt.der");
+    const CLIENT_KEY: &[u8] = include_bytes!("../../tests/all/tls/client.key.der");
+
+    struct View {
+        table: ResourceTable,
+        ctx: WasiCtx,
+    }
+
+    impl WasiView for View {
+        fn table(&self) -> &ResourceTable {
+            &self.table
+        }
+        fn table_mut(&mut self) -> &mut ResourceTable {
+            &mut self.table
+        }
+        fn ctx(&self) -> &WasiCtx {
+            &self.ctx
+        }
+        fn ctx_mut(&mut self) -> &mut WasiCtx {
+            &mut self.ctx
+        }
+    }
+
+    type Streams = (
+        Resource<ClientConnection>,
+        Resource<InputStream>,
+        Resource<OutputStream>,
+    );
+
+    fn roots() -> rustls::RootCertStore {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(CA_CERT.to_vec())).unwrap();
+        roots
+    }
+
+    fn trusting_client(
+    ) -> rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert> {
+        rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots())
+    }
+
+    /// Starts an echo server for `localhost` on one end of an in-memory
+    /// connection, returning the other end.
+    fn start_server(require_client_cert: bool) -> tokio::io::DuplexStream {
+        let builder = rustls::ServerConfig::builder().with_safe_defaults();
+        let builder = if require_client_cert {
+            builder.with_client_cert_verifier(
+                rustls::server::AllowAnyAuthenticatedClient::new(roots()).boxed(),
+            )
+        } else {
+            builder.with_no_client_auth()
+        };
+        let mut config = builder
+            .with_single_cert(
+                vec![rustls::Certificate(SERVER_CERT.to_vec())],
+                rustls::PrivateKey(SERVER_KEY.to_vec()),
+            )
+            .unwrap();
+        config.alpn_protocols = vec![b"echo".to_vec()];
+
+        let (client, server) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+            // Failed handshakes are reported to, and checked by, the client.
+            if let Ok(stream) = acceptor.accept(server).await {
+                let (mut reader, mut writer) = tokio::io::split(stream);
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            }
+        });
+        client
+    }
+
+    /// Runs a handshake for `localhost` through the `wasi:tls` host
+    /// implementation, as a guest would.
+    async fn handshake(
+        view: &mut View,
+        stream: tokio::io::DuplexStream,
+    ) -> Result<Result<Streams, Resource<Error>>> {
+        let (reader, writer) = tokio::io::split(stream);
+        let input = view
+            .table
+            .push(InputStream::Host(Box::new(AsyncReadStream::new(reader))))?;
+        let output: OutputStream = Box::new(AsyncWriteStream::new(1024, writer));
+        let output = view.table.push(output)?;
+
+        let handshake = HostClientHandshake::new(view, "localhost".to_string(), input, output)?;
+        view.set_alpn_protocols(
+            Resource::new_borrow(handshake.rep()),
+            vec!["echo".to_string()],
+        )?;
+        let future = view.finish(handshake)?;
+        view.table.get_mut(&future)?.ready().await;
+        let result = HostFutureClientStreams::get(view, Resource::new_borrow(future.rep()))?;
+        HostFutureClientStreams::drop(view, future)?;
+        Ok(result
+            .expect("handshake completed")
+            .expect("result not yet taken"))
+    }
+
+    /// Sends `data` over an established connection and reads the echo.
+    async fn echo(view: &mut View, streams: &Streams, data: &[u8]) -> Result<Vec<u8>> {
+        let output = view.table.get_mut(&streams.2)?;
+        output.write(Bytes::copy_from_slice(data))?;
+        output.flush()?;
+        output.ready().await;
+        output.check_write()?;
+
+        let input = match view.table.get_mut(&streams.1)? {
+            InputStream::Host(input) => input,
+            InputStream::File(_) => unreachable!(),
+        };
+        let mut received = Vec::new();
+        while received.len() < data.len() {
+            input.ready().await;
+            received.extend_from_slice(&input.read(data.len() - received.len())?);
+        }
+        Ok(received)
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn handshake_with_custom_roots() -> Result<()> {
+        let mut view = View {
+            table: ResourceTable::new(),
+            ctx: WasiCtxBuilder::new()
+                .tls_client_config(trusting_client().with_no_client_auth())
+                .build(),
+        };
+        let streams = handshake(&mut view, start_server(false)).await?.unwrap();
+        assert_eq!(
+            view.alpn_protocol(Resource::new_borrow(streams.0.rep()))?
+                .as_deref(),
+            Some("echo")
+        );
+        assert_eq!(echo(&mut view, &streams, b"hello").await?, b"hello");
+        Ok(())
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn handshake_with_untrusted_server_fails() -> Result<()> {
+        // The default configuration only trusts the Mozilla roots, which
+        // didn't issue the test certificates.
+        let mut view = View {
+            table: ResourceTable::new(),
+            ctx: WasiCtxBuilder::new().build(),
+        };
+        assert!(handshake(&mut view, start_server(false)).await?.is_err());
+        Ok(())
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn handshake_with_client_certificate() -> Result<()> {
+        let config = trusting_client().with_client_auth_cert(
+            vec![rustls::Certificate(CLIENT_CERT.to_vec())],
+            rustls::PrivateKey(CLIENT_KEY.to_vec()),
+        )?;
+        let mut view = View {
+            table: ResourceTable::new(),
+            ctx: WasiCtxBuilder::new().tls_client_config(config).build(),
+        };
+        let streams = handshake(&mut view, start_server(true)).await?.unwrap();
+        assert_eq!(echo(&mut view, &streams, b"hello").await?, b"hello");
+        Ok(())
+    }
+
+    #[test_log::test(tokio::test(flavor = "multi_thread"))]
+    async fn handshake_without_required_client_certificate_fails() -> Result<()> {
+        let mut view = View {
+            table: ResourceTable::new(),
+            ctx: WasiCtxBuilder::new()
+                .tls_client_config(trusting_client().with_no_client_auth())
+                .build(),
+        };
+        // With TLS 1.3 the client finishes its side of the handshake before
+        // the server checks its certificate, so the rejection may only show
+        // up once the connection is used.
+        match handshake(&mut view, start_server(true)).await? {
+            Ok(streams) => assert!(echo(&mut view, &streams, b"hello").await.is_err()),
+            Err(_) => {}
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::preview2::host::network::util;
 use crate::preview2::poll::Subscribe;
+use crate::preview2::virtual_network::{NetworkProvider, VirtualUdpBinding, VirtualUdpSocket};
 use crate::preview2::with_ambient_tokio_runtime;
 use async_trait::async_trait;
 use cap_net_ext::{AddressFamily, Blocking};
@@ -29,6 +30,21 @@ pub(crate) enum UdpState {
     Connected,
 }
 
+/// The socket backing a [`UdpSocket`].
+pub(crate) enum UdpBackend {
+    /// A host socket.
+    Host(Arc<tokio::net::UdpSocket>),
+    /// A socket on a virtual network.
+    Virtual(Box<VirtualUdpSocket>),
+}
+
+/// The transport used by the datagram streams of a [`UdpSocket`].
+#[derive(Clone)]
+pub(crate) enum UdpTransport {
+    Host(Arc<tokio::net::UdpSocket>),
+    Virtual(VirtualUdpBinding),
+}
+
 /// A host UDP socket, plus associated bookkeeping.
 ///
 /// The inner state is wrapped in an Arc because the same underlying socket is
@@ -36,7 +52,7 @@ pub(crate) enum UdpState {
 pub struct UdpSocket {
     /// The part of a `UdpSocket` which is reference-counted so that we
     /// can pass it to async tasks.
-    pub(crate) inner: Arc<tokio::net::UdpSocket>,
+    pub(crate) backend: UdpBackend,
 
     /// The current state in the bind/connect progression.
     pub(crate) udp_state: UdpState,
@@ -71,12 +87,31 @@ impl UdpSocket {
 
         let socket = Self::setup_tokio_udp_socket(fd)?;
 
-        Ok(UdpSocket {
-            inner: Arc::new(socket),
+        Ok(Self::from_backend(
+            UdpBackend::Host(Arc::new(socket)),
+            socket_address_family,
+        ))
+    }
+
+    /// Create a new socket in the given family on a virtual network.
+    pub(crate) fn new_virtual(provider: Arc<dyn NetworkProvider>, family: AddressFamily) -> Self {
+        let family = match family {
+            AddressFamily::Ipv4 => SocketAddressFamily::Ipv4,
+            AddressFamily::Ipv6 => SocketAddressFamily::Ipv6 { v6only: false },
+        };
+        Self::from_backend(
+            UdpBackend::Virtual(Box::new(VirtualUdpSocket::new(provider))),
+            family,
+        )
+    }
+
+    fn from_backend(backend: UdpBackend, family: SocketAddressFamily) -> Self {
+        UdpSocket {
+            backend,
             udp_state: UdpState::Default,
-            family: socket_address_family,
+            family,
             socket_addr_check: None,
-        })
+        }
     }
 
     fn setup_tokio_udp_socket(fd: rustix::fd::OwnedFd) -> io::Result<tokio::net::UdpSocket> {
@@ -85,20 +120,52 @@ impl UdpSocket {
         with_ambient_tokio_runtime(|| tokio::net::UdpSocket::try_from(std_socket))
     }
 
+    /// Returns the host socket backing this socket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this socket belongs to a virtual network.
     pub fn udp_socket(&self) -> &tokio::net::UdpSocket {
-        &self.inner
+        match &self.backend {
+            UdpBackend::Host(socket) => socket,
+            UdpBackend::Virtual(_) => panic!("virtual sockets are not backed by a host socket"),
+        }
+    }
+
+    pub(crate) fn as_virtual(&self) -> Option<&VirtualUdpSocket> {
+        match &self.backend {
+            UdpBackend::Host(_) => None,
+            UdpBackend::Virtual(socket) => Some(socket),
+        }
+    }
+
+    pub(crate) fn as_virtual_mut(&mut self) -> Option<&mut VirtualUdpSocket> {
+        match &mut self.backend {
+            UdpBackend::Host(_) => None,
+            UdpBackend::Virtual(socket) => Some(socket),
+        }
+    }
+
+    /// Returns the transport to be shared with this socket's datagram streams.
+    ///
+    /// Virtual sockets only have a transport once they're bound.
+    pub(crate) fn transport(&self) -> Option<UdpTransport> {
+        match &self.backend {
+            UdpBackend::Host(socket) => Some(UdpTransport::Host(socket.clone())),
+            UdpBackend::Virtual(socket) => socket.binding().cloned().map(UdpTransport::Virtual),
+        }
     }
 }
 
 pub struct IncomingDatagramStream {
-    pub(crate) inner: Arc<tokio::net::UdpSocket>,
+    pub(crate) inner: UdpTransport,
 
     /// If this has a value, the stream is "connected".
     pub(crate) remote_address: Option<SocketAddr>,
 }
 
 pub struct OutgoingDatagramStream {
-    pub(crate) inner: Arc<tokio::net::UdpSocket>,
+    pub(crate) inner: UdpTransport,
 
     /// If this has a value, the stream is "connected".
     pub(crate) remote_address: Option<SocketAddr>,
@@ -0,0 +1,1242 @@
+//! Virtualized networking for `wasi:sockets`.
+//!
+//! By default the TCP and UDP sockets created by guests are backed by real
+//! host sockets. A [`NetworkProvider`] configured with
+//! [`WasiCtxBuilder::network_provider`] replaces the host network entirely:
+//! all binds, connections and datagrams of sockets created within that
+//! context are routed through the provider and no host sockets are ever
+//! created.
+//!
+//! [`LoopbackNetwork`] is an in-memory provider which can be shared between
+//! stores so that guests can listen for and connect to each other, with
+//! optional latency and failure injection for testing.
+//!
+//! Note that the socket address check configured with
+//! [`WasiCtxBuilder::socket_addr_check`] still applies to virtual sockets.
+//!
+//! [`WasiCtxBuilder::network_provider`]: crate::preview2::WasiCtxBuilder::network_provider
+//! [`WasiCtxBuilder::socket_addr_check`]: crate::preview2::WasiCtxBuilder::socket_addr_check
+
+use crate::preview2::host::network::util;
+use crate::preview2::network::{SocketAddrUse, SocketAddressFamily};
+use crate::preview2::poll::Subscribe;
+use crate::preview2::{
+    poll_noop, with_ambient_tokio_runtime, AbortOnDropJoinHandle, HostInputStream,
+    HostOutputStream, StreamError,
+};
+use bytes::{Bytes, BytesMut};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// The number of bytes buffered in each direction of a virtual TCP
+/// connection.
+const CONNECTION_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The first port handed out when binding to port 0.
+const EPHEMERAL_PORT_START: u16 = 49152;
+
+/// The transport protocol of a virtual socket.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// A provider of virtual networking for `wasi:sockets`.
+///
+/// Providers decide how addresses are bound and how traffic is routed while
+/// the data itself is exchanged through the [`VirtualTcpListener`],
+/// [`VirtualTcpStream`] and [`VirtualUdpReceiver`] types of this module.
+///
+/// All addresses passed to a provider have already been validated by the
+/// `wasi:sockets` implementation, for example remote addresses are never
+/// unspecified and always match the address family of the socket.
+pub trait NetworkProvider: Send + Sync + 'static {
+    /// Binds `addr` for `protocol`, returning the address actually bound.
+    ///
+    /// A port of 0 requests that the provider pick an unused port. When
+    /// `dual_stack` is set, an IPv6 binding should also receive IPv4 traffic.
+    fn bind(
+        &self,
+        protocol: Protocol,
+        addr: SocketAddr,
+        dual_stack: bool,
+    ) -> io::Result<SocketAddr>;
+
+    /// Releases an address previously returned by [`NetworkProvider::bind`].
+    fn unbind(&self, protocol: Protocol, addr: SocketAddr);
+
+    /// Returns the address which traffic from the bound address `local` to
+    /// `remote` originates from.
+    ///
+    /// This is used to resolve the local address of sockets bound to an
+    /// unspecified address once they're connected.
+    fn source_address(&self, local: SocketAddr, remote: SocketAddr) -> SocketAddr;
+
+    /// Starts accepting TCP connections on the bound address `addr`.
+    fn tcp_listen(&self, addr: SocketAddr, backlog: usize) -> io::Result<VirtualTcpListener>;
+
+    /// Establishes a TCP connection from the bound address `local` to
+    /// `remote`.
+    fn tcp_connect(
+        &self,
+        local: SocketAddr,
+        remote: SocketAddr,
+    ) -> Pin<Box<dyn Future<Output = io::Result<VirtualTcpStream>> + Send>>;
+
+    /// Starts receiving datagrams sent to the bound address `addr`.
+    fn udp_receiver(&self, addr: SocketAddr) -> io::Result<VirtualUdpReceiver>;
+
+    /// Sends a datagram from the bound address `local` to `remote`.
+    ///
+    /// Like real UDP, datagrams which can't be delivered may be silently
+    /// dropped.
+    fn udp_send(&self, local: SocketAddr, remote: SocketAddr, data: Vec<u8>) -> io::Result<()>;
+}
+
+/// A bounded queue with a single consumer which can wait for items.
+struct Queue<T> {
+    items: Mutex<VecDeque<T>>,
+    capacity: usize,
+    notify: Notify,
+}
+
+impl<T> Queue<T> {
+    fn new(capacity: usize) -> Queue<T> {
+        Queue {
+            items: Mutex::new(VecDeque::new()),
+            capacity,
+            notify: Notify::new(),
+        }
+    }
+
+    fn push(&self, item: T) -> Result<(), T> {
+        let mut items = self.items.lock().unwrap();
+        if items.len() >= self.capacity {
+            return Err(item);
+        }
+        items.push_back(item);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.items.lock().unwrap().pop_front()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.lock().unwrap().is_empty()
+    }
+
+    async fn ready(&self) {
+        while self.is_empty() {
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// A TCP listener of a [`NetworkProvider`].
+///
+/// Providers create a listener in [`NetworkProvider::tcp_listen`] and keep a
+/// clone of it to [`enqueue`](VirtualTcpListener::enqueue) incoming
+/// connections.
+#[derive(Clone)]
+pub struct VirtualTcpListener {
+    queue: Arc<Queue<VirtualTcpStream>>,
+}
+
+impl VirtualTcpListener {
+    /// Creates a new listener which holds up to `backlog` connections that
+    /// haven't been accepted yet.
+    pub fn new(backlog: usize) -> VirtualTcpListener {
+        VirtualTcpListener {
+            queue: Arc::new(Queue::new(backlog.max(1))),
+        }
+    }
+
+    /// Adds an incoming connection to this listener, returning it back if
+    /// the backlog is full.
+    pub fn enqueue(&self, stream: VirtualTcpStream) -> Result<(), VirtualTcpStream> {
+        self.queue.push(stream)
+    }
+
+    pub(crate) fn accept(&self) -> Option<VirtualTcpStream> {
+        self.queue.pop()
+    }
+
+    pub(crate) async fn ready(&self) {
+        self.queue.ready().await
+    }
+}
+
+/// A queue of datagrams received by a virtual UDP socket.
+///
+/// Providers create a receiver in [`NetworkProvider::udp_receiver`] and keep
+/// a clone of it to [`deliver`](VirtualUdpReceiver::deliver) datagrams.
+#[derive(Clone)]
+pub struct VirtualUdpReceiver {
+    queue: Arc<Queue<(Vec<u8>, SocketAddr)>>,
+}
+
+impl VirtualUdpReceiver {
+    /// Creates a new receiver which buffers up to `capacity` datagrams.
+    pub fn new(capacity: usize) -> VirtualUdpReceiver {
+        VirtualUdpReceiver {
+            queue: Arc::new(Queue::new(capacity)),
+        }
+    }
+
+    /// Delivers a datagram sent from `remote_address`.
+    ///
+    /// Datagrams are dropped if the receive buffer is full.
+    pub fn deliver(&self, data: Vec<u8>, remote_address: SocketAddr) {
+        let _ = self.queue.push((data, remote_address));
+    }
+
+    pub(crate) fn receive(&self) -> Option<(Vec<u8>, SocketAddr)> {
+        self.queue.pop()
+    }
+
+    pub(crate) async fn ready(&self) {
+        self.queue.ready().await
+    }
+}
+
+/// One direction of a virtual TCP connection.
+struct Pipe {
+    state: Mutex<PipeState>,
+    readable: Notify,
+    writable: Notify,
+}
+
+struct PipeState {
+    buffer: BytesMut,
+    /// Data which has been written but is delayed by the connection's
+    /// latency, along with when it becomes readable, oldest first.
+    in_flight: VecDeque<(Instant, Bytes)>,
+    in_flight_len: usize,
+    latency: Duration,
+    read_closed: bool,
+    write_closed: bool,
+}
+
+impl PipeState {
+    /// Moves the data whose latency has elapsed into the readable buffer.
+    fn arrive(&mut self) {
+        let now = Instant::now();
+        while let Some((at, _)) = self.in_flight.front() {
+            if *at > now {
+                break;
+            }
+            let (_, data) = self.in_flight.pop_front().unwrap();
+            self.in_flight_len -= data.len();
+            self.buffer.extend_from_slice(&data);
+        }
+    }
+
+    /// Returns whether all data written has been read and no more will be.
+    fn drained(&self) -> bool {
+        self.write_closed && self.buffer.is_empty() && self.in_flight.is_empty()
+    }
+}
+
+impl Pipe {
+    fn new() -> Arc<Pipe> {
+        Arc::new(Pipe {
+            state: Mutex::new(PipeState {
+                buffer: BytesMut::new(),
+                in_flight: VecDeque::new(),
+                in_flight_len: 0,
+                latency: Duration::ZERO,
+                read_closed: false,
+                write_closed: false,
+            }),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        })
+    }
+
+    fn close_read(&self) {
+        self.state.lock().unwrap().read_closed = true;
+        self.writable.notify_one();
+        self.readable.notify_one();
+    }
+
+    fn close_write(&self) {
+        self.state.lock().unwrap().write_closed = true;
+        self.readable.notify_one();
+        self.writable.notify_one();
+    }
+}
+
+/// An established virtual TCP connection.
+///
+/// Connections are created in pairs with [`VirtualTcpStream::pair`]. Both
+/// directions of the connection are closed when a stream is dropped.
+pub struct VirtualTcpStream {
+    local_address: SocketAddr,
+    remote_address: SocketAddr,
+    inbound: Arc<Pipe>,
+    outbound: Arc<Pipe>,
+    faults: Option<Arc<StreamFaultInjector>>,
+}
+
+impl VirtualTcpStream {
+    /// Creates both ends of a connection between the addresses `a` and `b`.
+    ///
+    /// The first stream returned has the local address `a` and the second
+    /// stream has the local address `b`.
+    pub fn pair(a: SocketAddr, b: SocketAddr) -> (VirtualTcpStream, VirtualTcpStream) {
+        let a_to_b = Pipe::new();
+        let b_to_a = Pipe::new();
+        (
+            VirtualTcpStream {
+                local_address: a,
+                remote_address: b,
+                inbound: b_to_a.clone(),
+                outbound: a_to_b.clone(),
+                faults: None,
+            },
+            VirtualTcpStream {
+                local_address: b,
+                remote_address: a,
+                inbound: a_to_b,
+                outbound: b_to_a,
+                faults: None,
+            },
+        )
+    }
+
+    /// Returns the local address of this end of the connection.
+    pub fn local_address(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// Returns the address of the other end of the connection.
+    pub fn remote_address(&self) -> SocketAddr {
+        self.remote_address
+    }
+
+    pub(crate) fn shutdown(&self, how: std::net::Shutdown) {
+        if let std::net::Shutdown::Read | std::net::Shutdown::Both = how {
+            self.inbound.close_read();
+        }
+        if let std::net::Shutdown::Write | std::net::Shutdown::Both = how {
+            self.outbound.close_write();
+        }
+    }
+
+    pub(crate) fn read_stream(&self) -> VirtualReadStream {
+        VirtualReadStream {
+            pipe: self.inbound.clone(),
+            remote_address: self.remote_address,
+            faults: self.faults.clone(),
+        }
+    }
+
+    pub(crate) fn write_stream(&self) -> VirtualWriteStream {
+        VirtualWriteStream {
+            pipe: self.outbound.clone(),
+            remote_address: self.remote_address,
+            faults: self.faults.clone(),
+        }
+    }
+
+    /// Fails an operation on this connection if the fault injector it was
+    /// created with says so.
+    fn check_faults(
+        faults: &Option<Arc<StreamFaultInjector>>,
+        remote_address: &SocketAddr,
+        op: StreamOperation,
+    ) -> Result<(), StreamError> {
+        match faults {
+            Some(faults) => {
+                faults(remote_address, op).map_err(|e| StreamError::LastOperationFailed(e.into()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for VirtualTcpStream {
+    fn drop(&mut self) {
+        self.shutdown(std::net::Shutdown::Both);
+    }
+}
+
+pub(crate) struct VirtualReadStream {
+    pipe: Arc<Pipe>,
+    remote_address: SocketAddr,
+    faults: Option<Arc<StreamFaultInjector>>,
+}
+
+#[async_trait::async_trait]
+impl HostInputStream for VirtualReadStream {
+    fn read(&mut self, size: usize) -> Result<Bytes, StreamError> {
+        VirtualTcpStream::check_faults(&self.faults, &self.remote_address, StreamOperation::Read)?;
+        let mut state = self.pipe.state.lock().unwrap();
+        if state.read_closed || state.drained() {
+            return Err(StreamError::Closed);
+        }
+        state.arrive();
+        if state.buffer.is_empty() {
+            return Ok(Bytes::new());
+        }
+        let n = size.min(state.buffer.len());
+        let data = state.buffer.split_to(n).freeze();
+        self.pipe.writable.notify_one();
+        Ok(data)
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for VirtualReadStream {
+    async fn ready(&mut self) {
+        loop {
+            let next_arrival = {
+                let mut state = self.pipe.state.lock().unwrap();
+                state.arrive();
+                if !state.buffer.is_empty() || state.read_closed || state.drained() {
+                    return;
+                }
+                state.in_flight.front().map(|(at, _)| *at)
+            };
+            match next_arrival {
+                Some(at) => {
+                    let _ = tokio::time::timeout_at(at, self.pipe.readable.notified()).await;
+                }
+                None => self.pipe.readable.notified().await,
+            }
+        }
+    }
+}
+
+pub(crate) struct VirtualWriteStream {
+    pipe: Arc<Pipe>,
+    remote_address: SocketAddr,
+    faults: Option<Arc<StreamFaultInjector>>,
+}
+
+impl VirtualWriteStream {
+    fn permitted(state: &PipeState) -> Result<usize, StreamError> {
+        if state.write_closed {
+            return Err(StreamError::Closed);
+        }
+        if state.read_closed {
+            return Err(StreamError::LastOperationFailed(
+                io::Error::from(io::ErrorKind::BrokenPipe).into(),
+            ));
+        }
+        // Data in flight still takes up space in the connection's buffer.
+        Ok(CONNECTION_BUFFER_SIZE.saturating_sub(state.buffer.len() + state.in_flight_len))
+    }
+}
+
+impl HostOutputStream for VirtualWriteStream {
+    fn write(&mut self, bytes: Bytes) -> Result<(), StreamError> {
+        VirtualTcpStream::check_faults(&self.faults, &self.remote_address, StreamOperation::Write)?;
+        let mut state = self.pipe.state.lock().unwrap();
+        if bytes.len() > Self::permitted(&state)? {
+            return Err(StreamError::Trap(anyhow::anyhow!(
+                "unpermitted: must call check_write first"
+            )));
+        }
+        if state.latency.is_zero() {
+            state.buffer.extend_from_slice(&bytes);
+        } else {
+            let at = Instant::now() + state.latency;
+            state.in_flight_len += bytes.len();
+            state.in_flight.push_back((at, bytes));
+        }
+        self.pipe.readable.notify_one();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), StreamError> {
+        // Data is on its way to the reader as soon as it's written, so there's
+        // nothing to flush.
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> Result<usize, StreamError> {
+        Self::permitted(&self.pipe.state.lock().unwrap())
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for VirtualWriteStream {
+    async fn ready(&mut self) {
+        loop {
+            {
+                let state = self.pipe.state.lock().unwrap();
+                if !matches!(Self::permitted(&state), Ok(0)) {
+                    return;
+                }
+            }
+            self.pipe.writable.notified().await;
+        }
+    }
+}
+
+type FaultInjector = dyn Fn(&SocketAddr, SocketAddrUse) -> io::Result<()> + Send + Sync;
+type StreamFaultInjector = dyn Fn(&SocketAddr, StreamOperation) -> io::Result<()> + Send + Sync;
+
+/// An operation on an established TCP connection, as passed to the closure
+/// configured with [`LoopbackNetwork::with_stream_faults`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamOperation {
+    /// Reading data received from the other end of the connection.
+    Read,
+    /// Writing data to send to the other end of the connection.
+    Write,
+}
+
+/// An in-memory [`NetworkProvider`] in which all addresses are local.
+///
+/// Clones of a `LoopbackNetwork` share the same network, so giving each
+/// store's [`WasiCtx`](crate::preview2::WasiCtx) a clone allows guests in
+/// different stores to communicate with each other while never reaching the
+/// host network.
+///
+/// Any address may be bound and connections and datagrams are delivered to
+/// the socket bound to the destination address, or to a socket bound to the
+/// unspecified address of the same port.
+#[derive(Clone, Default)]
+pub struct LoopbackNetwork {
+    state: Arc<Mutex<LoopbackState>>,
+    latency: Duration,
+    faults: Option<Arc<FaultInjector>>,
+    stream_faults: Option<Arc<StreamFaultInjector>>,
+}
+
+#[derive(Default)]
+struct LoopbackState {
+    bindings: HashMap<(Protocol, u16), Vec<Binding>>,
+    next_port: u16,
+}
+
+impl LoopbackState {
+    /// Finds a port which isn't bound at all for `protocol`.
+    fn ephemeral_port(&mut self, protocol: Protocol) -> Option<u16> {
+        let range = u16::MAX - EPHEMERAL_PORT_START;
+        for _ in 0..range {
+            let port = EPHEMERAL_PORT_START + self.next_port % range;
+            self.next_port = self.next_port.wrapping_add(1);
+            if !self.bindings.contains_key(&(protocol, port)) {
+                return Some(port);
+            }
+        }
+        None
+    }
+}
+
+struct Binding {
+    addr: SocketAddr,
+    dual_stack: bool,
+    listener: Option<VirtualTcpListener>,
+    receiver: Option<VirtualUdpReceiver>,
+}
+
+impl Binding {
+    fn overlaps(&self, addr: &SocketAddr) -> bool {
+        let (a, b) = (self.addr.ip(), addr.ip());
+        match (a, b) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                a == b || a.is_unspecified() || b.is_unspecified()
+            }
+            (IpAddr::V6(_), IpAddr::V4(_)) => self.dual_stack && a.is_unspecified(),
+            (IpAddr::V4(_), IpAddr::V6(_)) => b.is_unspecified(),
+        }
+    }
+
+    fn accepts(&self, dest: &IpAddr) -> bool {
+        match (self.addr.ip(), dest) {
+            (IpAddr::V6(ip), IpAddr::V4(dest)) => {
+                self.dual_stack && (ip.is_unspecified() || ip.to_ipv4_mapped() == Some(*dest))
+            }
+            (ip, dest) => ip == *dest || (ip.is_unspecified() && ip.is_ipv4() == dest.is_ipv4()),
+        }
+    }
+
+    /// Converts `addr` into the address family of this binding.
+    fn in_family(&self, addr: SocketAddr) -> SocketAddr {
+        match (self.addr.ip(), addr.ip()) {
+            (IpAddr::V6(_), IpAddr::V4(ip)) => {
+                SocketAddr::new(IpAddr::V6(ip.to_ipv6_mapped()), addr.port())
+            }
+            _ => addr,
+        }
+    }
+}
+
+impl LoopbackNetwork {
+    /// Creates a new, empty, network.
+    pub fn new() -> LoopbackNetwork {
+        LoopbackNetwork::default()
+    }
+
+    /// Delays the establishment of connections, the delivery of datagrams and
+    /// the data sent over connections by `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> LoopbackNetwork {
+        self.latency = latency;
+        self
+    }
+
+    /// Injects failures into this network.
+    ///
+    /// The `faults` closure is invoked with the address of each bind,
+    /// connect and outgoing datagram, and an error returned from it fails
+    /// the operation.
+    pub fn with_faults(
+        mut self,
+        faults: impl Fn(&SocketAddr, SocketAddrUse) -> io::Result<()> + Send + Sync + 'static,
+    ) -> LoopbackNetwork {
+        self.faults = Some(Arc::new(faults));
+        self
+    }
+
+    /// Injects failures into the established TCP connections of this network.
+    ///
+    /// The `faults` closure is invoked with the address of the other end of
+    /// the connection on each read from and write to a connection, and an
+    /// error returned from it fails the operation.
+    pub fn with_stream_faults(
+        mut self,
+        faults: impl Fn(&SocketAddr, StreamOperation) -> io::Result<()> + Send + Sync + 'static,
+    ) -> LoopbackNetwork {
+        self.stream_faults = Some(Arc::new(faults));
+        self
+    }
+
+    /// Applies this network's latency and stream faults to a new connection.
+    fn impair(&self, stream: &mut VirtualTcpStream) {
+        stream.outbound.state.lock().unwrap().latency = self.latency;
+        stream.faults = self.stream_faults.clone();
+    }
+
+    fn check_faults(&self, addr: &SocketAddr, reason: SocketAddrUse) -> io::Result<()> {
+        match &self.faults {
+            Some(faults) => faults(addr, reason),
+            None => Ok(()),
+        }
+    }
+
+    fn with_binding<R>(
+        &self,
+        protocol: Protocol,
+        dest: &SocketAddr,
+        f: impl FnOnce(&Binding) -> R,
+    ) -> Option<R> {
+        let dest_ip = util::to_canonical(&dest.ip());
+        let state = self.state.lock().unwrap();
+        let bindings = state.bindings.get(&(protocol, dest.port()))?;
+        // Prefer an exact match over a binding to the unspecified address.
+        let binding = bindings
+            .iter()
+            .filter(|b| b.accepts(&dest_ip))
+            .max_by_key(|b| !b.addr.ip().is_unspecified())?;
+        Some(f(binding))
+    }
+}
+
+impl NetworkProvider for LoopbackNetwork {
+    fn bind(
+        &self,
+        protocol: Protocol,
+        addr: SocketAddr,
+        dual_stack: bool,
+    ) -> io::Result<SocketAddr> {
+        let use_ = match protocol {
+            Protocol::Tcp => SocketAddrUse::TcpBind,
+            Protocol::Udp => SocketAddrUse::UdpBind,
+        };
+        self.check_faults(&addr, use_)?;
+
+        let mut state = self.state.lock().unwrap();
+        let mut addr = addr;
+        if addr.port() == 0 {
+            let port = state
+                .ephemeral_port(protocol)
+                .ok_or(io::ErrorKind::AddrInUse)?;
+            addr.set_port(port);
+        }
+
+        let bindings = state.bindings.entry((protocol, addr.port())).or_default();
+        let candidate = Binding {
+            addr,
+            dual_stack,
+            listener: None,
+            receiver: None,
+        };
+        if bindings
+            .iter()
+            .any(|b| b.overlaps(&addr) || candidate.overlaps(&b.addr))
+        {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        bindings.push(candidate);
+        Ok(addr)
+    }
+
+    fn unbind(&self, protocol: Protocol, addr: SocketAddr) {
+        let mut state = self.state.lock().unwrap();
+        let key = (protocol, addr.port());
+        if let Some(bindings) = state.bindings.get_mut(&key) {
+            bindings.retain(|b| b.addr != addr);
+            if bindings.is_empty() {
+                state.bindings.remove(&key);
+            }
+        }
+    }
+
+    fn source_address(&self, local: SocketAddr, remote: SocketAddr) -> SocketAddr {
+        if !local.ip().is_unspecified() {
+            return local;
+        }
+        let ip = match (local.ip(), util::to_canonical(&remote.ip())) {
+            (IpAddr::V4(_), _) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            (IpAddr::V6(_), IpAddr::V4(_)) => IpAddr::V6(Ipv4Addr::LOCALHOST.to_ipv6_mapped()),
+            (IpAddr::V6(_), IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        };
+        SocketAddr::new(ip, local.port())
+    }
+
+    fn tcp_listen(&self, addr: SocketAddr, backlog: usize) -> io::Result<VirtualTcpListener> {
+        let mut state = self.state.lock().unwrap();
+        let binding = state
+            .bindings
+            .get_mut(&(Protocol::Tcp, addr.port()))
+            .and_then(|bindings| bindings.iter_mut().find(|b| b.addr == addr))
+            .ok_or(io::ErrorKind::AddrNotAvailable)?;
+        let listener = binding
+            .listener
+            .get_or_insert_with(|| VirtualTcpListener::new(backlog));
+        Ok(listener.clone())
+    }
+
+    fn tcp_connect(
+        &self,
+        local: SocketAddr,
+        remote: SocketAddr,
+    ) -> Pin<Box<dyn Future<Output = io::Result<VirtualTcpStream>> + Send>> {
+        let network = self.clone();
+        Box::pin(async move {
+            network.check_faults(&remote, SocketAddrUse::TcpConnect)?;
+            if !network.latency.is_zero() {
+                tokio::time::sleep(network.latency).await;
+            }
+            let source = network.source_address(local, remote);
+            network
+                .with_binding(Protocol::Tcp, &remote, |binding| {
+                    let listener = binding
+                        .listener
+                        .as_ref()
+                        .ok_or(io::ErrorKind::ConnectionRefused)?;
+                    let (mut client, mut server) = VirtualTcpStream::pair(source, remote);
+                    server.local_address = binding.in_family(canonical(remote));
+                    server.remote_address = binding.in_family(canonical(source));
+                    client.remote_address = remote;
+                    network.impair(&mut client);
+                    network.impair(&mut server);
+                    listener
+                        .enqueue(server)
+                        .map_err(|_| io::ErrorKind::ConnectionRefused)?;
+                    Ok(client)
+                })
+                .unwrap_or(Err(io::ErrorKind::ConnectionRefused.into()))
+        })
+    }
+
+    fn udp_receiver(&self, addr: SocketAddr) -> io::Result<VirtualUdpReceiver> {
+        let mut state = self.state.lock().unwrap();
+        let binding = state
+            .bindings
+            .get_mut(&(Protocol::Udp, addr.port()))
+            .and_then(|bindings| bindings.iter_mut().find(|b| b.addr == addr))
+            .ok_or(io::ErrorKind::AddrNotAvailable)?;
+        let receiver = binding
+            .receiver
+            .get_or_insert_with(|| VirtualUdpReceiver::new(1024));
+        Ok(receiver.clone())
+    }
+
+    fn udp_send(&self, local: SocketAddr, remote: SocketAddr, data: Vec<u8>) -> io::Result<()> {
+        self.check_faults(&remote, SocketAddrUse::UdpOutgoingDatagram)?;
+        let source = canonical(self.source_address(local, remote));
+        let Some((receiver, source)) = self
+            .with_binding(Protocol::Udp, &remote, |binding| {
+                Some((binding.receiver.clone()?, binding.in_family(source)))
+            })
+            .flatten()
+        else {
+            // Like real UDP, datagrams to unbound addresses are dropped.
+            return Ok(());
+        };
+        if self.latency.is_zero() {
+            receiver.deliver(data, source);
+        } else {
+            let latency = self.latency;
+            with_ambient_tokio_runtime(|| {
+                tokio::task::spawn(async move {
+                    tokio::time::sleep(latency).await;
+                    receiver.deliver(data, source);
+                })
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Converts IPv4-mapped IPv6 addresses into plain IPv4 addresses.
+fn canonical(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(util::to_canonical(&addr.ip()), addr.port())
+}
+
+/// Socket options of virtual sockets.
+///
+/// These have no effect on virtual traffic and are only stored to be reported
+/// back to the guest.
+pub(crate) struct VirtualSocketOptions {
+    pub(crate) keep_alive_enabled: bool,
+    pub(crate) keep_alive_idle_time: Duration,
+    pub(crate) keep_alive_interval: Duration,
+    pub(crate) keep_alive_count: u32,
+    pub(crate) hop_limit: u8,
+    pub(crate) receive_buffer_size: usize,
+    pub(crate) send_buffer_size: usize,
+}
+
+impl Default for VirtualSocketOptions {
+    fn default() -> Self {
+        VirtualSocketOptions {
+            keep_alive_enabled: false,
+            keep_alive_idle_time: Duration::from_secs(7200),
+            keep_alive_interval: Duration::from_secs(75),
+            keep_alive_count: 9,
+            hop_limit: 64,
+            receive_buffer_size: CONNECTION_BUFFER_SIZE,
+            send_buffer_size: CONNECTION_BUFFER_SIZE,
+        }
+    }
+}
+
+enum PendingConnect {
+    Waiting(AbortOnDropJoinHandle<io::Result<VirtualTcpStream>>),
+    Done(io::Result<VirtualTcpStream>),
+}
+
+/// The state of a `wasi:sockets` TCP socket backed by a [`NetworkProvider`].
+pub(crate) struct VirtualTcpSocket {
+    provider: Arc<dyn NetworkProvider>,
+    /// The address bound by this socket, released when it's dropped.
+    bound: Option<SocketAddr>,
+    listener: Option<VirtualTcpListener>,
+    connect: Option<PendingConnect>,
+    stream: Option<VirtualTcpStream>,
+    pub(crate) options: VirtualSocketOptions,
+}
+
+impl VirtualTcpSocket {
+    pub(crate) fn new(provider: Arc<dyn NetworkProvider>) -> VirtualTcpSocket {
+        VirtualTcpSocket {
+            provider,
+            bound: None,
+            listener: None,
+            connect: None,
+            stream: None,
+            options: VirtualSocketOptions::default(),
+        }
+    }
+
+    pub(crate) fn bind(&mut self, addr: SocketAddr, family: SocketAddressFamily) -> io::Result<()> {
+        let dual_stack = matches!(family, SocketAddressFamily::Ipv6 { v6only: false });
+        self.bound = Some(self.provider.bind(Protocol::Tcp, addr, dual_stack)?);
+        Ok(())
+    }
+
+    pub(crate) fn listen(&mut self, backlog: usize) -> io::Result<()> {
+        let addr = self.bound.ok_or(io::ErrorKind::InvalidInput)?;
+        self.listener = Some(self.provider.tcp_listen(addr, backlog)?);
+        Ok(())
+    }
+
+    /// Accepts a pending connection, returning the socket of the connection.
+    pub(crate) fn accept(&self) -> Option<VirtualTcpSocket> {
+        let stream = self.listener.as_ref()?.accept()?;
+        let mut socket = VirtualTcpSocket::new(self.provider.clone());
+        socket.stream = Some(stream);
+        Some(socket)
+    }
+
+    pub(crate) fn start_connect(
+        &mut self,
+        remote: SocketAddr,
+        family: SocketAddressFamily,
+    ) -> io::Result<()> {
+        // Connecting an unbound socket implicitly binds it to an unspecified
+        // address, as with host sockets.
+        let local = match self.bound {
+            Some(addr) => addr,
+            None => {
+                let ip = match family {
+                    SocketAddressFamily::Ipv4 => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    SocketAddressFamily::Ipv6 { .. } => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                };
+                self.bind(SocketAddr::new(ip, 0), family)?;
+                self.bound.unwrap()
+            }
+        };
+        let connect = self.provider.tcp_connect(local, remote);
+        self.connect = Some(PendingConnect::Waiting(crate::preview2::spawn(connect)));
+        Ok(())
+    }
+
+    /// Completes a connection started with `start_connect`, returning `None`
+    /// if it's still in progress.
+    pub(crate) fn finish_connect(&mut self) -> Option<io::Result<()>> {
+        let result = match self.connect.take()? {
+            PendingConnect::Done(result) => result,
+            PendingConnect::Waiting(mut task) => match poll_noop(Pin::new(&mut task)) {
+                Some(result) => result,
+                None => {
+                    self.connect = Some(PendingConnect::Waiting(task));
+                    return None;
+                }
+            },
+        };
+        Some(result.map(|stream| self.stream = Some(stream)))
+    }
+
+    pub(crate) fn local_address(&self) -> Option<SocketAddr> {
+        match &self.stream {
+            Some(stream) => Some(stream.local_address()),
+            None => self.bound,
+        }
+    }
+
+    pub(crate) fn remote_address(&self) -> Option<SocketAddr> {
+        self.stream.as_ref().map(|s| s.remote_address())
+    }
+
+    pub(crate) fn stream(&self) -> Option<&VirtualTcpStream> {
+        self.stream.as_ref()
+    }
+
+    pub(crate) async fn ready(&mut self) {
+        if let Some(PendingConnect::Waiting(task)) = &mut self.connect {
+            let result = task.await;
+            self.connect = Some(PendingConnect::Done(result));
+        } else if let Some(listener) = &self.listener {
+            listener.ready().await;
+        }
+    }
+}
+
+impl Drop for VirtualTcpSocket {
+    fn drop(&mut self) {
+        if let Some(addr) = self.bound {
+            self.provider.unbind(Protocol::Tcp, addr);
+        }
+    }
+}
+
+/// The state of a `wasi:sockets` UDP socket backed by a [`NetworkProvider`].
+pub(crate) struct VirtualUdpSocket {
+    provider: Arc<dyn NetworkProvider>,
+    binding: Option<VirtualUdpBinding>,
+    remote_address: Option<SocketAddr>,
+    pub(crate) options: VirtualSocketOptions,
+}
+
+impl VirtualUdpSocket {
+    pub(crate) fn new(provider: Arc<dyn NetworkProvider>) -> VirtualUdpSocket {
+        VirtualUdpSocket {
+            provider,
+            binding: None,
+            remote_address: None,
+            options: VirtualSocketOptions::default(),
+        }
+    }
+
+    pub(crate) fn bind(&mut self, addr: SocketAddr, family: SocketAddressFamily) -> io::Result<()> {
+        let dual_stack = matches!(family, SocketAddressFamily::Ipv6 { v6only: false });
+        let addr = self.provider.bind(Protocol::Udp, addr, dual_stack)?;
+        let receiver = match self.provider.udp_receiver(addr) {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                self.provider.unbind(Protocol::Udp, addr);
+                return Err(e);
+            }
+        };
+        self.binding = Some(VirtualUdpBinding {
+            provider: self.provider.clone(),
+            local_address: addr,
+            receiver,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn set_remote_address(&mut self, remote: Option<SocketAddr>) {
+        self.remote_address = remote;
+    }
+
+    pub(crate) fn local_address(&self) -> Option<SocketAddr> {
+        let local = self.binding.as_ref()?.local_address;
+        Some(match self.remote_address {
+            Some(remote) => self.provider.source_address(local, remote),
+            None => local,
+        })
+    }
+
+    pub(crate) fn remote_address(&self) -> Option<SocketAddr> {
+        self.remote_address
+    }
+
+    pub(crate) fn binding(&self) -> Option<&VirtualUdpBinding> {
+        self.binding.as_ref()
+    }
+}
+
+impl Drop for VirtualUdpSocket {
+    fn drop(&mut self) {
+        if let Some(binding) = &self.binding {
+            self.provider.unbind(Protocol::Udp, binding.local_address);
+        }
+    }
+}
+
+/// The bound address of a virtual UDP socket, shared with its datagram
+/// streams.
+#[derive(Clone)]
+pub(crate) struct VirtualUdpBinding {
+    provider: Arc<dyn NetworkProvider>,
+    local_address: SocketAddr,
+    receiver: VirtualUdpReceiver,
+}
+
+impl VirtualUdpBinding {
+    pub(crate) fn send(&self, remote: SocketAddr, data: Vec<u8>) -> io::Result<()> {
+        self.provider.udp_send(self.local_address, remote, data)
+    }
+
+    pub(crate) fn receive(&self) -> Option<(Vec<u8>, SocketAddr)> {
+        self.receiver.receive()
+    }
+
+    pub(crate) async fn ready(&self) {
+        self.receiver.ready().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v4(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+    }
+
+    fn v6_unspecified(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port)
+    }
+
+    async fn connect(
+        network: &LoopbackNetwork,
+        remote: SocketAddr,
+    ) -> io::Result<VirtualTcpSocket> {
+        let mut client = VirtualTcpSocket::new(Arc::new(network.clone()));
+        client.start_connect(remote, SocketAddressFamily::Ipv4)?;
+        client.ready().await;
+        client.finish_connect().unwrap()?;
+        Ok(client)
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn tcp_across_clones() {
+        // Each clone stands in for the network of a separate store.
+        let network = LoopbackNetwork::new();
+
+        let mut server = VirtualTcpSocket::new(Arc::new(network.clone()));
+        server.bind(v4(0), SocketAddressFamily::Ipv4).unwrap();
+        server.listen(8).unwrap();
+        let addr = server.local_address().unwrap();
+        assert!(addr.port() >= EPHEMERAL_PORT_START);
+
+        let client = connect(&network, addr).await.unwrap();
+        server.ready().await;
+        let accepted = server.accept().unwrap();
+        assert!(server.accept().is_none());
+
+        assert_eq!(client.remote_address(), Some(addr));
+        assert_eq!(accepted.local_address(), Some(addr));
+        assert_eq!(accepted.remote_address(), client.local_address());
+
+        let mut output = client.stream().unwrap().write_stream();
+        let mut input = accepted.stream().unwrap().read_stream();
+        assert_eq!(output.check_write().unwrap(), CONNECTION_BUFFER_SIZE);
+        output.write(Bytes::from_static(b"hello")).unwrap();
+        input.ready().await;
+        assert_eq!(input.read(100).unwrap(), &b"hello"[..]);
+        assert_eq!(input.read(100).unwrap(), &b""[..]);
+
+        // Closing the connection is visible to the other end.
+        drop(client);
+        drop(output);
+        input.ready().await;
+        assert!(matches!(input.read(100), Err(StreamError::Closed)));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn tcp_bind_conflicts_and_refusal() {
+        let network = LoopbackNetwork::new();
+        network
+            .bind(Protocol::Tcp, v6_unspecified(80), true)
+            .unwrap();
+
+        // A dual-stack binding also covers IPv4, but not UDP.
+        let err = network.bind(Protocol::Tcp, v4(80), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        network.bind(Protocol::Udp, v4(80), false).unwrap();
+
+        // Bound, but not listening.
+        let err = connect(&network, v4(80)).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+
+        // Unbinding releases the address.
+        network.unbind(Protocol::Tcp, v6_unspecified(80));
+        network.bind(Protocol::Tcp, v4(80), false).unwrap();
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn faults() {
+        let network = LoopbackNetwork::new().with_faults(|addr, use_| match use_ {
+            SocketAddrUse::TcpConnect if addr.port() == 1 => {
+                Err(io::ErrorKind::ConnectionReset.into())
+            }
+            SocketAddrUse::UdpBind => Err(io::ErrorKind::PermissionDenied.into()),
+            _ => Ok(()),
+        });
+
+        let mut server = VirtualTcpSocket::new(Arc::new(network.clone()));
+        server.bind(v4(1), SocketAddressFamily::Ipv4).unwrap();
+        server.listen(8).unwrap();
+        let err = connect(&network, v4(1)).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+
+        let mut udp = VirtualUdpSocket::new(Arc::new(network));
+        let err = udp.bind(v4(1), SocketAddressFamily::Ipv4).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(udp.binding().is_none());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn udp_latency() {
+        let latency = Duration::from_millis(50);
+        let network = LoopbackNetwork::new().with_latency(latency);
+
+        let mut server = VirtualUdpSocket::new(Arc::new(network.clone()));
+        server
+            .bind(
+                v6_unspecified(0),
+                SocketAddressFamily::Ipv6 { v6only: false },
+            )
+            .unwrap();
+        let server_port = server.local_address().unwrap().port();
+
+        let mut client = VirtualUdpSocket::new(Arc::new(network));
+        client.bind(v4(0), SocketAddressFamily::Ipv4).unwrap();
+        client.set_remote_address(Some(v4(server_port)));
+        let client_addr = client.local_address().unwrap();
+
+        let start = std::time::Instant::now();
+        let client = client.binding().unwrap();
+        client.send(v4(server_port), b"ping".to_vec()).unwrap();
+        // Datagrams to unbound addresses are dropped.
+        client.send(v4(1), b"lost".to_vec()).unwrap();
+
+        let server = server.binding().unwrap();
+        assert!(server.receive().is_none());
+        server.ready().await;
+        assert!(start.elapsed() >= latency);
+
+        // The dual-stack server sees the IPv4 client as an IPv4-mapped address.
+        let (data, from) = server.receive().unwrap();
+        assert_eq!(data, b"ping");
+        assert_eq!(canonical(from), client_addr);
+        assert!(from.is_ipv6());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn tcp_latency() {
+        let latency = Duration::from_millis(50);
+        let network = LoopbackNetwork::new().with_latency(latency);
+
+        let mut server = VirtualTcpSocket::new(Arc::new(network.clone()));
+        server.bind(v4(0), SocketAddressFamily::Ipv4).unwrap();
+        server.listen(8).unwrap();
+        let addr = server.local_address().unwrap();
+
+        let start = std::time::Instant::now();
+        let client = connect(&network, addr).await.unwrap();
+        assert!(start.elapsed() >= latency);
+        server.ready().await;
+        let accepted = server.accept().unwrap();
+
+        let mut output = client.stream().unwrap().write_stream();
+        let mut input = accepted.stream().unwrap().read_stream();
+        let start = std::time::Instant::now();
+        output.write(Bytes::from_static(b"hello")).unwrap();
+        // Data in flight still takes up space in the buffer.
+        assert_eq!(output.check_write().unwrap(), CONNECTION_BUFFER_SIZE - 5);
+        assert_eq!(input.read(100).unwrap(), &b""[..]);
+        input.ready().await;
+        assert!(start.elapsed() >= latency);
+        assert_eq!(input.read(100).unwrap(), &b"hello"[..]);
+        assert_eq!(output.check_write().unwrap(), CONNECTION_BUFFER_SIZE);
+
+        // Data written before the connection is closed still arrives.
+        output.write(Bytes::from_static(b"bye")).unwrap();
+        drop(client);
+        drop(output);
+        input.ready().await;
+        assert_eq!(input.read(100).unwrap(), &b"bye"[..]);
+        input.ready().await;
+        assert!(matches!(input.read(100), Err(StreamError::Closed)));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn stream_faults() {
+        let network = LoopbackNetwork::new().with_stream_faults(|addr, op| match op {
+            StreamOperation::Write if addr.port() == 1 => {
+                Err(io::ErrorKind::ConnectionReset.into())
+            }
+            _ => Ok(()),
+        });
+
+        let mut server = VirtualTcpSocket::new(Arc::new(network.clone()));
+        server.bind(v4(1), SocketAddressFamily::Ipv4).unwrap();
+        server.listen(8).unwrap();
+        let client = connect(&network, v4(1)).await.unwrap();
+        server.ready().await;
+        let accepted = server.accept().unwrap();
+
+        // Writes to port 1 fail, but the other direction still works.
+        let mut output = client.stream().unwrap().write_stream();
+        assert!(matches!(
+            output.write(Bytes::from_static(b"hello")),
+            Err(StreamError::LastOperationFailed(_))
+        ));
+        let mut output = accepted.stream().unwrap().write_stream();
+        let mut input = client.stream().unwrap().read_stream();
+        output.write(Bytes::from_static(b"hello")).unwrap();
+        input.ready().await;
+        assert_eq!(input.read(100).unwrap(), &b"hello"[..]);
+    }
+}
@@ -9,8 +9,10 @@ use wasmtime::{Config, Engine, Store};
 use wasmtime_wasi::preview2::bindings::wasi::clocks::wall_clock;
 use wasmtime_wasi::preview2::bindings::wasi::filesystem::types as filesystem;
 use wasmtime_wasi::preview2::command::{add_to_linker, Command};
+use wasmtime_wasi::preview2::virtual_network::LoopbackNetwork;
 use wasmtime_wasi::preview2::{
-    self, DirPerms, FilePerms, HostMonotonicClock, HostWallClock, WasiCtx, WasiCtxBuilder, WasiView,
+    self, DirPerms, FilePerms, HostMonotonicClock, HostWallClock, SocketAddrUse, WasiCtx,
+    WasiCtxBuilder, WasiView,
 };
 
 struct CommandCtx {
@@ -119,6 +121,88 @@ async fn api_read_only() -> Result<()> {
         .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
 }
 
+async fn run_on_network(path: &str, network: LoopbackNetwork) -> Result<()> {
+    run_on_network_with_args(path, &[], network).await
+}
+
+async fn run_on_network_with_args(
+    path: &str,
+    args: &[&str],
+    network: LoopbackNetwork,
+) -> Result<()> {
+    let table = ResourceTable::new();
+    let wasi = WasiCtxBuilder::new()
+        .args(args)
+        .inherit_network()
+        .network_provider(network)
+        .build();
+
+    let (mut store, command) = instantiate(path, CommandCtx { table, wasi }).await?;
+
+    command
+        .wasi_cli_run()
+        .call_run(&mut store)
+        .await?
+        .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn virtual_network_tcp() -> Result<()> {
+    run_on_network(
+        PREVIEW2_TCP_SAMPLE_APPLICATION_COMPONENT,
+        LoopbackNetwork::new(),
+    )
+    .await
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn virtual_network_udp() -> Result<()> {
+    run_on_network(
+        PREVIEW2_UDP_SAMPLE_APPLICATION_COMPONENT,
+        LoopbackNetwork::new().with_latency(Duration::from_millis(10)),
+    )
+    .await
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn virtual_network_shared_between_stores() -> Result<()> {
+    // Guests in separate stores on the same network don't interfere with
+    // each other's ephemeral ports.
+    let network = LoopbackNetwork::new();
+    tokio::try_join!(
+        run_on_network(PREVIEW2_TCP_SAMPLE_APPLICATION_COMPONENT, network.clone()),
+        run_on_network(PREVIEW2_TCP_SAMPLE_APPLICATION_COMPONENT, network.clone()),
+        run_on_network(PREVIEW2_UDP_SAMPLE_APPLICATION_COMPONENT, network),
+    )?;
+    Ok(())
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn api_loopback() -> Result<()> {
+    // A guest connects to a guest listening in another store.
+    let network = LoopbackNetwork::new();
+    tokio::try_join!(
+        run_on_network_with_args(
+            API_LOOPBACK_COMPONENT,
+            &["api_loopback", "server"],
+            network.clone(),
+        ),
+        run_on_network_with_args(API_LOOPBACK_COMPONENT, &["api_loopback", "client"], network,),
+    )?;
+    Ok(())
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn virtual_network_faults() -> Result<()> {
+    let network = LoopbackNetwork::new().with_faults(|_, use_| match use_ {
+        SocketAddrUse::TcpConnect => Err(std::io::ErrorKind::ConnectionRefused.into()),
+        _ => Ok(()),
+    });
+    let result = run_on_network(PREVIEW2_TCP_SAMPLE_APPLICATION_COMPONENT, network).await;
+    assert!(result.is_err());
+    Ok(())
+}
+
 // This is tested in the wasi-http crate, but need to satisfy the `foreach_api!`
 // macro above.
 #[allow(dead_code)]
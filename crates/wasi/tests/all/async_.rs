@@ -4,6 +4,14 @@ use test_programs_artifacts::*;
 use wasmtime_wasi::preview2::command::{add_to_linker, Command};
 
 async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
+    run_with(path, inherit_stdio, |_| {}).await
+}
+
+async fn run_with(
+    path: &str,
+    inherit_stdio: bool,
+    configure: impl FnOnce(&mut WasiCtxBuilder),
+) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
     let mut config = Config::new();
@@ -12,7 +20,7 @@ async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     let mut linker = Linker::new(&engine);
     add_to_linker(&mut linker)?;
 
-    let (mut store, _td) = store(&engine, name, inherit_stdio)?;
+    let (mut store, _td) = store_with(&engine, name, inherit_stdio, configure)?;
     let component = Component::from_file(&engine, path)?;
     let (command, _instance) = Command::instantiate_async(&mut store, &component, &linker).await?;
     command
@@ -317,6 +325,17 @@ async fn preview2_tcp_connect() {
     run(PREVIEW2_TCP_CONNECT_COMPONENT, false).await.unwrap()
 }
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn preview2_tls() {
+    let port = tls::start_echo_server();
+    run_with(PREVIEW2_TLS_COMPONENT, false, |builder| {
+        builder
+            .env("TLS_SERVER_PORT", port.to_string())
+            .tls_client_config(tls::client_config());
+    })
+    .await
+    .unwrap()
+}
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
 async fn preview2_udp_sockopts() {
     run(PREVIEW2_UDP_SOCKOPTS_COMPONENT, false).await.unwrap()
 }
@@ -49,6 +49,15 @@ fn prepare_workspace(exe_name: &str) -> Result<TempDir> {
 }
 
 fn store(engine: &Engine, name: &str, inherit_stdio: bool) -> Result<(Store<Ctx>, TempDir)> {
+    store_with(engine, name, inherit_stdio, |_| {})
+}
+
+fn store_with(
+    engine: &Engine,
+    name: &str,
+    inherit_stdio: bool,
+    configure: impl FnOnce(&mut WasiCtxBuilder),
+) -> Result<(Store<Ctx>, TempDir)> {
     let stdout = MemoryOutputPipe::new(4096);
     let stderr = MemoryOutputPipe::new(4096);
     let workspace = prepare_workspace(name)?;
@@ -72,6 +81,7 @@ fn store(engine: &Engine, name: &str, inherit_stdio: bool) -> Result<(Store<Ctx>
     for (var, val) in test_programs_artifacts::wasi_tests_environment() {
         builder.env(var, val);
     }
+    configure(&mut builder);
 
     let ctx = Ctx {
         table: ResourceTable::new(),
@@ -110,3 +120,4 @@ mod api;
 mod async_;
 mod preview1;
 mod sync;
+mod tls;
@@ -0,0 +1,66 @@
+//! A TLS echo server for the `wasi:tls` tests, using the test certificates
+//! in the `tls` directory.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use wasmtime_wasi::preview2::tls::rustls;
+
+const CA_CERT: &[u8] = include_bytes!("tls/ca.crt.der");
+const SERVER_CERT: &[u8] = include_bytes!("tls/server.crt.der");
+const SERVER_KEY: &[u8] = include_bytes!("tls/server.key.der");
+
+/// A client configuration which trusts the test certificate authority.
+pub fn client_config() -> rustls::ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(&rustls::Certificate(CA_CERT.to_vec())).unwrap();
+    rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth()
+}
+
+/// Starts a server on localhost, with a certificate for `localhost`, which
+/// echoes everything sent to it, and returns its port.
+pub fn start_echo_server() -> u16 {
+    let mut config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![rustls::Certificate(SERVER_CERT.to_vec())],
+            rustls::PrivateKey(SERVER_KEY.to_vec()),
+        )
+        .unwrap();
+    config.alpn_protocols = vec![b"echo".to_vec()];
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let config = config.clone();
+            std::thread::spawn(move || {
+                // Failed handshakes are expected in tests and reported by the
+                // client.
+                let _ = echo(config, stream?);
+                io::Result::Ok(())
+            });
+        }
+    });
+    port
+}
+
+fn echo(config: Arc<rustls::ServerConfig>, stream: TcpStream) -> io::Result<()> {
+    let conn = rustls::ServerConnection::new(config)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let mut tls = rustls::StreamOwned::new(conn, stream);
+    let mut buf = [0; 1024];
+    loop {
+        let n = tls.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        tls.write_all(&buf[..n])?;
+        tls.flush()?;
+    }
+}
@@ -25,6 +25,7 @@
 use crate::Engine;
 use anyhow::Result;
 use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::sync::Arc;
 use std::{any::Any, collections::HashMap};
 use wasmtime_environ::{
     Compiler, DefinedFuncIndex, FuncIndex, FunctionBodyData, ModuleTranslation, ModuleType,
@@ -160,10 +161,43 @@ struct CompileOutput {
     info: Option<WasmFunctionInfo>,
 }
 
+impl CompileOutput {
+    fn wasm_function(
+        module: StaticModuleIndex,
+        translation: &ModuleTranslation<'_>,
+        def_func_index: DefinedFuncIndex,
+        (info, function): (WasmFunctionInfo, Box<dyn Any + Send>),
+    ) -> Self {
+        let func_index = translation.module.func_index(def_func_index);
+        CompileOutput {
+            key: CompileKey::wasm_function(module, def_func_index),
+            symbol: format!(
+                "wasm[{}]::function[{}]",
+                module.as_u32(),
+                func_index.as_u32()
+            ),
+            function: CompiledFunction::Function(function),
+            info: Some(info),
+        }
+    }
+}
+
+/// The defined functions of a core wasm module which need to be compiled.
+///
+/// These are kept separate from other inputs since how they're compiled
+/// depends on whether the compiler inlines calls between functions.
+struct ModuleFunctions<'a> {
+    module: StaticModuleIndex,
+    translation: &'a ModuleTranslation<'a>,
+    types: &'a ModuleTypesBuilder,
+    functions: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'a>>,
+}
+
 /// The collection of things we need to compile for a Wasm module or component.
 #[derive(Default)]
 pub struct CompileInputs<'a> {
     inputs: Vec<CompileInput<'a>>,
+    functions: Vec<ModuleFunctions<'a>>,
 }
 
 impl<'a> CompileInputs<'a> {
@@ -253,23 +287,7 @@ impl<'a> CompileInputs<'a> {
         let mut sigs = BTreeSet::new();
 
         for (module, translation, functions) in translations {
-            for (def_func_index, func_body) in functions {
-                self.push_input(move |compiler| {
-                    let func_index = translation.module.func_index(def_func_index);
-                    let (info, function) =
-                        compiler.compile_function(translation, def_func_index, func_body, types)?;
-                    Ok(CompileOutput {
-                        key: CompileKey::wasm_function(module, def_func_index),
-                        symbol: format!(
-                            "wasm[{}]::function[{}]",
-                            module.as_u32(),
-                            func_index.as_u32()
-                        ),
-                        function: CompiledFunction::Function(function),
-                        info: Some(info),
-                    })
-                });
-
+            for def_func_index in functions.keys() {
                 let func_index = translation.module.func_index(def_func_index);
                 if translation.module.functions[func_index].is_escaping() {
                     self.push_input(move |compiler| {
@@ -315,6 +333,13 @@ impl<'a> CompileInputs<'a> {
             sigs.extend(translation.module.types.iter().map(|(_, ty)| match ty {
                 ModuleType::Function(ty) => *ty,
             }));
+
+            self.functions.push(ModuleFunctions {
+                module,
+                translation,
+                types,
+                functions,
+            });
         }
 
         for signature in sigs {
@@ -339,8 +364,38 @@ impl<'a> CompileInputs<'a> {
     pub fn compile(self, engine: &Engine) -> Result<UnlinkedCompileOutputs> {
         let compiler = engine.compiler();
 
+        let mut inputs = self.inputs;
+        if compiler.inlining() {
+            inputs.extend(Self::translate_for_inlining(engine, self.functions)?);
+        } else {
+            for ModuleFunctions {
+                module,
+                translation,
+                types,
+                functions,
+            } in self.functions
+            {
+                for (def_func_index, func_body) in functions {
+                    inputs.push(Box::new(move |compiler: &dyn Compiler| {
+                        let function = compiler.compile_function(
+                            translation,
+                            def_func_index,
+                            func_body,
+                            types,
+                        )?;
+                        Ok(CompileOutput::wasm_function(
+                            module,
+                            translation,
+                            def_func_index,
+                            function,
+                        ))
+                    }));
+                }
+            }
+        }
+
         // Compile each individual input in parallel.
-        let raw_outputs = engine.run_maybe_parallel(self.inputs, |f| f(compiler))?;
+        let raw_outputs = engine.run_maybe_parallel(inputs, |f| f(compiler))?;
 
         // Bucket the outputs by kind.
         let mut outputs: BTreeMap<u32, Vec<CompileOutput>> = BTreeMap::new();
@@ -364,6 +419,68 @@ impl<'a> CompileInputs<'a> {
 
         Ok(UnlinkedCompileOutputs { outputs })
     }
+
+    /// Translates all wasm functions up front, in parallel, and returns the
+    /// inputs to compile each of them.
+    ///
+    /// Each function is compiled with the translated bodies of all other
+    /// functions of its module at hand so that its calls can be inlined.
+    fn translate_for_inlining(
+        engine: &Engine,
+        modules: Vec<ModuleFunctions<'a>>,
+    ) -> Result<Vec<CompileInput<'a>>> {
+        let compiler = engine.compiler();
+
+        let mut modules_info = Vec::new();
+        let mut bodies = Vec::new();
+        for module in modules {
+            let i = modules_info.len();
+            modules_info.push((module.module, module.translation, module.types));
+            bodies.extend(
+                module
+                    .functions
+                    .into_iter()
+                    .map(|(index, body)| (i, index, body)),
+            );
+        }
+
+        let translated = engine.run_maybe_parallel(bodies, |(i, index, body)| {
+            let (_, translation, types) = modules_info[i];
+            let func = compiler.translate_function(translation, index, body, types)?;
+            Ok::<_, anyhow::Error>((i, func))
+        })?;
+
+        let mut functions = modules_info
+            .iter()
+            .map(|_| PrimaryMap::new())
+            .collect::<Vec<_>>();
+        for (i, func) in translated {
+            functions[i].push(func);
+        }
+
+        let mut inputs: Vec<CompileInput<'a>> = Vec::new();
+        for ((module, translation, types), functions) in modules_info.into_iter().zip(functions) {
+            let functions = Arc::new(functions);
+            for def_func_index in functions.keys() {
+                let functions = functions.clone();
+                inputs.push(Box::new(move |compiler: &dyn Compiler| {
+                    let function = compiler.compile_translated_function(
+                        translation,
+                        def_func_index,
+                        &functions,
+                        types,
+                    )?;
+                    Ok(CompileOutput::wasm_function(
+                        module,
+                        translation,
+                        def_func_index,
+                        function,
+                    ))
+                }));
+            }
+        }
+        Ok(inputs)
+    }
 }
 
 #[derive(Default)]
@@ -1,16 +1,20 @@
 use crate::code::CodeObject;
+use crate::component::matching::InstanceType;
+use crate::component::types;
+use crate::component::ResourceType;
 use crate::signatures::SignatureCollection;
-use crate::{Engine, Module, ResourcesRequired};
+use crate::{Engine, Module, PgoProfile, ResourcesRequired};
 use anyhow::{bail, Context, Result};
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::mem;
 use std::path::Path;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
 use std::sync::Arc;
 use wasmtime_environ::component::{
-    AllCallFunc, ComponentTypes, GlobalInitializer, InstantiateModule, StaticModuleIndex,
-    TrampolineIndex, Translator, VMComponentOffsets,
+    AllCallFunc, ComponentTypes, GlobalInitializer, InstantiateModule, ResourceIndex,
+    StaticModuleIndex, TrampolineIndex, Translator, VMComponentOffsets,
 };
 use wasmtime_environ::{FunctionLoc, HostPtr, ObjectKind, PrimaryMap, ScopeVec};
 use wasmtime_jit::{CodeMemory, CompiledModuleInfo};
@@ -29,6 +33,11 @@ pub struct Component {
 }
 
 struct ComponentInner {
+    /// A process-wide unique identifier for this component, used to give the
+    /// resources reflected by `Component::component_type` types which aren't
+    /// shared with any other component.
+    id: u64,
+
     /// Core wasm modules that the component defined internally, indexed by the
     /// compile-time-assigned `ModuleUpvarIndex`.
     static_modules: PrimaryMap<StaticModuleIndex, Module>,
@@ -85,8 +94,11 @@ impl Component {
     /// provided.
     //
     // FIXME: need to write more docs here.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn new(engine: &Engine, bytes: impl AsRef<[u8]>) -> Result<Component> {
         let bytes = bytes.as_ref();
         #[cfg(feature = "wat")]
@@ -98,8 +110,11 @@ impl Component {
     /// by `file`.
     //
     // FIXME: need to write more docs here.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn from_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Component> {
         match Self::new(
             engine,
@@ -124,8 +139,11 @@ impl Component {
     /// provided.
     //
     // FIXME: need to write more docs here.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn from_binary(engine: &Engine, binary: &[u8]) -> Result<Component> {
         use crate::module::HashedEngineCompileEnv;
 
@@ -209,7 +227,7 @@ impl Component {
     /// any necessary extra functions required for operation with components.
     /// The output artifact here is the serialized object file contained within
     /// an owned mmap along with metadata about the compilation itself.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn build_artifacts(
         engine: &Engine,
         binary: &[u8],
@@ -227,6 +245,12 @@ impl Component {
             Translator::new(tunables, &mut validator, &mut types, &scope)
                 .translate(binary)
                 .context("failed to parse WebAssembly module")?;
+        for (_, translation) in module_translations.iter_mut() {
+            engine
+                .config()
+                .pgo_profile
+                .prepare_translation(tunables.pgo_instrumentation, translation);
+        }
 
         let compile_inputs = CompileInputs::for_component(
             &types,
@@ -315,6 +339,10 @@ impl Component {
 
         Ok(Component {
             inner: Arc::new(ComponentInner {
+                id: {
+                    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+                    NEXT_ID.fetch_add(1, Relaxed)
+                },
                 static_modules,
                 code,
                 info,
@@ -326,10 +354,38 @@ impl Component {
         &self.inner.info.component
     }
 
+    /// Returns the execution profile collected so far by the core modules
+    /// inside of this component, or `None` if this component wasn't compiled
+    /// with [`Config::pgo_instrumentation`](crate::Config::pgo_instrumentation)
+    /// enabled.
+    ///
+    /// This is the same as [`Module::pgo_profile`](crate::Module::pgo_profile)
+    /// for all of those modules combined, and the returned profile can
+    /// similarly be passed to [`Config::pgo_profile`](crate::Config::pgo_profile)
+    /// to optimize subsequent compilations of this component.
+    pub fn pgo_profile(&self) -> Option<PgoProfile> {
+        let mut profile: Option<PgoProfile> = None;
+        for module in self.inner.static_modules.values() {
+            if let Some(module_profile) = module.pgo_profile() {
+                profile
+                    .get_or_insert_with(PgoProfile::new)
+                    .merge(&module_profile);
+            }
+        }
+        profile
+    }
+
     pub(crate) fn static_module(&self, idx: StaticModuleIndex) -> &Module {
         &self.inner.static_modules[idx]
     }
 
+    /// Returns all core modules within this component, including the adapter
+    /// modules generated for it.
+    #[cfg(feature = "profiling")]
+    pub(crate) fn static_modules(&self) -> impl ExactSizeIterator<Item = &Module> {
+        self.inner.static_modules.values()
+    }
+
     #[inline]
     pub(crate) fn types(&self) -> &Arc<ComponentTypes> {
         self.inner.component_types()
@@ -496,6 +552,67 @@ impl Component {
         }
         Some(resources)
     }
+
+    /// Returns the type of this component, describing all of its imports and
+    /// exports along with their types.
+    ///
+    /// This can be used to inspect the interface of a component before it's
+    /// instantiated, for example to validate it against what a host provides.
+    /// Note that resources defined by or imported into this component are
+    /// reflected with types unique to this component rather than the types of
+    /// any particular instantiation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> wasmtime::Result<()> {
+    /// use wasmtime::{Config, Engine, component::Component};
+    /// use wasmtime::component::types::ComponentItem;
+    ///
+    /// let mut config = Config::new();
+    /// config.wasm_component_model(true);
+    /// let engine = Engine::new(&config)?;
+    ///
+    /// let component = Component::new(&engine, &r#"
+    ///     (component
+    ///         (import "log" (func (param "msg" string)))
+    ///         (core module $m
+    ///             (func (export "add") (param i32 i32) (result i32)
+    ///                 local.get 0
+    ///                 local.get 1
+    ///                 i32.add)
+    ///         )
+    ///         (core instance $i (instantiate $m))
+    ///         (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+    ///             (canon lift (core func $i "add")))
+    ///     )
+    /// "#)?;
+    ///
+    /// let ty = component.component_type();
+    /// assert_eq!(ty.imports().len(), 1);
+    ///
+    /// let add = match ty.get_export("add") {
+    ///     Some(ComponentItem::ComponentFunc(func)) => func,
+    ///     _ => panic!("expected a function export"),
+    /// };
+    /// assert_eq!(add.params().len(), 2);
+    /// assert_eq!(add.results().len(), 1);
+    /// # Ok(()) }
+    /// ```
+    pub fn component_type(&self) -> types::Component {
+        // No resources have been created for this component yet, so resource
+        // types are all reflected as belonging to this uninstantiated
+        // component.
+        let resources: PrimaryMap<_, _> = (0..self.env_component().num_resources)
+            .map(|i| ResourceType::uninstantiated(self.inner.id, ResourceIndex::from_u32(i)))
+            .collect();
+        let resources = Arc::new(resources);
+        let instance = InstanceType {
+            types: self.types(),
+            resources: &resources,
+        };
+        types::Component::from(self.inner.info.component.ty, &instance)
+    }
 }
 
 impl ComponentRuntimeInfo for ComponentInner {
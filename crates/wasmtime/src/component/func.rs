@@ -335,7 +335,7 @@ impl Func {
             .await?
     }
 
-    fn call_impl(
+    pub(crate) fn call_impl(
         &self,
         mut store: impl AsContextMut,
         params: &[Val],
@@ -598,7 +598,7 @@ impl Func {
     }
 
     #[inline]
-    fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
+    pub(crate) fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
         let mut store = store.as_context_mut();
         let data = &mut store.0[self.0];
         let instance = data.instance;
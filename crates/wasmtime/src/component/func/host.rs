@@ -1,7 +1,7 @@
 use crate::component::func::{LiftContext, LowerContext, Options};
 use crate::component::matching::InstanceType;
 use crate::component::storage::slice_to_storage_mut;
-use crate::component::{ComponentNamedList, ComponentType, Lift, Lower, Type, Val};
+use crate::component::{types, ComponentNamedList, ComponentType, Lift, Lower, Type, Val};
 use crate::{AsContextMut, StoreContextMut, ValRaw};
 use anyhow::{anyhow, bail, Context, Result};
 use std::any::Any;
@@ -96,6 +96,29 @@ impl HostFunc {
         })
     }
 
+    /// Same as [`HostFunc::new_dynamic`] except that the function is
+    /// typechecked structurally against the type `ty`, which may originate
+    /// from a different component than the one importing this function.
+    pub(crate) fn new_dynamic_typed<T, F>(func: F, ty: types::ComponentFunc) -> Arc<HostFunc>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        Arc::new(HostFunc {
+            entrypoint: dynamic_entrypoint::<T, F>,
+            typecheck: Box::new(move |expected_index, expected_types| {
+                let expected = types::ComponentFunc::from(expected_index, expected_types);
+                if !ty.params().eq(expected.params()) {
+                    bail!("type mismatch with parameters");
+                }
+                if !ty.results().eq(expected.results()) {
+                    bail!("type mismatch with results");
+                }
+                Ok(())
+            }),
+            func: Box::new(func),
+        })
+    }
+
     pub fn typecheck(&self, ty: TypeFuncIndex, types: &InstanceType<'_>) -> Result<()> {
         (self.typecheck)(ty, types)
     }
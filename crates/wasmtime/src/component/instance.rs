@@ -1,5 +1,7 @@
 use crate::component::func::HostFunc;
 use crate::component::matching::InstanceType;
+use crate::component::resources::OwnState;
+use crate::component::types;
 use crate::component::{
     Component, ComponentNamedList, Func, Lift, Lower, ResourceImportIndex, ResourceType, TypedFunc,
 };
@@ -61,6 +63,30 @@ pub(crate) struct InstanceData {
 }
 
 impl Instance {
+    /// Returns the core wasm instances that were created to instantiate this
+    /// component instance.
+    #[cfg(feature = "coredump")]
+    pub(crate) fn core_instances<'a>(
+        &self,
+        store: &'a StoreOpaque,
+    ) -> impl Iterator<Item = (RuntimeInstanceIndex, crate::Instance)> + 'a {
+        store[self.0]
+            .iter()
+            .flat_map(|data| data.instances.iter().map(|(i, instance)| (i, *instance)))
+    }
+
+    /// Returns the compiled representation of the component this instance
+    /// was instantiated from, or `None` if its instantiation didn't finish.
+    #[cfg(feature = "coredump")]
+    pub(crate) fn env_component<'a>(
+        &self,
+        store: &'a StoreOpaque,
+    ) -> Option<&'a wasmtime_environ::component::Component> {
+        store[self.0]
+            .as_ref()
+            .map(|data| data.component.env_component())
+    }
+
     /// Returns information about the exports of this instance.
     ///
     /// This method can be used to extract exported values from this component
@@ -799,6 +825,41 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
         self.as_mut().into_instance(name)
     }
 
+    /// Returns all of the items exported from this instance, recursively,
+    /// which can be defined within a `Linker`.
+    ///
+    /// Exported types other than resources are not included in the returned
+    /// list.
+    pub(crate) fn forwarded_exports(&mut self) -> Vec<(String, ForwardedExport)> {
+        let ty = self.data.ty();
+        let store_id = self.store.id();
+        let mut ret = Vec::new();
+        for (name, export) in self.exports {
+            let item = match export {
+                Export::LiftedFunction { ty: index, .. } => ForwardedExport::Func(
+                    self.func(name).unwrap(),
+                    types::ComponentFunc::from(*index, &ty),
+                ),
+                Export::ModuleStatic(_) | Export::ModuleImport(_) => {
+                    ForwardedExport::Module(self.module(name).unwrap().clone())
+                }
+                Export::Type(TypeDef::Resource(id)) => {
+                    let (dtor, flags) = self.data.instance().dtor_and_flags(*id);
+                    ForwardedExport::Resource(
+                        ty.resource_type(*id),
+                        OwnState::new(store_id, flags, dtor),
+                    )
+                }
+                Export::Type(_) => continue,
+                Export::Instance(_) => {
+                    ForwardedExport::Instance(self.instance(name).unwrap().forwarded_exports())
+                }
+            };
+            ret.push((name.clone(), item));
+        }
+        ret
+    }
+
     /// Same as [`ExportInstance::instance`] but consumes self to yield a
     /// return value with the same lifetimes.
     pub fn into_instance(self, name: &str) -> Option<ExportInstance<'a, 'store>> {
@@ -813,3 +874,12 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
         }
     }
 }
+
+/// An item exported from an [`Instance`] which has been extracted to be
+/// defined within a [`Linker`](crate::component::Linker).
+pub(crate) enum ForwardedExport {
+    Func(Func, types::ComponentFunc),
+    Module(Module),
+    Resource(ResourceType, OwnState),
+    Instance(Vec<(String, ForwardedExport)>),
+}
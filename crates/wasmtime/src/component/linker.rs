@@ -1,11 +1,12 @@
 use crate::component::func::HostFunc;
-use crate::component::instance::RuntimeImport;
+use crate::component::instance::{ForwardedExport, RuntimeImport};
 use crate::component::matching::TypeChecker;
 use crate::component::{
-    Component, ComponentNamedList, Instance, InstancePre, Lift, Lower, ResourceType, Val,
+    types, Component, ComponentNamedList, Instance, InstancePre, Lift, Lower, ResourceType, Val,
 };
+use crate::store::StoreId;
 use crate::{AsContextMut, Engine, Module, StoreContextMut};
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use indexmap::IndexMap;
 use std::collections::hash_map::{Entry, HashMap};
 use std::future::Future;
@@ -308,6 +309,120 @@ impl<T> Linker<T> {
             .instantiate_async(store)
             .await
     }
+
+    /// Implement any function imports of the [`Component`] which aren't
+    /// defined in this linker with a function which traps.
+    ///
+    /// Functions imported through an instance are defined within the
+    /// instance of the same name, which is created if it isn't already defined
+    /// in this linker. Imported resources are defined as host resources with
+    /// [`ResourceType::host::<()>()`](ResourceType::host) whose destructor
+    /// does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component imports a module or a component
+    /// which isn't defined in this linker, since those can't be stubbed out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime::{Engine, Store};
+    /// use wasmtime::component::{Component, Linker};
+    ///
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let component = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (import "unknown" (func))
+    ///             (import "my:pkg/api" (instance
+    ///                 (export "unknown" (func (param "x" string)))
+    ///             ))
+    ///         )
+    ///     "#,
+    /// )?;
+    /// let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define_unknown_imports_as_traps(&component)?;
+    /// linker.instantiate(&mut store, &component)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn define_unknown_imports_as_traps(&mut self, component: &Component) -> Result<()> {
+        self.define_unknown_imports(component, |name, _ty| {
+            let name = name.to_string();
+            move |_: StoreContextMut<'_, T>, _: &[Val], _: &mut [Val]| {
+                bail!("unknown import: `{name}` has not been defined")
+            }
+        })
+    }
+
+    /// Implement any function imports of the [`Component`] which aren't
+    /// defined in this linker with a function that ignores its arguments and
+    /// returns default values.
+    ///
+    /// Default values are `false`, zero, `'\0'` or empty for primitives,
+    /// strings, lists and flags, the first case of variants and enums, `none`
+    /// for options and `ok` for results. Aggregates are built from the default
+    /// values of their contents. Functions returning a resource trap instead.
+    ///
+    /// Imports are defined in the same way, and with the same errors, as
+    /// [`Linker::define_unknown_imports_as_traps`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wasmtime::{Engine, Store};
+    /// use wasmtime::component::{Component, Linker};
+    ///
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let component = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (import "unknown" (func (result u32)))
+    ///         )
+    ///     "#,
+    /// )?;
+    /// let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define_unknown_imports_as_default_values(&component)?;
+    /// linker.instantiate(&mut store, &component)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn define_unknown_imports_as_default_values(
+        &mut self,
+        component: &Component,
+    ) -> Result<()> {
+        self.define_unknown_imports(component, |_name, ty| {
+            let results = ty.results().collect::<Vec<_>>();
+            move |_: StoreContextMut<'_, T>, _: &[Val], vals: &mut [Val]| {
+                for (val, ty) in vals.iter_mut().zip(&results) {
+                    *val = default_val(ty)?;
+                }
+                Ok(())
+            }
+        })
+    }
+
+    fn define_unknown_imports<F>(
+        &mut self,
+        component: &Component,
+        mut func: impl FnMut(&str, &types::ComponentFunc) -> F,
+    ) -> Result<()>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        let mut root = self.root();
+        for (name, item) in component.component_type().imports() {
+            root.define_unknown_import(component, None, name, item, &mut func)?;
+        }
+        Ok(())
+    }
 }
 
 impl<T> LinkerInstance<'_, T> {
@@ -512,6 +627,160 @@ impl<T> LinkerInstance<'_, T> {
         Ok(idx)
     }
 
+    /// Defines all of the exports of a component [`Instance`] within this
+    /// linker instance.
+    ///
+    /// This can be used to wire components together at runtime: the exports
+    /// of `instance`, which has already been instantiated within `store`, are
+    /// defined in this linker so they can satisfy the imports of another
+    /// component instantiated within the same store.
+    ///
+    /// The `path` argument selects which exports are defined. An empty path
+    /// defines the root exports of `instance`, while a non-empty path
+    /// names a nested exported instance, for example `&["my:pkg/iface"]`.
+    ///
+    /// Each export is defined as follows:
+    ///
+    /// * Functions forward their calls to the exported function, including
+    ///   invoking its `post-return` function.
+    /// * Resources are defined with the exporting instance's resource type, so
+    ///   `own` and `borrow` handles of the resource may be passed between
+    ///   components. When an importing component drops an owned resource the
+    ///   exporting instance's destructor is invoked.
+    /// * Core wasm modules are defined as with [`LinkerInstance::module`].
+    /// * Nested instances are defined recursively as with
+    ///   [`LinkerInstance::instance`].
+    ///
+    /// Exported types other than resources are skipped as they are not
+    /// needed to satisfy imports.
+    ///
+    /// Items defined by this method are only usable with components
+    /// instantiated within `store`. Invoking a forwarded function or
+    /// destructor within any other store results in an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` doesn't name an exported instance or if any
+    /// export conflicts with an existing definition in this linker and
+    /// shadowing is not allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wasmtime::{Engine, Store};
+    /// use wasmtime::component::{Component, Linker};
+    ///
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let backend = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (core module $m
+    ///                 (func (export "answer") (result i32) i32.const 42))
+    ///             (core instance $i (instantiate $m))
+    ///             (func $answer (result u32) (canon lift (core func $i "answer")))
+    ///             (instance $api (export "answer" (func $answer)))
+    ///             (export "my:pkg/api" (instance $api))
+    ///         )
+    ///     "#,
+    /// )?;
+    /// let frontend = Component::new(
+    ///     &engine,
+    ///     r#"
+    ///         (component
+    ///             (import "my:pkg/api" (instance $api
+    ///                 (export "answer" (func (result u32)))
+    ///             ))
+    ///             (core func $answer (canon lower (func $api "answer")))
+    ///             (core module $m
+    ///                 (import "" "answer" (func $answer (result i32)))
+    ///                 (func (export "answer") (result i32) call $answer))
+    ///             (core instance $i (instantiate $m
+    ///                 (with "" (instance (export "answer" (func $answer))))
+    ///             ))
+    ///             (func (export "answer") (result u32)
+    ///                 (canon lift (core func $i "answer")))
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let backend = Linker::new(&engine).instantiate(&mut store, &backend)?;
+    ///
+    /// let mut linker = Linker::new(&engine);
+    /// linker
+    ///     .instance("my:pkg/api")?
+    ///     .instance_exports(&mut store, &backend, &["my:pkg/api"])?;
+    /// let frontend = linker.instantiate(&mut store, &frontend)?;
+    /// let answer = frontend.get_typed_func::<(), (u32,)>(&mut store, "answer")?;
+    /// assert_eq!(answer.call(&mut store, ())?, (42,));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn instance_exports(
+        &mut self,
+        mut store: impl AsContextMut<Data = T>,
+        instance: &Instance,
+        path: &[&str],
+    ) -> Result<()> {
+        let store = store.as_context_mut();
+        let store_id = store.0.id();
+        let mut exports = instance.exports(store);
+        let mut export = exports.root();
+        for name in path {
+            export = export
+                .into_instance(name)
+                .ok_or_else(|| anyhow!("instance export `{name}` not found"))?;
+        }
+        let items = export.forwarded_exports();
+        drop(exports);
+        self.define_forwarded_exports(store_id, items)
+    }
+
+    fn define_forwarded_exports(
+        &mut self,
+        store_id: StoreId,
+        items: Vec<(String, ForwardedExport)>,
+    ) -> Result<()> {
+        for (name, item) in items {
+            match item {
+                ForwardedExport::Func(func, ty) => {
+                    let name = self.strings.intern(&name);
+                    let func = HostFunc::new_dynamic_typed(
+                        move |mut store: StoreContextMut<'_, T>,
+                              params: &[Val],
+                              results: &mut [Val]| {
+                            ensure!(
+                                store.0.id() == store_id,
+                                "function forwarded from another store"
+                            );
+                            func.call_impl(&mut store, params, results)?;
+                            func.post_return_impl(&mut store)
+                        },
+                        ty,
+                    );
+                    self.insert(name, Definition::Func(func))?;
+                }
+                ForwardedExport::Module(module) => self.module(&name, &module)?,
+                ForwardedExport::Resource(ty, state) => {
+                    self.resource(&name, ty, move |mut store, rep| {
+                        ensure!(
+                            store.0.id() == store_id,
+                            "resource forwarded from another store"
+                        );
+                        state.destroy(&mut store, rep)
+                    })?;
+                }
+                ForwardedExport::Instance(items) => {
+                    self.instance(&name)?
+                        .define_forwarded_exports(store_id, items)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Defines a nested instance within this instance.
     ///
     /// This can be used to describe arbitrarily nested levels of instances
@@ -546,6 +815,81 @@ impl<T> LinkerInstance<'_, T> {
         Ok(self)
     }
 
+    fn define_unknown_import<F>(
+        &mut self,
+        component: &Component,
+        instance: Option<&str>,
+        name: &str,
+        item: types::ComponentItem,
+        func: &mut impl FnMut(&str, &types::ComponentFunc) -> F,
+    ) -> Result<()>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        let key = self.strings.intern(name);
+        let full_name = match instance {
+            Some(instance) => format!("{instance}#{name}"),
+            None => name.to_string(),
+        };
+        match item {
+            types::ComponentItem::ComponentFunc(ty) => {
+                if !self.map.contains_key(&key) {
+                    let f = func(&full_name, &ty);
+                    self.func_new(component, name, f)?;
+                }
+            }
+            types::ComponentItem::ComponentInstance(ty) => {
+                // Definitions of anything other than an instance are left
+                // as-is for instantiation to report.
+                let map = match self
+                    .map
+                    .entry(key)
+                    .or_insert_with(|| Definition::Instance(NameMap::default()))
+                {
+                    Definition::Instance(map) => map,
+                    _ => return Ok(()),
+                };
+                self.path.truncate(self.path_len);
+                self.path.push(key);
+                let mut instance = LinkerInstance {
+                    engine: self.engine,
+                    path: self.path,
+                    path_len: self.path_len + 1,
+                    strings: self.strings,
+                    map,
+                    resource_imports: self.resource_imports,
+                    allow_shadowing: self.allow_shadowing,
+                    _marker: self._marker,
+                };
+                for (export, item) in ty.exports() {
+                    instance.define_unknown_import(
+                        component,
+                        Some(&full_name),
+                        export,
+                        item,
+                        func,
+                    )?;
+                }
+            }
+            types::ComponentItem::Resource(_) => {
+                // Each stub gets its own type so that distinct imports aren't
+                // unified with each other.
+                if !self.map.contains_key(&key) {
+                    self.resource(name, ResourceType::unique(), |_, _| Ok(()))?;
+                }
+            }
+            types::ComponentItem::CoreFunc(_)
+            | types::ComponentItem::Module(_)
+            | types::ComponentItem::Component(_) => {
+                if !self.map.contains_key(&key) {
+                    bail!("unknown import: `{full_name}` cannot be defined automatically");
+                }
+            }
+            types::ComponentItem::Type(_) => {}
+        }
+        Ok(())
+    }
+
     fn insert(&mut self, key: usize, item: Definition) -> Result<()> {
         match self.map.entry(key) {
             Entry::Occupied(_) if !self.allow_shadowing => {
@@ -578,3 +922,53 @@ impl Strings {
         self.string2idx.get(string).cloned()
     }
 }
+
+/// Returns the value returned for results of type `ty` by
+/// [`Linker::define_unknown_imports_as_default_values`].
+fn default_val(ty: &types::Type) -> Result<Val> {
+    use types::Type;
+
+    Ok(match ty {
+        Type::Bool => Val::Bool(false),
+        Type::S8 => Val::S8(0),
+        Type::U8 => Val::U8(0),
+        Type::S16 => Val::S16(0),
+        Type::U16 => Val::U16(0),
+        Type::S32 => Val::S32(0),
+        Type::U32 => Val::U32(0),
+        Type::S64 => Val::S64(0),
+        Type::U64 => Val::U64(0),
+        Type::Float32 => Val::Float32(0.0),
+        Type::Float64 => Val::Float64(0.0),
+        Type::Char => Val::Char('\0'),
+        Type::String => Val::String("".into()),
+        Type::List(list) => list.new_val(Box::new([]))?,
+        Type::Record(record) => {
+            let fields = record
+                .fields()
+                .map(|field| Ok((field.name, default_val(&field.ty)?)))
+                .collect::<Result<Vec<_>>>()?;
+            record.new_val(fields)?
+        }
+        Type::Tuple(tuple) => {
+            let values = tuple
+                .types()
+                .map(|ty| default_val(&ty))
+                .collect::<Result<_>>()?;
+            tuple.new_val(values)?
+        }
+        Type::Variant(variant) => {
+            let case = variant.cases().next().unwrap();
+            let payload = case.ty.as_ref().map(default_val).transpose()?;
+            variant.new_val(case.name, payload)?
+        }
+        Type::Enum(enum_) => enum_.new_val(enum_.names().next().unwrap())?,
+        Type::Option(option) => option.new_val(None)?,
+        Type::Result(result) => {
+            let payload = result.ok().as_ref().map(default_val).transpose()?;
+            result.new_val(Ok(payload))?
+        }
+        Type::Flags(flags) => flags.new_val(&[])?,
+        Type::Own(_) | Type::Borrow(_) => bail!("cannot return a default value for a resource"),
+    })
+}
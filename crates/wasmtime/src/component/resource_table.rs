@@ -111,6 +111,19 @@ impl ResourceTable {
         }
     }
 
+    /// Returns the number of entries in this table.
+    pub fn len(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.occupied().is_some())
+            .count()
+    }
+
+    /// Returns whether this table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Inserts a new value `T` into this table, returning a corresponding
     /// `Resource<T>` which can be used to refer to it after it was inserted.
     pub fn push<T>(&mut self, entry: T) -> Result<Resource<T>, ResourceTableError>
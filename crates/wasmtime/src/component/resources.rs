@@ -10,8 +10,11 @@ use std::any::TypeId;
 use std::fmt;
 use std::marker;
 use std::mem::MaybeUninit;
-use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
-use wasmtime_environ::component::{CanonicalAbiInfo, DefinedResourceIndex, InterfaceType};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering::Relaxed};
+use wasmtime_environ::component::{
+    CanonicalAbiInfo, DefinedResourceIndex, InterfaceType, ResourceIndex,
+};
 use wasmtime_runtime::component::{ComponentInstance, InstanceFlags, ResourceTables};
 use wasmtime_runtime::{SendSyncPtr, VMFuncRef, ValRaw};
 
@@ -67,6 +70,21 @@ impl ResourceType {
             },
         }
     }
+
+    pub(crate) fn uninstantiated(component: u64, index: ResourceIndex) -> ResourceType {
+        ResourceType {
+            kind: ResourceTypeKind::Uninstantiated { component, index },
+        }
+    }
+
+    /// Creates a new host resource type which is different from every other
+    /// resource type, and which no host value has.
+    pub(crate) fn unique() -> ResourceType {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        ResourceType {
+            kind: ResourceTypeKind::Unique(NEXT_ID.fetch_add(1, Relaxed)),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -80,6 +98,18 @@ enum ResourceTypeKind {
         instance: usize,
         id: DefinedResourceIndex,
     },
+    /// The type of a resource in a component that hasn't been instantiated
+    /// yet, as reflected by `Component::component_type`.
+    Uninstantiated {
+        // This is the unique id assigned to the `Component` when it was
+        // created, which distinguishes resources of different components.
+        // Unlike an address it's never reused once the component is dropped.
+        component: u64,
+        index: ResourceIndex,
+    },
+    /// A host resource type not associated with any Rust type, such as those
+    /// of imports stubbed out by the `Linker`.
+    Unique(u64),
 }
 
 /// A host-defined resource in the component model.
@@ -534,12 +564,65 @@ pub struct ResourceAny {
 }
 
 #[derive(Copy, Clone)]
-struct OwnState {
+pub(crate) struct OwnState {
     store: StoreId,
     flags: Option<InstanceFlags>,
     dtor: Option<SendSyncPtr<VMFuncRef>>,
 }
 
+impl OwnState {
+    pub(crate) fn new(
+        store: StoreId,
+        flags: Option<InstanceFlags>,
+        dtor: Option<NonNull<VMFuncRef>>,
+    ) -> OwnState {
+        OwnState {
+            store,
+            flags,
+            dtor: dtor.map(SendSyncPtr::new),
+        }
+    }
+
+    /// Runs the destructor, if any, for the owned resource `rep` whose
+    /// ownership state is described by `self`.
+    pub(crate) fn destroy<T>(&self, store: &mut StoreContextMut<'_, T>, rep: u32) -> Result<()> {
+        // Double-check that accessing the raw pointers on `self` are safe due
+        // to the presence of `store` here.
+        assert_eq!(
+            store.0.id(),
+            self.store,
+            "wrong store used to destroy resource"
+        );
+
+        // Implement the reentrance check required by the canonical ABI. Note
+        // that this happens whether or not a destructor is present.
+        //
+        // Note that this should be safe because the raw pointer access in
+        // `flags` is valid due to `store` being the owner of the flags and
+        // flags are never destroyed within the store.
+        if let Some(flags) = self.flags {
+            unsafe {
+                if !flags.may_enter() {
+                    bail!(Trap::CannotEnterComponent);
+                }
+            }
+        }
+
+        let dtor = match self.dtor {
+            Some(dtor) => dtor.as_non_null(),
+            None => return Ok(()),
+        };
+        let mut args = [ValRaw::u32(rep)];
+
+        // This should be safe because `dtor` has been checked to belong to the
+        // `store` provided which means it's valid and still alive. Additionally
+        // destructors have al been previously type-checked and are guaranteed
+        // to take one i32 argument and return no results, so the parameters
+        // here should be configured correctly.
+        unsafe { crate::Func::call_unchecked_raw(store, dtor, args.as_mut_ptr(), args.len()) }
+    }
+}
+
 impl ResourceAny {
     /// Attempts to convert an imported [`Resource`] into [`ResourceAny`].
     /// `idx` is the [`ResourceImportIndex`] returned by [`Linker::resource`].
@@ -658,50 +741,15 @@ impl ResourceAny {
         // `Own` entry which is currently being borrowed.
         let rep = host_resource_tables(store.0).resource_drop(None, self.idx)?;
 
-        let (rep, state) = match (rep, &self.own_state) {
-            (Some(rep), Some(state)) => (rep, state),
+        match (rep, &self.own_state) {
+            (Some(rep), Some(state)) => state.destroy(store, rep),
 
             // A `borrow` was removed from the table and no further
             // destruction, e.g. the destructor, is required so we're done.
-            (None, None) => return Ok(()),
+            (None, None) => Ok(()),
 
             _ => unreachable!(),
-        };
-
-        // Double-check that accessing the raw pointers on `state` are safe due
-        // to the presence of `store` above.
-        assert_eq!(
-            store.0.id(),
-            state.store,
-            "wrong store used to destroy resource"
-        );
-
-        // Implement the reentrance check required by the canonical ABI. Note
-        // that this happens whether or not a destructor is present.
-        //
-        // Note that this should be safe because the raw pointer access in
-        // `flags` is valid due to `store` being the owner of the flags and
-        // flags are never destroyed within the store.
-        if let Some(flags) = state.flags {
-            unsafe {
-                if !flags.may_enter() {
-                    bail!(Trap::CannotEnterComponent);
-                }
-            }
         }
-
-        let dtor = match state.dtor {
-            Some(dtor) => dtor.as_non_null(),
-            None => return Ok(()),
-        };
-        let mut args = [ValRaw::u32(rep)];
-
-        // This should be safe because `dtor` has been checked to belong to the
-        // `store` provided which means it's valid and still alive. Additionally
-        // destructors have al been previously type-checked and are guaranteed
-        // to take one i32 argument and return no results, so the parameters
-        // here should be configured correctly.
-        unsafe { crate::Func::call_unchecked_raw(store, dtor, args.as_mut_ptr(), args.len()) }
     }
 
     fn lower_to_index<U>(&self, cx: &mut LowerContext<'_, U>, ty: InterfaceType) -> Result<u32> {
@@ -2,15 +2,17 @@
 
 use crate::component::matching::InstanceType;
 use crate::component::values::{self, Val};
+use crate::{ExternType, FuncType};
 use anyhow::{anyhow, Result};
 use std::fmt;
 use std::mem;
 use std::ops::Deref;
 use std::sync::Arc;
 use wasmtime_environ::component::{
-    CanonicalAbiInfo, ComponentTypes, InterfaceType, ResourceIndex, TypeEnumIndex, TypeFlagsIndex,
-    TypeListIndex, TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex,
-    TypeTupleIndex, TypeVariantIndex,
+    CanonicalAbiInfo, ComponentTypes, InterfaceType, ResourceIndex, TypeComponentIndex,
+    TypeComponentInstanceIndex, TypeDef, TypeEnumIndex, TypeFlagsIndex, TypeFuncIndex,
+    TypeListIndex, TypeModuleIndex, TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex,
+    TypeResultIndex, TypeTupleIndex, TypeVariantIndex,
 };
 use wasmtime_environ::PrimaryMap;
 
@@ -750,3 +752,187 @@ impl Type {
         }
     }
 }
+
+/// The type of a component function.
+#[derive(Clone, Debug)]
+pub struct ComponentFunc(Handle<TypeFuncIndex>);
+
+impl ComponentFunc {
+    pub(crate) fn from(index: TypeFuncIndex, ty: &InstanceType<'_>) -> Self {
+        ComponentFunc(Handle::new(index, ty))
+    }
+
+    /// Retrieve the types of the parameters of this function in declaration
+    /// order.
+    pub fn params(&self) -> impl ExactSizeIterator<Item = Type> + '_ {
+        let params = self.0.types[self.0.index].params;
+        self.0.types[params]
+            .types
+            .iter()
+            .map(|ty| Type::from(ty, &self.0.instance()))
+    }
+
+    /// Retrieve the types of the results of this function in declaration
+    /// order.
+    pub fn results(&self) -> impl ExactSizeIterator<Item = Type> + '_ {
+        let results = self.0.types[self.0.index].results;
+        self.0.types[results]
+            .types
+            .iter()
+            .map(|ty| Type::from(ty, &self.0.instance()))
+    }
+}
+
+/// The type of a core WebAssembly module within a component.
+#[derive(Clone, Debug)]
+pub struct Module(Handle<TypeModuleIndex>);
+
+impl Module {
+    pub(crate) fn from(index: TypeModuleIndex, ty: &InstanceType<'_>) -> Self {
+        Module(Handle::new(index, ty))
+    }
+
+    /// Retrieve the imports of this module, as pairs of the module and field
+    /// names along with the type of the import.
+    pub fn imports(&self) -> impl ExactSizeIterator<Item = ((&str, &str), ExternType)> + '_ {
+        self.0.types[self.0.index]
+            .imports
+            .iter()
+            .map(|((module, field), ty)| {
+                (
+                    (module.as_str(), field.as_str()),
+                    ExternType::from_wasmtime(self.0.types.module_types(), ty),
+                )
+            })
+    }
+
+    /// Retrieve the exports of this module along with their types.
+    pub fn exports(&self) -> impl ExactSizeIterator<Item = (&str, ExternType)> + '_ {
+        self.0.types[self.0.index].exports.iter().map(|(name, ty)| {
+            (
+                name.as_str(),
+                ExternType::from_wasmtime(self.0.types.module_types(), ty),
+            )
+        })
+    }
+}
+
+/// The type of a component, describing its imports and exports.
+///
+/// This is returned by [`Component::component_type`] and can be used to
+/// inspect a component before it's instantiated.
+///
+/// [`Component::component_type`]: crate::component::Component::component_type
+#[derive(Clone, Debug)]
+pub struct Component(Handle<TypeComponentIndex>);
+
+impl Component {
+    pub(crate) fn from(index: TypeComponentIndex, ty: &InstanceType<'_>) -> Self {
+        Component(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type of the import named `name`, if there is one.
+    pub fn get_import(&self, name: &str) -> Option<ComponentItem> {
+        self.0.types[self.0.index]
+            .imports
+            .get(name)
+            .map(|ty| ComponentItem::from(ty, &self.0.instance()))
+    }
+
+    /// Retrieve the imports of this component along with their types, in
+    /// declaration order.
+    pub fn imports(&self) -> impl ExactSizeIterator<Item = (&str, ComponentItem)> + '_ {
+        self.0.types[self.0.index]
+            .imports
+            .iter()
+            .map(|(name, ty)| (name.as_str(), ComponentItem::from(ty, &self.0.instance())))
+    }
+
+    /// Retrieve the type of the export named `name`, if there is one.
+    pub fn get_export(&self, name: &str) -> Option<ComponentItem> {
+        self.0.types[self.0.index]
+            .exports
+            .get(name)
+            .map(|ty| ComponentItem::from(ty, &self.0.instance()))
+    }
+
+    /// Retrieve the exports of this component along with their types, in
+    /// declaration order.
+    pub fn exports(&self) -> impl ExactSizeIterator<Item = (&str, ComponentItem)> + '_ {
+        self.0.types[self.0.index]
+            .exports
+            .iter()
+            .map(|(name, ty)| (name.as_str(), ComponentItem::from(ty, &self.0.instance())))
+    }
+}
+
+/// The type of a component instance, describing its exports.
+#[derive(Clone, Debug)]
+pub struct ComponentInstance(Handle<TypeComponentInstanceIndex>);
+
+impl ComponentInstance {
+    pub(crate) fn from(index: TypeComponentInstanceIndex, ty: &InstanceType<'_>) -> Self {
+        ComponentInstance(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type of the export named `name`, if there is one.
+    pub fn get_export(&self, name: &str) -> Option<ComponentItem> {
+        self.0.types[self.0.index]
+            .exports
+            .get(name)
+            .map(|ty| ComponentItem::from(ty, &self.0.instance()))
+    }
+
+    /// Retrieve the exports of this instance along with their types, in
+    /// declaration order.
+    pub fn exports(&self) -> impl ExactSizeIterator<Item = (&str, ComponentItem)> + '_ {
+        self.0.types[self.0.index]
+            .exports
+            .iter()
+            .map(|(name, ty)| (name.as_str(), ComponentItem::from(ty, &self.0.instance())))
+    }
+}
+
+/// The type of an item imported or exported by a component or component
+/// instance.
+#[derive(Clone, Debug)]
+pub enum ComponentItem {
+    /// A component function.
+    ComponentFunc(ComponentFunc),
+    /// A core WebAssembly function.
+    CoreFunc(FuncType),
+    /// A core WebAssembly module.
+    Module(Module),
+    /// A component.
+    Component(Component),
+    /// A component instance.
+    ComponentInstance(ComponentInstance),
+    /// An interface type.
+    Type(Type),
+    /// A resource type.
+    ///
+    /// Resources defined by or imported into a component which hasn't been
+    /// instantiated yet are only equal to other resources of the same
+    /// component.
+    Resource(ResourceType),
+}
+
+impl ComponentItem {
+    pub(crate) fn from(def: &TypeDef, ty: &InstanceType<'_>) -> Self {
+        match *def {
+            TypeDef::ComponentFunc(index) => {
+                ComponentItem::ComponentFunc(ComponentFunc::from(index, ty))
+            }
+            TypeDef::CoreFunc(index) => ComponentItem::CoreFunc(FuncType::from_wasm_func_type(
+                ty.types.module_types()[index].clone(),
+            )),
+            TypeDef::Module(index) => ComponentItem::Module(Module::from(index, ty)),
+            TypeDef::Component(index) => ComponentItem::Component(Component::from(index, ty)),
+            TypeDef::ComponentInstance(index) => {
+                ComponentItem::ComponentInstance(ComponentInstance::from(index, ty))
+            }
+            TypeDef::Interface(ref interface) => ComponentItem::Type(Type::from(interface, ty)),
+            TypeDef::Resource(index) => ComponentItem::Resource(ty.resource_type(index)),
+        }
+    }
+}
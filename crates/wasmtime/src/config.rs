@@ -1,10 +1,17 @@
 use crate::memory::MemoryCreator;
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+use crate::pgo::PgoProfile;
 use crate::trampoline::MemoryCreatorProxy;
 use anyhow::{bail, ensure, Result};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-#[cfg(any(feature = "cache", feature = "cranelift", feature = "winch"))]
+#[cfg(any(
+    feature = "cache",
+    feature = "cranelift",
+    feature = "winch",
+    feature = "interpreter"
+))]
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -21,6 +28,8 @@ use crate::stack::{StackCreator, StackCreatorProxy};
 #[cfg(feature = "async")]
 use wasmtime_fiber::RuntimeFiberStackCreator;
 
+#[cfg(feature = "cache")]
+pub use wasmtime_cache::{HttpCacheBackend, ModuleCacheBackend};
 pub use wasmtime_environ::CacheStore;
 pub use wasmtime_runtime::MpkEnabled;
 
@@ -94,13 +103,15 @@ impl std::hash::Hash for ModuleVersionStrategy {
 /// a problematic config may cause `Engine::new` to fail.
 #[derive(Clone)]
 pub struct Config {
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     compiler_config: CompilerConfig,
     profiling_strategy: ProfilingStrategy,
 
     pub(crate) tunables: Tunables,
     #[cfg(feature = "cache")]
     pub(crate) cache_config: CacheConfig,
+    #[cfg(feature = "cache")]
+    cache_remote: Option<(Arc<dyn ModuleCacheBackend>, Arc<[u8]>)>,
     pub(crate) mem_creator: Option<Arc<dyn RuntimeMemoryCreator>>,
     pub(crate) allocation_strategy: InstanceAllocationStrategy,
     pub(crate) max_wasm_stack: usize,
@@ -118,26 +129,30 @@ pub struct Config {
     pub(crate) memory_init_cow: bool,
     pub(crate) memory_guaranteed_dense_image_size: u64,
     pub(crate) force_memory_init_memfd: bool,
+    pub(crate) memory_huge_pages: bool,
     pub(crate) wmemcheck: bool,
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    pub(crate) pgo_profile: PgoProfile,
     pub(crate) coredump_on_trap: bool,
     pub(crate) macos_use_mach_ports: bool,
 }
 
 /// User-provided configuration for the compiler.
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 #[derive(Debug, Clone)]
 struct CompilerConfig {
     strategy: Strategy,
     target: Option<target_lexicon::Triple>,
     settings: HashMap<String, String>,
     flags: HashSet<String>,
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<std::path::PathBuf>,
+    emit_compiled_clif: bool,
     wmemcheck: bool,
 }
 
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 impl CompilerConfig {
     fn new(strategy: Strategy) -> Self {
         Self {
@@ -147,6 +162,7 @@ impl CompilerConfig {
             flags: HashSet::new(),
             cache_store: None,
             clif_dir: None,
+            emit_compiled_clif: false,
             wmemcheck: false,
         }
     }
@@ -171,7 +187,7 @@ impl CompilerConfig {
     }
 }
 
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 impl Default for CompilerConfig {
     fn default() -> Self {
         Self::new(Strategy::Auto)
@@ -184,10 +200,12 @@ impl Config {
     pub fn new() -> Self {
         let mut ret = Self {
             tunables: Tunables::default(),
-            #[cfg(any(feature = "cranelift", feature = "winch"))]
+            #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
             compiler_config: CompilerConfig::default(),
             #[cfg(feature = "cache")]
             cache_config: CacheConfig::new_cache_disabled(),
+            #[cfg(feature = "cache")]
+            cache_remote: None,
             profiling_strategy: ProfilingStrategy::None,
             mem_creator: None,
             allocation_strategy: InstanceAllocationStrategy::OnDemand,
@@ -214,11 +232,14 @@ impl Config {
             memory_init_cow: true,
             memory_guaranteed_dense_image_size: 16 << 20,
             force_memory_init_memfd: false,
+            memory_huge_pages: false,
             wmemcheck: false,
+            #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+            pgo_profile: PgoProfile::new(),
             coredump_on_trap: false,
             macos_use_mach_ports: true,
         };
-        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
         {
             ret.cranelift_debug_verifier(false);
             ret.cranelift_opt_level(OptLevel::Speed);
@@ -249,8 +270,11 @@ impl Config {
     /// # Errors
     ///
     /// This method will error if the given target triple is not supported.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn target(&mut self, target: &str) -> Result<&mut Self> {
         self.compiler_config.target =
             Some(target_lexicon::Triple::from_str(target).map_err(|e| anyhow::anyhow!(e))?);
@@ -857,7 +881,8 @@ impl Config {
     /// Configures whether the WebAssembly component-model [proposal] will
     /// be enabled for compilation.
     ///
-    /// Note that this feature is a work-in-progress and is incomplete.
+    /// Note that this feature is a work-in-progress and is incomplete. It
+    /// isn't supported by [`Strategy::Interpreter`].
     ///
     /// This is `false` by default.
     ///
@@ -875,8 +900,11 @@ impl Config {
     /// and its documentation.
     ///
     /// The default value for this is `Strategy::Auto`.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn strategy(&mut self, strategy: Strategy) -> &mut Self {
         self.compiler_config.strategy = strategy;
         self
@@ -909,8 +937,11 @@ impl Config {
     /// developers of wasmtime itself.
     ///
     /// The default value for this is `false`
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn cranelift_debug_verifier(&mut self, enable: bool) -> &mut Self {
         let val = if enable { "true" } else { "false" };
         self.compiler_config
@@ -926,8 +957,11 @@ impl Config {
     /// more information see the documentation of [`OptLevel`].
     ///
     /// The default value for this is `OptLevel::None`.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn cranelift_opt_level(&mut self, level: OptLevel) -> &mut Self {
         let val = match level {
             OptLevel::None => "none",
@@ -948,8 +982,11 @@ impl Config {
     /// This is not required by the WebAssembly spec, so it is not enabled by default.
     ///
     /// The default value for this is `false`
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn cranelift_nan_canonicalization(&mut self, enable: bool) -> &mut Self {
         let val = if enable { "true" } else { "false" };
         self.compiler_config
@@ -971,8 +1008,11 @@ impl Config {
     /// solvers or logic engines to verify, but only a linear pass
     /// over a trail of "breadcrumbs" or facts at each intermediate
     /// value. Thus, it is appropriate to enable in production.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn cranelift_pcc(&mut self, enable: bool) -> &mut Self {
         let val = if enable { "true" } else { "false" };
         self.compiler_config
@@ -981,6 +1021,94 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift inlines calls between functions defined
+    /// in the same module.
+    ///
+    /// When enabled all functions of a module are translated before any of
+    /// them are compiled, and direct calls to small functions which don't
+    /// themselves make any calls are replaced with a copy of the callee's
+    /// body. This removes the call overhead of tiny helpers such as getters
+    /// which toolchains frequently emit as standalone functions. Calls to
+    /// imported functions, `call_indirect`, and `call_ref` are never
+    /// inlined.
+    ///
+    /// Inlined functions don't show up as their own frames in
+    /// [`WasmBacktrace`](crate::WasmBacktrace)s, and inlining is always
+    /// disabled when [`Config::debug_info`] or [`Config::cranelift_pcc`] are
+    /// enabled.
+    ///
+    /// This is only supported by Cranelift, so [`Engine::new`] fails if it's
+    /// enabled together with [`Strategy::Winch`].
+    ///
+    /// The default value for this is `false`.
+    ///
+    /// [`Engine::new`]: crate::Engine::new
+    #[cfg(feature = "cranelift")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))]
+    pub fn cranelift_inlining(&mut self, enable: bool) -> &mut Self {
+        let val = if enable { "true" } else { "false" };
+        self.compiler_config
+            .settings
+            .insert("wasmtime_inlining".to_string(), val.to_string());
+        self
+    }
+
+    /// Configures whether compiled code is instrumented to collect an
+    /// execution profile for profile-guided optimization.
+    ///
+    /// When enabled every function entry and every conditional branch (`if`
+    /// and `br_if`) of a compiled module is counted. The counts of a module
+    /// are shared by all of its instances and can be retrieved at any time
+    /// with [`Module::pgo_profile`](crate::Module::pgo_profile), or with
+    /// [`Component::pgo_profile`](crate::component::Component::pgo_profile)
+    /// for the core modules inside of a component. The resulting
+    /// [`PgoProfile`] can then be passed to [`Config::pgo_profile`] to
+    /// optimize the module for how it was actually executed.
+    ///
+    /// Instrumentation adds a call into the runtime to every function entry
+    /// and conditional branch, so instrumented code runs significantly slower
+    /// and is only meant to be used for collecting profiles with
+    /// representative workloads. Only Cranelift supports instrumentation.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
+    pub fn pgo_instrumentation(&mut self, enable: bool) -> &mut Self {
+        self.tunables.pgo_instrumentation = enable;
+        self
+    }
+
+    /// Configures an execution profile, previously collected with
+    /// [`Config::pgo_instrumentation`], to guide the optimization of modules
+    /// compiled with this configuration.
+    ///
+    /// Modules are matched up with their counts by a hash of their
+    /// WebAssembly bytes, and modules that aren't part of `profile` are
+    /// compiled as usual. For the modules that are, Cranelift lays out blocks
+    /// which were rarely reached at the end of the function, away from the
+    /// hot path, and when [`Config::cranelift_inlining`] is enabled inlines
+    /// larger callees that were called frequently while skipping functions
+    /// which never ran.
+    ///
+    /// Profiles don't influence register allocation: the register allocator
+    /// weighs spills by loop depth and has no way to take execution counts
+    /// into account, although moving cold blocks out of line already keeps
+    /// their spills and reloads off the hot path.
+    ///
+    /// By default no profile is used.
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
+    pub fn pgo_profile(&mut self, profile: PgoProfile) -> &mut Self {
+        self.pgo_profile = profile;
+        self
+    }
+
     /// Allows setting a Cranelift boolean flag or preset. This allows
     /// fine-tuning of Cranelift settings.
     ///
@@ -997,8 +1125,11 @@ impl Config {
     /// The validation of the flags are deferred until the engine is being built, and thus may
     /// cause `Engine::new` fail if the flag's name does not exist, or the value is not appropriate
     /// for the flag type.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub unsafe fn cranelift_flag_enable(&mut self, flag: &str) -> &mut Self {
         self.compiler_config.flags.insert(flag.to_string());
         self
@@ -1023,8 +1154,11 @@ impl Config {
     ///
     /// For example, feature `wasm_backtrace` will set `unwind_info` to `true`, but if it's
     /// manually set to false then it will fail.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub unsafe fn cranelift_flag_set(&mut self, name: &str, value: &str) -> &mut Self {
         self.compiler_config
             .settings
@@ -1053,7 +1187,7 @@ impl Config {
     #[cfg(feature = "cache")]
     #[cfg_attr(nightlydoc, doc(cfg(feature = "cache")))]
     pub fn cache_config_load(&mut self, path: impl AsRef<Path>) -> Result<&mut Self> {
-        self.cache_config = CacheConfig::from_file(Some(path.as_ref()))?;
+        self.set_cache_config(CacheConfig::from_file(Some(path.as_ref()))?);
         Ok(self)
     }
 
@@ -1098,10 +1232,54 @@ impl Config {
     #[cfg(feature = "cache")]
     #[cfg_attr(nightlydoc, doc(cfg(feature = "cache")))]
     pub fn cache_config_load_default(&mut self) -> Result<&mut Self> {
-        self.cache_config = CacheConfig::from_file(None)?;
+        self.set_cache_config(CacheConfig::from_file(None)?);
         Ok(self)
     }
 
+    /// Shares compiled modules between hosts through the `remote` store.
+    ///
+    /// When caching is enabled, for example with
+    /// [`Config::cache_config_load`], modules that aren't found in the local
+    /// cache directory are looked up in `remote` before being compiled, and
+    /// every module that's compiled is uploaded to it.
+    ///
+    /// Modules are stored with an HMAC keyed by `secret`, which must be the
+    /// same on every host sharing `remote`. A module read from `remote` whose
+    /// HMAC doesn't match, because it's corrupted or wasn't written by a host
+    /// knowing the secret, is recompiled rather than used. Every host knowing
+    /// the secret must be trusted, as it can make the others run arbitrary
+    /// native code, and `remote` itself can read the compiled code of every
+    /// module.
+    ///
+    /// Uploads happen synchronously once a module is compiled, so a slow
+    /// `remote` delays every compilation that misses the local cache.
+    ///
+    /// This takes precedence over a `remote-url` in the cache configuration
+    /// file, and has no effect when caching is disabled. An
+    /// [`HttpCacheBackend`] can be used to store modules on an HTTP server.
+    ///
+    /// This method is only available when the `cache` feature of this crate is
+    /// enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "cache")))]
+    pub fn cache_remote(
+        &mut self,
+        remote: Arc<dyn ModuleCacheBackend>,
+        secret: &[u8],
+    ) -> &mut Self {
+        self.cache_config.set_remote(remote.clone(), secret);
+        self.cache_remote = Some((remote, secret.into()));
+        self
+    }
+
+    #[cfg(feature = "cache")]
+    fn set_cache_config(&mut self, mut cache_config: CacheConfig) {
+        if let Some((remote, secret)) = &self.cache_remote {
+            cache_config.set_remote(remote.clone(), secret);
+        }
+        self.cache_config = cache_config;
+    }
+
     /// Sets a custom memory creator.
     ///
     /// Custom memory creators are used when creating host `Memory` objects or when
@@ -1520,6 +1698,10 @@ impl Config {
     /// Configures whether or not a coredump should be generated and attached to
     /// the anyhow::Error when a trap is raised.
     ///
+    /// The values of the locals of each frame are only captured when
+    /// [`Config::debug_info`] is also enabled, since they're recovered with the
+    /// help of the compiler's debug information.
+    ///
     /// This option is disabled by default.
     #[cfg(feature = "coredump")]
     #[cfg_attr(nightlydoc, doc(cfg(feature = "coredump")))]
@@ -1531,7 +1713,7 @@ impl Config {
     /// Enables memory error checking for wasm programs.
     ///
     /// This option is disabled by default.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub fn wmemcheck(&mut self, enable: bool) -> &mut Self {
         self.wmemcheck = enable;
         self.compiler_config.wmemcheck = enable;
@@ -1578,6 +1760,39 @@ impl Config {
         self
     }
 
+    /// Configures whether linear memories are backed by transparent huge
+    /// pages.
+    ///
+    /// Memory-intensive guests can spend a significant amount of time
+    /// resolving TLB misses, which huge pages reduce by covering more memory
+    /// with each TLB entry. When this option is enabled Wasmtime asks the
+    /// operating system to back linear memories with huge pages, both for
+    /// memories allocated on-demand and for the slots of the
+    /// [pooling allocator](crate::PoolingAllocationConfig).
+    ///
+    /// Huge pages are only a hint and the operating system may still use
+    /// regular pages, for example when huge pages are disabled, when the
+    /// system is low on contiguous physical memory, or for pages which hold a
+    /// [copy-on-write memory image](Config::memory_init_cow). Wasmtime falls
+    /// back to regular pages without reporting an error in these cases.
+    ///
+    /// Note that huge pages can increase resident memory, since a guest
+    /// touching a single byte may fault in an entire huge page. When used with
+    /// [`PoolingAllocationConfig::linear_memory_keep_resident`] the amount
+    /// kept resident is rounded up to a multiple of the huge page size so
+    /// that resetting a slot doesn't break up a huge page.
+    ///
+    /// Custom memories created with [`Config::with_host_memory`] are not
+    /// affected by this option.
+    ///
+    /// This option is only supported on Linux, via `madvise(MADV_HUGEPAGE)`,
+    /// and has no effect on other platforms. By default this option is
+    /// disabled.
+    pub fn memory_huge_pages(&mut self, enable: bool) -> &mut Self {
+        self.memory_huge_pages = enable;
+        self
+    }
+
     pub(crate) fn validate(&self) -> Result<()> {
         if self.features.reference_types && !self.features.bulk_memory {
             bail!("feature 'reference_types' requires 'bulk_memory' to be enabled");
@@ -1619,6 +1834,7 @@ impl Config {
                     self.mem_creator.clone(),
                     stack_size,
                 ));
+                allocator.set_huge_pages(self.memory_huge_pages);
                 #[cfg(feature = "async")]
                 if let Some(stack_creator) = &self.stack_creator {
                     allocator.set_stack_creator(stack_creator.clone());
@@ -1629,6 +1845,7 @@ impl Config {
             InstanceAllocationStrategy::Pooling(config) => {
                 let mut config = config.config;
                 config.stack_size = stack_size;
+                config.linear_memory_huge_pages = self.memory_huge_pages;
                 Ok(Box::new(wasmtime_runtime::PoolingInstanceAllocator::new(
                     &config,
                     &self.tunables,
@@ -1646,13 +1863,18 @@ impl Config {
         })
     }
 
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn build_compiler(mut self) -> Result<(Self, Box<dyn wasmtime_environ::Compiler>)> {
         let mut compiler = match self.compiler_config.strategy {
             #[cfg(feature = "cranelift")]
             Strategy::Auto => wasmtime_cranelift::builder(),
             #[cfg(all(feature = "winch", not(feature = "cranelift")))]
             Strategy::Auto => wasmtime_winch::builder(),
+            #[cfg(all(
+                feature = "interpreter",
+                not(any(feature = "cranelift", feature = "winch"))
+            ))]
+            Strategy::Auto => wasmtime_interpreter::builder(),
             #[cfg(feature = "cranelift")]
             Strategy::Cranelift => wasmtime_cranelift::builder(),
             #[cfg(not(feature = "cranelift"))]
@@ -1661,6 +1883,10 @@ impl Config {
             Strategy::Winch => wasmtime_winch::builder(),
             #[cfg(not(feature = "winch"))]
             Strategy::Winch => bail!("winch support not compiled in"),
+            #[cfg(feature = "interpreter")]
+            Strategy::Interpreter => wasmtime_interpreter::builder(),
+            #[cfg(not(feature = "interpreter"))]
+            Strategy::Interpreter => bail!("interpreter support not compiled in"),
         };
 
         if let Some(target) = &self.compiler_config.target {
@@ -1669,6 +1895,7 @@ impl Config {
 
         if let Some(path) = &self.compiler_config.clif_dir {
             compiler.clif_dir(path)?;
+            compiler.emit_compiled_clif(self.compiler_config.emit_compiled_clif);
         }
 
         // If probestack is enabled for a target, Wasmtime will always use the
@@ -1744,6 +1971,46 @@ impl Config {
             bail!("cannot disable the simd proposal but enable the relaxed simd proposal");
         }
 
+        let uses_winch = match self.compiler_config.strategy {
+            Strategy::Auto => !cfg!(feature = "cranelift"),
+            Strategy::Cranelift => false,
+            Strategy::Winch => true,
+            Strategy::Interpreter => false,
+        };
+        if self.tunables.pgo_instrumentation && uses_winch {
+            bail!("profile-guided optimization instrumentation is not supported by winch");
+        }
+        if uses_winch
+            && self
+                .compiler_config
+                .settings
+                .get("wasmtime_inlining")
+                .is_some_and(|s| s == "true")
+        {
+            bail!("inlining is not supported by winch");
+        }
+
+        // Functions compiled for the interpreter are run differently than
+        // native code, which needs to be known when loading them.
+        self.tunables.interpreter = match self.compiler_config.strategy {
+            Strategy::Auto => !cfg!(any(feature = "cranelift", feature = "winch")),
+            Strategy::Interpreter => true,
+            Strategy::Cranelift | Strategy::Winch => false,
+        };
+        if self.tunables.interpreter {
+            let unsupported = [
+                (self.features.memory64, "memory64"),
+                (self.features.tail_call, "tail calls"),
+                (self.features.function_references, "function references"),
+                (self.features.component_model, "component model"),
+            ];
+            for (enabled, proposal) in unsupported {
+                if enabled {
+                    bail!("the {proposal} proposal is not supported by the interpreter");
+                }
+            }
+        }
+
         // Apply compiler settings and flags
         for (k, v) in self.compiler_config.settings.iter() {
             compiler.set(k, v)?;
@@ -1772,12 +2039,26 @@ impl Config {
     }
 
     /// Enables clif output when compiling a WebAssembly module.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    ///
+    /// For each WebAssembly function `N` compiled with Cranelift the
+    /// directory `path` receives `wasm_func_N.clif` with the function's CLIF
+    /// as translated from WebAssembly.
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub fn emit_clif(&mut self, path: &Path) -> &mut Self {
         self.compiler_config.clif_dir = Some(path.to_path_buf());
         self
     }
 
+    /// Internal setting for whether [`Config::emit_clif`] also writes
+    /// `wasm_func_N.opt.clif` and `wasm_func_N.vcode` with the optimized CLIF
+    /// and the VCode of each function, as used by the compiler explorer.
+    #[doc(hidden)]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    pub fn emit_compiled_clif(&mut self, enable: bool) -> &mut Self {
+        self.compiler_config.emit_compiled_clif = enable;
+        self
+    }
+
     /// Configures whether, when on macOS, Mach ports are used for exception
     /// handling instead of traditional Unix-based signal handling.
     ///
@@ -1858,7 +2139,7 @@ impl fmt::Debug for Config {
                 &self.tunables.guard_before_linear_memory,
             )
             .field("parallel_compilation", &self.parallel_compilation);
-        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
         {
             f.field("compiler_config", &self.compiler_config);
         }
@@ -1890,6 +2171,29 @@ pub enum Strategy {
     /// A baseline compiler for WebAssembly, currently under active development and not ready for
     /// production applications.
     Winch,
+
+    /// Wasmtime's portable interpreter, which compiles WebAssembly to a
+    /// compact bytecode instead of native code and executes it with an
+    /// interpreter loop.
+    ///
+    /// This doesn't require generating executable code at runtime, at the
+    /// cost of running much slower than native code. Traps don't have wasm
+    /// backtraces.
+    ///
+    /// Creating an [`Engine`](crate::Engine) fails if the memory64, tail
+    /// call, function references or component model proposals are enabled,
+    /// or if native debug information, NaN canonicalization or incremental
+    /// compilation are requested. Modules using SIMD, `externref`, atomics,
+    /// shared memories or more than one memory fail to compile.
+    ///
+    /// Components can't be run by the interpreter: lifting and lowering
+    /// values between components and the host relies on trampolines compiled
+    /// to native code, which the interpreter doesn't have an equivalent of.
+    ///
+    /// Settings passed to [`Config::cranelift_flag_set`] and
+    /// [`Config::cranelift_flag_enable`] are rejected, except for those
+    /// which Wasmtime configures itself, which have no effect.
+    Interpreter,
 }
 
 /// Possible optimization levels for the Cranelift codegen backend.
@@ -2352,6 +2656,16 @@ impl PoolingAllocationConfig {
     /// The reservation size of each linear memory is controlled by the
     /// `static_memory_maximum_size` setting and this value cannot exceed the
     /// configured static memory maximum size.
+    ///
+    /// When the [memory64 proposal](Config::wasm_memory64) is enabled this
+    /// value may exceed the 65536 pages that a 32-bit memory is limited to, in
+    /// which case each slot in the pool reserves enough address space for a
+    /// memory of `memory_pages` pages followed by the static memory guard
+    /// region. 64-bit memories whose maximum exceeds the static memory
+    /// maximum size are dynamically bounds-checked, so pools intended only for
+    /// 64-bit memories can shrink the reservation of each slot by lowering
+    /// [`Config::static_memory_maximum_size`] and
+    /// [`Config::static_memory_guard_size`].
     pub fn memory_pages(&mut self, pages: u64) -> &mut Self {
         self.config.limits.memory_pages = pages;
         self
@@ -5,6 +5,10 @@ use crate::{
     Val, ValType, WasmBacktrace,
 };
 
+mod read;
+
+pub use self::read::*;
+
 /// Representation of a core dump of a WebAssembly module
 ///
 /// When the Config::coredump_on_trap option is enabled this structure is
@@ -17,7 +21,9 @@ use crate::{
 ///
 /// Note that some state, such as Wasm locals or values on the operand stack,
 /// may be optimized away by the compiler or otherwise not recovered in the
-/// coredump.
+/// coredump. Locals and operands are only recovered for modules compiled with
+/// [`Config::debug_info`][crate::Config::debug_info] enabled, and only when
+/// they're held in a stack slot at the point the frame was executing.
 ///
 /// Capturing of wasm coredumps can be configured through the
 /// [`Config::coredump_on_trap`][crate::Config::coredump_on_trap] method.
@@ -33,15 +39,41 @@ pub struct WasmCoreDump {
     instances: Vec<Instance>,
     memories: Vec<Memory>,
     globals: Vec<Global>,
+    #[cfg(feature = "component-model")]
+    component_instances: Vec<crate::component::Instance>,
     backtrace: WasmBacktrace,
+    locals: Vec<Vec<CoreDumpValue>>,
+    operand_stacks: Vec<Vec<CoreDumpValue>>,
 }
 
 impl WasmCoreDump {
-    pub(crate) fn new(store: &mut StoreOpaque, backtrace: WasmBacktrace) -> WasmCoreDump {
+    pub(crate) fn new(
+        store: &mut StoreOpaque,
+        backtrace: WasmBacktrace,
+        locals: Vec<Vec<wasm_encoder::CoreDumpValue>>,
+        operand_stacks: Vec<Vec<wasm_encoder::CoreDumpValue>>,
+    ) -> WasmCoreDump {
         let modules: Vec<_> = store.modules().all_modules().cloned().collect();
         let instances: Vec<Instance> = store.all_instances().collect();
         let store_memories: Vec<Memory> = store.all_memories().collect();
         let store_globals: Vec<Global> = store.all_globals().collect();
+        let convert = |frames: Vec<Vec<wasm_encoder::CoreDumpValue>>| -> Vec<Vec<_>> {
+            frames
+                .into_iter()
+                .map(|values| {
+                    values
+                        .into_iter()
+                        .map(|value| match value {
+                            wasm_encoder::CoreDumpValue::Missing => CoreDumpValue::Missing,
+                            wasm_encoder::CoreDumpValue::I32(x) => CoreDumpValue::I32(x),
+                            wasm_encoder::CoreDumpValue::I64(x) => CoreDumpValue::I64(x),
+                            wasm_encoder::CoreDumpValue::F32(x) => CoreDumpValue::F32(x.to_bits()),
+                            wasm_encoder::CoreDumpValue::F64(x) => CoreDumpValue::F64(x.to_bits()),
+                        })
+                        .collect()
+                })
+                .collect()
+        };
 
         WasmCoreDump {
             name: String::from("store_name"),
@@ -49,7 +81,11 @@ impl WasmCoreDump {
             instances,
             memories: store_memories,
             globals: store_globals,
+            #[cfg(feature = "component-model")]
+            component_instances: store.all_component_instances().to_vec(),
             backtrace,
+            locals: convert(locals),
+            operand_stacks: convert(operand_stacks),
         }
     }
 
@@ -61,6 +97,38 @@ impl WasmCoreDump {
         self.backtrace.frames()
     }
 
+    /// The values of the locals, including parameters, of each stack frame.
+    ///
+    /// The returned slice is parallel to [`WasmCoreDump::frames`]. A frame's
+    /// locals are empty if its function wasn't compiled with
+    /// [`Config::debug_info`][crate::Config::debug_info], and otherwise any
+    /// local that couldn't be recovered is [`CoreDumpValue::Missing`].
+    ///
+    /// Values are only recovered from the stack slots of the frame, as the
+    /// registers at the trap site aren't saved. A local which the compiler
+    /// kept in a register at the point the frame was executing, which is
+    /// common for the youngest frame, is therefore
+    /// [`CoreDumpValue::Missing`] even though it was live.
+    pub fn frame_locals(&self) -> &[Vec<CoreDumpValue>] {
+        &self.locals
+    }
+
+    /// The values on the operand stack of each stack frame, from the bottom of
+    /// the stack to its top.
+    ///
+    /// The returned slice is parallel to [`WasmCoreDump::frames`]. The stack is
+    /// the one before the frame's current instruction executed, so for a frame
+    /// that's calling another function it includes the call's arguments. Like
+    /// with [`WasmCoreDump::frame_locals`], a frame's stack is empty if its
+    /// function wasn't compiled with debug info, and otherwise any value that
+    /// couldn't be recovered is [`CoreDumpValue::Missing`]. That includes
+    /// every value held in a register rather than a stack slot, so operands
+    /// are more likely to be recovered for frames suspended at a call than
+    /// for the frame which trapped.
+    pub fn frame_operand_stacks(&self) -> &[Vec<CoreDumpValue>] {
+        &self.operand_stacks
+    }
+
     /// All modules instantiated inside the store when the core dump was
     /// created.
     pub fn modules(&self) -> &[Module] {
@@ -84,6 +152,16 @@ impl WasmCoreDump {
         self.memories.as_ref()
     }
 
+    /// All component instances within the store when the core dump was
+    /// created.
+    ///
+    /// The core instances that make up each component instance are also
+    /// included in [`WasmCoreDump::instances`].
+    #[cfg(feature = "component-model")]
+    pub fn component_instances(&self) -> &[crate::component::Instance] {
+        self.component_instances.as_ref()
+    }
+
     /// Serialize this core dump into [the standard core dump binary
     /// format][spec].
     ///
@@ -93,6 +171,7 @@ impl WasmCoreDump {
     /// Once serialized, you can write this core dump to disk, send it over the
     /// network, or pass it to other debugging tools that consume Wasm core
     /// dumps.
+    /// It can be read back in with [`CoreDumpFile::parse`].
     ///
     /// [spec]: https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
     pub fn serialize(&self, mut store: impl AsContextMut, name: &str) -> Vec<u8> {
@@ -246,10 +325,120 @@ impl WasmCoreDump {
             core_dump.section(&instances);
         }
 
+        // Component instances have no representation in the standard core
+        // dump format, so they're recorded in a custom section of our own
+        // describing each component instance, including the ones nested
+        // within others. See `COMPONENT_INSTANCES_SECTION` for its encoding.
+        #[cfg(feature = "component-model")]
+        if !self.component_instances.is_empty() {
+            use wasm_encoder::Encode;
+            use wasmtime_environ::component::{Export, RuntimeComponentInstanceIndex};
+
+            fn encode_exports<'a>(
+                exports: impl ExactSizeIterator<Item = (&'a String, &'a Export)>,
+                data: &mut Vec<u8>,
+            ) {
+                exports.len().encode(data);
+                for (name, export) in exports {
+                    name.as_str().encode(data);
+                    match export {
+                        Export::LiftedFunction { .. } => data.push(0x00),
+                        Export::ModuleStatic(_) | Export::ModuleImport(_) => data.push(0x01),
+                        Export::Instance(exports) => {
+                            data.push(0x02);
+                            encode_exports(exports.iter(), data);
+                        }
+                        Export::Type(_) => data.push(0x03),
+                    }
+                }
+            }
+
+            fn encode_option<T>(
+                value: Option<T>,
+                data: &mut Vec<u8>,
+                f: impl FnOnce(T, &mut Vec<u8>),
+            ) {
+                match value {
+                    Some(value) => {
+                        data.push(0x01);
+                        f(value, data);
+                    }
+                    None => data.push(0x00),
+                }
+            }
+
+            let instance_to_index: HashMap<_, _> = self
+                .instances()
+                .iter()
+                .enumerate()
+                .map(|(i, instance)| (instance.id(&store.0), i as u32))
+                .collect();
+            let mut count = 0u32;
+            let mut data = Vec::new();
+            for instance in self.component_instances.iter() {
+                let component = instance.env_component(&store.0);
+                let core_instances = instance
+                    .core_instances(&store.0)
+                    .filter_map(|(i, core)| {
+                        // Adapter modules aren't instantiated by any component,
+                        // so they're attributed to the root one.
+                        let owner = component
+                            .and_then(|c| c.runtime_instance_owners.get(i).copied().flatten())
+                            .unwrap_or(RuntimeComponentInstanceIndex::from_u32(0));
+                        Some((owner, *instance_to_index.get(&core.id(&store.0))?))
+                    })
+                    .collect::<Vec<_>>();
+
+                // An instance whose instantiation didn't finish is described
+                // as a single anonymous component instance.
+                let root = wasmtime_environ::component::RuntimeComponentInstanceInfo {
+                    name: None,
+                    parent: None,
+                };
+                let infos = match component {
+                    Some(c) => c.runtime_component_instances.iter().collect(),
+                    None => vec![(RuntimeComponentInstanceIndex::from_u32(0), &root)],
+                };
+
+                let base = count;
+                for (index, info) in infos {
+                    data.push(0x00);
+                    encode_option(info.name.as_ref(), &mut data, |name, data| {
+                        name.as_str().encode(data)
+                    });
+                    encode_option(info.parent, &mut data, |parent, data| {
+                        (base + parent.as_u32()).encode(data)
+                    });
+                    core_instances
+                        .iter()
+                        .filter(|(owner, _)| *owner == index)
+                        .map(|(_, i)| *i)
+                        .collect::<Vec<_>>()
+                        .encode(&mut data);
+                    // Only the exports of the root component are known, which
+                    // include any nested instances it exports.
+                    match component {
+                        Some(c) if info.parent.is_none() => {
+                            encode_exports(c.exports.iter(), &mut data)
+                        }
+                        _ => 0u32.encode(&mut data),
+                    }
+                    count += 1;
+                }
+            }
+            let mut section = Vec::new();
+            count.encode(&mut section);
+            section.extend(data);
+            core_dump.section(&wasm_encoder::CustomSection {
+                name: COMPONENT_INSTANCES_SECTION.into(),
+                data: section.into(),
+            });
+        }
+
         {
             let thread_name = "main";
             let mut stack = wasm_encoder::CoreDumpStackSection::new(thread_name);
-            for frame in self.frames() {
+            for (i, frame) in self.frames().iter().enumerate() {
                 // This isn't necessarily the right instance if there are
                 // multiple instances of the same module. See comment above
                 // `module_to_instance` for details.
@@ -262,10 +451,27 @@ impl WasmCoreDump {
                     .and_then(|o| u32::try_from(o).ok())
                     .unwrap_or(0);
 
-                // We can't currently recover locals and the operand stack. We
-                // should eventually be able to do that with Winch though.
-                let locals = [];
-                let operand_stack = [];
+                let encode = |frames: &[Vec<CoreDumpValue>]| {
+                    frames
+                        .get(i)
+                        .into_iter()
+                        .flatten()
+                        .map(|value| match *value {
+                            CoreDumpValue::I32(x) => wasm_encoder::CoreDumpValue::I32(x),
+                            CoreDumpValue::I64(x) => wasm_encoder::CoreDumpValue::I64(x),
+                            CoreDumpValue::F32(x) => {
+                                wasm_encoder::CoreDumpValue::F32(f32::from_bits(x))
+                            }
+                            CoreDumpValue::F64(x) => {
+                                wasm_encoder::CoreDumpValue::F64(f64::from_bits(x))
+                            }
+                            // The core dump format can't encode any other values.
+                            _ => wasm_encoder::CoreDumpValue::Missing,
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let locals = encode(&self.locals);
+                let operand_stack = encode(&self.operand_stacks);
 
                 stack.frame(instance, func, offset, locals, operand_stack);
             }
@@ -0,0 +1,533 @@
+use crate::{FrameInfo, Module};
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::ops::Range;
+use wasmparser::{
+    BinaryReader, ConstExpr, CoreDumpInstancesSection, CoreDumpModulesSection, CoreDumpSection,
+    CoreDumpStackSection, DataKind, FromReader, Operator, Parser, Payload,
+};
+
+const WASM_PAGE_SIZE: u64 = 0x10000;
+
+/// The name of the custom section recording the component instances of a
+/// core dump, which the standard format has no representation for.
+///
+/// It's encoded as a vector of component instances, including nested ones,
+/// where each is encoded as:
+///
+/// ```text
+/// instance ::= 0x00 name:option(string) parent:option(u32)
+///              core_instances:vec(u32) exports:vec(export)
+/// export   ::= name:string 0x00                    (function)
+///            | name:string 0x01                    (module)
+///            | name:string 0x02 exports:vec(export) (instance)
+///            | name:string 0x03                    (type)
+/// option(x) ::= 0x00 | 0x01 x
+/// ```
+///
+/// The parent is the index of the component instance which instantiated this
+/// one in this section, and the core instances are indices in the
+/// `coreinstances` section.
+pub(crate) const COMPONENT_INSTANCES_SECTION: &str = "wasmtime-componentinstances";
+
+/// A wasm core dump read back from [the standard core dump binary
+/// format][spec], such as one written by [`WasmCoreDump::serialize`].
+///
+/// Unlike [`WasmCoreDump`] this isn't tied to a live store, so it can be used
+/// to inspect core dumps after the fact, for example ones collected from
+/// crashes in production. Frames can be symbolicated with
+/// [`CoreDumpFrame::symbolize`] given the original module they were running.
+///
+/// [spec]: https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+/// [`WasmCoreDump`]: crate::WasmCoreDump
+/// [`WasmCoreDump::serialize`]: crate::WasmCoreDump::serialize
+#[derive(Debug)]
+pub struct CoreDumpFile {
+    name: String,
+    modules: Vec<String>,
+    instances: Vec<CoreDumpInstance>,
+    memories: Vec<CoreDumpMemory>,
+    globals: Vec<CoreDumpValue>,
+    threads: Vec<CoreDumpThread>,
+    component_instances: Vec<CoreDumpComponentInstance>,
+}
+
+impl CoreDumpFile {
+    /// Parses a core dump from its binary format.
+    ///
+    /// Returns an error if `bytes` isn't a well-formed core dump.
+    pub fn parse(bytes: &[u8]) -> Result<CoreDumpFile> {
+        let mut name = None;
+        let mut dump = CoreDumpFile {
+            name: String::new(),
+            modules: Vec::new(),
+            instances: Vec::new(),
+            memories: Vec::new(),
+            globals: Vec::new(),
+            threads: Vec::new(),
+            component_instances: Vec::new(),
+        };
+
+        for payload in Parser::new(0).parse_all(bytes) {
+            match payload? {
+                Payload::MemorySection(reader) => {
+                    for ty in reader {
+                        let size = ty?
+                            .initial
+                            .checked_mul(WASM_PAGE_SIZE)
+                            .context("memory size overflows")?;
+                        dump.memories.push(CoreDumpMemory {
+                            size,
+                            segments: Vec::new(),
+                        });
+                    }
+                }
+                Payload::GlobalSection(reader) => {
+                    for global in reader {
+                        dump.globals.push(eval_const_expr(&global?.init_expr)?);
+                    }
+                }
+                Payload::DataSection(reader) => {
+                    for data in reader {
+                        let data = data?;
+                        let DataKind::Active {
+                            memory_index,
+                            offset_expr,
+                        } = data.kind
+                        else {
+                            continue;
+                        };
+                        let offset = match eval_const_expr(&offset_expr)? {
+                            CoreDumpValue::I32(offset) => u64::from(offset as u32),
+                            CoreDumpValue::I64(offset) => offset as u64,
+                            _ => bail!("invalid offset for data segment"),
+                        };
+                        let memory = dump
+                            .memories
+                            .get_mut(memory_index as usize)
+                            .ok_or_else(|| anyhow!("data segment for unknown memory"))?;
+                        match offset.checked_add(data.data.len() as u64) {
+                            Some(end) if end <= memory.size => {}
+                            _ => bail!("data segment out of bounds of its memory"),
+                        }
+                        memory.segments.push((offset, data.data.to_vec()));
+                    }
+                }
+                Payload::CustomSection(section) => {
+                    let mut reader =
+                        BinaryReader::new_with_offset(section.data(), section.data_offset());
+                    match section.name() {
+                        "core" => {
+                            name = Some(CoreDumpSection::from_reader(&mut reader)?.name);
+                        }
+                        "coremodules" => {
+                            let section = CoreDumpModulesSection::from_reader(&mut reader)?;
+                            dump.modules
+                                .extend(section.modules.iter().map(|m| m.to_string()));
+                        }
+                        "coreinstances" => {
+                            let section = CoreDumpInstancesSection::from_reader(&mut reader)?;
+                            dump.instances
+                                .extend(section.instances.into_iter().map(|i| CoreDumpInstance {
+                                    module: i.module_index,
+                                    memories: i.memories,
+                                    globals: i.globals,
+                                }));
+                        }
+                        "corestack" => {
+                            let section = CoreDumpStackSection::from_reader(&mut reader)?;
+                            dump.threads.push(CoreDumpThread {
+                                name: section.name.to_string(),
+                                frames: section
+                                    .frames
+                                    .into_iter()
+                                    .map(|f| CoreDumpFrame {
+                                        instance: f.instanceidx,
+                                        func_index: f.funcidx,
+                                        func_offset: f.codeoffset,
+                                        locals: f.locals.iter().map(convert_value).collect(),
+                                        stack: f.stack.iter().map(convert_value).collect(),
+                                    })
+                                    .collect(),
+                            });
+                        }
+                        COMPONENT_INSTANCES_SECTION => {
+                            for _ in 0..reader.read_var_u32()? {
+                                if reader.read_u8()? != 0x00 {
+                                    bail!("invalid component instance encoding");
+                                }
+                                let name =
+                                    read_option(&mut reader, |r| Ok(r.read_string()?.to_string()))?;
+                                let parent = read_option(&mut reader, |r| r.read_var_u32())?;
+                                let core_instances = (0..reader.read_var_u32()?)
+                                    .map(|_| reader.read_var_u32())
+                                    .collect::<Result<_, _>>()?;
+                                let exports = read_component_exports(&mut reader)?;
+                                dump.component_instances.push(CoreDumpComponentInstance {
+                                    name,
+                                    parent,
+                                    core_instances,
+                                    exports,
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        dump.name = name
+            .context("not a wasm core dump: missing `core` custom section")?
+            .to_string();
+        Ok(dump)
+    }
+
+    /// The name of the "main" Wasm service or executable that was running
+    /// when the core dump was created.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of all modules that were instantiated when the core dump was
+    /// created.
+    ///
+    /// Instances refer to these by index.
+    pub fn modules(&self) -> &[String] {
+        &self.modules
+    }
+
+    /// All instances within the store when the core dump was created.
+    ///
+    /// Frames refer to these by index.
+    pub fn instances(&self) -> &[CoreDumpInstance] {
+        &self.instances
+    }
+
+    /// All memories within the store when the core dump was created.
+    pub fn memories(&self) -> &[CoreDumpMemory] {
+        &self.memories
+    }
+
+    /// The values of all globals within the store when the core dump was
+    /// created.
+    pub fn globals(&self) -> &[CoreDumpValue] {
+        &self.globals
+    }
+
+    /// The stacks of each thread that was running when the core dump was
+    /// created.
+    pub fn threads(&self) -> &[CoreDumpThread] {
+        &self.threads
+    }
+
+    /// The component instances within the store when the core dump was
+    /// created, including the ones nested within other component instances.
+    ///
+    /// This is empty if the core dump doesn't record any component instances.
+    pub fn component_instances(&self) -> &[CoreDumpComponentInstance] {
+        &self.component_instances
+    }
+}
+
+fn read_option<'a, T>(
+    reader: &mut BinaryReader<'a>,
+    read: impl FnOnce(&mut BinaryReader<'a>) -> wasmparser::Result<T>,
+) -> Result<Option<T>> {
+    match reader.read_u8()? {
+        0x00 => Ok(None),
+        0x01 => Ok(Some(read(reader)?)),
+        _ => bail!("invalid optional value encoding"),
+    }
+}
+
+fn read_component_exports(reader: &mut BinaryReader<'_>) -> Result<Vec<CoreDumpComponentExport>> {
+    (0..reader.read_var_u32()?)
+        .map(|_| -> Result<_> {
+            let name = reader.read_string()?.to_string();
+            let kind = match reader.read_u8()? {
+                0x00 => CoreDumpComponentExportKind::Func,
+                0x01 => CoreDumpComponentExportKind::Module,
+                0x02 => CoreDumpComponentExportKind::Instance(read_component_exports(reader)?),
+                0x03 => CoreDumpComponentExportKind::Type,
+                _ => bail!("invalid component export encoding"),
+            };
+            Ok(CoreDumpComponentExport { name, kind })
+        })
+        .collect()
+}
+
+/// A component instance recorded in a [`CoreDumpFile`].
+#[derive(Debug)]
+pub struct CoreDumpComponentInstance {
+    name: Option<String>,
+    parent: Option<u32>,
+    core_instances: Vec<u32>,
+    exports: Vec<CoreDumpComponentExport>,
+}
+
+impl CoreDumpComponentInstance {
+    /// The name of the component that was instantiated, from its
+    /// `component-name` custom section.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The index in [`CoreDumpFile::component_instances`] of the component
+    /// instance which instantiated this one, or `None` if this instance was
+    /// instantiated by the host.
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+
+    /// The indices in [`CoreDumpFile::instances`] of the core instances that
+    /// this component instance created.
+    pub fn core_instances(&self) -> &[u32] {
+        &self.core_instances
+    }
+
+    /// The exports of this component instance.
+    ///
+    /// Only the exports of instances instantiated by the host are recorded,
+    /// so this is empty for nested instances. Nested instances that are
+    /// exported show up as [`CoreDumpComponentExportKind::Instance`] though.
+    pub fn exports(&self) -> &[CoreDumpComponentExport] {
+        &self.exports
+    }
+}
+
+/// An export of a [`CoreDumpComponentInstance`].
+#[derive(Debug)]
+pub struct CoreDumpComponentExport {
+    name: String,
+    kind: CoreDumpComponentExportKind,
+}
+
+impl CoreDumpComponentExport {
+    /// The name of this export.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The kind of item this export is.
+    pub fn kind(&self) -> &CoreDumpComponentExportKind {
+        &self.kind
+    }
+}
+
+/// The kind of a [`CoreDumpComponentExport`].
+#[derive(Debug)]
+pub enum CoreDumpComponentExportKind {
+    /// A function.
+    Func,
+    /// A core wasm module.
+    Module,
+    /// An instance, with its own exports.
+    Instance(Vec<CoreDumpComponentExport>),
+    /// A type.
+    Type,
+}
+
+/// An instance recorded in a [`CoreDumpFile`].
+#[derive(Debug)]
+pub struct CoreDumpInstance {
+    module: u32,
+    memories: Vec<u32>,
+    globals: Vec<u32>,
+}
+
+impl CoreDumpInstance {
+    /// The index of this instance's module in [`CoreDumpFile::modules`].
+    pub fn module(&self) -> u32 {
+        self.module
+    }
+
+    /// The indices in [`CoreDumpFile::memories`] of this instance's memories,
+    /// in the order of the instance's memory index space.
+    pub fn memories(&self) -> &[u32] {
+        &self.memories
+    }
+
+    /// The indices in [`CoreDumpFile::globals`] of this instance's globals, in
+    /// the order of the instance's global index space.
+    pub fn globals(&self) -> &[u32] {
+        &self.globals
+    }
+}
+
+/// The contents of a linear memory recorded in a [`CoreDumpFile`].
+#[derive(Debug)]
+pub struct CoreDumpMemory {
+    size: u64,
+    segments: Vec<(u64, Vec<u8>)>,
+}
+
+impl CoreDumpMemory {
+    /// The size of this memory, in bytes, when the core dump was created.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Reads the bytes of this memory within `range`.
+    ///
+    /// Returns `None` if `range` is out of bounds of this memory.
+    pub fn read(&self, range: Range<u64>) -> Option<Vec<u8>> {
+        if range.start > range.end || range.end > self.size {
+            return None;
+        }
+        let mut bytes = vec![0; usize::try_from(range.end - range.start).ok()?];
+        for (offset, data) in &self.segments {
+            // segments are checked to be in bounds when parsed
+            let end = offset + data.len() as u64;
+            let start = range.start.max(*offset);
+            if start >= range.end.min(end) {
+                continue;
+            }
+            let len = (range.end.min(end) - start) as usize;
+            let src = (start - offset) as usize;
+            let dst = (start - range.start) as usize;
+            bytes[dst..][..len].copy_from_slice(&data[src..][..len]);
+        }
+        Some(bytes)
+    }
+}
+
+/// The stack of a thread recorded in a [`CoreDumpFile`].
+#[derive(Debug)]
+pub struct CoreDumpThread {
+    name: String,
+    frames: Vec<CoreDumpFrame>,
+}
+
+impl CoreDumpThread {
+    /// The name of this thread.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The stack frames of this thread.
+    ///
+    /// Frames appear in callee to caller order, that is youngest to oldest
+    /// frames.
+    pub fn frames(&self) -> &[CoreDumpFrame] {
+        &self.frames
+    }
+}
+
+/// A stack frame recorded in a [`CoreDumpFile`].
+#[derive(Debug)]
+pub struct CoreDumpFrame {
+    instance: u32,
+    func_index: u32,
+    func_offset: u32,
+    locals: Vec<CoreDumpValue>,
+    stack: Vec<CoreDumpValue>,
+}
+
+impl CoreDumpFrame {
+    /// The index of this frame's instance in [`CoreDumpFile::instances`].
+    pub fn instance(&self) -> u32 {
+        self.instance
+    }
+
+    /// The WebAssembly function index, within its module, of this frame's
+    /// function.
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// The offset from the start of the function's body to the instruction
+    /// this frame was executing.
+    pub fn func_offset(&self) -> u32 {
+        self.func_offset
+    }
+
+    /// The values of this frame's locals, including its parameters.
+    ///
+    /// Locals may be [`CoreDumpValue::Missing`] or absent altogether if they
+    /// couldn't be recovered.
+    pub fn locals(&self) -> &[CoreDumpValue] {
+        &self.locals
+    }
+
+    /// The values on this frame's operand stack, if they could be recovered.
+    pub fn stack(&self) -> &[CoreDumpValue] {
+        &self.stack
+    }
+
+    /// Symbolicates this frame with the name section and DWARF debug
+    /// information of `module`, which must be the original module that this
+    /// frame's instance was instantiated from.
+    ///
+    /// Returns `None` if this frame's function isn't defined in `module`.
+    pub fn symbolize(&self, module: &Module) -> Option<FrameInfo> {
+        FrameInfo::from_func_offset(module.clone(), self.func_index, self.func_offset)
+    }
+}
+
+/// A value of a local, an operand stack slot or a global recorded in a
+/// [`CoreDumpFile`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoreDumpValue {
+    /// A value that couldn't be recovered, for example because it was
+    /// optimized away.
+    Missing,
+    /// A 32-bit integer.
+    I32(i32),
+    /// A 64-bit integer.
+    I64(i64),
+    /// A 32-bit float, as its raw bits.
+    F32(u32),
+    /// A 64-bit float, as its raw bits.
+    F64(u64),
+    /// A 128-bit vector.
+    V128(u128),
+    /// A null reference.
+    NullRef,
+    /// A reference to the function with this index.
+    FuncRef(u32),
+}
+
+impl fmt::Display for CoreDumpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreDumpValue::Missing => write!(f, "<missing>"),
+            CoreDumpValue::I32(x) => write!(f, "i32 {x}"),
+            CoreDumpValue::I64(x) => write!(f, "i64 {x}"),
+            CoreDumpValue::F32(x) => write!(f, "f32 {}", f32::from_bits(*x)),
+            CoreDumpValue::F64(x) => write!(f, "f64 {}", f64::from_bits(*x)),
+            CoreDumpValue::V128(x) => write!(f, "v128 {x:#034x}"),
+            CoreDumpValue::NullRef => write!(f, "ref.null"),
+            CoreDumpValue::FuncRef(x) => write!(f, "ref.func {x}"),
+        }
+    }
+}
+
+fn convert_value(value: &wasmparser::CoreDumpValue) -> CoreDumpValue {
+    match *value {
+        wasmparser::CoreDumpValue::Missing => CoreDumpValue::Missing,
+        wasmparser::CoreDumpValue::I32(x) => CoreDumpValue::I32(x),
+        wasmparser::CoreDumpValue::I64(x) => CoreDumpValue::I64(x),
+        wasmparser::CoreDumpValue::F32(x) => CoreDumpValue::F32(x.to_bits()),
+        wasmparser::CoreDumpValue::F64(x) => CoreDumpValue::F64(x.to_bits()),
+    }
+}
+
+fn eval_const_expr(expr: &ConstExpr<'_>) -> Result<CoreDumpValue> {
+    let mut ops = expr.get_operators_reader();
+    let value = match ops.read()? {
+        Operator::I32Const { value } => CoreDumpValue::I32(value),
+        Operator::I64Const { value } => CoreDumpValue::I64(value),
+        Operator::F32Const { value } => CoreDumpValue::F32(value.bits()),
+        Operator::F64Const { value } => CoreDumpValue::F64(value.bits()),
+        Operator::V128Const { value } => CoreDumpValue::V128(value.i128() as u128),
+        Operator::RefNull { .. } => CoreDumpValue::NullRef,
+        Operator::RefFunc { function_index } => CoreDumpValue::FuncRef(function_index),
+        op => bail!("unsupported constant expression in core dump: {op:?}"),
+    };
+    match ops.read()? {
+        Operator::End => Ok(value),
+        _ => bail!("unsupported constant expression in core dump"),
+    }
+}
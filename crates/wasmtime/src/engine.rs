@@ -18,6 +18,8 @@ use wasmtime_runtime::{CompiledModuleIdAllocator, InstanceAllocator, MmapVec};
 
 mod serialization;
 
+pub use self::serialization::PrecompiledInfo;
+
 /// An `Engine` which is a global context for compilation and management of wasm
 /// modules.
 ///
@@ -47,7 +49,7 @@ pub struct Engine {
 
 struct EngineInner {
     config: Config,
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     compiler: Box<dyn wasmtime_environ::Compiler>,
     allocator: Box<dyn InstanceAllocator + Send + Sync>,
     profiler: Box<dyn ProfilingAgent>,
@@ -85,7 +87,7 @@ impl Engine {
         let config = config.clone();
         config.validate()?;
 
-        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
         let (config, compiler) = config.build_compiler()?;
 
         let allocator = config.build_allocator()?;
@@ -93,7 +95,7 @@ impl Engine {
 
         Ok(Engine {
             inner: Arc::new(EngineInner {
-                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
                 compiler,
                 config,
                 allocator,
@@ -133,7 +135,7 @@ impl Engine {
         &self.inner.config
     }
 
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn compiler(&self) -> &dyn wasmtime_environ::Compiler {
         &*self.inner.compiler
     }
@@ -217,8 +219,11 @@ impl Engine {
     ///
     /// [binary]: https://webassembly.github.io/spec/core/binary/index.html
     /// [text]: https://webassembly.github.io/spec/core/text/index.html
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn precompile_module(&self, bytes: &[u8]) -> Result<Vec<u8>> {
         #[cfg(feature = "wat")]
         let bytes = wat::parse_bytes(&bytes)?;
@@ -228,8 +233,11 @@ impl Engine {
 
     /// Same as [`Engine::precompile_module`] except for a
     /// [`Component`](crate::component::Component)
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     #[cfg(feature = "component-model")]
     #[cfg_attr(nightlydoc, doc(cfg(feature = "component-model")))]
     pub fn precompile_component(&self, bytes: &[u8]) -> Result<Vec<u8>> {
@@ -245,7 +253,7 @@ impl Engine {
     /// are compatible with a different [`Engine`] instance only if the two engines use
     /// compatible [`Config`]s. If this Hash matches between two [`Engine`]s then binaries
     /// from one are guaranteed to deserialize in the other.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
     pub fn precompile_compatibility_hash(&self) -> impl std::hash::Hash + '_ {
         crate::module::HashedEngineCompileEnv(self)
@@ -300,11 +308,11 @@ impl Engine {
     /// and/or running code for.
     pub(crate) fn target(&self) -> target_lexicon::Triple {
         // If a compiler is configured, use that target.
-        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
         return self.compiler().triple().clone();
 
         // ... otherwise it's the native target
-        #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+        #[cfg(not(any(feature = "cranelift", feature = "winch", feature = "interpreter")))]
         return target_lexicon::Triple::host();
     }
 
@@ -324,7 +332,7 @@ impl Engine {
     }
 
     fn _check_compatible_with_native_host(&self) -> Result<(), String> {
-        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
         {
             let compiler = self.compiler();
 
@@ -578,12 +586,12 @@ impl Engine {
         ))
     }
 
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn append_compiler_info(&self, obj: &mut Object<'_>) {
         serialization::append_compiler_info(self, obj);
     }
 
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn append_bti(&self, obj: &mut Object<'_>) {
         let section = obj.add_section(
             obj.segment_name(StandardSegment::Data).to_vec(),
@@ -656,6 +664,20 @@ impl Engine {
     pub fn detect_precompiled_file(&self, path: impl AsRef<Path>) -> Result<Option<Precompiled>> {
         serialization::detect_precompiled_file(path)
     }
+
+    /// Reads the configuration of the engine which produced the precompiled
+    /// artifact `bytes`, such as its target, compiler flags and Wasmtime
+    /// version.
+    ///
+    /// This is intended for diagnosing why an artifact can't be loaded, so
+    /// unlike deserialization this succeeds for artifacts which are
+    /// incompatible with this engine, and
+    /// [`PrecompiledInfo::check_compatible`] reports the reason they are
+    /// incompatible. An error is returned if `bytes` doesn't look like a
+    /// precompiled artifact produced by Wasmtime at all.
+    pub fn precompiled_info(&self, bytes: &[u8]) -> Result<PrecompiledInfo> {
+        serialization::precompiled_info(self, bytes)
+    }
 }
 
 impl Default for Engine {
@@ -39,7 +39,7 @@ const VERSION: u8 = 0;
 ///
 /// The blob of bytes is inserted into the object file specified to become part
 /// of the final compiled artifact.
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 pub fn append_compiler_info(engine: &Engine, obj: &mut Object<'_>) {
     let section = obj.add_section(
         obj.segment_name(StandardSegment::Data).to_vec(),
@@ -87,19 +87,22 @@ pub fn check_compatible(engine: &Engine, mmap: &MmapVec, expected: ObjectKind) -
     // a perf issue right now so doing that is left for another day's
     // refactoring.
     let obj = File::parse(&mmap[..]).context("failed to parse precompiled artifact as an ELF")?;
-    let expected_e_flags = match expected {
-        ObjectKind::Module => obj::EF_WASMTIME_MODULE,
-        ObjectKind::Component => obj::EF_WASMTIME_COMPONENT,
+    let expected = match expected {
+        ObjectKind::Module => Precompiled::Module,
+        ObjectKind::Component => Precompiled::Component,
     };
-    match obj.flags() {
-        FileFlags::Elf {
-            os_abi: obj::ELFOSABI_WASMTIME,
-            abi_version: 0,
-            e_flags,
-        } if e_flags == expected_e_flags => {}
-        _ => bail!("incompatible object file format"),
+    if detect_precompiled(&obj) != Some(expected) {
+        bail!("incompatible object file format");
     }
 
+    let (version, data) = engine_section(&obj)?;
+    check_version(engine, version)?;
+    bincode::deserialize::<Metadata<'_>>(data)?.check_compatible(engine)
+}
+
+/// Splits the engine section of `obj` into the version string and the
+/// `bincode`-encoded `Metadata` following it.
+fn engine_section<'data>(obj: &File<'data>) -> Result<(&'data [u8], &'data [u8])> {
     let data = obj
         .section_by_name(obj::ELF_WASM_ENGINE)
         .ok_or_else(|| anyhow!("failed to find section `{}`", obj::ELF_WASM_ENGINE))?
@@ -114,12 +117,13 @@ pub fn check_compatible(engine: &Engine, mmap: &MmapVec, expected: ObjectKind) -
         .split_first()
         .ok_or_else(|| anyhow!("invalid engine section"))?;
     let len = usize::from(*len);
-    let (version, data) = if data.len() < len + 1 {
+    if data.len() < len + 1 {
         bail!("engine section too small")
-    } else {
-        data.split_at(len)
-    };
+    }
+    Ok(data.split_at(len))
+}
 
+fn check_version(engine: &Engine, version: &[u8]) -> Result<()> {
     match &engine.config().module_version {
         ModuleVersionStrategy::WasmtimeVersion => {
             let version = std::str::from_utf8(version)?;
@@ -141,11 +145,134 @@ pub fn check_compatible(engine: &Engine, mmap: &MmapVec, expected: ObjectKind) -
         }
         ModuleVersionStrategy::None => { /* ignore the version info, accept all */ }
     }
-    bincode::deserialize::<Metadata<'_>>(data)?.check_compatible(engine)
+    Ok(())
+}
+
+/// Reads the engine configuration recorded in the precompiled artifact
+/// `bytes` and checks whether it's compatible with `engine`.
+pub fn precompiled_info(engine: &Engine, bytes: &[u8]) -> Result<PrecompiledInfo> {
+    let obj = File::parse(bytes).context("failed to parse precompiled artifact as an ELF")?;
+    let kind = detect_precompiled(&obj)
+        .ok_or_else(|| anyhow!("not a precompiled artifact produced by Wasmtime"))?;
+    let (version, data) = engine_section(&obj)?;
+    let mut info = PrecompiledInfo {
+        kind,
+        version: String::from_utf8_lossy(version).into_owned(),
+        target: String::new(),
+        shared_flags: Vec::new(),
+        isa_flags: Vec::new(),
+        tunables: Vec::new(),
+        features: Vec::new(),
+        incompatibility: None,
+    };
+
+    // The encoding of the rest of the section may differ between versions, so
+    // it's only decoded once the version is known to match.
+    if let Err(e) = check_version(engine, version) {
+        info.incompatibility = Some(format!("version mismatch: {e:#}"));
+        return Ok(info);
+    }
+    let metadata = bincode::deserialize::<Metadata<'_>>(data)?;
+
+    let flags = |flags: &[(&str, FlagValue<'_>)]| -> Vec<(String, String)> {
+        flags
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+    let settings = |value: serde_json::Value| -> Vec<(String, String)> {
+        match value {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    info.target = metadata.target.clone();
+    info.shared_flags = flags(&metadata.shared_flags);
+    info.isa_flags = flags(&metadata.isa_flags);
+    info.tunables = settings(serde_json::to_value(&metadata.tunables)?);
+    info.features = settings(serde_json::to_value(&metadata.features)?);
+    info.incompatibility = metadata
+        .check_compatible(engine)
+        .err()
+        .map(|e| format!("{e:#}"));
+    Ok(info)
+}
+
+/// The configuration of the engine which produced a precompiled artifact, as
+/// returned by [`Engine::precompiled_info`].
+///
+/// If the artifact was produced by a different version of Wasmtime only its
+/// kind and version are known, and the target and settings are empty.
+#[derive(Debug, Clone)]
+pub struct PrecompiledInfo {
+    kind: Precompiled,
+    version: String,
+    target: String,
+    shared_flags: Vec<(String, String)>,
+    isa_flags: Vec<(String, String)>,
+    tunables: Vec<(String, String)>,
+    features: Vec<(String, String)>,
+    incompatibility: Option<String>,
+}
+
+impl PrecompiledInfo {
+    /// Whether the artifact is a core wasm module or a component.
+    pub fn kind(&self) -> Precompiled {
+        self.kind
+    }
+
+    /// The version string the artifact was produced with, which is empty if
+    /// it was produced with [`ModuleVersionStrategy::None`].
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The target triple the artifact was compiled for, which is empty if the
+    /// artifact is from a different version.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The target-independent compiler settings the artifact was compiled
+    /// with.
+    pub fn shared_flags(&self) -> impl ExactSizeIterator<Item = (&str, &str)> + '_ {
+        self.shared_flags
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The target-specific compiler settings, such as enabled CPU features,
+    /// the artifact was compiled with.
+    pub fn isa_flags(&self) -> impl ExactSizeIterator<Item = (&str, &str)> + '_ {
+        self.isa_flags.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Settings of the producing engine which affect the generated code, such
+    /// as memory guard sizes or fuel consumption.
+    pub fn tunables(&self) -> impl ExactSizeIterator<Item = (&str, &str)> + '_ {
+        self.tunables.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The WebAssembly features enabled in the producing engine.
+    pub fn features(&self) -> impl ExactSizeIterator<Item = (&str, &str)> + '_ {
+        self.features.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns an error describing the first mismatch if the artifact can't
+    /// be loaded into the engine passed to [`Engine::precompiled_info`].
+    pub fn check_compatible(&self) -> Result<()> {
+        match &self.incompatibility {
+            Some(e) => bail!("{e}"),
+            None => Ok(()),
+        }
+    }
 }
 
 fn detect_precompiled<'data, R: object::ReadRef<'data>>(
-    obj: File<'data, R>,
+    obj: &File<'data, R>,
 ) -> Option<Precompiled> {
     match obj.flags() {
         FileFlags::Elf {
@@ -163,13 +290,13 @@ fn detect_precompiled<'data, R: object::ReadRef<'data>>(
 }
 
 pub fn detect_precompiled_bytes(bytes: &[u8]) -> Option<Precompiled> {
-    detect_precompiled(File::parse(bytes).ok()?)
+    detect_precompiled(&File::parse(bytes).ok()?)
 }
 
 pub fn detect_precompiled_file(path: impl AsRef<std::path::Path>) -> Result<Option<Precompiled>> {
     let read_cache = object::ReadCache::new(std::fs::File::open(path)?);
     let obj = File::parse(&read_cache)?;
-    Ok(detect_precompiled(obj))
+    Ok(detect_precompiled(&obj))
 }
 
 #[derive(Serialize, Deserialize)]
@@ -202,7 +329,7 @@ struct WasmFeatures {
 }
 
 impl Metadata<'_> {
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     fn new(engine: &Engine) -> Metadata<'static> {
         let wasmparser::WasmFeatures {
             reference_types,
@@ -346,6 +473,7 @@ impl Metadata<'_> {
             guard_before_linear_memory,
             relaxed_simd_deterministic,
             tail_callable,
+            interpreter,
 
             // This doesn't affect compilation, it's just a runtime setting.
             dynamic_memory_growth_reserve: _,
@@ -358,6 +486,11 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // Instrumented modules record this in their own metadata and
+            // collect their profile regardless of the loading engine's
+            // configuration.
+            pgo_instrumentation: _,
         } = self.tunables;
 
         Self::check_int(
@@ -407,6 +540,7 @@ impl Metadata<'_> {
             "relaxed simd deterministic semantics",
         )?;
         Self::check_bool(tail_callable, other.tail_callable, "WebAssembly tail calls")?;
+        Self::check_bool(interpreter, other.interpreter, "the portable interpreter")?;
 
         Ok(())
     }
@@ -654,4 +788,41 @@ Caused by:
 
         Ok(())
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_precompiled_info_version_mismatch() -> Result<()> {
+        let mut config = Config::new();
+        config.module_version(ModuleVersionStrategy::Custom("other".to_string()))?;
+        let engine = Engine::new(&config)?;
+        let mut bytes = crate::Module::new(&engine, "(module)")?.serialize()?;
+
+        // Garble everything after the version string, which is only readable
+        // by the version which wrote it.
+        let range = File::parse(&bytes[..])?
+            .section_by_name(obj::ELF_WASM_ENGINE)
+            .unwrap()
+            .file_range()
+            .unwrap();
+        let start = range.0 as usize + 2 + "other".len();
+        let end = (range.0 + range.1) as usize;
+        bytes[start..end].fill(0xff);
+
+        let info = Engine::default().precompiled_info(&bytes)?;
+        assert_eq!(info.kind(), Precompiled::Module);
+        assert_eq!(info.version(), "other");
+        assert_eq!(info.target(), "");
+        assert_eq!(info.shared_flags().len(), 0);
+        let err = info.check_compatible().unwrap_err().to_string();
+        assert!(err.starts_with("version mismatch: "), "bad error: {err}");
+        assert!(err.contains("'other'"), "bad error: {err}");
+
+        // The same engine as produced the artifact still decodes the rest.
+        bytes = crate::Module::new(&engine, "(module)")?.serialize()?;
+        let info = engine.precompiled_info(&bytes)?;
+        assert_eq!(info.target(), target_lexicon::Triple::host().to_string());
+        info.check_compatible()?;
+
+        Ok(())
+    }
 }
@@ -355,8 +355,11 @@ impl Func {
     /// documentation.
     ///
     /// [`Trap`]: crate::Trap
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn new<T>(
         store: impl AsContextMut<Data = T>,
         ty: FuncType,
@@ -393,8 +396,11 @@ impl Func {
     /// This function is not safe because it's not known at compile time that
     /// the `func` provided correctly interprets the argument types provided to
     /// it, or that the results it produces will be of the correct type.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub unsafe fn new_unchecked<T>(
         mut store: impl AsContextMut<Data = T>,
         ty: FuncType,
@@ -912,6 +918,14 @@ impl Func {
     ) -> Result<()> {
         invoke_wasm_and_catch_traps(store, |caller| {
             let func_ref = func_ref.as_ref();
+            #[cfg(feature = "interpreter")]
+            if wasmtime_runtime::interpreter::is_interpreted(func_ref) {
+                return wasmtime_runtime::interpreter::call(
+                    func_ref,
+                    params_and_returns,
+                    params_and_returns_capacity,
+                );
+            }
             (func_ref.array_call)(
                 func_ref.vmctx,
                 caller.cast::<VMOpaqueContext>(),
@@ -1967,7 +1981,11 @@ macro_rules! impl_into_func {
 
                         let ret = {
                             panic::catch_unwind(AssertUnwindSafe(|| {
-                                if let Err(trap) = caller.store.0.call_hook(CallHook::CallingHost) {
+                                let hook = caller
+                                    .store
+                                    .0
+                                    .call_hook_calling_host(caller.caller, vmctx.cast());
+                                if let Err(trap) = hook {
                                     return R::fallible_from_error(trap);
                                 }
                                 $(let $args = $args::from_abi($args, caller.store.0);)*
@@ -2055,12 +2073,23 @@ macro_rules! impl_into_func {
                 let array_call = array_call_trampoline::<T, F, $($args,)* R>;
                 let native_call = NonNull::new(native_call_shim::<T, F, $($args,)* R> as *mut _).unwrap();
 
+                // Engines using the interpreter have no trampolines to fill
+                // in the `wasm_call` with, and don't need any.
+                #[cfg(feature = "interpreter")]
+                let wasm_call = engine
+                    .config()
+                    .tunables
+                    .interpreter
+                    .then(wasmtime_runtime::interpreter::wasm_call);
+                #[cfg(not(feature = "interpreter"))]
+                let wasm_call = None;
+
                 let ctx = unsafe {
                     VMNativeCallHostFuncContext::new(
                         VMFuncRef {
                             native_call,
                             array_call,
-                            wasm_call: None,
+                            wasm_call,
                             type_index: shared_signature_id,
                             vmctx: ptr::null_mut(),
                         },
@@ -2114,7 +2143,7 @@ pub(crate) struct HostFunc {
 
 impl HostFunc {
     /// Analog of [`Func::new`]
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub fn new<T>(
         engine: &Engine,
         ty: FuncType,
@@ -2129,15 +2158,18 @@ impl HostFunc {
     }
 
     /// Analog of [`Func::new_unchecked`]
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub unsafe fn new_unchecked<T>(
         engine: &Engine,
         ty: FuncType,
         func: impl Fn(Caller<'_, T>, &mut [ValRaw]) -> Result<()> + Send + Sync + 'static,
     ) -> Self {
-        let func = move |caller_vmctx, values: &mut [ValRaw]| {
+        let func = move |caller_vmctx, callee_vmctx, values: &mut [ValRaw]| {
             Caller::<T>::with(caller_vmctx, |mut caller| {
-                caller.store.0.call_hook(CallHook::CallingHost)?;
+                caller
+                    .store
+                    .0
+                    .call_hook_calling_host(caller.caller, callee_vmctx)?;
                 let result = func(caller.sub_caller(), values)?;
                 caller.store.0.call_hook(CallHook::ReturningFromHost)?;
                 Ok(result)
@@ -9,6 +9,11 @@ use wasmtime_runtime::{
     VMContext, VMFuncRef, VMNativeCallFunction, VMOpaqueContext, VMSharedSignatureIndex,
 };
 
+/// The maximum number of parameters or results of a typed function, matching
+/// the signatures generated by `for_each_function_signature!`.
+#[cfg(feature = "interpreter")]
+const MAX_TYPED_ARITY: usize = 16;
+
 /// A statically typed WebAssembly function.
 ///
 /// Values of this type represent statically type-checked WebAssembly functions.
@@ -177,6 +182,32 @@ where
         let result = invoke_wasm_and_catch_traps(store, |caller| {
             let (func_ref, ret, params, returned) = &mut captures;
             let func_ref = func_ref.as_ref();
+            #[cfg(feature = "interpreter")]
+            if !wasmtime_runtime::interpreter::has_native_call(func_ref) {
+                // Without a `native_call` entry point the function only
+                // supports the array calling convention, so spill the
+                // arguments into a buffer large enough for both the parameters
+                // and results.
+                let mut values = [ValRaw::u64(0); MAX_TYPED_ARITY];
+                Params::store_raw(*params, values.as_mut_ptr());
+                if wasmtime_runtime::interpreter::is_interpreted(func_ref) {
+                    wasmtime_runtime::interpreter::call(
+                        func_ref,
+                        values.as_mut_ptr(),
+                        values.len(),
+                    );
+                } else {
+                    (func_ref.array_call)(
+                        func_ref.vmctx,
+                        caller.cast::<VMOpaqueContext>(),
+                        values.as_mut_ptr(),
+                        values.len(),
+                    );
+                }
+                ptr::write(ret.as_mut_ptr(), Results::load_raw(values.as_mut_ptr()));
+                *returned = true;
+                return;
+            }
             let result =
                 Params::invoke::<Results>(func_ref.native_call, func_ref.vmctx, caller, *params);
             ptr::write(ret.as_mut_ptr(), result);
@@ -476,6 +507,10 @@ pub unsafe trait WasmParams: Send {
         vmctx2: *mut VMContext,
         abi: Self::Abi,
     ) -> R::ResultAbi;
+
+    #[doc(hidden)]
+    #[cfg(feature = "interpreter")]
+    unsafe fn store_raw(abi: Self::Abi, raw: *mut ValRaw);
 }
 
 // Forward an impl from `T` to `(T,)` for convenience if there's only one
@@ -508,6 +543,11 @@ where
     ) -> R::ResultAbi {
         <(T,) as WasmParams>::invoke::<R>(func, vmctx1, vmctx2, abi)
     }
+
+    #[cfg(feature = "interpreter")]
+    unsafe fn store_raw(abi: Self::Abi, raw: *mut ValRaw) {
+        <(T,) as WasmParams>::store_raw(abi, raw)
+    }
 }
 
 macro_rules! impl_wasm_params {
@@ -587,6 +627,16 @@ macro_rules! impl_wasm_params {
                     fnptr(vmctx1, vmctx2, $($t,)* retptr)
                 })
             }
+
+            #[cfg(feature = "interpreter")]
+            unsafe fn store_raw(abi: Self::Abi, _raw: *mut ValRaw) {
+                let ($($t,)*) = abi;
+                let mut _i = 0;
+                $(
+                    $t::abi_into_raw($t, _raw.add(_i));
+                    _i += 1;
+                )*
+            }
         }
     };
 }
@@ -604,6 +654,9 @@ pub unsafe trait WasmResults: WasmParams {
     type ResultAbi: HostAbi;
     #[doc(hidden)]
     unsafe fn from_abi(store: &mut StoreOpaque, abi: Self::ResultAbi) -> Self;
+    #[doc(hidden)]
+    #[cfg(feature = "interpreter")]
+    unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi;
 }
 
 // Forwards from a bare type `T` to the 1-tuple type `(T,)`
@@ -616,6 +669,11 @@ where
     unsafe fn from_abi(store: &mut StoreOpaque, abi: Self::ResultAbi) -> Self {
         <(T,) as WasmResults>::from_abi(store, abi).0
     }
+
+    #[cfg(feature = "interpreter")]
+    unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi {
+        <(T,) as WasmResults>::load_raw(raw)
+    }
 }
 
 macro_rules! impl_wasm_results {
@@ -631,6 +689,16 @@ macro_rules! impl_wasm_results {
                 let ($($t,)*) = abi;
                 ($($t::from_abi($t, store),)*)
             }
+
+            #[cfg(feature = "interpreter")]
+            unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi {
+                let mut _i = 0;
+                $(
+                    let $t = $t::abi_from_raw(raw.add(_i));
+                    _i += 1;
+                )*
+                ($($t,)*)
+            }
         }
     };
 }
@@ -354,6 +354,11 @@ impl Instance {
         let caller_vmctx = instance.vmctx();
         unsafe {
             super::func::invoke_wasm_and_catch_traps(store, |_default_caller| {
+                #[cfg(feature = "interpreter")]
+                if wasmtime_runtime::interpreter::is_interpreted(f.func_ref.as_ref()) {
+                    let values = NonNull::<crate::ValRaw>::dangling().as_ptr();
+                    return wasmtime_runtime::interpreter::call(f.func_ref.as_ref(), values, 0);
+                }
                 let func = mem::transmute::<
                     NonNull<VMNativeCallFunction>,
                     extern "C" fn(*mut VMOpaqueContext, *mut VMContext),
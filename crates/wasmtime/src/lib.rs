@@ -413,7 +413,7 @@
 #[macro_use]
 mod func;
 
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 mod compiler;
 
 mod code;
@@ -425,6 +425,7 @@ mod limits;
 mod linker;
 mod memory;
 mod module;
+mod pgo;
 #[cfg(feature = "profiling")]
 mod profiling;
 mod r#ref;
@@ -449,6 +450,7 @@ pub use crate::limits::*;
 pub use crate::linker::*;
 pub use crate::memory::*;
 pub use crate::module::Module;
+pub use crate::pgo::PgoProfile;
 #[cfg(feature = "profiling")]
 pub use crate::profiling::GuestProfiler;
 pub use crate::r#ref::ExternRef;
@@ -456,7 +458,8 @@ pub use crate::resources::*;
 #[cfg(feature = "async")]
 pub use crate::store::CallHookHandler;
 pub use crate::store::{
-    AsContext, AsContextMut, CallHook, Store, StoreContext, StoreContextMut, UpdateDeadline,
+    AsContext, AsContextMut, CallHook, MemoryStats, Store, StoreContext, StoreContextMut,
+    StoreStats, TableStats, UpdateDeadline,
 };
 pub use crate::trap::*;
 pub use crate::types::*;
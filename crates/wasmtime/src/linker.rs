@@ -274,8 +274,11 @@ impl<T> Linker<T> {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn define_unknown_imports_as_traps(&mut self, module: &Module) -> anyhow::Result<()> {
         for import in module.imports() {
             if let Err(import_err) = self._get_by_import(&import) {
@@ -310,8 +313,11 @@ impl<T> Linker<T> {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn define_unknown_imports_as_default_values(
         &mut self,
         module: &Module,
@@ -345,6 +351,63 @@ impl<T> Linker<T> {
         Ok(())
     }
 
+    /// Defines a function named `module`/`name` in this [`Linker`] which
+    /// guests can call to discard a region of their linear memory.
+    ///
+    /// This is a guest-visible counterpart to [`Memory::discard`] for
+    /// allocators which free large regions and want to return them to the
+    /// host. The function has the signature `(param $addr i32) (param $len
+    /// i32)` and discards the bytes `addr..addr + len` of the memory exported
+    /// as `"memory"` by the calling instance.
+    ///
+    /// The function traps if the calling instance doesn't export a memory
+    /// named `"memory"` or if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let engine = Engine::default();
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"
+    ///         (module
+    ///             (import "wasmtime" "memory_discard" (func $discard (param i32 i32)))
+    ///             (memory (export "memory") 1)
+    ///             (func (export "free") (call $discard (i32.const 0) (i32.const 0x10000)))
+    ///         )
+    ///     "#,
+    /// )?;
+    /// let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define_memory_discard("wasmtime", "memory_discard")?;
+    /// let instance = linker.instantiate(&mut store, &module)?;
+    /// let free = instance.get_typed_func::<(), ()>(&mut store, "free")?;
+    /// free.call(&mut store, ())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Memory::discard`]: crate::Memory::discard
+    pub fn define_memory_discard(&mut self, module: &str, name: &str) -> Result<&mut Self> {
+        self.func_wrap(
+            module,
+            name,
+            |mut caller: Caller<'_, T>, addr: u32, len: u32| -> Result<()> {
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(memory)) => memory,
+                    _ => bail!("memory discard requires the caller to export `memory`"),
+                };
+                let start = usize::try_from(addr)?;
+                let end = start
+                    .checked_add(usize::try_from(len)?)
+                    .context("memory discard range overflows")?;
+                memory.discard(&mut caller, start..end)
+            },
+        )
+    }
+
     /// Defines a new item in this [`Linker`].
     ///
     /// This method will add a new definition, by name, to this instance of
@@ -415,8 +478,11 @@ impl<T> Linker<T> {
     /// Creates a [`Func::new`]-style function named in this linker.
     ///
     /// For more information see [`Linker::func_wrap`].
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn func_new(
         &mut self,
         module: &str,
@@ -433,8 +499,11 @@ impl<T> Linker<T> {
     /// Creates a [`Func::new_unchecked`]-style function named in this linker.
     ///
     /// For more information see [`Linker::func_wrap`].
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub unsafe fn func_new_unchecked(
         &mut self,
         module: &str,
@@ -753,8 +822,11 @@ impl<T> Linker<T> {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn module(
         &mut self,
         mut store: impl AsContextMut<Data = T>,
@@ -545,6 +545,72 @@ impl Memory {
         store.on_fiber(|store| self.grow(store, delta)).await?
     }
 
+    /// Zeroes the bytes of this memory in `range` and returns the physical
+    /// memory backing them to the operating system where possible.
+    ///
+    /// This is intended for embedders, and the guests they expose it to,
+    /// which free large regions of linear memory and want the process's
+    /// resident memory to shrink accordingly without tearing down the whole
+    /// instance. The size of the memory is unaffected and the discarded bytes
+    /// read as zero afterwards, even if they were initialized by a data
+    /// segment or a copy-on-write memory image.
+    ///
+    /// Pages of `range` are decommitted, for example with
+    /// `madvise(MADV_DONTNEED)` on Linux, while any bytes at the start or end
+    /// of `range` which don't cover a whole host page are overwritten with
+    /// zeros instead. Discarding is therefore most effective for large,
+    /// page-aligned ranges; aligning to the WebAssembly page size of 64KiB is
+    /// sufficient on all supported hosts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `range` is out of bounds of this memory or if the
+    /// operating system fails to decommit the memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this memory doesn't belong to `store`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let mut store = Store::new(&engine, ());
+    /// let module = Module::new(&engine, "(module (memory (export \"mem\") 2))")?;
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// let memory = instance.get_memory(&mut store, "mem").unwrap();
+    ///
+    /// memory.data_mut(&mut store).fill(1);
+    /// memory.discard(&mut store, 0x10000..0x20000)?;
+    /// assert!(memory.data(&store)[..0x10000].iter().all(|b| *b == 1));
+    /// assert!(memory.data(&store)[0x10000..].iter().all(|b| *b == 0));
+    /// assert!(memory.discard(&mut store, 0x10000..0x20001).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn discard(&self, mut store: impl AsContextMut, range: Range<usize>) -> Result<()> {
+        let store = store.as_context_mut().0;
+        if range.start > range.end || range.end > self.internal_data_size(store) {
+            bail!(MemoryAccessError { _private: () });
+        }
+
+        let page_size = wasmtime_runtime::page_size();
+        let start = (range.start + page_size - 1) & !(page_size - 1);
+        let end = range.end & !(page_size - 1);
+        unsafe {
+            let base = (*store[self.0].definition).base;
+            if start >= end {
+                std::ptr::write_bytes(base.add(range.start), 0, range.len());
+                return Ok(());
+            }
+            std::ptr::write_bytes(base.add(range.start), 0, start - range.start);
+            std::ptr::write_bytes(base.add(end), 0, range.end - end);
+            (*self.wasmtime_memory(store)).discard(start..end)
+        }
+    }
+
     fn wasmtime_memory(&self, store: &mut StoreOpaque) -> *mut wasmtime_runtime::Memory {
         unsafe {
             let export = &store[self.0];
@@ -734,7 +800,7 @@ impl SharedMemory {
 
         let tunables = &engine.config().tunables;
         let plan = MemoryPlan::for_memory(ty.wasmtime_memory().clone(), tunables);
-        let memory = wasmtime_runtime::SharedMemory::new(plan)?;
+        let memory = wasmtime_runtime::SharedMemory::new(plan, engine.config().memory_huge_pages)?;
         Ok(Self(memory, engine.clone()))
     }
 
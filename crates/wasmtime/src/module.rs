@@ -3,7 +3,7 @@ use crate::{
     resources::ResourcesRequired,
     signatures::SignatureCollection,
     types::{ExportType, ExternType, ImportType},
-    Engine,
+    Engine, PgoProfile,
 };
 use anyhow::{bail, Context, Result};
 use once_cell::sync::OnceCell;
@@ -16,12 +16,12 @@ use std::sync::Arc;
 use wasmparser::{Parser, ValidPayload, Validator};
 use wasmtime_environ::{
     DefinedFuncIndex, DefinedMemoryIndex, HostPtr, ModuleEnvironment, ModuleTypes, ObjectKind,
-    VMOffsets,
+    VMOffsets, WasmFunctionInfo,
 };
 use wasmtime_jit::{CodeMemory, CompiledModule, CompiledModuleInfo};
 use wasmtime_runtime::{
-    CompiledModuleId, MemoryImage, MmapVec, ModuleMemoryImages, VMArrayCallFunction,
-    VMNativeCallFunction, VMSharedSignatureIndex, VMWasmCallFunction,
+    CompiledModuleId, MemoryImage, MmapVec, ModuleMemoryImages, ProfileCounters,
+    VMArrayCallFunction, VMNativeCallFunction, VMSharedSignatureIndex, VMWasmCallFunction,
 };
 
 mod registry;
@@ -130,6 +130,10 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// Execution counts recorded by instrumented code, present when this
+    /// module was compiled with profile-guided optimization instrumentation.
+    pgo_counters: Option<ProfileCounters>,
 }
 
 impl std::fmt::Debug for Module {
@@ -206,8 +210,11 @@ impl Module {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn new(engine: &Engine, bytes: impl AsRef<[u8]>) -> Result<Module> {
         let bytes = bytes.as_ref();
         #[cfg(feature = "wat")]
@@ -243,8 +250,11 @@ impl Module {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn from_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Module> {
         let file = file.as_ref();
         match Self::new(
@@ -298,8 +308,11 @@ impl Module {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn from_binary(engine: &Engine, binary: &[u8]) -> Result<Module> {
         engine
             .check_compatible_with_native_host()
@@ -370,8 +383,11 @@ impl Module {
     /// This is because the file is mapped into memory and lazily loaded pages
     /// reflect the current state of the file, not necessarily the origianl
     /// state of the file.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub unsafe fn from_trusted_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Module> {
         let mmap = MmapVec::from_file(file.as_ref())?;
         if &mmap[0..4] == b"\x7fELF" {
@@ -394,7 +410,7 @@ impl Module {
     /// Additionally compilation returns an `Option` here which is always
     /// `Some`, notably compiled metadata about the module in addition to the
     /// type information found within.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
     pub(crate) fn build_artifacts(
         engine: &Engine,
         wasm: &[u8],
@@ -416,6 +432,11 @@ impl Module {
             .context("failed to parse WebAssembly module")?;
         let functions = mem::take(&mut translation.function_body_inputs);
 
+        engine
+            .config()
+            .pgo_profile
+            .prepare_translation(tunables.pgo_instrumentation, &mut translation);
+
         let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
         let unlinked_compile_outputs = compile_inputs.compile(engine)?;
         let types = types.finish();
@@ -580,6 +601,11 @@ impl Module {
             .allocator()
             .validate_module(module.module(), &offsets)?;
 
+        let env_module = module.module();
+        let pgo_counters = env_module.pgo_key.map(|_| {
+            ProfileCounters::new(env_module.functions.len() - env_module.num_imported_funcs)
+        });
+
         Ok(Self {
             inner: Arc::new(ModuleInner {
                 engine: engine.clone(),
@@ -588,6 +614,7 @@ impl Module {
                 module,
                 serializable,
                 offsets,
+                pgo_counters,
             }),
         })
     }
@@ -646,8 +673,11 @@ impl Module {
     /// this method, but if a module is both instantiated and serialized then
     /// this method can be useful to get the serialized version without
     /// compiling twice.
-    #[cfg(any(feature = "cranelift", feature = "winch"))]
-    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter")))
+    )]
     pub fn serialize(&self) -> Result<Vec<u8>> {
         // The current representation of compiled modules within a compiled
         // component means that it cannot be serialized. The mmap returned here
@@ -900,6 +930,57 @@ impl Module {
         &self.inner.engine
     }
 
+    /// Returns the execution profile collected so far by this module's
+    /// instances, or `None` if this module wasn't compiled with
+    /// [`Config::pgo_instrumentation`](crate::Config::pgo_instrumentation)
+    /// enabled.
+    ///
+    /// The returned [`PgoProfile`] is a snapshot of the counts of all
+    /// instances of this module, including those that have already been
+    /// dropped. Pass it to [`Config::pgo_profile`](crate::Config::pgo_profile)
+    /// to optimize subsequent compilations of this module for the recorded
+    /// behavior.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> wasmtime::Result<()> {
+    /// use wasmtime::{Config, Engine, Instance, Module, Store};
+    ///
+    /// let wat = r#"
+    ///     (module
+    ///         (func (export "run") (param i32) (result i32)
+    ///             (if (result i32) (local.get 0)
+    ///                 (then (i32.const 1))
+    ///                 (else (i32.const 2))))
+    ///     )
+    /// "#;
+    ///
+    /// let mut config = Config::new();
+    /// config.pgo_instrumentation(true);
+    /// let engine = Engine::new(&config)?;
+    /// let module = Module::new(&engine, wat)?;
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    /// for i in 0..100 {
+    ///     run.call(&mut store, i)?;
+    /// }
+    /// let profile = module.pgo_profile().unwrap();
+    ///
+    /// // Compile the module again, this time optimized with the profile.
+    /// let mut config = Config::new();
+    /// config.pgo_profile(profile);
+    /// let engine = Engine::new(&config)?;
+    /// Module::new(&engine, wat)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn pgo_profile(&self) -> Option<PgoProfile> {
+        let key = self.env_module().pgo_key?;
+        let counters = self.inner.pgo_counters.as_ref()?;
+        Some(PgoProfile::from_module(key, counters.profile()))
+    }
+
     /// Returns a summary of the resources required to instantiate this
     /// [`Module`].
     ///
@@ -1126,10 +1207,10 @@ fn _assert_send_sync() {
 /// The hash computed for this structure is used to key the global wasmtime
 /// cache and dictates whether artifacts are reused. Consequently the contents
 /// of this hash dictate when artifacts are or aren't re-used.
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 pub(crate) struct HashedEngineCompileEnv<'a>(pub &'a Engine);
 
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 impl std::hash::Hash for HashedEngineCompileEnv<'_> {
     fn hash<H: std::hash::Hasher>(&self, hasher: &mut H) {
         // Hash the compiler's state based on its target and configuration.
@@ -1143,6 +1224,7 @@ impl std::hash::Hash for HashedEngineCompileEnv<'_> {
         config.tunables.hash(hasher);
         config.features.hash(hasher);
         config.wmemcheck.hash(hasher);
+        config.pgo_profile.hash(hasher);
 
         // Catch accidental bugs of reusing across crate versions.
         config.module_version.hash(hasher);
@@ -1174,11 +1256,19 @@ impl wasmtime_runtime::ModuleRuntimeInfo for ModuleInner {
             .as_ptr()
             .cast::<VMNativeCallFunction>()
             .cast_mut();
+        #[cfg(feature = "interpreter")]
+        if self.module.code_memory().is_bytecode() {
+            return Some(wasmtime_runtime::interpreter::native_call());
+        }
         Some(NonNull::new(ptr).unwrap())
     }
 
     fn array_to_wasm_trampoline(&self, index: DefinedFuncIndex) -> Option<VMArrayCallFunction> {
         let ptr = self.module.array_to_wasm_trampoline(index)?.as_ptr();
+        #[cfg(feature = "interpreter")]
+        if self.module.code_memory().is_bytecode() {
+            return Some(wasmtime_runtime::interpreter::array_call());
+        }
         Some(unsafe { mem::transmute::<*const u8, VMArrayCallFunction>(ptr) })
     }
 
@@ -1216,6 +1306,10 @@ impl wasmtime_runtime::ModuleRuntimeInfo for ModuleInner {
     fn offsets(&self) -> &VMOffsets<HostPtr> {
         &self.offsets
     }
+
+    fn pgo_counters(&self) -> Option<&ProfileCounters> {
+        self.pgo_counters.as_ref()
+    }
 }
 
 impl wasmtime_runtime::ModuleInfo for ModuleInner {
@@ -1242,6 +1336,12 @@ impl wasmtime_runtime::ModuleInfo for ModuleInner {
 
         Some(&info.stack_maps[index].stack_map)
     }
+
+    fn lookup_func_info(&self, pc: usize) -> Option<(&WasmFunctionInfo, u32)> {
+        let text_offset = pc - self.module.text().as_ptr() as usize;
+        let (index, func_offset) = self.module.func_by_text_offset(text_offset)?;
+        Some((self.module.wasm_func_info(index), func_offset))
+    }
 }
 
 /// A barebones implementation of ModuleRuntimeInfo that is useful for
@@ -1324,6 +1424,10 @@ impl wasmtime_runtime::ModuleRuntimeInfo for BareModuleInfo {
     fn offsets(&self) -> &VMOffsets<HostPtr> {
         &self.offsets
     }
+
+    fn pgo_counters(&self) -> Option<&ProfileCounters> {
+        None
+    }
 }
 
 /// Helper method to construct a `ModuleMemoryImages` for an associated
@@ -0,0 +1,193 @@
+use anyhow::{bail, Context, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use wasmtime_environ::ModuleProfile;
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+use wasmtime_environ::ModuleTranslation;
+
+/// Magic bytes at the start of a serialized [`PgoProfile`].
+const MAGIC: &[u8] = b"\0wasmtime-pgo\0";
+
+/// Version of the serialized [`PgoProfile`] format, bumped whenever the
+/// encoding or the meaning of branch sites changes.
+const VERSION: u32 = 1;
+
+/// An execution profile of WebAssembly modules used for profile-guided
+/// optimization.
+///
+/// Profiles are collected by running modules compiled with
+/// [`Config::pgo_instrumentation`](crate::Config::pgo_instrumentation)
+/// enabled and calling [`Module::pgo_profile`](crate::Module::pgo_profile) or
+/// [`Component::pgo_profile`](crate::component::Component::pgo_profile).
+/// They record how often each function of a module was called and which way
+/// each of its conditional branches went. Handing a profile back to
+/// [`Config::pgo_profile`](crate::Config::pgo_profile) then lets the compiler
+/// move code which was rarely executed out of the hot path and spend its
+/// inlining budget on the functions which were called the most.
+///
+/// A profile holds the counts of any number of modules, each keyed by a hash
+/// of the module's original WebAssembly bytes. Counts for a module are only
+/// ever applied when compiling exactly the same bytes, so a single profile may
+/// be shared by all modules of an application and profiles of modules that
+/// have since changed are ignored.
+///
+/// Profiles can be stored with [`PgoProfile::serialize`], for example next to
+/// the `*.cwasm` file produced by
+/// [`Engine::precompile_module`](crate::Engine::precompile_module), and
+/// profiles from multiple runs can be combined with [`PgoProfile::merge`].
+#[derive(Clone, Debug, Default, Hash)]
+pub struct PgoProfile {
+    modules: BTreeMap<[u8; 32], Arc<ModuleProfile>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedPgoProfile {
+    version: u32,
+    modules: Vec<([u8; 32], ModuleProfile)>,
+}
+
+impl PgoProfile {
+    /// Creates a new profile which contains no counts.
+    pub fn new() -> PgoProfile {
+        PgoProfile::default()
+    }
+
+    /// Returns whether this profile contains no modules.
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Adds all counts of `other` to this profile.
+    ///
+    /// Counts of the same module are summed, which can be used to combine
+    /// profiles collected over multiple runs of a program.
+    pub fn merge(&mut self, other: &PgoProfile) {
+        for (key, profile) in other.modules.iter() {
+            match self.modules.get_mut(key) {
+                Some(mine) => Arc::make_mut(mine).merge(profile),
+                None => {
+                    self.modules.insert(*key, profile.clone());
+                }
+            }
+        }
+    }
+
+    /// Serializes this profile into a byte buffer which can be stored and
+    /// later restored with [`PgoProfile::deserialize`].
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let serialized = SerializedPgoProfile {
+            version: VERSION,
+            modules: self
+                .modules
+                .iter()
+                .map(|(key, profile)| (*key, ModuleProfile::clone(profile)))
+                .collect(),
+        };
+        let mut bytes = MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &serialized)?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a profile previously produced by
+    /// [`PgoProfile::serialize`].
+    ///
+    /// Returns an error if `bytes` isn't a profile or was produced by an
+    /// incompatible version of Wasmtime.
+    pub fn deserialize(bytes: &[u8]) -> Result<PgoProfile> {
+        let bytes = match bytes.strip_prefix(MAGIC) {
+            Some(bytes) => bytes,
+            None => bail!("bytes are not a serialized profile"),
+        };
+        let serialized: SerializedPgoProfile =
+            bincode::deserialize(bytes).context("failed to deserialize profile")?;
+        if serialized.version != VERSION {
+            bail!(
+                "profile version {} is not compatible with this version of \
+                 Wasmtime, which uses version {VERSION}",
+                serialized.version
+            );
+        }
+        Ok(PgoProfile {
+            modules: serialized
+                .modules
+                .into_iter()
+                .map(|(key, profile)| (key, Arc::new(profile)))
+                .collect(),
+        })
+    }
+
+    pub(crate) fn from_module(key: [u8; 32], profile: ModuleProfile) -> PgoProfile {
+        let mut modules = BTreeMap::new();
+        modules.insert(key, Arc::new(profile));
+        PgoProfile { modules }
+    }
+
+    /// Hands the counts of the module being compiled in `translation` to the
+    /// compiler and, if `instrument` is set, records the key that the counts
+    /// collected by the compiled module will be stored under.
+    ///
+    /// Both core modules and the modules inside of components are compiled
+    /// through here.
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    pub(crate) fn prepare_translation(
+        &self,
+        instrument: bool,
+        translation: &mut ModuleTranslation<'_>,
+    ) {
+        // Profiles are keyed by the module's bytes, so only hash them if
+        // there's a profile to look up or one will be collected.
+        if !instrument && self.is_empty() {
+            return;
+        }
+        let key = PgoProfile::module_key(translation.wasm);
+        if instrument {
+            translation.module.pgo_key = Some(key);
+        }
+        translation.profile = self.modules.get(&key).cloned();
+    }
+
+    /// Returns the key that the profile of the module with the WebAssembly
+    /// bytes `wasm` is stored under.
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
+    fn module_key(wasm: &[u8]) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        Sha256::digest(wasm).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime_environ::{DefinedFuncIndex, FunctionProfile};
+
+    #[test]
+    fn serialize_and_merge() -> Result<()> {
+        let mut module = ModuleProfile::default();
+        module.functions.insert(
+            DefinedFuncIndex::from_u32(3),
+            FunctionProfile {
+                entries: 5,
+                branches: Vec::new(),
+            },
+        );
+        let mut profile = PgoProfile::from_module([1; 32], module);
+
+        let roundtrip = PgoProfile::deserialize(&profile.serialize()?)?;
+        assert_eq!(roundtrip.modules[&[1; 32]], profile.modules[&[1; 32]]);
+        assert!(!roundtrip.modules.contains_key(&[2; 32]));
+
+        profile.merge(&roundtrip);
+        let merged = &profile.modules[&[1; 32]];
+        assert_eq!(
+            merged
+                .function(DefinedFuncIndex::from_u32(3))
+                .unwrap()
+                .entries,
+            10
+        );
+
+        assert!(PgoProfile::deserialize(b"not a profile").is_err());
+        Ok(())
+    }
+}
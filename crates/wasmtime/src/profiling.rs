@@ -1,9 +1,11 @@
-use crate::{AsContext, Module};
+#[cfg(feature = "component-model")]
+use crate::component::Component;
+use crate::{AsContext, CallHook, Module};
 use anyhow::Result;
 use fxprof_processed_profile::debugid::DebugId;
 use fxprof_processed_profile::{
-    CategoryHandle, CpuDelta, Frame, FrameFlags, FrameInfo, LibraryInfo, Profile,
-    ReferenceTimestamp, Symbol, SymbolTable, Timestamp,
+    CategoryColor, CategoryPairHandle, CpuDelta, Frame, FrameFlags, FrameInfo, LibraryHandle,
+    LibraryInfo, Profile, ReferenceTimestamp, StringHandle, Symbol, SymbolTable, Timestamp,
 };
 use std::ops::Range;
 use std::sync::Arc;
@@ -11,13 +13,6 @@ use std::time::{Duration, Instant};
 use wasmtime_jit::CompiledModule;
 use wasmtime_runtime::Backtrace;
 
-// TODO: collect more data
-// - Provide additional hooks for recording host-guest transitions, to be
-//   invoked from a Store::call_hook
-// - On non-Windows, measure thread-local CPU usage between events with
-//   rustix::time::clock_gettime(ClockId::ThreadCPUTime)
-// - Report which wasm module, and maybe instance, each frame came from
-
 /// Collects basic profiling data for a single WebAssembly guest.
 ///
 /// This profiler can't provide measurements that are as accurate or detailed
@@ -39,7 +34,16 @@ use wasmtime_runtime::Backtrace;
 /// If you use epoch interruption, then samples will only be collected at
 /// function entry points and loop headers. This introduces some bias to the
 /// results. In addition, samples will only be taken at times when WebAssembly
-/// functions are running, not during host-calls.
+/// functions are running, not during host-calls. To account for the time spent
+/// in host-calls, forward every transition between WebAssembly and the host to
+/// [`GuestProfiler::call_hook`] from a callback registered with
+/// [`Store::call_hook_with_store()`](crate::Store::call_hook_with_store).
+///
+/// Each sample is weighted by the CPU time the current thread used since the
+/// previous sample or host-call transition. This is measured with the
+/// thread's CPU clock on Unix platforms and falls back to wall-clock time
+/// elsewhere. Guests which migrate between threads, such as those running on
+/// an async executor, may have some of their CPU time attributed inaccurately.
 ///
 /// It is technically possible to use fuel interruption instead. That
 /// introduces worse bias since samples occur after a certain number of
@@ -73,10 +77,26 @@ use wasmtime_runtime::Backtrace;
 #[derive(Debug)]
 pub struct GuestProfiler {
     profile: Profile,
-    modules: Vec<(Range<usize>, fxprof_processed_profile::LibraryHandle)>,
+    modules: Vec<ProfiledModule>,
     process: fxprof_processed_profile::ProcessHandle,
     thread: fxprof_processed_profile::ThreadHandle,
     start: Instant,
+    cpu_clock: CpuClock,
+    host_category: CategoryPairHandle,
+    host_label: StringHandle,
+    /// The labels of the host-calls the guest is currently in, which may be
+    /// nested when the host calls back into WebAssembly.
+    host_calls: Vec<StringHandle>,
+}
+
+/// A module whose frames appear in the profile.
+#[derive(Debug)]
+struct ProfiledModule {
+    text: Range<usize>,
+    lib: LibraryHandle,
+    /// Every module gets its own category so the profile shows which module
+    /// time was spent in.
+    category: CategoryPairHandle,
 }
 
 impl GuestProfiler {
@@ -99,17 +119,34 @@ impl GuestProfiler {
         let zero = ReferenceTimestamp::from_millis_since_unix_epoch(0.0);
         let mut profile = Profile::new(module_name, zero, interval.into());
 
+        const MODULE_COLORS: [CategoryColor; 5] = [
+            CategoryColor::Blue,
+            CategoryColor::Green,
+            CategoryColor::Purple,
+            CategoryColor::Orange,
+            CategoryColor::Red,
+        ];
         let mut modules: Vec<_> = modules
             .into_iter()
-            .filter_map(|(name, module)| {
+            .enumerate()
+            .filter_map(|(i, (name, module))| {
                 let compiled = module.compiled_module();
                 let text = compiled.text().as_ptr_range();
-                let address_range = text.start as usize..text.end as usize;
-                module_symbols(name, compiled).map(|lib| (address_range, profile.add_lib(lib)))
+                let text = text.start as usize..text.end as usize;
+                let category = profile.add_category(&name, MODULE_COLORS[i % MODULE_COLORS.len()]);
+                let lib = profile.add_lib(module_symbols(name, compiled)?);
+                Some(ProfiledModule {
+                    text,
+                    lib,
+                    category: category.into(),
+                })
             })
             .collect();
 
-        modules.sort_unstable_by_key(|(range, _)| range.start);
+        modules.sort_unstable_by_key(|module| module.text.start);
+
+        let host_category = profile.add_category("Host", CategoryColor::Yellow).into();
+        let host_label = profile.intern_string("host call");
 
         profile.set_reference_timestamp(std::time::SystemTime::now().into());
         let process = profile.add_process(module_name, 0, Timestamp::from_nanos_since_reference(0));
@@ -121,20 +158,118 @@ impl GuestProfiler {
             process,
             thread,
             start,
+            cpu_clock: CpuClock::new(start),
+            host_category,
+            host_label,
+            host_calls: Vec::new(),
         }
     }
 
+    /// Begin profiling a new guest running an instance of `component`.
+    ///
+    /// This is like [`GuestProfiler::new`] except that all core modules
+    /// within `component`, including the adapter modules Wasmtime generates
+    /// for it, appear in stack traces. Each of them is named after
+    /// `component_name` along with the module's own name if it has one, or its
+    /// index within the component otherwise. Frames in any `extra_modules`
+    /// are included as well.
+    #[cfg(feature = "component-model")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "component-model")))]
+    pub fn new_component(
+        component_name: &str,
+        interval: Duration,
+        component: &Component,
+        extra_modules: Vec<(String, Module)>,
+    ) -> Self {
+        let modules = component
+            .static_modules()
+            .enumerate()
+            .map(|(i, module)| {
+                let name = match module.name() {
+                    Some(name) => format!("{component_name}/{name}"),
+                    None => format!("{component_name}/module{i}"),
+                };
+                (name, module.clone())
+            })
+            .chain(extra_modules)
+            .collect();
+        Self::new(component_name, interval, modules)
+    }
+
     /// Add a sample to the profile. This function collects a backtrace from
     /// any stack frames for allowed modules on the current stack. It should
     /// typically be called from a callback registered using
     /// [`Store::epoch_deadline_callback()`](crate::Store::epoch_deadline_callback).
     pub fn sample(&mut self, store: impl AsContext) {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        let now = self.now();
+        let cpu_delta = self.cpu_clock.delta();
+        let frames = self.wasm_frames(&store);
+        self.profile
+            .add_sample(self.thread, now, frames.into_iter(), cpu_delta, 1);
+    }
+
+    /// Record a transition between WebAssembly and the host. This function
+    /// should typically be called with every event passed to a callback
+    /// registered using
+    /// [`Store::call_hook_with_store()`](crate::Store::call_hook_with_store).
+    ///
+    /// The CPU time used by WebAssembly up to a host-call is recorded as a
+    /// sample of the calling stack, and the CPU time spent in the host-call
+    /// itself is recorded as a sample of that same stack with an additional
+    /// frame on top, so host time shows up as its own entry below the
+    /// import's caller in the profile. That frame is named after the import
+    /// that was called, as `module::name`, or is just "host call" if the
+    /// import isn't known.
+    pub fn call_hook(&mut self, store: impl AsContext, kind: CallHook) {
+        match kind {
+            CallHook::CallingHost => {
+                let now = self.now();
+                let cpu_delta = self.cpu_clock.delta();
+                let frames = self.wasm_frames(&store);
+                self.profile
+                    .add_sample(self.thread, now, frames.into_iter(), cpu_delta, 1);
+                let label = match store.as_context().0.calling_host_import() {
+                    Some((module, name)) => {
+                        self.profile.intern_string(&format!("{module}::{name}"))
+                    }
+                    None => self.host_label,
+                };
+                self.host_calls.push(label);
+            }
+            CallHook::ReturningFromHost => {
+                let now = self.now();
+                let cpu_delta = self.cpu_clock.delta();
+                let label = self.host_calls.pop().unwrap_or(self.host_label);
+                let mut frames = self.wasm_frames(&store);
+                frames.push(FrameInfo {
+                    frame: Frame::Label(label),
+                    category_pair: self.host_category,
+                    flags: FrameFlags::empty(),
+                });
+                self.profile
+                    .add_sample(self.thread, now, frames.into_iter(), cpu_delta, 1);
+            }
+            // Time spent in the host before WebAssembly is entered isn't part
+            // of the guest's execution, unless WebAssembly is being re-entered
+            // from within a host-call.
+            CallHook::CallingWasm => {
+                if self.host_calls.is_empty() {
+                    self.cpu_clock.delta();
+                }
+            }
+            CallHook::ReturningFromWasm => {}
+        }
+    }
+
+    fn now(&self) -> Timestamp {
+        Timestamp::from_nanos_since_reference(self.start.elapsed().as_nanos().try_into().unwrap())
+    }
 
+    /// Collects the stack frames for allowed modules on the current stack,
+    /// oldest first.
+    fn wasm_frames(&self, store: impl AsContext) -> Vec<FrameInfo> {
         let backtrace = Backtrace::new(store.as_context().0.vmruntime_limits());
-        let frames = backtrace
+        backtrace
             .frames()
             // Samply needs to see the oldest frame first, but we list the newest
             // first, so iterate in reverse.
@@ -143,24 +278,21 @@ impl GuestProfiler {
                 // Find the first module whose start address includes this PC.
                 let module_idx = self
                     .modules
-                    .partition_point(|(range, _)| range.start > frame.pc());
-                if let Some((range, lib)) = self.modules.get(module_idx) {
-                    if range.contains(&frame.pc()) {
-                        return Some(FrameInfo {
-                            frame: Frame::RelativeAddressFromReturnAddress(
-                                *lib,
-                                u32::try_from(frame.pc() - range.start).unwrap(),
-                            ),
-                            category_pair: CategoryHandle::OTHER.into(),
-                            flags: FrameFlags::empty(),
-                        });
-                    }
+                    .partition_point(|module| module.text.start > frame.pc());
+                let module = self.modules.get(module_idx)?;
+                if !module.text.contains(&frame.pc()) {
+                    return None;
                 }
-                None
-            });
-
-        self.profile
-            .add_sample(self.thread, now, frames, CpuDelta::ZERO, 1);
+                Some(FrameInfo {
+                    frame: Frame::RelativeAddressFromReturnAddress(
+                        module.lib,
+                        u32::try_from(frame.pc() - module.text.start).unwrap(),
+                    ),
+                    category_pair: module.category,
+                    flags: FrameFlags::empty(),
+                })
+            })
+            .collect()
     }
 
     /// When the guest finishes running, call this function to write the
@@ -170,9 +302,7 @@ impl GuestProfiler {
     ///
     /// [fmt]: https://github.com/firefox-devtools/profiler/blob/main/docs-developer/processed-profile-format.md
     pub fn finish(mut self, output: impl std::io::Write) -> Result<()> {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        let now = self.now();
         self.profile.set_thread_end_time(self.thread, now);
         self.profile.set_process_end_time(self.process, now);
 
@@ -210,3 +340,50 @@ fn module_symbols(name: String, compiled: &CompiledModule) -> Option<LibraryInfo
         symbol_table: Some(Arc::new(SymbolTable::new(symbols))),
     })
 }
+
+/// Measures the CPU time used by the current thread between events, falling
+/// back to wall-clock time on platforms without a per-thread CPU clock.
+#[derive(Debug)]
+struct CpuClock {
+    start: Instant,
+    last: Duration,
+}
+
+impl CpuClock {
+    fn new(start: Instant) -> Self {
+        let mut clock = CpuClock {
+            start,
+            last: Duration::ZERO,
+        };
+        clock.last = clock.now();
+        clock
+    }
+
+    /// Returns the CPU time used since the previous call.
+    fn delta(&mut self) -> CpuDelta {
+        let now = self.now();
+        // The thread's clock may be behind if the guest moved to another
+        // thread since the last event.
+        let delta = now.saturating_sub(self.last);
+        self.last = now;
+        CpuDelta::from_nanos(delta.as_nanos().try_into().unwrap_or(u64::MAX))
+    }
+
+    #[cfg(unix)]
+    fn now(&self) -> Duration {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `time` is a valid pointer for `clock_gettime` to write to.
+        if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+            return self.start.elapsed();
+        }
+        Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+    }
+
+    #[cfg(not(unix))]
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
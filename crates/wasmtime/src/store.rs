@@ -98,7 +98,8 @@ use std::task::{Context, Poll};
 use wasmtime_runtime::{
     mpk::ProtectionKey, ExportGlobal, InstanceAllocationRequest, InstanceAllocator, InstanceHandle,
     ModuleInfo, OnDemandInstanceAllocator, SignalHandler, StoreBox, StorePtr, VMContext,
-    VMExternRef, VMExternRefActivationsTable, VMFuncRef, VMRuntimeLimits, WasmFault,
+    VMExternRef, VMExternRefActivationsTable, VMFuncRef, VMOpaqueContext, VMRuntimeLimits,
+    WasmFault,
 };
 
 mod context;
@@ -107,6 +108,8 @@ mod data;
 pub use self::data::*;
 mod func_refs;
 use func_refs::FuncRefs;
+mod stats;
+pub use self::stats::*;
 
 /// A [`Store`] is a collection of WebAssembly instances and host-defined state.
 ///
@@ -228,6 +231,7 @@ pub trait CallHookHandler<T>: Send {
 
 enum CallHookInner<T> {
     Sync(Box<dyn FnMut(&mut T, CallHook) -> Result<()> + Send + Sync>),
+    SyncWithStore(Box<dyn FnMut(StoreContextMut<'_, T>, CallHook) -> Result<()> + Send + Sync>),
     #[cfg(feature = "async")]
     Async(Box<dyn CallHookHandler<T> + Send + Sync>),
 }
@@ -292,7 +296,7 @@ pub struct StoreOpaque {
     runtime_limits: VMRuntimeLimits,
     instances: Vec<StoreInstance>,
     #[cfg(feature = "component-model")]
-    num_component_instances: usize,
+    component_instances: Vec<crate::component::Instance>,
     signal_handler: Option<Box<SignalHandler<'static>>>,
     externref_activations_table: VMExternRefActivationsTable,
     modules: ModuleRegistry,
@@ -350,6 +354,10 @@ pub struct StoreOpaque {
     /// guest code.
     pkey: Option<ProtectionKey>,
 
+    /// The module and name of the import being called while the call hook
+    /// runs for a call from WebAssembly into the host, if known.
+    calling_host_import: Option<(String, String)>,
+
     /// Runtime state for components used in the handling of resources, borrow,
     /// and calls. These also interact with the `ResourceAny` type and its
     /// internal representation.
@@ -479,7 +487,7 @@ impl<T> Store<T> {
                 runtime_limits: Default::default(),
                 instances: Vec::new(),
                 #[cfg(feature = "component-model")]
-                num_component_instances: 0,
+                component_instances: Vec::new(),
                 signal_handler: None,
                 externref_activations_table: VMExternRefActivationsTable::new(),
                 modules: ModuleRegistry::default(),
@@ -504,6 +512,7 @@ impl<T> Store<T> {
                 wasm_val_raw_storage: Vec::new(),
                 rooted_host_funcs: ManuallyDrop::new(Vec::new()),
                 pkey,
+                calling_host_import: None,
                 #[cfg(feature = "component-model")]
                 component_host_table: Default::default(),
                 #[cfg(feature = "component-model")]
@@ -766,6 +775,21 @@ impl<T> Store<T> {
         self.inner.call_hook = Some(CallHookInner::Sync(Box::new(hook)));
     }
 
+    /// Configure a function that runs on calls and returns between WebAssembly
+    /// and host code, and which is passed the whole store.
+    ///
+    /// This is like [`Store::call_hook`], and replaces any hook configured
+    /// with it, except that the function is passed a [`StoreContextMut`]
+    /// instead of just the store's data. This can be used to inspect the
+    /// guest's stack on each transition, for example to forward them to
+    /// [`GuestProfiler::call_hook`](crate::GuestProfiler::call_hook).
+    pub fn call_hook_with_store(
+        &mut self,
+        hook: impl FnMut(StoreContextMut<'_, T>, CallHook) -> Result<()> + Send + Sync + 'static,
+    ) {
+        self.inner.call_hook = Some(CallHookInner::SyncWithStore(Box::new(hook)));
+    }
+
     /// Returns the [`Engine`] that this store is associated with.
     pub fn engine(&self) -> &Engine {
         self.inner.engine()
@@ -791,6 +815,14 @@ impl<T> Store<T> {
         self.inner.get_fuel()
     }
 
+    /// Returns a snapshot of the memory, tables and instances currently used
+    /// by this store.
+    ///
+    /// See [`StoreStats`] for more information.
+    pub fn stats(&self) -> StoreStats {
+        self.inner.stats()
+    }
+
     /// Set the fuel to this [`Store`] for wasm to consume while executing.
     ///
     /// For this method to work fuel consumption must be enabled via
@@ -993,6 +1025,13 @@ impl<'a, T> StoreContext<'a, T> {
     pub fn get_fuel(&self) -> Result<u64> {
         self.0.get_fuel()
     }
+
+    /// Returns a snapshot of the resources used by this store.
+    ///
+    /// For more information see [`Store::stats`].
+    pub fn stats(&self) -> StoreStats {
+        self.0.stats()
+    }
 }
 
 impl<'a, T> StoreContextMut<'a, T> {
@@ -1029,6 +1068,13 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.get_fuel()
     }
 
+    /// Returns a snapshot of the resources used by this store.
+    ///
+    /// For more information see [`Store::stats`].
+    pub fn stats(&self) -> StoreStats {
+        self.0.stats()
+    }
+
     /// Set the amount of fuel in this store.
     ///
     /// For more information see [`Store::set_fuel`]
@@ -1094,6 +1140,18 @@ impl<T> StoreInner<T> {
         match &mut self.call_hook {
             Some(CallHookInner::Sync(hook)) => hook(&mut self.data, s),
 
+            // The hook is taken out of the store for the duration of the call
+            // so the store itself can be handed to it.
+            Some(CallHookInner::SyncWithStore(_)) => {
+                let mut hook = match self.call_hook.take() {
+                    Some(CallHookInner::SyncWithStore(hook)) => hook,
+                    _ => unreachable!(),
+                };
+                let result = hook(StoreContextMut(self), s);
+                self.call_hook = Some(CallHookInner::SyncWithStore(hook));
+                result
+            }
+
             #[cfg(feature = "async")]
             Some(CallHookInner::Async(handler)) => unsafe {
                 Ok(self
@@ -1106,6 +1164,26 @@ impl<T> StoreInner<T> {
             None => Ok(()),
         }
     }
+
+    /// Runs the call hook for a call from the `caller` instance into the host
+    /// function whose callee context is `callee`.
+    ///
+    /// A hook passed the whole store can find out which import was called
+    /// with [`StoreOpaque::calling_host_import`].
+    pub fn call_hook_calling_host(
+        &mut self,
+        caller: &wasmtime_runtime::Instance,
+        callee: *mut VMOpaqueContext,
+    ) -> Result<()> {
+        if let Some(CallHookInner::SyncWithStore(_)) = &self.call_hook {
+            self.inner.calling_host_import = caller
+                .imported_function_name(callee)
+                .map(|(module, name)| (module.to_string(), name.to_string()));
+        }
+        let result = self.call_hook(CallHook::CallingHost);
+        self.inner.calling_host_import = None;
+        result
+    }
 }
 
 fn get_fuel(injected_fuel: i64, fuel_reserve: u64) -> u64 {
@@ -1267,6 +1345,18 @@ impl StoreOpaque {
         InstanceId(self.instances.len() - 1)
     }
 
+    /// Returns the module and name of the import being called, while the call
+    /// hook runs for a call from WebAssembly into the host.
+    ///
+    /// This is only known for hooks configured with
+    /// [`Store::call_hook_with_store`].
+    #[cfg_attr(not(feature = "profiling"), allow(dead_code))]
+    pub(crate) fn calling_host_import(&self) -> Option<(&str, &str)> {
+        self.calling_host_import
+            .as_ref()
+            .map(|(module, name)| (module.as_str(), name.as_str()))
+    }
+
     pub fn instance(&self, id: InstanceId) -> &InstanceHandle {
         &self.instances[id.0].handle
     }
@@ -1388,6 +1478,45 @@ impl StoreOpaque {
         })
     }
 
+    pub fn stats(&self) -> StoreStats {
+        let mut stats = StoreStats::default();
+        // NB: host-created memories and tables live in dummy instances, so
+        // those are visited too but not counted as instances.
+        for instance in self.instances.iter() {
+            if let StoreInstanceKind::Real { .. } = instance.kind {
+                stats.instances += 1;
+            }
+            stats
+                .memories
+                .extend(
+                    instance
+                        .handle
+                        .defined_memory_objects()
+                        .map(|memory| MemoryStats {
+                            size_bytes: memory.byte_size(),
+                            maximum_bytes: memory.maximum_byte_size(),
+                            reserved_bytes: memory.reserved_byte_size(),
+                            resident_bytes: memory.resident_byte_size(),
+                        }),
+                );
+            stats.tables.extend(
+                instance
+                    .handle
+                    .defined_table_objects()
+                    .map(|table| TableStats {
+                        size: table.size(),
+                        maximum: table.maximum(),
+                    }),
+            );
+        }
+        #[cfg(feature = "component-model")]
+        {
+            stats.component_instances = self.component_instances.len();
+            stats.host_resource_handles = self.component_host_table.num_handles();
+        }
+        stats
+    }
+
     pub fn get_fuel(&self) -> Result<u64> {
         anyhow::ensure!(
             self.engine().config().tunables.consume_fuel,
@@ -1626,12 +1755,12 @@ at https://bytecodealliance.org/security.
 
     #[cfg(feature = "component-model")]
     pub(crate) fn push_component_instance(&mut self, instance: crate::component::Instance) {
-        // We don't actually need the instance itself right now, but it seems
-        // like something we will almost certainly eventually want to keep
-        // around, so force callers to provide it.
-        let _ = instance;
+        self.component_instances.push(instance);
+    }
 
-        self.num_component_instances += 1;
+    #[cfg(all(feature = "component-model", feature = "coredump"))]
+    pub(crate) fn all_component_instances(&self) -> &[crate::component::Instance] {
+        &self.component_instances
     }
 }
 
@@ -2001,6 +2130,10 @@ unsafe impl<T> wasmtime_runtime::Store for StoreInner<T> {
         (&mut inner.externref_activations_table, &inner.modules)
     }
 
+    fn module_info_lookup(&self) -> &dyn wasmtime_runtime::ModuleInfoLookup {
+        &self.inner.modules
+    }
+
     fn memory_growing(
         &mut self,
         current: usize,
@@ -2237,7 +2370,7 @@ impl Drop for StoreOpaque {
 
             #[cfg(feature = "component-model")]
             {
-                for _ in 0..self.num_component_instances {
+                for _ in 0..self.component_instances.len() {
                     allocator.decrement_component_instance_count();
                 }
             }
@@ -0,0 +1,91 @@
+/// A snapshot of the resources used by a [`Store`](crate::Store).
+///
+/// This is returned by [`Store::stats`](crate::Store::stats) and is intended
+/// for exporting per-store metrics. Taking a snapshot visits every instance,
+/// memory and table in the store, so it is not free, but it does not pause or
+/// otherwise affect execution of wasm.
+///
+/// Linear memories and tables never shrink, so their current sizes are also
+/// the largest sizes they have reached.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct StoreStats {
+    /// The number of core wasm instances in the store.
+    pub instances: usize,
+
+    /// The number of component instances in the store.
+    #[cfg(feature = "component-model")]
+    pub component_instances: usize,
+
+    /// The number of component resource handles currently owned by the host,
+    /// for example resources returned to the host by a guest export.
+    ///
+    /// This does not include entries of any
+    /// [`ResourceTable`](crate::component::ResourceTable) held in the store's
+    /// data; use [`ResourceTable::len`](crate::component::ResourceTable::len)
+    /// for those.
+    #[cfg(feature = "component-model")]
+    pub host_resource_handles: usize,
+
+    /// Statistics for each linear memory defined in the store, including
+    /// memories created by the host.
+    ///
+    /// A shared memory is only reported by the store it was created in.
+    pub memories: Vec<MemoryStats>,
+
+    /// Statistics for each table defined in the store, including tables
+    /// created by the host.
+    pub tables: Vec<TableStats>,
+}
+
+impl StoreStats {
+    /// Returns the sum of [`MemoryStats::size_bytes`] for all memories.
+    pub fn total_memory_bytes(&self) -> usize {
+        self.memories.iter().map(|m| m.size_bytes).sum()
+    }
+
+    /// Returns the sum of [`MemoryStats::resident_bytes`] for all memories, or
+    /// `None` if resident sizes can't be determined on this platform.
+    pub fn total_resident_bytes(&self) -> Option<usize> {
+        self.memories.iter().map(|m| m.resident_bytes).sum()
+    }
+}
+
+/// Statistics about a single linear memory, as reported by [`StoreStats`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MemoryStats {
+    /// The current size of the memory, in bytes.
+    pub size_bytes: usize,
+
+    /// The maximum size, in bytes, the memory may grow to, if bounded.
+    ///
+    /// This takes into account limits imposed by the instance allocator, such
+    /// as the slot size of the pooling allocator, in addition to the memory's
+    /// type.
+    pub maximum_bytes: Option<usize>,
+
+    /// The amount of virtual address space, in bytes, reserved for this
+    /// memory including its guard regions.
+    pub reserved_bytes: usize,
+
+    /// The number of bytes of the memory that are currently backed by
+    /// physical memory, or `None` if this can't be determined on this
+    /// platform.
+    ///
+    /// Pages which have never been touched, or which were reset by the
+    /// pooling allocator, are not resident, so this is often much smaller
+    /// than [`MemoryStats::size_bytes`].
+    pub resident_bytes: Option<usize>,
+}
+
+/// Statistics about a single table, as reported by [`StoreStats`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TableStats {
+    /// The current number of elements in the table.
+    pub size: u32,
+
+    /// The maximum number of elements the table may grow to, if bounded.
+    pub maximum: Option<u32>,
+}
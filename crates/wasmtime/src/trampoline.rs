@@ -41,7 +41,8 @@ fn create_handle(
         let module = Arc::new(module);
         let runtime_info =
             &BareModuleInfo::maybe_imported_func(module, one_signature).into_traitobj();
-        let allocator = OnDemandInstanceAllocator::new(config.mem_creator.clone(), 0);
+        let mut allocator = OnDemandInstanceAllocator::new(config.mem_creator.clone(), 0);
+        allocator.set_huge_pages(config.memory_huge_pages);
         let handle = allocator.allocate_module(InstanceAllocationRequest {
             imports,
             host_state,
@@ -12,7 +12,7 @@ use wasmtime_runtime::{
 struct TrampolineState<F> {
     func: F,
     #[allow(dead_code)]
-    code_memory: CodeMemory,
+    code_memory: Option<CodeMemory>,
 }
 
 /// Shim to call a host-defined function that uses the array calling convention.
@@ -28,7 +28,7 @@ unsafe extern "C" fn array_call_shim<F>(
     values_vec: *mut ValRaw,
     values_vec_len: usize,
 ) where
-    F: Fn(*mut VMContext, &mut [ValRaw]) -> Result<()> + 'static,
+    F: Fn(*mut VMContext, *mut VMOpaqueContext, &mut [ValRaw]) -> Result<()> + 'static,
 {
     // Here we are careful to use `catch_unwind` to ensure Rust panics don't
     // unwind past us. The primary reason for this is that Rust considers it UB
@@ -52,7 +52,11 @@ unsafe extern "C" fn array_call_shim<F>(
         debug_assert!(state.is::<TrampolineState<F>>());
         let state = &*(state as *const _ as *const TrampolineState<F>);
         let values_vec = std::slice::from_raw_parts_mut(values_vec, values_vec_len);
-        (state.func)(VMContext::from_opaque(caller_vmctx), values_vec)
+        (state.func)(
+            VMContext::from_opaque(caller_vmctx),
+            vmctx.cast(),
+            values_vec,
+        )
     }));
 
     match result {
@@ -72,17 +76,44 @@ unsafe extern "C" fn array_call_shim<F>(
     }
 }
 
-#[cfg(any(feature = "cranelift", feature = "winch"))]
+#[cfg(any(feature = "cranelift", feature = "winch", feature = "interpreter"))]
 pub fn create_array_call_function<F>(
     ft: &FuncType,
     func: F,
     engine: &Engine,
 ) -> Result<StoreBox<VMArrayCallHostFuncContext>>
 where
-    F: Fn(*mut VMContext, &mut [ValRaw]) -> Result<()> + Send + Sync + 'static,
+    F: Fn(*mut VMContext, *mut VMOpaqueContext, &mut [ValRaw]) -> Result<()>
+        + Send
+        + Sync
+        + 'static,
 {
     use std::ptr;
 
+    let array_call = array_call_shim::<F>;
+
+    // The interpreter calls host functions through their `array_call`, so no
+    // trampolines are needed (or can be compiled) in that case.
+    #[cfg(feature = "interpreter")]
+    if engine.config().tunables.interpreter {
+        let sig = engine.signatures().register(ft.as_wasm_func_type());
+        unsafe {
+            return Ok(VMArrayCallHostFuncContext::new(
+                VMFuncRef {
+                    array_call,
+                    wasm_call: Some(wasmtime_runtime::interpreter::wasm_call()),
+                    native_call: wasmtime_runtime::interpreter::native_call(),
+                    type_index: sig,
+                    vmctx: ptr::null_mut(),
+                },
+                Box::new(TrampolineState {
+                    func,
+                    code_memory: None,
+                }),
+            ));
+        }
+    }
+
     let mut obj = engine
         .compiler()
         .object(wasmtime_environ::ObjectKind::Module)?;
@@ -108,8 +139,6 @@ where
 
     let text = code_memory.text();
 
-    let array_call = array_call_shim::<F>;
-
     let wasm_call = text[wasm_call_range.start as usize..].as_ptr() as *mut _;
     let wasm_call = Some(NonNull::new(wasm_call).unwrap());
 
@@ -127,7 +156,10 @@ where
                 type_index: sig,
                 vmctx: ptr::null_mut(),
             },
-            Box::new(TrampolineState { func, code_memory }),
+            Box::new(TrampolineState {
+                func,
+                code_memory: Some(code_memory),
+            }),
         ))
     }
 }
@@ -67,10 +67,13 @@ pub fn create_memory(
         pkey: None,
     };
 
+    let mut ondemand = OnDemandInstanceAllocator::default();
+    ondemand.set_huge_pages(store.engine().config().memory_huge_pages);
+
     unsafe {
         let handle = SingleMemoryInstance {
             preallocation,
-            ondemand: OnDemandInstanceAllocator::default(),
+            ondemand,
         }
         .allocate_module(request)?;
         let instance_id = store.add_dummy_instance(handle.clone());
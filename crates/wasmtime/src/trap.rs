@@ -138,8 +138,18 @@ pub(crate) fn from_runtime_box(
     let _ = &coredumpstack;
     #[cfg(feature = "coredump")]
     if let Some(coredump) = coredumpstack {
-        let bt = WasmBacktrace::from_captured(store, coredump.bt, pc);
-        let cd = WasmCoreDump::new(store, bt);
+        let (bt, frame_indices) = WasmBacktrace::from_captured_frames(store, coredump.bt, pc);
+        let wasm_frames = |values: Vec<Vec<wasm_encoder::CoreDumpValue>>| -> Vec<_> {
+            values
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| frame_indices.binary_search(i).is_ok())
+                .map(|(_, values)| values)
+                .collect()
+        };
+        let locals = wasm_frames(coredump.locals);
+        let operand_stack = wasm_frames(coredump.operand_stack);
+        let cd = WasmCoreDump::new(store, bt, locals, operand_stack);
         error = error.context(cd);
     }
 
@@ -287,12 +297,23 @@ impl WasmBacktrace {
         runtime_trace: wasmtime_runtime::Backtrace,
         trap_pc: Option<usize>,
     ) -> Self {
+        Self::from_captured_frames(store, runtime_trace, trap_pc).0
+    }
+
+    /// Like `from_captured`, but also returns the index within
+    /// `runtime_trace` of each of the resulting frames.
+    fn from_captured_frames(
+        store: &StoreOpaque,
+        runtime_trace: wasmtime_runtime::Backtrace,
+        trap_pc: Option<usize>,
+    ) -> (Self, Vec<usize>) {
         let mut wasm_trace = Vec::<FrameInfo>::with_capacity(runtime_trace.frames().len());
+        let mut frame_indices = Vec::with_capacity(runtime_trace.frames().len());
         let mut hint_wasm_backtrace_details_env = false;
         let wasm_backtrace_details_env_used =
             store.engine().config().wasm_backtrace_details_env_used;
 
-        for frame in runtime_trace.frames() {
+        for (i, frame) in runtime_trace.frames().enumerate() {
             debug_assert!(frame.pc() != 0);
 
             // Note that we need to be careful about the pc we pass in
@@ -337,6 +358,7 @@ impl WasmBacktrace {
             // store's module registry.
             if let Some((info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
                 wasm_trace.push(info);
+                frame_indices.push(i);
 
                 // If this frame has unparsed debug information and the
                 // store's configuration indicates that we were
@@ -351,11 +373,12 @@ impl WasmBacktrace {
             }
         }
 
-        Self {
+        let bt = Self {
             wasm_trace,
             runtime_trace,
             hint_wasm_backtrace_details_env,
-        }
+        };
+        (bt, frame_indices)
     }
 
     /// Returns a list of function frames in WebAssembly this backtrace
@@ -468,42 +491,37 @@ impl FrameInfo {
             text_offset
         );
 
-        // Use our wasm-relative pc to symbolize this frame. If there's a
-        // symbolication context (dwarf debug info) available then we can try to
-        // look this up there.
-        //
-        // Note that dwarf pcs are code-section-relative, hence the subtraction
-        // from the location of `instr`. Also note that all errors are ignored
-        // here for now since technically wasm modules can always have any
-        // custom section contents.
-        let mut symbols = Vec::new();
-
-        let _ = &mut symbols;
-        #[cfg(feature = "addr2line")]
-        if let Some(s) = &compiled_module.symbolize_context().ok().and_then(|c| c) {
-            if let Some(offset) = instr.and_then(|i| i.file_offset()) {
-                let to_lookup = u64::from(offset) - s.code_section_offset();
-                if let Ok(mut frames) = s.addr2line().find_frames(to_lookup).skip_all_loads() {
-                    while let Ok(Some(frame)) = frames.next() {
-                        symbols.push(FrameSymbol {
-                            name: frame
-                                .function
-                                .as_ref()
-                                .and_then(|l| l.raw_name().ok())
-                                .map(|s| s.to_string()),
-                            file: frame
-                                .location
-                                .as_ref()
-                                .and_then(|l| l.file)
-                                .map(|s| s.to_string()),
-                            line: frame.location.as_ref().and_then(|l| l.line),
-                            column: frame.location.as_ref().and_then(|l| l.column),
-                        });
-                    }
-                }
-            }
-        }
+        let symbols = symbolize(&module, instr);
+        Some(FrameInfo {
+            module,
+            func_index,
+            func_name,
+            instr,
+            func_start,
+            symbols,
+        })
+    }
 
+    /// Recreates frame information from a function index and an offset from
+    /// the start of that function's body, such as the frames recorded in a
+    /// wasm coredump.
+    ///
+    /// Returns `None` if `func_index` isn't a function defined by `module`.
+    #[cfg(feature = "coredump")]
+    pub(crate) fn from_func_offset(
+        module: Module,
+        func_index: u32,
+        func_offset: u32,
+    ) -> Option<FrameInfo> {
+        let compiled_module = module.compiled_module();
+        let index = wasmtime_environ::FuncIndex::from_u32(func_index);
+        let defined = compiled_module.module().defined_func_index(index)?;
+        let func_start = compiled_module.wasm_func_info(defined).start_srcloc;
+        let instr = func_start
+            .file_offset()
+            .map(|start| FilePos::new(start + func_offset));
+        let func_name = compiled_module.func_name(index).map(|s| s.to_string());
+        let symbols = symbolize(&module, instr);
         Some(FrameInfo {
             module,
             func_index,
@@ -591,6 +609,50 @@ impl FrameInfo {
     }
 }
 
+/// Uses the wasm-relative pc `instr` to symbolize a frame. If there's a
+/// symbolication context (dwarf debug info) available then we can try to look
+/// this up there.
+///
+/// Note that dwarf pcs are code-section-relative, hence the subtraction from
+/// the location of `instr`. Also note that all errors are ignored here for now
+/// since technically wasm modules can always have any custom section contents.
+fn symbolize(module: &Module, instr: Option<FilePos>) -> Vec<FrameSymbol> {
+    let mut symbols = Vec::new();
+
+    let _ = (&mut symbols, module, instr);
+    #[cfg(feature = "addr2line")]
+    if let Some(s) = &module
+        .compiled_module()
+        .symbolize_context()
+        .ok()
+        .and_then(|c| c)
+    {
+        if let Some(offset) = instr.and_then(|i| i.file_offset()) {
+            let to_lookup = u64::from(offset) - s.code_section_offset();
+            if let Ok(mut frames) = s.addr2line().find_frames(to_lookup).skip_all_loads() {
+                while let Ok(Some(frame)) = frames.next() {
+                    symbols.push(FrameSymbol {
+                        name: frame
+                            .function
+                            .as_ref()
+                            .and_then(|l| l.raw_name().ok())
+                            .map(|s| s.to_string()),
+                        file: frame
+                            .location
+                            .as_ref()
+                            .and_then(|l| l.file)
+                            .map(|s| s.to_string()),
+                        line: frame.location.as_ref().and_then(|l| l.line),
+                        column: frame.location.as_ref().and_then(|l| l.column),
+                    });
+                }
+            }
+        }
+    }
+
+    symbols
+}
+
 /// Debug information for a symbol that is attached to a [`FrameInfo`].
 ///
 /// When DWARF debug information is present in a wasm file then this structure
@@ -105,6 +105,10 @@ impl wasmtime_environ::Compiler for Compiler {
             WasmFunctionInfo {
                 start_srcloc,
                 stack_maps: Box::new([]),
+                locals: Box::new([]),
+                local_slots: Box::new([]),
+                operand_slots: Box::new([]),
+                operand_stack_heights: Box::new([]),
             },
             Box::new(compiled_function),
         ))
@@ -60,6 +60,7 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasmtime-cache",
     "winch-codegen",
     "wasmtime-winch",
+    "wasmtime-interpreter",
     "wasmtime",
     // wasi-common/wiggle
     "wiggle",
@@ -68,7 +69,10 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasi-tokio",
     // other misc wasmtime crates
     "wasmtime-wasi",
+    "wasmtime-wasi-config",
     "wasmtime-wasi-http",
+    "wasmtime-wasi-keyvalue",
+    "wasmtime-wasi-logging",
     "wasmtime-wasi-nn",
     "wasmtime-wasi-threads",
     "wasmtime-wast",
@@ -89,6 +93,9 @@ const PUBLIC_CRATES: &[&str] = &[
     // patch releases.
     "wasmtime",
     "wasmtime-wasi",
+    "wasmtime-wasi-config",
+    "wasmtime-wasi-keyvalue",
+    "wasmtime-wasi-logging",
     "wasmtime-wasi-nn",
     "wasmtime-wasi-threads",
     "wasmtime-cli",
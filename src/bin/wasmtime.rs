@@ -58,10 +58,18 @@ enum Subcommand {
     #[cfg(feature = "cranelift")]
     Compile(wasmtime_cli::commands::CompileCommand),
 
+    /// Inspects a wasm core dump.
+    #[cfg(feature = "coredump")]
+    Coredump(wasmtime_cli::commands::CoredumpCommand),
+
     /// Explore the compilation of a WebAssembly module to native code.
     #[cfg(feature = "explore")]
     Explore(wasmtime_cli::commands::ExploreCommand),
 
+    /// Inspects a precompiled `*.cwasm` artifact.
+    #[cfg(feature = "objdump")]
+    Objdump(wasmtime_cli::commands::ObjdumpCommand),
+
     /// Serves requests from a wasi-http proxy component.
     #[cfg(feature = "serve")]
     Serve(wasmtime_cli::commands::ServeCommand),
@@ -73,6 +81,10 @@ enum Subcommand {
     /// Runs a WebAssembly test script file
     #[cfg(feature = "wast")]
     Wast(wasmtime_cli::commands::WastCommand),
+
+    /// Prints the WIT interface of a component
+    #[cfg(feature = "wit")]
+    Wit(wasmtime_cli::commands::WitCommand),
 }
 
 impl Wasmtime {
@@ -88,9 +100,15 @@ impl Wasmtime {
             #[cfg(feature = "cranelift")]
             Subcommand::Compile(c) => c.execute(),
 
+            #[cfg(feature = "coredump")]
+            Subcommand::Coredump(c) => c.execute(),
+
             #[cfg(feature = "explore")]
             Subcommand::Explore(c) => c.execute(),
 
+            #[cfg(feature = "objdump")]
+            Subcommand::Objdump(c) => c.execute(),
+
             #[cfg(feature = "serve")]
             Subcommand::Serve(c) => c.execute(),
 
@@ -99,6 +117,9 @@ impl Wasmtime {
 
             #[cfg(feature = "wast")]
             Subcommand::Wast(c) => c.execute(),
+
+            #[cfg(feature = "wit")]
+            Subcommand::Wit(c) => c.execute(),
         }
     }
 }
@@ -8,6 +8,11 @@ mod serve;
 #[cfg(feature = "serve")]
 pub use self::serve::*;
 
+#[cfg(feature = "coredump")]
+mod coredump;
+#[cfg(feature = "coredump")]
+pub use self::coredump::*;
+
 #[cfg(feature = "explore")]
 mod explore;
 #[cfg(feature = "explore")]
@@ -28,7 +33,17 @@ mod compile;
 #[cfg(feature = "compile")]
 pub use self::compile::*;
 
+#[cfg(feature = "objdump")]
+mod objdump;
+#[cfg(feature = "objdump")]
+pub use self::objdump::*;
+
 #[cfg(feature = "cranelift")]
 mod settings;
 #[cfg(feature = "cranelift")]
 pub use self::settings::*;
+
+#[cfg(feature = "wit")]
+mod wit;
+#[cfg(feature = "wit")]
+pub use self::wit::*;
@@ -0,0 +1,259 @@
+//! The module that implements the `wasmtime coredump` command.
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::PathBuf;
+use wasmtime::{
+    CoreDumpComponentExport, CoreDumpComponentExportKind, CoreDumpFile, Engine, Module,
+};
+use wasmtime_cli_flags::CommonOptions;
+
+/// Inspects a wasm core dump, such as one written by `wasmtime run
+/// -Dcoredump=...`.
+///
+/// The stack of each thread is printed along with the locals and globals of
+/// each frame, as well as the name, exports and core instances of each
+/// component instance.
+/// Frames are symbolicated with the name section and DWARF debug
+/// information of the original modules if they are provided. A module is
+/// matched to the core dump's modules by the name in its name section, or
+/// otherwise by position if as many modules are provided as the core dump
+/// recorded.
+#[derive(Parser, PartialEq)]
+pub struct CoredumpCommand {
+    #[command(flatten)]
+    common: CommonOptions,
+
+    /// Dump the contents of a range of memory, as `START..END` for the first
+    /// memory or `MEMORY:START..END` for any other one.
+    #[arg(long, value_name = "RANGE", value_parser = parse_memory_range)]
+    memory: Vec<MemoryRange>,
+
+    /// The path of the core dump to inspect
+    #[arg(required = true, value_name = "COREDUMP")]
+    coredump: PathBuf,
+
+    /// The paths of the original modules that were running
+    #[arg(value_name = "MODULE")]
+    modules: Vec<PathBuf>,
+}
+
+#[derive(Clone, PartialEq)]
+struct MemoryRange {
+    memory: u32,
+    range: Range<u64>,
+}
+
+fn parse_memory_range(s: &str) -> Result<MemoryRange> {
+    let (memory, range) = match s.split_once(':') {
+        Some((memory, range)) => (memory.parse()?, range),
+        None => (0, s),
+    };
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| anyhow!("expected a range of the form `START..END`"))?;
+    let parse = |s: &str| -> Result<u64> {
+        match s.strip_prefix("0x") {
+            Some(hex) => Ok(u64::from_str_radix(hex, 16)?),
+            None => Ok(s.parse()?),
+        }
+    };
+    let range = parse(start)?..parse(end)?;
+    if range.start > range.end {
+        bail!("the start of a range must not be after its end");
+    }
+    Ok(MemoryRange { memory, range })
+}
+
+impl CoredumpCommand {
+    /// Executes the command.
+    pub fn execute(mut self) -> Result<()> {
+        self.common.init_logging()?;
+
+        let bytes = std::fs::read(&self.coredump)
+            .with_context(|| format!("failed to read: {}", self.coredump.display()))?;
+        let dump = CoreDumpFile::parse(&bytes)
+            .with_context(|| format!("failed to parse core dump: {}", self.coredump.display()))?;
+
+        let engine = Engine::new(&self.common.config(None)?)?;
+        let modules = self
+            .modules
+            .iter()
+            .map(|path| {
+                Module::from_file(&engine, path)
+                    .with_context(|| format!("failed to compile: {}", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let modules = dump
+            .modules()
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                modules
+                    .iter()
+                    .find(|m| m.name() == Some(name.as_str()))
+                    .or_else(|| {
+                        if modules.len() == dump.modules().len() {
+                            modules.get(i)
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        println!("core dump of `{}`", dump.name());
+        if !dump.component_instances().is_empty() {
+            println!();
+            println!("component instances:");
+            for (i, instance) in dump.component_instances().iter().enumerate() {
+                let mut line = format!("  {i:>3}: {}", instance.name().unwrap_or("<unnamed>"));
+                if let Some(parent) = instance.parent() {
+                    write!(line, " (instantiated by {parent})")?;
+                }
+                println!("{line}");
+                if !instance.core_instances().is_empty() {
+                    let mut line = "       core instances:".to_string();
+                    for core_instance in instance.core_instances() {
+                        let module_name = dump
+                            .instances()
+                            .get(*core_instance as usize)
+                            .and_then(|instance| dump.modules().get(instance.module() as usize))
+                            .map(|s| s.as_str())
+                            .unwrap_or("<unknown>");
+                        write!(line, " {core_instance} ({module_name})")?;
+                    }
+                    println!("{line}");
+                }
+                if !instance.exports().is_empty() {
+                    println!("       exports:");
+                    print_component_exports(instance.exports(), 9);
+                }
+            }
+        }
+        for thread in dump.threads() {
+            println!();
+            println!("thread `{}`:", thread.name());
+            for (i, frame) in thread.frames().iter().enumerate() {
+                let instance = dump
+                    .instances()
+                    .get(frame.instance() as usize)
+                    .ok_or_else(|| anyhow!("frame {i} refers to an unknown instance"))?;
+                let module_name = dump
+                    .modules()
+                    .get(instance.module() as usize)
+                    .map(|s| s.as_str())
+                    .unwrap_or("<unknown>");
+
+                let info = modules
+                    .get(instance.module() as usize)
+                    .copied()
+                    .flatten()
+                    .and_then(|module| frame.symbolize(module));
+                let mut line = format!("  {i:>3}: {module_name}!");
+                match info.as_ref().and_then(|info| info.func_name()) {
+                    Some(name) => write!(line, "{name}")?,
+                    None => write!(line, "<wasm function {}>", frame.func_index())?,
+                }
+                match info.as_ref().and_then(|info| info.module_offset()) {
+                    Some(offset) => write!(line, " (wasm offset {offset:#x})")?,
+                    None => write!(line, " (function offset {:#x})", frame.func_offset())?,
+                }
+                println!("{line}");
+                for symbol in info.iter().flat_map(|info| info.symbols()) {
+                    let mut line = "           at ".to_string();
+                    match symbol.name() {
+                        Some(name) => write!(line, "{name}")?,
+                        None => write!(line, "<unknown>")?,
+                    }
+                    if let Some(file) = symbol.file() {
+                        write!(line, " {file}")?;
+                        if let Some(l) = symbol.line() {
+                            write!(line, ":{l}")?;
+                            if let Some(c) = symbol.column() {
+                                write!(line, ":{c}")?;
+                            }
+                        }
+                    }
+                    println!("{line}");
+                }
+
+                if !frame.locals().is_empty() {
+                    println!("       locals:");
+                    for (j, value) in frame.locals().iter().enumerate() {
+                        println!("         {j}: {value}");
+                    }
+                }
+                if !frame.stack().is_empty() {
+                    println!("       stack:");
+                    for (j, value) in frame.stack().iter().enumerate() {
+                        println!("         {j}: {value}");
+                    }
+                }
+                if !instance.globals().is_empty() {
+                    println!("       globals:");
+                    for (j, global) in instance.globals().iter().enumerate() {
+                        match dump.globals().get(*global as usize) {
+                            Some(value) => println!("         {j}: {value}"),
+                            None => println!("         {j}: <unknown>"),
+                        }
+                    }
+                }
+            }
+        }
+
+        for MemoryRange { memory, range } in &self.memory {
+            let contents = dump
+                .memories()
+                .get(*memory as usize)
+                .ok_or_else(|| anyhow!("the core dump has no memory {memory}"))?;
+            let data = contents.read(range.clone()).ok_or_else(|| {
+                anyhow!(
+                    "range {:#x}..{:#x} is out of bounds of memory {memory} ({:#x} bytes)",
+                    range.start,
+                    range.end,
+                    contents.size()
+                )
+            })?;
+            println!();
+            println!("memory {memory} [{:#x}..{:#x}]:", range.start, range.end);
+            for (i, chunk) in data.chunks(16).enumerate() {
+                let mut line = format!("  {:08x}:", range.start + 16 * i as u64);
+                for byte in chunk {
+                    write!(line, " {byte:02x}")?;
+                }
+                for _ in chunk.len()..16 {
+                    line.push_str("   ");
+                }
+                line.push_str("  ");
+                line.extend(chunk.iter().map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                }));
+                println!("{line}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn print_component_exports(exports: &[CoreDumpComponentExport], indent: usize) {
+    for export in exports {
+        let kind = match export.kind() {
+            CoreDumpComponentExportKind::Func => "func",
+            CoreDumpComponentExportKind::Module => "module",
+            CoreDumpComponentExportKind::Instance(_) => "instance",
+            CoreDumpComponentExportKind::Type => "type",
+        };
+        println!("{:indent$}{} ({kind})", "", export.name());
+        if let CoreDumpComponentExportKind::Instance(exports) = export.kind() {
+            print_component_exports(exports, indent + 2);
+        }
+    }
+}
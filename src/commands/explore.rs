@@ -5,7 +5,8 @@ use clap::Parser;
 use std::path::PathBuf;
 use wasmtime_cli_flags::CommonOptions;
 
-/// Explore the compilation of a WebAssembly module to native code.
+/// Explore the compilation of a WebAssembly module or component to native
+/// code.
 #[derive(Parser, PartialEq)]
 pub struct ExploreCommand {
     #[command(flatten)]
@@ -15,7 +16,7 @@ pub struct ExploreCommand {
     #[arg(long, value_name = "TARGET")]
     target: Option<String>,
 
-    /// The path of the WebAssembly module to compile
+    /// The path of the WebAssembly module or component to compile
     #[arg(required = true, value_name = "MODULE")]
     module: PathBuf,
 
@@ -30,7 +31,11 @@ impl ExploreCommand {
     pub fn execute(mut self) -> Result<()> {
         self.common.init_logging()?;
 
-        let config = self.common.config(self.target.as_deref())?;
+        let mut config = self.common.config(self.target.as_deref())?;
+        // A cached artifact skips compilation entirely, and with it the CLIF
+        // and VCode that the explorer displays.
+        #[cfg(feature = "cache")]
+        config.disable_cache();
 
         let wasm = std::fs::read(&self.module)
             .with_context(|| format!("failed to read Wasm module: {}", self.module.display()))?;
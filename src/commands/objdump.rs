@@ -0,0 +1,268 @@
+//! The module that implements the `wasmtime objdump` command.
+
+use anyhow::{anyhow, bail, Context, Result};
+use capstone::arch::BuildsCapstone;
+use clap::Parser;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use wasmtime::Engine;
+use wasmtime_cli_flags::CommonOptions;
+use wasmtime_environ::object::{File, Object, ObjectSection, ObjectSymbol, SymbolKind};
+use wasmtime_environ::{obj, FilePos, Trap};
+
+/// Inspects a precompiled `*.cwasm` artifact produced by `wasmtime compile`.
+///
+/// By default this prints the configuration the artifact was compiled with,
+/// whether it can be loaded with the given flags on this host, and the list of
+/// compiled functions with their sizes.
+#[derive(Parser, PartialEq)]
+pub struct ObjdumpCommand {
+    #[command(flatten)]
+    common: CommonOptions,
+
+    /// Print all compiler flags, tunables and WebAssembly features the
+    /// artifact was compiled with.
+    #[arg(long)]
+    flags: bool,
+
+    /// Disassemble the native code of each function.
+    #[arg(short, long)]
+    disassemble: bool,
+
+    /// Print the trap table of each function.
+    #[arg(long)]
+    traps: bool,
+
+    /// Print the mapping from native code offsets to WebAssembly offsets of
+    /// each function.
+    #[arg(long)]
+    addrmap: bool,
+
+    /// Only print functions whose symbol name contains this string.
+    #[arg(long, value_name = "STR")]
+    filter: Option<String>,
+
+    /// The path of the precompiled artifact to inspect
+    #[arg(required = true, value_name = "CWASM")]
+    cwasm: PathBuf,
+}
+
+/// A compiled function within the text section of an artifact.
+struct Function<'a> {
+    name: &'a str,
+    /// Offsets within the text section.
+    start: u64,
+    end: u64,
+}
+
+impl ObjdumpCommand {
+    /// Executes the command.
+    pub fn execute(mut self) -> Result<()> {
+        self.common.init_logging()?;
+
+        let bytes = std::fs::read(&self.cwasm)
+            .with_context(|| format!("failed to read: {}", self.cwasm.display()))?;
+        let engine = Engine::new(&self.common.config(None)?)?;
+        let info = engine.precompiled_info(&bytes)?;
+
+        println!("kind: {:?}", info.kind());
+        println!("version: {}", info.version());
+        println!("target: {}", info.target());
+        match info.check_compatible() {
+            Ok(()) => println!("compatible with this host: yes"),
+            Err(e) => println!("compatible with this host: no, {e}"),
+        }
+        if self.flags {
+            let sections = [
+                ("shared flags", info.shared_flags().collect::<Vec<_>>()),
+                ("isa flags", info.isa_flags().collect()),
+                ("tunables", info.tunables().collect()),
+                ("features", info.features().collect()),
+            ];
+            for (title, settings) in sections {
+                println!();
+                println!("{title}:");
+                for (name, value) in settings {
+                    println!("  {name} = {value}");
+                }
+            }
+        }
+
+        let obj =
+            File::parse(&bytes[..]).context("failed to parse precompiled artifact as an ELF")?;
+        let text = obj
+            .section_by_name(".text")
+            .ok_or_else(|| anyhow!("failed to find the `.text` section"))?;
+        let text_base = text.address();
+        let code = text.data()?;
+        let addrmap = section_data(&obj, obj::ELF_WASMTIME_ADDRMAP)?;
+        let addrmap = wasmtime_environ::iterate_address_map(addrmap)
+            .map(|iter| iter.collect::<Vec<_>>())
+            .unwrap_or_default();
+        let traps = section_data(&obj, obj::ELF_WASMTIME_TRAPS)?;
+        let traps = wasmtime_environ::iterate_traps(traps)
+            .map(|iter| iter.collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let mut functions = obj
+            .symbols()
+            .filter(|sym| {
+                sym.kind() == SymbolKind::Text && sym.section_index() == Some(text.index())
+            })
+            .filter_map(|sym| {
+                let start = sym.address() - text_base;
+                Some(Function {
+                    name: sym.name().ok()?,
+                    start,
+                    end: start + sym.size(),
+                })
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by_key(|f| f.start);
+
+        let mut disassembler = if self.disassemble {
+            if info.target().is_empty() {
+                bail!("cannot disassemble an artifact from another version of Wasmtime");
+            }
+            Some(disassembler(info.target())?)
+        } else {
+            None
+        };
+
+        println!();
+        println!("functions:");
+        let total: u64 = functions.iter().map(|f| f.end - f.start).sum();
+        println!("  {} functions, {total:#x} bytes of code", functions.len());
+        for func in &functions {
+            if let Some(filter) = &self.filter {
+                if !func.name.contains(filter.as_str()) {
+                    continue;
+                }
+            }
+
+            let range = |offset: u32| (func.start..func.end).contains(&u64::from(offset));
+            let func_addrmap = addrmap
+                .iter()
+                .filter(|(offset, _)| range(*offset))
+                .collect::<Vec<_>>();
+            let func_traps = traps
+                .iter()
+                .filter(|(offset, _)| range(*offset))
+                .collect::<Vec<_>>();
+
+            let mut line = format!(
+                "  {:#010x} {:#8x} {}",
+                func.start,
+                func.end - func.start,
+                func.name
+            );
+            if let Some(pos) = func_addrmap.iter().find_map(|(_, pos)| pos.file_offset()) {
+                write!(line, " (wasm offset {pos:#x})")?;
+            }
+            println!("{line}");
+
+            if self.addrmap {
+                for (offset, pos) in &func_addrmap {
+                    println!("      {offset:#010x} -> {}", display_pos(*pos));
+                }
+            }
+            if self.traps {
+                for (offset, trap) in &func_traps {
+                    println!("      {offset:#010x} trap: {}", display_trap(*trap));
+                }
+            }
+
+            if let Some(cs) = &mut disassembler {
+                let body = usize::try_from(func.start)
+                    .ok()
+                    .zip(usize::try_from(func.end).ok())
+                    .and_then(|(start, end)| code.get(start..end))
+                    .ok_or_else(|| {
+                        anyhow!("function `{}` is outside of the `.text` section", func.name)
+                    })?;
+                let instructions = cs
+                    .disasm_all(body, func.start)
+                    .map_err(|e| anyhow!("{e}"))?;
+                for inst in instructions.iter() {
+                    let offset = u32::try_from(inst.address())?;
+                    let mut line = format!(
+                        "      {offset:#010x}  {:<8} {}",
+                        inst.mnemonic().unwrap_or(""),
+                        inst.op_str().unwrap_or("")
+                    );
+                    let pos = match func_addrmap.binary_search_by_key(&offset, |(o, _)| *o) {
+                        Ok(i) => Some(func_addrmap[i].1),
+                        Err(0) => None,
+                        Err(i) => Some(func_addrmap[i - 1].1),
+                    };
+                    if let Some(pos) = pos.and_then(|pos| pos.file_offset()) {
+                        write!(line, "  ;; wasm {pos:#x}")?;
+                    }
+                    if let Some((_, trap)) = func_traps.iter().find(|(o, _)| {
+                        (inst.address()..inst.address() + inst.bytes().len() as u64)
+                            .contains(&u64::from(*o))
+                    }) {
+                        write!(line, "  ;; trap: {}", display_trap(*trap))?;
+                    }
+                    println!("{}", line.trim_end());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the contents of the section `name`, which is empty if the artifact
+/// was compiled without it.
+fn section_data<'a>(obj: &File<'a>, name: &str) -> Result<&'a [u8]> {
+    match obj.section_by_name(name) {
+        Some(section) => Ok(section.data()?),
+        None => Ok(&[]),
+    }
+}
+
+fn display_pos(pos: FilePos) -> String {
+    match pos.file_offset() {
+        Some(offset) => format!("wasm {offset:#x}"),
+        None => "<no wasm offset>".to_string(),
+    }
+}
+
+fn display_trap(trap: Option<Trap>) -> String {
+    match trap {
+        Some(trap) => format!("{trap:?}"),
+        None => "<unknown>".to_string(),
+    }
+}
+
+fn disassembler(target: &str) -> Result<capstone::Capstone> {
+    let target: target_lexicon::Triple = target
+        .parse()
+        .map_err(|e| anyhow!("invalid target `{target}`: {e}"))?;
+    let mut cs = match target.architecture {
+        target_lexicon::Architecture::Aarch64(_) => capstone::Capstone::new()
+            .arm64()
+            .mode(capstone::arch::arm64::ArchMode::Arm)
+            .build(),
+        target_lexicon::Architecture::Riscv64(_) => capstone::Capstone::new()
+            .riscv()
+            .mode(capstone::arch::riscv::ArchMode::RiscV64)
+            .build(),
+        target_lexicon::Architecture::S390x => capstone::Capstone::new()
+            .sysz()
+            .mode(capstone::arch::sysz::ArchMode::Default)
+            .build(),
+        target_lexicon::Architecture::X86_64 => capstone::Capstone::new()
+            .x86()
+            .mode(capstone::arch::x86::ArchMode::Mode64)
+            .build(),
+        _ => bail!("disassembly is not supported for target `{target}`"),
+    }
+    .map_err(|e| anyhow!("{e}"))?;
+
+    // Skip over anything that looks like data, such as inline constant pools
+    // or trapping instructions on AArch64.
+    cs.set_skipdata(true).map_err(|e| anyhow!("{e}"))?;
+    Ok(cs)
+}
@@ -13,7 +13,7 @@ use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
-use wasmtime::{Engine, Func, Module, Store, StoreLimits, Val, ValType};
+use wasmtime::{Engine, Func, Module, PgoProfile, Store, StoreLimits, Val, ValType};
 use wasmtime_wasi::maybe_exit_on_error;
 use wasmtime_wasi::preview2;
 use wasmtime_wasi::sync::{ambient_authority, Dir, TcpListener, WasiCtxBuilder};
@@ -80,10 +80,18 @@ pub struct RunCommand {
     pub vars: Vec<(String, Option<String>)>,
 
     /// The name of the function to run
+    ///
+    /// For components, functions exported from an interface are named as
+    /// `INTERFACE#FUNCTION`, for example `my:pkg/api#greet`.
     #[arg(long, value_name = "FUNCTION")]
     pub invoke: Option<String>,
 
     /// Load the given WebAssembly module before the main module
+    ///
+    /// Core modules satisfy imports from the module `NAME`. Components are
+    /// instantiated and satisfy imports of the instance `NAME`, using the
+    /// component's export of that name if it exports an instance called
+    /// `NAME`, such as an interface, or otherwise all of its exports.
     #[arg(
         long = "preload",
         number_of_values = 1,
@@ -97,6 +105,9 @@ pub struct RunCommand {
     /// Arguments passed to the wasm module will be configured as WASI CLI
     /// arguments unless the `--invoke` CLI argument is passed in which case
     /// arguments will be interpreted as arguments to the function specified.
+    /// Arguments to component functions, and the values they return, are
+    /// written in the WAVE syntax, for example `"hello"`, `[1, 2]`,
+    /// `{x: 1, y: some(2)}` or `ok("done")`.
     #[arg(value_name = "WASM", trailing_var_arg = true, required = true)]
     pub module_and_args: Vec<OsString>,
 }
@@ -142,6 +153,12 @@ impl RunCommand {
             }
         }
 
+        if self.run.common.codegen.pgo_instrument == Some(true)
+            && self.run.common.codegen.pgo_profile.is_none()
+        {
+            bail!("`-C pgo-instrument` requires `-C pgo-profile` to write the profile to");
+        }
+
         let mut linker = match &main {
             RunTarget::Core(_) => CliLinker::Core(wasmtime::Linker::new(&engine)),
             #[cfg(feature = "component-model")]
@@ -184,7 +201,22 @@ impl RunCommand {
             let module = match self.run.load_module(&engine, path)? {
                 RunTarget::Core(m) => m,
                 #[cfg(feature = "component-model")]
-                RunTarget::Component(_) => bail!("components cannot be loaded with `--preload`"),
+                RunTarget::Component(component) => {
+                    match &mut linker {
+                        CliLinker::Component(linker) => {
+                            self.preload_component(linker, &mut store, name, &component)
+                                .context(format!(
+                                    "failed to process preload `{}` at `{}`",
+                                    name,
+                                    path.display()
+                                ))?;
+                        }
+                        CliLinker::Core(_) => {
+                            bail!("components cannot be preloaded for core modules")
+                        }
+                    }
+                    continue;
+                }
             };
             modules.push((name.clone(), module.clone()));
 
@@ -204,20 +236,30 @@ impl RunCommand {
                 }
                 #[cfg(feature = "component-model")]
                 CliLinker::Component(_) => {
-                    bail!("--preload cannot be used with components");
+                    bail!("core modules cannot be preloaded for components");
                 }
             }
         }
 
         // Load the main wasm module.
-        match self
+        let profiled_modules = modules
+            .iter()
+            .map(|(_, module)| module.clone())
+            .collect::<Vec<_>>();
+        let result = self
             .load_main_module(&mut store, &mut linker, &main, modules)
             .with_context(|| {
                 format!(
                     "failed to run main module `{}`",
                     self.module_and_args[0].to_string_lossy()
                 )
-            }) {
+            });
+
+        // Write out the execution profile before handling errors since that
+        // may exit the process, and traps are still representative runs.
+        self.write_pgo_profile(&main, &profiled_modules)?;
+
+        match result {
             Ok(()) => (),
             Err(e) => {
                 // Exit the process if Wasmtime understands the error;
@@ -230,6 +272,38 @@ impl RunCommand {
         Ok(())
     }
 
+    /// Merges the execution profiles collected by the instrumented `main`
+    /// component and `modules` into the file at `-C pgo-profile`, if
+    /// instrumentation is enabled.
+    fn write_pgo_profile(&self, main: &RunTarget, modules: &[Module]) -> Result<()> {
+        let codegen = &self.run.common.codegen;
+        let path = match &codegen.pgo_profile {
+            Some(path) if codegen.pgo_instrument == Some(true) => path,
+            _ => return Ok(()),
+        };
+
+        // Accumulate counts across runs so a profile can be collected from
+        // several representative workloads.
+        let mut profile = match std::fs::read(path) {
+            Ok(bytes) => PgoProfile::deserialize(&bytes)
+                .with_context(|| format!("failed to load profile `{path}`"))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PgoProfile::new(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read profile `{path}`")),
+        };
+        let component_profile = match main {
+            RunTarget::Core(_) => None,
+            #[cfg(feature = "component-model")]
+            RunTarget::Component(component) => component.pgo_profile(),
+        };
+        let module_profiles = modules.iter().filter_map(|module| module.pgo_profile());
+        for module_profile in module_profiles.chain(component_profile) {
+            profile.merge(&module_profile);
+        }
+        std::fs::write(path, profile.serialize()?)
+            .with_context(|| format!("failed to write profile `{path}`"))?;
+        Ok(())
+    }
+
     fn compute_preopen_dirs(&self) -> Result<Vec<(String, Dir)>> {
         let mut preopen_dirs = Vec::new();
 
@@ -282,14 +356,15 @@ impl RunCommand {
     fn setup_epoch_handler(
         &self,
         store: &mut Store<Host>,
+        main: &RunTarget,
         modules: Vec<(String, Module)>,
     ) -> Result<Box<dyn FnOnce(&mut Store<Host>)>> {
         if let Some(Profile::Guest { path, interval }) = &self.run.profile {
             #[cfg(feature = "profiling")]
-            return Ok(self.setup_guest_profiler(store, modules, path, *interval));
+            return Ok(self.setup_guest_profiler(store, main, modules, path, *interval));
             #[cfg(not(feature = "profiling"))]
             {
-                let _ = (modules, path, interval);
+                let _ = (main, modules, path, interval);
                 bail!("support for profiling disabled at compile time");
             }
         }
@@ -310,6 +385,7 @@ impl RunCommand {
     fn setup_guest_profiler(
         &self,
         store: &mut Store<Host>,
+        main: &RunTarget,
         modules: Vec<(String, Module)>,
         path: &str,
         interval: std::time::Duration,
@@ -317,8 +393,14 @@ impl RunCommand {
         use wasmtime::{AsContextMut, GuestProfiler, UpdateDeadline};
 
         let module_name = self.module_and_args[0].to_str().unwrap_or("<main module>");
-        store.data_mut().guest_profiler =
-            Some(Arc::new(GuestProfiler::new(module_name, interval, modules)));
+        let profiler = match main {
+            RunTarget::Core(_) => GuestProfiler::new(module_name, interval, modules),
+            #[cfg(feature = "component-model")]
+            RunTarget::Component(component) => {
+                GuestProfiler::new_component(module_name, interval, component, modules)
+            }
+        };
+        store.data_mut().guest_profiler = Some(Arc::new(profiler));
 
         fn sample(mut store: impl AsContextMut<Data = Host>) {
             let mut profiler = store
@@ -333,9 +415,19 @@ impl RunCommand {
             store.as_context_mut().data_mut().guest_profiler = Some(profiler);
         }
 
+        store.call_hook_with_store(|mut store, kind| {
+            let mut profiler = store.data_mut().guest_profiler.take().unwrap();
+            Arc::get_mut(&mut profiler)
+                .expect("profiling doesn't support threads yet")
+                .call_hook(&store, kind);
+            store.data_mut().guest_profiler = Some(profiler);
+            Ok(())
+        });
+
         if let Some(timeout) = self.run.common.wasm.timeout {
-            let mut timeout = (timeout.as_secs_f64() / interval.as_secs_f64()).ceil() as u64;
-            assert!(timeout > 0);
+            // A timeout shorter than the interval expires on the first tick.
+            let mut timeout =
+                ((timeout.as_secs_f64() / interval.as_secs_f64()).ceil() as u64).max(1);
             store.epoch_deadline_callback(move |mut store| {
                 sample(&mut store);
                 timeout -= 1;
@@ -375,6 +467,29 @@ impl RunCommand {
         });
     }
 
+    /// Instantiates the preloaded `component` and defines its exports in
+    /// `linker` as the instance `name`.
+    #[cfg(feature = "component-model")]
+    fn preload_component(
+        &self,
+        linker: &mut wasmtime::component::Linker<Host>,
+        store: &mut Store<Host>,
+        name: &str,
+        component: &wasmtime::component::Component,
+    ) -> Result<()> {
+        use wasmtime::component::types::ComponentItem;
+
+        let instance = linker.instantiate(&mut *store, component)?;
+        let export = [name];
+        let path = match component.component_type().get_export(name) {
+            Some(ComponentItem::ComponentInstance(_)) => &export[..],
+            _ => &[],
+        };
+        linker
+            .instance(name)?
+            .instance_exports(&mut *store, &instance, path)
+    }
+
     fn load_main_module(
         &self,
         store: &mut Store<Host>,
@@ -390,7 +505,10 @@ impl RunCommand {
                 CliLinker::Core(linker) => {
                     linker.define_unknown_imports_as_traps(module.unwrap_core())?;
                 }
-                _ => bail!("cannot use `--trap-unknown-imports` with components"),
+                #[cfg(feature = "component-model")]
+                CliLinker::Component(linker) => {
+                    linker.define_unknown_imports_as_traps(module.unwrap_component())?;
+                }
             }
             #[cfg(not(feature = "cranelift"))]
             bail!("support for `unknown-imports-trap` disabled at compile time");
@@ -403,13 +521,28 @@ impl RunCommand {
                 CliLinker::Core(linker) => {
                     linker.define_unknown_imports_as_default_values(module.unwrap_core())?;
                 }
-                _ => bail!("cannot use `--default-values-unknown-imports` with components"),
+                #[cfg(feature = "component-model")]
+                CliLinker::Component(linker) => {
+                    linker.define_unknown_imports_as_default_values(module.unwrap_component())?;
+                }
             }
             #[cfg(not(feature = "cranelift"))]
             bail!("support for `unknown-imports-trap` disabled at compile time");
         }
 
-        let finish_epoch_handler = self.setup_epoch_handler(store, modules)?;
+        if self.run.common.wasm.memory_discard == Some(true) {
+            match linker {
+                CliLinker::Core(linker) => {
+                    linker.define_memory_discard("wasmtime", "memory_discard")?;
+                }
+                #[cfg(feature = "component-model")]
+                CliLinker::Component(_) => {
+                    bail!("support for `memory-discard` is not available with components");
+                }
+            }
+        }
+
+        let finish_epoch_handler = self.setup_epoch_handler(store, module, modules)?;
 
         let result = match linker {
             CliLinker::Core(linker) => {
@@ -446,26 +579,49 @@ impl RunCommand {
             }
             #[cfg(feature = "component-model")]
             CliLinker::Component(linker) => {
-                if self.invoke.is_some() {
-                    bail!("using `--invoke` with components is not supported");
-                }
-
                 let component = module.unwrap_component();
 
-                let (command, _instance) =
-                    preview2::command::sync::Command::instantiate(&mut *store, component, linker)?;
-                let result = command
-                    .wasi_cli_run()
-                    .call_run(&mut *store)
-                    .context("failed to invoke `run` function")
-                    .map_err(|e| self.handle_core_dump(&mut *store, e));
-
-                // Translate the `Result<(),()>` produced by wasm into a feigned
-                // explicit exit here with status 1 if `Err(())` is returned.
-                result.and_then(|wasm_result| match wasm_result {
-                    Ok(()) => Ok(()),
-                    Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
-                })
+                if let Some(name) = &self.invoke {
+                    let instance = linker.instantiate(&mut *store, component).context(format!(
+                        "failed to instantiate {:?}",
+                        self.module_and_args[0]
+                    ))?;
+
+                    // Functions exported from an interface are named as
+                    // `interface#func`, everything else is a root export.
+                    let func = {
+                        let mut exports = instance.exports(&mut *store);
+                        let mut root = exports.root();
+                        match name.rsplit_once('#') {
+                            Some((interface, func)) => root
+                                .instance(interface)
+                                .and_then(|mut instance| instance.func(func)),
+                            None => root.func(name),
+                        }
+                    }
+                    .ok_or_else(|| anyhow!("no func export named `{}` found", name))?;
+
+                    self.invoke_component_func(store, func)
+                } else {
+                    let (command, _instance) = preview2::command::sync::Command::instantiate(
+                        &mut *store,
+                        component,
+                        linker,
+                    )?;
+                    let result = command
+                        .wasi_cli_run()
+                        .call_run(&mut *store)
+                        .context("failed to invoke `run` function")
+                        .map_err(|e| self.handle_core_dump(&mut *store, e));
+
+                    // Translate the `Result<(),()>` produced by wasm into a
+                    // feigned explicit exit here with status 1 if `Err(())` is
+                    // returned.
+                    result.and_then(|wasm_result| match wasm_result {
+                        Ok(()) => Ok(()),
+                        Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
+                    })
+                }
             }
         };
         finish_epoch_handler(store);
@@ -548,6 +704,47 @@ impl RunCommand {
         Ok(())
     }
 
+    #[cfg(feature = "component-model")]
+    fn invoke_component_func(
+        &self,
+        store: &mut Store<Host>,
+        func: wasmtime::component::Func,
+    ) -> Result<()> {
+        let name = self.invoke.as_deref().unwrap();
+        let mut args = self.module_and_args.iter().skip(1);
+        let mut params = Vec::new();
+        for ty in func.params(&*store).iter() {
+            let arg = match args.next() {
+                Some(arg) => arg,
+                None => bail!("not enough arguments for `{}`", name),
+            };
+            let arg = arg
+                .to_str()
+                .ok_or_else(|| anyhow!("argument is not valid utf-8: {arg:?}"))?;
+            params.push(
+                crate::wave::parse(ty, arg)
+                    .with_context(|| format!("failed to parse argument `{arg}`"))?,
+            );
+        }
+
+        // Results are overwritten by the call, so any value works as a
+        // placeholder here.
+        let mut results = vec![wasmtime::component::Val::Bool(false); func.results(&*store).len()];
+        let invoke_res = func
+            .call(&mut *store, &params, &mut results)
+            .with_context(|| format!("failed to invoke `{}`", name));
+        if let Err(err) = invoke_res {
+            return Err(self.handle_core_dump(&mut *store, err));
+        }
+        func.post_return(&mut *store)?;
+
+        for result in results.iter() {
+            println!("{}", crate::wave::display(result));
+        }
+
+        Ok(())
+    }
+
     #[cfg(feature = "coredump")]
     fn handle_core_dump(&self, store: &mut Store<Host>, err: Error) -> Error {
         let coredump_path = match &self.run.common.debug.coredump {
@@ -707,6 +904,75 @@ impl RunCommand {
             }
         }
 
+        if self.run.common.wasi.keyvalue == Some(true) {
+            #[cfg(not(feature = "wasi-keyvalue"))]
+            {
+                bail!("Cannot enable wasi-keyvalue when the binary is not compiled with this feature.");
+            }
+            #[cfg(feature = "wasi-keyvalue")]
+            {
+                match linker {
+                    CliLinker::Core(_) => {
+                        bail!("Cannot enable wasi-keyvalue for core wasm modules");
+                    }
+                    CliLinker::Component(linker) => {
+                        wasmtime_wasi_keyvalue::add_to_linker(linker)?;
+                    }
+                }
+
+                store.data_mut().wasi_keyvalue = Some(self.run.wasi_keyvalue_ctx()?);
+            }
+        }
+
+        if self.run.common.wasi.config == Some(true) {
+            #[cfg(not(feature = "wasi-config"))]
+            {
+                bail!(
+                    "Cannot enable wasi-config when the binary is not compiled with this feature."
+                );
+            }
+            #[cfg(feature = "wasi-config")]
+            {
+                match linker {
+                    CliLinker::Core(_) => {
+                        bail!("Cannot enable wasi-config for core wasm modules");
+                    }
+                    CliLinker::Component(linker) => {
+                        wasmtime_wasi_config::add_to_linker(linker, |host| {
+                            host.wasi_config.as_mut().unwrap()
+                        })?;
+                    }
+                }
+
+                store.data_mut().wasi_config = Some(self.run.wasi_config_ctx()?);
+            }
+        }
+
+        if self.run.common.wasi.logging == Some(true) {
+            #[cfg(not(feature = "wasi-logging"))]
+            {
+                bail!(
+                    "Cannot enable wasi-logging when the binary is not compiled with this feature."
+                );
+            }
+            #[cfg(feature = "wasi-logging")]
+            {
+                match linker {
+                    CliLinker::Core(_) => {
+                        bail!("Cannot enable wasi-logging for core wasm modules");
+                    }
+                    CliLinker::Component(linker) => {
+                        wasmtime_wasi_logging::add_to_linker(linker, |host| {
+                            host.wasi_logging.as_mut().unwrap()
+                        })?;
+                    }
+                }
+
+                let path = Path::new(&self.module_and_args[0]);
+                store.data_mut().wasi_logging = Some(self.run.wasi_logging_ctx(path));
+            }
+        }
+
         Ok(())
     }
 
@@ -809,6 +1075,12 @@ struct Host {
     wasi_threads: Option<Arc<WasiThreadsCtx<Host>>>,
     #[cfg(feature = "wasi-http")]
     wasi_http: Option<Arc<WasiHttpCtx>>,
+    #[cfg(feature = "wasi-keyvalue")]
+    wasi_keyvalue: Option<wasmtime_wasi_keyvalue::WasiKeyValueCtx>,
+    #[cfg(feature = "wasi-config")]
+    wasi_config: Option<wasmtime_wasi_config::WasiConfigCtx>,
+    #[cfg(feature = "wasi-logging")]
+    wasi_logging: Option<wasmtime_wasi_logging::WasiLoggingCtx>,
     limits: StoreLimits,
     #[cfg(feature = "profiling")]
     guest_profiler: Option<Arc<wasmtime::GuestProfiler>>,
@@ -855,6 +1127,17 @@ impl wasmtime_wasi_http::types::WasiHttpView for Host {
     }
 }
 
+#[cfg(feature = "wasi-keyvalue")]
+impl wasmtime_wasi_keyvalue::WasiKeyValueView for Host {
+    fn ctx(&self) -> &wasmtime_wasi_keyvalue::WasiKeyValueCtx {
+        self.wasi_keyvalue.as_ref().unwrap()
+    }
+
+    fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
+        Arc::get_mut(&mut self.preview2_table).expect("preview2 is not compatible with threads")
+    }
+}
+
 #[cfg(not(unix))]
 fn ctx_set_listenfd(num_fd: usize, _builder: &mut WasiCtxBuilder) -> Result<usize> {
     Ok(num_fd)
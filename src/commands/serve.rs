@@ -9,7 +9,7 @@ use std::{
         Arc,
     },
 };
-use wasmtime::component::{InstancePre, Linker};
+use wasmtime::component::{Component, InstancePre, Linker};
 use wasmtime::{Engine, Store, StoreLimits};
 use wasmtime_wasi::preview2::{self, StreamError, StreamResult, WasiCtx, WasiCtxBuilder, WasiView};
 use wasmtime_wasi_http::io::TokioIo;
@@ -30,6 +30,18 @@ struct Host {
 
     #[cfg(feature = "wasi-nn")]
     nn: Option<WasiNnCtx>,
+
+    #[cfg(feature = "wasi-keyvalue")]
+    keyvalue: Option<wasmtime_wasi_keyvalue::WasiKeyValueCtx>,
+
+    #[cfg(feature = "wasi-config")]
+    config: Option<wasmtime_wasi_config::WasiConfigCtx>,
+
+    #[cfg(feature = "wasi-logging")]
+    logging: Option<wasmtime_wasi_logging::WasiLoggingCtx>,
+
+    #[cfg(feature = "profiling")]
+    guest_profiler: Option<wasmtime::GuestProfiler>,
 }
 
 impl WasiView for Host {
@@ -60,6 +72,17 @@ impl WasiHttpView for Host {
     }
 }
 
+#[cfg(feature = "wasi-keyvalue")]
+impl wasmtime_wasi_keyvalue::WasiKeyValueView for Host {
+    fn ctx(&self) -> &wasmtime_wasi_keyvalue::WasiKeyValueCtx {
+        self.keyvalue.as_ref().unwrap()
+    }
+
+    fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
+        &mut self.table
+    }
+}
+
 const DEFAULT_ADDR: std::net::SocketAddr = std::net::SocketAddr::new(
     std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0)),
     8080,
@@ -95,7 +118,8 @@ impl ServeCommand {
         }
 
         if let Some(Profile::Guest { .. }) = &self.run.profile {
-            bail!("Cannot use the guest profiler with components");
+            #[cfg(not(feature = "profiling"))]
+            bail!("support for profiling disabled at compile time");
         }
 
         if self.run.common.wasi.nn == Some(true) {
@@ -109,6 +133,31 @@ impl ServeCommand {
             bail!("wasi-threads does not support components yet")
         }
 
+        if self.run.common.wasi.keyvalue == Some(true) {
+            #[cfg(not(feature = "wasi-keyvalue"))]
+            {
+                bail!("Cannot enable wasi-keyvalue when the binary is not compiled with this feature.");
+            }
+        }
+
+        if self.run.common.wasi.config == Some(true) {
+            #[cfg(not(feature = "wasi-config"))]
+            {
+                bail!(
+                    "Cannot enable wasi-config when the binary is not compiled with this feature."
+                );
+            }
+        }
+
+        if self.run.common.wasi.logging == Some(true) {
+            #[cfg(not(feature = "wasi-logging"))]
+            {
+                bail!(
+                    "Cannot enable wasi-logging when the binary is not compiled with this feature."
+                );
+            }
+        }
+
         // The serve command requires both wasi-http and the component model, so we enable those by
         // default here.
         if self.run.common.wasi.http.replace(true) == Some(false) {
@@ -138,7 +187,12 @@ impl ServeCommand {
         Ok(())
     }
 
-    fn new_store(&self, engine: &Engine, req_id: u64) -> Result<Store<Host>> {
+    fn new_store(
+        &self,
+        engine: &Engine,
+        component: &Component,
+        req_id: u64,
+    ) -> Result<Store<Host>> {
         let mut builder = WasiCtxBuilder::new();
 
         builder.envs(&[("REQUEST_ID", req_id.to_string())]);
@@ -162,6 +216,18 @@ impl ServeCommand {
 
             #[cfg(feature = "wasi-nn")]
             nn: None,
+
+            #[cfg(feature = "wasi-keyvalue")]
+            keyvalue: None,
+
+            #[cfg(feature = "wasi-config")]
+            config: None,
+
+            #[cfg(feature = "wasi-logging")]
+            logging: None,
+
+            #[cfg(feature = "profiling")]
+            guest_profiler: None,
         };
 
         if self.run.common.wasi.nn == Some(true) {
@@ -182,7 +248,12 @@ impl ServeCommand {
 
         let mut store = Store::new(engine, host);
 
-        if self.run.common.wasm.timeout.is_some() {
+        if let Some(Profile::Guest { interval, .. }) = &self.run.profile {
+            #[cfg(feature = "profiling")]
+            self.setup_guest_profiler(&mut store, component, req_id, *interval);
+            #[cfg(not(feature = "profiling"))]
+            let _ = (component, interval);
+        } else if self.run.common.wasm.timeout.is_some() {
             store.set_epoch_deadline(1);
         }
 
@@ -198,6 +269,73 @@ impl ServeCommand {
         Ok(store)
     }
 
+    /// Samples the guest on every epoch tick, which happens once per
+    /// `interval` while profiling, and enforces any timeout by counting ticks.
+    #[cfg(feature = "profiling")]
+    fn setup_guest_profiler(
+        &self,
+        store: &mut Store<Host>,
+        component: &Component,
+        req_id: u64,
+        interval: std::time::Duration,
+    ) {
+        use wasmtime::{GuestProfiler, UpdateDeadline};
+
+        let name = format!("{} [{req_id}]", self.component.display());
+        store.data_mut().guest_profiler = Some(GuestProfiler::new_component(
+            &name,
+            interval,
+            component,
+            Vec::new(),
+        ));
+
+        let mut timeout = self
+            .run
+            .common
+            .wasm
+            .timeout
+            // A timeout shorter than the interval expires on the first tick.
+            .map(|timeout| ((timeout.as_secs_f64() / interval.as_secs_f64()).ceil() as u64).max(1));
+        store.call_hook_with_store(|mut store, kind| {
+            let mut profiler = store.data_mut().guest_profiler.take().unwrap();
+            profiler.call_hook(&store, kind);
+            store.data_mut().guest_profiler = Some(profiler);
+            Ok(())
+        });
+        store.epoch_deadline_callback(move |mut store| {
+            let mut profiler = store.data_mut().guest_profiler.take().unwrap();
+            profiler.sample(&store);
+            store.data_mut().guest_profiler = Some(profiler);
+
+            if let Some(timeout) = &mut timeout {
+                *timeout = timeout.saturating_sub(1);
+                if *timeout == 0 {
+                    bail!("timeout exceeded");
+                }
+            }
+            Ok(UpdateDeadline::Continue(1))
+        });
+        store.set_epoch_deadline(1);
+    }
+
+    /// Writes the profile of request `req_id`, if one was collected, next to
+    /// the path given with `--profile`.
+    #[cfg(feature = "profiling")]
+    fn write_guest_profile(&self, store: &mut Store<Host>, req_id: u64) {
+        let (profiler, path) = match (store.data_mut().guest_profiler.take(), &self.run.profile) {
+            (Some(profiler), Some(Profile::Guest { path, .. })) => (profiler, path),
+            _ => return,
+        };
+        let path = request_profile_path(path, req_id);
+        let result = std::fs::File::create(&path)
+            .map_err(anyhow::Error::new)
+            .and_then(|output| profiler.finish(std::io::BufWriter::new(output)));
+        match result {
+            Ok(()) => log::info!("Request {req_id} profile written to {}", path.display()),
+            Err(e) => eprintln!("failed writing profile at {}: {e:#}", path.display()),
+        }
+    }
+
     fn add_to_linker(&self, linker: &mut Linker<Host>) -> Result<()> {
         // Repurpose the `-Scommon` flag of `wasmtime run` for `wasmtime serve`
         // to serve as a signal to enable all WASI interfaces instead of just
@@ -230,6 +368,41 @@ impl ServeCommand {
             bail!("support for wasi-threads is not available with components");
         }
 
+        if self.run.common.wasi.keyvalue == Some(true) {
+            #[cfg(not(feature = "wasi-keyvalue"))]
+            {
+                bail!("support for wasi-keyvalue was disabled at compile time");
+            }
+            #[cfg(feature = "wasi-keyvalue")]
+            {
+                wasmtime_wasi_keyvalue::add_to_linker(linker)?;
+            }
+        }
+
+        if self.run.common.wasi.config == Some(true) {
+            #[cfg(not(feature = "wasi-config"))]
+            {
+                bail!("support for wasi-config was disabled at compile time");
+            }
+            #[cfg(feature = "wasi-config")]
+            {
+                wasmtime_wasi_config::add_to_linker(linker, |host| host.config.as_mut().unwrap())?;
+            }
+        }
+
+        if self.run.common.wasi.logging == Some(true) {
+            #[cfg(not(feature = "wasi-logging"))]
+            {
+                bail!("support for wasi-logging was disabled at compile time");
+            }
+            #[cfg(feature = "wasi-logging")]
+            {
+                wasmtime_wasi_logging::add_to_linker(linker, |host| {
+                    host.logging.as_mut().unwrap()
+                })?;
+            }
+        }
+
         if self.run.common.wasi.http == Some(false) {
             bail!("support for wasi-http must be enabled for `serve` subcommand");
         }
@@ -253,8 +426,10 @@ impl ServeCommand {
                 config.profiler(s);
             }
 
-            // We bail early in `execute` if the guest profiler is configured.
-            Some(Profile::Guest { .. }) => unreachable!(),
+            // Further configured for each request's store.
+            Some(Profile::Guest { .. }) => {
+                config.epoch_interruption(true);
+            }
 
             None => {}
         }
@@ -275,15 +450,18 @@ impl ServeCommand {
 
         eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
 
-        let _epoch_thread = if let Some(timeout) = self.run.common.wasm.timeout {
-            Some(EpochThread::spawn(timeout, engine.clone()))
-        } else {
-            None
+        // While profiling the epoch is used to take samples and timeouts are
+        // counted in samples instead.
+        let epoch_interval = match &self.run.profile {
+            Some(Profile::Guest { interval, .. }) => Some(*interval),
+            _ => self.run.common.wasm.timeout,
         };
+        let _epoch_thread =
+            epoch_interval.map(|interval| EpochThread::spawn(interval, engine.clone()));
 
         log::info!("Listening on {}", self.addr);
 
-        let handler = ProxyHandler::new(self, engine, instance);
+        let handler = ProxyHandler::new(self, engine, instance)?;
 
         loop {
             let (stream, _) = listener.accept().await?;
@@ -339,6 +517,21 @@ struct ProxyHandlerInner {
     engine: Engine,
     instance_pre: InstancePre<Host>,
     next_id: AtomicU64,
+
+    // Key-value state is shared between all requests, unlike the rest of
+    // each request's `Host`.
+    #[cfg(feature = "wasi-keyvalue")]
+    keyvalue: Option<wasmtime_wasi_keyvalue::WasiKeyValueCtx>,
+
+    // Config is loaded once up front rather than re-reading any config file
+    // on every request.
+    #[cfg(feature = "wasi-config")]
+    config: Option<wasmtime_wasi_config::WasiConfigCtx>,
+
+    // Template for each request's logging context, sharing a single rate
+    // limiter between all requests.
+    #[cfg(feature = "wasi-logging")]
+    logging: Option<wasmtime_wasi_logging::WasiLoggingCtx>,
 }
 
 impl ProxyHandlerInner {
@@ -351,13 +544,40 @@ impl ProxyHandlerInner {
 struct ProxyHandler(Arc<ProxyHandlerInner>);
 
 impl ProxyHandler {
-    fn new(cmd: ServeCommand, engine: Engine, instance_pre: InstancePre<Host>) -> Self {
-        Self(Arc::new(ProxyHandlerInner {
+    fn new(cmd: ServeCommand, engine: Engine, instance_pre: InstancePre<Host>) -> Result<Self> {
+        #[cfg(feature = "wasi-keyvalue")]
+        let keyvalue = if cmd.run.common.wasi.keyvalue == Some(true) {
+            Some(cmd.run.wasi_keyvalue_ctx()?)
+        } else {
+            None
+        };
+
+        #[cfg(feature = "wasi-config")]
+        let config = if cmd.run.common.wasi.config == Some(true) {
+            Some(cmd.run.wasi_config_ctx()?)
+        } else {
+            None
+        };
+
+        #[cfg(feature = "wasi-logging")]
+        let logging = if cmd.run.common.wasi.logging == Some(true) {
+            Some(cmd.run.wasi_logging_ctx(&cmd.component))
+        } else {
+            None
+        };
+
+        Ok(Self(Arc::new(ProxyHandlerInner {
             cmd,
             engine,
             instance_pre,
             next_id: AtomicU64::from(0),
-        }))
+            #[cfg(feature = "wasi-keyvalue")]
+            keyvalue,
+            #[cfg(feature = "wasi-config")]
+            config,
+            #[cfg(feature = "wasi-logging")]
+            logging,
+        })))
     }
 }
 
@@ -416,7 +636,23 @@ impl hyper::service::Service<Request> for ProxyHandler {
                 req.uri()
             );
 
-            let mut store = inner.cmd.new_store(&inner.engine, req_id)?;
+            let mut store =
+                inner
+                    .cmd
+                    .new_store(&inner.engine, inner.instance_pre.component(), req_id)?;
+
+            #[cfg(feature = "wasi-keyvalue")]
+            {
+                store.data_mut().keyvalue = inner.keyvalue.clone();
+            }
+            #[cfg(feature = "wasi-config")]
+            {
+                store.data_mut().config = inner.config.clone();
+            }
+            #[cfg(feature = "wasi-logging")]
+            {
+                store.data_mut().logging = inner.logging.clone().map(|ctx| ctx.request_id(req_id));
+            }
 
             let req = store.data_mut().new_incoming_request(req)?;
             let out = store.data_mut().new_response_outparam(sender)?;
@@ -425,11 +661,15 @@ impl hyper::service::Service<Request> for ProxyHandler {
                 wasmtime_wasi_http::proxy::Proxy::instantiate_pre(&mut store, &inner.instance_pre)
                     .await?;
 
-            if let Err(e) = proxy
+            let result = proxy
                 .wasi_http_incoming_handler()
-                .call_handle(store, req, out)
-                .await
-            {
+                .call_handle(&mut store, req, out)
+                .await;
+
+            #[cfg(feature = "profiling")]
+            inner.cmd.write_guest_profile(&mut store, req_id);
+
+            if let Err(e) = result {
                 log::error!("[{req_id}] :: {:#?}", e);
                 return Err(e);
             }
@@ -516,3 +756,17 @@ impl preview2::HostOutputStream for LogStream {
 impl preview2::Subscribe for LogStream {
     async fn ready(&mut self) {}
 }
+
+/// Returns the path the guest profile of request `req_id` is written to,
+/// which is `path` with the request id inserted before its extension.
+#[cfg(feature = "profiling")]
+fn request_profile_path(path: &str, req_id: u64) -> PathBuf {
+    let path = std::path::Path::new(path);
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!(".{req_id}"));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
@@ -0,0 +1,46 @@
+//! The module that implements the `wasmtime wit` command.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use wit_component::WitPrinter;
+
+/// Prints the WIT interface of a component
+///
+/// The imports and exports of the component are printed as a WIT world along
+/// with all the interfaces and types they use.
+#[derive(Parser, PartialEq)]
+pub struct WitCommand {
+    /// The path of the component to print the interface of
+    #[arg(required = true, value_name = "COMPONENT")]
+    component: PathBuf,
+}
+
+impl WitCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        let bytes = std::fs::read(&self.component)
+            .with_context(|| format!("failed to read: {}", self.component.display()))?;
+        // Parse the text format here specifically to add the `path` to the
+        // error message if there's a syntax error.
+        #[cfg(feature = "wat")]
+        let bytes = wat::parse_bytes(&bytes).map_err(|mut e| {
+            e.set_path(&self.component);
+            e
+        })?;
+
+        if !wasmparser::Parser::is_component(&bytes) {
+            bail!("`{}` is not a component", self.component.display());
+        }
+
+        let decoded = wit_component::decode(&bytes).with_context(|| {
+            format!(
+                "failed to decode the interface of `{}`",
+                self.component.display()
+            )
+        })?;
+        let wit = WitPrinter::default().print(decoded.resolve(), decoded.package())?;
+        print!("{wit}");
+        Ok(())
+    }
+}
@@ -63,7 +63,10 @@ pub struct RunCommon {
     ///
     /// where `path` is where to write the profile and `interval` is the
     /// duration between samples. When used with `--wasm-timeout` the timeout
-    /// will be rounded up to the nearest multiple of this interval.
+    /// will be rounded up to the nearest multiple of this interval. With
+    /// `serve` a separate profile is written for each request, with the
+    /// request id inserted before the extension of `path` (e.g.
+    /// `wasmtime-guest-profile.0.json`).
     #[arg(
         long,
         value_name = "STRATEGY",
@@ -97,6 +100,63 @@ impl RunCommon {
         limits.build()
     }
 
+    /// Creates the wasi-keyvalue context configured by `-S keyvalue-dir`,
+    /// falling back to an in-memory store.
+    #[cfg(feature = "wasi-keyvalue")]
+    pub fn wasi_keyvalue_ctx(&self) -> Result<wasmtime_wasi_keyvalue::WasiKeyValueCtx> {
+        use std::sync::Arc;
+        use wasmtime_wasi_keyvalue::{FileStore, WasiKeyValueCtx};
+
+        Ok(match &self.common.wasi.keyvalue_dir {
+            Some(dir) => {
+                let store = FileStore::open(dir)
+                    .with_context(|| format!("failed to open keyvalue directory `{dir}`"))?;
+                WasiKeyValueCtx::new(Arc::new(store))
+            }
+            None => WasiKeyValueCtx::default(),
+        })
+    }
+
+    /// Creates the wasi-logging context for the component at `path`, limited
+    /// by `-S logging-rate-limit` if given.
+    #[cfg(feature = "wasi-logging")]
+    pub fn wasi_logging_ctx(&self, path: &Path) -> wasmtime_wasi_logging::WasiLoggingCtx {
+        use std::sync::Arc;
+        use wasmtime_wasi_logging::{RateLimiter, WasiLoggingCtx};
+
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_else(|| path.to_string_lossy());
+        let mut ctx = WasiLoggingCtx::new(name);
+        if let Some(limit) = self.common.wasi.logging_rate_limit {
+            ctx = ctx.rate_limiter(Arc::new(RateLimiter::new(limit)));
+        }
+        ctx
+    }
+
+    /// Creates the wasi-config context configured by the `-S config-*`
+    /// options. Values from `config-var` take precedence over those from
+    /// `config-inherit-env`, which in turn take precedence over `config-file`.
+    #[cfg(feature = "wasi-config")]
+    pub fn wasi_config_ctx(&self) -> Result<wasmtime_wasi_config::WasiConfigCtx> {
+        use wasmtime_wasi_config::{EnvConfig, StaticConfig, TomlConfig, WasiConfigCtx};
+
+        let wasi = &self.common.wasi;
+        let mut ctx = WasiConfigCtx::new().provider(StaticConfig::new(
+            wasi.config_var
+                .iter()
+                .map(|pair| (pair.key.clone(), pair.value.clone())),
+        ));
+        if wasi.config_inherit_env == Some(true) {
+            ctx = ctx.provider(EnvConfig::new());
+        }
+        if let Some(file) = &wasi.config_file {
+            ctx = ctx.provider(TomlConfig::from_file(file)?);
+        }
+        Ok(ctx)
+    }
+
     pub fn ensure_allow_precompiled(&self) -> Result<()> {
         if self.allow_precompiled {
             Ok(())
@@ -8,5 +8,8 @@ pub mod commands;
 
 pub(crate) mod common;
 
+#[cfg(feature = "component-model")]
+pub(crate) mod wave;
+
 #[cfg(feature = "old-cli")]
 pub mod old_cli;
@@ -234,8 +234,9 @@ pub struct RunCommand {
 fn parse_module(s: OsString) -> anyhow::Result<PathBuf> {
     // Do not accept wasmtime subcommand names as the module name
     match s.to_str() {
-        Some("help") | Some("run") | Some("compile") | Some("serve") | Some("explore")
-        | Some("settings") | Some("wast") | Some("config") => {
+        Some("help") | Some("run") | Some("compile") | Some("serve") | Some("coredump")
+        | Some("explore") | Some("objdump") | Some("settings") | Some("wast") | Some("wit")
+        | Some("config") => {
             bail!("module name cannot be the same as a subcommand")
         }
         _ => Ok(s.into()),
@@ -0,0 +1,430 @@
+//! Parsing and printing of component model values in a textual syntax.
+//!
+//! The syntax follows the WebAssembly Value Encoding (WAVE), which mirrors
+//! how types are written in WIT:
+//!
+//! * `bool`s are `true` or `false`, integers are written in decimal and
+//!   floats additionally accept `nan`, `inf` and `-inf`.
+//! * `char`s are quoted as `'x'` and `string`s as `"xyz"`, with the escapes
+//!   `\\`, `\'`, `\"`, `\t`, `\n`, `\r` and `\u{...}`.
+//! * `list`s are written as `[a, b]`, `tuple`s as `(a, b)` and `record`s as
+//!   `{name: a, other: b}`, with `{:}` for a record without any fields. Fields
+//!   of an `option` type may be omitted from records, in which case they are
+//!   `none`.
+//! * `enum` cases are written by name and `variant` cases as `name` or
+//!   `name(payload)`, as are `option`s (`some(a)` and `none`) and `result`s
+//!   (`ok`, `ok(a)`, `err` and `err(b)`).
+//! * `flags` are written as the set of flags which are set, as in
+//!   `{read, write}`.
+//!
+//! Names which collide with a keyword, like an enum case named `none`, may be
+//! prefixed with `%`. Resources cannot be written.
+
+use anyhow::{anyhow, bail, Context as _, Error, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use wasmtime::component::{Type, Val};
+
+const KEYWORDS: &[&str] = &["true", "false", "some", "none", "ok", "err", "inf", "nan"];
+
+/// Parses `s` as a value of type `ty`.
+pub fn parse(ty: &Type, s: &str) -> Result<Val> {
+    let mut parser = Parser { src: s, pos: 0 };
+    let val = parser.value(ty)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(val)
+}
+
+/// Returns a wrapper which displays `val` in the syntax accepted by
+/// [`parse`].
+pub fn display(val: &Val) -> impl fmt::Display + '_ {
+    DisplayVal(val)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: impl fmt::Display) -> Error {
+        anyhow!("{msg} at offset {}", self.pos)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// Skips whitespace and returns the next character, if any.
+    fn peek(&mut self) -> Option<char> {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format_args!("expected `{c}`")))
+        }
+    }
+
+    /// Consumes the longest run of characters matching `f`.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Parses a kebab-case name, optionally prefixed with `%`.
+    fn label(&mut self) -> Result<&'a str> {
+        self.eat('%');
+        let start = self.pos;
+        let label = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-');
+        if label.is_empty() {
+            self.pos = start;
+            return Err(self.error("expected a name"));
+        }
+        Ok(label)
+    }
+
+    /// Parses a keyword such as `true` or `some`.
+    fn keyword(&mut self) -> Result<&'a str> {
+        self.peek();
+        let keyword = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-');
+        if keyword.is_empty() {
+            return Err(self.error("expected a keyword"));
+        }
+        Ok(keyword)
+    }
+
+    fn number<T>(&mut self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.peek();
+        let start = self.pos;
+        let s = self.take_while(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+        s.parse()
+            .with_context(|| format!("invalid number `{s}` at offset {start}"))
+    }
+
+    /// Parses a literal enclosed in `quote`, handling escapes.
+    fn quoted(&mut self, quote: char) -> Result<String> {
+        self.expect(quote)?;
+        let rest = self.rest();
+        let mut ret = String::new();
+        let mut chars = rest.char_indices();
+        let end = loop {
+            let c = match chars.next() {
+                Some((i, c)) if c == quote => break i + c.len_utf8(),
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, '\\')) => '\\',
+                    Some((_, '\'')) => '\'',
+                    Some((_, '"')) => '"',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((i, 'u')) => {
+                        let hex = rest[i + 1..]
+                            .strip_prefix('{')
+                            .and_then(|s| s.split_once('}'))
+                            .map(|(hex, _)| hex)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        let c = u32::from_str_radix(hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        // Skip the braces along with the hex digits.
+                        for _ in 0..hex.len() + 2 {
+                            chars.next();
+                        }
+                        c
+                    }
+                    _ => return Err(self.error("invalid escape")),
+                },
+                Some((_, c)) => c,
+                None => return Err(self.error(format_args!("missing closing `{quote}`"))),
+            };
+            ret.push(c);
+        };
+        self.pos += end;
+        Ok(ret)
+    }
+
+    /// Parses the items of a comma-separated sequence terminated by `close`,
+    /// allowing a trailing comma.
+    fn seq<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(item(self)?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Parses the parenthesized payload of a case if it has one.
+    fn payload(&mut self, ty: Option<&Type>) -> Result<Option<Val>> {
+        match ty {
+            Some(ty) => {
+                self.expect('(')?;
+                let val = self.value(ty)?;
+                self.expect(')')?;
+                Ok(Some(val))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn value(&mut self, ty: &Type) -> Result<Val> {
+        Ok(match ty {
+            Type::Bool => match self.keyword()? {
+                "true" => Val::Bool(true),
+                "false" => Val::Bool(false),
+                _ => return Err(self.error("expected `true` or `false`")),
+            },
+            Type::S8 => Val::S8(self.number()?),
+            Type::U8 => Val::U8(self.number()?),
+            Type::S16 => Val::S16(self.number()?),
+            Type::U16 => Val::U16(self.number()?),
+            Type::S32 => Val::S32(self.number()?),
+            Type::U32 => Val::U32(self.number()?),
+            Type::S64 => Val::S64(self.number()?),
+            Type::U64 => Val::U64(self.number()?),
+            Type::Float32 => Val::Float32(self.number()?),
+            Type::Float64 => Val::Float64(self.number()?),
+            Type::Char => {
+                let s = self.quoted('\'')?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Val::Char(c),
+                    _ => return Err(self.error("expected a single character")),
+                }
+            }
+            Type::String => Val::String(self.quoted('"')?.into()),
+            Type::List(list) => {
+                let ty = list.ty();
+                self.expect('[')?;
+                let items = self.seq(']', |p| p.value(&ty))?;
+                list.new_val(items.into())?
+            }
+            Type::Tuple(tuple) => {
+                let mut types = tuple.types();
+                self.expect('(')?;
+                let items = self.seq(')', |p| match types.next() {
+                    Some(ty) => p.value(&ty),
+                    None => Err(p.error("too many tuple elements")),
+                })?;
+                tuple.new_val(items.into())?
+            }
+            Type::Record(record) => {
+                let mut types = record
+                    .fields()
+                    .map(|field| (field.name, field.ty))
+                    .collect::<HashMap<_, _>>();
+                let mut values = HashMap::new();
+                self.expect('{')?;
+                if self.eat(':') {
+                    self.expect('}')?;
+                } else {
+                    self.seq('}', |p| {
+                        let name = p.label()?;
+                        let ty = types.remove(name).ok_or_else(|| {
+                            p.error(format_args!("unexpected or duplicate field `{name}`"))
+                        })?;
+                        p.expect(':')?;
+                        values.insert(name, p.value(&ty)?);
+                        Ok(())
+                    })?;
+                }
+                let mut fields = Vec::new();
+                for field in record.fields() {
+                    let value = match (values.remove(field.name), &field.ty) {
+                        (Some(value), _) => value,
+                        (None, Type::Option(option)) => option.new_val(None)?,
+                        (None, _) => {
+                            return Err(self.error(format_args!("missing field `{}`", field.name)))
+                        }
+                    };
+                    fields.push((field.name, value));
+                }
+                record.new_val(fields)?
+            }
+            Type::Variant(variant) => {
+                let name = self.label()?;
+                let case = variant
+                    .cases()
+                    .find(|case| case.name == name)
+                    .ok_or_else(|| self.error(format_args!("unknown case `{name}`")))?;
+                let payload = self.payload(case.ty.as_ref())?;
+                variant.new_val(name, payload)?
+            }
+            Type::Enum(enum_) => {
+                let name = self.label()?;
+                if !enum_.names().any(|n| n == name) {
+                    return Err(self.error(format_args!("unknown case `{name}`")));
+                }
+                enum_.new_val(name)?
+            }
+            Type::Option(option) => match self.keyword()? {
+                "some" => option.new_val(self.payload(Some(&option.ty()))?)?,
+                "none" => option.new_val(None)?,
+                _ => return Err(self.error("expected `some` or `none`")),
+            },
+            Type::Result(result) => match self.keyword()? {
+                "ok" => result.new_val(Ok(self.payload(result.ok().as_ref())?))?,
+                "err" => result.new_val(Err(self.payload(result.err().as_ref())?))?,
+                _ => return Err(self.error("expected `ok` or `err`")),
+            },
+            Type::Flags(flags) => {
+                self.expect('{')?;
+                let names = self.seq('}', |p| p.label())?;
+                for name in names.iter() {
+                    if !flags.names().any(|n| n == *name) {
+                        return Err(self.error(format_args!("unknown flag `{name}`")));
+                    }
+                }
+                flags.new_val(&names)?
+            }
+            Type::Own(_) | Type::Borrow(_) => bail!("resources cannot be written as values"),
+        })
+    }
+}
+
+struct DisplayVal<'a>(&'a Val);
+
+impl fmt::Display for DisplayVal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Val::Bool(b) => write!(f, "{b}"),
+            Val::S8(n) => write!(f, "{n}"),
+            Val::U8(n) => write!(f, "{n}"),
+            Val::S16(n) => write!(f, "{n}"),
+            Val::U16(n) => write!(f, "{n}"),
+            Val::S32(n) => write!(f, "{n}"),
+            Val::U32(n) => write!(f, "{n}"),
+            Val::S64(n) => write!(f, "{n}"),
+            Val::U64(n) => write!(f, "{n}"),
+            // Infinities are already displayed as `inf` and `-inf`.
+            Val::Float32(n) if n.is_nan() => f.write_str("nan"),
+            Val::Float64(n) if n.is_nan() => f.write_str("nan"),
+            Val::Float32(n) => write!(f, "{n}"),
+            Val::Float64(n) => write!(f, "{n}"),
+            Val::Char(c) => {
+                f.write_str("'")?;
+                write_escaped(f, *c, '\'')?;
+                f.write_str("'")
+            }
+            Val::String(s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    write_escaped(f, c, '"')?;
+                }
+                f.write_str("\"")
+            }
+            Val::List(list) => write_seq(f, '[', list.iter().map(DisplayVal), ']'),
+            Val::Tuple(tuple) => write_seq(f, '(', tuple.values().iter().map(DisplayVal), ')'),
+            Val::Record(record) => {
+                if record.fields().next().is_none() {
+                    return f.write_str("{:}");
+                }
+                let fields = record
+                    .fields()
+                    .map(|(name, val)| format!("{}: {}", Label(name), DisplayVal(val)));
+                write_seq(f, '{', fields, '}')
+            }
+            Val::Variant(variant) => {
+                write!(f, "{}", Label(variant.discriminant()))?;
+                write_payload(f, variant.payload())
+            }
+            Val::Enum(enum_) => write!(f, "{}", Label(enum_.discriminant())),
+            Val::Option(option) => match option.value() {
+                Some(val) => {
+                    f.write_str("some")?;
+                    write_payload(f, Some(val))
+                }
+                None => f.write_str("none"),
+            },
+            Val::Result(result) => match result.value() {
+                Ok(val) => {
+                    f.write_str("ok")?;
+                    write_payload(f, val)
+                }
+                Err(val) => {
+                    f.write_str("err")?;
+                    write_payload(f, val)
+                }
+            },
+            Val::Flags(flags) => write_seq(f, '{', flags.flags().map(Label), '}'),
+            Val::Resource(_) => f.write_str("<resource>"),
+        }
+    }
+}
+
+struct Label<'a>(&'a str);
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if KEYWORDS.contains(&self.0) {
+            f.write_str("%")?;
+        }
+        f.write_str(self.0)
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, c: char, quote: char) -> fmt::Result {
+    match c {
+        '\\' => f.write_str("\\\\"),
+        '\t' => f.write_str("\\t"),
+        '\n' => f.write_str("\\n"),
+        '\r' => f.write_str("\\r"),
+        c if c == quote => write!(f, "\\{c}"),
+        c if c.is_control() => write!(f, "\\u{{{:x}}}", u32::from(c)),
+        c => write!(f, "{c}"),
+    }
+}
+
+fn write_seq<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    open: char,
+    items: impl Iterator<Item = T>,
+    close: char,
+) -> fmt::Result {
+    write!(f, "{open}")?;
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    write!(f, "{close}")
+}
+
+fn write_payload(f: &mut fmt::Formatter<'_>, payload: Option<&Val>) -> fmt::Result {
+    match payload {
+        Some(val) => write!(f, "({})", DisplayVal(val)),
+        None => Ok(()),
+    }
+}
@@ -230,6 +230,38 @@ fn call_linked_func() -> Result<(), Error> {
     Ok(())
 }
 
+// Use a hook that is passed the whole store:
+#[test]
+fn call_hook_with_store() -> Result<(), Error> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, State::default());
+    store.call_hook_with_store(|mut store, s| store.data_mut().call_hook(s));
+    let mut linker = Linker::new(&engine);
+
+    linker.func_wrap("host", "f", |caller: Caller<State>| {
+        assert_eq!(caller.data().context, vec![Context::Wasm, Context::Host]);
+    })?;
+
+    let wat = r#"
+        (module
+            (import "host" "f" (func $f))
+            (func (export "export") (call $f))
+        )
+    "#;
+    let module = Module::new(&engine, wat)?;
+
+    let inst = linker.instantiate(&mut store, &module)?;
+    let export = inst.get_typed_func::<(), ()>(&mut store, "export")?;
+    export.call(&mut store, ())?;
+
+    assert_eq!(store.data().calls_into_host, 1);
+    assert_eq!(store.data().returns_from_host, 1);
+    assert_eq!(store.data().calls_into_wasm, 1);
+    assert_eq!(store.data().returns_from_wasm, 1);
+
+    Ok(())
+}
+
 // Use the Linker to define an async func, call it through WebAssembly:
 #[tokio::test]
 async fn call_linked_func_async() -> Result<(), Error> {
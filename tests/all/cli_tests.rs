@@ -126,6 +126,41 @@ fn run_coredump_smoketest() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn coredump_inspect() -> Result<()> {
+    let wasm = build_wasm("tests/all/cli_tests/coredump_inspect.wat")?;
+    let coredump_file = NamedTempFile::new()?;
+    let coredump_arg = format!("-Dcoredump={}", coredump_file.path().display());
+    run_wasmtime(&[
+        "run",
+        "--invoke",
+        "a",
+        "-Ccache=n",
+        &coredump_arg,
+        wasm.path().to_str().unwrap(),
+    ])
+    .unwrap_err();
+
+    let stdout = run_wasmtime(&[
+        "coredump",
+        "--memory",
+        "0..0x20",
+        coredump_file.path().to_str().unwrap(),
+        wasm.path().to_str().unwrap(),
+    ])?;
+    assert!(stdout.contains("thread `main`:"), "{stdout}");
+    let c = stdout.find("!c (wasm offset").expect(&stdout);
+    let b = stdout.find("!b (wasm offset").expect(&stdout);
+    assert!(c < b, "{stdout}");
+    assert!(stdout.contains("memory 0 [0x0..0x20]:"), "{stdout}");
+    assert!(stdout.contains("68 65 6c 6c 6f"), "{stdout}");
+
+    // Without the original module frames are only identified by index.
+    let stdout = run_wasmtime(&["coredump", coredump_file.path().to_str().unwrap()])?;
+    assert!(stdout.contains("<wasm function 2>"), "{stdout}");
+    Ok(())
+}
+
 // Running simple wat
 #[test]
 fn run_wasmtime_simple_wat() -> Result<()> {
@@ -455,6 +490,42 @@ fn run_cwasm() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "objdump"), ignore)]
+fn objdump_cwasm() -> Result<()> {
+    let td = TempDir::new()?;
+    let cwasm = td.path().join("foo.cwasm");
+    let cwasm = cwasm.to_str().unwrap();
+    run_wasmtime(&[
+        "compile",
+        "-Wfuel=1000",
+        "tests/all/cli_tests/simple.wat",
+        "-o",
+        cwasm,
+    ])?;
+
+    let stdout = run_wasmtime(&["objdump", "-Wfuel=1000", "--flags", cwasm])?;
+    assert!(stdout.contains("kind: Module"), "{stdout}");
+    assert!(
+        stdout.contains("compatible with this host: yes"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("consume_fuel = true"), "{stdout}");
+    assert!(stdout.contains("wasm[0]::function[0]"), "{stdout}");
+
+    // Without the same flags the artifact can't be loaded.
+    let stdout = run_wasmtime(&["objdump", cwasm])?;
+    assert!(
+        stdout.contains("compatible with this host: no, Module was compiled with fuel support"),
+        "{stdout}"
+    );
+
+    let stdout = run_wasmtime(&["objdump", "--disassemble", "--filter", "function[0]", cwasm])?;
+    assert!(stdout.contains(";; wasm 0x"), "{stdout}");
+    assert!(!stdout.contains("trampoline"), "{stdout}");
+    Ok(())
+}
+
 #[cfg(unix)]
 #[test]
 fn hello_wasi_snapshot0_from_stdin() -> Result<()> {
@@ -842,6 +913,346 @@ fn run_precompiled_component() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn invoke_component() -> Result<()> {
+    let path = "tests/all/cli_tests/component-invoke.wat";
+    let invoke = |args: &[&str]| {
+        let mut all = vec!["run", "-Wcomponent-model", "--invoke"];
+        all.extend_from_slice(args);
+        run_wasmtime_for_output(&all, None)
+    };
+
+    let output = invoke(&["add", path, "1", "41"])?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+
+    let output = invoke(&[
+        "my:pkg/api#echo-strings",
+        path,
+        r#"["hello", "tab\t", "\u{1F600}", "\"quoted\""]"#,
+    ])?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[\"hello\", \"tab\\t\", \"😀\", \"\\\"quoted\\\"\"]\n"
+    );
+
+    // Omitted `option` fields are `none` and names which collide with
+    // keywords are escaped when printed.
+    let things = r#"[
+        {
+            name: "a",
+            color: %none,
+            perms: {read, exec},
+            shape: circle(1.5),
+            at: (-3, 'x'),
+            status: ok("fine"),
+        },
+        {
+            name: "",
+            color: red,
+            perms: {},
+            shape: empty,
+            at: (0, '\u{7f}'),
+            tag: some(7),
+            status: err(404),
+        },
+    ]"#;
+    let output = invoke(&["echo-things", path, things])?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[{name: \"a\", color: %none, perms: {read, exec}, shape: circle(1.5), \
+         at: (-3, 'x'), tag: none, status: ok(\"fine\")}, \
+         {name: \"\", color: red, perms: {}, shape: empty, at: (0, '\\u{7f}'), \
+         tag: some(7), status: err(404)}]\n"
+    );
+
+    for (args, error) in [
+        (&["add", path, "1"][..], "not enough arguments for `add`"),
+        (
+            &["add", path, "1", "-1"][..],
+            "failed to parse argument `-1`",
+        ),
+        (
+            &["add", path, "1", "2 3"][..],
+            "unexpected trailing characters",
+        ),
+        (
+            &["missing", path][..],
+            "no func export named `missing` found",
+        ),
+        (
+            &["echo-things", path, "[{name: \"a\"}]"][..],
+            "missing field `color`",
+        ),
+    ] {
+        let output = invoke(args)?;
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(error), "bad stderr: {stderr}");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn preload_component() -> Result<()> {
+    let path = "tests/all/cli_tests/component-preload.wat";
+    let run = |args: &[&str]| {
+        let mut all = vec!["run", "-Wcomponent-model"];
+        all.extend_from_slice(args);
+        run_wasmtime_for_output(&all, None)
+    };
+
+    // The root exports of the preloaded component satisfy the `math` import.
+    let preload = "math=tests/all/cli_tests/component-invoke.wat";
+    let output = run(&[
+        "--preload",
+        preload,
+        "-Wunknown-imports-trap",
+        "--invoke",
+        "add-one",
+        path,
+        "41",
+    ])?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+
+    let output = run(&[
+        "--preload",
+        preload,
+        "-Wunknown-imports-trap",
+        "--invoke",
+        "missing",
+        path,
+    ])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown import: `missing` has not been defined"),
+        "bad stderr: {stderr}"
+    );
+
+    // Without the preload the whole `math` instance is synthesized.
+    let output = run(&[
+        "-Wunknown-imports-default",
+        "--invoke",
+        "add-one",
+        path,
+        "41",
+    ])?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "0\n");
+
+    // Imports still need to be satisfied by default.
+    let output = run(&["--preload", preload, "--invoke", "add-one", path, "41"])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("import `missing` has the wrong type"),
+        "bad stderr: {stderr}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn memory_discard() -> Result<()> {
+    let path = "tests/all/cli_tests/memory-discard.wat";
+    let output = run_wasmtime(&["run", "-Wmemory-discard", "--invoke", "discard", path])?;
+    assert_eq!(output, "0\n");
+
+    // The import isn't provided by default.
+    let output = run_wasmtime_for_output(&["run", "--invoke", "discard", path], None)?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn wasi_keyvalue() -> Result<()> {
+    let path = "tests/all/cli_tests/wasi-keyvalue.wat";
+    let dir = TempDir::new()?;
+    let keyvalue_dir = format!("keyvalue-dir={}", dir.path().display());
+
+    // The in-memory store starts out empty on every run.
+    for _ in 0..2 {
+        let output = run_wasmtime(&[
+            "run",
+            "-Wcomponent-model",
+            "-Skeyvalue",
+            "--invoke",
+            "run",
+            path,
+        ])?;
+        assert_eq!(output, "1\n");
+    }
+
+    // ...while values persist between runs in a directory.
+    for expected in ["1\n", "2\n"] {
+        let output = run_wasmtime(&[
+            "run",
+            "-Wcomponent-model",
+            "-Skeyvalue",
+            "-S",
+            &keyvalue_dir,
+            "--invoke",
+            "run",
+            path,
+        ])?;
+        assert_eq!(output, expected);
+    }
+
+    // The interface isn't available unless requested.
+    let output =
+        run_wasmtime_for_output(&["run", "-Wcomponent-model", "--invoke", "run", path], None)?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn wasi_config() -> Result<()> {
+    let path = "tests/all/cli_tests/wasi-config.wat";
+    let mut file = NamedTempFile::new()?;
+    writeln!(file, "greeting = \"from the file\"")?;
+    writeln!(file, "port = 80")?;
+    writeln!(file, "[db]\nhosts = [\"a\", \"b\"]")?;
+    let config_file = format!("config-file={}", file.path().display());
+    let get = |flags: &[&str], func: &str, key: &str| {
+        let key = format!("{key:?}");
+        let mut args = vec!["run", "-Wcomponent-model", "-Sconfig", "-S", &config_file];
+        args.extend_from_slice(flags);
+        args.extend_from_slice(&["--invoke", func, path, &key]);
+        run_wasmtime(&args)
+    };
+
+    // Values from the command line take precedence over the file.
+    let output = get(&["-Sconfig-var=greeting=hello"], "get", "greeting")?;
+    assert_eq!(output, "ok(some(\"hello\"))\n");
+    let output = get(&[], "get", "greeting")?;
+    assert_eq!(output, "ok(some(\"from the file\"))\n");
+    let output = get(&[], "get", "missing")?;
+    assert_eq!(output, "ok(none)\n");
+
+    // Values keep their type when requested with `get-typed`, and are
+    // rendered as strings otherwise.
+    let output = get(&[], "get", "port")?;
+    assert_eq!(output, "ok(some(\"80\"))\n");
+    let output = get(&[], "get-typed", "port")?;
+    assert_eq!(output, "ok(some(integer(80)))\n");
+    let output = get(&[], "get-typed", "db.hosts")?;
+    assert_eq!(output, "ok(some(list([text(\"a\"), text(\"b\")])))\n");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn wasi_logging() -> Result<()> {
+    let path = "tests/all/cli_tests/wasi-logging.wat";
+    let run = |log: Option<&str>| -> Result<String> {
+        let mut cmd = get_wasmtime_command()?;
+        cmd.args(&[
+            "run",
+            "-Wcomponent-model",
+            "-Slogging",
+            "--invoke",
+            "run",
+            path,
+        ]);
+        match log {
+            Some(log) => cmd.env("WASMTIME_LOG", log),
+            None => cmd.env_remove("WASMTIME_LOG"),
+        };
+        let output = cmd.output()?;
+        assert!(output.status.success(), "{output:?}");
+        Ok(String::from_utf8(output.stderr)?)
+    };
+
+    // Guest records at `info` and above are shown by default.
+    let stderr = run(None)?;
+    assert!(stderr.contains("hello from the guest"), "{stderr}");
+    assert!(stderr.contains("greeting"), "{stderr}");
+    assert!(!stderr.contains("details for debugging"), "{stderr}");
+
+    // A default level for all targets doesn't hide guest records...
+    let stderr = run(Some("error"))?;
+    assert!(stderr.contains("hello from the guest"), "{stderr}");
+
+    // ...and neither does a directive which only mentions the target in a
+    // field filter.
+    let stderr = run(Some("wasmtime[{target=wasi_logging}]=trace"))?;
+    assert!(stderr.contains("hello from the guest"), "{stderr}");
+
+    // Directives for the target, or a prefix of it, override the default.
+    let stderr = run(Some("wasi_logging=debug"))?;
+    assert!(stderr.contains("hello from the guest"), "{stderr}");
+    assert!(stderr.contains("details for debugging"), "{stderr}");
+    let stderr = run(Some("warn,wasi=error"))?;
+    assert!(!stderr.contains("hello from the guest"), "{stderr}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "profiling"), ignore)]
+fn guest_profile_component() -> Result<()> {
+    let dir = TempDir::new()?;
+    let profile = dir.path().join("profile.json");
+    let output = run_wasmtime(&[
+        "run",
+        "-Wcomponent-model",
+        &format!("--profile=guest,{}", profile.display()),
+        "--invoke",
+        "add",
+        "tests/all/cli_tests/component-invoke.wat",
+        "1",
+        "2",
+    ])?;
+    assert_eq!(output, "3\n");
+
+    let profile = std::fs::read_to_string(&profile)?;
+    assert!(profile.contains("component-invoke.wat"), "{profile}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "wit"), ignore)]
+fn wit_of_component() -> Result<()> {
+    let wit = run_wasmtime(&["wit", "tests/all/cli_tests/component-invoke.wat"])?;
+    assert!(wit.contains("world "), "{wit}");
+    assert!(
+        wit.contains("export add: func(a: u32, b: u32) -> u32;"),
+        "{wit}"
+    );
+    assert!(wit.contains("export my:pkg/api"), "{wit}");
+
+    let output = run_wasmtime_for_output(&["wit", "tests/all/cli_tests/simple.wat"], None)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("is not a component"), "{stderr}");
+    Ok(())
+}
+
+#[test]
+fn explore_component() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = dir.path().join("explore.html");
+    run_wasmtime(&[
+        "explore",
+        "tests/all/cli_tests/component-invoke.wat",
+        "--output",
+        output.to_str().unwrap(),
+    ])?;
+    let html = std::fs::read_to_string(&output)?;
+    assert!(html.contains("window.MODULES"));
+    assert!(html.contains("Optimized CLIF"));
+    assert!(html.contains("VCode"));
+    Ok(())
+}
+
 #[test]
 fn memory_growth_failure() -> Result<()> {
     let output = get_wasmtime_command()?
@@ -1463,6 +1874,68 @@ mod test_programs {
         Ok(())
     }
 
+    #[test]
+    #[cfg_attr(not(all(feature = "serve", feature = "profiling")), ignore)]
+    fn serve_guest_profile() -> Result<()> {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpStream;
+
+        let dir = tempfile::tempdir()?;
+        let profile = dir.path().join("profile.json");
+        let mut child = get_wasmtime_command()?
+            .args(&[
+                "serve",
+                "-Ccache=no",
+                "--addr=127.0.0.1:0",
+                &format!("--profile=guest,{}", profile.display()),
+                API_PROXY_COMPONENT,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let result = (|| -> Result<String> {
+            let mut stderr = BufReader::new(child.stderr.take().unwrap());
+            let mut line = String::new();
+            stderr.read_line(&mut line)?;
+            let addr = line
+                .trim()
+                .strip_prefix("Serving HTTP on http://")
+                .and_then(|s| s.strip_suffix('/'))
+                .ok_or_else(|| anyhow::anyhow!("unexpected output: {line}"))?
+                .to_string();
+
+            let mut stream = TcpStream::connect(&addr)?;
+            write!(
+                stream,
+                "GET / HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+            )?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+            assert!(response.contains("hello, world!"), "{response}");
+
+            // The profile of the first request is written once it completes.
+            let request_profile = dir.path().join("profile.0.json");
+            for _ in 0..100 {
+                if let Ok(profile) = std::fs::read_to_string(&request_profile) {
+                    if profile.trim_end().ends_with('}') {
+                        return Ok(profile);
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            anyhow::bail!("no profile written at {}", request_profile.display())
+        })();
+        child.kill()?;
+        child.wait()?;
+
+        let profile = result?;
+        assert!(profile.contains("[0]"), "{profile}");
+        assert!(!dir.path().join("profile.json").exists());
+        Ok(())
+    }
+
     // Test to ensure that prints in the guest aren't buffered on the host by
     // accident. The test here will print something without a newline and then
     // wait for input on stdin, and the test here is to ensure that the
@@ -0,0 +1,55 @@
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 16))
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      ;; round the heap up to the requested alignment
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ret) (local.get 3)))
+      local.get $ret)
+
+    (func (export "add") (param i32 i32) (result i32)
+      (i32.add (local.get 0) (local.get 1)))
+
+    ;; Returns the list given as an argument, which works for any element type
+    ;; since lists are passed as a pointer and length.
+    (func (export "echo") (param i32 i32) (result i32)
+      (i32.store (i32.const 0) (local.get 0))
+      (i32.store (i32.const 4) (local.get 1))
+      i32.const 0)
+  )
+  (core instance $i (instantiate $m))
+
+  (type $color' (enum "red" "green" "none"))
+  (export $color "color" (type $color'))
+  (type $perms' (flags "read" "write" "exec"))
+  (export $perms "perms" (type $perms'))
+  (type $shape' (variant (case "circle" float32) (case "square" u32) (case "empty")))
+  (export $shape "shape" (type $shape'))
+  (type $thing' (record
+    (field "name" string)
+    (field "color" $color)
+    (field "perms" $perms)
+    (field "shape" $shape)
+    (field "at" (tuple s32 char))
+    (field "tag" (option u8))
+    (field "status" (result string (error u16)))))
+  (export $thing "thing" (type $thing'))
+
+  (func $add (param "a" u32) (param "b" u32) (result u32)
+    (canon lift (core func $i "add")))
+  (func $echo-strings (param "x" (list string)) (result (list string))
+    (canon lift (core func $i "echo") (memory $i "memory") (realloc (func $i "realloc"))))
+  (func $echo-things (param "x" (list $thing)) (result (list $thing))
+    (canon lift (core func $i "echo") (memory $i "memory") (realloc (func $i "realloc"))))
+
+  (export "add" (func $add))
+  (export "echo-things" (func $echo-things))
+  (instance (export "my:pkg/api")
+    (export "echo-strings" (func $echo-strings)))
+)
@@ -0,0 +1,28 @@
+(component
+  (import "math" (instance $math
+    (export "add" (func (param "a" u32) (param "b" u32) (result u32)))
+  ))
+  (import "missing" (func $missing (result u32)))
+
+  (core func $add (canon lower (func $math "add")))
+  (core func $missing (canon lower (func $missing)))
+  (core module $m
+    (import "" "add" (func $add (param i32 i32) (result i32)))
+    (import "" "missing" (func $missing (result i32)))
+    (func (export "add-one") (param i32) (result i32)
+      (call $add (local.get 0) (i32.const 1)))
+    (func (export "missing") (result i32)
+      call $missing)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance
+      (export "add" (func $add))
+      (export "missing" (func $missing))
+    ))
+  ))
+
+  (func (export "add-one") (param "x" u32) (result u32)
+    (canon lift (core func $i "add-one")))
+  (func (export "missing") (result u32)
+    (canon lift (core func $i "missing")))
+)
@@ -0,0 +1,13 @@
+(module
+    (memory 1)
+    (data (i32.const 0x10) "hello")
+    (func (export "a")
+        call $b
+    )
+    (func $b
+        call $c
+    )
+    (func $c
+        unreachable
+    )
+)
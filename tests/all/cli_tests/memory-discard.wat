@@ -0,0 +1,8 @@
+(module
+  (import "wasmtime" "memory_discard" (func $discard (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\2a")
+  (func (export "discard") (result i32)
+    (call $discard (i32.const 0) (i32.const 0x10000))
+    (i32.load8_u (i32.const 0)))
+)
@@ -0,0 +1,69 @@
+(component
+  (import "wasi:config/store@0.2.0-draft" (instance $config
+    (type $error (variant (case "upstream" string) (case "io" string)))
+    (export "error" (type $error' (eq $error)))
+    (export "get" (func (param "key" string) (result (result (option string) (error $error')))))
+  ))
+  (alias export $config "error" (type $error))
+  (import "wasmtime-ext:config/typed" (instance $typed
+    (export "error" (type $error' (eq $error)))
+    (type $scalar (variant
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "text" string)))
+    (export "scalar" (type $scalar' (eq $scalar)))
+    (type $value (variant
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "text" string)
+      (case "list" (list $scalar'))))
+    (export "value" (type $value' (eq $value)))
+    (export "get" (func (param "key" string) (result (result (option $value') (error $error')))))
+  ))
+  (alias export $typed "value" (type $value))
+
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      ;; round the heap up to the requested alignment
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ret) (local.get 3)))
+      local.get $ret)
+  )
+  (core instance $libc (instantiate $libc))
+  (core func $get (canon lower (func $config "get")
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (core func $get-typed (canon lower (func $typed "get")
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+
+  ;; Both functions forward their argument to the import and return its
+  ;; result as-is.
+  (core module $m
+    (import "" "get" (func $get (param i32 i32 i32)))
+    (import "" "get-typed" (func $get-typed (param i32 i32 i32)))
+    (func (export "get") (param i32 i32) (result i32)
+      (call $get (local.get 0) (local.get 1) (i32.const 8))
+      i32.const 8)
+    (func (export "get-typed") (param i32 i32) (result i32)
+      (call $get-typed (local.get 0) (local.get 1) (i32.const 8))
+      i32.const 8)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance
+      (export "get" (func $get))
+      (export "get-typed" (func $get-typed))
+    ))
+  ))
+
+  (func (export "get") (param "key" string) (result (result (option string) (error $error)))
+    (canon lift (core func $i "get") (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (func (export "get-typed") (param "key" string) (result (result (option $value) (error $error)))
+    (canon lift (core func $i "get-typed") (memory $libc "memory") (realloc (func $libc "realloc"))))
+)
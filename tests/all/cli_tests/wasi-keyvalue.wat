@@ -0,0 +1,70 @@
+(component
+  (import "wasi:keyvalue/store@0.2.0-draft" (instance $store
+    (type $error (variant (case "no-such-store") (case "access-denied") (case "other" string)))
+    (export "error" (type $error' (eq $error)))
+    (export "bucket" (type $bucket (sub resource)))
+    (export "open" (func (param "identifier" string) (result (result (own $bucket) (error $error')))))
+  ))
+  (alias export $store "error" (type $error))
+  (alias export $store "bucket" (type $bucket))
+  (import "wasi:keyvalue/atomics@0.2.0-draft" (instance $atomics
+    (export "error" (type $error' (eq $error)))
+    (export "bucket" (type $bucket' (eq $bucket)))
+    (export "increment" (func
+      (param "bucket" (borrow $bucket'))
+      (param "key" string)
+      (param "delta" u64)
+      (result (result u64 (error $error')))))
+  ))
+
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      ;; round the heap up to the requested alignment
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ret) (local.get 3)))
+      local.get $ret)
+  )
+  (core instance $libc (instantiate $libc))
+  (core func $open (canon lower (func $store "open")
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (core func $increment (canon lower (func $atomics "increment")
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (core func $drop (canon resource.drop $bucket))
+
+  ;; Increments the `hits` counter of the `counters` bucket and returns its
+  ;; new value.
+  (core module $m
+    (import "libc" "memory" (memory 1))
+    (import "" "open" (func $open (param i32 i32 i32)))
+    (import "" "increment" (func $increment (param i32 i32 i32 i64 i32)))
+    (import "" "drop" (func $drop (param i32)))
+    (data (i32.const 100) "counters")
+    (data (i32.const 108) "hits")
+
+    (func (export "run") (result i64)
+      (local $bucket i32)
+      (call $open (i32.const 100) (i32.const 8) (i32.const 0))
+      (if (i32.load8_u (i32.const 0)) (then unreachable))
+      (local.set $bucket (i32.load (i32.const 4)))
+      (call $increment (local.get $bucket) (i32.const 108) (i32.const 4) (i64.const 1) (i32.const 16))
+      (if (i32.load8_u (i32.const 16)) (then unreachable))
+      (call $drop (local.get $bucket))
+      (i64.load (i32.const 24)))
+  )
+  (core instance $i (instantiate $m
+    (with "libc" (instance $libc))
+    (with "" (instance
+      (export "open" (func $open))
+      (export "increment" (func $increment))
+      (export "drop" (func $drop))
+    ))
+  ))
+
+  (func (export "run") (result u64) (canon lift (core func $i "run")))
+)
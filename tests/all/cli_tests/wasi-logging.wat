@@ -0,0 +1,31 @@
+(component
+  (import "wasi:logging/logging@0.1.0-draft" (instance $logging
+    (type $level (enum "trace" "debug" "info" "warn" "error" "critical"))
+    (export "level" (type $level' (eq $level)))
+    (export "log" (func (param "level" $level') (param "context" string) (param "message" string)))
+  ))
+
+  (core module $libc (memory (export "memory") 1))
+  (core instance $libc (instantiate $libc))
+  (core func $log (canon lower (func $logging "log") (memory $libc "memory")))
+
+  (core module $m
+    (import "libc" "memory" (memory 1))
+    (import "" "log" (func $log (param i32 i32 i32 i32 i32)))
+    (data (i32.const 0) "greeting")
+    (data (i32.const 8) "hello from the guest")
+    (data (i32.const 28) "details for debugging")
+
+    (func (export "run")
+      ;; info
+      (call $log (i32.const 2) (i32.const 0) (i32.const 8) (i32.const 8) (i32.const 20))
+      ;; debug
+      (call $log (i32.const 1) (i32.const 0) (i32.const 8) (i32.const 28) (i32.const 21)))
+  )
+  (core instance $i (instantiate $m
+    (with "libc" (instance $libc))
+    (with "" (instance (export "log" (func $log))))
+  ))
+
+  (func (export "run") (canon lift (core func $i "run")))
+)
@@ -17,6 +17,7 @@ mod nested;
 mod post_return;
 mod resources;
 mod strings;
+mod types;
 
 #[test]
 #[cfg_attr(miri, ignore)]
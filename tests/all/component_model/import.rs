@@ -985,3 +985,197 @@ fn use_types_across_component_boundaries() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn define_unknown_imports() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "known" (func $known (result u32)))
+            (import "unknown" (func $unknown (param "x" string) (result u32)))
+            (import "my:pkg/api" (instance $api
+                (export "get" (func (result u32)))
+            ))
+
+            (core module $libc (memory (export "memory") 1))
+            (core instance $libc (instantiate $libc))
+            (core func $known (canon lower (func $known)))
+            (core func $unknown (canon lower (func $unknown) (memory $libc "memory")))
+            (core func $get (canon lower (func $api "get")))
+
+            (core module $m
+                (import "" "known" (func $known (result i32)))
+                (import "" "unknown" (func $unknown (param i32 i32) (result i32)))
+                (import "" "get" (func $get (result i32)))
+                (func (export "known") (result i32) call $known)
+                (func (export "unknown") (result i32)
+                    (call $unknown (i32.const 0) (i32.const 0)))
+                (func (export "get") (result i32) call $get)
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "known" (func $known))
+                    (export "unknown" (func $unknown))
+                    (export "get" (func $get))
+                ))
+            ))
+
+            (func (export "known") (result u32) (canon lift (core func $i "known")))
+            (func (export "unknown") (result u32) (canon lift (core func $i "unknown")))
+            (func (export "get") (result u32) (canon lift (core func $i "get")))
+        )"#,
+    )?;
+
+    // Each call gets a fresh instance since traps poison the instance.
+    let call = |linker: &Linker<()>, name: &str| -> Result<u32> {
+        let mut store = Store::new(&engine, ());
+        let instance = linker.instantiate(&mut store, &component)?;
+        let func = instance.get_typed_func::<(), (u32,)>(&mut store, name)?;
+        Ok(func.call(&mut store, ())?.0)
+    };
+
+    // Imports which are defined are left as-is.
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap("known", |_, ()| Ok((7u32,)))?;
+    linker.define_unknown_imports_as_traps(&component)?;
+    assert_eq!(call(&linker, "known")?, 7);
+    let err = call(&linker, "unknown").unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `unknown` has not been defined"),
+        "bad error: {err:?}"
+    );
+    let err = call(&linker, "get").unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `my:pkg/api#get` has not been defined"),
+        "bad error: {err:?}"
+    );
+
+    // Functions are added to instances which are already defined.
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap("known", |_, ()| Ok((7u32,)))?;
+    linker.instance("my:pkg/api")?;
+    linker.define_unknown_imports_as_default_values(&component)?;
+    assert_eq!(call(&linker, "known")?, 7);
+    assert_eq!(call(&linker, "unknown")?, 0);
+    assert_eq!(call(&linker, "get")?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn define_unknown_imports_with_resources() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "my:pkg/res" (instance $res
+                (export "r" (type $r (sub resource)))
+                (export "make" (func (result (own $r))))
+            ))
+            (alias export $res "r" (type $r))
+
+            (core func $make (canon lower (func $res "make")))
+            (core func $drop (canon resource.drop $r))
+
+            (core module $m
+                (import "" "make" (func $make (result i32)))
+                (import "" "drop" (func $drop (param i32)))
+                (func (export "run") (call $drop (call $make)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "make" (func $make))
+                    (export "drop" (func $drop))
+                ))
+            ))
+
+            (func (export "run") (canon lift (core func $i "run")))
+        )"#,
+    )?;
+
+    // The resource is stubbed out so the component can be instantiated, and
+    // only calling the function which creates one traps.
+    let mut linker = Linker::<()>::new(&engine);
+    linker.define_unknown_imports_as_traps(&component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `my:pkg/res#make` has not been defined"),
+        "bad error: {err:?}"
+    );
+
+    let mut linker = Linker::<()>::new(&engine);
+    linker.define_unknown_imports_as_default_values(&component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("cannot return a default value for a resource"),
+        "bad error: {err:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn define_unknown_imports_with_distinct_resources() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "a" (type $a (sub resource)))
+            (import "b" (type $b (sub resource)))
+            (export "a" (type $a))
+            (export "b" (type $b))
+        )"#,
+    )?;
+
+    // Every stubbed resource import has a type of its own.
+    let mut linker = Linker::<()>::new(&engine);
+    linker.define_unknown_imports_as_traps(&component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let a = instance.get_resource(&mut store, "a").unwrap();
+    let b = instance.get_resource(&mut store, "b").unwrap();
+    assert_ne!(a, b);
+    assert_ne!(a, ResourceType::host::<()>());
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    assert_eq!(instance.get_resource(&mut store, "a"), Some(a));
+
+    Ok(())
+}
+
+#[test]
+fn define_unknown_imports_rejects_modules() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "m" (core module))
+        )"#,
+    )?;
+
+    let mut linker = Linker::<()>::new(&engine);
+    let err = linker
+        .define_unknown_imports_as_traps(&component)
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `m` cannot be defined automatically"),
+        "bad error: {err:?}"
+    );
+
+    // Modules which are already defined are left as-is.
+    let module = wasmtime::Module::new(&engine, "(module)")?;
+    linker.root().module("m", &module)?;
+    linker.define_unknown_imports_as_traps(&component)?;
+    let mut store = Store::new(&engine, ());
+    linker.instantiate(&mut store, &component)?;
+
+    Ok(())
+}
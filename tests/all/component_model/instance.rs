@@ -55,3 +55,183 @@ fn instance_exports() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn instance_exports_in_linker() -> Result<()> {
+    let engine = super::engine();
+    let backend = Component::new(
+        &engine,
+        r#"
+            (component
+                (core module $m
+                    (func (export "add") (param i32 i32) (result i32)
+                        (i32.add (local.get 0) (local.get 1)))
+                )
+                (core instance $i (instantiate $m))
+                (func $add (param "a" u32) (param "b" u32) (result u32)
+                    (canon lift (core func $i "add")))
+                (instance $math (export "add" (func $add)))
+                (export "my:pkg/math" (instance $math))
+            )
+        "#,
+    )?;
+    let frontend = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "my:pkg/math" (instance $math
+                    (export "add" (func (param "a" u32) (param "b" u32) (result u32)))
+                ))
+                (core func $add (canon lower (func $math "add")))
+                (core module $m
+                    (import "" "add" (func $add (param i32 i32) (result i32)))
+                    (func (export "run") (param i32) (result i32)
+                        (call $add (local.get 0) (i32.const 100)))
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance (export "add" (func $add))))
+                ))
+                (func (export "run") (param "x" u32) (result u32)
+                    (canon lift (core func $i "run")))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let backend = Linker::new(&engine).instantiate(&mut store, &backend)?;
+
+    let mut linker = Linker::new(&engine);
+    assert!(linker
+        .root()
+        .instance_exports(&mut store, &backend, &["missing"])
+        .is_err());
+    linker
+        .instance("my:pkg/math")?
+        .instance_exports(&mut store, &backend, &["my:pkg/math"])?;
+    let i = linker.instantiate(&mut store, &frontend)?;
+    let run = i.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, (23,))?, (123,));
+    run.post_return(&mut store)?;
+
+    // Exports may only be used within the store they originated from.
+    let mut store2 = Store::new(&engine, ());
+    let i = linker.instantiate(&mut store2, &frontend)?;
+    let run = i.get_typed_func::<(u32,), (u32,)>(&mut store2, "run")?;
+    assert!(run.call(&mut store2, (23,)).is_err());
+
+    // Mismatched types are rejected during instantiation.
+    let mismatched = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "my:pkg/math" (instance
+                    (export "add" (func (param "a" u64) (param "b" u64) (result u64)))
+                ))
+            )
+        "#,
+    )?;
+    assert!(linker.instantiate(&mut store, &mismatched).is_err());
+    Ok(())
+}
+
+#[test]
+fn instance_exports_resources_in_linker() -> Result<()> {
+    let engine = super::engine();
+    let backend = Component::new(
+        &engine,
+        r#"
+            (component
+                (core module $m
+                    (global $drops (mut i32) i32.const 0)
+                    (func (export "dtor") (param i32)
+                        (global.set $drops (i32.add (global.get $drops) (local.get 0))))
+                    (func (export "drops") (result i32) global.get $drops)
+                    (func (export "rep") (param i32) (result i32) local.get 0)
+                )
+                (core instance $i (instantiate $m))
+                (type $r' (resource (rep i32) (dtor (func $i "dtor"))))
+                (export $r "r" (type $r'))
+                (core func $ctor (canon resource.new $r))
+                (func (export "[constructor]r") (param "rep" u32) (result (own $r))
+                    (canon lift (core func $ctor)))
+                (func (export "[method]r.rep") (param "self" (borrow $r)) (result u32)
+                    (canon lift (core func $i "rep")))
+                (func (export "drops") (result u32)
+                    (canon lift (core func $i "drops")))
+            )
+        "#,
+    )?;
+    let frontend = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "backend" (instance $backend
+                    (export "r" (type $r (sub resource)))
+                    (export "[constructor]r" (func (param "rep" u32) (result (own $r))))
+                    (export "[method]r.rep" (func (param "self" (borrow $r)) (result u32)))
+                ))
+                (alias export $backend "r" (type $r))
+                (core func $ctor (canon lower (func $backend "[constructor]r")))
+                (core func $rep (canon lower (func $backend "[method]r.rep")))
+                (core func $drop (canon resource.drop $r))
+                (core module $m
+                    (import "" "ctor" (func $ctor (param i32) (result i32)))
+                    (import "" "rep" (func $rep (param i32) (result i32)))
+                    (import "" "drop" (func $drop (param i32)))
+                    (func (export "run") (param i32) (result i32)
+                        (local $handle i32)
+                        (local $rep i32)
+                        (local.set $handle (call $ctor (local.get 0)))
+                        (local.set $rep (call $rep (local.get $handle)))
+                        (call $drop (local.get $handle))
+                        local.get $rep)
+                    (func (export "pass") (param i32) (result i32) local.get 0)
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance
+                        (export "ctor" (func $ctor))
+                        (export "rep" (func $rep))
+                        (export "drop" (func $drop))
+                    ))
+                ))
+                (func (export "run") (param "rep" u32) (result u32)
+                    (canon lift (core func $i "run")))
+                (func (export "pass") (param "r" (own $r)) (result (own $r))
+                    (canon lift (core func $i "pass")))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let backend = Linker::new(&engine).instantiate(&mut store, &backend)?;
+    let r = backend.get_resource(&mut store, "r").unwrap();
+    let ctor = backend.get_typed_func::<(u32,), (ResourceAny,)>(&mut store, "[constructor]r")?;
+    let drops = backend.get_typed_func::<(), (u32,)>(&mut store, "drops")?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("backend")?
+        .instance_exports(&mut store, &backend, &[])?;
+    let frontend = linker.instantiate(&mut store, &frontend)?;
+    let run = frontend.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    let pass = frontend.get_typed_func::<(ResourceAny,), (ResourceAny,)>(&mut store, "pass")?;
+
+    // The frontend creates, borrows, and drops a backend resource which runs
+    // the backend's destructor.
+    assert_eq!(run.call(&mut store, (10,))?, (10,));
+    run.post_return(&mut store)?;
+    assert_eq!(drops.call(&mut store, ())?, (10,));
+    drops.post_return(&mut store)?;
+
+    // Backend resources can be passed through the frontend and are still
+    // destroyed by the backend.
+    let (resource,) = ctor.call(&mut store, (5,))?;
+    ctor.post_return(&mut store)?;
+    let (resource,) = pass.call(&mut store, (resource,))?;
+    pass.post_return(&mut store)?;
+    assert_eq!(resource.ty(), r);
+    resource.resource_drop(&mut store)?;
+    assert_eq!(drops.call(&mut store, ())?, (15,));
+    drops.post_return(&mut store)?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use wasmtime::component::types::{ComponentItem, Type};
+use wasmtime::component::{Component, Linker, ResourceType};
+use wasmtime::{Store, ValType};
+
+#[test]
+fn component_type_imports_and_exports() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (type $point (record (field "x" s32) (field "y" s32)))
+                (import "point" (type $point' (eq $point)))
+                (import "host" (instance $host
+                    (export "log" (func (param "msg" string)))
+                    (export "m" (core module
+                        (export "f" (func (param i32) (result i64)))
+                    ))
+                ))
+                (import "get" (func (result (list u8))))
+
+                (core module $m
+                    (func (export "add") (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        i32.add)
+                )
+                (core instance $i (instantiate $m))
+                (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+                    (canon lift (core func $i "add")))
+            )
+        "#,
+    )?;
+    let ty = component.component_type();
+
+    let imports = ty.imports().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(imports, ["point", "host", "get"]);
+    match ty.get_import("point") {
+        Some(ComponentItem::Type(Type::Record(record))) => {
+            let fields = record.fields().map(|f| f.name).collect::<Vec<_>>();
+            assert_eq!(fields, ["x", "y"]);
+        }
+        other => panic!("unexpected import type {other:?}"),
+    }
+    let host = match ty.get_import("host") {
+        Some(ComponentItem::ComponentInstance(instance)) => instance,
+        other => panic!("unexpected import type {other:?}"),
+    };
+    match host.get_export("log") {
+        Some(ComponentItem::ComponentFunc(func)) => {
+            assert_eq!(func.params().collect::<Vec<_>>(), [Type::String]);
+            assert_eq!(func.results().len(), 0);
+        }
+        other => panic!("unexpected export type {other:?}"),
+    }
+    match host.get_export("m") {
+        Some(ComponentItem::Module(module)) => {
+            assert_eq!(module.imports().len(), 0);
+            let (name, ty) = module.exports().next().unwrap();
+            assert_eq!(name, "f");
+            let ty = ty.unwrap_func();
+            assert_eq!(ty.params().collect::<Vec<_>>(), [ValType::I32]);
+            assert_eq!(ty.results().collect::<Vec<_>>(), [ValType::I64]);
+        }
+        other => panic!("unexpected export type {other:?}"),
+    }
+    match ty.get_import("get") {
+        Some(ComponentItem::ComponentFunc(func)) => {
+            let result = func.results().next().unwrap();
+            assert_eq!(result.unwrap_list().ty(), Type::U8);
+        }
+        other => panic!("unexpected import type {other:?}"),
+    }
+
+    assert_eq!(ty.exports().len(), 1);
+    match ty.get_export("add") {
+        Some(ComponentItem::ComponentFunc(func)) => {
+            assert_eq!(func.params().collect::<Vec<_>>(), [Type::U32, Type::U32]);
+            assert_eq!(func.results().collect::<Vec<_>>(), [Type::U32]);
+        }
+        other => panic!("unexpected export type {other:?}"),
+    }
+    assert!(ty.get_export("missing").is_none());
+    Ok(())
+}
+
+#[test]
+fn component_type_resources() -> Result<()> {
+    let engine = super::engine();
+    let wat = r#"
+            (component
+                (import "imported" (type $imported (sub resource)))
+
+                (type $defined (resource (rep i32)))
+                (export $exported "defined" (type $defined))
+
+                (core module $m
+                    (func (export "f") (param i32) (result i32)
+                        local.get 0)
+                )
+                (core instance $i (instantiate $m))
+                (func (export "f") (param "x" (borrow $imported)) (result (own $exported))
+                    (canon lift (core func $i "f")))
+            )
+        "#;
+    let component = Component::new(&engine, wat)?;
+    let ty = component.component_type();
+
+    let imported = match ty.get_import("imported") {
+        Some(ComponentItem::Resource(ty)) => ty,
+        other => panic!("unexpected import type {other:?}"),
+    };
+    let defined = match ty.get_export("defined") {
+        Some(ComponentItem::Resource(ty)) => ty,
+        other => panic!("unexpected export type {other:?}"),
+    };
+    assert_ne!(imported, defined);
+    assert_ne!(imported, ResourceType::host::<u32>());
+
+    match ty.get_export("f") {
+        Some(ComponentItem::ComponentFunc(func)) => {
+            assert_eq!(func.params().collect::<Vec<_>>(), [Type::Borrow(imported)]);
+            assert_eq!(func.results().collect::<Vec<_>>(), [Type::Own(defined)]);
+        }
+        other => panic!("unexpected export type {other:?}"),
+    }
+
+    // The types reflected before instantiation are stable.
+    let ty2 = component.component_type();
+    assert!(matches!(
+        ty2.get_import("imported"),
+        Some(ComponentItem::Resource(ty)) if ty == imported
+    ));
+
+    // Reflecting types doesn't interfere with instantiation.
+    let mut linker = Linker::<()>::new(&engine);
+    linker
+        .root()
+        .resource("imported", ResourceType::host::<u32>(), |_, _| Ok(()))?;
+    let mut store = Store::new(&engine, ());
+    linker.instantiate(&mut store, &component)?;
+
+    // Resources of another component are different types, even when the
+    // component is identical and the first one is gone.
+    drop(store);
+    drop((ty, ty2));
+    drop(component);
+    let other = Component::new(&engine, wat)?.component_type();
+    assert!(matches!(
+        other.get_import("imported"),
+        Some(ComponentItem::Resource(ty)) if ty != imported
+    ));
+    assert!(matches!(
+        other.get_export("defined"),
+        Some(ComponentItem::Resource(ty)) if ty != defined
+    ));
+    Ok(())
+}
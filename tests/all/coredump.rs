@@ -258,3 +258,247 @@ fn multiple_globals_memories_and_instances() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_round_trips_through_serialize() -> Result<()> {
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    let engine = Engine::new(&config).unwrap();
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module $m
+                (global (mut i32) (i32.const 42))
+                (memory 1)
+                (data (i32.const 0x100) "hello")
+                (func $a (export "a")
+                    call $b
+                )
+                (func $b
+                    unreachable
+                )
+            )
+        "#,
+    )?;
+
+    let mut store = Store::<()>::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let a_func = instance.get_typed_func::<(), ()>(&mut store, "a")?;
+    let err = a_func.call(&mut store, ()).unwrap_err();
+    let core_dump = err.downcast_ref::<WasmCoreDump>().unwrap();
+    let bytes = core_dump.serialize(&mut store, "round-trip");
+
+    let file = CoreDumpFile::parse(&bytes)?;
+    assert_eq!(file.name(), "round-trip");
+    assert_eq!(file.modules(), ["m"]);
+    assert_eq!(file.instances().len(), 1);
+    assert_eq!(file.instances()[0].module(), 0);
+    assert_eq!(file.globals(), [CoreDumpValue::I32(42)]);
+
+    assert_eq!(file.memories().len(), 1);
+    let memory = &file.memories()[0];
+    assert_eq!(memory.size(), 0x10000);
+    assert_eq!(memory.read(0xfe..0x107).unwrap(), b"\0\0hello\0\0");
+    assert!(memory.read(0xffff..0x10001).is_none());
+
+    assert_eq!(file.threads().len(), 1);
+    let frames = file.threads()[0].frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].func_index(), 1);
+    assert_eq!(frames[1].func_index(), 0);
+
+    let info = frames[0].symbolize(&module).unwrap();
+    assert_eq!(info.func_name(), Some("b"));
+    assert_eq!(info.module_offset(), core_dump.frames()[0].module_offset());
+    let info = frames[1].symbolize(&module).unwrap();
+    assert_eq!(info.func_name(), Some("a"));
+
+    assert!(CoreDumpFile::parse(&wat::parse_str("(module)")?).is_err());
+
+    // Crafted core dumps are rejected rather than overflowing.
+    let out_of_bounds = wat::parse_str(
+        r#"
+            (module
+                (@custom "core" "\00\04dump")
+                (memory 1)
+                (data (i32.const 0xffff) "ab")
+            )
+        "#,
+    )?;
+    assert!(CoreDumpFile::parse(&out_of_bounds).is_err());
+    let huge = wat::parse_str(
+        r#"
+            (module
+                (@custom "core" "\00\04dump")
+                (memory i64 0x1000000000000)
+            )
+        "#,
+    )?;
+    assert!(CoreDumpFile::parse(&huge).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_has_frame_locals() -> Result<()> {
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    config.debug_info(true);
+    // Optimizations may fold the updates below into values that are only
+    // computed after the call, leaving older values of the locals behind.
+    config.cranelift_opt_level(OptLevel::None);
+    let engine = Engine::new(&config).unwrap();
+    let mut store = Store::<()>::new(&engine, ());
+
+    // Locals are only recovered from stack slots, so `$a` keeps more values
+    // alive across its call than there are callee-saved registers, making
+    // sure some of them are spilled. The locals are used heavily in loops
+    // while its parameter is used just once more after the call, so the
+    // parameter is the first to be spilled. Cranelift only tracks locals
+    // within the blocks that define or use them, so they're also updated
+    // right before the call.
+    const N: usize = 24;
+    let locals = "i32 ".repeat(N);
+    let update = (1..=N)
+        .map(|i| format!("(local.set {i} (i32.add (local.get {i}) (i32.const {i})))"))
+        .collect::<String>();
+    let sum = (1..=N)
+        .map(|i| format!("(local.get {i}) i32.add "))
+        .collect::<String>();
+    let wat = format!(
+        r#"
+      (module
+          (func $a (export "a") (param i32) (result i32)
+              (local {locals})
+              (local $i i32)
+              (loop $l
+                  {update}
+                  (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                  (br_if $l (i32.lt_u (local.get $i) (i32.const 10))))
+              {update}
+              local.get 0
+              (call $b (i32.const 1))
+              (loop $l
+                  {update}
+                  (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+                  (br_if $l (local.get $i)))
+              (i32.const 0)
+              {sum}
+              i32.add
+          )
+          (func $b (param i32)
+              (local i64 f32)
+              unreachable
+          )
+      )
+    "#
+    );
+
+    let module = Module::new(store.engine(), &wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let a_func = instance.get_typed_func::<i32, i32>(&mut store, "a")?;
+
+    let e = a_func.call(&mut store, 7).unwrap_err();
+    let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(cd.frame_locals().len(), cd.frames().len());
+    assert_eq!(cd.frame_locals()[0].len(), 3);
+    assert_eq!(cd.frame_locals()[1].len(), N + 2);
+    assert_eq!(cd.frame_locals()[1][0], CoreDumpValue::I32(7));
+    // At the call each local `i` holds `11 * i`.
+    let spilled = (1..=N)
+        .filter(|&i| cd.frame_locals()[1][i] == CoreDumpValue::I32(11 * i as i32))
+        .count();
+    assert!(spilled > 0);
+
+    // The operand stack of the caller holds the parameter that's left for
+    // after the call, along with the call's argument.
+    assert_eq!(cd.frame_operand_stacks().len(), cd.frames().len());
+    assert_eq!(cd.frame_operand_stacks()[1].len(), 2);
+    assert_eq!(cd.frame_operand_stacks()[1][0], CoreDumpValue::I32(7));
+
+    let file = CoreDumpFile::parse(&cd.serialize(&mut store, "locals"))?;
+    let frames = file.threads()[0].frames();
+    assert_eq!(frames[0].locals().len(), 3);
+    assert_eq!(frames[1].locals().len(), N + 2);
+    assert_eq!(frames[0].locals(), &cd.frame_locals()[0][..]);
+    assert_eq!(frames[1].locals(), &cd.frame_locals()[1][..]);
+    assert_eq!(frames[1].stack(), &cd.frame_operand_stacks()[1][..]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_has_component_instances() -> Result<()> {
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut store = Store::<()>::new(&engine, ());
+
+    let wat = r#"
+      (component $app
+          (component $inner
+              (core module $m
+                  (func (export "run")
+                      unreachable
+                  )
+              )
+              (core instance $i (instantiate $m))
+              (func (export "run") (canon lift (core func $i "run")))
+          )
+          (instance $inner (instantiate $inner))
+          (core module $n)
+          (core instance (instantiate $n))
+          (alias export $inner "run" (func $run))
+          (export "api" (instance $inner))
+          (export "run" (func $run))
+      )
+    "#;
+
+    let component = component::Component::new(&engine, wat)?;
+    let instance = component::Linker::new(&engine).instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+    let e = run.call(&mut store, ()).unwrap_err();
+    let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(cd.component_instances().len(), 1);
+    assert_eq!(cd.instances().len(), 2);
+
+    let file = CoreDumpFile::parse(&cd.serialize(&mut store, "component"))?;
+    let instances = file.component_instances();
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0].name(), Some("app"));
+    assert_eq!(instances[0].parent(), None);
+    assert_eq!(instances[1].name(), Some("inner"));
+    assert_eq!(instances[1].parent(), Some(0));
+    assert_eq!(instances[0].core_instances().len(), 1);
+    assert_eq!(instances[1].core_instances().len(), 1);
+    let frame_instance = file.threads()[0].frames()[0].instance();
+    assert_eq!(instances[1].core_instances(), [frame_instance]);
+
+    let exports = instances[0].exports();
+    assert_eq!(exports.len(), 2);
+    assert_eq!(exports[0].name(), "api");
+    match exports[0].kind() {
+        CoreDumpComponentExportKind::Instance(exports) => {
+            assert_eq!(exports.len(), 1);
+            assert_eq!(exports[0].name(), "run");
+            assert!(matches!(
+                exports[0].kind(),
+                CoreDumpComponentExportKind::Func
+            ));
+        }
+        kind => bail!("unexpected export kind: {kind:?}"),
+    }
+    assert_eq!(exports[1].name(), "run");
+    assert!(matches!(
+        exports[1].kind(),
+        CoreDumpComponentExportKind::Func
+    ));
+    assert!(instances[1].exports().is_empty());
+    Ok(())
+}
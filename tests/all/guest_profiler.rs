@@ -0,0 +1,79 @@
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use wasmtime::*;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn records_host_calls() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module $guest
+                (import "host" "spin" (func $spin))
+                (import "host" "nap" (func $nap))
+                (func (export "run")
+                    call $spin
+                    call $nap)
+            )
+        "#,
+    )?;
+
+    let profiler = GuestProfiler::new(
+        "guest",
+        Duration::from_millis(10),
+        vec![("guest".to_string(), module.clone())],
+    );
+    let mut store = Store::new(&engine, Some(profiler));
+    store.call_hook_with_store(|mut store, kind| {
+        let mut profiler = store.data_mut().take().unwrap();
+        profiler.call_hook(&store, kind);
+        *store.data_mut() = Some(profiler);
+        Ok(())
+    });
+
+    let spin = Func::wrap(&mut store, || {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(30) {
+            std::hint::spin_loop();
+        }
+    });
+    let nap = Func::wrap(&mut store, || {
+        std::thread::sleep(Duration::from_millis(30));
+    });
+    let instance = Instance::new(&mut store, &module, &[spin.into(), nap.into()])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+
+    let mut output = Vec::new();
+    store.into_data().unwrap().finish(&mut output)?;
+    let profile: serde_json::Value = serde_json::from_slice(&output)?;
+
+    let categories = profile["meta"]["categories"].as_array().unwrap();
+    let names: Vec<_> = categories.iter().map(|c| c["name"].as_str()).collect();
+    assert!(names.contains(&Some("guest")), "{names:?}");
+    assert!(names.contains(&Some("Host")), "{names:?}");
+
+    // Host frames are named after the import that was called.
+    let text = String::from_utf8_lossy(&output);
+    assert!(text.contains("\"host::spin\""), "{text}");
+    assert!(text.contains("\"host::nap\""), "{text}");
+
+    // Each host-call is recorded as one sample of the wasm stack leading up
+    // to it and one sample with the host-call itself on top.
+    let samples = &profile["threads"][0]["samples"];
+    assert_eq!(samples["length"], 4, "{samples}");
+    assert_ne!(samples["stack"][1], samples["stack"][3], "{samples}");
+
+    // Samples are weighted by CPU time, so the busy host-call should weigh
+    // far more than the sleeping one even though both took as long.
+    #[cfg(unix)]
+    {
+        let cpu = &samples["threadCPUDelta"];
+        let spin = cpu[1].as_u64().unwrap();
+        let nap = cpu[3].as_u64().unwrap();
+        assert!(spin > 0, "{samples}");
+        assert!(spin > 4 * nap, "{samples}");
+    }
+    Ok(())
+}
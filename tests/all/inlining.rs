@@ -0,0 +1,129 @@
+//! Tests for Cranelift's inlining of calls between functions of the same
+//! module, enabled with `Config::cranelift_inlining`.
+
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use wasmtime::*;
+
+fn engine(opt_level: OptLevel) -> Result<Engine> {
+    let mut config = Config::new();
+    config.cranelift_inlining(true);
+    config.cranelift_opt_level(opt_level);
+    Engine::new(&config)
+}
+
+fn instantiate(engine: &Engine, wat: &str) -> Result<(Store<()>, Instance)> {
+    let mut store = Store::new(engine, ());
+    let module = Module::new(engine, wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Ok((store, instance))
+}
+
+#[test]
+fn inlined_helpers_compute_same_results() -> Result<()> {
+    for opt_level in [OptLevel::None, OptLevel::Speed] {
+        let (mut store, instance) = instantiate(
+            &engine(opt_level)?,
+            r#"
+                (module
+                    (memory 1)
+                    (func $load (param i32) (result i32)
+                        local.get 0
+                        i32.load offset=4)
+                    (func $max (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        local.get 0
+                        local.get 1
+                        i32.gt_s
+                        select)
+                    (func $swap (param i32 i32) (result i32 i32)
+                        local.get 1
+                        local.get 0)
+                    (func $classify (param i32) (result i32)
+                        (block $c (block $b (block $a
+                            local.get 0
+                            br_table $a $b $c)
+                            i32.const 10
+                            return)
+                            i32.const 20
+                            return)
+                        i32.const 30)
+                    (func (export "run") (param i32 i32) (result i32)
+                        (i32.store offset=4 (i32.const 0) (local.get 0))
+                        (call $swap (call $load (i32.const 0)) (local.get 1))
+                        call $max
+                        local.get 1
+                        call $classify
+                        i32.add)
+                )
+            "#,
+        )?;
+        let run = instance.get_typed_func::<(i32, i32), i32>(&mut store, "run")?;
+        assert_eq!(run.call(&mut store, (3, 0))?, 13);
+        assert_eq!(run.call(&mut store, (-5, 1))?, 21);
+        assert_eq!(run.call(&mut store, (7, 9))?, 39);
+    }
+    Ok(())
+}
+
+#[test]
+fn traps_in_inlined_callee() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        &engine(OptLevel::Speed)?,
+        r#"
+            (module
+                (func $div (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.div_u)
+                (func (export "run") (param i32) (result i32)
+                    i32.const 100
+                    local.get 0
+                    call $div)
+            )
+        "#,
+    )?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 4)?, 25);
+    let trap = run.call(&mut store, 0).unwrap_err().downcast::<Trap>()?;
+    assert_eq!(trap, Trap::IntegerDivisionByZero);
+    Ok(())
+}
+
+#[test]
+fn recursion_and_imports_are_not_inlined() -> Result<()> {
+    let engine = engine(OptLevel::Speed)?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "double" (func $double (param i32) (result i32)))
+                (func $fib (export "fib") (param i32) (result i32)
+                    (if (result i32) (i32.lt_u (local.get 0) (i32.const 2))
+                        (then (local.get 0))
+                        (else
+                            (i32.add
+                                (call $fib (i32.sub (local.get 0) (i32.const 1)))
+                                (call $fib (i32.sub (local.get 0) (i32.const 2)))))))
+                (func (export "run") (param i32) (result i32)
+                    (call $double (call $fib (local.get 0))))
+            )
+        "#,
+    )?;
+    let double = Func::wrap(&mut store, |x: i32| x * 2);
+    let instance = Instance::new(&mut store, &module, &[double.into()])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 10)?, 110);
+    Ok(())
+}
+
+#[test]
+fn winch_does_not_support_inlining() {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    config.cranelift_inlining(true);
+    assert!(Engine::new(&config).is_err());
+}
@@ -0,0 +1,606 @@
+use anyhow::Result;
+use wasmtime::*;
+
+fn engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter);
+    Engine::new(&config)
+}
+
+fn instantiate(wat: &str) -> Result<(Store<()>, Instance)> {
+    let engine = engine()?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Ok((store, instance))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn arithmetic() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (func (export "i32") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.mul
+                    local.get 0
+                    i32.sub
+                    i32.const 3
+                    i32.rotl)
+                (func (export "i64") (param i64 i64) (result i64)
+                    local.get 0
+                    local.get 1
+                    i64.div_s
+                    i64.clz)
+                (func (export "f64") (param f64 f32) (result f64)
+                    local.get 0
+                    local.get 1
+                    f64.promote_f32
+                    f64.min
+                    f64.sqrt)
+                (func (export "trunc_sat") (param f32) (result i32)
+                    local.get 0
+                    i32.trunc_sat_f32_s)
+            )
+        "#,
+    )?;
+
+    let f = instance.get_typed_func::<(i32, i32), i32>(&mut store, "i32")?;
+    assert_eq!(f.call(&mut store, (6, 7))?, 36 << 3);
+    let f = instance.get_typed_func::<(i64, i64), i64>(&mut store, "i64")?;
+    assert_eq!(f.call(&mut store, (-100, -10))?, 60);
+    let f = instance.get_typed_func::<(f64, f32), f64>(&mut store, "f64")?;
+    assert_eq!(f.call(&mut store, (16.0, 25.0))?, 4.0);
+    assert!(f.call(&mut store, (f64::NAN, 1.0))?.is_nan());
+    let f = instance.get_typed_func::<f32, i32>(&mut store, "trunc_sat")?;
+    assert_eq!(f.call(&mut store, 1e20)?, i32::MAX);
+    assert_eq!(f.call(&mut store, f32::NAN)?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn control_flow() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (func $fib (export "fib") (param i32) (result i32)
+                    local.get 0
+                    i32.const 2
+                    i32.lt_u
+                    if (result i32)
+                        local.get 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        call $fib
+                        local.get 0
+                        i32.const 2
+                        i32.sub
+                        call $fib
+                        i32.add
+                    end)
+                (func (export "sum") (param i32) (result i32)
+                    (local i32)
+                    block
+                        loop
+                            local.get 0
+                            i32.eqz
+                            br_if 1
+                            local.get 1
+                            local.get 0
+                            i32.add
+                            local.set 1
+                            local.get 0
+                            i32.const 1
+                            i32.sub
+                            local.set 0
+                            br 0
+                        end
+                    end
+                    local.get 1)
+                (func (export "select") (param i32) (result i32)
+                    block
+                        block
+                            block
+                                local.get 0
+                                br_table 0 1 2
+                            end
+                            i32.const 10
+                            return
+                        end
+                        i32.const 20
+                        return
+                    end
+                    i32.const 30)
+            )
+        "#,
+    )?;
+
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    assert_eq!(fib.call(&mut store, 20)?, 6765);
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, 100)?, 5050);
+    let select = instance.get_typed_func::<i32, i32>(&mut store, "select")?;
+    assert_eq!(select.call(&mut store, 0)?, 10);
+    assert_eq!(select.call(&mut store, 1)?, 20);
+    assert_eq!(select.call(&mut store, 2)?, 30);
+    assert_eq!(select.call(&mut store, 100)?, 30);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn memory_and_globals() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (global $count (mut i64) (i64.const 0))
+                (data (i32.const 8) "\01\02\03\04")
+                (func (export "load") (param i32) (result i32)
+                    local.get 0
+                    i32.load)
+                (func (export "store") (param i32 i64)
+                    local.get 0
+                    local.get 1
+                    i64.store
+                    global.get $count
+                    i64.const 1
+                    i64.add
+                    global.set $count)
+                (func (export "count") (result i64)
+                    global.get $count)
+                (func (export "grow") (param i32) (result i32)
+                    local.get 0
+                    memory.grow)
+            )
+        "#,
+    )?;
+
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    assert_eq!(load.call(&mut store, 8)?, 0x04030201);
+    let store_fn = instance.get_typed_func::<(i32, i64), ()>(&mut store, "store")?;
+    store_fn.call(&mut store, (100, 0x1122334455667788))?;
+    store_fn.call(&mut store, (104, -1))?;
+    assert_eq!(load.call(&mut store, 100)?, 0x55667788);
+    assert_eq!(load.call(&mut store, 104)?, -1);
+    let count = instance.get_typed_func::<(), i64>(&mut store, "count")?;
+    assert_eq!(count.call(&mut store, ())?, 2);
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.data(&store)[100], 0x88);
+    let grow = instance.get_typed_func::<i32, i32>(&mut store, "grow")?;
+    assert_eq!(grow.call(&mut store, 1)?, 1);
+    assert_eq!(memory.size(&store), 2);
+    assert_eq!(load.call(&mut store, 65536)?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn traps() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (memory 1)
+                (func (export "div") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.div_s)
+                (func (export "unreachable")
+                    unreachable)
+                (func (export "oob") (param i32) (result i32)
+                    local.get 0
+                    i32.load)
+                (func $recurse (export "recurse")
+                    call $recurse)
+            )
+        "#,
+    )?;
+
+    let div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div")?;
+    let err = div.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::IntegerDivisionByZero);
+    let err = div.call(&mut store, (i32::MIN, -1)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::IntegerOverflow);
+
+    let unreachable = instance.get_typed_func::<(), ()>(&mut store, "unreachable")?;
+    let err = unreachable.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+
+    let oob = instance.get_typed_func::<i32, i32>(&mut store, "oob")?;
+    let err = oob.call(&mut store, 65535).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::MemoryOutOfBounds);
+
+    let recurse = instance.get_typed_func::<(), ()>(&mut store, "recurse")?;
+    let err = recurse.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::StackOverflow);
+
+    // The store remains usable after traps.
+    assert_eq!(div.call(&mut store, (7, 2))?, 3);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tables_and_references() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (type $ret (func (result i32)))
+                (table $t 2 10 funcref)
+                (global $g (mut funcref) (ref.null func))
+                (elem $e func $one $two)
+                (func $one (result i32)
+                    i32.const 1)
+                (func $two (result i32)
+                    i32.const 2)
+                (func (export "init")
+                    i32.const 0
+                    i32.const 0
+                    i32.const 2
+                    table.init $t $e
+                    elem.drop $e)
+                (func (export "call") (param i32) (result i32)
+                    local.get 0
+                    call_indirect $t (type $ret))
+                (func (export "get") (param i32) (result funcref)
+                    local.get 0
+                    table.get $t)
+                (func (export "swap")
+                    (local funcref)
+                    i32.const 0
+                    table.get $t
+                    local.set 0
+                    i32.const 0
+                    i32.const 1
+                    table.get $t
+                    table.set $t
+                    i32.const 1
+                    local.get 0
+                    table.set $t)
+                (func (export "size") (result i32)
+                    table.size $t)
+                (func (export "grow") (param i32) (result i32)
+                    ref.func $one
+                    local.get 0
+                    table.grow $t)
+                (func (export "copy") (param i32 i32 i32)
+                    local.get 0
+                    local.get 1
+                    local.get 2
+                    table.copy $t $t)
+                (func (export "clear") (param i32 i32)
+                    local.get 0
+                    ref.null func
+                    local.get 1
+                    table.fill $t)
+                (func (export "is_null") (param i32) (result i32)
+                    local.get 0
+                    table.get $t
+                    ref.is_null)
+                (func (export "global") (result i32)
+                    ref.func $two
+                    global.set $g
+                    i32.const 0
+                    global.get $g
+                    table.set $t
+                    i32.const 0
+                    call_indirect $t (type $ret))
+            )
+        "#,
+    )?;
+
+    let init = instance.get_typed_func::<(), ()>(&mut store, "init")?;
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    let get = instance.get_typed_func::<i32, Option<Func>>(&mut store, "get")?;
+    let swap = instance.get_typed_func::<(), ()>(&mut store, "swap")?;
+    let size = instance.get_typed_func::<(), i32>(&mut store, "size")?;
+    let grow = instance.get_typed_func::<i32, i32>(&mut store, "grow")?;
+    let copy = instance.get_typed_func::<(i32, i32, i32), ()>(&mut store, "copy")?;
+    let clear = instance.get_typed_func::<(i32, i32), ()>(&mut store, "clear")?;
+    let is_null = instance.get_typed_func::<i32, i32>(&mut store, "is_null")?;
+    let global = instance.get_typed_func::<(), i32>(&mut store, "global")?;
+
+    let err = call.call(&mut store, 0).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::IndirectCallToNull);
+    init.call(&mut store, ())?;
+    assert_eq!(call.call(&mut store, 0)?, 1);
+    assert_eq!(call.call(&mut store, 1)?, 2);
+    let err = init.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    swap.call(&mut store, ())?;
+    assert_eq!(call.call(&mut store, 0)?, 2);
+    assert_eq!(call.call(&mut store, 1)?, 1);
+    let one = get.call(&mut store, 1)?.unwrap();
+    assert_eq!(one.typed::<(), i32>(&store)?.call(&mut store, ())?, 1);
+
+    assert_eq!(size.call(&mut store, ())?, 2);
+    assert_eq!(grow.call(&mut store, 3)?, 2);
+    assert_eq!(grow.call(&mut store, 100)?, -1);
+    assert_eq!(size.call(&mut store, ())?, 5);
+    assert_eq!(call.call(&mut store, 4)?, 1);
+
+    copy.call(&mut store, (2, 0, 2))?;
+    assert_eq!(call.call(&mut store, 2)?, 2);
+    assert_eq!(call.call(&mut store, 3)?, 1);
+    let err = copy.call(&mut store, (4, 0, 2)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    clear.call(&mut store, (0, 2))?;
+    assert_eq!(is_null.call(&mut store, 0)?, 1);
+    assert_eq!(is_null.call(&mut store, 2)?, 0);
+    assert!(get.call(&mut store, 1)?.is_none());
+    let err = is_null.call(&mut store, 5).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    assert_eq!(global.call(&mut store, ())?, 2);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn host_functions() -> Result<()> {
+    let engine = engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "add" (func $add (param i32 i32) (result i32)))
+                (import "" "fail" (func $fail))
+                (type $binop (func (param i32 i32) (result i32)))
+                (table 2 funcref)
+                (elem (i32.const 0) $add $sub)
+                (func $sub (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.sub)
+                (func (export "call_add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    call $add)
+                (func (export "call_indirect") (param i32 i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    local.get 2
+                    call_indirect (type $binop))
+                (func (export "fail")
+                    call $fail)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let add = Func::wrap(&mut store, |a: i32, b: i32| a + b);
+    let fail = Func::new(&mut store, FuncType::new([], []), |_, _, _| {
+        anyhow::bail!("host error")
+    });
+    let instance = Instance::new(&mut store, &module, &[add.into(), fail.into()])?;
+
+    let call_add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "call_add")?;
+    assert_eq!(call_add.call(&mut store, (1, 2))?, 3);
+
+    let call_indirect =
+        instance.get_typed_func::<(i32, i32, i32), i32>(&mut store, "call_indirect")?;
+    assert_eq!(call_indirect.call(&mut store, (5, 3, 0))?, 8);
+    assert_eq!(call_indirect.call(&mut store, (5, 3, 1))?, 2);
+    let err = call_indirect.call(&mut store, (5, 3, 2)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    let err = instance
+        .get_typed_func::<(), ()>(&mut store, "fail")?
+        .call(&mut store, ())
+        .unwrap_err();
+    assert!(err.to_string().contains("host error"), "{err:?}");
+
+    // Host functions defined with `Func::new` have no trampolines in the
+    // interpreter and can still be called directly.
+    let double = Func::new(
+        &mut store,
+        FuncType::new([ValType::I32], [ValType::I32]),
+        |_, params, results| {
+            results[0] = Val::I32(params[0].unwrap_i32() * 2);
+            Ok(())
+        },
+    );
+    assert_eq!(double.typed::<i32, i32>(&store)?.call(&mut store, 21)?, 42);
+    let mut results = [Val::I32(0)];
+    double.call(&mut store, &[Val::I32(4)], &mut results)?;
+    assert_eq!(results[0].unwrap_i32(), 8);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn untyped_calls_and_start() -> Result<()> {
+    let (mut store, instance) = instantiate(
+        r#"
+            (module
+                (global $g (mut i32) (i32.const 0))
+                (func $start
+                    i32.const 42
+                    global.set $g)
+                (start $start)
+                (func (export "get") (result i32)
+                    global.get $g)
+                (func (export "swap") (param f32 i64) (result i64 f32)
+                    local.get 1
+                    local.get 0)
+            )
+        "#,
+    )?;
+
+    let get = instance.get_func(&mut store, "get").unwrap();
+    let mut results = [Val::I32(0)];
+    get.call(&mut store, &[], &mut results)?;
+    assert_eq!(results[0].unwrap_i32(), 42);
+
+    let swap = instance.get_func(&mut store, "swap").unwrap();
+    let mut results = [Val::I64(0), Val::F32(0)];
+    swap.call(&mut store, &[1.5f32.into(), 7i64.into()], &mut results)?;
+    assert_eq!(results[0].unwrap_i64(), 7);
+    assert_eq!(results[1].unwrap_f32(), 1.5);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn serialize() -> Result<()> {
+    let engine = engine()?;
+    let bytes = engine.precompile_module(
+        r#"
+            (module
+                (func (export "f") (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add)
+            )
+        "#
+        .as_bytes(),
+    )?;
+    let module = unsafe { Module::deserialize(&engine, &bytes)? };
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+    assert_eq!(f.call(&mut store, 1)?, 2);
+
+    // Interpreted modules can't be loaded into engines generating native code
+    // and vice versa.
+    let native = Engine::default();
+    assert!(unsafe { Module::deserialize(&native, &bytes) }.is_err());
+    let bytes = native.precompile_module(b"(module)")?;
+    assert!(unsafe { Module::deserialize(&engine, &bytes) }.is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn unsupported() -> Result<()> {
+    let engine = engine()?;
+    assert!(Module::new(&engine, "(module (func (param externref)))").is_err());
+    assert!(Module::new(
+        &engine,
+        "(module (table 1 externref) (func (result i32) table.size 0))"
+    )
+    .is_err());
+    assert!(Module::new(&engine, "(module (memory i64 1))").is_err());
+    assert!(Module::new(
+        &engine,
+        "(module (func (result v128) v128.const i64x2 0 0))"
+    )
+    .is_err());
+    assert!(Module::new(&engine, "(module (memory 1 1 shared) (func))").is_err());
+    assert!(Module::new(
+        &engine,
+        "(module (memory 1) (memory 1) (func (result i32) (i32.load 1 (i32.const 0))))"
+    )
+    .is_err());
+
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter).debug_info(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("debug information"), "{err:?}");
+
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter).wasm_memory64(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("memory64"), "{err:?}");
+
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter).wasm_tail_call(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("tail calls"), "{err:?}");
+
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter);
+    unsafe {
+        config.cranelift_flag_set("opt_levle", "none");
+    }
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("opt_levle"), "{err:?}");
+
+    let mut config = Config::new();
+    config
+        .strategy(Strategy::Interpreter)
+        .cranelift_nan_canonicalization(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("NaN canonicalization"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn fuel() -> Result<()> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Interpreter).consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func (export "loop")
+                    (loop $l br $l))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    let looping = instance.get_typed_func::<(), ()>(&mut store, "loop")?;
+
+    // Without any fuel nothing can run.
+    let err = add.call(&mut store, (1, 2)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+
+    // Each of the operators in `add` consumes one unit of fuel, like it does
+    // in native code.
+    store.set_fuel(10)?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+    assert_eq!(store.get_fuel()?, 7);
+
+    // Infinite loops eventually run out of fuel.
+    store.set_fuel(10_000)?;
+    let err = looping.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn epoch_interruption() -> Result<()> {
+    let mut config = Config::new();
+    config
+        .strategy(Strategy::Interpreter)
+        .epoch_interruption(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "tick" (func $tick))
+                (func (export "loop")
+                    (loop $l
+                        call $tick
+                        br $l))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let tick = Func::wrap(&mut store, |caller: Caller<'_, ()>| {
+        caller.engine().increment_epoch();
+    });
+    let instance = Instance::new(&mut store, &module, &[tick.into()])?;
+    let looping = instance.get_typed_func::<(), ()>(&mut store, "loop")?;
+    let err = looping.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::Interrupt);
+    Ok(())
+}
@@ -14,11 +14,15 @@ mod func;
 mod funcref;
 mod gc;
 mod globals;
+#[cfg(feature = "profiling")]
+mod guest_profiler;
 mod host_funcs;
 mod iloop;
 mod import_calling_export;
 mod import_indexes;
+mod inlining;
 mod instance;
+mod interpreter;
 mod invoke_func_via_table;
 mod limits;
 mod linker;
@@ -27,6 +31,7 @@ mod memory_creator;
 mod module;
 mod module_serialize;
 mod name;
+mod pgo;
 mod pooling_allocator;
 mod relocs;
 mod stack_creator;
@@ -660,3 +660,344 @@ fn init_with_negative_segment() -> Result<()> {
     Instance::new(&mut store, &module, &[])?;
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn dynamic_memory_redundant_bounds_checks() -> Result<()> {
+    for spectre in [false, true] {
+        let mut config = Config::new();
+        config.static_memory_maximum_size(0);
+        config.dynamic_memory_guard_size(0);
+        unsafe {
+            config.cranelift_flag_set(
+                "enable_heap_access_spectre_mitigation",
+                if spectre { "true" } else { "false" },
+            );
+        }
+        let engine = Engine::new(&config)?;
+        let mut store = Store::new(&engine, ());
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (memory (export "memory") 1)
+
+                    ;; The second load is covered by the first one's check,
+                    ;; but the third one needs a check of its own.
+                    (func (export "same-index") (param i32) (result i32)
+                        (i32.load offset=4 (local.get 0))
+                        (i32.load (local.get 0))
+                        i32.add
+                        (i32.load offset=8 (local.get 0))
+                        i32.add)
+
+                    ;; Checks in one arm of an `if` don't cover the other arm
+                    ;; nor the code after the `if`.
+                    (func (export "if-arms") (param i32 i32) (result i32)
+                        (if (local.get 1)
+                            (then (drop (i32.load8_u (local.get 0))))
+                            (else (drop (i32.load16_u (local.get 0)))))
+                        (i32.load (local.get 0)))
+
+                    ;; A check before a loop covers accesses in the loop, even
+                    ;; after the memory grows.
+                    (func (export "loop") (param i32 i32) (result i32)
+                        (local i32)
+                        (local.set 2 (i32.load (local.get 0)))
+                        (loop
+                            (drop (memory.grow (i32.const 1)))
+                            (local.set 2 (i32.add (local.get 2) (i32.load (local.get 0))))
+                            (br_if 0 (local.tee 1 (i32.sub (local.get 1) (i32.const 1)))))
+                        (local.get 2))
+
+                    ;; Masked indices always fit within the minimum size.
+                    (func (export "masked") (param i32) (result i32)
+                        (i32.load (i32.and (local.get 0) (i32.const 0xfff0))))
+                )
+            "#,
+        )?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        memory.data_mut(&mut store)[0xfff0..0xfff4].copy_from_slice(&3u32.to_le_bytes());
+
+        let same_index = instance.get_typed_func::<u32, u32>(&mut store, "same-index")?;
+        assert_eq!(same_index.call(&mut store, 0xfff0 - 8)?, 3);
+        let trap = same_index
+            .call(&mut store, 0xfff8)
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+
+        let if_arms = instance.get_typed_func::<(u32, u32), u32>(&mut store, "if-arms")?;
+        assert_eq!(if_arms.call(&mut store, (0xfff0, 1))?, 3);
+        for (index, flag) in [(0xffff, 1), (0xfffe, 0), (0xfffd, 1)] {
+            let trap = if_arms
+                .call(&mut store, (index, flag))
+                .unwrap_err()
+                .downcast::<Trap>()?;
+            assert_eq!(trap, Trap::MemoryOutOfBounds);
+        }
+
+        let masked = instance.get_typed_func::<u32, u32>(&mut store, "masked")?;
+        assert_eq!(masked.call(&mut store, 0xffff_fff0)?, 3);
+
+        let lp = instance.get_typed_func::<(u32, u32), u32>(&mut store, "loop")?;
+        assert_eq!(lp.call(&mut store, (0xfff0, 3))?, 12);
+        assert_eq!(memory.size(&store), 4);
+        let trap = lp
+            .call(&mut store, (4 << 16, 1))
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn dynamic_memory_loop_induction_bounds_checks() -> Result<()> {
+    let wat = r#"
+        (module
+            (memory (export "memory") 1)
+
+            ;; The index stays below the bound that the back edge is guarded
+            ;; by, which is within the minimum size, so it needs no check.
+            (func (export "in-bounds") (result i32)
+                (local i32 i32)
+                (local.set 0 (i32.const 0xfff0))
+                (loop
+                    (local.set 1 (i32.add (local.get 1) (i32.load8_u (local.get 0))))
+                    (br_if 0 (i32.lt_u (local.tee 0 (i32.add (local.get 0) (i32.const 1)))
+                                       (i32.const 0x10000))))
+                (local.get 1))
+
+            ;; The bound is one past the end of the minimum size.
+            (func (export "past-end") (result i32)
+                (local i32 i32)
+                (local.set 0 (i32.const 0xfff0))
+                (loop
+                    (local.set 1 (i32.add (local.get 1) (i32.load8_u (local.get 0))))
+                    (br_if 0 (i32.lt_u (local.tee 0 (i32.add (local.get 0) (i32.const 1)))
+                                       (i32.const 0x10001))))
+                (local.get 1))
+
+            ;; The index's value on entry to the loop is unknown.
+            (func (export "unknown-start") (param i32) (result i32)
+                (local i32)
+                (loop
+                    (local.set 1 (i32.add (local.get 1) (i32.load8_u (local.get 0))))
+                    (br_if 0 (i32.lt_u (local.tee 0 (i32.add (local.get 0) (i32.const 1)))
+                                       (i32.const 0x10000))))
+                (local.get 1))
+        )
+    "#;
+
+    for spectre in [false, true] {
+        let dir = tempfile::tempdir()?;
+        let mut config = Config::new();
+        config.static_memory_maximum_size(0);
+        config.dynamic_memory_guard_size(0);
+        config.emit_clif(dir.path());
+        unsafe {
+            config.cranelift_flag_set(
+                "enable_heap_access_spectre_mitigation",
+                if spectre { "true" } else { "false" },
+            );
+        }
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+
+        // Without Spectre mitigations only the access within the loop's
+        // bound is left unchecked. With them, every access keeps its guard
+        // since a mispredicted back edge runs the loop body past its bound.
+        let clif =
+            |func: u32| std::fs::read_to_string(dir.path().join(format!("wasm_func_{func}.clif")));
+        if spectre {
+            for func in 0..3 {
+                assert!(clif(func)?.contains("select_spectre_guard"));
+            }
+        } else {
+            assert!(!clif(0)?.contains("heap_oob"));
+            assert!(clif(1)?.contains("heap_oob"));
+            assert!(clif(2)?.contains("heap_oob"));
+        }
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        memory.data_mut(&mut store)[0xfff0..0xfff4].copy_from_slice(&3u32.to_le_bytes());
+        memory.data_mut(&mut store)[0xffff] = 4;
+
+        let in_bounds = instance.get_typed_func::<(), u32>(&mut store, "in-bounds")?;
+        assert_eq!(in_bounds.call(&mut store, ())?, 7);
+
+        let past_end = instance.get_typed_func::<(), u32>(&mut store, "past-end")?;
+        let trap = past_end
+            .call(&mut store, ())
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+
+        let unknown_start = instance.get_typed_func::<u32, u32>(&mut store, "unknown-start")?;
+        assert_eq!(unknown_start.call(&mut store, 0xfff0)?, 7);
+        let trap = unknown_start
+            .call(&mut store, 0x10000)
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn discard() -> Result<()> {
+    let mut pool = crate::small_pool_config();
+    pool.memory_pages(4);
+    let mut pooling = Config::new();
+    pooling.allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+    for config in [Config::new(), pooling] {
+        let engine = Engine::new(&config)?;
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (memory (export "memory") 4)
+                    (data (i32.const 0x10000) "init")
+                    (func (export "load") (param i32) (result i32)
+                        (i32.load8_u (local.get 0)))
+                )
+            "#,
+        )?;
+
+        for _ in 0..2 {
+            let mut store = Store::new(&engine, ());
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let memory = instance.get_memory(&mut store, "memory").unwrap();
+            let load = instance.get_typed_func::<u32, u32>(&mut store, "load")?;
+
+            // Each instantiation starts with the data segment in place, even
+            // if it was discarded by a previous instance in the same slot.
+            assert_eq!(&memory.data(&store)[0x10000..0x10004], b"init");
+
+            memory.data_mut(&mut store)[0x20000..0x30000].fill(1);
+            memory.discard(&mut store, 0..0x30000)?;
+            assert!(memory.data(&store).iter().all(|b| *b == 0));
+            assert_eq!(load.call(&mut store, 0x10000)?, 0);
+
+            // Bytes outside of the range are untouched, and partial pages at
+            // either end of the range are zeroed too.
+            memory.data_mut(&mut store).fill(1);
+            memory.discard(&mut store, 0x10001..0x2ffff)?;
+            let data = memory.data(&store);
+            assert_eq!(data[0x10000], 1);
+            assert!(data[0x10001..0x2ffff].iter().all(|b| *b == 0));
+            assert!(data[0x2ffff..].iter().all(|b| *b == 1));
+
+            memory.discard(&mut store, 0x100..0x100)?;
+            assert!(memory.discard(&mut store, 0x30000..0x40001).is_err());
+            assert!(memory.discard(&mut store, 0x20000..0x10000).is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn discard_from_guest() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasmtime" "memory_discard" (func $discard (param i32 i32)))
+                (memory (export "memory") 2)
+                (data (i32.const 0x10000) "init")
+                (func (export "discard") (param i32 i32)
+                    (call $discard (local.get 0) (local.get 1)))
+                (func (export "load") (param i32) (result i32)
+                    (i32.load8_u (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    linker.define_memory_discard("wasmtime", "memory_discard")?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    let discard = instance.get_typed_func::<(u32, u32), ()>(&mut store, "discard")?;
+    let load = instance.get_typed_func::<u32, u32>(&mut store, "load")?;
+
+    assert_eq!(load.call(&mut store, 0x10000)?, u32::from(b'i'));
+    discard.call(&mut store, (0x10000, 0x10000))?;
+    assert_eq!(load.call(&mut store, 0x10000)?, 0);
+
+    let err = discard.call(&mut store, (0x10000, 0x10001)).unwrap_err();
+    assert!(err.downcast_ref::<MemoryAccessError>().is_some(), "{err:?}");
+    assert!(discard.call(&mut store, (u32::MAX, u32::MAX)).is_err());
+
+    // The calling instance has to export its memory.
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasmtime" "memory_discard" (func $discard (param i32 i32)))
+                (memory 1)
+                (func (export "discard") (call $discard (i32.const 0) (i32.const 0)))
+            )
+        "#,
+    )?;
+    let instance = linker.instantiate(&mut store, &module)?;
+    let discard = instance.get_typed_func::<(), ()>(&mut store, "discard")?;
+    let err = discard.call(&mut store, ()).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("requires the caller to export `memory`"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn huge_pages() -> Result<()> {
+    let mut pool = crate::small_pool_config();
+    pool.memory_pages(64).linear_memory_keep_resident(1 << 16);
+    let mut pooling = Config::new();
+    pooling.allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+    for mut config in [Config::new(), pooling] {
+        // Huge pages are only a hint, so this should behave the same whether
+        // or not the host supports them.
+        config.memory_huge_pages(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (memory (export "memory") 32 64)
+                    (data (i32.const 0x100000) "init")
+                )
+            "#,
+        )?;
+
+        for _ in 0..2 {
+            let mut store = Store::new(&engine, ());
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let memory = instance.get_memory(&mut store, "memory").unwrap();
+            assert_eq!(&memory.data(&store)[0x100000..0x100004], b"init");
+            assert!(memory.data(&store)[..0x100000].iter().all(|b| *b == 0));
+
+            memory.grow(&mut store, 32)?;
+            memory.data_mut(&mut store).fill(1);
+            memory.discard(&mut store, 0x200000..0x210000)?;
+            assert_eq!(memory.data(&store)[0x200000], 0);
+            assert_eq!(memory.data(&store)[0x3fffff], 1);
+        }
+
+        let mut store = Store::new(&engine, ());
+        let memory = Memory::new(&mut store, MemoryType::new(32, None))?;
+        memory.data_mut(&mut store).fill(1);
+        memory.grow(&mut store, 32)?;
+        assert_eq!(memory.data(&store)[0x1fffff], 1);
+        assert_eq!(memory.data(&store)[0x200000], 0);
+    }
+    Ok(())
+}
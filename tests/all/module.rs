@@ -239,3 +239,40 @@ fn large_add_chain_no_stack_overflow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn emit_clif() -> Result<()> {
+    let wat = r#"
+        (module
+            (func (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+            )
+        )
+    "#;
+
+    // By default only the CLIF translated from wasm is written.
+    let dir = tempfile::tempdir()?;
+    let mut config = Config::new();
+    config.emit_clif(dir.path());
+    Module::new(&Engine::new(&config)?, wat)?;
+    assert!(dir.path().join("wasm_func_0.clif").exists());
+    assert!(!dir.path().join("wasm_func_0.opt.clif").exists());
+    assert!(!dir.path().join("wasm_func_0.vcode").exists());
+
+    // The compiler explorer also asks for the compiled stages, and the VCode
+    // is annotated with the wasm offsets of its instructions.
+    let dir = tempfile::tempdir()?;
+    config.emit_clif(dir.path()).emit_compiled_clif(true);
+    Module::new(&Engine::new(&config)?, wat)?;
+    assert!(dir.path().join("wasm_func_0.opt.clif").exists());
+    let vcode = std::fs::read_to_string(dir.path().join("wasm_func_0.vcode"))?;
+    assert!(
+        vcode.lines().any(|line| line.starts_with("@00")),
+        "no wasm offsets in:\n{vcode}"
+    );
+
+    Ok(())
+}
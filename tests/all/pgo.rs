@@ -0,0 +1,204 @@
+//! Tests for collecting execution profiles with
+//! `Config::pgo_instrumentation` and compiling with them through
+//! `Config::pgo_profile`.
+
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (func $double (param i32) (result i32)
+            local.get 0
+            i32.const 1
+            i32.shl)
+
+        ;; Sums `double(i)` for all `i` below the argument, except that
+        ;; multiples of 1000 add one more. Traps if the argument is negative.
+        (func (export "run") (param i32) (result i32)
+            (local $i i32) (local $sum i32)
+            (if (i32.lt_s (local.get 0) (i32.const 0))
+                (then unreachable))
+            (block $done
+                (loop $loop
+                    (br_if $done (i32.ge_s (local.get $i) (local.get 0)))
+                    (local.set $sum
+                        (i32.add (local.get $sum) (call $double (local.get $i))))
+                    (if (i32.eqz (i32.rem_u (local.get $i) (i32.const 1000)))
+                        (then
+                            (local.set $sum (i32.add (local.get $sum) (i32.const 1)))))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    br $loop))
+            local.get $sum)
+
+        (func (export "never") (result i32)
+            (call $double (i32.const 21)))
+    )
+"#;
+
+fn expected(n: i32) -> i32 {
+    (0..n).map(|i| 2 * i + i32::from(i % 1000 == 0)).sum()
+}
+
+fn run(module: &Module, n: i32) -> Result<i32> {
+    let mut store = Store::new(module.engine(), ());
+    let instance = Instance::new(&mut store, module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    run.call(&mut store, n)
+}
+
+fn collect_profile() -> Result<PgoProfile> {
+    let mut config = Config::new();
+    config.pgo_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, WAT)?;
+
+    let empty = module.pgo_profile().unwrap();
+    for n in [10, 100, 5000] {
+        assert_eq!(run(&module, n)?, expected(n));
+    }
+    assert!(run(&module, -1).is_err());
+
+    let profile = module.pgo_profile().unwrap();
+    assert_ne!(profile.serialize()?, empty.serialize()?);
+    Ok(profile)
+}
+
+#[test]
+fn uninstrumented_modules_have_no_profile() -> Result<()> {
+    let module = Module::new(&Engine::default(), WAT)?;
+    assert!(module.pgo_profile().is_none());
+    Ok(())
+}
+
+#[test]
+fn compile_with_profile() -> Result<()> {
+    let profile = PgoProfile::deserialize(&collect_profile()?.serialize()?)?;
+
+    for inlining in [false, true] {
+        for opt_level in [OptLevel::None, OptLevel::Speed] {
+            let mut config = Config::new();
+            config.pgo_profile(profile.clone());
+            config.cranelift_inlining(inlining);
+            config.cranelift_opt_level(opt_level);
+            let engine = Engine::new(&config)?;
+            let module = Module::new(&engine, WAT)?;
+            assert!(module.pgo_profile().is_none());
+
+            for n in [0, 1, 999, 1001, 5000] {
+                assert_eq!(run(&module, n)?, expected(n));
+            }
+            assert!(run(&module, -1).is_err());
+
+            // Functions which never ran while profiling still work.
+            let mut store = Store::new(&engine, ());
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let never = instance.get_typed_func::<(), i32>(&mut store, "never")?;
+            assert_eq!(never.call(&mut store, ())?, 42);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn profiles_of_other_modules_are_ignored() -> Result<()> {
+    let mut config = Config::new();
+    config.pgo_profile(collect_profile()?);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"(module (func (export "run") (param i32) (result i32) local.get 0))"#,
+    )?;
+    assert_eq!(run(&module, 7)?, 7);
+    Ok(())
+}
+
+#[test]
+fn merge_profiles() -> Result<()> {
+    let a = collect_profile()?;
+    let mut merged = a.clone();
+    merged.merge(&a);
+    assert_ne!(merged.serialize()?, a.serialize()?);
+
+    let mut empty = PgoProfile::new();
+    assert!(empty.is_empty());
+    empty.merge(&a);
+    assert_eq!(empty.serialize()?, a.serialize()?);
+
+    assert!(PgoProfile::deserialize(b"not a profile").is_err());
+    Ok(())
+}
+
+#[test]
+fn precompiled_instrumented_module() -> Result<()> {
+    let mut config = Config::new();
+    config.pgo_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let bytes = engine.precompile_module(WAT.as_bytes())?;
+
+    // Instrumented artifacts collect profiles in any engine.
+    let engine = Engine::default();
+    let module = unsafe { Module::deserialize(&engine, &bytes)? };
+    assert_eq!(run(&module, 10)?, expected(10));
+    assert!(module.pgo_profile().is_some());
+    Ok(())
+}
+
+#[test]
+fn winch_does_not_support_instrumentation() {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    config.pgo_instrumentation(true);
+    assert!(Engine::new(&config).is_err());
+}
+
+#[test]
+fn component_profile() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let wat = format!(
+        r#"
+            (component
+                {}
+                (core instance $i (instantiate $m))
+                (func (export "run") (param "n" s32) (result s32)
+                    (canon lift (core func $i "run")))
+            )
+        "#,
+        WAT.replacen("(module", "(core module $m", 1)
+    );
+    let run = |engine: &Engine, component: &Component, n: i32| -> Result<i32> {
+        let mut store = Store::new(engine, ());
+        let instance = Linker::new(engine).instantiate(&mut store, component)?;
+        let run = instance.get_typed_func::<(i32,), (i32,)>(&mut store, "run")?;
+        let (result,) = run.call(&mut store, (n,))?;
+        run.post_return(&mut store)?;
+        Ok(result)
+    };
+
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let component = Component::new(&Engine::new(&config)?, &wat)?;
+    assert!(component.pgo_profile().is_none());
+
+    config.pgo_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let component = Component::new(&engine, &wat)?;
+    let empty = component.pgo_profile().unwrap();
+    for n in [10, 100, 5000] {
+        assert_eq!(run(&engine, &component, n)?, expected(n));
+    }
+    let profile = component.pgo_profile().unwrap();
+    assert_ne!(profile.serialize()?, empty.serialize()?);
+
+    let mut config = Config::new();
+    config.wasm_component_model(true).pgo_profile(profile);
+    let engine = Engine::new(&config)?;
+    let component = Component::new(&engine, &wat)?;
+    assert!(component.pgo_profile().is_none());
+    for n in [0, 999, 5000] {
+        assert_eq!(run(&engine, &component, n)?, expected(n));
+    }
+    Ok(())
+}
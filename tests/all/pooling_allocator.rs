@@ -1185,3 +1185,101 @@ fn total_memories_limit() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn store_stats_reflect_pool_slots() -> Result<()> {
+    let mut pool = crate::small_pool_config();
+    pool.memory_pages(3);
+    let mut config = Config::new();
+    config.allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+    config.dynamic_memory_guard_size(0);
+    config.static_memory_guard_size(0);
+    config.static_memory_maximum_size(3 * 65536);
+
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (memory 1) (table 2 funcref))"#)?;
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+
+    let stats = store.stats();
+    assert_eq!(stats.instances, 1);
+    assert_eq!(stats.memories.len(), 1);
+    assert_eq!(stats.memories[0].size_bytes, 65536);
+    assert_eq!(stats.memories[0].maximum_bytes, Some(3 * 65536));
+    assert!(stats.memories[0].reserved_bytes >= 3 * 65536);
+    assert_eq!(stats.tables[0].size, 2);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[cfg(target_pointer_width = "64")]
+fn memory64_larger_than_4gib() -> Result<()> {
+    if skip_pooling_allocator_tests() {
+        return Ok(());
+    }
+
+    // Slots are sized for 64-bit memories slightly larger than 4GiB, which
+    // are dynamically bounds-checked against their current size.
+    let mut pool = crate::small_pool_config();
+    pool.memory_pages(0x10002);
+    let mut config = Config::new();
+    config.wasm_memory64(true);
+    config.static_memory_maximum_size(0);
+    config.static_memory_guard_size(1 << 16);
+    config.dynamic_memory_guard_size(1 << 16);
+    config.allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+    let engine = Engine::new(&config)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "m") i64 0x10001)
+                (func (export "store") (param i64 i32)
+                    (i32.store (local.get 0) (local.get 1)))
+                (func (export "load") (param i64) (result i32)
+                    (i32.load (local.get 0)))
+            )
+        "#,
+    )?;
+
+    for _ in 0..2 {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let memory = instance.get_memory(&mut store, "m").unwrap();
+        let store_fn = instance.get_typed_func::<(u64, u32), ()>(&mut store, "store")?;
+        let load = instance.get_typed_func::<u64, u32>(&mut store, "load")?;
+
+        // Memory beyond 4GiB is usable and reset between instantiations.
+        assert_eq!(load.call(&mut store, 0x1_0000_0000)?, 0);
+        store_fn.call(&mut store, (0x1_0000_0000, 42))?;
+        assert_eq!(load.call(&mut store, 0x1_0000_0000)?, 42);
+
+        let trap = load
+            .call(&mut store, 0x1_0001_0000)
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+
+        // The memory can grow up to the limit of the pool and no further.
+        assert_eq!(memory.grow(&mut store, 1)?, 0x10001);
+        store_fn.call(&mut store, (0x1_0001_0000, 1))?;
+        assert!(memory.grow(&mut store, 1).is_err());
+        let trap = load
+            .call(&mut store, 0x1_0002_0000)
+            .unwrap_err()
+            .downcast::<Trap>()?;
+        assert_eq!(trap, Trap::MemoryOutOfBounds);
+    }
+
+    // A module with a memory larger than the pool's slots is rejected.
+    let err = Module::new(&engine, r#"(module (memory i64 0x10003))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "memory index 0 has a minimum page size of 65539 which exceeds the limit of 65538",
+    );
+    Ok(())
+}
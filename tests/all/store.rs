@@ -20,3 +20,60 @@ fn into_inner() {
     Store::new(&engine, A).into_data();
     assert_eq!(HITS.load(SeqCst), 2);
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn stats() -> anyhow::Result<()> {
+    use wasmtime::{Instance, Memory, MemoryType, Module};
+
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let stats = store.stats();
+    assert_eq!(stats.instances, 0);
+    assert!(stats.memories.is_empty());
+    assert!(stats.tables.is_empty());
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "m") 1 4)
+                (table 3 funcref)
+                (func (export "touch")
+                    (i32.store (i32.const 0) (i32.const 1))))
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Memory::new(&mut store, MemoryType::new(2, None))?;
+
+    let stats = store.stats();
+    assert_eq!(stats.instances, 1);
+    assert_eq!(stats.memories.len(), 2);
+    assert_eq!(stats.total_memory_bytes(), 3 * 65536);
+    let memory = stats
+        .memories
+        .iter()
+        .find(|m| m.maximum_bytes == Some(4 * 65536))
+        .unwrap();
+    assert_eq!(memory.size_bytes, 65536);
+    assert!(memory.reserved_bytes >= memory.size_bytes);
+    assert_eq!(stats.tables.len(), 1);
+    assert_eq!(stats.tables[0].size, 3);
+    assert_eq!(stats.tables[0].maximum, None);
+
+    instance
+        .get_typed_func::<(), ()>(&mut store, "touch")?
+        .call(&mut store, ())?;
+    instance
+        .get_memory(&mut store, "m")
+        .unwrap()
+        .grow(&mut store, 1)?;
+
+    let stats = store.stats();
+    assert_eq!(stats.total_memory_bytes(), 4 * 65536);
+    if cfg!(target_os = "linux") {
+        let resident = stats.total_resident_bytes().unwrap();
+        assert!(resident > 0 && resident <= stats.total_memory_bytes());
+    }
+    Ok(())
+}